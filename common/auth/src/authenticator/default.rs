@@ -23,6 +23,7 @@ pub const DEFAULT_SCOPE_MAPPINGS: &[(&str, &[&str])] = &[
         "read:document",
         &[
             "ai",
+            "export.dataset",
             "read.advisory",
             "read.importer",
             "read.metadata",
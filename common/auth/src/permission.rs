@@ -111,6 +111,8 @@ permission! {
 
         #[strum(serialize = "upload.dataset")]
         UploadDataset,
+        #[strum(serialize = "export.dataset")]
+        ExportDataset,
 
         #[strum(serialize = "read.systemInformation")]
         ReadSystemInformation,
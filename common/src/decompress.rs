@@ -1,9 +1,14 @@
 use actix_web::http::header;
 use anyhow::anyhow;
 use bytes::Bytes;
-use std::{io::Read, path::Path, pin::Pin};
+use std::{
+    io::{self, Cursor, Read},
+    path::Path,
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
 use tokio::{
-    io::{AsyncRead, BufReader},
+    io::{AsyncRead, AsyncReadExt, BufReader, ReadBuf},
     runtime::Handle,
     task::JoinError,
 };
@@ -26,7 +31,8 @@ pub enum Error {
 ///
 /// If a content type is present, then it is expected to indicate its compression type by appending
 /// it using and extension to the subtype, like `+bz2`. If that's not present, or no content-type
-/// is present altogether, then it will try detecting it based on some magic bytes.
+/// (or `application/octet-stream`) is present altogether, then it will try detecting it based on
+/// some magic bytes.
 ///
 /// If no magic bytes could be detected, it will assume the content is not compressed.
 ///
@@ -45,6 +51,43 @@ fn decompress(bytes: Bytes, compression: Compression, limit: usize) -> Result<By
         })
 }
 
+/// The magic bytes of a zstd frame.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// A content type which doesn't tell anything about the payload, so it gets detected instead.
+const GENERIC_CONTENT_TYPE: &str = "application/octet-stream";
+
+/// Decompress a zstd encoded payload.
+///
+/// This is handled separately, as the compression detector doesn't support zstd.
+#[instrument(skip(bytes), fields(bytes_len=bytes.len()), err(level=tracing::Level::INFO))]
+fn decompress_zstd(bytes: Bytes, limit: usize) -> Result<Bytes, Error> {
+    let decoder = zstd::stream::read::Decoder::new(&*bytes)?;
+
+    // a limit of zero means: unlimited
+    let limit = match limit {
+        0 => u64::MAX - 1,
+        limit => limit as u64,
+    };
+
+    let mut data = vec![];
+    decoder.take(limit + 1).read_to_end(&mut data)?;
+    if data.len() as u64 > limit {
+        return Err(Error::PayloadTooLarge);
+    }
+
+    Ok(data.into())
+}
+
+/// The compression of a payload.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Encoding {
+    /// A compression supported by the compression detector, including none at all
+    Detected(Compression),
+    /// zstd, which the compression detector doesn't support
+    Zstd,
+}
+
 /// An async version of [`decompress`].
 #[instrument(skip(bytes), fields(bytes_len=bytes.len()), err(level=tracing::Level::INFO))]
 pub async fn decompress_async(
@@ -52,38 +95,136 @@ pub async fn decompress_async(
     content_type: Option<header::ContentType>,
     limit: usize,
 ) -> Result<Result<Bytes, Error>, JoinError> {
-    let compression = match detect(content_type, &bytes) {
+    let encoding = match detect(content_type, &bytes) {
         Err(err) => return Ok(Err(err)),
-        Ok(compression) => compression,
+        Ok(encoding) => encoding,
     };
 
-    match compression {
-        Compression::None => Ok(Ok(bytes)),
-        compression => {
-            // only spawn thread when necessary
+    match encoding {
+        Encoding::Detected(Compression::None) => Ok(Ok(bytes)),
+        // only spawn thread when necessary
+        Encoding::Detected(compression) => {
             Handle::current()
                 .spawn_blocking(move || decompress(bytes, compression, limit))
                 .await
         }
+        Encoding::Zstd => {
+            Handle::current()
+                .spawn_blocking(move || decompress_zstd(bytes, limit))
+                .await
+        }
     }
 }
 
-fn detect(content_type: Option<header::ContentType>, bytes: &[u8]) -> Result<Compression, Error> {
-    let content_type = content_type.as_ref().map(|ct| ct.as_ref());
+/// The number of bytes used for detecting the compression of a stream.
+const DETECTION_PREFIX: u64 = 512;
+
+/// A streaming version of [`decompress_async`].
+///
+/// The compression is detected from the first bytes of the stream, the same way
+/// [`decompress_async`] does. The decompressed content is limited to `limit` bytes, a limit of
+/// zero meaning unlimited. Exceeding it fails reading with [`io::ErrorKind::FileTooLarge`].
+pub async fn decompress_async_reader<R>(
+    mut reader: R,
+    content_type: Option<header::ContentType>,
+    limit: usize,
+) -> Result<Pin<Box<dyn AsyncRead + Send>>, Error>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    let mut prefix = Vec::new();
+    (&mut reader)
+        .take(DETECTION_PREFIX)
+        .read_to_end(&mut prefix)
+        .await?;
+
+    let encoding = detect(content_type, &prefix)?;
+
+    let source = BufReader::new(Cursor::new(prefix).chain(reader));
+    let reader: Pin<Box<dyn AsyncRead + Send>> = match encoding {
+        Encoding::Detected(Compression::None) => Box::pin(source),
+        Encoding::Detected(Compression::Gzip) => {
+            Box::pin(async_compression::tokio::bufread::GzipDecoder::new(source))
+        }
+        Encoding::Detected(Compression::Bzip2) => {
+            Box::pin(async_compression::tokio::bufread::BzDecoder::new(source))
+        }
+        Encoding::Detected(Compression::Xz) => {
+            Box::pin(async_compression::tokio::bufread::XzDecoder::new(source))
+        }
+        #[allow(unreachable_patterns)]
+        Encoding::Detected(_) => return Err(Error::UnknownType),
+        Encoding::Zstd => Box::pin(async_compression::tokio::bufread::ZstdDecoder::new(source)),
+    };
+
+    Ok(match limit {
+        0 => reader,
+        limit => Box::pin(LimitedReader::new(reader, limit as u64)),
+    })
+}
+
+/// A reader failing with [`io::ErrorKind::FileTooLarge`] once it exceeds a limit.
+pub struct LimitedReader<R> {
+    inner: R,
+    remaining: u64,
+}
+
+impl<R> LimitedReader<R> {
+    pub fn new(inner: R, limit: u64) -> Self {
+        Self {
+            inner,
+            remaining: limit,
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for LimitedReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let before = buf.filled().len();
+        ready!(Pin::new(&mut self.inner).poll_read(cx, buf))?;
+        let read = (buf.filled().len() - before) as u64;
+
+        match self.remaining.checked_sub(read) {
+            Some(remaining) => {
+                self.remaining = remaining;
+                Poll::Ready(Ok(()))
+            }
+            None => Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::FileTooLarge,
+                "payload too large",
+            ))),
+        }
+    }
+}
+
+/// Detect the compression of a payload, either declared by the content type, or detected by its
+/// magic bytes if no content type, or a generic one, is present.
+fn detect(content_type: Option<header::ContentType>, bytes: &[u8]) -> Result<Encoding, Error> {
+    // a generic content type doesn't declare anything
+    let content_type = content_type
+        .as_ref()
+        .filter(|ct| ct.0.essence_str() != GENERIC_CONTENT_TYPE)
+        .map(|ct| ct.as_ref());
 
     // check what the user has declared
 
     let declared = content_type.map(|content_type| {
         if content_type.ends_with("+bzip2") {
-            Compression::Bzip2
+            Encoding::Detected(Compression::Bzip2)
         } else if content_type.ends_with("+xz") {
-            Compression::Xz
+            Encoding::Detected(Compression::Xz)
         } else if content_type.ends_with("+gzip") {
-            Compression::Gzip
+            Encoding::Detected(Compression::Gzip)
+        } else if content_type.ends_with("+zstd") {
+            Encoding::Zstd
         } else {
             // The user provided a type, and it doesn't indicate a supported compression type,
             // So we just accept the payload as-is.
-            Compression::None
+            Encoding::Detected(Compression::None)
         }
     });
 
@@ -91,11 +232,14 @@ fn detect(content_type: Option<header::ContentType>, bytes: &[u8]) -> Result<Com
 
     Ok(match declared {
         Some(declared) => declared,
+        None if bytes.starts_with(&ZSTD_MAGIC) => Encoding::Zstd,
         None => {
             let detector = Detector::default();
-            detector
-                .detect(bytes)
-                .map_err(|err| Error::Detector(anyhow!("{err}")))?
+            Encoding::Detected(
+                detector
+                    .detect(bytes)
+                    .map_err(|err| Error::Detector(anyhow!("{err}")))?,
+            )
         }
     })
 }
@@ -111,6 +255,7 @@ pub async fn decompress_async_read(
     Ok(match path.extension().and_then(|ext| ext.to_str()) {
         Some("xz") => Box::pin(async_compression::tokio::bufread::LzmaDecoder::new(source)),
         Some("gz") => Box::pin(async_compression::tokio::bufread::GzipDecoder::new(source)),
+        Some("zst") => Box::pin(async_compression::tokio::bufread::ZstdDecoder::new(source)),
         // Anything else could be .sql, .tar, or an unsupported compression format.
         // In that case, the following code would fail to understand the compressed content.
         None | Some(_) => Box::pin(source),
@@ -126,6 +271,7 @@ pub fn decompress_read(path: impl AsRef<Path>) -> anyhow::Result<Box<dyn Read +
     Ok(match path.extension().and_then(|ext| ext.to_str()) {
         Some("xz") => Box::new(lzma_rust2::XzReader::new(source, false)),
         Some("gz") => Box::new(flate2::read::GzDecoder::new(source)),
+        Some("zst") => Box::new(zstd::stream::read::Decoder::with_buffer(source)?),
         // Anything else could be .sql, .tar, or an unsupported compression format.
         // In that case, the following code would fail to understand the compressed content.
         None | Some(_) => Box::new(source),
@@ -134,9 +280,10 @@ pub fn decompress_read(path: impl AsRef<Path>) -> anyhow::Result<Box<dyn Read +
 
 #[cfg(test)]
 mod test {
-    use crate::decompress::decompress_async;
+    use crate::decompress::{Error, decompress_async, decompress_async_reader};
     use actix_web::http::header::ContentType;
     use test_log::test;
+    use tokio::io::AsyncReadExt;
     use trustify_test_context::document_bytes_raw;
 
     #[test(tokio::test)]
@@ -215,6 +362,46 @@ mod test {
         Ok(())
     }
 
+    #[test(tokio::test)]
+    async fn decompress_zstd() -> anyhow::Result<()> {
+        let data = document_bytes_raw("ubi9-9.2-755.1697625012.json").await?;
+        let encoded = zstd::encode_all(&*data, 3)?;
+
+        let bytes = decompress_async(encoded.clone().into(), None, 0).await??;
+        assert_eq!(bytes, data);
+
+        let result = decompress_async(encoded.into(), None, 1024).await?;
+        assert!(matches!(result, Err(Error::PayloadTooLarge)));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn decompress_zstd_with_generic_type() -> anyhow::Result<()> {
+        let data = document_bytes_raw("ubi9-9.2-755.1697625012.json").await?;
+        let encoded = zstd::encode_all(&*data, 3)?;
+
+        // a generic content type doesn't declare anything, so we detect the compression
+
+        let bytes = decompress_async(encoded.clone().into(), Some(ContentType::octet_stream()), 0)
+            .await??;
+        assert_eq!(bytes, data);
+
+        // the streaming variant must behave the same
+
+        let mut reader = decompress_async_reader(
+            std::io::Cursor::new(encoded),
+            Some(ContentType::octet_stream()),
+            0,
+        )
+        .await?;
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        assert_eq!(bytes, data);
+
+        Ok(())
+    }
+
     #[test(tokio::test)]
     async fn decompress_xz_with_correct_type() -> anyhow::Result<()> {
         let bytes = decompress_async(
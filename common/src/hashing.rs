@@ -12,6 +12,7 @@ pub struct Contexts {
     sha512: Context,
     sha384: Context,
     sha256: Context,
+    blake3: blake3::Hasher,
     size: u64,
 }
 
@@ -21,6 +22,7 @@ impl Contexts {
             sha512: Context::new(&SHA512),
             sha384: Context::new(&SHA384),
             sha256: Context::new(&SHA256),
+            blake3: blake3::Hasher::new(),
             size: 0,
         }
     }
@@ -29,6 +31,7 @@ impl Contexts {
         self.sha512.update(data);
         self.sha384.update(data);
         self.sha256.update(data);
+        self.blake3.update(data);
         self.size += data.len() as u64;
     }
 
@@ -37,6 +40,7 @@ impl Contexts {
             sha512: self.sha512.clone().finish(),
             sha384: self.sha384.clone().finish(),
             sha256: self.sha256.clone().finish(),
+            blake3: self.blake3.finalize(),
             size: self.size,
         }
     }
@@ -46,6 +50,7 @@ impl Contexts {
             sha512: self.sha512.finish(),
             sha384: self.sha384.finish(),
             sha256: self.sha256.finish(),
+            blake3: self.blake3.finalize(),
             size: self.size,
         }
     }
@@ -62,6 +67,7 @@ pub struct Digests {
     pub sha512: Digest,
     pub sha384: Digest,
     pub sha256: Digest,
+    pub blake3: blake3::Hash,
     pub size: u64,
 }
 
@@ -75,7 +81,7 @@ impl Digests {
 }
 
 impl<R> HashingRead<R> {
-    /// Creates a HashingRead that uses SHA-512, SHA-384, and SHA-256
+    /// Creates a HashingRead that uses SHA-512, SHA-384, SHA-256, and BLAKE3
     pub fn new(inner: R) -> Self {
         Self {
             inner,
@@ -161,6 +167,7 @@ mod test {
         assert_eq!(digest(&SHA256, &data).as_ref(), digests.sha256.as_ref());
         assert_eq!(digest(&SHA384, &data).as_ref(), digests.sha384.as_ref());
         assert_eq!(digest(&SHA512, &data).as_ref(), digests.sha512.as_ref());
+        assert_eq!(blake3::hash(&data), digests.blake3);
     }
 
     /// HashingRead should return the correct hash of the data read so far when hash() is called
@@ -176,5 +183,6 @@ mod test {
         assert_eq!(digest(&SHA256, data_read).as_ref(), digests.sha256.as_ref());
         assert_eq!(digest(&SHA384, data_read).as_ref(), digests.sha384.as_ref());
         assert_eq!(digest(&SHA512, data_read).as_ref(), digests.sha512.as_ref());
+        assert_eq!(blake3::hash(data_read), digests.blake3);
     }
 }
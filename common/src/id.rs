@@ -26,6 +26,7 @@ pub enum Id {
     Sha256(String),
     Sha384(String),
     Sha512(String),
+    Blake3(String),
 }
 
 impl Id {
@@ -101,6 +102,7 @@ impl Id {
             Id::Sha256(_) => "sha256",
             Id::Sha384(_) => "sha384",
             Id::Sha512(_) => "sha512",
+            Id::Blake3(_) => "blake3",
             Id::Uuid(_) => "urn:uuid",
         }
     }
@@ -110,6 +112,7 @@ impl Id {
             Id::Sha256(inner) => inner.clone(),
             Id::Sha384(inner) => inner.clone(),
             Id::Sha512(inner) => inner.clone(),
+            Id::Blake3(inner) => inner.clone(),
             Id::Uuid(inner) => inner.simple().to_string(),
         }
     }
@@ -126,6 +129,10 @@ impl Id {
         Self::from_digest(digest, Id::Sha512)
     }
 
+    pub fn blake3(hash: &blake3::Hash) -> Self {
+        Id::Blake3(hash.to_hex().to_string())
+    }
+
     fn from_digest<F>(digest: &Digest, f: F) -> Self
     where
         F: FnOnce(String) -> Self,
@@ -212,6 +219,9 @@ impl Display for Id {
             Id::Sha512(inner) => {
                 write!(f, "sha512:{inner}")
             }
+            Id::Blake3(inner) => {
+                write!(f, "blake3:{inner}")
+            }
             Id::Uuid(inner) => {
                 write!(f, "{}", inner.urn())
             }
@@ -240,6 +250,7 @@ impl FromStr for Id {
                 "sha256" => Ok(Self::Sha256(value.to_string())),
                 "sha384" => Ok(Self::Sha384(value.to_string())),
                 "sha512" => Ok(Self::Sha512(value.to_string())),
+                "blake3" => Ok(Self::Blake3(value.to_string())),
                 "urn" => Ok(Self::Uuid(
                     Uuid::try_parse(key).map_err(IdError::InvalidUuid)?,
                 )),
@@ -278,6 +289,15 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn blake3() -> Result<(), anyhow::Error> {
+        let key: Id = serde_json::from_value(json!("blake3:abcdef"))?;
+        assert_eq!(key, Id::Blake3("abcdef".to_string()));
+        assert_eq!(key.to_string(), "blake3:abcdef");
+
+        Ok(())
+    }
+
     #[test]
     fn invalid() {
         assert!(Id::parse_uuid("invalid").is_err());
@@ -20,3 +20,5 @@ pub mod service;
 pub mod time;
 pub mod tls;
 pub mod uuid;
+pub mod vers;
+pub mod version;
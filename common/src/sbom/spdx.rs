@@ -1,6 +1,10 @@
 use sbom_walker::report::ReportSink;
+use serde::{Deserialize, Deserializer, de::Error as _};
 use serde_json::Value;
-use spdx_rs::models::SPDX;
+use spdx_rs::models::{
+    Annotation, DocumentCreationInformation, FileInformation, OtherLicensingInformationDetected,
+    PackageInformation, Relationship, SPDX, Snippet,
+};
 
 /// Parse a SPDX document, possibly replacing invalid license expressions.
 ///
@@ -15,14 +19,8 @@ pub fn fix_license(report: &dyn ReportSink, mut json: Value) -> (Value, bool) {
     let mut changed = false;
     if let Some(packages) = json["packages"].as_array_mut() {
         for package in packages {
-            if let Some(declared) = package["licenseDeclared"].as_str()
-                && let Err(err) = spdx_expression::SpdxExpression::parse(declared)
-            {
-                package["licenseDeclared"] = "NOASSERTION".into();
+            if let Some(message) = fix_package_license(package) {
                 changed = true;
-
-                let message =
-                    format!("Replacing faulty SPDX license expression with NOASSERTION: {err}");
                 log::debug!("{message}");
                 report.error(message);
             }
@@ -31,3 +29,139 @@ pub fn fix_license(report: &dyn ReportSink, mut json: Value) -> (Value, bool) {
 
     (json, changed)
 }
+
+/// Replace an invalid license expression of a package with `NOASSERTION`, returning the reason.
+fn fix_package_license(package: &mut Value) -> Option<String> {
+    let declared = package["licenseDeclared"].as_str()?;
+    let err = spdx_expression::SpdxExpression::parse(declared).err()?;
+
+    package["licenseDeclared"] = "NOASSERTION".into();
+    Some(format!(
+        "Replacing faulty SPDX license expression with NOASSERTION: {err}"
+    ))
+}
+
+/// A SPDX document, deserialized directly into its typed form.
+///
+/// Invalid license expressions are replaced the same way [`parse_spdx`] does. However, only a
+/// single package is held as JSON value at a time, which allows parsing the document from a
+/// reader, without holding the raw document in memory.
+#[derive(Deserialize)]
+pub struct LenientSpdx {
+    #[serde(flatten)]
+    document_creation_information: DocumentCreationInformation,
+    #[serde(rename = "packages", default)]
+    package_information: Vec<LenientPackage>,
+    #[serde(rename = "hasExtractedLicensingInfos", default)]
+    other_licensing_information_detected: Vec<OtherLicensingInformationDetected>,
+    #[serde(rename = "files", default)]
+    file_information: Vec<FileInformation>,
+    #[serde(rename = "snippets", default)]
+    snippet_information: Vec<Snippet>,
+    #[serde(default)]
+    relationships: Vec<Relationship>,
+    #[serde(default)]
+    annotations: Vec<Annotation>,
+}
+
+impl LenientSpdx {
+    /// Report replaced license expressions, returning the document and a flag indicating if
+    /// license expressions got replaced.
+    pub fn into_spdx(self, report: &dyn ReportSink) -> (SPDX, bool) {
+        let mut changed = false;
+        let package_information = self
+            .package_information
+            .into_iter()
+            .map(|LenientPackage { package, replaced }| {
+                if let Some(message) = replaced {
+                    changed = true;
+                    log::debug!("{message}");
+                    report.error(message);
+                }
+                package
+            })
+            .collect();
+
+        (
+            SPDX {
+                document_creation_information: self.document_creation_information,
+                package_information,
+                other_licensing_information_detected: self.other_licensing_information_detected,
+                file_information: self.file_information,
+                snippet_information: self.snippet_information,
+                relationships: self.relationships,
+                annotations: self.annotations,
+                spdx_ref_counter: 0,
+            },
+            changed,
+        )
+    }
+}
+
+/// A package, with an invalid license expression replaced.
+struct LenientPackage {
+    package: PackageInformation,
+    replaced: Option<String>,
+}
+
+impl<'de> Deserialize<'de> for LenientPackage {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut package = Value::deserialize(deserializer)?;
+        let replaced = fix_package_license(&mut package);
+        let package = serde_json::from_value(package).map_err(D::Error::custom)?;
+
+        Ok(Self { package, replaced })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn lenient_equals_value() -> Result<(), anyhow::Error> {
+        let json = json!({
+            "spdxVersion": "SPDX-2.3",
+            "dataLicense": "CC0-1.0",
+            "SPDXID": "SPDXRef-DOCUMENT",
+            "name": "test",
+            "documentNamespace": "https://example.com/test",
+            "creationInfo": {
+                "created": "2024-01-01T00:00:00Z",
+                "creators": ["Tool: test"],
+            },
+            "packages": [
+                {
+                    "SPDXID": "SPDXRef-a",
+                    "name": "a",
+                    "downloadLocation": "NOASSERTION",
+                    "licenseDeclared": "MIT",
+                },
+                {
+                    "SPDXID": "SPDXRef-b",
+                    "name": "b",
+                    "downloadLocation": "NOASSERTION",
+                    "licenseDeclared": "not a valid ( expression",
+                },
+            ],
+            "relationships": [
+                {
+                    "spdxElementId": "SPDXRef-DOCUMENT",
+                    "relationshipType": "DESCRIBES",
+                    "relatedSpdxElement": "SPDXRef-a",
+                },
+            ],
+        });
+
+        let (expected, expected_changed) = parse_spdx(&(), json.clone())?;
+        let lenient: LenientSpdx = serde_json::from_slice(&serde_json::to_vec(&json)?)?;
+        let (actual, changed) = lenient.into_spdx(&());
+
+        assert!(changed);
+        assert_eq!(changed, expected_changed);
+        assert_eq!(actual, expected);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,401 @@
+//! Version ranges, following the `vers` specification.
+//!
+//! See: <https://github.com/package-url/purl-spec/blob/main/VERSION-RANGE-SPEC.rst>
+
+use crate::version::Scheme;
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{Error, Visitor},
+};
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum VersError {
+    #[error("missing 'vers:' prefix")]
+    MissingPrefix,
+    #[error("missing versioning scheme")]
+    MissingScheme,
+    #[error("missing constraints")]
+    MissingConstraints,
+    #[error("invalid constraint: {0}")]
+    InvalidConstraint(String),
+    #[error("'*' must be the only constraint")]
+    InvalidWildcard,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Comparator {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparator {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Equal => "",
+            Self::NotEqual => "!=",
+            Self::Less => "<",
+            Self::LessOrEqual => "<=",
+            Self::Greater => ">",
+            Self::GreaterOrEqual => ">=",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Constraint {
+    pub comparator: Comparator,
+    pub version: String,
+}
+
+impl FromStr for Constraint {
+    type Err = VersError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        // longest comparators first
+        let (comparator, version) = [
+            ("!=", Comparator::NotEqual),
+            ("<=", Comparator::LessOrEqual),
+            (">=", Comparator::GreaterOrEqual),
+            ("<", Comparator::Less),
+            (">", Comparator::Greater),
+            ("=", Comparator::Equal),
+        ]
+        .into_iter()
+        .find_map(|(prefix, comparator)| s.strip_prefix(prefix).map(|v| (comparator, v)))
+        .unwrap_or((Comparator::Equal, s));
+
+        let version = version.trim();
+        if version.is_empty() || version.contains(['<', '>', '=', '!', '*']) {
+            return Err(VersError::InvalidConstraint(s.to_string()));
+        }
+
+        Ok(Self {
+            comparator,
+            version: version.to_string(),
+        })
+    }
+}
+
+impl Display for Constraint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.comparator.as_str(), self.version)
+    }
+}
+
+/// A bound of an interval, with its version and whether it's inclusive
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bound {
+    pub version: String,
+    pub inclusive: bool,
+}
+
+/// A single, continuous range of versions. A missing bound means unbounded.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Interval {
+    pub low: Option<Bound>,
+    pub high: Option<Bound>,
+}
+
+/// A `vers` version range, like `vers:maven/>=1.0|<2.0`
+///
+/// An empty list of constraints represents the wildcard `*`, matching all versions.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Vers {
+    pub scheme: String,
+    pub constraints: Vec<Constraint>,
+}
+
+impl Vers {
+    /// Convert into a single interval, if the range can be expressed as one.
+    ///
+    /// This is the case for a single version, a single lower and/or upper bound, or the wildcard.
+    pub fn interval(&self) -> Option<Interval> {
+        let mut result = Interval::default();
+
+        for constraint in &self.constraints {
+            let bound = |inclusive| {
+                Some(Bound {
+                    version: constraint.version.clone(),
+                    inclusive,
+                })
+            };
+
+            let (slot, value) = match constraint.comparator {
+                Comparator::Equal if self.constraints.len() == 1 => {
+                    return Some(Interval {
+                        low: bound(true),
+                        high: bound(true),
+                    });
+                }
+                Comparator::Equal | Comparator::NotEqual => return None,
+                Comparator::Greater => (&mut result.low, bound(false)),
+                Comparator::GreaterOrEqual => (&mut result.low, bound(true)),
+                Comparator::Less => (&mut result.high, bound(false)),
+                Comparator::LessOrEqual => (&mut result.high, bound(true)),
+            };
+
+            if slot.is_some() {
+                // more than one interval
+                return None;
+            }
+            *slot = value;
+        }
+
+        Some(result)
+    }
+
+    /// Split the range into the intervals it consists of.
+    ///
+    /// The constraints are sorted by their version, following the ordering of the versioning
+    /// scheme, so that an upper bound closes the interval of the preceding lower bound. A single
+    /// version becomes an interval containing only that version. A range consisting of excluded
+    /// versions only, like the wildcard, results in a single unbounded interval.
+    ///
+    /// Excluded versions are not part of the result, see [`Self::excluded`].
+    pub fn intervals(&self) -> Vec<Interval> {
+        let bound = |constraint: &Constraint, inclusive| Bound {
+            version: constraint.version.clone(),
+            inclusive,
+        };
+
+        let scheme = Scheme::from_vers(&self.scheme);
+        let mut constraints = self.constraints.iter().collect::<Vec<_>>();
+        constraints.sort_by(|a, b| scheme.compare(&a.version, &b.version));
+
+        let mut result = vec![];
+        let mut low = None;
+
+        for constraint in constraints {
+            match constraint.comparator {
+                Comparator::Equal => result.push(Interval {
+                    low: Some(bound(constraint, true)),
+                    high: Some(bound(constraint, true)),
+                }),
+                Comparator::NotEqual => {}
+                Comparator::Greater | Comparator::GreaterOrEqual => {
+                    if let Some(low) = low.take() {
+                        // two lower bounds in a row, the first one isn't closed
+                        result.push(Interval {
+                            low: Some(low),
+                            high: None,
+                        });
+                    }
+                    low = Some(bound(
+                        constraint,
+                        constraint.comparator == Comparator::GreaterOrEqual,
+                    ));
+                }
+                Comparator::Less | Comparator::LessOrEqual => result.push(Interval {
+                    low: low.take(),
+                    high: Some(bound(
+                        constraint,
+                        constraint.comparator == Comparator::LessOrEqual,
+                    )),
+                }),
+            }
+        }
+
+        if let Some(low) = low {
+            result.push(Interval {
+                low: Some(low),
+                high: None,
+            });
+        }
+
+        if result.is_empty() {
+            result.push(Interval::default());
+        }
+
+        result
+    }
+
+    /// The versions excluded from the range.
+    pub fn excluded(&self) -> impl Iterator<Item = &str> {
+        self.constraints
+            .iter()
+            .filter(|constraint| constraint.comparator == Comparator::NotEqual)
+            .map(|constraint| constraint.version.as_str())
+    }
+}
+
+impl FromStr for Vers {
+    type Err = VersError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s.strip_prefix("vers:").ok_or(VersError::MissingPrefix)?;
+        let (scheme, constraints) = s.split_once('/').ok_or(VersError::MissingConstraints)?;
+
+        let scheme = scheme.trim().to_lowercase();
+        if scheme.is_empty() {
+            return Err(VersError::MissingScheme);
+        }
+
+        let constraints = constraints.trim();
+        if constraints.is_empty() {
+            return Err(VersError::MissingConstraints);
+        }
+
+        if constraints == "*" {
+            return Ok(Self {
+                scheme,
+                constraints: vec![],
+            });
+        }
+        if constraints.contains('*') {
+            return Err(VersError::InvalidWildcard);
+        }
+
+        let constraints = constraints
+            .split('|')
+            .map(Constraint::from_str)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            scheme,
+            constraints,
+        })
+    }
+}
+
+impl Display for Vers {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "vers:{}/", self.scheme)?;
+
+        if self.constraints.is_empty() {
+            return write!(f, "*");
+        }
+
+        for (i, constraint) in self.constraints.iter().enumerate() {
+            if i > 0 {
+                write!(f, "|")?;
+            }
+            write!(f, "{constraint}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Serialize for Vers {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Vers {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(VersVisitor)
+    }
+}
+
+struct VersVisitor;
+
+impl Visitor<'_> for VersVisitor {
+    type Value = Vers;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter.write_str("a vers version range")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        v.parse().map_err(Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    fn bound(version: &str, inclusive: bool) -> Option<Bound> {
+        Some(Bound {
+            version: version.into(),
+            inclusive,
+        })
+    }
+
+    #[rstest]
+    #[case("vers:maven/1.0", "vers:maven/1.0")]
+    #[case("vers:npm/ >=1.0 | <2.0 ", "vers:npm/>=1.0|<2.0")]
+    #[case("vers:PyPI/*", "vers:pypi/*")]
+    #[case("vers:deb/=1.0|!=1.1", "vers:deb/1.0|!=1.1")]
+    fn parse(#[case] input: &str, #[case] output: &str) {
+        assert_eq!(Vers::from_str(input).unwrap().to_string(), output);
+    }
+
+    #[rstest]
+    #[case("maven/1.0", VersError::MissingPrefix)]
+    #[case("vers:/1.0", VersError::MissingScheme)]
+    #[case("vers:maven", VersError::MissingConstraints)]
+    #[case("vers:maven/", VersError::MissingConstraints)]
+    #[case("vers:maven/>=1.0|*", VersError::InvalidWildcard)]
+    #[case("vers:maven/>=", VersError::InvalidConstraint(">=".into()))]
+    #[case("vers:maven/>>1.0", VersError::InvalidConstraint(">>1.0".into()))]
+    fn parse_error(#[case] input: &str, #[case] error: VersError) {
+        assert_eq!(Vers::from_str(input), Err(error));
+    }
+
+    #[rstest]
+    #[case("vers:maven/*", Some(Interval::default()))]
+    #[case("vers:maven/1.0", Some(Interval { low: bound("1.0", true), high: bound("1.0", true) }))]
+    #[case("vers:maven/>1.0", Some(Interval { low: bound("1.0", false), high: None }))]
+    #[case("vers:maven/>=1.0|<=2.0", Some(Interval { low: bound("1.0", true), high: bound("2.0", true) }))]
+    #[case("vers:maven/1.0|2.0", None)]
+    #[case("vers:maven/!=1.0", None)]
+    #[case("vers:maven/>=1.0|<2.0|>=3.0", None)]
+    fn interval(#[case] input: &str, #[case] expected: Option<Interval>) {
+        assert_eq!(Vers::from_str(input).unwrap().interval(), expected);
+    }
+
+    #[rstest]
+    #[case("vers:maven/*", vec![Interval::default()])]
+    #[case("vers:maven/!=1.0", vec![Interval::default()])]
+    #[case("vers:maven/1.0|2.0", vec![
+        Interval { low: bound("1.0", true), high: bound("1.0", true) },
+        Interval { low: bound("2.0", true), high: bound("2.0", true) },
+    ])]
+    #[case("vers:maven/<1.0|>=2.0|<3.0|>4.0", vec![
+        Interval { low: None, high: bound("1.0", false) },
+        Interval { low: bound("2.0", true), high: bound("3.0", false) },
+        Interval { low: bound("4.0", false), high: None },
+    ])]
+    #[case("vers:maven/1.0|>=2.0|!=2.5", vec![
+        Interval { low: bound("1.0", true), high: bound("1.0", true) },
+        Interval { low: bound("2.0", true), high: None },
+    ])]
+    #[case("vers:npm/<1.10.0|>=1.9.0", vec![
+        Interval { low: bound("1.9.0", true), high: bound("1.10.0", false) },
+    ])]
+    #[case("vers:maven/<1.0|>=1.0-SNAPSHOT", vec![
+        Interval { low: bound("1.0-SNAPSHOT", true), high: bound("1.0", false) },
+    ])]
+    fn intervals(#[case] input: &str, #[case] expected: Vec<Interval>) {
+        assert_eq!(Vers::from_str(input).unwrap().intervals(), expected);
+    }
+
+    #[test]
+    fn excluded() {
+        let vers = Vers::from_str("vers:maven/>=1.0|!=1.1|!=1.2|<2.0").unwrap();
+        assert_eq!(vers.excluded().collect::<Vec<_>>(), vec!["1.1", "1.2"]);
+    }
+}
@@ -0,0 +1,96 @@
+use super::cmp_numeric;
+use std::cmp::Ordering;
+
+/// Compare Debian versions in the form of `[epoch:]upstream_version[-debian_revision]`.
+pub fn compare(a: &str, b: &str) -> Ordering {
+    let (epoch_a, upstream_a, revision_a) = split(a);
+    let (epoch_b, upstream_b, revision_b) = split(b);
+
+    cmp_numeric(epoch_a.as_bytes(), epoch_b.as_bytes())
+        .then_with(|| verrevcmp(upstream_a.as_bytes(), upstream_b.as_bytes()))
+        .then_with(|| verrevcmp(revision_a.as_bytes(), revision_b.as_bytes()))
+}
+
+fn split(version: &str) -> (&str, &str, &str) {
+    let (epoch, rest) = match version.split_once(':') {
+        Some((epoch, rest)) if epoch.bytes().all(|c| c.is_ascii_digit()) => (epoch, rest),
+        _ => ("0", version),
+    };
+
+    match rest.rsplit_once('-') {
+        Some((upstream, revision)) => (epoch, upstream, revision),
+        None => (epoch, rest, ""),
+    }
+}
+
+/// The weight of a character in a non-digit part: a tilde sorts before everything, even the end
+/// of the part, letters sort before all other characters.
+fn order(c: Option<u8>) -> i32 {
+    match c {
+        Some(b'~') => -1,
+        None => 0,
+        Some(c) if c.is_ascii_digit() => 0,
+        Some(c) if c.is_ascii_alphabetic() => c as i32,
+        Some(c) => c as i32 + 256,
+    }
+}
+
+/// The comparison of `dpkg`, alternating between non-digit and digit parts.
+fn verrevcmp(a: &[u8], b: &[u8]) -> Ordering {
+    let (mut i, mut j) = (0, 0);
+
+    while i < a.len() || j < b.len() {
+        // non-digit part
+        while a.get(i).is_some_and(|c| !c.is_ascii_digit())
+            || b.get(j).is_some_and(|c| !c.is_ascii_digit())
+        {
+            let (order_a, order_b) = (order(a.get(i).copied()), order(b.get(j).copied()));
+            if order_a != order_b {
+                return order_a.cmp(&order_b);
+            }
+            i += 1;
+            j += 1;
+        }
+
+        // digit part
+        let start_a = i;
+        while a.get(i).is_some_and(u8::is_ascii_digit) {
+            i += 1;
+        }
+        let start_b = j;
+        while b.get(j).is_some_and(u8::is_ascii_digit) {
+            j += 1;
+        }
+
+        let result = cmp_numeric(&a[start_a..i], &b[start_b..j]);
+        if result.is_ne() {
+            return result;
+        }
+    }
+
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("1.0", "1.0", Ordering::Equal)]
+    #[case("1.0", "1.00", Ordering::Equal)]
+    #[case("1.0", "1.1", Ordering::Less)]
+    #[case("1.0~rc1", "1.0", Ordering::Less)]
+    #[case("1.0~rc1", "1.0~~", Ordering::Greater)]
+    #[case("1.0a", "1.0", Ordering::Greater)]
+    #[case("1.0a", "1.0+", Ordering::Less)]
+    #[case("1.0-1", "1.0-2", Ordering::Less)]
+    #[case("1.0-1", "1.0", Ordering::Greater)]
+    #[case("1:0.9", "2.0", Ordering::Greater)]
+    #[case("2.36-9+deb12u4", "2.36-9+deb12u10", Ordering::Less)]
+    #[case("1.2.3-1ubuntu1", "1.2.3-1", Ordering::Greater)]
+    fn compare(#[case] a: &str, #[case] b: &str, #[case] expected: Ordering) {
+        assert_eq!(super::compare(a, b), expected);
+        assert_eq!(super::compare(b, a), expected.reverse());
+    }
+}
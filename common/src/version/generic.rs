@@ -0,0 +1,59 @@
+use super::{cmp_numeric, split_while};
+use std::cmp::Ordering;
+
+/// Compare versions segment by segment, numeric segments by value, others lexically.
+///
+/// Characters other than ASCII letters and digits only separate segments. Numeric segments are
+/// considered newer than alphabetic ones, and a version with more segments is newer than its
+/// prefix.
+pub fn compare(a: &str, b: &str) -> Ordering {
+    let mut a = a.as_bytes();
+    let mut b = b.as_bytes();
+
+    loop {
+        a = split_while(a, |c| !c.is_ascii_alphanumeric()).1;
+        b = split_while(b, |c| !c.is_ascii_alphanumeric()).1;
+
+        let (Some(first_a), Some(first_b)) = (a.first(), b.first()) else {
+            return a.len().cmp(&b.len());
+        };
+
+        let (numeric_a, numeric_b) = (first_a.is_ascii_digit(), first_b.is_ascii_digit());
+        if numeric_a != numeric_b {
+            return numeric_a.cmp(&numeric_b);
+        }
+
+        let (segment_a, rest_a) = split_while(a, |c| c.is_ascii_digit() == numeric_a);
+        let (segment_b, rest_b) = split_while(b, |c| c.is_ascii_digit() == numeric_b);
+
+        let result = match numeric_a {
+            true => cmp_numeric(segment_a, segment_b),
+            false => segment_a.cmp(segment_b),
+        };
+        if result.is_ne() {
+            return result;
+        }
+
+        a = rest_a;
+        b = rest_b;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("1.0", "1.0", Ordering::Equal)]
+    #[case("1.0", "1_0", Ordering::Equal)]
+    #[case("1.10", "1.9", Ordering::Greater)]
+    #[case("1.0.1", "1.0", Ordering::Greater)]
+    #[case("1.0a", "1.0", Ordering::Greater)]
+    #[case("1.0a", "1.0b", Ordering::Less)]
+    #[case("1.1", "1.a", Ordering::Greater)]
+    fn compare(#[case] a: &str, #[case] b: &str, #[case] expected: Ordering) {
+        assert_eq!(super::compare(a, b), expected);
+        assert_eq!(super::compare(b, a), expected.reverse());
+    }
+}
@@ -0,0 +1,153 @@
+use super::cmp_numeric;
+use std::cmp::Ordering;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Item {
+    Number(String),
+    Qualifier(String),
+}
+
+impl Item {
+    /// Items which are equal to a missing item, and can be dropped from the end
+    fn is_null(&self) -> bool {
+        match self {
+            Self::Number(n) => n.bytes().all(|c| c == b'0'),
+            Self::Qualifier(q) => q.is_empty(),
+        }
+    }
+}
+
+/// The rank of a qualifier, with unknown qualifiers sorting after all known ones.
+fn rank(qualifier: &str) -> usize {
+    match qualifier {
+        "alpha" => 0,
+        "beta" => 1,
+        "milestone" => 2,
+        "rc" => 3,
+        "snapshot" => 4,
+        "" => 5,
+        "sp" => 6,
+        _ => 7,
+    }
+}
+
+fn cmp_qualifier(a: &str, b: &str) -> Ordering {
+    rank(a).cmp(&rank(b)).then_with(|| a.cmp(b))
+}
+
+/// Split a version into its items, following the rules of Maven's `ComparableVersion`.
+///
+/// Items are separated by `.` and `-`, as well as transitions between digits and letters.
+/// Trailing null items (like `0` or `final`) get dropped, so that `1.0.0` equals `1`.
+///
+/// In contrast to Maven, this doesn't nest list items for `-` separated parts.
+fn parse(version: &str) -> Vec<Item> {
+    let version = version.to_lowercase();
+    let mut result = vec![];
+
+    let mut push = |segment: &str, numeric: bool, followed_by_digit: bool| {
+        let item = match numeric {
+            true => Item::Number(segment.to_string()),
+            false => Item::Qualifier(
+                match segment {
+                    "a" if followed_by_digit => "alpha",
+                    "b" if followed_by_digit => "beta",
+                    "m" if followed_by_digit => "milestone",
+                    "cr" => "rc",
+                    "ga" | "final" | "release" => "",
+                    other => other,
+                }
+                .to_string(),
+            ),
+        };
+        result.push(item);
+    };
+
+    let bytes = version.as_bytes();
+    let mut start = 0;
+    for i in 0..=bytes.len() {
+        let end = match bytes.get(i) {
+            None | Some(b'.' | b'-') => true,
+            Some(c) => i > start && c.is_ascii_digit() != bytes[i - 1].is_ascii_digit(),
+        };
+
+        if end {
+            if i > start {
+                let numeric = bytes[start].is_ascii_digit();
+                let followed_by_digit = bytes.get(i).is_some_and(u8::is_ascii_digit);
+                push(&version[start..i], numeric, followed_by_digit);
+            }
+            start = match bytes.get(i) {
+                Some(b'.' | b'-') => i + 1,
+                _ => i,
+            };
+        }
+    }
+
+    while result.last().is_some_and(Item::is_null) {
+        result.pop();
+    }
+
+    result
+}
+
+/// Compare Maven versions.
+pub fn compare(a: &str, b: &str) -> Ordering {
+    let a = parse(a);
+    let b = parse(b);
+
+    for i in 0..a.len().max(b.len()) {
+        let result = match (a.get(i), b.get(i)) {
+            (Some(Item::Number(a)), Some(Item::Number(b))) => {
+                cmp_numeric(a.as_bytes(), b.as_bytes())
+            }
+            (Some(Item::Number(_)), Some(Item::Qualifier(_))) => Ordering::Greater,
+            (Some(Item::Qualifier(_)), Some(Item::Number(_))) => Ordering::Less,
+            (Some(Item::Qualifier(a)), Some(Item::Qualifier(b))) => cmp_qualifier(a, b),
+            (Some(Item::Number(a)), None) => cmp_numeric(a.as_bytes(), b"0"),
+            (None, Some(Item::Number(b))) => cmp_numeric(b"0", b.as_bytes()),
+            (Some(Item::Qualifier(a)), None) => cmp_qualifier(a, ""),
+            (None, Some(Item::Qualifier(b))) => cmp_qualifier("", b),
+            (None, None) => Ordering::Equal,
+        };
+        if result.is_ne() {
+            return result;
+        }
+    }
+
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("1", "1.0.0", Ordering::Equal)]
+    #[case("1.0", "1.0-final", Ordering::Equal)]
+    #[case("1.0", "1.0-GA", Ordering::Equal)]
+    #[case("1.0", "1.1", Ordering::Less)]
+    #[case("1.10", "1.9", Ordering::Greater)]
+    #[case("1.0-alpha1", "1.0", Ordering::Less)]
+    #[case("1.0-a1", "1.0-alpha1", Ordering::Equal)]
+    #[case("1.0-alpha1", "1.0-beta1", Ordering::Less)]
+    #[case("1.0-beta1", "1.0-M1", Ordering::Less)]
+    #[case("1.0-M1", "1.0-RC1", Ordering::Less)]
+    #[case("1.0-CR1", "1.0-RC1", Ordering::Equal)]
+    #[case("1.0-RC1", "1.0-SNAPSHOT", Ordering::Less)]
+    #[case("1.0-SNAPSHOT", "1.0", Ordering::Less)]
+    #[case("1.0", "1.0-sp1", Ordering::Less)]
+    #[case("1.0-sp1", "1.0-foo", Ordering::Less)]
+    #[case("1.0-foo", "1.0.1", Ordering::Less)]
+    #[case(
+        "2.13.8.Final-redhat-00004",
+        "2.13.8.Final-redhat-00005",
+        Ordering::Less
+    )]
+    #[case("2.13.8.Final-redhat-00004", "2.13.8", Ordering::Greater)]
+    fn compare(#[case] a: &str, #[case] b: &str, #[case] expected: Ordering) {
+        assert_eq!(super::compare(a, b), expected);
+        assert_eq!(super::compare(b, a), expected.reverse());
+    }
+}
@@ -0,0 +1,105 @@
+//! Comparing versions, following the ordering rules of the different ecosystems.
+
+mod deb;
+mod generic;
+mod maven;
+mod pep440;
+mod rpm;
+mod semver;
+
+use std::cmp::Ordering;
+
+/// The rules of ordering versions
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Scheme {
+    /// Semantic versioning, parsed leniently
+    Semver,
+    /// Maven's `ComparableVersion`
+    Maven,
+    /// RPM `epoch:version-release`
+    Rpm,
+    /// Debian `epoch:upstream-revision`
+    Deb,
+    /// Python's PEP 440
+    Pypi,
+    /// Comparing numeric and alphabetic segments
+    Generic,
+}
+
+impl Scheme {
+    /// Get the scheme for a `vers` versioning scheme, falling back to the generic one.
+    pub fn from_vers(scheme: &str) -> Self {
+        match scheme {
+            "semver" | "npm" | "cargo" | "golang" | "hex" | "pub" | "swift" => Self::Semver,
+            "maven" => Self::Maven,
+            "rpm" => Self::Rpm,
+            "deb" => Self::Deb,
+            "pypi" => Self::Pypi,
+            _ => Self::Generic,
+        }
+    }
+
+    /// Compare two versions.
+    ///
+    /// Versions which cannot be parsed according to the scheme are compared using the generic
+    /// ordering.
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        match self {
+            Self::Semver => semver::compare(a, b),
+            Self::Maven => maven::compare(a, b),
+            Self::Rpm => rpm::compare(a, b),
+            Self::Deb => deb::compare(a, b),
+            Self::Pypi => pep440::compare(a, b),
+            Self::Generic => generic::compare(a, b),
+        }
+    }
+}
+
+/// Compare two strings of ASCII digits by their numeric value, without any limit of their length.
+fn cmp_numeric(a: &[u8], b: &[u8]) -> Ordering {
+    fn trim(s: &[u8]) -> &[u8] {
+        let zeros = s.iter().take_while(|c| **c == b'0').count();
+        &s[zeros..]
+    }
+
+    let a = trim(a);
+    let b = trim(b);
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+/// Split off the longest prefix of bytes matching the predicate.
+fn split_while(s: &[u8], f: impl Fn(u8) -> bool) -> (&[u8], &[u8]) {
+    let len = s.iter().take_while(|c| f(**c)).count();
+    s.split_at(len)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("1", "1", Ordering::Equal)]
+    #[case("001", "1", Ordering::Equal)]
+    #[case("10", "9", Ordering::Greater)]
+    #[case(
+        "99999999999999999999999",
+        "99999999999999999999998",
+        Ordering::Greater
+    )]
+    #[case("", "0", Ordering::Equal)]
+    fn numeric(#[case] a: &str, #[case] b: &str, #[case] expected: Ordering) {
+        assert_eq!(cmp_numeric(a.as_bytes(), b.as_bytes()), expected);
+    }
+
+    #[rstest]
+    #[case("npm", Scheme::Semver)]
+    #[case("maven", Scheme::Maven)]
+    #[case("rpm", Scheme::Rpm)]
+    #[case("deb", Scheme::Deb)]
+    #[case("pypi", Scheme::Pypi)]
+    #[case("gem", Scheme::Generic)]
+    fn from_vers(#[case] scheme: &str, #[case] expected: Scheme) {
+        assert_eq!(Scheme::from_vers(scheme), expected);
+    }
+}
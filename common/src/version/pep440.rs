@@ -0,0 +1,222 @@
+use super::generic;
+use std::cmp::Ordering;
+
+/// The pre-release part, ordered as required for comparison
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Pre {
+    /// A development release of the final release, like `1.0.dev1`
+    DevOnly,
+    /// A pre-release, with its phase (`a`, `b`, or `rc`) and number
+    Phase(u8, u64),
+    /// Not a pre-release
+    None,
+}
+
+/// The development release part, ordered as required for comparison
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Dev {
+    Number(u64),
+    None,
+}
+
+/// A segment of the local version label, numeric ones sort after alphanumeric ones
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Local {
+    Text(String),
+    Number(u64),
+}
+
+/// A parsed version, with its fields in the order of comparison
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Version {
+    epoch: u64,
+    /// The release segments, without trailing zeros
+    release: Vec<u64>,
+    pre: Pre,
+    post: Option<u64>,
+    dev: Dev,
+    local: Vec<Local>,
+}
+
+/// A simple cursor over the normalized version string.
+struct Parser<'a> {
+    input: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn separator(&mut self) {
+        if let Some(rest) = self.input.strip_prefix(['.', '-', '_']) {
+            self.input = rest;
+        }
+    }
+
+    fn number(&mut self) -> Option<u64> {
+        let len = self.input.bytes().take_while(u8::is_ascii_digit).count();
+        if len == 0 {
+            return None;
+        }
+        let (number, rest) = self.input.split_at(len);
+        self.input = rest;
+        number.parse().ok()
+    }
+
+    /// Consume the first matching keyword, followed by an optional separator and number.
+    fn keyword(&mut self, keywords: &[&'a str]) -> Option<(&'a str, u64)> {
+        let keyword = *keywords
+            .iter()
+            .find(|keyword| self.input.starts_with(**keyword))?;
+        self.input = &self.input[keyword.len()..];
+
+        let before = self.input;
+        self.separator();
+        let number = self.number().unwrap_or_else(|| {
+            // an implicit number, so don't consume the separator
+            self.input = before;
+            0
+        });
+
+        Some((keyword, number))
+    }
+
+    /// Try a part, preceded by an optional separator, restoring the input if it doesn't match.
+    fn part<T>(&mut self, f: impl FnOnce(&mut Self) -> Option<T>) -> Option<T> {
+        let before = self.input;
+        self.separator();
+        let result = f(self);
+        if result.is_none() {
+            self.input = before;
+        }
+        result
+    }
+}
+
+fn parse(version: &str) -> Option<Version> {
+    let version = version.trim().to_lowercase();
+    let version = version.strip_prefix('v').unwrap_or(version.as_str());
+
+    let (version, local) = match version.split_once('+') {
+        Some((version, local)) => (version, Some(local)),
+        None => (version, None),
+    };
+
+    let mut parser = Parser { input: version };
+
+    let mut epoch = 0;
+    if let Some((epoch_part, rest)) = version.split_once('!') {
+        epoch = epoch_part.parse().ok()?;
+        parser.input = rest;
+    }
+
+    let mut release = vec![parser.number()?];
+    while let Some(rest) = parser.input.strip_prefix('.')
+        && rest.starts_with(|c: char| c.is_ascii_digit())
+    {
+        parser.input = rest;
+        release.push(parser.number()?);
+    }
+    while release.last() == Some(&0) {
+        release.pop();
+    }
+
+    let pre = parser.part(|p| {
+        p.keyword(&["alpha", "beta", "preview", "pre", "rc", "a", "b", "c"])
+            .map(|(phase, number)| {
+                let phase = match phase {
+                    "alpha" | "a" => 0,
+                    "beta" | "b" => 1,
+                    _ => 2,
+                };
+                (phase, number)
+            })
+    });
+
+    let post = parser
+        .part(|p| p.keyword(&["post", "rev", "r"]).map(|(_, number)| number))
+        .or_else(|| {
+            // implicit post release, like `1.0-1`
+            let before = parser.input;
+            let number = parser.input.strip_prefix('-').and_then(|rest| {
+                parser.input = rest;
+                parser.number()
+            });
+            if number.is_none() {
+                parser.input = before;
+            }
+            number
+        });
+
+    let dev = parser.part(|p| p.keyword(&["dev"]).map(|(_, number)| number));
+
+    if !parser.input.is_empty() {
+        return None;
+    }
+
+    let local = local
+        .map(|local| {
+            local
+                .split(['.', '-', '_'])
+                .map(|segment| match segment.parse() {
+                    Ok(number) => Local::Number(number),
+                    Err(_) => Local::Text(segment.to_string()),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(Version {
+        epoch,
+        release,
+        pre: match (pre, post, dev) {
+            (Some((phase, number)), _, _) => Pre::Phase(phase, number),
+            (None, None, Some(_)) => Pre::DevOnly,
+            (None, _, _) => Pre::None,
+        },
+        post,
+        dev: dev.map(Dev::Number).unwrap_or(Dev::None),
+        local,
+    })
+}
+
+/// Compare Python versions, following PEP 440.
+pub fn compare(a: &str, b: &str) -> Ordering {
+    match (parse(a), parse(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        _ => generic::compare(a, b),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("1.0", "1.0.0", Ordering::Equal)]
+    #[case("1.0", "v1.0", Ordering::Equal)]
+    #[case("1.0", "1.1", Ordering::Less)]
+    #[case("1.10", "1.9", Ordering::Greater)]
+    #[case("1.0.dev1", "1.0a1", Ordering::Less)]
+    #[case("1.0a1", "1.0alpha1", Ordering::Equal)]
+    #[case("1.0a1", "1.0-a.1", Ordering::Equal)]
+    #[case("1.0a1.dev1", "1.0a1", Ordering::Less)]
+    #[case("1.0a1", "1.0b1", Ordering::Less)]
+    #[case("1.0b1", "1.0rc1", Ordering::Less)]
+    #[case("1.0c1", "1.0rc1", Ordering::Equal)]
+    #[case("1.0rc1", "1.0", Ordering::Less)]
+    #[case("1.0", "1.0.post1", Ordering::Less)]
+    #[case("1.0.post1", "1.0-1", Ordering::Equal)]
+    #[case("1.0.post1.dev1", "1.0.post1", Ordering::Less)]
+    #[case("1.0", "1.0+local", Ordering::Less)]
+    #[case("1.0+abc", "1.0+1", Ordering::Less)]
+    #[case("1!0.1", "2.0", Ordering::Greater)]
+    fn compare(#[case] a: &str, #[case] b: &str, #[case] expected: Ordering) {
+        assert_eq!(super::compare(a, b), expected);
+        assert_eq!(super::compare(b, a), expected.reverse());
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(parse("1.0-foo"), None);
+        assert_eq!(parse("foo"), None);
+    }
+}
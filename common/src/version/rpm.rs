@@ -0,0 +1,141 @@
+use super::{cmp_numeric, split_while};
+use std::cmp::Ordering;
+
+/// Compare RPM versions in the form of `[epoch:]version[-release]`.
+///
+/// A missing epoch is considered `0`. The release is only compared if both versions have one,
+/// so that `1.0` matches all releases of `1.0`.
+pub fn compare(a: &str, b: &str) -> Ordering {
+    let (epoch_a, version_a, release_a) = split_evr(a);
+    let (epoch_b, version_b, release_b) = split_evr(b);
+
+    cmp_numeric(epoch_a.as_bytes(), epoch_b.as_bytes())
+        .then_with(|| rpmvercmp(version_a, version_b))
+        .then_with(|| match (release_a, release_b) {
+            (Some(release_a), Some(release_b)) => rpmvercmp(release_a, release_b),
+            _ => Ordering::Equal,
+        })
+}
+
+fn split_evr(evr: &str) -> (&str, &str, Option<&str>) {
+    let (epoch, rest) = match evr.split_once(':') {
+        Some((epoch, rest)) if epoch.bytes().all(|c| c.is_ascii_digit()) => (epoch, rest),
+        _ => ("0", evr),
+    };
+
+    match rest.rsplit_once('-') {
+        Some((version, release)) => (epoch, version, Some(release)),
+        None => (epoch, rest, None),
+    }
+}
+
+/// The segment comparison of `rpmvercmp`, including the handling of `~` and `^`.
+fn rpmvercmp(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+
+    let separator = |c: u8| !c.is_ascii_alphanumeric() && c != b'~' && c != b'^';
+
+    let mut a = a.as_bytes();
+    let mut b = b.as_bytes();
+
+    loop {
+        a = split_while(a, separator).1;
+        b = split_while(b, separator).1;
+
+        // a tilde sorts before everything, even the end of the version
+        match (a.first(), b.first()) {
+            (Some(b'~'), Some(b'~')) => {
+                a = &a[1..];
+                b = &b[1..];
+                continue;
+            }
+            (Some(b'~'), _) => return Ordering::Less,
+            (_, Some(b'~')) => return Ordering::Greater,
+            _ => {}
+        }
+
+        // a caret sorts after the end of the version, but before everything else
+        match (a.first(), b.first()) {
+            (Some(b'^'), Some(b'^')) => {
+                a = &a[1..];
+                b = &b[1..];
+                continue;
+            }
+            (Some(b'^'), None) => return Ordering::Greater,
+            (None, Some(b'^')) => return Ordering::Less,
+            (Some(b'^'), _) => return Ordering::Less,
+            (_, Some(b'^')) => return Ordering::Greater,
+            _ => {}
+        }
+
+        let Some(first) = a.first() else {
+            break;
+        };
+        if b.is_empty() {
+            break;
+        }
+
+        let numeric = first.is_ascii_digit();
+        let segment = |c: u8| match numeric {
+            true => c.is_ascii_digit(),
+            false => c.is_ascii_alphabetic(),
+        };
+
+        let (segment_a, rest_a) = split_while(a, segment);
+        let (segment_b, rest_b) = split_while(b, segment);
+
+        // segments of different types, numeric ones are newer
+        if segment_b.is_empty() {
+            return match numeric {
+                true => Ordering::Greater,
+                false => Ordering::Less,
+            };
+        }
+
+        let result = match numeric {
+            true => cmp_numeric(segment_a, segment_b),
+            false => segment_a.cmp(segment_b),
+        };
+        if result.is_ne() {
+            return result;
+        }
+
+        a = rest_a;
+        b = rest_b;
+    }
+
+    // the version with characters left is newer
+    a.is_empty().cmp(&b.is_empty()).reverse()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("1.0", "1.0", Ordering::Equal)]
+    #[case("1.0", "2.0", Ordering::Less)]
+    #[case("2.0.1", "2.0", Ordering::Greater)]
+    #[case("2.0.1a", "2.0.1", Ordering::Greater)]
+    #[case("5.5p1", "5.5p10", Ordering::Less)]
+    #[case("10xyz", "10.1xyz", Ordering::Less)]
+    #[case("xyz10", "xyz10.1", Ordering::Less)]
+    #[case("1.0~rc1", "1.0", Ordering::Less)]
+    #[case("1.0~rc1", "1.0~rc2", Ordering::Less)]
+    #[case("1.0^", "1.0", Ordering::Greater)]
+    #[case("1.0^git1", "1.0.1", Ordering::Less)]
+    #[case("1.0^git1", "1.0~rc1", Ordering::Greater)]
+    #[case("1.0", "1_0", Ordering::Equal)]
+    #[case("a", "1", Ordering::Less)]
+    #[case("1:1.0", "2.0", Ordering::Greater)]
+    #[case("0:1.0", "1.0", Ordering::Equal)]
+    #[case("3.0.7-18.el9_2", "3.0.7-24.el9", Ordering::Less)]
+    #[case("3.0.7-18.el9_2", "3.0.7", Ordering::Equal)]
+    fn compare(#[case] a: &str, #[case] b: &str, #[case] expected: Ordering) {
+        assert_eq!(super::compare(a, b), expected);
+        assert_eq!(super::compare(b, a), expected.reverse());
+    }
+}
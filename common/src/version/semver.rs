@@ -0,0 +1,35 @@
+use super::generic;
+use std::cmp::Ordering;
+
+/// Compare semantic versions, ignoring build metadata.
+///
+/// Versions are parsed leniently, so that `v1.2` equals `1.2.0`.
+pub fn compare(a: &str, b: &str) -> Ordering {
+    match (lenient_semver::parse(a), lenient_semver::parse(b)) {
+        (Ok(a), Ok(b)) => {
+            (a.major, a.minor, a.patch, &a.pre).cmp(&(b.major, b.minor, b.patch, &b.pre))
+        }
+        _ => generic::compare(a, b),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("1.2.3", "1.2.3", Ordering::Equal)]
+    #[case("v1.2", "1.2.0", Ordering::Equal)]
+    #[case("1.2.3+build1", "1.2.3+build2", Ordering::Equal)]
+    #[case("1.10.0", "1.9.0", Ordering::Greater)]
+    #[case("1.0.0-alpha", "1.0.0", Ordering::Less)]
+    #[case("1.0.0-alpha", "1.0.0-alpha.1", Ordering::Less)]
+    #[case("1.0.0-alpha.beta", "1.0.0-beta", Ordering::Less)]
+    #[case("1.0.0-beta.2", "1.0.0-beta.11", Ordering::Less)]
+    #[case("1.0.0-rc.1", "1.0.0", Ordering::Less)]
+    fn compare(#[case] a: &str, #[case] b: &str, #[case] expected: Ordering) {
+        assert_eq!(super::compare(a, b), expected);
+        assert_eq!(super::compare(b, a), expected.reverse());
+    }
+}
@@ -40,6 +40,9 @@ pub enum Relation {
 
     #[sea_orm(has_many = "super::advisory_vulnerability::Entity")]
     AdvisoryVulnerability,
+
+    #[sea_orm(has_many = "super::advisory_revision::Entity")]
+    Revision,
 }
 
 impl Related<super::source_document::Entity> for Entity {
@@ -70,6 +73,12 @@ impl Related<advisory_vulnerability::Entity> for Entity {
     }
 }
 
+impl Related<super::advisory_revision::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Revision.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
 
 impl TryFilterForId for Entity {
@@ -85,6 +94,9 @@ impl TryFilterForId for Entity {
             Id::Sha512(hash) => super::source_document::Column::Sha512
                 .eq(hash)
                 .into_condition(),
+            Id::Blake3(hash) => super::source_document::Column::Blake3
+                .eq(hash)
+                .into_condition(),
             n => return Err(IdError::UnsupportedAlgorithm(n.prefix().to_string())),
         })
     }
@@ -0,0 +1,35 @@
+use sea_orm::entity::prelude::*;
+use time::OffsetDateTime;
+
+/// An entry of the revision history of an advisory, as stated by the document
+///
+/// For example, the `revision_history` of the tracking information of a CSAF document.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "advisory_revision")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub advisory_id: Uuid,
+    /// The version of the document this revision created
+    #[sea_orm(primary_key)]
+    pub number: String,
+    pub date: OffsetDateTime,
+    pub summary: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::advisory::Entity",
+        from = "Column::AdvisoryId",
+        to = "super::advisory::Column::Id"
+    )]
+    Advisory,
+}
+
+impl Related<super::advisory::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Advisory.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
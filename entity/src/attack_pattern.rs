@@ -0,0 +1,23 @@
+use sea_orm::entity::prelude::*;
+
+/// An attack pattern of the CAPEC catalog
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "attack_pattern")]
+pub struct Model {
+    /// The ID, in the form of `CAPEC-<number>`
+    #[sea_orm(primary_key)]
+    pub id: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub likelihood: Option<String>,
+    pub severity: Option<String>,
+    pub child_of: Option<Vec<String>>,
+    pub parent_of: Option<Vec<String>>,
+    /// The weaknesses (`CWE-<number>`) which this attack pattern exploits
+    pub related_weaknesses: Option<Vec<String>>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
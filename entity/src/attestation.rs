@@ -0,0 +1,62 @@
+use crate::labels::Labels;
+use sea_orm::{FromJsonQueryResult, entity::prelude::*};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use time::OffsetDateTime;
+
+/// A provenance attestation (in-toto statement), describing how its subjects got built
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "attestation")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+    pub source_document_id: Uuid,
+    pub labels: Labels,
+    /// The type of the predicate, e.g. `https://slsa.dev/provenance/v1`
+    pub predicate_type: String,
+    pub builder_id: Option<String>,
+    pub build_type: Option<String>,
+    pub invocation_id: Option<String>,
+    pub started_on: Option<OffsetDateTime>,
+    pub finished_on: Option<OffsetDateTime>,
+    /// The materials (resolved dependencies) which went into the build
+    #[sea_orm(column_type = "JsonBinary")]
+    pub materials: Materials,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, FromJsonQueryResult, Serialize, Deserialize)]
+pub struct Materials(pub Vec<Material>);
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Material {
+    #[serde(default)]
+    pub uri: String,
+    /// Digests, by algorithm
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub digest: BTreeMap<String, String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::source_document::Entity"
+        from = "Column::SourceDocumentId"
+        to = "super::source_document::Column::Id")]
+    SourceDocument,
+    #[sea_orm(has_many = "super::attestation_subject::Entity")]
+    Subject,
+}
+
+impl Related<super::source_document::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SourceDocument.def()
+    }
+}
+
+impl Related<super::attestation_subject::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Subject.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
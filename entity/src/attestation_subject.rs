@@ -0,0 +1,34 @@
+use sea_orm::entity::prelude::*;
+
+/// A digest of an artifact, which is the subject of an attestation
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "attestation_subject")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub attestation_id: Uuid,
+    #[sea_orm(primary_key)]
+    pub name: String,
+    /// The digest algorithm, e.g. `sha256`
+    #[sea_orm(primary_key)]
+    pub algorithm: String,
+    /// The hex encoded digest value
+    pub digest: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::attestation::Entity",
+        from = "Column::AttestationId",
+        to = "super::attestation::Column::Id"
+    )]
+    Attestation,
+}
+
+impl Related<super::attestation::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Attestation.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -17,6 +17,8 @@ pub struct Model {
     pub progress_current: Option<i32>,
     pub progress_total: Option<i32>,
     pub progress_message: Option<String>,
+    pub progress_failed: Option<i32>,
+    pub progress_skipped: Option<i32>,
 
     /// an importer specific continuation token
     pub continuation: Option<serde_json::Value>,
@@ -25,6 +27,9 @@ pub struct Model {
 
     /// Unix time in nanos, for coordinating importer replicas
     pub heartbeat: Option<Decimal>,
+
+    /// A requested change of the lifecycle
+    pub control: Option<Control>,
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, EnumIter, DeriveActiveEnum)]
@@ -34,6 +39,15 @@ pub enum State {
     Running = 1,
 }
 
+#[derive(Copy, Clone, Debug, Eq, PartialEq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "i32", db_type = "Integer")]
+pub enum Control {
+    /// Stop the current run after the current document, and don't start new runs
+    Pause = 0,
+    /// Abort the current run
+    Cancel = 1,
+}
+
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_many = "super::importer_report::Entity")]
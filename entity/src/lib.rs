@@ -1,6 +1,10 @@
 pub mod advisory;
+pub mod advisory_revision;
 pub mod advisory_vulnerability;
 pub mod advisory_vulnerability_score;
+pub mod attack_pattern;
+pub mod attestation;
+pub mod attestation_subject;
 pub mod base_purl;
 pub mod cpe;
 pub mod expanded_license;
@@ -36,6 +40,7 @@ pub mod sbom_node_cpe_ref;
 pub mod sbom_node_purl_ref;
 pub mod sbom_package;
 pub mod sbom_package_license;
+pub mod sbom_vex;
 pub mod source_document;
 pub mod status;
 pub mod user_preferences;
@@ -43,5 +48,9 @@ pub mod version_range;
 pub mod version_scheme;
 pub mod versioned_purl;
 pub mod vulnerability;
+pub mod vulnerability_alias;
 pub mod vulnerability_description;
+pub mod vulnerability_epss;
+pub mod vulnerability_exception;
+pub mod vulnerability_kev;
 pub mod weakness;
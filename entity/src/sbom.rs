@@ -156,6 +156,9 @@ impl TryFilterForId for Entity {
             Id::Sha512(hash) => super::source_document::Column::Sha512
                 .eq(hash)
                 .into_condition(),
+            Id::Blake3(hash) => super::source_document::Column::Blake3
+                .eq(hash)
+                .into_condition(),
             n => return Err(IdError::UnsupportedAlgorithm(n.prefix().to_string())),
         })
     }
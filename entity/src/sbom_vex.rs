@@ -0,0 +1,100 @@
+use sea_orm::entity::prelude::*;
+
+/// A VEX statement embedded in an SBOM, about a vulnerability affecting one of its components
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "sbom_vex")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub sbom_id: Uuid,
+    #[sea_orm(primary_key)]
+    pub node_id: String,
+    #[sea_orm(primary_key)]
+    pub vulnerability_id: String,
+    /// The state of the analysis, `None` if the vulnerability was reported without one
+    pub state: Option<VexState>,
+    pub justification: Option<String>,
+    pub response: Vec<String>,
+    pub detail: Option<String>,
+}
+
+/// The state of an impact analysis
+/// https://cyclonedx.org/docs/1.6/json/#vulnerabilities_items_analysis_state
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    serde::Serialize,
+    serde::Deserialize,
+    strum::EnumString,
+    strum::Display,
+    utoipa::ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "vex_state")]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum VexState {
+    /// The vulnerability was remediated.
+    #[sea_orm(string_value = "resolved")]
+    Resolved,
+    /// The vulnerability was remediated, and evidence of the changes is provided in the pedigree.
+    #[sea_orm(string_value = "resolved_with_pedigree")]
+    ResolvedWithPedigree,
+    /// The vulnerability may be directly or indirectly exploitable.
+    #[sea_orm(string_value = "exploitable")]
+    Exploitable,
+    /// The vulnerability is being investigated.
+    #[sea_orm(string_value = "in_triage")]
+    InTriage,
+    /// The vulnerability is not specific to the component and was falsely identified.
+    #[sea_orm(string_value = "false_positive")]
+    FalsePositive,
+    /// The component is not affected by the vulnerability.
+    #[sea_orm(string_value = "not_affected")]
+    NotAffected,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::sbom::Entity",
+        from = "Column::SbomId",
+        to = "super::sbom::Column::SbomId"
+    )]
+    Sbom,
+    #[sea_orm(
+        belongs_to = "super::sbom_node::Entity",
+        from = "(Column::SbomId, Column::NodeId)",
+        to = "(super::sbom_node::Column::SbomId, super::sbom_node::Column::NodeId)"
+    )]
+    Node,
+    #[sea_orm(
+        belongs_to = "super::vulnerability::Entity",
+        from = "Column::VulnerabilityId",
+        to = "super::vulnerability::Column::Id"
+    )]
+    Vulnerability,
+}
+
+impl Related<super::sbom::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Sbom.def()
+    }
+}
+
+impl Related<super::sbom_node::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Node.def()
+    }
+}
+
+impl Related<super::vulnerability::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Vulnerability.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
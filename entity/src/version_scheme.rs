@@ -1,5 +1,6 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
+use trustify_common::version::Scheme;
 
 #[derive(
     Copy,
@@ -38,6 +39,50 @@ pub enum VersionScheme {
     Cargo,
 }
 
+impl VersionScheme {
+    /// Translate from a `vers` versioning scheme, which is based on the PURL type.
+    pub fn from_vers(scheme: &str) -> Self {
+        match scheme {
+            "cargo" => Self::Cargo,
+            "composer" => Self::Packagist,
+            "gem" => Self::Gem,
+            "git" => Self::Git,
+            "golang" => Self::Golang,
+            "hex" => Self::Hex,
+            "maven" => Self::Maven,
+            "npm" => Self::Npm,
+            "nuget" => Self::NuGet,
+            "pub" => Self::Pub,
+            "pypi" => Self::Python,
+            "rpm" => Self::Rpm,
+            "semver" => Self::Semver,
+            "swift" => Self::Swift,
+            _ => Self::Generic,
+        }
+    }
+
+    /// The ordering of versions following this scheme, or `None` if they can't be ordered.
+    pub fn ordering(&self) -> Option<Scheme> {
+        match self {
+            Self::Git => None,
+            Self::Generic => Some(Scheme::Generic),
+            Self::Rpm => Some(Scheme::Rpm),
+            Self::Python => Some(Scheme::Pypi),
+            Self::Maven => Some(Scheme::Maven),
+            Self::Semver
+            | Self::Golang
+            | Self::Npm
+            | Self::Packagist
+            | Self::NuGet
+            | Self::Gem
+            | Self::Hex
+            | Self::Swift
+            | Self::Pub
+            | Self::Cargo => Some(Scheme::Semver),
+        }
+    }
+}
+
 /// Translate from other ecosystems to our internal version scheme.
 ///
 /// For CVE see: <https://github.com/CVEProject/cve-schema/blob/6af5c9c49c5b62e7b1f46756e1f3aef328848e1c/schema/CVE_Record_Format.json#L306-L318>
@@ -44,6 +44,9 @@ pub enum Relation {
 
     #[sea_orm(has_many = "super::purl_status::Entity")]
     PurlStatuses,
+
+    #[sea_orm(has_many = "super::vulnerability_alias::Entity")]
+    Aliases,
 }
 
 impl Related<super::purl_status::Entity> for Entity {
@@ -84,4 +87,10 @@ impl Related<vulnerability_description::Entity> for Entity {
     }
 }
 
+impl Related<super::vulnerability_alias::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Aliases.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
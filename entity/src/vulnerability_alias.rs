@@ -0,0 +1,46 @@
+use sea_orm::entity::prelude::*;
+
+/// An alternative identifier of a vulnerability, as stated by an advisory
+///
+/// For example, a GitHub Security Advisory (`GHSA-…`) which is an alias of a CVE.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "vulnerability_alias")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub vulnerability_id: String,
+    #[sea_orm(primary_key)]
+    pub alias: String,
+    /// The advisory stating the alias
+    #[sea_orm(primary_key)]
+    pub advisory_id: Uuid,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::vulnerability::Entity",
+        from = "Column::VulnerabilityId",
+        to = "super::vulnerability::Column::Id"
+    )]
+    Vulnerability,
+    #[sea_orm(
+        belongs_to = "super::advisory::Entity",
+        from = "Column::AdvisoryId",
+        to = "super::advisory::Column::Id"
+    )]
+    Advisory,
+}
+
+impl Related<super::vulnerability::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Vulnerability.def()
+    }
+}
+
+impl Related<super::advisory::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Advisory.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
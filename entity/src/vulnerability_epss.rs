@@ -0,0 +1,25 @@
+use sea_orm::entity::prelude::*;
+use time::OffsetDateTime;
+
+/// The EPSS (Exploit Prediction Scoring System) score of a vulnerability
+///
+/// This is keyed by the CVE identifier only, as the scores get published for all CVEs, regardless
+/// of whether the vulnerability is known to the system.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "vulnerability_epss")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub vulnerability_id: String,
+    /// The probability of exploitation activity in the next 30 days
+    pub probability: f64,
+    /// The percentile of the probability, compared to all other scored vulnerabilities
+    pub percentile: f64,
+    /// The date the score was calculated for
+    pub date: Option<OffsetDateTime>,
+    pub model_version: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,76 @@
+use sea_orm::entity::prelude::*;
+use time::OffsetDateTime;
+
+/// An exception for a vulnerability, in the context of a product
+///
+/// While the exception is active (it has no expiry, or the expiry is in the future), findings of
+/// the vulnerability are suppressed from the default views of SBOMs belonging to the product.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "vulnerability_exception")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: Uuid,
+    pub product_id: Uuid,
+    pub vulnerability_id: String,
+    pub kind: ExceptionKind,
+    pub justification: String,
+    pub expires: Option<OffsetDateTime>,
+    pub created: OffsetDateTime,
+}
+
+/// The kind of exception
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    serde::Serialize,
+    serde::Deserialize,
+    strum::EnumString,
+    strum::Display,
+    utoipa::ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "exception_kind")]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case", ascii_case_insensitive)]
+pub enum ExceptionKind {
+    /// The risk of the finding was reviewed and accepted.
+    #[sea_orm(string_value = "accepted")]
+    Accepted,
+    /// The finding does not apply to the product.
+    #[sea_orm(string_value = "false-positive")]
+    FalsePositive,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::product::Entity",
+        from = "Column::ProductId",
+        to = "super::product::Column::Id"
+    )]
+    Product,
+    #[sea_orm(
+        belongs_to = "super::vulnerability::Entity",
+        from = "Column::VulnerabilityId",
+        to = "super::vulnerability::Column::Id"
+    )]
+    Vulnerability,
+}
+
+impl Related<super::product::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Product.def()
+    }
+}
+
+impl Related<super::vulnerability::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Vulnerability.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,28 @@
+use sea_orm::entity::prelude::*;
+use time::OffsetDateTime;
+
+/// An entry of the CISA catalog of known exploited vulnerabilities (KEV)
+///
+/// Like [`super::vulnerability_epss`], this is keyed by the CVE identifier only.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "vulnerability_kev")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub vulnerability_id: String,
+    /// The date the vulnerability was added to the catalog
+    pub date_added: OffsetDateTime,
+    /// The date by which federal agencies must apply the required action
+    pub due_date: Option<OffsetDateTime>,
+    pub vendor_project: Option<String>,
+    pub product: Option<String>,
+    pub name: Option<String>,
+    pub required_action: Option<String>,
+    /// If the vulnerability is known to be used in ransomware campaigns
+    pub known_ransomware_campaign_use: bool,
+    pub catalog_version: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
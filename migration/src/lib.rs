@@ -58,6 +58,19 @@ mod m0002160_fix_ref_fk;
 mod m0002170_drop_cvss_tables;
 mod m0002180_advisory_fk_indexes;
 mod m0002190_vulnerability_base_score_advisory;
+mod m0002200_vulnerability_exception;
+mod m0002210_sbom_vex;
+mod m0002220_vulnerability_alias;
+mod m0002230_vulnerability_epss;
+mod m0002240_vulnerability_kev;
+mod m0002250_attack_pattern;
+mod m0002260_attestation;
+mod m0002270_source_document_blake3;
+mod m0002280_importer_control;
+mod m0002290_importer_progress_counts;
+mod m0002300_sbom_node_purl_ref_index;
+mod m0002310_advisory_revision;
+mod m0002320_lease;
 
 pub trait MigratorExt: Send {
     fn build_migrations() -> Migrations;
@@ -132,6 +145,19 @@ impl MigratorExt for Migrator {
             .normal(m0002170_drop_cvss_tables::Migration)
             .normal(m0002180_advisory_fk_indexes::Migration)
             .normal(m0002190_vulnerability_base_score_advisory::Migration)
+            .normal(m0002200_vulnerability_exception::Migration)
+            .normal(m0002210_sbom_vex::Migration)
+            .normal(m0002220_vulnerability_alias::Migration)
+            .normal(m0002230_vulnerability_epss::Migration)
+            .normal(m0002240_vulnerability_kev::Migration)
+            .normal(m0002250_attack_pattern::Migration)
+            .normal(m0002260_attestation::Migration)
+            .normal(m0002270_source_document_blake3::Migration)
+            .normal(m0002280_importer_control::Migration)
+            .normal(m0002290_importer_progress_counts::Migration)
+            .normal(m0002300_sbom_node_purl_ref_index::Migration)
+            .normal(m0002310_advisory_revision::Migration)
+            .normal(m0002320_lease::Migration)
     }
 }
 
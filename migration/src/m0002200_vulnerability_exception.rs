@@ -0,0 +1,150 @@
+use sea_orm_migration::prelude::*;
+use sea_query::extension::postgres::Type;
+use strum::VariantNames;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let builder = manager.get_connection().get_database_backend();
+        let values = ExceptionKind::VARIANTS.iter().skip(1).copied();
+        let stmt = builder
+            .build(Type::create().as_enum(ExceptionKind::Table).values(values))
+            .to_string();
+        manager.get_connection().execute_unprepared(&stmt).await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(VulnerabilityException::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(VulnerabilityException::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(VulnerabilityException::ProductId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(VulnerabilityException::VulnerabilityId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(VulnerabilityException::Kind)
+                            .custom(ExceptionKind::Table)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(VulnerabilityException::Justification)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(VulnerabilityException::Expires)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(VulnerabilityException::Created)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_col(VulnerabilityException::ProductId)
+                            .to(Product::Table, Product::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_col(VulnerabilityException::VulnerabilityId)
+                            .to(Vulnerability::Table, Vulnerability::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .table(VulnerabilityException::Table)
+                    .name(Indexes::VulnerabilityExceptionProductIdIdx.to_string())
+                    .col(VulnerabilityException::ProductId)
+                    .col(VulnerabilityException::VulnerabilityId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(VulnerabilityException::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(
+                Type::drop()
+                    .if_exists()
+                    .name(ExceptionKind::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Indexes {
+    VulnerabilityExceptionProductIdIdx,
+}
+
+#[derive(DeriveIden)]
+enum VulnerabilityException {
+    Table,
+    Id,
+    ProductId,
+    VulnerabilityId,
+    Kind,
+    Justification,
+    Expires,
+    Created,
+}
+
+#[derive(DeriveIden)]
+enum Product {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Vulnerability {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden, strum::VariantNames, strum::Display, Clone)]
+#[strum(serialize_all = "kebab-case", ascii_case_insensitive)]
+#[allow(unused)]
+enum ExceptionKind {
+    Table,
+    Accepted,
+    #[sea_orm(iden = "false-positive")]
+    FalsePositive,
+}
@@ -0,0 +1,141 @@
+use sea_orm_migration::prelude::*;
+use sea_query::extension::postgres::Type;
+use strum::VariantNames;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let builder = manager.get_connection().get_database_backend();
+        let values = VexState::VARIANTS.iter().skip(1).copied();
+        let stmt = builder
+            .build(Type::create().as_enum(VexState::Table).values(values))
+            .to_string();
+        manager.get_connection().execute_unprepared(&stmt).await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(SbomVex::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(SbomVex::SbomId).uuid().not_null())
+                    .col(ColumnDef::new(SbomVex::NodeId).string().not_null())
+                    .col(ColumnDef::new(SbomVex::VulnerabilityId).string().not_null())
+                    .col(
+                        ColumnDef::new(SbomVex::State)
+                            .custom(VexState::Table)
+                            .null(),
+                    )
+                    .col(ColumnDef::new(SbomVex::Justification).string().null())
+                    .col(
+                        ColumnDef::new(SbomVex::Response)
+                            .array(ColumnType::Text)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(SbomVex::Detail).string().null())
+                    .primary_key(
+                        Index::create()
+                            .col(SbomVex::SbomId)
+                            .col(SbomVex::NodeId)
+                            .col(SbomVex::VulnerabilityId)
+                            .primary(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_col(SbomVex::SbomId)
+                            .to(Sbom::Table, Sbom::SbomId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(SbomVex::Table, (SbomVex::SbomId, SbomVex::NodeId))
+                            .to(SbomNode::Table, (SbomNode::SbomId, SbomNode::NodeId))
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_col(SbomVex::VulnerabilityId)
+                            .to(Vulnerability::Table, Vulnerability::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .table(SbomVex::Table)
+                    .name(Indexes::SbomVexVulnerabilityIdIdx.to_string())
+                    .col(SbomVex::VulnerabilityId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SbomVex::Table).if_exists().to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().if_exists().name(VexState::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Indexes {
+    SbomVexVulnerabilityIdIdx,
+}
+
+#[derive(DeriveIden)]
+enum SbomVex {
+    Table,
+    SbomId,
+    NodeId,
+    VulnerabilityId,
+    State,
+    Justification,
+    Response,
+    Detail,
+}
+
+#[derive(DeriveIden)]
+enum Sbom {
+    Table,
+    SbomId,
+}
+
+#[derive(DeriveIden)]
+enum SbomNode {
+    Table,
+    SbomId,
+    NodeId,
+}
+
+#[derive(DeriveIden)]
+enum Vulnerability {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden, strum::VariantNames, strum::Display, Clone)]
+#[strum(serialize_all = "snake_case")]
+#[allow(unused)]
+enum VexState {
+    Table,
+    Resolved,
+    ResolvedWithPedigree,
+    Exploitable,
+    InTriage,
+    FalsePositive,
+    NotAffected,
+}
@@ -0,0 +1,103 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(VulnerabilityAlias::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(VulnerabilityAlias::VulnerabilityId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(VulnerabilityAlias::Alias)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(VulnerabilityAlias::AdvisoryId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(VulnerabilityAlias::VulnerabilityId)
+                            .col(VulnerabilityAlias::Alias)
+                            .col(VulnerabilityAlias::AdvisoryId)
+                            .primary(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_col(VulnerabilityAlias::VulnerabilityId)
+                            .to(Vulnerability::Table, Vulnerability::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_col(VulnerabilityAlias::AdvisoryId)
+                            .to(Advisory::Table, Advisory::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .table(VulnerabilityAlias::Table)
+                    .name(Indexes::VulnerabilityAliasAliasIdx.to_string())
+                    .col(VulnerabilityAlias::Alias)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(VulnerabilityAlias::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Indexes {
+    VulnerabilityAliasAliasIdx,
+}
+
+#[derive(DeriveIden)]
+enum VulnerabilityAlias {
+    Table,
+    VulnerabilityId,
+    Alias,
+    AdvisoryId,
+}
+
+#[derive(DeriveIden)]
+enum Vulnerability {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Advisory {
+    Table,
+    Id,
+}
@@ -0,0 +1,71 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // EPSS covers all published CVEs, not only the ones we know about. So there is no foreign
+        // key to the vulnerability table.
+        manager
+            .create_table(
+                Table::create()
+                    .table(VulnerabilityEpss::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(VulnerabilityEpss::VulnerabilityId)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(VulnerabilityEpss::Probability)
+                            .double()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(VulnerabilityEpss::Percentile)
+                            .double()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(VulnerabilityEpss::Date)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(VulnerabilityEpss::ModelVersion)
+                            .string()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(VulnerabilityEpss::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum VulnerabilityEpss {
+    Table,
+    VulnerabilityId,
+    Probability,
+    Percentile,
+    Date,
+    ModelVersion,
+}
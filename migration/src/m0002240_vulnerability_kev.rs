@@ -0,0 +1,88 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Like EPSS, the catalog may list vulnerabilities we don't know about. So there is no
+        // foreign key to the vulnerability table.
+        manager
+            .create_table(
+                Table::create()
+                    .table(VulnerabilityKev::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(VulnerabilityKev::VulnerabilityId)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(VulnerabilityKev::DateAdded)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(VulnerabilityKev::DueDate)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(VulnerabilityKev::VendorProject)
+                            .string()
+                            .null(),
+                    )
+                    .col(ColumnDef::new(VulnerabilityKev::Product).string().null())
+                    .col(ColumnDef::new(VulnerabilityKev::Name).string().null())
+                    .col(
+                        ColumnDef::new(VulnerabilityKev::RequiredAction)
+                            .string()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(VulnerabilityKev::KnownRansomwareCampaignUse)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(VulnerabilityKev::CatalogVersion)
+                            .string()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(VulnerabilityKev::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum VulnerabilityKev {
+    Table,
+    VulnerabilityId,
+    DateAdded,
+    DueDate,
+    VendorProject,
+    Product,
+    Name,
+    RequiredAction,
+    KnownRansomwareCampaignUse,
+    CatalogVersion,
+}
@@ -0,0 +1,59 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AttackPattern::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AttackPattern::Id)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(AttackPattern::Name).string().null())
+                    .col(ColumnDef::new(AttackPattern::Description).string().null())
+                    .col(ColumnDef::new(AttackPattern::Likelihood).string().null())
+                    .col(ColumnDef::new(AttackPattern::Severity).string().null())
+                    .col(ColumnDef::new(AttackPattern::ChildOf).array(ColumnType::Text))
+                    .col(ColumnDef::new(AttackPattern::ParentOf).array(ColumnType::Text))
+                    .col(ColumnDef::new(AttackPattern::RelatedWeaknesses).array(ColumnType::Text))
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(AttackPattern::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum AttackPattern {
+    Table,
+    Id,
+    Name,
+    Description,
+    Likelihood,
+    Severity,
+    ChildOf,
+    ParentOf,
+    RelatedWeaknesses,
+}
@@ -0,0 +1,169 @@
+use crate::UuidV4;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Attestation::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Attestation::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Func::cust(UuidV4)),
+                    )
+                    .col(
+                        ColumnDef::new(Attestation::SourceDocumentId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Attestation::Labels).json_binary().not_null())
+                    .col(
+                        ColumnDef::new(Attestation::PredicateType)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Attestation::BuilderId).string().null())
+                    .col(ColumnDef::new(Attestation::BuildType).string().null())
+                    .col(ColumnDef::new(Attestation::InvocationId).string().null())
+                    .col(
+                        ColumnDef::new(Attestation::StartedOn)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(Attestation::FinishedOn)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(Attestation::Materials)
+                            .json_binary()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_col(Attestation::SourceDocumentId)
+                            .to(SourceDocument::Table, SourceDocument::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(AttestationSubject::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AttestationSubject::AttestationId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(AttestationSubject::Name).string().not_null())
+                    .col(
+                        ColumnDef::new(AttestationSubject::Algorithm)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AttestationSubject::Digest)
+                            .string()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(AttestationSubject::AttestationId)
+                            .col(AttestationSubject::Name)
+                            .col(AttestationSubject::Algorithm)
+                            .primary(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_col(AttestationSubject::AttestationId)
+                            .to(Attestation::Table, Attestation::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .table(AttestationSubject::Table)
+                    .name(Indexes::AttestationSubjectDigestIdx.to_string())
+                    .col(AttestationSubject::Digest)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(AttestationSubject::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(Attestation::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Indexes {
+    AttestationSubjectDigestIdx,
+}
+
+#[derive(DeriveIden)]
+enum Attestation {
+    Table,
+    Id,
+    SourceDocumentId,
+    Labels,
+    PredicateType,
+    BuilderId,
+    BuildType,
+    InvocationId,
+    StartedOn,
+    FinishedOn,
+    Materials,
+}
+
+#[derive(DeriveIden)]
+enum AttestationSubject {
+    Table,
+    AttestationId,
+    Name,
+    Algorithm,
+    Digest,
+}
+
+#[derive(DeriveIden)]
+enum SourceDocument {
+    Table,
+    Id,
+}
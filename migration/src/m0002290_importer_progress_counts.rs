@@ -0,0 +1,42 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Importer::Table)
+                    .add_column(ColumnDef::new(Importer::ProgressFailed).integer().null())
+                    .add_column(ColumnDef::new(Importer::ProgressSkipped).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Importer::Table)
+                    .drop_column(Importer::ProgressSkipped)
+                    .drop_column(Importer::ProgressFailed)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Importer {
+    Table,
+    ProgressFailed,
+    ProgressSkipped,
+}
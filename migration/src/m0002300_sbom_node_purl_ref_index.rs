@@ -0,0 +1,50 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // allows finding the SBOMs of a PURL using an index only scan
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .table(SbomNodePurlRef::Table)
+                    .name(Indexes::IdxSbomNodePurlRefPurlSbom.to_string())
+                    .col(SbomNodePurlRef::QualifiedPurlId)
+                    .col(SbomNodePurlRef::SbomId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .if_exists()
+                    .table(SbomNodePurlRef::Table)
+                    .name(Indexes::IdxSbomNodePurlRefPurlSbom.to_string())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Indexes {
+    IdxSbomNodePurlRefPurlSbom,
+}
+
+#[derive(DeriveIden)]
+enum SbomNodePurlRef {
+    Table,
+    QualifiedPurlId,
+    SbomId,
+}
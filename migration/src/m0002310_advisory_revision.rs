@@ -0,0 +1,76 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AdvisoryRevision::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AdvisoryRevision::AdvisoryId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(AdvisoryRevision::Number).string().not_null())
+                    .col(
+                        ColumnDef::new(AdvisoryRevision::Date)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AdvisoryRevision::Summary)
+                            .string()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(AdvisoryRevision::AdvisoryId)
+                            .col(AdvisoryRevision::Number)
+                            .primary(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_col(AdvisoryRevision::AdvisoryId)
+                            .to(Advisory::Table, Advisory::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(AdvisoryRevision::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum AdvisoryRevision {
+    Table,
+    AdvisoryId,
+    Number,
+    Date,
+    Summary,
+}
+
+#[derive(DeriveIden)]
+enum Advisory {
+    Table,
+    Id,
+}
@@ -89,7 +89,7 @@ pub(crate) mod trustify_benches {
                                 .transaction(async |tx| {
                                     ctx.ingestor
                                         .ingest(
-                                            &data,
+                                            data.clone(),
                                             Format::Advisory,
                                             Labels::default(),
                                             None,
@@ -6,28 +6,31 @@ mod test;
 use crate::{
     Error,
     advisory::{
-        model::{AdvisoryDetails, AdvisorySummary},
+        model::{
+            AdvisoryDetails, AdvisoryHead, AdvisorySummary, csaf::CsafDocument, diff::AdvisoryDiff,
+        },
         service::AdvisoryService,
     },
     common::service::delete_doc,
-    endpoints::Deprecation,
+    endpoints::{Deprecation, Withdrawal},
+    source_document::download::download_response,
 };
-use actix_web::{HttpResponse, Responder, delete, get, http::header, post, web};
+use actix_web::{HttpRequest, HttpResponse, Responder, delete, get, http::header, post, web};
 use config::Config;
-use futures_util::TryStreamExt;
 use sea_orm::TransactionTrait;
 use std::str::FromStr;
 use time::OffsetDateTime;
 use trustify_auth::{CreateAdvisory, DeleteAdvisory, ReadAdvisory, authorizer::Require};
 use trustify_common::{
     db::{self, pagination_cache::PaginationCache, query::Query},
-    decompress::decompress_async,
+    decompress::decompress_async_reader,
     id::Id,
     model::{BinaryData, Paginated, PaginatedResults},
 };
 use trustify_entity::labels::Labels;
-use trustify_module_ingestor::service::{Cache, Format, IngestorService};
-use trustify_module_storage::service::StorageBackend;
+use trustify_module_ingestor::service::{
+    Cache, Format, IngestOptions, IngestorService, ValidationLevel, signature::Signature, stream,
+};
 use trustify_query::TrustifyQuery;
 use trustify_query_derive::Query;
 use utoipa::IntoParams;
@@ -52,6 +55,9 @@ pub fn configure(
         .service(delete)
         .service(upload)
         .service(download)
+        .service(export_csaf)
+        .service(revisions)
+        .service(diff)
         .service(label::set)
         .service(label::update)
         .service(label::all);
@@ -81,6 +87,7 @@ struct AdvisoryQuery {
         TrustifyQuery<AdvisoryQuery>,
         Paginated,
         Deprecation,
+        Withdrawal,
     ),
     responses(
         (status = 200, description = "Matching vulnerabilities", body = PaginatedResults<AdvisorySummary>),
@@ -94,12 +101,13 @@ pub async fn all(
     web::Query(search): web::Query<Query>,
     web::Query(paginated): web::Query<Paginated>,
     web::Query(Deprecation { deprecated }): web::Query<Deprecation>,
+    web::Query(Withdrawal { withdrawn }): web::Query<Withdrawal>,
     _: Require<ReadAdvisory>,
 ) -> actix_web::Result<impl Responder> {
     let tx = db.begin().await?;
     Ok(HttpResponse::Ok().json(
         state
-            .fetch_advisories(search, paginated, deprecated, &tx)
+            .fetch_advisories(search, paginated, deprecated, withdrawn, &tx)
             .await?,
     ))
 }
@@ -160,7 +168,7 @@ pub async fn delete(
         && service.delete_advisory(v.head.uuid, &tx).await?
     {
         tx.commit().await?;
-        if let Err(e) = delete_doc(&v.source_document, i.storage()).await {
+        if let Err(e) = delete_doc(&v.source_document, db.as_ref(), i.storage()).await {
             log::error!("Ignoring {e}");
         }
     }
@@ -181,6 +189,10 @@ struct UploadParams {
     #[serde(default = "default_format")]
     #[param(inline)]
     format: Format,
+    /// How to deal with documents violating their specification.
+    #[serde(default)]
+    #[param(inline)]
+    level: ValidationLevel,
 }
 
 const fn default_format() -> Format {
@@ -191,13 +203,20 @@ const fn default_format() -> Format {
     tag = "advisory",
     operation_id = "uploadAdvisory",
     request_body = inline(BinaryData),
-    params(UploadParams),
+    params(
+        UploadParams,
+        ("x-sigstore-bundle" = Option<String>, Header, description = "A Sigstore bundle of the document, base64 encoded"),
+        ("x-signature" = Option<String>, Header, description = "A detached signature of the document, base64 encoded"),
+        ("x-signing-certificate" = Option<String>, Header, description = "The certificate of the detached signature, base64 encoded"),
+    ),
     responses(
         (status = 201, description = "Upload a file"),
         (status = 400, description = "The file could not be parsed as an advisory"),
+        (status = 400, description = "The signature of the document is invalid or untrusted"),
     )
 )]
 #[post("/v3/advisory")]
+#[allow(clippy::too_many_arguments)]
 /// Upload a new advisory
 pub async fn upload(
     service: web::Data<IngestorService>,
@@ -206,26 +225,37 @@ pub async fn upload(
         issuer,
         labels,
         format,
+        level,
     }): web::Query<UploadParams>,
     content_type: Option<web::Header<header::ContentType>>,
-    bytes: web::Bytes,
+    req: HttpRequest,
+    payload: web::Payload,
     db: web::Data<db::ReadWrite>,
     _: Require<CreateAdvisory>,
 ) -> Result<impl Responder, Error> {
-    let bytes = decompress_async(bytes, content_type.map(|ct| ct.0), config.upload_limit).await??;
+    let signature = Signature::from_headers(req.headers())?;
 
     let tx = db.begin().await?;
 
-    let result = service
-        .ingest(
-            &bytes,
-            format,
-            labels,
-            issuer,
-            Cache::Skip, /* we only cache SBOMs */
-            &tx,
+    let result = stream::with_reader(payload, async |reader| {
+        let reader =
+            decompress_async_reader(reader, content_type.map(|ct| ct.0), config.upload_limit)
+                .await?;
+        Ok::<_, Error>(
+            service
+                .ingest_stream(
+                    reader,
+                    format,
+                    labels,
+                    issuer,
+                    Cache::Skip, /* we only cache SBOMs */
+                    IngestOptions::default().level(level).signature(signature),
+                    &tx,
+                )
+                .await?,
         )
-        .await?;
+    })
+    .await?;
     log::info!("Uploaded Advisory: {}", result.id);
 
     tx.commit().await?;
@@ -241,7 +271,9 @@ pub async fn upload(
     ),
     responses(
         (status = 200, description = "Download a an advisory", body = inline(BinaryData)),
+        (status = 206, description = "Download the requested range of the document", body = inline(BinaryData)),
         (status = 404, description = "The document could not be found"),
+        (status = 416, description = "The requested range is not satisfiable"),
     )
 )]
 #[get("/v3/advisory/{key}/download")]
@@ -251,6 +283,7 @@ pub async fn download(
     ingestor: web::Data<IngestorService>,
     advisory: web::Data<AdvisoryService>,
     key: web::Path<String>,
+    request: HttpRequest,
     _: Require<ReadAdvisory>,
 ) -> Result<impl Responder, Error> {
     let id = Id::from_str(&key).map_err(Error::IdKey)?;
@@ -261,15 +294,92 @@ pub async fn download(
         return Ok(HttpResponse::NotFound().finish());
     };
 
-    let stream = ingestor
-        .storage()
-        .retrieve(advisory.source_document.try_into()?)
-        .await
-        .map_err(Error::Storage)?
-        .map(|stream| stream.map_err(Error::Storage));
+    download_response(&request, ingestor.storage(), &advisory.source_document).await
+}
 
-    Ok(match stream {
-        Some(s) => HttpResponse::Ok().streaming(s),
-        None => HttpResponse::NotFound().finish(),
-    })
+#[utoipa::path(
+    tag = "advisory",
+    operation_id = "exportAdvisoryCsaf",
+    params(
+        ("key" = Id, Path, description = "Identifier of the advisory, either `urn:uuid:<uuid>` or a digest e.g. `sha256:<hex>`"),
+    ),
+    responses(
+        (status = 200, description = "The advisory as CSAF 2.0 document", body = CsafDocument),
+        (status = 404, description = "The advisory could not be found"),
+    ),
+)]
+#[get("/v3/advisory/{key}/csaf")]
+/// Export an advisory as CSAF 2.0 document
+pub async fn export_csaf(
+    state: web::Data<AdvisoryService>,
+    db: web::Data<db::ReadOnly>,
+    key: web::Path<String>,
+    _: Require<ReadAdvisory>,
+) -> actix_web::Result<impl Responder> {
+    let id = Id::from_str(&key).map_err(Error::IdKey)?;
+    let tx = db.begin().await?;
+
+    match state.export_csaf(id, &tx).await? {
+        Some(document) => Ok(HttpResponse::Ok().json(document)),
+        None => Ok(HttpResponse::NotFound().finish()),
+    }
+}
+
+#[utoipa::path(
+    tag = "advisory",
+    operation_id = "listAdvisoryRevisions",
+    params(
+        ("key" = Id, Path, description = "Identifier of one of the revisions"),
+    ),
+    responses(
+        (status = 200, description = "All revisions of the advisory, oldest first", body = Vec<AdvisoryHead>),
+        (status = 404, description = "The advisory could not be found"),
+    ),
+)]
+#[get("/v3/advisory/{key}/revisions")]
+/// List all revisions of an advisory
+pub async fn revisions(
+    state: web::Data<AdvisoryService>,
+    db: web::Data<db::ReadOnly>,
+    key: web::Path<String>,
+    _: Require<ReadAdvisory>,
+) -> actix_web::Result<impl Responder> {
+    let id = Id::from_str(&key).map_err(Error::IdKey)?;
+    let tx = db.begin().await?;
+
+    match state.fetch_revisions(id, &tx).await? {
+        Some(revisions) => Ok(HttpResponse::Ok().json(revisions)),
+        None => Ok(HttpResponse::NotFound().finish()),
+    }
+}
+
+#[utoipa::path(
+    tag = "advisory",
+    operation_id = "diffAdvisories",
+    params(
+        ("key" = Id, Path, description = "Identifier of the revision to compare from"),
+        ("other" = Id, Path, description = "Identifier of the revision to compare to"),
+    ),
+    responses(
+        (status = 200, description = "The differences between the two revisions", body = AdvisoryDiff),
+        (status = 404, description = "One of the advisories could not be found"),
+    ),
+)]
+#[get("/v3/advisory/{key}/diff/{other}")]
+/// Compare two revisions of an advisory
+pub async fn diff(
+    state: web::Data<AdvisoryService>,
+    db: web::Data<db::ReadOnly>,
+    path: web::Path<(String, String)>,
+    _: Require<ReadAdvisory>,
+) -> actix_web::Result<impl Responder> {
+    let (key, other) = path.into_inner();
+    let from = Id::from_str(&key).map_err(Error::IdKey)?;
+    let to = Id::from_str(&other).map_err(Error::IdKey)?;
+    let tx = db.begin().await?;
+
+    match state.diff_advisories(from, to, &tx).await? {
+        Some(diff) => Ok(HttpResponse::Ok().json(diff)),
+        None => Ok(HttpResponse::NotFound().finish()),
+    }
 }
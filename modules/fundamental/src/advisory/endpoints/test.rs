@@ -23,7 +23,9 @@ use trustify_module_ingestor::{
     service::Format,
 };
 use trustify_module_storage::service::{StorageBackend, StorageKey};
-use trustify_test_context::{TrustifyContext, call::CallService, document_bytes};
+use trustify_test_context::{
+    TrustifyContext, call::CallService, document_bytes, subset::ContainsSubset,
+};
 use urlencoding::encode;
 
 #[test_context(TrustifyContext)]
@@ -381,9 +383,12 @@ async fn search_advisories(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
 #[test_context(TrustifyContext)]
 #[test(actix_web::test)]
 async fn rejected_cve(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
-    let query = async |expected_count, q| {
+    let query = async |expected_count, q, withdrawn| {
         let app = caller(ctx).await.unwrap();
-        let uri = format!("/api/v3/advisory?total=true&q={}", urlencoding::encode(q));
+        let uri = format!(
+            "/api/v3/advisory?total=true&withdrawn={withdrawn}&q={}",
+            urlencoding::encode(q)
+        );
         let req = TestRequest::get().uri(&uri).to_request();
         let response: Value = app.call_and_read_body_json(req).await;
         tracing::debug!(test = "", "{response:#?}");
@@ -394,11 +399,15 @@ async fn rejected_cve(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
     ctx.ingest_documents(["cve/CVE-2024-25704.json", "cve/CVE-2024-29025.json"])
         .await?;
 
-    query(2, "labels:type=cve").await;
-    query(1, "withdrawn=\x00&identifier=CVE-2024-29025").await;
-    query(1, "withdrawn!=\x00&identifier=CVE-2024-25704").await;
-    query(1, "withdrawn=2024-04-25T18:21:10.150Z").await;
-    query(1, "withdrawn<2025-01-01").await;
+    // rejected CVEs are ignored, unless requested
+    query(1, "labels:type=cve", "Ignore").await;
+    query(0, "identifier=CVE-2024-25704", "Ignore").await;
+
+    query(2, "labels:type=cve", "Consider").await;
+    query(1, "withdrawn=\x00&identifier=CVE-2024-29025", "Consider").await;
+    query(1, "withdrawn!=\x00&identifier=CVE-2024-25704", "Consider").await;
+    query(1, "withdrawn=2024-04-25T18:21:10.150Z", "Consider").await;
+    query(1, "withdrawn<2025-01-01", "Consider").await;
 
     Ok(())
 }
@@ -560,6 +569,39 @@ async fn upload_with_labels(ctx: &TrustifyContext) -> Result<(), anyhow::Error>
     Ok(())
 }
 
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn upload_with_untrusted_signature(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    let app = caller(ctx).await?;
+    let payload = document_bytes("csaf/cve-2023-33201.json").await?;
+
+    // an invalid signature is rejected
+    let request = TestRequest::post()
+        .uri("/api/v3/advisory")
+        .insert_header(("x-signature", "c2lnbmF0dXJl"))
+        .insert_header(("x-signing-certificate", "Y2VydGlmaWNhdGU="))
+        .set_payload(payload.clone())
+        .to_request();
+    let response = app.call_service(request).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // the signer can't be provided as a plain label
+
+    let request = TestRequest::post()
+        .uri("/api/v3/advisory?labels.sigstore/signer=someone@example.com")
+        .set_payload(payload)
+        .to_request();
+    let result: IngestResult = app.call_and_read_body_json(request).await;
+
+    let request = TestRequest::get()
+        .uri(&format!("/api/v3/advisory/urn:uuid:{}", result.id))
+        .to_request();
+    let result: AdvisoryDetails = app.call_and_read_body_json(request).await;
+    assert_eq!(result.head.labels, Labels::new().add("type", "csaf"));
+
+    Ok(())
+}
+
 const DOC: &str = "csaf/cve-2023-33201.json";
 
 /// Test downloading a document by its SHA256 digest
@@ -591,6 +633,47 @@ async fn download_advisory_by_id(ctx: &TrustifyContext) -> Result<(), anyhow::Er
     Ok(())
 }
 
+/// Test downloading a range of a document
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn download_advisory_range(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    let doc = document_bytes(DOC).await?;
+    let app = caller(ctx).await?;
+    let result = ctx.ingest_document(DOC).await?;
+    let uri = format!("/api/v3/advisory/urn:uuid:{}/download", result.id);
+
+    let request = TestRequest::get()
+        .uri(&uri)
+        .insert_header(("Range", "bytes=10-19"))
+        .to_request();
+    let response = app.call_service(request).await;
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(
+        response.headers().get("Content-Range").unwrap(),
+        &format!("bytes 10-19/{}", doc.len())
+    );
+    let body = actix_web::test::read_body(response).await;
+    assert_eq!(body, doc.slice(10..20));
+
+    // the last bytes
+    let request = TestRequest::get()
+        .uri(&uri)
+        .insert_header(("Range", "bytes=-5"))
+        .to_request();
+    let body = app.call_and_read_body(request).await;
+    assert_eq!(body, doc.slice(doc.len() - 5..));
+
+    // beyond the end
+    let request = TestRequest::get()
+        .uri(&uri)
+        .insert_header(("Range", format!("bytes={}-", doc.len())))
+        .to_request();
+    let response = app.call_service(request).await;
+    assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+
+    Ok(())
+}
+
 /// Test updating labels
 #[test_context(TrustifyContext)]
 #[test(actix_web::test)]
@@ -861,3 +944,156 @@ async fn list_advisories_limit_exceeded(ctx: &TrustifyContext) -> Result<(), any
 
     Ok(())
 }
+
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn export_csaf(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    let app = caller(ctx).await?;
+
+    let id = ctx.ingest_document("osv/RUSTSEC-2021-0079.json").await?.id;
+
+    let request = TestRequest::get()
+        .uri(&format!("/api/v3/advisory/urn:uuid:{id}/csaf"))
+        .to_request();
+    let response: Value = app.call_and_read_body_json(request).await;
+    log::debug!("{response:#?}");
+
+    assert!(response.contains_subset(json!({
+        "document": {
+            "category": "csaf_security_advisory",
+            "csaf_version": "2.0",
+        },
+    })));
+
+    let products = response["product_tree"]["full_product_names"]
+        .as_array()
+        .expect("must have products");
+    let hyper = products
+        .iter()
+        .find(|product| {
+            product["name"]
+                .as_str()
+                .is_some_and(|name| name.starts_with("pkg:cargo/hyper "))
+        })
+        .expect("must contain hyper");
+
+    let vulnerabilities = response["vulnerabilities"]
+        .as_array()
+        .expect("must have vulnerabilities");
+    assert!(!vulnerabilities.is_empty());
+    assert!(vulnerabilities.iter().any(|vulnerability| {
+        vulnerability["product_status"]["known_affected"]
+            .as_array()
+            .is_some_and(|ids| ids.contains(&hyper["product_id"]))
+    }));
+
+    // unknown advisory
+
+    let request = TestRequest::get()
+        .uri("/api/v3/advisory/urn:uuid:00000000-0000-0000-0000-000000000000/csaf")
+        .to_request();
+    let response = app.call_service(request).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn diff_revisions(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    let app = caller(ctx).await?;
+
+    let from = ctx.ingest_document("cve/CVE-2024-26308.json").await?.id;
+    let to = ctx
+        .ingest_document("cve/CVE-2024-26308-updated.json")
+        .await?
+        .id;
+
+    // both are revisions of the same advisory
+
+    let request = TestRequest::get()
+        .uri(&format!("/api/v3/advisory/urn:uuid:{to}/revisions"))
+        .to_request();
+    let response: Value = app.call_and_read_body_json(request).await;
+    log::debug!("{response:#?}");
+
+    assert_eq!(response.as_array().map(Vec::len), Some(2));
+    assert_eq!(response[0]["uuid"], json!(format!("urn:uuid:{from}")));
+    assert_eq!(response[1]["uuid"], json!(format!("urn:uuid:{to}")));
+
+    // compare them
+
+    let request = TestRequest::get()
+        .uri(&format!(
+            "/api/v3/advisory/urn:uuid:{from}/diff/urn:uuid:{to}"
+        ))
+        .to_request();
+    let response: Value = app.call_and_read_body_json(request).await;
+    log::debug!("{response:#?}");
+
+    assert!(response.contains_subset(json!({
+        "vulnerabilities": [{
+            "identifier": "CVE-2024-26308",
+            "change": "modified",
+            "added_scores": [{
+                "type": "3.1",
+                "vector": "CVSS:3.1/AV:L/AC:L/PR:N/UI:R/S:U/C:N/I:N/A:H",
+            }],
+        }],
+    })));
+    let vulnerability = &response["vulnerabilities"][0];
+    assert!(vulnerability["description"].is_object());
+    assert_eq!(vulnerability["removed_scores"], Value::Null);
+    assert_eq!(vulnerability["added_statuses"], Value::Null);
+
+    // no differences with itself
+
+    let request = TestRequest::get()
+        .uri(&format!(
+            "/api/v3/advisory/urn:uuid:{to}/diff/urn:uuid:{to}"
+        ))
+        .to_request();
+    let response: Value = app.call_and_read_body_json(request).await;
+    assert_eq!(response["vulnerabilities"], json!([]));
+
+    Ok(())
+}
+
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn advisory_lifecycle(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    let app = caller(ctx).await?;
+
+    let csaf = ctx.ingest_document("csaf/cve-2023-33201.json").await?.id;
+    let rejected = ctx.ingest_document("cve/CVE-2024-25704.json").await?.id;
+
+    let uri = format!("/api/v3/advisory/urn:uuid:{csaf}");
+    let request = TestRequest::get().uri(&uri).to_request();
+    let response: Value = app.call_and_read_body_json(request).await;
+
+    assert_eq!(response["lifecycle"], "current");
+    assert_eq!(
+        response["revision_history"],
+        json!([
+            {
+                "number": "1",
+                "date": "2023-06-16T00:00:00Z",
+                "summary": "Initial version",
+            },
+            {
+                "number": "2",
+                "date": "2023-10-05T20:23:30Z",
+                "summary": "Current version",
+            },
+        ])
+    );
+
+    let uri = format!("/api/v3/advisory/urn:uuid:{rejected}");
+    let request = TestRequest::get().uri(&uri).to_request();
+    let response: Value = app.call_and_read_body_json(request).await;
+
+    assert_eq!(response["lifecycle"], "withdrawn");
+    assert_eq!(response["revision_history"], json!([]));
+
+    Ok(())
+}
@@ -0,0 +1,145 @@
+//! A subset of the CSAF 2.0 format, as produced when exporting an advisory.
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+pub const CSAF_VERSION: &str = "2.0";
+pub const CATEGORY_SECURITY_ADVISORY: &str = "csaf_security_advisory";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct CsafDocument {
+    pub document: CsafDocumentMetadata,
+    pub product_tree: CsafProductTree,
+    pub vulnerabilities: Vec<CsafVulnerability>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct CsafDocumentMetadata {
+    pub category: String,
+    pub csaf_version: String,
+    pub title: String,
+    pub publisher: CsafPublisher,
+    pub tracking: CsafTracking,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct CsafPublisher {
+    pub category: String,
+    pub name: String,
+    pub namespace: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct CsafTracking {
+    pub id: String,
+    pub status: String,
+    pub version: String,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String)]
+    pub initial_release_date: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String)]
+    pub current_release_date: OffsetDateTime,
+    pub revision_history: Vec<CsafRevision>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct CsafRevision {
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String)]
+    pub date: OffsetDateTime,
+    pub number: String,
+    pub summary: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct CsafNote {
+    pub category: String,
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, ToSchema)]
+pub struct CsafProductTree {
+    pub full_product_names: Vec<CsafProduct>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct CsafProduct {
+    pub name: String,
+    pub product_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product_identification_helper: Option<CsafProductIdentificationHelper>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct CsafProductIdentificationHelper {
+    pub purl: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct CsafVulnerability {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cve: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ids: Vec<CsafVulnerabilityId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub notes: Vec<CsafNote>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "time::serde::rfc3339::option"
+    )]
+    #[schema(value_type = Option<String>)]
+    pub discovery_date: Option<OffsetDateTime>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "time::serde::rfc3339::option"
+    )]
+    #[schema(value_type = Option<String>)]
+    pub release_date: Option<OffsetDateTime>,
+    pub product_status: CsafProductStatus,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scores: Vec<CsafScore>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct CsafVulnerabilityId {
+    pub system_name: String,
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, ToSchema)]
+pub struct CsafProductStatus {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub known_affected: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub known_not_affected: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fixed: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub under_investigation: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recommended: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct CsafScore {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cvss_v2: Option<CsafCvss>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cvss_v3: Option<CsafCvss>,
+    pub products: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CsafCvss {
+    pub version: String,
+    pub vector_string: String,
+    pub base_score: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_severity: Option<String>,
+}
@@ -2,10 +2,16 @@ pub mod advisory_vulnerability;
 
 use crate::advisory::service::AdvisoryCatcher;
 use crate::source_document::model::SourceDocument;
-use crate::{Error, advisory::model::AdvisoryHead};
+use crate::{
+    Error,
+    advisory::model::{AdvisoryHead, AdvisoryLifecycle},
+};
 use advisory_vulnerability::AdvisoryVulnerabilitySummary;
-use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QuerySelect};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, EntityTrait, ModelTrait, QueryFilter, QueryOrder, QuerySelect,
+};
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
 use trustify_common::memo::Memo;
 use trustify_entity::{self as entity};
 use utoipa::ToSchema;
@@ -20,6 +26,33 @@ pub struct AdvisoryDetails {
 
     /// Vulnerabilities addressed within this advisory.
     pub vulnerabilities: Vec<AdvisoryVulnerabilitySummary>,
+
+    /// The lifecycle state of this advisory.
+    pub lifecycle: AdvisoryLifecycle,
+
+    /// The revision history of this advisory, as stated by the document, oldest first.
+    pub revision_history: Vec<RevisionHistoryEntry>,
+}
+
+/// An entry of the revision history of an advisory.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema, PartialEq, Eq)]
+pub struct RevisionHistoryEntry {
+    /// The version of the document this revision created.
+    pub number: String,
+    /// The date (in RFC3339 format) of the revision.
+    #[serde(with = "time::serde::rfc3339")]
+    pub date: OffsetDateTime,
+    pub summary: String,
+}
+
+impl From<entity::advisory_revision::Model> for RevisionHistoryEntry {
+    fn from(value: entity::advisory_revision::Model) -> Self {
+        Self {
+            number: value.number,
+            date: value.date,
+            summary: value.summary,
+        }
+    }
 }
 
 impl AdvisoryDetails {
@@ -41,6 +74,16 @@ impl AdvisoryDetails {
             AdvisoryVulnerabilitySummary::from_entities(&advisory.advisory, &vulnerabilities, tx)
                 .await?;
 
+        let revision_history = advisory
+            .advisory
+            .find_related(entity::advisory_revision::Entity)
+            .order_by_asc(entity::advisory_revision::Column::Date)
+            .all(tx)
+            .await?
+            .into_iter()
+            .map(RevisionHistoryEntry::from)
+            .collect();
+
         Ok(AdvisoryDetails {
             head: AdvisoryHead::from_advisory(
                 &advisory.advisory,
@@ -50,6 +93,8 @@ impl AdvisoryDetails {
             .await?,
             source_document: SourceDocument::from_entity(&advisory.source_document),
             vulnerabilities,
+            lifecycle: AdvisoryLifecycle::from_entity(&advisory.advisory),
+            revision_history,
         })
     }
 }
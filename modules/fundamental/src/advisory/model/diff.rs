@@ -0,0 +1,82 @@
+use crate::{advisory::model::AdvisoryHead, common::model::ScoredVector};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// The differences between two revisions of an advisory
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct AdvisoryDiff {
+    /// The revision compared from
+    pub from: AdvisoryHead,
+    /// The revision compared to
+    pub to: AdvisoryHead,
+
+    /// A change of the advisory title
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<TextChange>,
+
+    /// Vulnerabilities which got added, removed, or changed
+    pub vulnerabilities: Vec<VulnerabilityDiff>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Change {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// A changed text, with its old and new value
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct TextChange {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+impl TextChange {
+    /// Create a change, if the values differ.
+    pub fn new(from: Option<&str>, to: Option<&str>) -> Option<Self> {
+        (from != to).then(|| Self {
+            from: from.map(ToString::to_string),
+            to: to.map(ToString::to_string),
+        })
+    }
+}
+
+/// The differences of a vulnerability, as covered by the two revisions
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct VulnerabilityDiff {
+    /// The vulnerability identifier
+    pub identifier: String,
+    pub change: Change,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<TextChange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<TextChange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<TextChange>,
+
+    /// Scores only present in the newer revision
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added_scores: Vec<ScoredVector>,
+    /// Scores only present in the older revision
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed_scores: Vec<ScoredVector>,
+
+    /// Statuses only present in the newer revision
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added_statuses: Vec<ProductStatus>,
+    /// Statuses only present in the older revision
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed_statuses: Vec<ProductStatus>,
+}
+
+/// The status of a package or product
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, ToSchema)]
+pub struct ProductStatus {
+    /// The status, like `affected` or `fixed`
+    pub status: String,
+    /// The package or product, including its version range
+    pub product: String,
+}
@@ -1,3 +1,6 @@
+pub mod csaf;
+pub mod diff;
+
 mod details;
 mod summary;
 
@@ -54,6 +57,30 @@ pub struct AdvisoryHead {
     pub labels: Labels,
 }
 
+/// The lifecycle state of an advisory.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, ToSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AdvisoryLifecycle {
+    /// The most recent version of the advisory.
+    Current,
+    /// Superseded by a more recent version of the advisory.
+    Superseded,
+    /// Withdrawn by its issuer, like a rejected CVE or a withdrawn OSV entry.
+    Withdrawn,
+}
+
+impl AdvisoryLifecycle {
+    pub fn from_entity(advisory: &advisory::Model) -> Self {
+        if advisory.withdrawn.is_some() {
+            Self::Withdrawn
+        } else if advisory.deprecated {
+            Self::Superseded
+        } else {
+            Self::Current
+        }
+    }
+}
+
 impl AdvisoryHead {
     #[instrument(
         skip_all,
@@ -0,0 +1,539 @@
+use super::{AdvisoryCatcher, AdvisoryService};
+use crate::{
+    Error,
+    advisory::model::csaf::{
+        CATEGORY_SECURITY_ADVISORY, CSAF_VERSION, CsafCvss, CsafDocument, CsafDocumentMetadata,
+        CsafNote, CsafProduct, CsafProductIdentificationHelper, CsafProductStatus, CsafProductTree,
+        CsafPublisher, CsafRevision, CsafScore, CsafTracking, CsafVulnerability,
+        CsafVulnerabilityId,
+    },
+};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DbBackend, EntityTrait, FromQueryResult, QueryFilter, QueryOrder,
+    Statement, prelude::Uuid,
+};
+use std::collections::{BTreeMap, BTreeSet};
+use time::OffsetDateTime;
+use tracing::instrument;
+use trustify_common::{id::Id, purl::Purl};
+use trustify_entity::{
+    advisory_vulnerability, advisory_vulnerability_score, advisory_vulnerability_score::ScoreType,
+};
+
+/// The status of a package or product, regarding a vulnerability of the advisory
+#[derive(Debug, FromQueryResult)]
+pub(super) struct StatusRow {
+    pub(super) vulnerability_id: String,
+    pub(super) status: String,
+    purl_type: Option<String>,
+    purl_namespace: Option<String>,
+    purl_name: Option<String>,
+    product: Option<String>,
+    version_scheme_id: String,
+    low_version: Option<String>,
+    low_inclusive: Option<bool>,
+    high_version: Option<String>,
+    high_inclusive: Option<bool>,
+}
+
+impl StatusRow {
+    /// The base PURL of the package, if the status is about a package.
+    fn purl(&self) -> Option<Purl> {
+        Some(Purl {
+            ty: self.purl_type.clone()?,
+            namespace: self.purl_namespace.clone(),
+            name: self.purl_name.clone()?,
+            version: None,
+            qualifiers: Default::default(),
+        })
+    }
+
+    /// A name for the package or product and its version range.
+    pub(super) fn name(&self) -> Option<String> {
+        let vers = vers(self);
+        match (self.purl(), &self.product) {
+            (Some(purl), _) => Some(format!("{purl} {vers}")),
+            (None, Some(product)) => Some(format!("{product} {vers}")),
+            (None, None) => None,
+        }
+    }
+}
+
+impl AdvisoryService {
+    /// Export an advisory as a CSAF 2.0 document, built from the stored information.
+    ///
+    /// This works for all advisories, independent of the format they were ingested in. Products
+    /// are created from the package and product statuses of the advisory.
+    #[instrument(skip(self, connection), err(level=tracing::Level::INFO))]
+    pub async fn export_csaf<C: ConnectionTrait>(
+        &self,
+        id: Id,
+        connection: &C,
+    ) -> Result<Option<CsafDocument>, Error> {
+        let Some(advisory) = self.fetch_catcher(id, connection).await? else {
+            return Ok(None);
+        };
+
+        let advisory_id = advisory.advisory.id;
+
+        let vulnerabilities = advisory_vulnerability::Entity::find()
+            .filter(advisory_vulnerability::Column::AdvisoryId.eq(advisory_id))
+            .order_by_asc(advisory_vulnerability::Column::VulnerabilityId)
+            .all(connection)
+            .await?;
+
+        let scores = advisory_vulnerability_score::Entity::find()
+            .filter(advisory_vulnerability_score::Column::AdvisoryId.eq(advisory_id))
+            .all(connection)
+            .await?;
+
+        let statuses = fetch_statuses(advisory_id, connection).await?;
+
+        Ok(Some(build(&advisory, vulnerabilities, scores, statuses)))
+    }
+}
+
+/// Fetch the package and product statuses of an advisory.
+pub(super) async fn fetch_statuses<C: ConnectionTrait>(
+    advisory_id: Uuid,
+    connection: &C,
+) -> Result<Vec<StatusRow>, Error> {
+    Ok(StatusRow::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"
+SELECT
+    ps.vulnerability_id, s.slug AS status,
+    bp.type AS purl_type, bp.namespace AS purl_namespace, bp.name AS purl_name,
+    NULL::text AS product,
+    vr.version_scheme_id, vr.low_version, vr.low_inclusive, vr.high_version, vr.high_inclusive
+FROM purl_status ps
+    JOIN status s ON s.id = ps.status_id
+    JOIN base_purl bp ON bp.id = ps.base_purl_id
+    JOIN version_range vr ON vr.id = ps.version_range_id
+WHERE ps.advisory_id = $1
+UNION ALL
+SELECT
+    pst.vulnerability_id, s.slug AS status,
+    NULL::text, NULL::text, NULL::text,
+    p.name || coalesce(' / ' || pst.package, '') AS product,
+    vr.version_scheme_id, vr.low_version, vr.low_inclusive, vr.high_version, vr.high_inclusive
+FROM product_status pst
+    JOIN status s ON s.id = pst.status_id
+    JOIN product_version_range pvr ON pvr.id = pst.product_version_range_id
+    JOIN product p ON p.id = pvr.product_id
+    JOIN version_range vr ON vr.id = pvr.version_range_id
+WHERE pst.advisory_id = $1
+"#,
+        [advisory_id.into()],
+    ))
+    .all(connection)
+    .await?)
+}
+
+fn build(
+    advisory: &AdvisoryCatcher,
+    vulnerabilities: Vec<advisory_vulnerability::Model>,
+    scores: Vec<advisory_vulnerability_score::Model>,
+    statuses: Vec<StatusRow>,
+) -> CsafDocument {
+    let mut products = Products::default();
+
+    // product statuses, by vulnerability
+
+    let mut product_statuses = BTreeMap::<String, Statuses>::new();
+    for row in statuses {
+        let Some(product_id) = products.id(&row) else {
+            continue;
+        };
+        product_statuses
+            .entry(row.vulnerability_id)
+            .or_default()
+            .add(&row.status, product_id);
+    }
+
+    // scores, by vulnerability
+
+    let mut vulnerability_scores = BTreeMap::<String, Vec<_>>::new();
+    for score in scores {
+        vulnerability_scores
+            .entry(score.vulnerability_id.clone())
+            .or_default()
+            .push(score);
+    }
+
+    let vulnerabilities = vulnerabilities
+        .into_iter()
+        .map(|vulnerability| {
+            let statuses = product_statuses
+                .remove(&vulnerability.vulnerability_id)
+                .unwrap_or_default();
+            let scores = vulnerability_scores
+                .remove(&vulnerability.vulnerability_id)
+                .unwrap_or_default();
+            self::vulnerability(vulnerability, statuses, scores)
+        })
+        .collect();
+
+    let advisory_model = &advisory.advisory;
+
+    let now = OffsetDateTime::now_utc();
+    let initial = advisory_model
+        .published
+        .or(advisory_model.modified)
+        .unwrap_or(now);
+    let current = advisory_model.modified.unwrap_or(initial);
+    let version = advisory_model
+        .version
+        .clone()
+        .filter(|version| valid_version(version))
+        .unwrap_or_else(|| "1".to_string());
+
+    let publisher = match &advisory.issuer {
+        Some(issuer) => CsafPublisher {
+            category: "vendor".into(),
+            name: issuer.name.clone(),
+            namespace: issuer
+                .website
+                .clone()
+                .unwrap_or_else(|| "urn:unknown".into()),
+        },
+        None => CsafPublisher {
+            category: "other".into(),
+            name: "Unknown".into(),
+            namespace: "urn:unknown".into(),
+        },
+    };
+
+    CsafDocument {
+        document: CsafDocumentMetadata {
+            category: CATEGORY_SECURITY_ADVISORY.into(),
+            csaf_version: CSAF_VERSION.into(),
+            title: advisory_model
+                .title
+                .clone()
+                .unwrap_or_else(|| advisory_model.identifier.clone()),
+            publisher,
+            tracking: CsafTracking {
+                id: advisory_model.document_id.clone(),
+                status: "final".into(),
+                version: version.clone(),
+                initial_release_date: initial,
+                current_release_date: current,
+                revision_history: vec![CsafRevision {
+                    date: current,
+                    number: version,
+                    summary: format!("Exported from {}", advisory_model.identifier),
+                }],
+            },
+        },
+        product_tree: products.into_tree(),
+        vulnerabilities,
+    }
+}
+
+fn vulnerability(
+    vulnerability: advisory_vulnerability::Model,
+    statuses: Statuses,
+    scores: Vec<advisory_vulnerability_score::Model>,
+) -> CsafVulnerability {
+    let id = vulnerability.vulnerability_id;
+
+    let (cve, ids) = match id.starts_with("CVE-") {
+        true => (Some(id.clone()), vec![]),
+        false => (
+            None,
+            vec![CsafVulnerabilityId {
+                system_name: id
+                    .split_once('-')
+                    .map(|(prefix, _)| prefix)
+                    .unwrap_or(&id)
+                    .into(),
+                text: id.clone(),
+            }],
+        ),
+    };
+
+    // a security advisory requires notes for each vulnerability
+
+    let mut notes = vec![];
+    if let Some(summary) = vulnerability.summary {
+        notes.push(CsafNote {
+            category: "summary".into(),
+            text: summary,
+        });
+    }
+    if let Some(description) = vulnerability.description {
+        notes.push(CsafNote {
+            category: "description".into(),
+            text: description,
+        });
+    }
+    if notes.is_empty() {
+        notes.push(CsafNote {
+            category: "general".into(),
+            text: vulnerability.title.clone().unwrap_or_else(|| id.clone()),
+        });
+    }
+
+    // scores apply to the affected products, or all products if there are none
+
+    let products = match statuses.affected().is_empty() {
+        true => statuses.all(),
+        false => statuses.affected(),
+    };
+
+    let scores = match products.is_empty() {
+        true => vec![],
+        false => scores
+            .into_iter()
+            .filter_map(|score| self::score(score, products.clone()))
+            .collect(),
+    };
+
+    CsafVulnerability {
+        cve,
+        ids,
+        title: vulnerability.title,
+        notes,
+        discovery_date: vulnerability.discovery_date,
+        release_date: vulnerability.release_date,
+        product_status: statuses.into_product_status(),
+        scores,
+    }
+}
+
+/// Convert a score, CSAF 2.0 only supports CVSS v2 and v3.
+fn score(score: advisory_vulnerability_score::Model, products: Vec<String>) -> Option<CsafScore> {
+    let cvss = |version: &str, severity: Option<String>| CsafCvss {
+        version: version.into(),
+        vector_string: score.vector.clone(),
+        base_score: score.score,
+        base_severity: severity,
+    };
+
+    let severity = Some(score.severity.to_string().to_uppercase());
+
+    match score.r#type {
+        ScoreType::V2_0 => Some(CsafScore {
+            cvss_v2: Some(cvss("2.0", None)),
+            cvss_v3: None,
+            products,
+        }),
+        ScoreType::V3_0 => Some(CsafScore {
+            cvss_v2: None,
+            cvss_v3: Some(cvss("3.0", severity)),
+            products,
+        }),
+        ScoreType::V3_1 => Some(CsafScore {
+            cvss_v2: None,
+            cvss_v3: Some(cvss("3.1", severity)),
+            products,
+        }),
+        ScoreType::V4_0 => None,
+    }
+}
+
+/// CSAF requires either an integer or a semantic version.
+fn valid_version(version: &str) -> bool {
+    let parts = version.split('.').collect::<Vec<_>>();
+    matches!(parts.len(), 1 | 3)
+        && parts
+            .iter()
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Products of the document, identified by their name.
+#[derive(Default)]
+struct Products {
+    products: BTreeMap<String, (String, Option<String>)>,
+}
+
+impl Products {
+    /// Get the ID of the product of a status, creating the product if necessary.
+    fn id(&mut self, row: &StatusRow) -> Option<String> {
+        let name = row.name()?;
+        // only a single version can be identified by a PURL
+        let purl = row
+            .purl()
+            .zip(exact_version(row))
+            .map(|(mut purl, version)| {
+                purl.version = Some(version.to_string());
+                purl.to_string()
+            });
+
+        let next = format!("CSAFPID-{}", self.products.len() + 1);
+        let (id, _) = self.products.entry(name).or_insert((next, purl));
+        Some(id.clone())
+    }
+
+    fn into_tree(self) -> CsafProductTree {
+        CsafProductTree {
+            full_product_names: self
+                .products
+                .into_iter()
+                .map(|(name, (product_id, purl))| CsafProduct {
+                    name,
+                    product_id,
+                    product_identification_helper: purl
+                        .map(|purl| CsafProductIdentificationHelper { purl }),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The single version of a range, if it is one.
+fn exact_version(row: &StatusRow) -> Option<&str> {
+    match (&row.low_version, &row.high_version) {
+        (Some(low), Some(high))
+            if low == high
+                && row.low_inclusive.unwrap_or(true)
+                && row.high_inclusive.unwrap_or(true) =>
+        {
+            Some(low)
+        }
+        _ => None,
+    }
+}
+
+/// Render a version range using the `vers` specification.
+fn vers(row: &StatusRow) -> String {
+    let scheme = &row.version_scheme_id;
+
+    if let Some(version) = exact_version(row) {
+        return format!("vers:{scheme}/{version}");
+    }
+
+    let mut constraints = vec![];
+    if let Some(low) = &row.low_version {
+        match row.low_inclusive.unwrap_or(true) {
+            true => constraints.push(format!(">={low}")),
+            false => constraints.push(format!(">{low}")),
+        }
+    }
+    if let Some(high) = &row.high_version {
+        match row.high_inclusive.unwrap_or(false) {
+            true => constraints.push(format!("<={high}")),
+            false => constraints.push(format!("<{high}")),
+        }
+    }
+
+    match constraints.is_empty() {
+        true => format!("vers:{scheme}/*"),
+        false => format!("vers:{scheme}/{}", constraints.join("|")),
+    }
+}
+
+/// The products of a vulnerability, by status
+#[derive(Default)]
+struct Statuses(BTreeMap<String, BTreeSet<String>>);
+
+impl Statuses {
+    fn add(&mut self, status: &str, product_id: String) {
+        self.0
+            .entry(status.to_string())
+            .or_default()
+            .insert(product_id);
+    }
+
+    fn affected(&self) -> Vec<String> {
+        self.0
+            .get("affected")
+            .map(|ids| ids.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn all(&self) -> Vec<String> {
+        self.0
+            .values()
+            .flatten()
+            .cloned()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    fn into_product_status(self) -> CsafProductStatus {
+        let mut result = CsafProductStatus::default();
+        for (status, ids) in self.0 {
+            let target = match status.as_str() {
+                "affected" => &mut result.known_affected,
+                "not_affected" => &mut result.known_not_affected,
+                "fixed" => &mut result.fixed,
+                "under_investigation" => &mut result.under_investigation,
+                "recommended" => &mut result.recommended,
+                other => {
+                    log::debug!("Skipping unsupported status: {other}");
+                    continue;
+                }
+            };
+            target.extend(ids);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    fn row(low: Option<&str>, high: Option<&str>, high_inclusive: Option<bool>) -> StatusRow {
+        StatusRow {
+            vulnerability_id: "CVE-2024-0001".into(),
+            status: "affected".into(),
+            purl_type: Some("maven".into()),
+            purl_namespace: Some("io.quarkus".into()),
+            purl_name: Some("quarkus-core".into()),
+            product: None,
+            version_scheme_id: "maven".into(),
+            low_version: low.map(ToString::to_string),
+            low_inclusive: Some(true),
+            high_version: high.map(ToString::to_string),
+            high_inclusive,
+        }
+    }
+
+    #[rstest]
+    #[case(row(Some("1.0"), Some("1.0"), Some(true)), "vers:maven/1.0")]
+    #[case(row(Some("1.0"), Some("2.0"), Some(false)), "vers:maven/>=1.0|<2.0")]
+    #[case(row(None, Some("2.0"), Some(true)), "vers:maven/<=2.0")]
+    #[case(row(None, None, None), "vers:maven/*")]
+    fn vers_ranges(#[case] row: StatusRow, #[case] expected: &str) {
+        assert_eq!(vers(&row), expected);
+    }
+
+    #[test]
+    fn products() {
+        let mut products = Products::default();
+        let exact = row(Some("1.0"), Some("1.0"), Some(true));
+        let range = row(Some("1.0"), Some("2.0"), Some(false));
+
+        assert_eq!(products.id(&exact).as_deref(), Some("CSAFPID-1"));
+        assert_eq!(products.id(&range).as_deref(), Some("CSAFPID-2"));
+        assert_eq!(products.id(&exact).as_deref(), Some("CSAFPID-1"));
+
+        let tree = products.into_tree();
+        assert_eq!(tree.full_product_names.len(), 2);
+        assert_eq!(
+            tree.full_product_names[0]
+                .product_identification_helper
+                .as_ref()
+                .map(|helper| helper.purl.as_str()),
+            Some("pkg:maven/io.quarkus/quarkus-core@1.0")
+        );
+        assert_eq!(
+            tree.full_product_names[1].product_identification_helper,
+            None
+        );
+    }
+
+    #[rstest]
+    #[case("1", true)]
+    #[case("1.2.3", true)]
+    #[case("1.2", false)]
+    #[case("2024-01-01", false)]
+    fn versions(#[case] version: &str, #[case] expected: bool) {
+        assert_eq!(valid_version(version), expected);
+    }
+}
@@ -0,0 +1,320 @@
+use super::{AdvisoryCatcher, AdvisoryService, csaf::fetch_statuses};
+use crate::{
+    Error,
+    advisory::model::{
+        AdvisoryHead,
+        diff::{AdvisoryDiff, Change, ProductStatus, TextChange, VulnerabilityDiff},
+    },
+    common::model::ScoredVector,
+};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect, RelationTrait,
+    prelude::Uuid,
+};
+use sea_query::JoinType;
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::instrument;
+use trustify_common::{db::multi_model::SelectIntoMultiModel, id::Id, memo::Memo};
+use trustify_entity::{
+    advisory, advisory_vulnerability, advisory_vulnerability_score, source_document,
+};
+
+/// The information of one revision, relevant for comparing it
+#[derive(Default)]
+struct Revision {
+    vulnerabilities: BTreeMap<String, advisory_vulnerability::Model>,
+    scores: BTreeMap<String, Vec<ScoredVector>>,
+    statuses: BTreeMap<String, BTreeSet<ProductStatus>>,
+}
+
+impl AdvisoryService {
+    /// List all revisions of an advisory, which are all advisories sharing its identifier.
+    ///
+    /// When an advisory gets ingested again with a different digest, the previous revisions are
+    /// kept and marked as deprecated.
+    #[instrument(skip(self, connection), err(level=tracing::Level::INFO))]
+    pub async fn fetch_revisions<C: ConnectionTrait>(
+        &self,
+        id: Id,
+        connection: &C,
+    ) -> Result<Option<Vec<AdvisoryHead>>, Error> {
+        let Some(advisory) = self.fetch_catcher(id, connection).await? else {
+            return Ok(None);
+        };
+
+        let revisions = advisory::Entity::find()
+            .left_join(source_document::Entity)
+            .join(JoinType::LeftJoin, advisory::Relation::Issuer.def())
+            .filter(advisory::Column::Identifier.eq(&advisory.advisory.identifier))
+            .order_by_asc(advisory::Column::Modified)
+            .order_by_asc(advisory::Column::Published)
+            .order_by_asc(source_document::Column::Ingested)
+            .try_into_multi_model::<AdvisoryCatcher>()?
+            .all(connection)
+            .await?;
+
+        let mut result = Vec::with_capacity(revisions.len());
+        for revision in revisions {
+            result.push(
+                AdvisoryHead::from_advisory(
+                    &revision.advisory,
+                    Memo::Provided(revision.issuer),
+                    connection,
+                )
+                .await?,
+            );
+        }
+
+        Ok(Some(result))
+    }
+
+    /// Compare two revisions of an advisory.
+    ///
+    /// Returns `None` if one of the advisories could not be found.
+    #[instrument(skip(self, connection), err(level=tracing::Level::INFO))]
+    pub async fn diff_advisories<C: ConnectionTrait>(
+        &self,
+        from: Id,
+        to: Id,
+        connection: &C,
+    ) -> Result<Option<AdvisoryDiff>, Error> {
+        let Some(from) = self.fetch_catcher(from, connection).await? else {
+            return Ok(None);
+        };
+        let Some(to) = self.fetch_catcher(to, connection).await? else {
+            return Ok(None);
+        };
+
+        let from_revision = Revision::load(from.advisory.id, connection).await?;
+        let to_revision = Revision::load(to.advisory.id, connection).await?;
+
+        Ok(Some(AdvisoryDiff {
+            title: TextChange::new(from.advisory.title.as_deref(), to.advisory.title.as_deref()),
+            from: AdvisoryHead::from_advisory(
+                &from.advisory,
+                Memo::Provided(from.issuer),
+                connection,
+            )
+            .await?,
+            to: AdvisoryHead::from_advisory(&to.advisory, Memo::Provided(to.issuer), connection)
+                .await?,
+            vulnerabilities: diff(from_revision, to_revision),
+        }))
+    }
+}
+
+impl Revision {
+    async fn load<C: ConnectionTrait>(advisory_id: Uuid, connection: &C) -> Result<Self, Error> {
+        let mut result = Self::default();
+
+        for vulnerability in advisory_vulnerability::Entity::find()
+            .filter(advisory_vulnerability::Column::AdvisoryId.eq(advisory_id))
+            .all(connection)
+            .await?
+        {
+            result
+                .vulnerabilities
+                .insert(vulnerability.vulnerability_id.clone(), vulnerability);
+        }
+
+        for score in advisory_vulnerability_score::Entity::find()
+            .filter(advisory_vulnerability_score::Column::AdvisoryId.eq(advisory_id))
+            .all(connection)
+            .await?
+        {
+            result
+                .scores
+                .entry(score.vulnerability_id.clone())
+                .or_default()
+                .push(score.into());
+        }
+
+        for status in fetch_statuses(advisory_id, connection).await? {
+            let Some(product) = status.name() else {
+                continue;
+            };
+            result
+                .statuses
+                .entry(status.vulnerability_id)
+                .or_default()
+                .insert(ProductStatus {
+                    status: status.status,
+                    product,
+                });
+        }
+
+        Ok(result)
+    }
+}
+
+/// Compare the vulnerabilities of two revisions, only reporting those which changed.
+fn diff(mut from: Revision, mut to: Revision) -> Vec<VulnerabilityDiff> {
+    let identifiers = from
+        .vulnerabilities
+        .keys()
+        .chain(to.vulnerabilities.keys())
+        .cloned()
+        .collect::<BTreeSet<_>>();
+
+    let mut result = vec![];
+
+    for identifier in identifiers {
+        let from_vulnerability = from.vulnerabilities.remove(&identifier);
+        let to_vulnerability = to.vulnerabilities.remove(&identifier);
+
+        let change = match (&from_vulnerability, &to_vulnerability) {
+            (None, Some(_)) => Change::Added,
+            (Some(_), None) => Change::Removed,
+            _ => Change::Modified,
+        };
+
+        let text = |f: fn(&advisory_vulnerability::Model) -> Option<&str>| {
+            TextChange::new(
+                from_vulnerability.as_ref().and_then(f),
+                to_vulnerability.as_ref().and_then(f),
+            )
+        };
+
+        let from_scores = from.scores.remove(&identifier).unwrap_or_default();
+        let to_scores = to.scores.remove(&identifier).unwrap_or_default();
+
+        let from_statuses = from.statuses.remove(&identifier).unwrap_or_default();
+        let to_statuses = to.statuses.remove(&identifier).unwrap_or_default();
+
+        let diff = VulnerabilityDiff {
+            identifier,
+            change,
+            title: text(|v| v.title.as_deref()),
+            summary: text(|v| v.summary.as_deref()),
+            description: text(|v| v.description.as_deref()),
+            added_scores: difference(&to_scores, &from_scores),
+            removed_scores: difference(&from_scores, &to_scores),
+            added_statuses: to_statuses.difference(&from_statuses).cloned().collect(),
+            removed_statuses: from_statuses.difference(&to_statuses).cloned().collect(),
+        };
+
+        if diff.change != Change::Modified || has_changes(&diff) {
+            result.push(diff);
+        }
+    }
+
+    result
+}
+
+/// The scores of `a`, which are not in `b`.
+fn difference(a: &[ScoredVector], b: &[ScoredVector]) -> Vec<ScoredVector> {
+    a.iter()
+        .filter(|score| !b.contains(score))
+        .cloned()
+        .collect()
+}
+
+fn has_changes(diff: &VulnerabilityDiff) -> bool {
+    diff.title.is_some()
+        || diff.summary.is_some()
+        || diff.description.is_some()
+        || !diff.added_scores.is_empty()
+        || !diff.removed_scores.is_empty()
+        || !diff.added_statuses.is_empty()
+        || !diff.removed_statuses.is_empty()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn vulnerability(id: &str, title: &str) -> advisory_vulnerability::Model {
+        advisory_vulnerability::Model {
+            advisory_id: Uuid::nil(),
+            vulnerability_id: id.into(),
+            title: Some(title.into()),
+            summary: None,
+            description: None,
+            reserved_date: None,
+            discovery_date: None,
+            release_date: None,
+            cwes: None,
+        }
+    }
+
+    fn status(status: &str, product: &str) -> ProductStatus {
+        ProductStatus {
+            status: status.into(),
+            product: product.into(),
+        }
+    }
+
+    fn revision(
+        vulnerabilities: impl IntoIterator<Item = advisory_vulnerability::Model>,
+        statuses: impl IntoIterator<Item = (&'static str, ProductStatus)>,
+    ) -> Revision {
+        let mut result = Revision::default();
+        for vulnerability in vulnerabilities {
+            result
+                .vulnerabilities
+                .insert(vulnerability.vulnerability_id.clone(), vulnerability);
+        }
+        for (id, status) in statuses {
+            result.statuses.entry(id.into()).or_default().insert(status);
+        }
+        result
+    }
+
+    #[test]
+    fn changes() {
+        let from = revision(
+            [
+                vulnerability("CVE-1", "one"),
+                vulnerability("CVE-2", "two"),
+                vulnerability("CVE-3", "three"),
+            ],
+            [
+                ("CVE-1", status("affected", "pkg:cargo/a vers:semver/<1.0")),
+                ("CVE-3", status("affected", "pkg:cargo/c vers:semver/*")),
+            ],
+        );
+        let to = revision(
+            [
+                vulnerability("CVE-1", "one"),
+                vulnerability("CVE-3", "three, updated"),
+                vulnerability("CVE-4", "four"),
+            ],
+            [
+                ("CVE-1", status("affected", "pkg:cargo/a vers:semver/<1.0")),
+                ("CVE-3", status("fixed", "pkg:cargo/c vers:semver/*")),
+            ],
+        );
+
+        let result = diff(from, to);
+
+        // "CVE-1" is unchanged
+        assert_eq!(
+            result
+                .iter()
+                .map(|diff| (diff.identifier.as_str(), diff.change))
+                .collect::<Vec<_>>(),
+            vec![
+                ("CVE-2", Change::Removed),
+                ("CVE-3", Change::Modified),
+                ("CVE-4", Change::Added),
+            ]
+        );
+
+        let modified = &result[1];
+        assert_eq!(
+            modified.title,
+            Some(TextChange {
+                from: Some("three".into()),
+                to: Some("three, updated".into()),
+            })
+        );
+        assert_eq!(
+            modified.added_statuses,
+            vec![status("fixed", "pkg:cargo/c vers:semver/*")]
+        );
+        assert_eq!(
+            modified.removed_statuses,
+            vec![status("affected", "pkg:cargo/c vers:semver/*")]
+        );
+    }
+}
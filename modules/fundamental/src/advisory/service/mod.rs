@@ -21,7 +21,7 @@ use trustify_common::{
     model::{PaginatedResults, Pagination},
 };
 use trustify_entity::{advisory, labels::Labels, organization, source_document};
-use trustify_module_ingestor::common::{Deprecation, DeprecationExt};
+use trustify_module_ingestor::common::{Deprecation, DeprecationExt, Withdrawal, WithdrawalExt};
 use uuid::Uuid;
 
 pub struct AdvisoryService {
@@ -40,10 +40,12 @@ impl AdvisoryService {
         search: Query,
         paginated: impl Pagination,
         deprecation: Deprecation,
+        withdrawal: Withdrawal,
         connection: &C,
     ) -> Result<PaginatedResults<AdvisorySummary>, Error> {
         let limiter = advisory::Entity::find()
             .with_deprecation(deprecation)
+            .with_withdrawal(withdrawal)
             .left_join(source_document::Entity)
             .join(JoinType::LeftJoin, advisory::Relation::Issuer.def())
             .filtering_with(
@@ -74,13 +76,7 @@ impl AdvisoryService {
         id: Id,
         connection: &C,
     ) -> Result<Option<AdvisoryDetails>, Error> {
-        let results = advisory::Entity::find()
-            .left_join(source_document::Entity)
-            .join(JoinType::LeftJoin, advisory::Relation::Issuer.def())
-            .try_filter(id)?
-            .try_into_multi_model::<AdvisoryCatcher>()?
-            .one(connection)
-            .await?;
+        let results = self.fetch_catcher(id, connection).await?;
 
         if let Some(catcher) = results {
             Ok(Some(
@@ -91,6 +87,21 @@ impl AdvisoryService {
         }
     }
 
+    /// fetch one advisory, including its issuer and source document
+    async fn fetch_catcher<C: ConnectionTrait>(
+        &self,
+        id: Id,
+        connection: &C,
+    ) -> Result<Option<AdvisoryCatcher>, Error> {
+        Ok(advisory::Entity::find()
+            .left_join(source_document::Entity)
+            .join(JoinType::LeftJoin, advisory::Relation::Issuer.def())
+            .try_filter(id)?
+            .try_into_multi_model::<AdvisoryCatcher>()?
+            .one(connection)
+            .await?)
+    }
+
     /// delete one advisory
     pub async fn delete_advisory<C: ConnectionTrait>(
         &self,
@@ -211,6 +222,9 @@ impl FromQueryResultMultiModel for AdvisoryCatcher {
     }
 }
 
+mod csaf;
+mod diff;
+
 #[cfg(test)]
 #[allow(deprecated)]
 pub mod test;
@@ -86,6 +86,7 @@ async fn all_advisories(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
                 ..Default::default()
             },
             Default::default(),
+            Default::default(),
             &ctx.db,
         )
         .await?;
@@ -163,7 +164,7 @@ async fn single_advisory(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
             })
         if sha256 == jenny256.to_string() && sha384 == jenny384.to_string() && sha512 == jenny512.to_string()));
 
-    let fetched = fetch.fetch_advisory(id, &ctx.db).await?;
+    let fetched = fetch.fetch_advisory(id.clone(), &ctx.db).await?;
     assert!(matches!(
             fetched,
             Some(AdvisoryDetails {
@@ -178,6 +179,14 @@ async fn single_advisory(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
             })
         if sha256 == jenny256.to_string() && sha384 == jenny384.to_string() && sha512 == jenny512.to_string()));
 
+    let fetched = fetch
+        .fetch_advisory(Id::blake3(&digests.blake3), &ctx.db)
+        .await?;
+    assert_eq!(
+        fetched.map(|advisory| advisory.head.uuid),
+        Some(id.try_as_uid().expect("must be a uuid"))
+    );
+
     Ok(())
 }
 
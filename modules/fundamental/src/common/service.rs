@@ -1,8 +1,13 @@
 use crate::{Error, common::LicenseRefMapping, source_document::model::SourceDocument};
-use sea_orm::{ConnectionTrait, DbBackend, FromQueryResult, PaginatorTrait, Statement};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseTransaction, DbBackend, EntityTrait, FromQueryResult,
+    PaginatorTrait, QueryFilter, QuerySelect, Statement, TransactionTrait,
+};
 use spdx_expression;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 use tracing::instrument;
+use trustify_entity::source_document;
+use trustify_module_ingestor::service::lock::try_lock_for_removal;
 use trustify_module_storage::service::{StorageBackend, StorageKey, dispatch::DispatchBackend};
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
@@ -65,12 +70,73 @@ fn escape(text: String) -> String {
     text.replace('%', "\\").replace('\\', "\\\\")
 }
 
-/// Delete the original raw json doc from storage. An appropriate
+/// Delete the original raw json doc from storage, unless it is still referenced. An appropriate
 /// message is returned in the event of an error, but it's up to the
 /// caller to either log the message or return failure to its caller.
-pub async fn delete_doc(doc: &SourceDocument, storage: impl DocumentDelete) -> Result<(), Error> {
-    let key = doc.try_into()?;
-    storage.delete(key).await
+pub async fn delete_doc<C: TransactionTrait>(
+    doc: &SourceDocument,
+    db: &C,
+    storage: impl DocumentDelete,
+) -> Result<(), Error> {
+    let key: StorageKey = doc.try_into()?;
+
+    let tx = db.begin().await?;
+    if lock_unreferenced([key.to_string()], &tx).await?.is_empty() {
+        log::info!("Keeping document, as it is still referenced: {key}");
+        return Ok(());
+    }
+
+    storage.delete(key).await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Filter the SHA256 digests of stored documents, keeping only those which are no longer referenced.
+///
+/// A document is stored only once, keyed by its digest, no matter how often it gets ingested. All
+/// ingested documents (advisories, SBOMs, attestations) reference it through their source
+/// document. So a document must only be removed from the storage once no source document
+/// references its digest anymore, e.g. because the same document was ingested again in the
+/// meantime.
+#[instrument(skip_all, err(level=tracing::Level::INFO))]
+pub async fn unreferenced<C: ConnectionTrait>(
+    digests: impl IntoIterator<Item = String>,
+    connection: &C,
+) -> Result<Vec<String>, Error> {
+    let digests: Vec<String> = digests.into_iter().collect();
+    if digests.is_empty() {
+        return Ok(digests);
+    }
+
+    let referenced: HashSet<String> = source_document::Entity::find()
+        .select_only()
+        .column(source_document::Column::Sha256)
+        .filter(source_document::Column::Sha256.is_in(digests.clone()))
+        .into_tuple()
+        .all(connection)
+        .await?
+        .into_iter()
+        .collect();
+
+    Ok(digests
+        .into_iter()
+        .filter(|digest| !referenced.contains(digest))
+        .collect())
+}
+
+/// Lock the stored documents of the SHA256 digests for removal, keeping only those which are no
+/// longer referenced.
+///
+/// Documents which are currently being ingested are kept, as they will be referenced. The locks
+/// are held until the end of the transaction, which must only end after removing the documents
+/// from the storage. See [`trustify_module_ingestor::service::lock`].
+pub async fn lock_unreferenced(
+    digests: impl IntoIterator<Item = String>,
+    tx: &DatabaseTransaction,
+) -> Result<Vec<String>, Error> {
+    let digests = try_lock_for_removal(digests.into_iter().collect(), tx).await?;
+    unreferenced(digests, tx).await
 }
 
 pub trait DocumentDelete {
@@ -120,11 +186,14 @@ pub fn extract_license_ref_mappings(
 mod test {
     use super::*;
     use anyhow::anyhow;
+    use test_context::test_context;
     use test_log::test;
     use trustify_module_storage::service::StorageKey;
+    use trustify_test_context::TrustifyContext;
 
+    #[test_context(TrustifyContext)]
     #[test(tokio::test)]
-    async fn delete_failure() -> Result<(), anyhow::Error> {
+    async fn delete_failure(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
         // Setup mock that simulates a delete error
         struct FailingDelete {}
         impl DocumentDelete for FailingDelete {
@@ -135,7 +204,7 @@ mod test {
 
         // Failing to delete an invalid doc from storage should log an error
         let doc = SourceDocument::default();
-        match delete_doc(&doc, FailingDelete {}).await {
+        match delete_doc(&doc, &ctx.db, FailingDelete {}).await {
             Ok(_) => panic!("expected error"),
             Err(e) => assert!(e.to_string().contains("Missing prefix")),
         };
@@ -147,11 +216,45 @@ mod test {
             ),
             ..Default::default()
         };
-        match delete_doc(&doc, FailingDelete {}).await {
+        match delete_doc(&doc, &ctx.db, FailingDelete {}).await {
             Ok(_) => panic!("expected error"),
             Err(e) => assert!(e.to_string().contains("Delete failed")),
         };
 
         Ok(())
     }
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn keep_referenced(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        const UNKNOWN: &str = "488c5d97daed3613746f0c246f4a3d1b26ea52ce43d6bdd33f4219f881a00c07";
+
+        ctx.ingest_document("ubi9-9.2-755.1697625012.json").await?;
+        let doc = source_document::Entity::find()
+            .one(&ctx.db)
+            .await?
+            .expect("must have a source document");
+
+        // the ingested document is referenced, the unknown one isn't
+        assert_eq!(
+            unreferenced([doc.sha256.clone(), UNKNOWN.to_string()], &ctx.db).await?,
+            vec![UNKNOWN.to_string()]
+        );
+
+        // deleting a referenced document from the storage must keep it
+        struct FailingDelete {}
+        impl DocumentDelete for FailingDelete {
+            async fn delete(&self, _key: StorageKey) -> Result<(), Error> {
+                Err(Error::Storage(anyhow!("Must not delete")))
+            }
+        }
+        delete_doc(
+            &SourceDocument::from_entity(&doc),
+            &ctx.db,
+            FailingDelete {},
+        )
+        .await?;
+
+        Ok(())
+    }
 }
@@ -2,7 +2,9 @@ use actix_web::web;
 use trustify_common::db::{self, pagination_cache::PaginationCache};
 use trustify_module_analysis::service::AnalysisService;
 use trustify_module_ingestor::graph::Graph;
-use trustify_module_ingestor::service::IngestorService;
+use trustify_module_ingestor::service::{
+    IngestorService, limits::Limits, loader::LoaderRegistry, quota::Quotas, signature::TrustRoot,
+};
 use trustify_module_storage::service::dispatch::DispatchBackend;
 use utoipa::{IntoParams, ToSchema};
 
@@ -11,6 +13,10 @@ pub struct Config {
     pub sbom_upload_limit: usize,
     pub advisory_upload_limit: usize,
     pub max_group_name_length: usize,
+    pub limits: Limits,
+    pub loaders: LoaderRegistry,
+    pub quotas: Option<Quotas>,
+    pub trust_root: TrustRoot,
 }
 
 pub fn configure(
@@ -22,7 +28,11 @@ pub fn configure(
     analysis: AnalysisService,
     cache: PaginationCache,
 ) {
-    let ingestor_service = IngestorService::new(Graph::new(), storage, Some(analysis));
+    let ingestor_service = IngestorService::new(Graph::new(), storage, Some(analysis))
+        .with_limits(config.limits)
+        .with_loaders(config.loaders)
+        .with_quotas(config.quotas)
+        .with_trust_root(config.trust_root);
     svc.app_data(web::Data::new(ingestor_service));
 
     crate::advisory::endpoints::configure(
@@ -32,6 +42,7 @@ pub fn configure(
         config.advisory_upload_limit,
         cache.clone(),
     );
+    crate::exception::endpoints::configure(svc, db_rw.clone(), db_ro.clone(), cache.clone());
     crate::license::endpoints::configure(svc, db_ro.clone());
     crate::organization::endpoints::configure(svc, db_ro.clone(), cache.clone());
     crate::purl::endpoints::configure(svc, db_ro.clone(), cache.clone());
@@ -45,6 +56,7 @@ pub fn configure(
     );
     crate::vulnerability::endpoints::configure(svc, db_ro.clone(), cache.clone());
     crate::weakness::endpoints::configure(svc, db_ro.clone(), cache.clone());
+    crate::source_document::endpoints::configure(svc, db_rw.clone(), cache.clone());
     crate::sbom_group::endpoints::configure(svc, db_rw, db_ro, config.max_group_name_length, cache);
 }
 
@@ -54,3 +66,10 @@ pub struct Deprecation {
     #[param(inline)]
     pub deprecated: trustify_module_ingestor::common::Deprecation,
 }
+
+#[derive(Clone, Debug, PartialEq, Eq, Default, ToSchema, serde::Deserialize, IntoParams)]
+pub struct Withdrawal {
+    #[serde(default)]
+    #[param(inline)]
+    pub withdrawn: trustify_module_ingestor::common::Withdrawal,
+}
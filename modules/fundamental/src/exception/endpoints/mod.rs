@@ -0,0 +1,166 @@
+#[cfg(test)]
+mod test;
+
+use crate::{
+    Error,
+    exception::{
+        model::{ExceptionRequest, VulnerabilityException},
+        service::ExceptionService,
+    },
+};
+use actix_web::{HttpRequest, HttpResponse, Responder, delete, get, http::header, post, web};
+use sea_orm::TransactionTrait;
+use serde_json::json;
+use time::OffsetDateTime;
+use trustify_auth::{CreateMetadata, DeleteMetadata, ReadMetadata, authorizer::Require};
+use trustify_common::{
+    db::{self, pagination_cache::PaginationCache, query::Query},
+    model::{Paginated, PaginatedResults},
+};
+use trustify_entity::vulnerability_exception::ExceptionKind;
+use trustify_query::TrustifyQuery;
+use trustify_query_derive::Query;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+#[derive(Clone, Debug, PartialEq, Eq, Default, serde::Deserialize, IntoParams)]
+pub struct ExceptedParams {
+    /// Include findings which are suppressed by an active vulnerability exception
+    #[serde(default)]
+    pub excepted: bool,
+}
+
+pub fn configure(
+    config: &mut utoipa_actix_web::service_config::ServiceConfig,
+    db_rw: db::ReadWrite,
+    db_ro: db::ReadOnly,
+    cache: PaginationCache,
+) {
+    let service = ExceptionService::new(cache);
+    config
+        .app_data(web::Data::new(db_rw))
+        .app_data(web::Data::new(db_ro))
+        .app_data(web::Data::new(service))
+        .service(all)
+        .service(create)
+        .service(get)
+        .service(delete);
+}
+
+#[allow(dead_code)]
+#[derive(Query)]
+struct ExceptionQuery {
+    product_id: Uuid,
+    vulnerability_id: String,
+    kind: ExceptionKind,
+    justification: String,
+    expires: Option<OffsetDateTime>,
+    created: OffsetDateTime,
+}
+
+#[utoipa::path(
+    tag = "exception",
+    operation_id = "listExceptions",
+    params(
+        TrustifyQuery<ExceptionQuery>,
+        Paginated,
+    ),
+    responses(
+        (status = 200, description = "Matching exceptions", body = PaginatedResults<VulnerabilityException>),
+    ),
+)]
+#[get("/v3/exception")]
+/// List vulnerability exceptions
+pub async fn all(
+    service: web::Data<ExceptionService>,
+    db: web::Data<db::ReadOnly>,
+    web::Query(search): web::Query<Query>,
+    web::Query(paginated): web::Query<Paginated>,
+    _: Require<ReadMetadata>,
+) -> actix_web::Result<impl Responder> {
+    let tx = db.begin().await?;
+    Ok(HttpResponse::Ok().json(service.list(search, paginated, &tx).await?))
+}
+
+#[utoipa::path(
+    tag = "exception",
+    operation_id = "createException",
+    request_body = ExceptionRequest,
+    responses(
+        (
+            status = 201, description = "Created the requested exception",
+            headers(
+                ("location" = String, description = "The relative URL to the created resource")
+            )
+        ),
+        (status = 400, description = "The request was not valid"),
+    ),
+)]
+#[post("/v3/exception")]
+/// Create a new vulnerability exception
+pub async fn create(
+    req: HttpRequest,
+    service: web::Data<ExceptionService>,
+    db: web::Data<db::ReadWrite>,
+    web::Json(request): web::Json<ExceptionRequest>,
+    _: Require<CreateMetadata>,
+) -> Result<impl Responder, Error> {
+    let tx = db.begin().await?;
+    let id = service.create(request, &tx).await?;
+    tx.commit().await?;
+
+    Ok(HttpResponse::Created()
+        .append_header((header::LOCATION, format!("{}/{}", req.path(), id)))
+        .json(json!({"id": id})))
+}
+
+#[utoipa::path(
+    tag = "exception",
+    operation_id = "getException",
+    params(
+        ("id", Path, description = "The ID of the exception"),
+    ),
+    responses(
+        (status = 200, description = "Matching exception", body = VulnerabilityException),
+        (status = 404, description = "The exception could not be found"),
+    ),
+)]
+#[get("/v3/exception/{id}")]
+/// Retrieve a vulnerability exception
+pub async fn get(
+    service: web::Data<ExceptionService>,
+    db: web::Data<db::ReadOnly>,
+    id: web::Path<Uuid>,
+    _: Require<ReadMetadata>,
+) -> actix_web::Result<impl Responder> {
+    let tx = db.begin().await?;
+    match service.read(*id, &tx).await? {
+        Some(exception) => Ok(HttpResponse::Ok().json(exception)),
+        None => Ok(HttpResponse::NotFound().finish()),
+    }
+}
+
+#[utoipa::path(
+    tag = "exception",
+    operation_id = "deleteException",
+    params(
+        ("id", Path, description = "The ID of the exception"),
+    ),
+    responses(
+        (status = 204, description = "The exception was deleted or did not exist"),
+    ),
+)]
+#[delete("/v3/exception/{id}")]
+/// Delete a vulnerability exception
+pub async fn delete(
+    service: web::Data<ExceptionService>,
+    db: web::Data<db::ReadWrite>,
+    id: web::Path<Uuid>,
+    _: Require<DeleteMetadata>,
+) -> Result<impl Responder, Error> {
+    let tx = db.begin().await?;
+    service.delete(*id, &tx).await?;
+    tx.commit().await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,380 @@
+use crate::test::caller;
+use actix_http::StatusCode;
+use actix_web::test::TestRequest;
+use serde_json::{Value, json};
+use std::str::FromStr;
+use test_context::test_context;
+use test_log::test;
+use time::{Duration, OffsetDateTime, format_description::well_known::Rfc3339};
+use trustify_module_ingestor::graph::product::ProductInformation;
+use trustify_test_context::{TrustifyContext, call::CallService};
+use uuid::Uuid;
+
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn create_read_delete(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    let app = caller(ctx).await?;
+
+    let product = ctx
+        .graph
+        .ingest_product("Test Product", ProductInformation::default(), &ctx.db)
+        .await?;
+    ctx.graph
+        .ingest_vulnerability("CVE-2023-0044", (), &ctx.db)
+        .await?;
+
+    let response = app
+        .call_service(
+            TestRequest::post()
+                .uri("/api/v3/exception")
+                .set_json(json!({
+                    "product_id": product.product.id,
+                    "vulnerability_id": "CVE-2023-0044",
+                    "kind": "false-positive",
+                    "justification": "The affected code is never called",
+                }))
+                .to_request(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body: Value = actix_web::test::read_body_json(response).await;
+    let id = body["id"].as_str().expect("must have an ID").to_string();
+
+    let exception: Value = app
+        .call_and_read_body_json(
+            TestRequest::get()
+                .uri(&format!("/api/v3/exception/{id}"))
+                .to_request(),
+        )
+        .await;
+    assert_eq!(exception["vulnerability_id"], "CVE-2023-0044");
+    assert_eq!(exception["kind"], "false-positive");
+    assert_eq!(exception["expires"], Value::Null);
+
+    let list: Value = app
+        .call_and_read_body_json(
+            TestRequest::get()
+                .uri(&format!(
+                    "/api/v3/exception?q=product_id%3D{}",
+                    product.product.id
+                ))
+                .to_request(),
+        )
+        .await;
+    assert_eq!(list["items"].as_array().map(Vec::len), Some(1));
+
+    let response = app
+        .call_service(
+            TestRequest::delete()
+                .uri(&format!("/api/v3/exception/{id}"))
+                .to_request(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = app
+        .call_service(
+            TestRequest::get()
+                .uri(&format!("/api/v3/exception/{id}"))
+                .to_request(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn reject_invalid(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    let app = caller(ctx).await?;
+
+    let product = ctx
+        .graph
+        .ingest_product("Test Product", ProductInformation::default(), &ctx.db)
+        .await?;
+    ctx.graph
+        .ingest_vulnerability("CVE-2023-0044", (), &ctx.db)
+        .await?;
+
+    let expired = (OffsetDateTime::now_utc() - Duration::days(1)).format(&Rfc3339)?;
+
+    for request in [
+        // empty justification
+        json!({
+            "product_id": product.product.id,
+            "vulnerability_id": "CVE-2023-0044",
+            "kind": "accepted",
+            "justification": " ",
+        }),
+        // already expired
+        json!({
+            "product_id": product.product.id,
+            "vulnerability_id": "CVE-2023-0044",
+            "kind": "accepted",
+            "justification": "Mitigated by configuration",
+            "expires": expired,
+        }),
+        // unknown vulnerability
+        json!({
+            "product_id": product.product.id,
+            "vulnerability_id": "CVE-0000-0000",
+            "kind": "accepted",
+            "justification": "Mitigated by configuration",
+        }),
+        // unknown product
+        json!({
+            "product_id": Uuid::now_v7(),
+            "vulnerability_id": "CVE-2023-0044",
+            "kind": "accepted",
+            "justification": "Mitigated by configuration",
+        }),
+    ] {
+        let response = app
+            .call_service(
+                TestRequest::post()
+                    .uri("/api/v3/exception")
+                    .set_json(request)
+                    .to_request(),
+            )
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    Ok(())
+}
+
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn suppress_sbom_advisories(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    let id = ctx
+        .ingest_documents([
+            "quarkus-bom-2.13.8.Final-redhat-00004.json",
+            "csaf/cve-2023-0044.json",
+        ])
+        .await?[0]
+        .id
+        .to_string();
+    let sbom_id = Uuid::from_str(&id)?;
+
+    let product = ctx
+        .graph
+        .ingest_product("Quarkus", ProductInformation::default(), &ctx.db)
+        .await?;
+    product
+        .ingest_product_version("2.13.8".to_string(), Some(sbom_id), &ctx.db)
+        .await?;
+
+    let app = caller(ctx).await?;
+
+    let advisories = async |query: &str| -> Value {
+        app.call_and_read_body_json(
+            TestRequest::get()
+                .uri(&format!("/api/v3/sbom/urn:uuid:{sbom_id}/advisory{query}"))
+                .to_request(),
+        )
+        .await
+    };
+
+    assert_eq!(advisories("").await.as_array().map(Vec::len), Some(1));
+
+    let response = app
+        .call_service(
+            TestRequest::post()
+                .uri("/api/v3/exception")
+                .set_json(json!({
+                    "product_id": product.product.id,
+                    "vulnerability_id": "CVE-2023-0044",
+                    "kind": "accepted",
+                    "justification": "Only reachable from the admin console",
+                    "expires": (OffsetDateTime::now_utc() + Duration::days(30)).format(&Rfc3339)?,
+                }))
+                .to_request(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    // suppressed by default
+    assert_eq!(advisories("").await.as_array().map(Vec::len), Some(0));
+    // but still available on request
+    assert_eq!(
+        advisories("?excepted=true").await.as_array().map(Vec::len),
+        Some(1)
+    );
+
+    Ok(())
+}
+
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn suppress_sbom_openvex(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    let id = ctx
+        .ingest_documents([
+            "quarkus-bom-2.13.8.Final-redhat-00004.json",
+            "csaf/cve-2023-0044.json",
+        ])
+        .await?[0]
+        .id
+        .to_string();
+    let sbom_id = Uuid::from_str(&id)?;
+
+    let product = ctx
+        .graph
+        .ingest_product("Quarkus", ProductInformation::default(), &ctx.db)
+        .await?;
+    product
+        .ingest_product_version("2.13.8".to_string(), Some(sbom_id), &ctx.db)
+        .await?;
+
+    let app = caller(ctx).await?;
+
+    let vulnerabilities = async |query: &str| -> Vec<String> {
+        let v: Value = app
+            .call_and_read_body_json(
+                TestRequest::get()
+                    .uri(&format!("/api/v3/sbom/urn:uuid:{sbom_id}/openvex{query}"))
+                    .to_request(),
+            )
+            .await;
+        v["statements"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|statement| statement["vulnerability"]["name"].as_str())
+            .map(ToString::to_string)
+            .collect()
+    };
+
+    assert!(
+        vulnerabilities("")
+            .await
+            .contains(&"CVE-2023-0044".to_string())
+    );
+
+    let response = app
+        .call_service(
+            TestRequest::post()
+                .uri("/api/v3/exception")
+                .set_json(json!({
+                    "product_id": product.product.id,
+                    "vulnerability_id": "CVE-2023-0044",
+                    "kind": "false-positive",
+                    "justification": "The affected class is not shipped",
+                }))
+                .to_request(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    // suppressed by default
+    assert!(
+        !vulnerabilities("")
+            .await
+            .contains(&"CVE-2023-0044".to_string())
+    );
+    // but still available on request
+    assert!(
+        vulnerabilities("?excepted=true")
+            .await
+            .contains(&"CVE-2023-0044".to_string())
+    );
+
+    Ok(())
+}
+
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn suppress_vulnerabilities(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    let id = ctx
+        .ingest_documents([
+            "quarkus-bom-2.13.8.Final-redhat-00004.json",
+            "csaf/cve-2023-0044.json",
+        ])
+        .await?[0]
+        .id
+        .to_string();
+    let sbom_id = Uuid::from_str(&id)?;
+
+    let product = ctx
+        .graph
+        .ingest_product("Quarkus", ProductInformation::default(), &ctx.db)
+        .await?;
+    product
+        .ingest_product_version("2.13.8".to_string(), Some(sbom_id), &ctx.db)
+        .await?;
+
+    let app = caller(ctx).await?;
+
+    let vulnerabilities = async |query: &str| -> Vec<String> {
+        let v: Value = app
+            .call_and_read_body_json(
+                TestRequest::get()
+                    .uri(&format!("/api/v3/vulnerability{query}"))
+                    .to_request(),
+            )
+            .await;
+        v["items"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|item| item["identifier"].as_str())
+            .map(ToString::to_string)
+            .collect()
+    };
+    let sboms = async |query: &str| -> Vec<String> {
+        let v: Value = app
+            .call_and_read_body_json(
+                TestRequest::get()
+                    .uri(&format!("/api/v3/vulnerability/CVE-2023-0044{query}"))
+                    .to_request(),
+            )
+            .await;
+        v["advisories"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|advisory| advisory["sboms"].as_array().into_iter().flatten())
+            .filter_map(|sbom| sbom["id"].as_str())
+            .map(ToString::to_string)
+            .collect()
+    };
+
+    assert!(
+        vulnerabilities("")
+            .await
+            .contains(&"CVE-2023-0044".to_string())
+    );
+    assert!(sboms("").await.contains(&format!("urn:uuid:{sbom_id}")));
+
+    let response = app
+        .call_service(
+            TestRequest::post()
+                .uri("/api/v3/exception")
+                .set_json(json!({
+                    "product_id": product.product.id,
+                    "vulnerability_id": "CVE-2023-0044",
+                    "kind": "accepted",
+                    "justification": "Only reachable from the admin console",
+                }))
+                .to_request(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    // the exception is scoped to the product, other products are still affected
+    assert!(
+        vulnerabilities("")
+            .await
+            .contains(&"CVE-2023-0044".to_string())
+    );
+    // the SBOMs of the product are suppressed by default
+    assert!(!sboms("").await.contains(&format!("urn:uuid:{sbom_id}")));
+    // but still available on request
+    assert!(
+        sboms("?excepted=true")
+            .await
+            .contains(&format!("urn:uuid:{sbom_id}"))
+    );
+
+    Ok(())
+}
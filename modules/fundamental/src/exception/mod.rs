@@ -0,0 +1,3 @@
+pub(crate) mod endpoints;
+pub mod model;
+pub mod service;
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use trustify_entity::vulnerability_exception::{self, ExceptionKind};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// An exception, suppressing findings of a vulnerability for a product
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema, PartialEq, Eq)]
+pub struct VulnerabilityException {
+    /// The ID of the exception
+    pub id: Uuid,
+    /// The ID of the product the exception applies to
+    pub product_id: Uuid,
+    /// The identifier of the vulnerability
+    pub vulnerability_id: String,
+    /// The kind of exception
+    pub kind: ExceptionKind,
+    /// The reason why the exception was granted
+    pub justification: String,
+    /// The date (in RFC3339 format) the exception expires, if ever
+    #[schema(required)]
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub expires: Option<OffsetDateTime>,
+    /// The date (in RFC3339 format) the exception was created
+    #[serde(with = "time::serde::rfc3339")]
+    pub created: OffsetDateTime,
+}
+
+impl VulnerabilityException {
+    /// Check if the exception is active at the provided point in time
+    pub fn is_active_at(&self, now: OffsetDateTime) -> bool {
+        self.expires.is_none_or(|expires| expires > now)
+    }
+}
+
+impl From<vulnerability_exception::Model> for VulnerabilityException {
+    fn from(value: vulnerability_exception::Model) -> Self {
+        Self {
+            id: value.id,
+            product_id: value.product_id,
+            vulnerability_id: value.vulnerability_id,
+            kind: value.kind,
+            justification: value.justification,
+            expires: value.expires,
+            created: value.created,
+        }
+    }
+}
+
+/// Request to create a new exception
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema, PartialEq, Eq)]
+pub struct ExceptionRequest {
+    /// The ID of the product the exception applies to
+    pub product_id: Uuid,
+    /// The identifier of the vulnerability
+    pub vulnerability_id: String,
+    /// The kind of exception
+    pub kind: ExceptionKind,
+    /// The reason why the exception is granted, must not be empty
+    pub justification: String,
+    /// The date (in RFC3339 format) the exception expires, if ever
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub expires: Option<OffsetDateTime>,
+}
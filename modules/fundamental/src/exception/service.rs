@@ -0,0 +1,243 @@
+use crate::{
+    Error,
+    exception::model::{ExceptionRequest, VulnerabilityException},
+    sbom::model::details::SbomAdvisory,
+    vulnerability::model::VulnerabilityAdvisorySummary,
+};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, JoinType, QueryFilter,
+    QuerySelect, RelationTrait, Select, Set,
+};
+use std::collections::HashSet;
+use time::OffsetDateTime;
+use tracing::instrument;
+use trustify_common::{
+    db::{
+        limiter::LimiterTrait,
+        pagination_cache::PaginationCache,
+        query::{Filtering, Query},
+    },
+    model::{PaginatedResults, Pagination},
+};
+use trustify_entity::{product, product_version, vulnerability, vulnerability_exception};
+use uuid::Uuid;
+
+pub struct ExceptionService {
+    cache: PaginationCache,
+}
+
+impl ExceptionService {
+    pub fn new(cache: PaginationCache) -> Self {
+        Self { cache }
+    }
+
+    pub async fn list<C: ConnectionTrait>(
+        &self,
+        search: Query,
+        paginated: impl Pagination,
+        connection: &C,
+    ) -> Result<PaginatedResults<VulnerabilityException>, Error> {
+        let limiter = vulnerability_exception::Entity::find()
+            .filtering(search)?
+            .limiting(connection, paginated, &self.cache)?;
+
+        Ok(
+            PaginatedResults::<vulnerability_exception::Model>::new(limiter, paginated)
+                .await?
+                .map(VulnerabilityException::from),
+        )
+    }
+
+    #[instrument(skip(self, connection), err(level=tracing::Level::INFO))]
+    pub async fn read<C: ConnectionTrait>(
+        &self,
+        id: Uuid,
+        connection: &C,
+    ) -> Result<Option<VulnerabilityException>, Error> {
+        Ok(vulnerability_exception::Entity::find_by_id(id)
+            .one(connection)
+            .await?
+            .map(Into::into))
+    }
+
+    /// Create a new exception, returning its ID
+    #[instrument(skip(self, connection), err(level=tracing::Level::INFO))]
+    pub async fn create<C: ConnectionTrait>(
+        &self,
+        request: ExceptionRequest,
+        connection: &C,
+    ) -> Result<Uuid, Error> {
+        let ExceptionRequest {
+            product_id,
+            vulnerability_id,
+            kind,
+            justification,
+            expires,
+        } = request;
+
+        if justification.trim().is_empty() {
+            return Err(Error::bad_request(
+                "Missing justification",
+                Some("An exception requires a justification"),
+            ));
+        }
+
+        let now = OffsetDateTime::now_utc();
+        if expires.is_some_and(|expires| expires <= now) {
+            return Err(Error::bad_request(
+                "Invalid expiry",
+                Some("The expiry of an exception must be in the future"),
+            ));
+        }
+
+        if product::Entity::find_by_id(product_id)
+            .one(connection)
+            .await?
+            .is_none()
+        {
+            return Err(Error::bad_request(
+                "Unknown product",
+                Some(format!("Product '{product_id}' does not exist")),
+            ));
+        }
+
+        if vulnerability::Entity::find_by_id(&vulnerability_id)
+            .one(connection)
+            .await?
+            .is_none()
+        {
+            return Err(Error::bad_request(
+                "Unknown vulnerability",
+                Some(format!("Vulnerability '{vulnerability_id}' does not exist")),
+            ));
+        }
+
+        let id = Uuid::now_v7();
+
+        vulnerability_exception::ActiveModel {
+            id: Set(id),
+            product_id: Set(product_id),
+            vulnerability_id: Set(vulnerability_id),
+            kind: Set(kind),
+            justification: Set(justification),
+            expires: Set(expires),
+            created: Set(now),
+        }
+        .insert(connection)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// Delete an exception, returning the number of deleted entries
+    #[instrument(skip(self, connection), err(level=tracing::Level::INFO))]
+    pub async fn delete<C: ConnectionTrait>(&self, id: Uuid, connection: &C) -> Result<u64, Error> {
+        let result = vulnerability_exception::Entity::delete_by_id(id)
+            .exec(connection)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+
+    /// Get the IDs of all vulnerabilities which have an active exception for any of the products
+    /// the SBOM belongs to.
+    #[instrument(skip(self, connection), err(level=tracing::Level::INFO))]
+    pub async fn suppressed_for_sbom<C: ConnectionTrait>(
+        &self,
+        sbom_id: Uuid,
+        connection: &C,
+    ) -> Result<HashSet<String>, Error> {
+        let select = vulnerability_exception::Entity::find()
+            .join(
+                JoinType::Join,
+                vulnerability_exception::Relation::Product.def(),
+            )
+            .join(JoinType::Join, product::Relation::ProductVersion.def())
+            .filter(product_version::Column::SbomId.eq(sbom_id));
+
+        Ok(active(select, connection)
+            .await?
+            .map(|exception| exception.vulnerability_id)
+            .collect())
+    }
+
+    /// Get the IDs of all SBOMs which belong to a product having an active exception for the
+    /// vulnerability.
+    #[instrument(skip(self, connection), err(level=tracing::Level::INFO))]
+    pub async fn suppressed_sboms<C: ConnectionTrait>(
+        &self,
+        vulnerability_id: &str,
+        connection: &C,
+    ) -> Result<HashSet<Uuid>, Error> {
+        let select = vulnerability_exception::Entity::find()
+            .filter(vulnerability_exception::Column::VulnerabilityId.eq(vulnerability_id));
+        let products: HashSet<Uuid> = active(select, connection)
+            .await?
+            .map(|exception| exception.product_id)
+            .collect();
+
+        if products.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let ids: Vec<Option<Uuid>> = product_version::Entity::find()
+            .select_only()
+            .column(product_version::Column::SbomId)
+            .filter(product_version::Column::ProductId.is_in(products))
+            .filter(product_version::Column::SbomId.is_not_null())
+            .into_tuple()
+            .all(connection)
+            .await?;
+
+        Ok(ids.into_iter().flatten().collect())
+    }
+}
+
+/// Load the exceptions of the select which are currently active.
+async fn active<C: ConnectionTrait>(
+    select: Select<vulnerability_exception::Entity>,
+    connection: &C,
+) -> Result<impl Iterator<Item = VulnerabilityException>, Error> {
+    let now = OffsetDateTime::now_utc();
+
+    Ok(select
+        .all(connection)
+        .await?
+        .into_iter()
+        .map(VulnerabilityException::from)
+        .filter(move |exception| exception.is_active_at(now)))
+}
+
+/// Remove all statuses of suppressed vulnerabilities, dropping advisories which have no
+/// remaining status.
+pub fn suppress_advisories(
+    advisories: Vec<SbomAdvisory>,
+    suppressed: &HashSet<String>,
+) -> Vec<SbomAdvisory> {
+    if suppressed.is_empty() {
+        return advisories;
+    }
+
+    advisories
+        .into_iter()
+        .filter_map(|mut advisory| {
+            advisory
+                .status
+                .retain(|status| !suppressed.contains(&status.vulnerability.identifier));
+            (!advisory.status.is_empty()).then_some(advisory)
+        })
+        .collect()
+}
+
+/// Remove all SBOMs which are suppressed from the advisories of a vulnerability.
+pub fn suppress_sboms(advisories: &mut [VulnerabilityAdvisorySummary], suppressed: &HashSet<Uuid>) {
+    if suppressed.is_empty() {
+        return;
+    }
+
+    for advisory in advisories {
+        advisory
+            .sboms
+            .retain(|sbom| !suppressed.contains(&sbom.head.id));
+    }
+}
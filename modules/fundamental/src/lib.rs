@@ -4,10 +4,12 @@ pub mod advisory;
 pub mod common;
 pub mod endpoints;
 pub mod error;
+pub mod exception;
 pub mod license;
 pub mod organization;
 pub mod product;
 pub mod purl;
+pub mod retention;
 pub mod sbom;
 pub mod source_document;
 #[allow(deprecated)]
@@ -1,6 +1,10 @@
 pub mod sbom_license;
 
-use crate::{Error, purl::model::VersionedPurlHead, sbom::model::SbomHead};
+use crate::{
+    Error,
+    purl::model::VersionedPurlHead,
+    sbom::model::{SbomHead, SbomPackageSummary},
+};
 use serde::{Deserialize, Serialize};
 use spdx::License;
 use trustify_entity::license;
@@ -69,3 +73,22 @@ pub struct SpdxLicenseDetails {
     pub summary: SpdxLicenseSummary,
     pub text: String,
 }
+
+/// The license inventory of an SBOM
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SbomLicenseReport {
+    /// The number of packages in the SBOM
+    pub packages: u64,
+    /// The licenses, with the number of packages using them
+    pub licenses: Vec<LicenseUsage>,
+    /// Packages without any license information, or only `NOASSERTION`
+    pub unknown: Vec<SbomPackageSummary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LicenseUsage {
+    /// The license expression, with license references expanded
+    pub license: String,
+    /// The number of packages declaring or concluding the license
+    pub packages: u64,
+}
@@ -33,6 +33,7 @@ use trustify_entity::{
 use utoipa::ToSchema;
 
 pub mod license_export;
+mod report;
 
 #[cfg(test)]
 mod test;
@@ -0,0 +1,139 @@
+use super::LicenseService;
+use crate::{
+    Error,
+    common::license_filtering::license_text_coalesce,
+    license::model::{LicenseUsage, SbomLicenseReport},
+    sbom::model::SbomPackageSummary,
+};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, EntityTrait, FromQueryResult, PaginatorTrait, QueryFilter,
+    QueryOrder, QuerySelect, RelationTrait,
+};
+use sea_query::{Expr, Func, JoinType};
+use tracing::instrument;
+use trustify_common::id::{Id, TrySelectForId};
+use trustify_entity::{
+    license, sbom, sbom_license_expanded, sbom_node, sbom_package, sbom_package_license,
+};
+
+/// The license text stating that no license information was determined
+const NOASSERTION: &str = "NOASSERTION";
+
+#[derive(FromQueryResult)]
+struct UsageRow {
+    license: String,
+    packages: i64,
+}
+
+impl LicenseService {
+    /// Create the license inventory of an SBOM, for compliance review.
+    ///
+    /// Returns `None` if the SBOM could not be found.
+    #[instrument(skip(self, connection), err(level=tracing::Level::INFO))]
+    pub async fn license_report<C: ConnectionTrait>(
+        &self,
+        id: Id,
+        connection: &C,
+    ) -> Result<Option<SbomLicenseReport>, Error> {
+        let Some(sbom) = sbom::Entity::find()
+            .join(JoinType::LeftJoin, sbom::Relation::SourceDocument.def())
+            .try_filter(id)?
+            .one(connection)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let packages = sbom_package::Entity::find()
+            .filter(sbom_package::Column::SbomId.eq(sbom.sbom_id))
+            .count(connection)
+            .await?;
+
+        let license_expr = license_text_coalesce();
+        let licenses = sbom_package_license::Entity::find()
+            .select_only()
+            .column_as(license_expr.clone(), "license")
+            .expr_as(
+                Func::count_distinct(Expr::col((
+                    sbom_package_license::Entity,
+                    sbom_package_license::Column::NodeId,
+                ))),
+                "packages",
+            )
+            .join(
+                JoinType::LeftJoin,
+                sbom_package_license::Relation::SbomLicenseExpanded.def(),
+            )
+            .join(
+                JoinType::LeftJoin,
+                sbom_license_expanded::Relation::ExpandedLicense.def(),
+            )
+            .join(
+                JoinType::LeftJoin,
+                sbom_package_license::Relation::License.def(),
+            )
+            .filter(sbom_package_license::Column::SbomId.eq(sbom.sbom_id))
+            .filter(Expr::col((license::Entity, license::Column::Text)).ne(NOASSERTION))
+            .group_by(license_expr.clone())
+            .order_by_desc(Expr::cust("packages"))
+            .order_by_asc(license_expr)
+            .into_model::<UsageRow>()
+            .all(connection)
+            .await?
+            .into_iter()
+            .map(|row| LicenseUsage {
+                license: row.license,
+                packages: row.packages as u64,
+            })
+            .collect();
+
+        // packages having at least one license, other than NOASSERTION
+        let known = sea_query::Query::select()
+            .expr(Expr::val(1))
+            .from(sbom_package_license::Entity)
+            .inner_join(
+                license::Entity,
+                Expr::col((license::Entity, license::Column::Id)).equals((
+                    sbom_package_license::Entity,
+                    sbom_package_license::Column::LicenseId,
+                )),
+            )
+            .and_where(
+                Expr::col((
+                    sbom_package_license::Entity,
+                    sbom_package_license::Column::SbomId,
+                ))
+                .equals((sbom_package::Entity, sbom_package::Column::SbomId)),
+            )
+            .and_where(
+                Expr::col((
+                    sbom_package_license::Entity,
+                    sbom_package_license::Column::NodeId,
+                ))
+                .equals((sbom_package::Entity, sbom_package::Column::NodeId)),
+            )
+            .and_where(Expr::col((license::Entity, license::Column::Text)).ne(NOASSERTION))
+            .to_owned();
+
+        let unknown = sbom_package::Entity::find()
+            .join(JoinType::Join, sbom_package::Relation::Node.def())
+            .select_only()
+            .column_as(sbom_package::Column::NodeId, "id")
+            .column_as(sbom_node::Column::Name, "name")
+            .column_as(sbom_package::Column::Group, "group")
+            .column_as(sbom_package::Column::Version, "version")
+            .filter(sbom_package::Column::SbomId.eq(sbom.sbom_id))
+            .filter(Expr::exists(known).not())
+            .order_by_asc(sbom_node::Column::Name)
+            .order_by_asc(sbom_package::Column::Version)
+            .into_model::<SbomPackageSummary>()
+            .all(connection)
+            .await?;
+
+        Ok(Some(SbomLicenseReport {
+            packages,
+            licenses,
+            unknown,
+        }))
+    }
+}
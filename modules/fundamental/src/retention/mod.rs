@@ -0,0 +1,454 @@
+use crate::{
+    Error, advisory::service::AdvisoryService, common::service::lock_unreferenced,
+    sbom::service::SbomService,
+};
+use sea_orm::{ConnectionTrait, DbBackend, FromQueryResult, Statement, TransactionTrait};
+use sea_query::{ArrayType, Value};
+use std::time::Duration;
+use time::OffsetDateTime;
+use tracing::instrument;
+use trustify_common::db::pagination_cache::PaginationCache;
+use trustify_module_storage::service::{StorageBackend, StorageKey};
+use uuid::Uuid;
+
+/// Rules for deleting documents which are no longer needed
+#[derive(Clone, Debug, Default)]
+pub struct RetentionPolicy {
+    /// Delete advisory revisions, which were superseded by a newer revision for longer than this
+    pub superseded_advisories: Option<Duration>,
+    /// Keep only the latest SBOMs of those sharing the same values for these labels
+    ///
+    /// SBOMs missing one of the labels are always kept. No SBOMs get deleted if this is empty.
+    pub sbom_labels: Vec<String>,
+    /// The number of SBOMs to keep for each combination of label values, at least one
+    pub sbom_keep: u32,
+}
+
+impl RetentionPolicy {
+    /// Check if the policy doesn't have any rules
+    pub fn is_empty(&self) -> bool {
+        self.superseded_advisories.is_none() && self.sbom_labels.is_empty()
+    }
+
+    /// Check if the rules of the policy are valid
+    pub fn validate(&self) -> Result<(), Error> {
+        if !self.sbom_labels.is_empty() && self.sbom_keep < 1 {
+            return Err(Error::bad_request(
+                "Invalid retention policy",
+                Some("At least one SBOM must be kept for each combination of label values"),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// A document selected for deletion by the retention policy
+#[derive(Clone, Debug, PartialEq, Eq, FromQueryResult, serde::Serialize)]
+pub struct ExpiredDocument {
+    pub id: Uuid,
+    /// The identifier of the advisory, or the document ID of the SBOM
+    pub name: Option<String>,
+    /// The SHA256 digest of the source document
+    pub digest: String,
+}
+
+/// The outcome of applying a retention policy
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize)]
+pub struct RetentionReport {
+    /// If nothing got deleted, but only reported
+    pub dry_run: bool,
+    pub advisories: Vec<ExpiredDocument>,
+    pub sboms: Vec<ExpiredDocument>,
+}
+
+impl RetentionReport {
+    pub fn is_empty(&self) -> bool {
+        self.advisories.is_empty() && self.sboms.is_empty()
+    }
+}
+
+pub struct RetentionService {
+    advisory: AdvisoryService,
+    sbom: SbomService,
+}
+
+impl RetentionService {
+    pub fn new(cache: PaginationCache) -> Self {
+        Self {
+            advisory: AdvisoryService::new(cache.clone()),
+            sbom: SbomService::new(cache),
+        }
+    }
+
+    /// Find the documents the policy would delete.
+    #[instrument(skip(self, connection), err(level=tracing::Level::INFO))]
+    pub async fn evaluate<C: ConnectionTrait>(
+        &self,
+        policy: &RetentionPolicy,
+        connection: &C,
+    ) -> Result<RetentionReport, Error> {
+        policy.validate()?;
+
+        let advisories = match policy.superseded_advisories {
+            Some(age) => superseded_advisories(age, connection).await?,
+            None => vec![],
+        };
+
+        let sboms = match policy.sbom_labels.is_empty() {
+            true => vec![],
+            false => outdated_sboms(&policy.sbom_labels, policy.sbom_keep, connection).await?,
+        };
+
+        Ok(RetentionReport {
+            dry_run: true,
+            advisories,
+            sboms,
+        })
+    }
+
+    /// Apply the policy, deleting the documents it selects.
+    ///
+    /// For a dry run, the documents are only reported. Otherwise, they are deleted from the
+    /// database in a single transaction, and then removed from the storage. Failing to remove them
+    /// from the storage is only logged, as the blobs can still be collected as garbage later on.
+    #[instrument(skip(self, db, storage), err(level=tracing::Level::INFO))]
+    pub async fn apply<C, S>(
+        &self,
+        policy: &RetentionPolicy,
+        dry_run: bool,
+        db: &C,
+        storage: &S,
+    ) -> Result<RetentionReport, Error>
+    where
+        C: ConnectionTrait + TransactionTrait,
+        S: StorageBackend,
+    {
+        let tx = db.begin().await?;
+
+        let mut report = self.evaluate(policy, &tx).await?;
+        report.dry_run = dry_run;
+
+        if dry_run || report.is_empty() {
+            return Ok(report);
+        }
+
+        let mut digests = vec![];
+        for advisory in &report.advisories {
+            if self.advisory.delete_advisory(advisory.id, &tx).await? {
+                digests.push(advisory.digest.clone());
+            }
+        }
+        digests.extend(
+            self.sbom
+                .delete_sboms(report.sboms.iter().map(|sbom| sbom.id).collect(), &tx)
+                .await?,
+        );
+
+        tx.commit().await?;
+
+        let tx = db.begin().await?;
+        let keys = lock_unreferenced(digests, &tx)
+            .await?
+            .iter()
+            .map(|digest| StorageKey::from_sha256(digest))
+            .collect::<Vec<_>>();
+        if let Err(err) = storage.delete_many(&keys).await {
+            log::warn!("Failed to remove expired documents from the storage: {err:?}");
+        }
+        tx.commit().await?;
+
+        Ok(report)
+    }
+
+    /// Apply the policy, logging the outcome.
+    pub async fn run<C, S>(&self, policy: &RetentionPolicy, dry_run: bool, db: &C, storage: &S)
+    where
+        C: ConnectionTrait + TransactionTrait,
+        S: StorageBackend,
+    {
+        match self.apply(policy, dry_run, db, storage).await {
+            Ok(report) if report.dry_run => log::info!(
+                "Retention (dry run) would delete {} advisories and {} SBOMs: {}",
+                report.advisories.len(),
+                report.sboms.len(),
+                serde_json::to_string(&report).unwrap_or_default(),
+            ),
+            Ok(report) => log::info!(
+                "Retention deleted {} advisories and {} SBOMs",
+                report.advisories.len(),
+                report.sboms.len()
+            ),
+            Err(err) => log::warn!("Failed to apply retention policy: {err}"),
+        }
+    }
+}
+
+/// Advisories which are deprecated, because a newer revision was ingested longer ago than `age`
+async fn superseded_advisories<C: ConnectionTrait>(
+    age: Duration,
+    connection: &C,
+) -> Result<Vec<ExpiredDocument>, Error> {
+    let before = OffsetDateTime::now_utc() - age;
+
+    Ok(
+        ExpiredDocument::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+SELECT a.id, a.identifier AS name, d.sha256 AS digest
+FROM advisory a
+    JOIN source_document d ON d.id = a.source_document_id
+WHERE a.deprecated
+    AND EXISTS (
+        SELECT 1
+        FROM advisory latest
+            JOIN source_document ld ON ld.id = latest.source_document_id
+        WHERE latest.identifier = a.identifier
+            AND NOT latest.deprecated
+            AND ld.ingested < $1
+    )
+ORDER BY a.identifier, a.id
+"#,
+            [before.into()],
+        ))
+        .all(connection)
+        .await?,
+    )
+}
+
+/// SBOMs which are not among the latest `keep` ones, of those sharing the same label values
+async fn outdated_sboms<C: ConnectionTrait>(
+    labels: &[String],
+    keep: u32,
+    connection: &C,
+) -> Result<Vec<ExpiredDocument>, Error> {
+    let partition = (1..=labels.len())
+        .map(|n| format!("s.labels->>${n}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let n = labels.len();
+
+    let sql = format!(
+        r#"
+SELECT id, name, digest
+FROM (
+    SELECT
+        s.sbom_id AS id,
+        s.document_id AS name,
+        d.sha256 AS digest,
+        row_number() OVER (
+            PARTITION BY {partition}
+            ORDER BY d.ingested DESC, s.sbom_id DESC
+        ) AS n
+    FROM sbom s
+        JOIN source_document d ON d.id = s.source_document_id
+    WHERE s.labels ?& ${keys}
+) ranked
+WHERE n > ${keep}
+ORDER BY name, id
+"#,
+        keys = n + 1,
+        keep = n + 2,
+    );
+
+    let labels: Vec<Value> = labels.iter().map(|label| label.clone().into()).collect();
+    let mut values = labels.clone();
+    values.push(Value::Array(ArrayType::String, Some(Box::new(labels))));
+    values.push(i64::from(keep).into());
+
+    Ok(
+        ExpiredDocument::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            sql,
+            values,
+        ))
+        .all(connection)
+        .await?,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use test_context::test_context;
+    use test_log::test;
+    use trustify_common::id::Id;
+    use trustify_entity::labels::Labels;
+    use trustify_module_ingestor::model::IngestResult;
+    use trustify_test_context::{TrustifyContext, document_bytes};
+
+    fn labels(product: &str, version: &str) -> Labels {
+        Labels::new()
+            .add("product", product)
+            .add("version", version)
+    }
+
+    /// Ingest CVE-2021-32714 twice, as two different revisions
+    async fn revisions(ctx: &TrustifyContext) -> anyhow::Result<(IngestResult, IngestResult)> {
+        let mut cve: serde_json::Value =
+            serde_json::from_slice(&document_bytes("cve/CVE-2021-32714.json").await?)?;
+        let first = ctx.ingest_json(cve.clone()).await?;
+
+        cve["cveMetadata"]["dateUpdated"] = "2024-01-01T00:00:00.000Z".into();
+        let second = ctx.ingest_json(cve).await?;
+
+        Ok((first, second))
+    }
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn superseded_advisories(ctx: &TrustifyContext) -> anyhow::Result<()> {
+        let (first, second) = revisions(ctx).await?;
+        let service = RetentionService::new(PaginationCache::for_test());
+
+        // the latest revision is too recent
+        let policy = RetentionPolicy {
+            superseded_advisories: Some(Duration::from_secs(86400)),
+            ..Default::default()
+        };
+        let report = service.apply(&policy, false, &ctx.db, &ctx.storage).await?;
+        assert!(report.is_empty());
+
+        // dry run
+        let policy = RetentionPolicy {
+            superseded_advisories: Some(Duration::ZERO),
+            ..Default::default()
+        };
+        let report = service.apply(&policy, true, &ctx.db, &ctx.storage).await?;
+        assert!(report.dry_run);
+        assert_eq!(report.advisories.len(), 1);
+        assert_eq!(report.advisories[0].id.to_string(), first.id);
+        assert_eq!(report.advisories[0].name.as_deref(), Some("CVE-2021-32714"));
+
+        // delete, keeping the latest revision
+        let report = service.apply(&policy, false, &ctx.db, &ctx.storage).await?;
+        assert!(!report.dry_run);
+        assert_eq!(report.advisories.len(), 1);
+
+        let advisory = AdvisoryService::new(PaginationCache::for_test());
+        assert!(
+            advisory
+                .fetch_advisory(Id::parse_uuid(&first.id)?, &ctx.db)
+                .await?
+                .is_none()
+        );
+        assert!(
+            advisory
+                .fetch_advisory(Id::parse_uuid(&second.id)?, &ctx.db)
+                .await?
+                .is_some()
+        );
+        assert!(
+            ctx.storage
+                .retrieve(StorageKey::from_sha256(&report.advisories[0].digest))
+                .await
+                .map_err(|err| anyhow::anyhow!("{err}"))?
+                .is_none()
+        );
+
+        // nothing left to do
+        let report = service.apply(&policy, false, &ctx.db, &ctx.storage).await?;
+        assert!(report.is_empty());
+
+        Ok(())
+    }
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn outdated_sboms(ctx: &TrustifyContext) -> anyhow::Result<()> {
+        use trustify_module_ingestor::service::Format;
+
+        let old = ctx
+            .ingest_document_as(
+                "zookeeper-3.9.2-cyclonedx.json",
+                Format::SBOM,
+                labels("p", "1"),
+            )
+            .await?;
+        let latest = ctx
+            .ingest_document_as(
+                "ubi9-9.2-755.1697625012.json",
+                Format::SBOM,
+                labels("p", "1"),
+            )
+            .await?;
+        let other = ctx
+            .ingest_document_as(
+                "quarkus-bom-2.13.8.Final-redhat-00004.json",
+                Format::SBOM,
+                labels("p", "2"),
+            )
+            .await?;
+        // missing the version label
+        let unlabeled = ctx
+            .ingest_document_as(
+                "spdx/OCP-TOOLS-4.11-RHEL-8.json",
+                Format::SBOM,
+                ("product", "p"),
+            )
+            .await?;
+
+        let service = RetentionService::new(PaginationCache::for_test());
+        let policy = RetentionPolicy {
+            sbom_labels: vec!["product".into(), "version".into()],
+            sbom_keep: 1,
+            ..Default::default()
+        };
+
+        let report = service.apply(&policy, true, &ctx.db, &ctx.storage).await?;
+        assert_eq!(
+            report
+                .sboms
+                .iter()
+                .map(|sbom| sbom.id.to_string())
+                .collect::<Vec<_>>(),
+            vec![old.id.clone()]
+        );
+
+        // keeping two, nothing gets deleted
+        let report = service
+            .apply(
+                &RetentionPolicy {
+                    sbom_keep: 2,
+                    ..policy.clone()
+                },
+                false,
+                &ctx.db,
+                &ctx.storage,
+            )
+            .await?;
+        assert!(report.is_empty());
+
+        let report = service.apply(&policy, false, &ctx.db, &ctx.storage).await?;
+        assert_eq!(report.sboms.len(), 1);
+
+        // keeping none is rejected, instead of deleting all of them
+        let result = service
+            .apply(
+                &RetentionPolicy {
+                    sbom_keep: 0,
+                    ..policy.clone()
+                },
+                false,
+                &ctx.db,
+                &ctx.storage,
+            )
+            .await;
+        assert!(matches!(result, Err(Error::BadRequest(..))));
+
+        let sbom = SbomService::new(PaginationCache::for_test());
+        for (result, exists) in [
+            (old, false),
+            (latest, true),
+            (other, true),
+            (unlabeled, true),
+        ] {
+            let found = sbom
+                .fetch_sbom_summary(Id::parse_uuid(&result.id)?, &ctx.db)
+                .await?;
+            assert_eq!(found.is_some(), exists, "{}", result.id);
+        }
+
+        Ok(())
+    }
+}
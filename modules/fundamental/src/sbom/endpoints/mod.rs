@@ -10,23 +10,32 @@ use uuid::Uuid;
 
 use crate::{
     Error,
-    common::LicenseRefMapping,
+    common::{LicenseRefMapping, service::lock_unreferenced},
+    exception::{
+        endpoints::ExceptedParams,
+        service::{ExceptionService, suppress_advisories},
+    },
     license::{
         get_sanitize_filename,
+        model::SbomLicenseReport,
         service::{LicenseService, license_export::LicenseExporter},
     },
     sbom::{
         model::{
             SbomExternalPackageReference, SbomModel, SbomNodeReference, SbomPackage,
-            SbomPackageRelation, SbomSummary, Which, details::SbomAdvisory,
+            SbomPackageRelation, SbomPurlMatch, SbomSummary, Which,
+            checksum::{ArtifactChecksum, SbomPackageMatch},
+            details::SbomAdvisory,
+            openvex::OpenVexDocument,
+            spdx::SpdxDocument,
         },
         service::{SbomService, sbom::FetchOptions},
     },
     sbom_group::service::SbomGroupService,
+    source_document::download::download_response,
 };
-use actix_web::{HttpResponse, Responder, delete, get, http::header, post, web};
+use actix_web::{HttpRequest, HttpResponse, Responder, delete, get, http::header, post, web};
 use config::Config;
-use futures_util::TryStreamExt;
 use sea_orm::TransactionTrait;
 use serde_qs::actix::QsQuery;
 use std::str::FromStr;
@@ -37,14 +46,17 @@ use trustify_auth::{
 };
 use trustify_common::{
     db::{self, pagination_cache::PaginationCache, query::Query},
-    decompress::decompress_async,
+    decompress::decompress_async_reader,
     id::Id,
     model::{BinaryData, Paginated, PaginatedResults},
 };
 use trustify_entity::{labels::Labels, relationship::Relationship};
 use trustify_module_ingestor::{
     model::IngestResult,
-    service::{Cache, Format, IngestorService},
+    service::{
+        Cache, Format, IngestOptions, IngestorService, ValidationLevel, signature::Signature,
+        stream,
+    },
 };
 use trustify_module_storage::service::{StorageBackend, StorageKey};
 
@@ -62,20 +74,26 @@ pub fn configure(
     upload_limit: usize,
     cache: PaginationCache,
 ) {
-    let sbom_service = SbomService::new(cache);
+    let sbom_service = SbomService::new(cache.clone());
+    let exception_service = ExceptionService::new(cache);
 
     config
         .app_data(web::Data::new(db_rw))
         .app_data(web::Data::new(db_ro))
         .app_data(web::Data::new(sbom_service))
+        .app_data(web::Data::new(exception_service))
         .app_data(web::Data::new(Config { upload_limit }))
         .service(v2::all)
         .service(v3::all)
         .service(all_related)
         .service(count_related)
+        .service(by_purl)
+        .service(by_checksum)
         .service(all_models)
         .service(get)
         .service(get_sbom_advisories)
+        .service(export_spdx)
+        .service(export_openvex)
         .service(delete)
         .service(delete_many)
         .service(packages)
@@ -87,6 +105,7 @@ pub fn configure(
         .service(label::update)
         .service(label::all)
         .service(get_unique_licenses)
+        .service(get_license_report)
         .service(get_license_export);
 }
 
@@ -119,6 +138,38 @@ pub async fn get_unique_licenses(
     }
 }
 
+/// Get the license inventory of an SBOM.
+///
+/// Lists the licenses with the number of packages using them, and the packages without any
+/// license information.
+#[utoipa::path(
+    tag = "sbom",
+    operation_id = "getSbomLicenseReport",
+    params(
+        ("id" = String, Path, description = "ID of the SBOM"),
+    ),
+    responses(
+        (status = 200, description = "The license inventory of the SBOM", body = SbomLicenseReport),
+        (status = 400, description = "Invalid ID format"),
+        (status = 404, description = "The SBOM could not be found"),
+    ),
+)]
+#[get("/v3/sbom/{id}/license-report")]
+pub async fn get_license_report(
+    fetcher: web::Data<LicenseService>,
+    db: web::Data<db::ReadOnly>,
+    id: web::Path<String>,
+    _: Require<ReadSbom>,
+) -> actix_web::Result<impl Responder> {
+    let id = Id::from_str(&id).map_err(Error::IdKey)?;
+    let tx = db.begin().await?;
+
+    match fetcher.license_report(id, &tx).await? {
+        Some(report) => Ok(HttpResponse::Ok().json(report)),
+        None => Ok(HttpResponse::NotFound().finish()),
+    }
+}
+
 #[utoipa::path(
     tag = "sbom",
     operation_id = "getLicenseExport",
@@ -296,6 +347,78 @@ pub async fn all_related(
     Ok(HttpResponse::Ok().json(result))
 }
 
+/// Find all SBOMs containing a package matching the provided PURL.
+///
+/// In contrast to `/v3/sbom/by-package`, the PURL doesn't need to match exactly. A PURL without a
+/// version matches all versions of the package, which can be limited further using a version range.
+/// Qualifiers are ignored.
+#[utoipa::path(
+    tag = "sbom",
+    operation_id = "listSbomsByPurl",
+    params(
+        Query,
+        Paginated,
+        PurlRangeQuery,
+    ),
+    responses(
+        (status = 200, description = "Matching SBOMs", body = PaginatedResults<SbomPurlMatch>),
+        (status = 400, description = "Invalid PURL or version range"),
+    ),
+)]
+#[get("/v3/sbom/by-purl")]
+pub async fn by_purl(
+    sbom: web::Data<SbomService>,
+    db: web::Data<db::ReadOnly>,
+    web::Query(search): web::Query<Query>,
+    web::Query(paginated): web::Query<Paginated>,
+    web::Query(PurlRangeQuery { purl, range }): web::Query<PurlRangeQuery>,
+    _: Require<ReadSbom>,
+) -> actix_web::Result<impl Responder> {
+    let tx = db.begin().await?;
+
+    let result = sbom
+        .find_sboms_by_purl(&purl, range.as_ref(), paginated, search, &tx)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(result))
+}
+
+/// Find all packages whose artifact has the provided checksum, along with their SBOMs.
+///
+/// The checksum is provided as `<algorithm>:<hex value>`, supported algorithms are `md5`, `sha1`,
+/// `sha256`, `sha384`, and `sha512`.
+#[utoipa::path(
+    tag = "sbom",
+    operation_id = "listPackagesByChecksum",
+    params(
+        ("checksum", Path, description = "Checksum of the artifact, e.g. `sha256:<hex>`"),
+        Paginated,
+    ),
+    responses(
+        (status = 200, description = "Matching packages", body = PaginatedResults<SbomPackageMatch>),
+        (status = 400, description = "Invalid checksum"),
+    ),
+)]
+#[get("/v3/sbom/by-checksum/{checksum}")]
+pub async fn by_checksum(
+    sbom: web::Data<SbomService>,
+    db: web::Data<db::ReadOnly>,
+    checksum: web::Path<String>,
+    web::Query(paginated): web::Query<Paginated>,
+    _: Require<ReadSbom>,
+) -> actix_web::Result<impl Responder> {
+    let checksum = ArtifactChecksum::from_str(&checksum).map_err(|err| {
+        Error::BadRequest("Invalid checksum".into(), Some(err.to_string().into()))
+    })?;
+    let tx = db.begin().await?;
+
+    let result = sbom
+        .find_packages_by_checksum(&checksum, paginated, &tx)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(result))
+}
+
 /// Count all SBOMs containing the provided packages.
 ///
 /// The packages can be provided either via a PURL or using the ID of a package as returned by
@@ -363,6 +486,7 @@ pub async fn get(
     operation_id = "getSbomAdvisories",
     params(
         ("id" = Id, Path),
+        ExceptedParams,
     ),
     responses(
         (status = 200, description = "Matching SBOM", body = Vec<SbomAdvisory>),
@@ -372,8 +496,10 @@ pub async fn get(
 #[get("/v3/sbom/{id}/advisory")]
 pub async fn get_sbom_advisories(
     fetcher: web::Data<SbomService>,
+    exceptions: web::Data<ExceptionService>,
     db: web::Data<db::ReadOnly>,
     id: web::Path<String>,
+    web::Query(ExceptedParams { excepted }): web::Query<ExceptedParams>,
     _: Require<GetSbomAdvisories>,
 ) -> actix_web::Result<impl Responder> {
     let id = Id::from_str(&id).map_err(Error::IdKey)?;
@@ -381,14 +507,104 @@ pub async fn get_sbom_advisories(
 
     let statuses: Vec<String> = vec!["affected".to_string()];
     match fetcher.fetch_sbom_details(id, statuses, &tx).await? {
-        Some(v) => Ok(HttpResponse::Ok().json(v.advisories)),
+        Some(v) if excepted => Ok(HttpResponse::Ok().json(v.advisories)),
+        Some(v) => {
+            let suppressed = exceptions
+                .suppressed_for_sbom(v.summary.head.id, &tx)
+                .await?;
+            Ok(HttpResponse::Ok().json(suppress_advisories(v.advisories, &suppressed)))
+        }
         None => Ok(HttpResponse::NotFound().finish()),
     }
 }
 
 all!(GetSbomAdvisories -> ReadSbom, ReadAdvisory);
 
-async fn delete_blobs<T: StorageBackend>(digests: &[String], storage: &T) {
+/// Export an SBOM as SPDX 2.3 document
+///
+/// The document is built from the stored information, independent of the format the SBOM was
+/// ingested in.
+#[utoipa::path(
+    tag = "sbom",
+    operation_id = "exportSbomSpdx",
+    params(
+        ("id" = Id, Path),
+    ),
+    responses(
+        (status = 200, description = "The SBOM, as SPDX 2.3 JSON document", body = SpdxDocument),
+        (status = 404, description = "The SBOM could not be found"),
+    ),
+)]
+#[get("/v3/sbom/{id}/spdx")]
+pub async fn export_spdx(
+    fetcher: web::Data<SbomService>,
+    db: web::Data<db::ReadOnly>,
+    id: web::Path<String>,
+    _: Require<ReadSbom>,
+) -> actix_web::Result<impl Responder> {
+    let id = Id::from_str(&id).map_err(Error::IdKey)?;
+    let tx = db.begin().await?;
+
+    match fetcher.export_spdx(id, &tx).await? {
+        Some(v) => Ok(HttpResponse::Ok().json(v)),
+        None => Ok(HttpResponse::NotFound().finish()),
+    }
+}
+
+/// Generate an OpenVEX document for an SBOM
+///
+/// The statements reflect the statuses of the SBOM's packages, as stated by the stored advisories.
+/// Vulnerabilities with an active exception are omitted, unless requested.
+#[utoipa::path(
+    tag = "sbom",
+    operation_id = "exportSbomOpenVex",
+    params(
+        ("id" = Id, Path),
+        ExceptedParams,
+    ),
+    responses(
+        (status = 200, description = "The VEX statements of the SBOM, as OpenVEX document", body = OpenVexDocument),
+        (status = 404, description = "The SBOM could not be found"),
+    ),
+)]
+#[get("/v3/sbom/{id}/openvex")]
+pub async fn export_openvex(
+    fetcher: web::Data<SbomService>,
+    exceptions: web::Data<ExceptionService>,
+    db: web::Data<db::ReadOnly>,
+    id: web::Path<String>,
+    web::Query(ExceptedParams { excepted }): web::Query<ExceptedParams>,
+    _: Require<GetSbomAdvisories>,
+) -> actix_web::Result<impl Responder> {
+    let id = Id::from_str(&id).map_err(Error::IdKey)?;
+    let tx = db.begin().await?;
+
+    let exceptions = (!excepted).then_some(exceptions.get_ref());
+    match fetcher.export_openvex(id, exceptions, &tx).await? {
+        Some(v) => Ok(HttpResponse::Ok().json(v)),
+        None => Ok(HttpResponse::NotFound().finish()),
+    }
+}
+
+/// Delete the blobs of deleted SBOMs from the storage, unless they are still referenced
+async fn delete_blobs<C: TransactionTrait, T: StorageBackend>(
+    digests: Vec<String>,
+    db: &C,
+    storage: &T,
+) {
+    let locked = async {
+        let tx = db.begin().await?;
+        let digests = lock_unreferenced(digests, &tx).await?;
+        Ok::<_, Error>((tx, digests))
+    };
+    let (tx, digests) = match locked.await {
+        Ok(locked) => locked,
+        Err(e) => {
+            log::error!("Failed to check SBOMs for references, keeping them in the storage: {e}");
+            return;
+        }
+    };
+
     if let Err(e) = storage
         .delete_many(
             &digests
@@ -400,6 +616,11 @@ async fn delete_blobs<T: StorageBackend>(digests: &[String], storage: &T) {
     {
         log::error!("Failed to remove SBOMs from the storage: {e:#?}");
     }
+
+    // only release the locks once removed
+    if let Err(e) = tx.commit().await {
+        log::warn!("Failed to release the locks of removed SBOMs: {e}");
+    }
 }
 
 /// Delete an SBOM
@@ -429,7 +650,7 @@ pub async fn delete(
         && !digests.is_empty()
     {
         tx.commit().await?;
-        delete_blobs(&digests, i.storage()).await;
+        delete_blobs(digests, db.as_ref(), i.storage()).await;
     }
     Ok(HttpResponse::NoContent().finish())
 }
@@ -466,7 +687,7 @@ pub async fn delete_many(
 
     if !digests.is_empty() {
         tx.commit().await?;
-        delete_blobs(&digests, i.storage()).await;
+        delete_blobs(digests, db.as_ref(), i.storage()).await;
     }
 
     Ok(HttpResponse::NoContent().finish())
@@ -650,6 +871,11 @@ struct UploadQuery {
     #[param(inline)]
     cache: Cache,
 
+    /// How to deal with documents violating their specification.
+    #[serde(default)]
+    #[param(inline)]
+    level: ValidationLevel,
+
     /// Optional group IDs to assign the SBOM to after ingestion.
     ///
     /// If one or more group IDs are invalid, the upload will fail with 400 Bad Request
@@ -668,10 +894,14 @@ const fn default_format() -> Format {
     request_body = Vec <u8>,
     params(
         UploadQuery,
+        ("x-sigstore-bundle" = Option<String>, Header, description = "A Sigstore bundle of the document, base64 encoded"),
+        ("x-signature" = Option<String>, Header, description = "A detached signature of the document, base64 encoded"),
+        ("x-signing-certificate" = Option<String>, Header, description = "The certificate of the detached signature, base64 encoded"),
     ),
     responses(
         (status = 201, description = "Upload an SBOM", body = IngestResult),
         (status = 400, description = "The file could not be parsed as an SBOM"),
+        (status = 400, description = "The signature of the document is invalid or untrusted"),
         (status = 400, description = "One or more group IDs are invalid or do not exist"),
     )
 )]
@@ -687,20 +917,36 @@ pub async fn upload(
         labels,
         format,
         cache,
+        level,
         group,
     }): QsQuery<UploadQuery>,
     content_type: Option<web::Header<header::ContentType>>,
-    bytes: web::Bytes,
+    req: HttpRequest,
+    payload: web::Payload,
     _: Require<CreateSbom>,
 ) -> Result<impl Responder, Error> {
-    let bytes = decompress_async(bytes, content_type.map(|ct| ct.0), config.upload_limit).await??;
+    let signature = Signature::from_headers(req.headers()).map_err(Error::Ingestor)?;
 
     let tx = db.begin().await?;
 
-    let mut result = ingestor
-        .ingest(&bytes, format, labels, None, cache, &tx)
-        .await
-        .map_err(Error::Ingestor)?;
+    let mut result = stream::with_reader(payload, async |reader| {
+        let reader =
+            decompress_async_reader(reader, content_type.map(|ct| ct.0), config.upload_limit)
+                .await?;
+        ingestor
+            .ingest_stream(
+                reader,
+                format,
+                labels,
+                None,
+                cache,
+                IngestOptions::default().level(level).signature(signature),
+                &tx,
+            )
+            .await
+            .map_err(Error::Ingestor)
+    })
+    .await?;
 
     if !group.is_empty() {
         sbom_group
@@ -729,7 +975,9 @@ pub async fn upload(
     ),
     responses(
         (status = 200, description = "Download a an SBOM", body = inline(BinaryData)),
+        (status = 206, description = "Download the requested range of the document", body = inline(BinaryData)),
         (status = 404, description = "The document could not be found"),
+        (status = 416, description = "The requested range is not satisfiable"),
     )
 )]
 #[get("/v3/sbom/{key}/download")]
@@ -738,6 +986,7 @@ pub async fn download(
     db: web::Data<db::ReadOnly>,
     sbom: web::Data<SbomService>,
     key: web::Path<String>,
+    request: HttpRequest,
     _: Require<ReadSbom>,
 ) -> Result<impl Responder, Error> {
     let id = Id::from_str(&key).map_err(Error::IdKey)?;
@@ -747,15 +996,5 @@ pub async fn download(
         return Ok(HttpResponse::NotFound().finish());
     };
 
-    let stream = ingestor
-        .storage()
-        .retrieve(sbom.source_document.try_into()?)
-        .await
-        .map_err(Error::Storage)?
-        .map(|stream| stream.map_err(Error::Storage));
-
-    Ok(match stream {
-        Some(s) => HttpResponse::Ok().streaming(s),
-        None => HttpResponse::NotFound().finish(),
-    })
+    download_response(&request, ingestor.storage(), &sbom.source_document).await
 }
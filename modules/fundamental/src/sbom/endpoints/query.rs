@@ -2,7 +2,7 @@ use crate::sbom::model::SbomExternalPackageReference;
 use actix_http::body::BoxBody;
 use actix_web::{HttpResponse, ResponseError};
 use std::fmt::{Display, Formatter};
-use trustify_common::{cpe::Cpe, error::ErrorInformation, purl::Purl};
+use trustify_common::{cpe::Cpe, error::ErrorInformation, purl::Purl, vers::Vers};
 
 #[derive(Clone, Debug, serde::Deserialize, utoipa::IntoParams, utoipa::ToSchema)]
 pub struct ExternalReferenceQuery {
@@ -59,3 +59,14 @@ impl<'a> TryFrom<&'a ExternalReferenceQuery> for SbomExternalPackageReference<'a
         })
     }
 }
+
+#[derive(Clone, Debug, serde::Deserialize, utoipa::IntoParams)]
+pub struct PurlRangeQuery {
+    /// The PURL of the package, versions and qualifiers are optional
+    pub purl: Purl,
+    /// A version range the package version must be in, using the `vers` syntax, e.g.
+    /// `vers:maven/>=1.0|<2.0`. Only valid with a PURL without a version.
+    #[serde(default)]
+    #[param(value_type = Option<String>)]
+    pub range: Option<Vers>,
+}
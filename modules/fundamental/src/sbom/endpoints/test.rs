@@ -21,7 +21,9 @@ use std::{collections::HashMap, io::Read, str::FromStr};
 use test_context::test_context;
 use test_log::test;
 use trustify_common::{id::Id, model::PaginatedResults};
-use trustify_module_ingestor::{model::IngestResult, service::Format};
+use trustify_module_ingestor::{
+    graph::product::ProductInformation, model::IngestResult, service::Format,
+};
 use trustify_module_storage::service::{StorageBackend, StorageKey};
 use trustify_test_context::{
     IngestionResult, TrustifyContext, call::CallService, document_bytes, subset::ContainsSubset,
@@ -1654,6 +1656,181 @@ async fn query_sboms_by_package(ctx: &TrustifyContext) -> Result<(), anyhow::Err
     Ok(())
 }
 
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn query_sboms_by_purl(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    let app = caller(ctx).await?;
+    let query = async |purl, range: Option<&str>| {
+        let mut uri = format!("/api/v3/sbom/by-purl?total=true&purl={}", encode(purl));
+        if let Some(range) = range {
+            uri.push_str(&format!("&range={}", encode(range)));
+        }
+        let request = TestRequest::get().uri(&uri).to_request();
+        app.call_service(request).await
+    };
+    let items = async |purl, range| {
+        let response = query(purl, range).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response: Value = serde_json::from_slice(&read_body(response).await).unwrap();
+        tracing::debug!(test = "", "{response:#?}");
+        response
+    };
+    let total = async |purl, range| items(purl, range).await["total"].clone();
+
+    // Ingest 2 SBOM's that depend on the same purl, one of them describing a product version
+    let result = ctx
+        .ingest_documents(["spdx/simple-ext-a.json", "spdx/simple-ext-b.json"])
+        .await?;
+    let sbom_id = Uuid::from_str(&result[0].id)?;
+    ctx.graph
+        .ingest_product("Simple", ProductInformation::default(), &ctx.db)
+        .await?
+        .ingest_product_version("1.0".to_string(), Some(sbom_id), &ctx.db)
+        .await?;
+
+    // any version, ignoring qualifiers
+    assert_eq!(total("pkg:rpm/redhat/A", None).await, 2);
+    assert_eq!(total("pkg:rpm/redhat/Z", None).await, 0);
+
+    // exact version
+    assert_eq!(total("pkg:rpm/redhat/A@0.0.0", None).await, 2);
+    assert_eq!(total("pkg:rpm/redhat/A@1.0.0", None).await, 0);
+
+    // range
+    assert_eq!(total("pkg:rpm/redhat/A", Some("vers:rpm/<1.0.0")).await, 2);
+    assert_eq!(total("pkg:rpm/redhat/A", Some("vers:rpm/>0.0.0")).await, 0);
+    assert_eq!(total("pkg:rpm/redhat/A", Some("vers:rpm/*")).await, 2);
+    assert_eq!(
+        total("pkg:rpm/redhat/A", Some("vers:rpm/0.0.0|1.0.0")).await,
+        2
+    );
+    assert_eq!(total("pkg:rpm/redhat/A", Some("vers:rpm/!=0.0.0")).await, 0);
+
+    // products
+    let response = items("pkg:rpm/redhat/A", None).await;
+    for item in response["items"].as_array().into_iter().flatten() {
+        let products = item["products"].as_array().cloned().unwrap_or_default();
+        match item["id"] == sbom_id.urn().to_string() {
+            true => {
+                assert_eq!(products.len(), 1);
+                assert_eq!(products[0]["product"]["name"], "Simple");
+                assert_eq!(products[0]["version"], "1.0");
+            }
+            false => assert!(products.is_empty()),
+        }
+    }
+
+    // invalid
+    for (purl, range) in [
+        ("pkg:rpm/redhat/A@0.0.0", "vers:rpm/<1.0.0"),
+        ("pkg:rpm/redhat/A", "rpm/<1.0.0"),
+    ] {
+        let response = query(purl, Some(range)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{purl} {range}");
+    }
+
+    Ok(())
+}
+
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn license_report(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    let app = caller(ctx).await?;
+    let id = ctx.ingest_document("spdx/rhelai1_binary.json").await?.id;
+
+    let uri = format!("/api/v3/sbom/urn:uuid:{id}/license-report");
+    let req = TestRequest::get().uri(&uri).to_request();
+    let response: Value = app.call_and_read_body_json(req).await;
+    tracing::debug!(test = "", "{response:#?}");
+
+    assert_eq!(response["packages"], 5);
+
+    // only "CharLS" declares a license, the concluded ones are all NOASSERTION
+    let licenses = response["licenses"].as_array().expect("must be an array");
+    assert_eq!(licenses.len(), 1);
+    assert_eq!(licenses[0]["packages"], 1);
+
+    let unknown = response["unknown"]
+        .as_array()
+        .expect("must be an array")
+        .iter()
+        .filter_map(|package| package["name"].as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        unknown,
+        [
+            "NGX",
+            "nvidia-bootc-1-4",
+            "registry.redhat.io/rhel9-eus/rhel-9.4-bootc",
+            "registry.stage.redhat.io/rhelai1/driver-toolkit-rhel9",
+        ]
+    );
+
+    // not found
+    let uri = format!("/api/v3/sbom/urn:uuid:{}/license-report", Uuid::new_v4());
+    let req = TestRequest::get().uri(&uri).to_request();
+    let response = app.call_service(req).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn query_packages_by_checksum(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    let app = caller(ctx).await?;
+    let id = ctx
+        .ingest_document("cyclonedx/application.cdx.json")
+        .await?
+        .id;
+
+    let query = async |checksum: &str| {
+        let uri = format!("/api/v3/sbom/by-checksum/{}?total=true", encode(checksum));
+        let request = TestRequest::get().uri(&uri).to_request();
+        app.call_service(request).await
+    };
+
+    for checksum in [
+        "sha1:229bb1ef6b14dfbf74ff443af9097d836dc5f0dd",
+        "SHA-1:229BB1EF6B14DFBF74FF443AF9097D836DC5F0DD",
+        "md5:ff52ddc06ddda6cd457a369aa2a7f9a3",
+    ] {
+        let response = query(checksum).await;
+        assert_eq!(response.status(), StatusCode::OK, "{checksum}");
+        let response: Value = serde_json::from_slice(&read_body(response).await)?;
+        tracing::debug!(test = "", "{response:#?}");
+        assert!(
+            response.contains_subset(json!({
+                "total": 1,
+                "items": [{
+                    "sbom": { "id": format!("urn:uuid:{id}") },
+                    "package": {
+                        "name": "logback-classic",
+                        "version": "1.5.8",
+                        "purl": [{ "purl": "pkg:maven/ch.qos.logback/logback-classic@1.5.8?type=jar" }],
+                    },
+                }],
+            })),
+            "{checksum}"
+        );
+    }
+
+    // unknown, but valid
+    let response =
+        query("sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response: Value = serde_json::from_slice(&read_body(response).await)?;
+    assert_eq!(response["total"], 0);
+
+    // invalid
+    for checksum in ["229bb1ef", "crc32:00000000", "sha1:229bb1ef"] {
+        let response = query(checksum).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{checksum}");
+    }
+
+    Ok(())
+}
+
 #[test_context(TrustifyContext)]
 #[test(actix_web::test)]
 async fn query_sboms_by_array_values(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
@@ -2228,3 +2405,127 @@ async fn related_by_hash(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
 
     Ok(())
 }
+
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn export_spdx(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    let app = caller(ctx).await?;
+
+    async fn export(app: &impl CallService, id: &str) -> Value {
+        let req = TestRequest::get()
+            .uri(&format!("/api/v3/sbom/urn:uuid:{id}/spdx"))
+            .to_request();
+        app.call_and_read_body_json(req).await
+    }
+
+    // SPDX, identifiers are kept
+
+    let id = ctx.ingest_document("spdx/simple.json").await?.id;
+    let spdx = export(&app, &id).await;
+    log::debug!("{spdx:#?}");
+
+    assert_eq!(spdx["spdxVersion"], "SPDX-2.3");
+    assert_eq!(spdx["SPDXID"], "SPDXRef-DOCUMENT");
+    assert_eq!(spdx["documentDescribes"], json!(["SPDXRef-A"]));
+    assert_eq!(spdx["packages"].as_array().map(Vec::len), Some(8));
+    assert!(spdx.contains_subset(json!({
+        "relationships": [
+            {
+                "spdxElementId": "SPDXRef-A",
+                "relationshipType": "CONTAINS",
+                "relatedSpdxElement": "SPDXRef-B",
+            },
+            {
+                "spdxElementId": "SPDXRef-DOCUMENT",
+                "relationshipType": "DESCRIBES",
+                "relatedSpdxElement": "SPDXRef-A",
+            },
+        ]
+    })));
+
+    // CycloneDX, references get converted
+
+    let id = ctx.ingest_document("cyclonedx/simple.json").await?.id;
+    let spdx = export(&app, &id).await;
+    log::debug!("{spdx:#?}");
+
+    assert!(spdx.contains_subset(json!({
+        "relationships": [
+            {
+                "spdxElementId": "SPDXRef-bb",
+                "relationshipType": "DEPENDS_ON",
+                "relatedSpdxElement": "SPDXRef-cc",
+            },
+        ]
+    })));
+
+    // not found
+
+    let req = TestRequest::get()
+        .uri(&format!("/api/v3/sbom/urn:uuid:{}/spdx", Uuid::now_v7()))
+        .to_request();
+    let response = app.call_service(req).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn export_openvex(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    let id = ctx
+        .ingest_documents([
+            "quarkus-bom-2.13.8.Final-redhat-00004.json",
+            "csaf/cve-2023-0044.json",
+        ])
+        .await?[0]
+        .id
+        .to_string();
+
+    let app = caller(ctx).await?;
+    let v: Value = app
+        .call_and_read_body_json(
+            TestRequest::get()
+                .uri(&format!("/api/v3/sbom/urn:uuid:{id}/openvex"))
+                .to_request(),
+        )
+        .await;
+
+    log::debug!("{v:#?}");
+
+    assert!(v.contains_subset(json!({
+        "@context": "https://openvex.dev/ns/v0.2.0",
+        "version": 1,
+        "statements": [{
+            "vulnerability": { "name": "CVE-2023-0044" },
+            "status": "affected",
+            "status_notes": "Stated by: https://www.redhat.com/#CVE-2023-0044",
+        }],
+    })));
+
+    let statement = v["statements"]
+        .as_array()
+        .and_then(|statements| {
+            statements
+                .iter()
+                .find(|statement| statement["status"] == "affected")
+        })
+        .expect("must have an affected statement");
+    assert!(statement["action_statement"].is_string());
+    assert!(
+        statement["products"]
+            .as_array()
+            .is_some_and(|products| !products.is_empty())
+    );
+
+    let response = app
+        .call_service(
+            TestRequest::get()
+                .uri("/api/v3/sbom/urn:uuid:00000000-0000-0000-0000-000000000000/openvex")
+                .to_request(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    Ok(())
+}
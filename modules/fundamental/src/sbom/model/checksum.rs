@@ -0,0 +1,143 @@
+use crate::sbom::model::{SbomHead, SbomPackage};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+use utoipa::ToSchema;
+
+/// A package of an SBOM, found by the checksum of its artifact
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct SbomPackageMatch {
+    /// The SBOM containing the package
+    pub sbom: SbomHead,
+    /// The package carrying the checksum
+    pub package: SbomPackage,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum ChecksumError {
+    #[error("checksum must be in the format '<algorithm>:<value>'")]
+    MissingAlgorithm,
+    #[error("unsupported checksum algorithm: {0}")]
+    UnsupportedAlgorithm(String),
+    #[error("checksum value must be {0} hex digits")]
+    InvalidValue(usize),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChecksumAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl ChecksumAlgorithm {
+    /// The checksum type, as stored during ingestion
+    pub fn as_type(&self) -> &'static str {
+        match self {
+            Self::Md5 => "MD5",
+            Self::Sha1 => "SHA-1",
+            Self::Sha256 => "SHA-256",
+            Self::Sha384 => "SHA-384",
+            Self::Sha512 => "SHA-512",
+        }
+    }
+
+    /// The number of hex digits of a value
+    fn len(&self) -> usize {
+        match self {
+            Self::Md5 => 32,
+            Self::Sha1 => 40,
+            Self::Sha256 => 64,
+            Self::Sha384 => 96,
+            Self::Sha512 => 128,
+        }
+    }
+}
+
+/// The checksum of an artifact, like `sha256:<hex>`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArtifactChecksum {
+    pub algorithm: ChecksumAlgorithm,
+    /// The value, as lowercase hex digits
+    pub value: String,
+}
+
+impl FromStr for ArtifactChecksum {
+    type Err = ChecksumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (algorithm, value) = s.split_once(':').ok_or(ChecksumError::MissingAlgorithm)?;
+
+        let algorithm = match algorithm.to_lowercase().replace('-', "").as_str() {
+            "md5" => ChecksumAlgorithm::Md5,
+            "sha1" => ChecksumAlgorithm::Sha1,
+            "sha256" => ChecksumAlgorithm::Sha256,
+            "sha384" => ChecksumAlgorithm::Sha384,
+            "sha512" => ChecksumAlgorithm::Sha512,
+            _ => return Err(ChecksumError::UnsupportedAlgorithm(algorithm.to_string())),
+        };
+
+        if value.len() != algorithm.len() || !value.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ChecksumError::InvalidValue(algorithm.len()));
+        }
+
+        Ok(Self {
+            algorithm,
+            value: value.to_lowercase(),
+        })
+    }
+}
+
+impl Display for ArtifactChecksum {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let algorithm = match self.algorithm {
+            ChecksumAlgorithm::Md5 => "md5",
+            ChecksumAlgorithm::Sha1 => "sha1",
+            ChecksumAlgorithm::Sha256 => "sha256",
+            ChecksumAlgorithm::Sha384 => "sha384",
+            ChecksumAlgorithm::Sha512 => "sha512",
+        };
+        write!(f, "{algorithm}:{}", self.value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("md5:D41D8CD98F00B204E9800998ECF8427E", ChecksumAlgorithm::Md5)]
+    #[case(
+        "SHA-1:da39a3ee5e6b4b0d3255bfef95601890afd80709",
+        ChecksumAlgorithm::Sha1
+    )]
+    #[case(
+        "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        ChecksumAlgorithm::Sha256
+    )]
+    fn parse(#[case] input: &str, #[case] algorithm: ChecksumAlgorithm) {
+        let checksum = ArtifactChecksum::from_str(input).unwrap();
+        assert_eq!(checksum.algorithm, algorithm);
+        assert_eq!(
+            checksum.value,
+            input.split_once(':').unwrap().1.to_lowercase()
+        );
+    }
+
+    #[rstest]
+    #[case("d41d8cd98f00b204e9800998ecf8427e", ChecksumError::MissingAlgorithm)]
+    #[case("crc32:00000000", ChecksumError::UnsupportedAlgorithm("crc32".into()))]
+    #[case("md5:d41d8cd98f00b204", ChecksumError::InvalidValue(32))]
+    #[case(
+        "md5:x41d8cd98f00b204e9800998ecf8427e",
+        ChecksumError::InvalidValue(32)
+    )]
+    fn parse_error(#[case] input: &str, #[case] error: ChecksumError) {
+        assert_eq!(ArtifactChecksum::from_str(input), Err(error));
+    }
+}
@@ -1,10 +1,14 @@
+pub mod checksum;
 pub mod details;
+pub mod openvex;
 pub mod raw_sql;
+pub mod spdx;
 
 use super::service::SbomService;
 use crate::{
     Error,
     common::{LicenseInfo, LicenseRefMapping},
+    product::model::ProductHead,
     purl::model::summary::purl::PurlSummary,
     sbom::service::sbom::IntoPackage,
     source_document::model::SourceDocument,
@@ -106,6 +110,23 @@ impl<P: IntoPackage> SbomSummary<P> {
     }
 }
 
+/// An SBOM containing a package, along with the product versions it describes
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct SbomPurlMatch {
+    #[serde(flatten)]
+    pub sbom: SbomSummary,
+
+    /// The product versions the SBOM is assigned to
+    pub products: Vec<SbomProductVersion>,
+}
+
+/// A version of a product, described by an SBOM
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct SbomProductVersion {
+    pub product: ProductHead,
+    pub version: String,
+}
+
 #[derive(FromQueryResult)]
 pub struct ModelCatcher {
     pub id: String,
@@ -153,7 +174,9 @@ pub struct SbomPackage {
     pub licenses_ref_mapping: Vec<LicenseRefMapping>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema, Default)]
+#[derive(
+    Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema, Default, FromQueryResult,
+)]
 pub struct SbomPackageSummary {
     /// The SBOM internal ID of a package
     pub id: String,
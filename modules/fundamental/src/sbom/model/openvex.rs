@@ -0,0 +1,78 @@
+//! A subset of the OpenVEX 0.2.0 format, as produced when generating VEX for an SBOM.
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+pub const CONTEXT: &str = "https://openvex.dev/ns/v0.2.0";
+pub const AUTHOR: &str = "Trustify";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct OpenVexDocument {
+    #[serde(rename = "@context")]
+    pub context: String,
+    #[serde(rename = "@id")]
+    pub id: String,
+    pub author: String,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String)]
+    pub timestamp: OffsetDateTime,
+    pub version: u32,
+    pub statements: Vec<OpenVexStatement>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct OpenVexStatement {
+    pub vulnerability: OpenVexVulnerability,
+    pub products: Vec<OpenVexProduct>,
+    pub status: OpenVexStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_notes: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impact_statement: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action_statement: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct OpenVexVulnerability {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct OpenVexProduct {
+    #[serde(rename = "@id")]
+    pub id: String,
+    pub identifiers: OpenVexIdentifiers,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct OpenVexIdentifiers {
+    pub purl: String,
+}
+
+#[derive(
+    Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum OpenVexStatus {
+    NotAffected,
+    Affected,
+    Fixed,
+    UnderInvestigation,
+}
+
+impl OpenVexStatus {
+    /// Map the slug of a stored status, `None` if it has no VEX equivalent.
+    pub fn from_slug(slug: &str) -> Option<Self> {
+        match slug {
+            "not_affected" => Some(Self::NotAffected),
+            "affected" => Some(Self::Affected),
+            "fixed" => Some(Self::Fixed),
+            "under_investigation" => Some(Self::UnderInvestigation),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! A subset of the SPDX 2.3 JSON format, as produced when exporting an SBOM.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+pub const SPDX_VERSION: &str = "SPDX-2.3";
+pub const DATA_LICENSE: &str = "CC0-1.0";
+pub const DOCUMENT_ID: &str = "SPDXRef-DOCUMENT";
+pub const NOASSERTION: &str = "NOASSERTION";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxDocument {
+    pub spdx_version: String,
+    pub data_license: String,
+    #[serde(rename = "SPDXID")]
+    pub spdx_id: String,
+    pub name: String,
+    pub document_namespace: String,
+    pub creation_info: SpdxCreationInfo,
+    pub document_describes: Vec<String>,
+    pub packages: Vec<SpdxPackage>,
+    pub relationships: Vec<SpdxRelationship>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub has_extracted_licensing_infos: Vec<SpdxExtractedLicensingInfo>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxCreationInfo {
+    pub created: String,
+    pub creators: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxPackage {
+    #[serde(rename = "SPDXID")]
+    pub spdx_id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_info: Option<String>,
+    pub download_location: String,
+    pub files_analyzed: bool,
+    pub license_concluded: String,
+    pub license_declared: String,
+    pub copyright_text: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub external_refs: Vec<SpdxExternalRef>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxExternalRef {
+    pub reference_category: String,
+    pub reference_type: String,
+    pub reference_locator: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxRelationship {
+    pub spdx_element_id: String,
+    pub relationship_type: String,
+    pub related_spdx_element: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxExtractedLicensingInfo {
+    pub license_id: String,
+    pub extracted_text: String,
+}
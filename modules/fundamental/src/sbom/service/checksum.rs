@@ -0,0 +1,126 @@
+use super::{
+    SbomService,
+    sbom::{IntoPackage, PackageCatcher, join_licenses, join_purls_and_cpes},
+};
+use crate::{
+    Error,
+    sbom::model::{
+        SbomHead, SbomPackage,
+        checksum::{ArtifactChecksum, SbomPackageMatch},
+    },
+};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, EntityTrait, FromQueryResult, QueryFilter, QueryOrder,
+    QuerySelect, QueryTrait, RelationTrait, prelude::Uuid,
+};
+use sea_query::{Expr, JoinType};
+use std::collections::{BTreeSet, HashMap};
+use tracing::instrument;
+use trustify_common::{
+    db::limiter::{LimitedResult, limit_selector},
+    model::{PaginatedResults, Pagination},
+};
+use trustify_entity::{sbom, sbom_node, sbom_node_checksum, sbom_package};
+
+#[derive(FromQueryResult)]
+struct ChecksumCatcher {
+    sbom_id: Uuid,
+    #[sea_orm(nested)]
+    package: PackageCatcher,
+}
+
+impl SbomService {
+    /// Find all packages, across all SBOMs, whose artifact has the provided checksum.
+    ///
+    /// This uses the checksums recorded in SPDX and CycloneDX documents, allowing to identify a
+    /// binary artifact. Looking up the vulnerabilities of the returned PURLs then tells if it is
+    /// affected by any.
+    #[instrument(skip(self, connection), err(level=tracing::Level::INFO))]
+    pub async fn find_packages_by_checksum<C: ConnectionTrait>(
+        &self,
+        checksum: &ArtifactChecksum,
+        paginated: impl Pagination,
+        connection: &C,
+    ) -> Result<PaginatedResults<SbomPackageMatch>, Error> {
+        // the value is stored as found in the document, which might be uppercase
+        let nodes = sbom_node_checksum::Entity::find()
+            .select_only()
+            .column(sbom_node_checksum::Column::SbomId)
+            .column(sbom_node_checksum::Column::NodeId)
+            .filter(sbom_node_checksum::Column::Type.eq(checksum.algorithm.as_type()))
+            .filter(
+                sbom_node_checksum::Column::Value
+                    .is_in([checksum.value.clone(), checksum.value.to_uppercase()]),
+            );
+
+        let mut query = sbom_package::Entity::find()
+            .filter(
+                Expr::tuple([
+                    Expr::col((sbom_package::Entity, sbom_package::Column::SbomId)).into(),
+                    Expr::col((sbom_package::Entity, sbom_package::Column::NodeId)).into(),
+                ])
+                .in_subquery(nodes.into_query()),
+            )
+            .join(JoinType::Join, sbom_package::Relation::Node.def())
+            .select_only()
+            .column(sbom_package::Column::SbomId)
+            .group_by(sbom_package::Column::SbomId)
+            .column_as(sbom_package::Column::NodeId, "id")
+            .group_by(sbom_package::Column::NodeId)
+            .column_as(sbom_package::Column::Group, "group")
+            .group_by(sbom_package::Column::Group)
+            .column_as(sbom_package::Column::Version, "version")
+            .group_by(sbom_package::Column::Version)
+            .column_as(sbom_node::Column::Name, "name")
+            .group_by(sbom_node::Column::Name)
+            .join(JoinType::LeftJoin, sbom_node::Relation::Purl.def())
+            .join(JoinType::LeftJoin, sbom_node::Relation::Cpe.def());
+
+        query = join_purls_and_cpes(join_licenses(query))
+            .order_by_asc(sbom_package::Column::SbomId)
+            .order_by_asc(sbom_package::Column::NodeId);
+
+        // limit and execute
+
+        let limiter =
+            limit_selector::<_, _, _, ChecksumCatcher>(connection, query, paginated, &self.cache)?;
+
+        let LimitedResult { items, total } = limiter.fetch().await?;
+        let total = total.requested(paginated.total()).await?;
+
+        // fetch the SBOMs of the current page
+
+        let ids = items
+            .iter()
+            .map(|item| item.sbom_id)
+            .collect::<BTreeSet<_>>();
+
+        let mut heads = HashMap::with_capacity(ids.len());
+        for (sbom, node) in sbom::Entity::find()
+            .filter(sbom::Column::SbomId.is_in(ids))
+            .find_also_linked(sbom::SbomNodeLink)
+            .all(connection)
+            .await?
+        {
+            let Some(node) = node else {
+                continue;
+            };
+            heads.insert(
+                sbom.sbom_id,
+                SbomHead::from_entity(&sbom, &node, connection).await?,
+            );
+        }
+
+        let items = items
+            .into_iter()
+            .filter_map(|item| {
+                Some(SbomPackageMatch {
+                    sbom: heads.get(&item.sbom_id)?.clone(),
+                    package: SbomPackage::from_row(item.package),
+                })
+            })
+            .collect();
+
+        Ok(PaginatedResults { items, total })
+    }
+}
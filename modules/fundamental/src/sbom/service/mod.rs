@@ -1,6 +1,9 @@
 pub mod assertion;
+pub mod checksum;
 pub mod label;
+pub mod openvex;
 pub mod sbom;
+pub mod spdx;
 
 #[cfg(test)]
 mod test;
@@ -0,0 +1,137 @@
+use super::SbomService;
+use crate::{
+    Error,
+    exception::service::{ExceptionService, suppress_advisories},
+    sbom::model::{
+        details::SbomAdvisory,
+        openvex::{
+            AUTHOR, CONTEXT, OpenVexDocument, OpenVexIdentifiers, OpenVexProduct, OpenVexStatement,
+            OpenVexStatus, OpenVexVulnerability,
+        },
+    },
+};
+use sea_orm::{ConnectionTrait, StreamTrait, prelude::Uuid};
+use std::collections::{BTreeMap, BTreeSet};
+use time::OffsetDateTime;
+use tracing::instrument;
+use trustify_common::id::Id;
+
+impl SbomService {
+    /// Generate an OpenVEX document for an SBOM, stating the status of its packages regarding all
+    /// known vulnerabilities.
+    ///
+    /// The statements are built from the package statuses of the stored advisories. Statuses
+    /// without a VEX equivalent (like `recommended`) are omitted.
+    ///
+    /// If the exceptions are provided, vulnerabilities with an active exception for the SBOM are
+    /// omitted too, the same way they are suppressed from the advisories of the SBOM.
+    #[instrument(skip(self, exceptions, connection), err(level=tracing::Level::INFO))]
+    pub async fn export_openvex<C>(
+        &self,
+        id: Id,
+        exceptions: Option<&ExceptionService>,
+        connection: &C,
+    ) -> Result<Option<OpenVexDocument>, Error>
+    where
+        C: ConnectionTrait + StreamTrait,
+    {
+        let Some(details) = self.fetch_sbom_details(id, vec![], connection).await? else {
+            return Ok(None);
+        };
+
+        let advisories = match exceptions {
+            Some(exceptions) => {
+                let suppressed = exceptions
+                    .suppressed_for_sbom(details.summary.head.id, connection)
+                    .await?;
+                suppress_advisories(details.advisories, &suppressed)
+            }
+            None => details.advisories,
+        };
+
+        Ok(Some(OpenVexDocument {
+            context: CONTEXT.into(),
+            id: format!("urn:uuid:{}", Uuid::new_v4()),
+            author: AUTHOR.into(),
+            timestamp: OffsetDateTime::now_utc(),
+            version: 1,
+            statements: statements(&advisories),
+        }))
+    }
+}
+
+/// A statement being collected
+#[derive(Default)]
+struct Collector<'a> {
+    description: Option<&'a str>,
+    products: BTreeSet<String>,
+    advisories: BTreeSet<&'a str>,
+}
+
+/// Build the statements, one per vulnerability and status, merging the packages and advisories.
+fn statements(advisories: &[SbomAdvisory]) -> Vec<OpenVexStatement> {
+    let mut collected = BTreeMap::<(&str, OpenVexStatus), Collector>::new();
+
+    for advisory in advisories {
+        for status in &advisory.status {
+            let Some(vex_status) = OpenVexStatus::from_slug(&status.status) else {
+                continue;
+            };
+
+            let products = status
+                .packages
+                .iter()
+                .flat_map(|package| &package.purl)
+                .map(|purl| purl.head.purl.to_string())
+                .collect::<Vec<_>>();
+            if products.is_empty() {
+                continue;
+            }
+
+            let collector = collected
+                .entry((status.identifier(), vex_status))
+                .or_default();
+            collector.description = collector.description.or(status
+                .vulnerability
+                .title
+                .as_deref()
+                .or(status.vulnerability.description.as_deref()));
+            collector.products.extend(products);
+            collector.advisories.insert(&advisory.head.identifier);
+        }
+    }
+
+    collected
+        .into_iter()
+        .map(|((vulnerability, status), collector)| {
+            let advisories = collector
+                .advisories
+                .into_iter()
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            OpenVexStatement {
+                vulnerability: OpenVexVulnerability {
+                    name: vulnerability.to_string(),
+                    description: collector.description.map(ToString::to_string),
+                },
+                products: collector
+                    .products
+                    .into_iter()
+                    .map(|purl| OpenVexProduct {
+                        id: purl.clone(),
+                        identifiers: OpenVexIdentifiers { purl },
+                    })
+                    .collect(),
+                status,
+                status_notes: Some(format!("Stated by: {advisories}")),
+                // required for "not_affected", as there is no stored justification
+                impact_statement: (status == OpenVexStatus::NotAffected)
+                    .then(|| format!("Not affected according to: {advisories}")),
+                // required for "affected"
+                action_statement: (status == OpenVexStatus::Affected)
+                    .then(|| format!("Review the remediations of: {advisories}")),
+            }
+        })
+        .collect()
+}
@@ -2,10 +2,12 @@ use super::SbomService;
 use crate::{
     Error,
     common::license_filtering::{LICENSE, license_text_coalesce},
+    product::model::ProductHead,
     purl::model::summary::purl::PurlSummary,
     sbom::model::{
         ModelCatcher, SbomExternalPackageReference, SbomModel, SbomNodeReference, SbomPackage,
-        SbomPackageRelation, SbomPackageSummary, SbomSummary, Which, details::SbomDetails,
+        SbomPackageRelation, SbomPackageSummary, SbomProductVersion, SbomPurlMatch, SbomSummary,
+        Which, details::SbomDetails,
     },
 };
 use futures_util::{StreamExt, TryStreamExt, stream};
@@ -14,7 +16,7 @@ use sea_orm::{
     IntoSimpleExpr, QueryFilter, QueryOrder, QueryResult, QuerySelect, QueryTrait, RelationTrait,
     Select, SelectColumns, Statement, StreamTrait, prelude::Uuid,
 };
-use sea_query::{ColumnType, Expr, JoinType, UnionType, extension::postgres::PgExpr};
+use sea_query::{ColumnType, Condition, Expr, JoinType, UnionType, extension::postgres::PgExpr};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::{collections::HashMap, fmt::Debug, sync::Arc, vec::Vec};
@@ -31,15 +33,17 @@ use trustify_common::{
     purl::Purl,
     requested_field::BoolRequestedField,
     service::{Mappable, Resulting},
+    vers::{Bound, Interval, Vers},
 };
 use trustify_entity::{
     advisory, advisory_vulnerability, base_purl,
     cpe::{self, CpeDto},
     labels::Labels,
-    license, organization, package_relates_to_package, qualified_purl,
+    license, organization, package_relates_to_package, product, product_version, qualified_purl,
     relationship::Relationship,
     sbom, sbom_ai, sbom_group_assignment, sbom_license_expanded, sbom_node, sbom_node_cpe_ref,
     sbom_node_purl_ref, sbom_package, sbom_package_license, source_document, status,
+    version_scheme::VersionScheme,
     versioned_purl, vulnerability,
 };
 
@@ -661,6 +665,98 @@ impl SbomService {
                 .filter(sbom_node_cpe_ref::Column::CpeId.eq(cpe.uuid())),
         };
 
+        self.fetch_summaries(select, paginated, query, connection)
+            .await
+    }
+
+    /// Find all SBOMs containing a package, matching a PURL and optionally a version range.
+    ///
+    /// Type, namespace, and name of the PURL must match, qualifiers are ignored. The version must
+    /// be in the range if one is provided, or be equal to the version of the PURL if present.
+    ///
+    /// Versions are matched by the database, following the versioning scheme of the range.
+    ///
+    /// Each SBOM comes with the product versions it is assigned to, if any.
+    #[instrument(skip(self, connection), err(level=tracing::Level::INFO))]
+    pub async fn find_sboms_by_purl<C: ConnectionTrait>(
+        &self,
+        purl: &Purl,
+        range: Option<&Vers>,
+        paginated: impl Pagination,
+        query: Query,
+        connection: &C,
+    ) -> Result<PaginatedResults<SbomPurlMatch>, Error> {
+        let mut nodes = sbom_node_purl_ref::Entity::find()
+            .select_only()
+            .column(sbom_node_purl_ref::Column::SbomId)
+            .join(JoinType::Join, sbom_node_purl_ref::Relation::Purl.def())
+            .join(
+                JoinType::Join,
+                qualified_purl::Relation::VersionedPurl.def(),
+            )
+            .join(JoinType::Join, versioned_purl::Relation::BasePurl.def())
+            .filter(base_purl::Column::Type.eq(&purl.ty))
+            .filter(base_purl::Column::Name.eq(&purl.name))
+            .filter(match &purl.namespace {
+                Some(namespace) => base_purl::Column::Namespace.eq(namespace),
+                None => base_purl::Column::Namespace.is_null(),
+            });
+
+        nodes = match (range, &purl.version) {
+            (Some(_), Some(_)) => {
+                return Err(Error::BadRequest(
+                    "Either provide a versioned PURL or a range, but not both".into(),
+                    None,
+                ));
+            }
+            (Some(range), None) => nodes.filter(version_range_condition(range)),
+            (None, Some(version)) => nodes.filter(versioned_purl::Column::Version.eq(version)),
+            (None, None) => nodes,
+        };
+
+        let select =
+            sbom::Entity::find().filter(sbom::Column::SbomId.in_subquery(nodes.into_query()));
+
+        let sboms = self
+            .fetch_summaries(select, paginated, query, connection)
+            .await?;
+
+        // add the product versions of the page
+
+        let mut products = HashMap::<Uuid, Vec<SbomProductVersion>>::new();
+        for (version, product) in product_version::Entity::find()
+            .filter(
+                product_version::Column::SbomId.is_in(sboms.items.iter().map(|sbom| sbom.head.id)),
+            )
+            .find_also_related(product::Entity)
+            .all(connection)
+            .await?
+        {
+            if let (Some(sbom_id), Some(product)) = (version.sbom_id, product) {
+                products
+                    .entry(sbom_id)
+                    .or_default()
+                    .push(SbomProductVersion {
+                        product: ProductHead::from_entity(&product).await?,
+                        version: version.version,
+                    });
+            }
+        }
+
+        Ok(sboms.map(|sbom| SbomPurlMatch {
+            products: products.remove(&sbom.head.id).unwrap_or_default(),
+            sbom,
+        }))
+    }
+
+    /// Fetch the summaries of a selection of SBOMs, filtered and paginated.
+    async fn fetch_summaries<C: ConnectionTrait>(
+        &self,
+        select: Select<sbom::Entity>,
+        paginated: impl Pagination,
+        query: Query,
+        connection: &C,
+    ) -> Result<PaginatedResults<SbomSummary>, Error> {
         let query = select
             .find_also_linked(sbom::SbomNodeLink)
             .find_also_related(source_document::Entity)
@@ -824,6 +920,53 @@ impl SbomService {
     }
 }
 
+/// Build a condition, matching the version of a `versioned_purl` against a range.
+///
+/// The version must be in one of the intervals of the range, and not be one of its excluded
+/// versions. Versions are compared by the `version_matches` function of the database, the same
+/// way affected ranges of advisories are matched.
+fn version_range_condition(range: &Vers) -> Condition {
+    let scheme = VersionScheme::from_vers(&range.scheme).to_string();
+
+    let matches = |Interval { low, high }: Interval| {
+        let values: [sea_orm::Value; 5] = [
+            scheme.clone().into(),
+            low.as_ref().map(|bound| bound.version.clone()).into(),
+            low.as_ref().map(|bound| bound.inclusive).into(),
+            high.as_ref().map(|bound| bound.version.clone()).into(),
+            high.as_ref().map(|bound| bound.inclusive).into(),
+        ];
+        Expr::cust_with_values(
+            r#"version_matches("versioned_purl"."version", ROW(NULL, $1, $2, $3, $4, $5)::version_range)"#,
+            values,
+        )
+    };
+
+    let intervals = range
+        .intervals()
+        .into_iter()
+        .fold(Condition::any(), |condition, interval| {
+            condition.add(matches(interval))
+        });
+
+    range
+        .excluded()
+        .fold(Condition::all().add(intervals), |condition, version| {
+            let bound = Some(Bound {
+                version: version.to_string(),
+                inclusive: true,
+            });
+            condition.add(
+                Condition::all()
+                    .add(matches(Interval {
+                        low: bound.clone(),
+                        high: bound,
+                    }))
+                    .not(),
+            )
+        })
+}
+
 pub trait IntoPackage: Sized {
     type Row: FromQueryResult + Send + Sync + 'static;
 
@@ -940,7 +1083,7 @@ impl IntoPackage for SbomPackage {
 /// built using [`package_from_row`].
 ///
 /// This will add the columns `purls` and `cpes` to the selected output.
-fn join_purls_and_cpes<E>(query: Select<E>) -> Select<E>
+pub(super) fn join_purls_and_cpes<E>(query: Select<E>) -> Select<E>
 where
     E: EntityTrait,
 {
@@ -984,7 +1127,7 @@ where
 /// built using [`package_from_row`].
 ///
 /// This will add the column `licenses` to the selected output.
-fn join_licenses<E>(query: Select<E>) -> Select<E>
+pub(super) fn join_licenses<E>(query: Select<E>) -> Select<E>
 where
     E: EntityTrait,
 {
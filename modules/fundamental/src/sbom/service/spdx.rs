@@ -0,0 +1,429 @@
+use super::SbomService;
+use crate::{
+    Error,
+    common::LicenseInfo,
+    purl::model::summary::purl::PurlSummary,
+    sbom::model::{
+        SbomPackage,
+        spdx::{
+            DATA_LICENSE, DOCUMENT_ID, NOASSERTION, SPDX_VERSION, SpdxCreationInfo, SpdxDocument,
+            SpdxExternalRef, SpdxExtractedLicensingInfo, SpdxPackage, SpdxRelationship,
+        },
+    },
+};
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, prelude::Uuid};
+use spdx_expression::SpdxExpression;
+use std::collections::{HashMap, HashSet};
+use time::{OffsetDateTime, UtcOffset, format_description::well_known::Rfc3339};
+use tracing::instrument;
+use trustify_common::{db::query::Query, id::Id, model::Paginated};
+use trustify_entity::{
+    package_relates_to_package, relationship::Relationship, sbom, sbom_node,
+    sbom_package_license::LicenseCategory,
+};
+
+/// The number of packages fetched at once
+const PAGE_SIZE: u64 = 1000;
+
+/// Creator prefixes defined by the SPDX specification
+const CREATOR_PREFIXES: [&str; 3] = ["Person:", "Organization:", "Tool:"];
+
+impl SbomService {
+    /// Export an SBOM as an SPDX 2.3 document, built from the stored graph.
+    ///
+    /// This works for all SBOMs, independent of the format they were ingested in. Only what is
+    /// stored in the graph gets exported, so the outcome is not identical to the original document.
+    #[instrument(skip(self, connection), err(level=tracing::Level::INFO))]
+    pub async fn export_spdx<C: ConnectionTrait>(
+        &self,
+        id: Id,
+        connection: &C,
+    ) -> Result<Option<SpdxDocument>, Error> {
+        let Some((sbom, node, _)) = self.fetch_sbom(id, connection).await? else {
+            return Ok(None);
+        };
+
+        let packages = self.all_packages(sbom.sbom_id, connection).await?;
+
+        let relationships = package_relates_to_package::Entity::find()
+            .filter(package_relates_to_package::Column::SbomId.eq(sbom.sbom_id))
+            .order_by_asc(package_relates_to_package::Column::LeftNodeId)
+            .order_by_asc(package_relates_to_package::Column::Relationship)
+            .order_by_asc(package_relates_to_package::Column::RightNodeId)
+            .all(connection)
+            .await?;
+
+        Ok(Some(build(&sbom, &node, packages, relationships)))
+    }
+
+    /// Fetch all packages of an SBOM, page by page.
+    async fn all_packages<C: ConnectionTrait>(
+        &self,
+        sbom_id: Uuid,
+        connection: &C,
+    ) -> Result<Vec<SbomPackage>, Error> {
+        let mut result = vec![];
+
+        loop {
+            let page = self
+                .fetch_sbom_packages(
+                    sbom_id,
+                    Query::default(),
+                    Paginated {
+                        offset: result.len() as u64,
+                        limit: PAGE_SIZE,
+                        total: false,
+                    },
+                    connection,
+                )
+                .await?;
+
+            let done = (page.items.len() as u64) < PAGE_SIZE;
+            result.extend(page.items);
+            if done {
+                break;
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+fn build(
+    sbom: &sbom::Model,
+    node: &sbom_node::Model,
+    packages: Vec<SbomPackage>,
+    relationships: Vec<package_relates_to_package::Model>,
+) -> SpdxDocument {
+    let mut ids = Ids::new(&sbom.node_id);
+    let mut licenses = Licenses::default();
+
+    let packages = packages
+        .into_iter()
+        .map(|package| {
+            let (concluded, declared) = licenses.split(&package.licenses);
+            SpdxPackage {
+                spdx_id: ids.id(&package.id),
+                name: package.name,
+                version_info: package.version,
+                download_location: NOASSERTION.into(),
+                files_analyzed: false,
+                license_concluded: concluded,
+                license_declared: declared,
+                copyright_text: NOASSERTION.into(),
+                external_refs: external_refs(&package.purl, &package.cpe),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // relationships can only reference the document and its packages
+
+    let relationships = relationships
+        .into_iter()
+        .filter_map(|rel| {
+            let (Some(left), Some(right)) =
+                (ids.get(&rel.left_node_id), ids.get(&rel.right_node_id))
+            else {
+                log::debug!(
+                    "Skipping relationship to unknown element: {} {} {}",
+                    rel.left_node_id,
+                    rel.relationship,
+                    rel.right_node_id
+                );
+                return None;
+            };
+
+            let (left, relationship_type, right) = relationship(left, rel.relationship, right);
+            Some(SpdxRelationship {
+                spdx_element_id: left.to_string(),
+                relationship_type: relationship_type.into(),
+                related_spdx_element: right.to_string(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let document_describes = relationships
+        .iter()
+        .filter(|rel| rel.spdx_element_id == DOCUMENT_ID && rel.relationship_type == "DESCRIBES")
+        .map(|rel| rel.related_spdx_element.clone())
+        .collect();
+
+    let creators = std::iter::once(format!("Tool: trustify-{}", env!("CARGO_PKG_VERSION")))
+        .chain(sbom.authors.iter().map(|author| {
+            match CREATOR_PREFIXES
+                .iter()
+                .any(|prefix| author.starts_with(prefix))
+            {
+                true => author.clone(),
+                false => format!("Organization: {author}"),
+            }
+        }))
+        .collect();
+
+    SpdxDocument {
+        spdx_version: SPDX_VERSION.into(),
+        data_license: DATA_LICENSE.into(),
+        spdx_id: DOCUMENT_ID.into(),
+        name: node.name.clone(),
+        document_namespace: format!("urn:uuid:{}", sbom.sbom_id),
+        creation_info: SpdxCreationInfo {
+            created: created(sbom.published),
+            creators,
+        },
+        document_describes,
+        packages,
+        relationships,
+        has_extracted_licensing_infos: licenses.extracted,
+    }
+}
+
+/// The creation timestamp, in the format required by SPDX, which doesn't allow fractions.
+fn created(published: Option<OffsetDateTime>) -> String {
+    published
+        .unwrap_or_else(OffsetDateTime::now_utc)
+        .to_offset(UtcOffset::UTC)
+        .replace_nanosecond(0)
+        .ok()
+        .and_then(|created| created.format(&Rfc3339).ok())
+        .unwrap_or_default()
+}
+
+/// Map a stored relationship to the SPDX relationship type.
+///
+/// The stored relationships are normalized, pointing from the left to the right side. This
+/// reverses what is done when ingesting SPDX documents.
+fn relationship<'a>(
+    left: &'a str,
+    relationship: Relationship,
+    right: &'a str,
+) -> (&'a str, &'static str, &'a str) {
+    match relationship {
+        Relationship::Contains => (left, "CONTAINS", right),
+        Relationship::Dependency => (left, "DEPENDS_ON", right),
+        Relationship::DevDependency => (right, "DEV_DEPENDENCY_OF", left),
+        Relationship::OptionalDependency => (right, "OPTIONAL_DEPENDENCY_OF", left),
+        Relationship::ProvidedDependency => (right, "PROVIDED_DEPENDENCY_OF", left),
+        Relationship::TestDependency => (right, "TEST_DEPENDENCY_OF", left),
+        Relationship::RuntimeDependency => (right, "RUNTIME_DEPENDENCY_OF", left),
+        Relationship::Example => (right, "EXAMPLE_OF", left),
+        Relationship::Generates => (left, "GENERATES", right),
+        Relationship::AncestorOf => (left, "ANCESTOR_OF", right),
+        Relationship::Variant => (right, "VARIANT_OF", left),
+        Relationship::BuildTool => (right, "BUILD_TOOL_OF", left),
+        Relationship::DevTool => (right, "DEV_TOOL_OF", left),
+        Relationship::Describes => (left, "DESCRIBES", right),
+        Relationship::Package => (right, "PACKAGE_OF", left),
+        Relationship::Undefined => (left, "OTHER", right),
+    }
+}
+
+fn external_refs(purls: &[PurlSummary], cpes: &[String]) -> Vec<SpdxExternalRef> {
+    let purls = purls.iter().map(|purl| SpdxExternalRef {
+        reference_category: "PACKAGE-MANAGER".into(),
+        reference_type: "purl".into(),
+        reference_locator: purl.head.purl.to_string(),
+    });
+
+    let cpes = cpes.iter().map(|cpe| SpdxExternalRef {
+        reference_category: "SECURITY".into(),
+        reference_type: match cpe.starts_with("cpe:2.3:") {
+            true => "cpe23Type",
+            false => "cpe22Type",
+        }
+        .into(),
+        reference_locator: cpe.clone(),
+    });
+
+    purls.chain(cpes).collect()
+}
+
+/// The SPDX identifiers of the elements of a document.
+///
+/// Node IDs which are valid SPDX identifiers are used as is, others (like CycloneDX `bom-ref`s)
+/// get converted.
+struct Ids {
+    document: String,
+    ids: HashMap<String, String>,
+    used: HashSet<String>,
+}
+
+impl Ids {
+    fn new(document: &str) -> Self {
+        Self {
+            document: document.to_string(),
+            ids: Default::default(),
+            used: HashSet::from([DOCUMENT_ID.to_string()]),
+        }
+    }
+
+    /// Get the identifier of an existing element.
+    fn get(&self, node_id: &str) -> Option<&str> {
+        match node_id == self.document {
+            true => Some(DOCUMENT_ID),
+            false => self.ids.get(node_id).map(String::as_str),
+        }
+    }
+
+    /// Assign an identifier to a new element.
+    fn id(&mut self, node_id: &str) -> String {
+        if let Some(id) = self.ids.get(node_id) {
+            return id.clone();
+        }
+
+        let base = match valid_id(node_id) {
+            true => node_id.to_string(),
+            false => {
+                let name = node_id.strip_prefix("SPDXRef-").unwrap_or(node_id);
+                let name = name
+                    .chars()
+                    .map(
+                        |c| match c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                            true => c,
+                            false => '-',
+                        },
+                    )
+                    .collect::<String>();
+                format!("SPDXRef-{name}")
+            }
+        };
+
+        let mut id = base.clone();
+        let mut n = 1;
+        while self.used.contains(&id) {
+            n += 1;
+            id = format!("{base}-{n}");
+        }
+
+        self.used.insert(id.clone());
+        self.ids.insert(node_id.to_string(), id.clone());
+        id
+    }
+}
+
+fn valid_id(id: &str) -> bool {
+    id.strip_prefix("SPDXRef-").is_some_and(|name| {
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+    })
+}
+
+/// License expressions of packages, collecting those which aren't valid SPDX expressions.
+#[derive(Default)]
+struct Licenses {
+    refs: HashMap<String, String>,
+    extracted: Vec<SpdxExtractedLicensingInfo>,
+}
+
+impl Licenses {
+    /// Split licenses into the concluded and the declared expression.
+    fn split(&mut self, licenses: &[LicenseInfo]) -> (String, String) {
+        let mut concluded = vec![];
+        let mut declared = vec![];
+
+        for license in licenses {
+            let expression = self.expression(&license.license_name);
+            match license.license_type {
+                LicenseCategory::Concluded => concluded.push(expression),
+                LicenseCategory::Declared => declared.push(expression),
+            }
+        }
+
+        (combine(concluded), combine(declared))
+    }
+
+    /// A valid SPDX expression for a license, referencing an extracted license if necessary.
+    fn expression(&mut self, license: &str) -> String {
+        if license == NOASSERTION || license == "NONE" || SpdxExpression::parse(license).is_ok() {
+            return license.to_string();
+        }
+
+        if let Some(id) = self.refs.get(license) {
+            return id.clone();
+        }
+
+        let id = format!("LicenseRef-{}", self.refs.len() + 1);
+        self.refs.insert(license.to_string(), id.clone());
+        self.extracted.push(SpdxExtractedLicensingInfo {
+            license_id: id.clone(),
+            extracted_text: license.to_string(),
+        });
+        id
+    }
+}
+
+/// Combine multiple license expressions into one, requiring all of them.
+fn combine(mut expressions: Vec<String>) -> String {
+    expressions.sort_unstable();
+    expressions.dedup();
+
+    match expressions.len() {
+        0 => NOASSERTION.into(),
+        1 => expressions.remove(0),
+        _ => expressions
+            .iter()
+            .map(|expression| match expression.contains(' ') {
+                true => format!("({expression})"),
+                false => expression.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join(" AND "),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("SPDXRef-Package", true)]
+    #[case("SPDXRef-a.b-1", true)]
+    #[case("SPDXRef-", false)]
+    #[case("pkg:maven/a/b@1", false)]
+    #[case("SPDXRef-a_b", false)]
+    fn valid(#[case] id: &str, #[case] expected: bool) {
+        assert_eq!(valid_id(id), expected);
+    }
+
+    #[test]
+    fn ids() {
+        let mut ids = Ids::new("doc");
+        assert_eq!(ids.id("SPDXRef-A"), "SPDXRef-A");
+        assert_eq!(ids.id("pkg:npm/a@1"), "SPDXRef-pkg-npm-a-1");
+        assert_eq!(ids.id("pkg:npm/a-1"), "SPDXRef-pkg-npm-a-1-2");
+        assert_eq!(ids.id("pkg:npm/a@1"), "SPDXRef-pkg-npm-a-1");
+        assert_eq!(ids.get("doc"), Some(DOCUMENT_ID));
+        assert_eq!(ids.get("unknown"), None);
+    }
+
+    #[test]
+    fn licenses() {
+        let mut licenses = Licenses::default();
+        let (concluded, declared) = licenses.split(&[
+            LicenseInfo {
+                license_name: "Apache-2.0 OR MIT".into(),
+                license_type: LicenseCategory::Declared,
+            },
+            LicenseInfo {
+                license_name: "BSD-3-Clause".into(),
+                license_type: LicenseCategory::Declared,
+            },
+            LicenseInfo {
+                license_name: "Some custom license".into(),
+                license_type: LicenseCategory::Concluded,
+            },
+        ]);
+
+        assert_eq!(declared, "(Apache-2.0 OR MIT) AND BSD-3-Clause");
+        assert_eq!(concluded, "LicenseRef-1");
+        assert_eq!(licenses.extracted.len(), 1);
+        assert_eq!(licenses.extracted[0].extracted_text, "Some custom license");
+
+        assert_eq!(
+            licenses.split(&[]),
+            (NOASSERTION.into(), NOASSERTION.into())
+        );
+    }
+}
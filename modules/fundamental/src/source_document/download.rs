@@ -0,0 +1,65 @@
+use crate::{Error, source_document::model::SourceDocument};
+use actix_web::{
+    HttpRequest, HttpResponse,
+    http::header::{ACCEPT_RANGES, ContentRange, ContentRangeSpec, Header, Range},
+};
+use futures_util::TryStreamExt;
+use trustify_module_storage::service::{StorageBackend, StorageKey, dispatch::DispatchBackend};
+
+/// Create a response, streaming a source document from the storage.
+///
+/// A request for a single range of bytes gets a partial response, which allows clients to resume
+/// downloads. Requests for multiple ranges, or ranges of other units, get the whole document.
+pub async fn download_response(
+    request: &HttpRequest,
+    storage: &DispatchBackend,
+    document: &SourceDocument,
+) -> Result<HttpResponse, Error> {
+    let key: StorageKey = document.try_into()?;
+    let size = document.size;
+
+    let range = match Range::parse(request) {
+        Ok(Range::Bytes(ranges)) if ranges.len() == 1 => {
+            match ranges[0].to_satisfiable_range(size) {
+                Some((first, last)) => Some(first..last + 1),
+                None => {
+                    return Ok(HttpResponse::RangeNotSatisfiable()
+                        .insert_header(ContentRange(ContentRangeSpec::Bytes {
+                            range: None,
+                            instance_length: Some(size),
+                        }))
+                        .finish());
+                }
+            }
+        }
+        _ => None,
+    };
+
+    let Some(range) = range else {
+        return Ok(match storage.retrieve(key).await.map_err(Error::Storage)? {
+            Some(stream) => HttpResponse::Ok()
+                .insert_header((ACCEPT_RANGES, "bytes"))
+                .no_chunking(size)
+                .streaming(stream.map_err(Error::Storage)),
+            None => HttpResponse::NotFound().finish(),
+        });
+    };
+
+    Ok(
+        match storage
+            .retrieve_range(key, range.clone())
+            .await
+            .map_err(Error::Storage)?
+        {
+            Some(stream) => HttpResponse::PartialContent()
+                .insert_header((ACCEPT_RANGES, "bytes"))
+                .insert_header(ContentRange(ContentRangeSpec::Bytes {
+                    range: Some((range.start, range.end - 1)),
+                    instance_length: Some(size),
+                }))
+                .no_chunking(range.end - range.start)
+                .streaming(stream.map_err(Error::Storage)),
+            None => HttpResponse::NotFound().finish(),
+        },
+    )
+}
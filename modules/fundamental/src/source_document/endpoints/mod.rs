@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod test;
+
+use crate::{
+    Error,
+    source_document::reingest::{ReingestCursor, ReingestReport, ReingestService},
+};
+use actix_web::{HttpResponse, Responder, post, web};
+use std::str::FromStr;
+use trustify_auth::{UploadDataset, authorizer::Require};
+use trustify_common::{
+    db::{self, pagination_cache::PaginationCache},
+    id::Id,
+};
+use trustify_entity::labels::Labels;
+use trustify_module_ingestor::{
+    model::IngestResult,
+    service::{Format, IngestorService},
+};
+use utoipa::IntoParams;
+
+pub fn configure(
+    config: &mut utoipa_actix_web::service_config::ServiceConfig,
+    db_rw: db::ReadWrite,
+    cache: PaginationCache,
+) {
+    let service = ReingestService::new(cache);
+
+    config
+        .app_data(web::Data::new(db_rw))
+        .app_data(web::Data::new(service))
+        .service(reingest)
+        .service(reingest_all);
+}
+
+#[utoipa::path(
+    tag = "dataset",
+    operation_id = "reingestDocument",
+    params(
+        ("id" = Id, Path, description = "Identifier of the SBOM or advisory, either `urn:uuid:<uuid>` or a digest e.g. `sha256:<hex>`"),
+    ),
+    responses(
+        (status = 200, description = "Re-ingested the document", body = IngestResult),
+        (status = 404, description = "The SBOM or advisory, or its content, could not be found"),
+    ),
+)]
+#[post("/v3/reingest/{id}")]
+/// Re-ingest the stored document of an SBOM or advisory
+///
+/// The SBOM or advisory is replaced by the outcome of loading its original document again, keeping
+/// its labels. The result carries the new ID.
+pub async fn reingest(
+    service: web::Data<ReingestService>,
+    ingestor: web::Data<IngestorService>,
+    db: web::Data<db::ReadWrite>,
+    id: web::Path<String>,
+    _: Require<UploadDataset>,
+) -> Result<impl Responder, Error> {
+    let id = Id::from_str(&id)?;
+
+    Ok(match service.reingest(id, &ingestor, db.as_ref()).await? {
+        Some(result) => HttpResponse::Ok().json(result),
+        None => HttpResponse::NotFound().finish(),
+    })
+}
+
+#[derive(IntoParams, Clone, Debug, PartialEq, Eq, serde::Deserialize)]
+struct ReingestParams {
+    /// Only re-ingest documents of this format: `sbom` or `advisory` for all documents of that
+    /// kind, or a specific format like `csaf`. All documents by default.
+    #[serde(default = "default_format")]
+    #[param(inline)]
+    format: Format,
+    /// Only re-ingest documents carrying these labels.
+    ///
+    /// Only use keys with a prefix of `labels.`
+    #[serde(flatten, with = "trustify_entity::labels::prefixed")]
+    labels: Labels,
+}
+
+const fn default_format() -> Format {
+    Format::Unknown
+}
+
+#[derive(IntoParams, Clone, Debug, PartialEq, Eq, serde::Deserialize)]
+struct ReingestPage {
+    /// Continue after this cursor, as returned with the previous page.
+    #[serde(default)]
+    after: Option<String>,
+    /// The maximum number of documents to process.
+    #[serde(default = "default_limit")]
+    limit: u64,
+}
+
+const fn default_limit() -> u64 {
+    100
+}
+
+#[utoipa::path(
+    tag = "dataset",
+    operation_id = "reingestDocuments",
+    params(ReingestParams, ReingestPage),
+    responses(
+        (status = 200, description = "Processed the matching documents", body = ReingestReport),
+        (status = 400, description = "The cursor is invalid"),
+    ),
+)]
+#[post("/v3/reingest")]
+/// Re-ingest a page of the stored documents of SBOMs and advisories matching the format and labels
+///
+/// Each document is processed individually. The result reports the documents which were
+/// re-ingested, as well as those missing from the storage or failing to load. Unless the last page
+/// was processed, the result carries the cursor to continue with the next page.
+pub async fn reingest_all(
+    service: web::Data<ReingestService>,
+    ingestor: web::Data<IngestorService>,
+    db: web::Data<db::ReadWrite>,
+    web::Query(ReingestParams { format, labels }): web::Query<ReingestParams>,
+    web::Query(ReingestPage { after, limit }): web::Query<ReingestPage>,
+    _: Require<UploadDataset>,
+) -> Result<impl Responder, Error> {
+    let after = after.as_deref().map(ReingestCursor::from_str).transpose()?;
+
+    let result = service
+        .reingest_all(format, labels, after, limit, &ingestor, db.as_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(result))
+}
@@ -0,0 +1,196 @@
+use crate::test::caller;
+use actix_http::StatusCode;
+use actix_web::test::TestRequest;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use serde_json::Value;
+use test_context::test_context;
+use test_log::test;
+use trustify_entity::{product, product_version, sbom_group, sbom_group_assignment};
+use trustify_module_ingestor::{model::IngestResult, service::Format};
+use trustify_test_context::{TrustifyContext, call::CallService};
+use uuid::Uuid;
+
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn reingest(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    let sbom = ctx
+        .ingest_document_as(
+            "zookeeper-3.9.2-cyclonedx.json",
+            Format::Unknown,
+            ("source", "sbom"),
+        )
+        .await?;
+    let advisory = ctx
+        .ingest_document_as(
+            "csaf/CVE-2023-20862.json",
+            Format::Unknown,
+            ("source", "csaf"),
+        )
+        .await?;
+
+    let app = caller(ctx).await?;
+
+    // a single SBOM, replaced by a new one, keeping its labels
+
+    let uri = format!("/api/v3/reingest/urn:uuid:{}", sbom.id);
+    let request = TestRequest::post().uri(&uri).to_request();
+    let result: IngestResult = app.call_and_read_body_json(request).await;
+    assert_ne!(result.id, sbom.id);
+    assert_eq!(result.document_id, sbom.document_id);
+
+    let uri = format!("/api/v3/sbom/urn:uuid:{}", sbom.id);
+    let request = TestRequest::get().uri(&uri).to_request();
+    let response = app.call_service(request).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let uri = format!("/api/v3/sbom/urn:uuid:{}", result.id);
+    let request = TestRequest::get().uri(&uri).to_request();
+    let response: Value = app.call_and_read_body_json(request).await;
+    assert_eq!(response["labels"]["source"], "sbom");
+
+    // unknown documents
+
+    let request = TestRequest::post()
+        .uri("/api/v3/reingest/urn:uuid:a4e9c7c0-2c0d-4e53-8b6f-0c0e8d0e3a11")
+        .to_request();
+    let response = app.call_service(request).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // in bulk, filtered by label
+
+    let request = TestRequest::post()
+        .uri("/api/v3/reingest?labels.source=csaf")
+        .to_request();
+    let response: Value = app.call_and_read_body_json(request).await;
+    let reingested = response["reingested"].as_array().expect("must be an array");
+    assert_eq!(reingested.len(), 1);
+    assert_eq!(reingested[0]["document_id"], advisory.document_id.unwrap());
+    assert_ne!(reingested[0]["id"], advisory.id);
+    assert_eq!(response["missing"], Value::Array(vec![]));
+
+    // in bulk, filtered by format
+
+    let request = TestRequest::post()
+        .uri("/api/v3/reingest?format=cyclonedx")
+        .to_request();
+    let response: Value = app.call_and_read_body_json(request).await;
+    let reingested = response["reingested"].as_array().expect("must be an array");
+    assert_eq!(reingested.len(), 1);
+    assert_eq!(reingested[0]["document_id"], result.document_id.unwrap());
+
+    let request = TestRequest::post()
+        .uri("/api/v3/reingest?format=spdx")
+        .to_request();
+    let response: Value = app.call_and_read_body_json(request).await;
+    assert_eq!(response["reingested"], Value::Array(vec![]));
+
+    Ok(())
+}
+
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn reingest_keeps_associations(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    let sbom = ctx
+        .ingest_document("zookeeper-3.9.2-cyclonedx.json")
+        .await?;
+    let sbom_id = sbom.id.parse::<Uuid>()?;
+
+    let group = sbom_group::ActiveModel {
+        id: Set(Uuid::now_v7()),
+        parent: Set(None),
+        name: Set("group".into()),
+        description: Set(None),
+        revision: Set(Uuid::now_v7()),
+        labels: Set(Default::default()),
+    }
+    .insert(&ctx.db)
+    .await?;
+    sbom_group_assignment::ActiveModel {
+        sbom_id: Set(sbom_id),
+        group_id: Set(group.id),
+    }
+    .insert(&ctx.db)
+    .await?;
+
+    let product = product::ActiveModel {
+        id: Set(Uuid::now_v7()),
+        name: Set("product".into()),
+        vendor_id: Set(None),
+        cpe_key: Set(None),
+    }
+    .insert(&ctx.db)
+    .await?;
+    let product_version = product_version::ActiveModel {
+        id: Set(Uuid::now_v7()),
+        product_id: Set(product.id),
+        sbom_id: Set(Some(sbom_id)),
+        version: Set("1.0".into()),
+    }
+    .insert(&ctx.db)
+    .await?;
+
+    let app = caller(ctx).await?;
+
+    let uri = format!("/api/v3/reingest/urn:uuid:{}", sbom.id);
+    let request = TestRequest::post().uri(&uri).to_request();
+    let result: IngestResult = app.call_and_read_body_json(request).await;
+    let id = result.id.parse::<Uuid>()?;
+    assert_ne!(id, sbom_id);
+
+    // the new SBOM is still assigned to the group, and linked to the product version
+
+    let assignments = sbom_group_assignment::Entity::find()
+        .filter(sbom_group_assignment::Column::GroupId.eq(group.id))
+        .all(&ctx.db)
+        .await?;
+    assert_eq!(assignments.len(), 1);
+    assert_eq!(assignments[0].sbom_id, id);
+
+    let product_version = product_version::Entity::find_by_id(product_version.id)
+        .one(&ctx.db)
+        .await?
+        .expect("must still exist");
+    assert_eq!(product_version.sbom_id, Some(id));
+
+    Ok(())
+}
+
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn reingest_paged(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    ctx.ingest_documents(["zookeeper-3.9.2-cyclonedx.json", "csaf/CVE-2023-20862.json"])
+        .await?;
+
+    let app = caller(ctx).await?;
+
+    // one page after another, each document only once
+
+    let mut seen = vec![];
+    let mut uri = "/api/v3/reingest?limit=1".to_string();
+    loop {
+        let request = TestRequest::post().uri(&uri).to_request();
+        let response: Value = app.call_and_read_body_json(request).await;
+
+        let reingested = response["reingested"].as_array().expect("must be an array");
+        assert!(reingested.len() <= 1);
+        seen.extend(reingested.iter().map(|doc| doc["document_id"].clone()));
+
+        match response["next"].as_str() {
+            Some(next) => uri = format!("/api/v3/reingest?limit=1&after={next}"),
+            None => break,
+        }
+    }
+
+    assert_eq!(seen.len(), 2);
+    assert_ne!(seen[0], seen[1]);
+
+    // an invalid cursor
+
+    let request = TestRequest::post()
+        .uri("/api/v3/reingest?after=foo")
+        .to_request();
+    let response = app.call_service(request).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
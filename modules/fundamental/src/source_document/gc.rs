@@ -0,0 +1,200 @@
+use crate::{
+    Error,
+    common::service::{lock_unreferenced, unreferenced},
+};
+use futures_util::TryStreamExt;
+use sea_orm::{ConnectionTrait, TransactionTrait};
+use std::{
+    collections::BTreeMap,
+    pin::pin,
+    time::{Duration, SystemTime},
+};
+use tracing::instrument;
+use trustify_module_storage::service::{DeleteManyError, StorageBackend, StorageEntry, StorageKey};
+
+/// The number of blobs checked against the database at once
+const BATCH_SIZE: usize = 1000;
+
+/// Options for collecting the garbage of the storage
+#[derive(Clone, Debug, Default)]
+pub struct GcOptions {
+    /// Delete orphaned blobs, instead of only reporting them
+    pub delete: bool,
+    /// Ignore blobs modified more recently, as they might belong to an ongoing ingestion
+    pub grace_period: Duration,
+}
+
+/// The outcome of collecting the garbage of the storage
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize)]
+pub struct GcReport {
+    /// The number of blobs scanned
+    pub scanned: u64,
+    /// The keys of blobs without a source document referencing them
+    pub orphaned: Vec<String>,
+    /// The total size of the orphaned blobs in the storage
+    pub orphaned_bytes: u64,
+    /// The number of orphaned blobs which got deleted
+    pub deleted: u64,
+    /// The orphaned blobs which failed to be deleted, with the reason
+    pub errors: BTreeMap<String, String>,
+}
+
+/// Find blobs in the storage which are not referenced by any source document, and optionally
+/// delete them.
+///
+/// Blobs get orphaned when an ingestion fails after the document was stored, or when removing a
+/// blob fails after the document was deleted. Blobs with a key which isn't a SHA256 digest are
+/// not considered, as they aren't managed by us.
+///
+/// When deleting, blobs which are currently being ingested are not considered orphaned.
+#[instrument(skip(connection, storage), err(level=tracing::Level::INFO))]
+pub async fn collect_garbage<C, S>(
+    connection: &C,
+    storage: &S,
+    options: &GcOptions,
+) -> Result<GcReport, Error>
+where
+    C: ConnectionTrait + TransactionTrait,
+    S: StorageBackend,
+    S::Error: Into<anyhow::Error>,
+{
+    let threshold = SystemTime::now()
+        .checked_sub(options.grace_period)
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+
+    let entries = storage
+        .list()
+        .await
+        .map_err(|err| Error::Storage(err.into()))?;
+    let mut batches = pin!(entries.try_chunks(BATCH_SIZE));
+
+    let mut report = GcReport::default();
+
+    while let Some(batch) = batches
+        .try_next()
+        .await
+        .map_err(|err| Error::Storage(err.1.into()))?
+    {
+        report.scanned += batch.len() as u64;
+
+        let batch: BTreeMap<String, StorageEntry> = batch
+            .into_iter()
+            .filter(|entry| entry.key.is_sha256())
+            .filter(|entry| entry.modified.is_none_or(|modified| modified < threshold))
+            .map(|entry| (entry.key.to_string(), entry))
+            .collect();
+
+        let keys = batch.keys().cloned();
+        let (tx, orphaned) = match options.delete {
+            true => {
+                let tx = connection.begin().await?;
+                let orphaned = lock_unreferenced(keys, &tx).await?;
+                (Some(tx), orphaned)
+            }
+            false => (None, unreferenced(keys, connection).await?),
+        };
+        if orphaned.is_empty() {
+            continue;
+        }
+
+        for key in &orphaned {
+            log::info!("Orphaned blob: {key}");
+            report.orphaned_bytes += batch[key].size;
+        }
+
+        if let Some(tx) = tx {
+            delete(storage, &orphaned, &mut report).await;
+            // only release the locks once deleted
+            tx.commit().await?;
+        }
+
+        report.orphaned.extend(orphaned);
+    }
+
+    Ok(report)
+}
+
+/// Delete orphaned blobs, recording the outcome in the report
+async fn delete<S>(storage: &S, orphaned: &[String], report: &mut GcReport)
+where
+    S: StorageBackend,
+    S::Error: Into<anyhow::Error>,
+{
+    let keys = orphaned
+        .iter()
+        .map(|key| StorageKey::from_sha256(key))
+        .collect::<Vec<_>>();
+
+    match storage.delete_many(&keys).await {
+        Ok(()) => report.deleted += keys.len() as u64,
+        Err(DeleteManyError::Generic(err)) => {
+            let err = err.into().to_string();
+            report
+                .errors
+                .extend(orphaned.iter().map(|key| (key.clone(), err.clone())));
+        }
+        Err(DeleteManyError::Individual(errors)) => {
+            report.deleted += (keys.len() - errors.len()) as u64;
+            report.errors.extend(
+                errors
+                    .into_iter()
+                    .map(|(key, err)| (key.to_string(), err.into().to_string())),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use test_context::test_context;
+    use test_log::test;
+    use trustify_test_context::TrustifyContext;
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn orphaned(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        ctx.ingest_document("ubi9-9.2-755.1697625012.json").await?;
+        let orphan = ctx
+            .storage
+            .store(&b"not ingested"[..])
+            .await
+            .map_err(|err| anyhow::anyhow!("{err}"))?;
+        let orphan = orphan.key().to_string();
+
+        // within the grace period, nothing is considered
+        let options = GcOptions {
+            delete: true,
+            grace_period: Duration::from_secs(3600),
+        };
+        let report = collect_garbage(&ctx.db, &ctx.storage, &options).await?;
+        assert_eq!(report.scanned, 2);
+        assert!(report.orphaned.is_empty());
+
+        // report only
+        let options = GcOptions {
+            delete: false,
+            grace_period: Duration::ZERO,
+        };
+        let report = collect_garbage(&ctx.db, &ctx.storage, &options).await?;
+        assert_eq!(report.orphaned, vec![orphan.clone()]);
+        assert_eq!(report.deleted, 0);
+
+        // delete
+        let options = GcOptions {
+            delete: true,
+            ..options
+        };
+        let report = collect_garbage(&ctx.db, &ctx.storage, &options).await?;
+        assert_eq!(report.orphaned, vec![orphan.clone()]);
+        assert_eq!(report.deleted, 1);
+        assert!(report.errors.is_empty());
+
+        // the orphan is gone, the ingested document is kept
+        let report = collect_garbage(&ctx.db, &ctx.storage, &options).await?;
+        assert_eq!(report.scanned, 1);
+        assert!(report.orphaned.is_empty());
+
+        Ok(())
+    }
+}
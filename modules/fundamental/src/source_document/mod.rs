@@ -1 +1,6 @@
+pub mod download;
+pub mod endpoints;
+pub mod gc;
 pub mod model;
+pub mod reingest;
+pub mod scrub;
@@ -11,6 +11,8 @@ pub struct SourceDocument {
     pub sha256: String,
     pub sha384: String,
     pub sha512: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blake3: Option<String>,
     pub size: u64,
     /// The timestamp the document was ingested
     #[serde(with = "time::serde::rfc3339")]
@@ -23,6 +25,7 @@ impl Default for SourceDocument {
             sha256: <_>::default(),
             sha384: <_>::default(),
             sha512: <_>::default(),
+            blake3: <_>::default(),
             size: <_>::default(),
             ingested: OffsetDateTime::now_utc(),
         }
@@ -35,6 +38,10 @@ impl SourceDocument {
             sha256: format!("sha256:{}", source_document.sha256),
             sha384: format!("sha384:{}", source_document.sha384),
             sha512: format!("sha512:{}", source_document.sha512),
+            blake3: source_document
+                .blake3
+                .as_ref()
+                .map(|blake3| format!("blake3:{blake3}")),
             size: source_document.size as u64,
             ingested: source_document.ingested,
         }
@@ -0,0 +1,520 @@
+//! Running stored documents through their loaders again.
+//!
+//! After fixing a loader, or migrating the schema, the information extracted from documents
+//! ingested before may be incomplete or wrong. Re-ingesting replaces the SBOM or advisory with the
+//! outcome of loading its original document, as it was kept in the storage. Only SBOMs and
+//! advisories are covered, the same documents a dataset export contains.
+//!
+//! A new SBOM carries over the associations of the one it replaces: the product version it is
+//! linked to, the groups it is assigned to, and the external references of other SBOMs pointing
+//! to it.
+
+use crate::{Error, advisory::service::AdvisoryService, sbom::service::SbomService};
+use futures_util::TryStreamExt;
+use sea_orm::{
+    ColumnTrait, Condition, ConnectionTrait, DbBackend, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect, Statement, TransactionTrait,
+};
+use sea_query::{Expr, extension::postgres::PgExpr};
+use std::{collections::BTreeMap, fmt, str::FromStr};
+use time::OffsetDateTime;
+use tracing::instrument;
+use trustify_common::{
+    db::pagination_cache::PaginationCache,
+    id::{Id, TrySelectForId},
+};
+use trustify_entity::{
+    advisory, labels::Labels, organization, sbom, sbom_group_assignment, source_document,
+};
+use trustify_module_ingestor::{
+    model::IngestResult,
+    service::{Cache, Format, IngestorService},
+};
+use trustify_module_storage::service::{StorageBackend, StorageKey};
+use uuid::Uuid;
+
+/// The outcome of re-ingesting documents in bulk
+#[derive(Clone, Debug, Default, serde::Serialize, utoipa::ToSchema)]
+pub struct ReingestReport {
+    /// The documents which were re-ingested
+    pub reingested: Vec<IngestResult>,
+    /// The SHA256 digests of source documents missing from the storage
+    pub missing: Vec<String>,
+    /// The SHA256 digests of source documents which failed to be re-ingested, with the reason
+    pub errors: BTreeMap<String, String>,
+    /// The cursor to continue with the next page of documents, if there might be more
+    pub next: Option<String>,
+}
+
+/// The position of a document, when paging through documents.
+///
+/// Documents are processed starting with the most recently ingested ones. Re-ingesting a document
+/// makes it the most recent one, so that it doesn't show up again on a later page.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ReingestCursor {
+    ingested: OffsetDateTime,
+    /// The ID of the source document
+    id: Uuid,
+}
+
+impl ReingestCursor {
+    fn of(doc: &source_document::Model) -> Self {
+        Self {
+            ingested: doc.ingested,
+            id: doc.id,
+        }
+    }
+
+    /// Only documents coming after the cursor
+    fn condition(&self) -> Condition {
+        Condition::any()
+            .add(source_document::Column::Ingested.lt(self.ingested))
+            .add(
+                Condition::all()
+                    .add(source_document::Column::Ingested.eq(self.ingested))
+                    .add(source_document::Column::Id.lt(self.id)),
+            )
+    }
+}
+
+impl fmt::Display for ReingestCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.ingested.unix_timestamp_nanos(), self.id)
+    }
+}
+
+impl FromStr for ReingestCursor {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::BadRequest("Invalid cursor".into(), Some(s.to_string().into()));
+
+        let (ingested, id) = s.split_once('.').ok_or_else(invalid)?;
+        let ingested = ingested.parse().map_err(|_| invalid())?;
+
+        Ok(Self {
+            ingested: OffsetDateTime::from_unix_timestamp_nanos(ingested).map_err(|_| invalid())?,
+            id: Uuid::parse_str(id).map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// The kind of document which was ingested
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Kind {
+    Sbom,
+    Advisory,
+}
+
+impl Kind {
+    /// The vague format of the kind, which gets resolved by inspecting the document
+    fn format(self) -> Format {
+        match self {
+            Self::Sbom => Format::SBOM,
+            Self::Advisory => Format::Advisory,
+        }
+    }
+}
+
+/// A document ingested before, with what's required to ingest it the same way again
+struct StoredDocument {
+    kind: Kind,
+    /// The ID of the SBOM or advisory
+    id: Uuid,
+    labels: Labels,
+    issuer: Option<String>,
+    source_document: source_document::Model,
+}
+
+pub struct ReingestService {
+    sbom: SbomService,
+    advisory: AdvisoryService,
+}
+
+impl ReingestService {
+    pub fn new(cache: PaginationCache) -> Self {
+        Self {
+            sbom: SbomService::new(cache.clone()),
+            advisory: AdvisoryService::new(cache),
+        }
+    }
+
+    /// Re-ingest the document of a single SBOM or advisory.
+    ///
+    /// Returns `None` if there is neither an SBOM nor an advisory with that ID.
+    #[instrument(skip(self, ingestor, db), err(level=tracing::Level::INFO))]
+    pub async fn reingest<C>(
+        &self,
+        id: Id,
+        ingestor: &IngestorService,
+        db: &C,
+    ) -> Result<Option<IngestResult>, Error>
+    where
+        C: ConnectionTrait + TransactionTrait,
+    {
+        let Some(doc) = find(id, db).await? else {
+            return Ok(None);
+        };
+
+        let Some(data) = retrieve(ingestor, &doc.source_document).await? else {
+            return Err(Error::NotFound(format!(
+                "Missing content for source document: {}",
+                doc.source_document.sha256
+            )));
+        };
+
+        let format = doc.kind.format().resolve(&data)?;
+        self.replace(doc, format, data, ingestor, db)
+            .await
+            .map(Some)
+    }
+
+    /// Re-ingest a page of the documents of all SBOMs and advisories matching the format and
+    /// labels.
+    ///
+    /// The format can either be [`Format::SBOM`] or [`Format::Advisory`], to select all documents
+    /// of that kind, a specific format, or [`Format::Unknown`] for all documents. Each document is
+    /// re-ingested in its own transaction, so that a failing document doesn't prevent the others
+    /// from being processed.
+    ///
+    /// At most `limit` documents, coming after the cursor, are processed. The report carries the
+    /// cursor to continue with.
+    #[instrument(skip(self, ingestor, db), err(level=tracing::Level::INFO))]
+    pub async fn reingest_all<C>(
+        &self,
+        format: Format,
+        labels: Labels,
+        after: Option<ReingestCursor>,
+        limit: u64,
+        ingestor: &IngestorService,
+        db: &C,
+    ) -> Result<ReingestReport, Error>
+    where
+        C: ConnectionTrait + TransactionTrait,
+    {
+        let mut report = ReingestReport::default();
+
+        let page = find_page(format, labels, after, limit, db).await?;
+        if page.len() as u64 >= limit {
+            report.next = page
+                .last()
+                .map(|doc| ReingestCursor::of(&doc.source_document).to_string());
+        }
+
+        for doc in page {
+            let sha256 = doc.source_document.sha256.clone();
+
+            let data = match retrieve(ingestor, &doc.source_document).await {
+                Ok(Some(data)) => data,
+                Ok(None) => {
+                    log::warn!("Missing content for source document: {sha256}");
+                    report.missing.push(sha256);
+                    continue;
+                }
+                Err(err) => {
+                    report.errors.insert(sha256, err.to_string());
+                    continue;
+                }
+            };
+
+            let actual = match doc.kind.format().resolve(&data) {
+                Ok(actual) => actual,
+                Err(err) => {
+                    report.errors.insert(sha256, err.to_string());
+                    continue;
+                }
+            };
+
+            if !matches!(format, Format::Unknown | Format::SBOM | Format::Advisory)
+                && actual != format
+            {
+                continue;
+            }
+
+            match self.replace(doc, actual, data, ingestor, db).await {
+                Ok(result) => report.reingested.push(result),
+                Err(err) => {
+                    log::warn!("Failed to re-ingest source document: {sha256}: {err}");
+                    report.errors.insert(sha256, err.to_string());
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Delete the SBOM or advisory, and ingest its document again, within a single transaction.
+    ///
+    /// The content is kept in the storage, as ingesting the document stores the very same content
+    /// again. The associations of an SBOM are moved over to the new one.
+    async fn replace<C>(
+        &self,
+        doc: StoredDocument,
+        format: Format,
+        data: Vec<u8>,
+        ingestor: &IngestorService,
+        db: &C,
+    ) -> Result<IngestResult, Error>
+    where
+        C: ConnectionTrait + TransactionTrait,
+    {
+        let tx = db.begin().await?;
+
+        let associations = match doc.kind {
+            Kind::Sbom => {
+                let associations = SbomAssociations::detach(doc.id, &tx).await?;
+                self.sbom.delete_sboms(vec![doc.id], &tx).await?;
+                Some(associations)
+            }
+            Kind::Advisory => {
+                self.advisory.delete_advisory(doc.id, &tx).await?;
+                None
+            }
+        };
+
+        let result = ingestor
+            .ingest(data, format, doc.labels, doc.issuer, Cache::Skip, &tx)
+            .await?;
+
+        if let Some(associations) = associations {
+            let id = Uuid::parse_str(&result.id)
+                .map_err(|err| Error::Internal(format!("Invalid ID of re-ingested SBOM: {err}")))?;
+            associations.attach(id, &tx).await?;
+        }
+
+        tx.commit().await?;
+
+        log::info!(
+            "Re-ingested source document {} as {}",
+            doc.source_document.sha256,
+            result.id
+        );
+
+        Ok(result)
+    }
+}
+
+/// Find the SBOM or advisory with the ID
+async fn find<C: ConnectionTrait>(id: Id, connection: &C) -> Result<Option<StoredDocument>, Error> {
+    let sbom = sbom::Entity::find()
+        .find_also_related(source_document::Entity)
+        .try_filter(id.clone())?
+        .one(connection)
+        .await?;
+
+    if let Some((sbom, Some(source_document))) = sbom {
+        return Ok(Some(StoredDocument::from_sbom(sbom, source_document)));
+    }
+
+    let advisory = advisory::Entity::find()
+        .find_also_related(source_document::Entity)
+        .try_filter(id)?
+        .one(connection)
+        .await?;
+
+    Ok(match advisory {
+        Some((advisory, Some(source_document))) => {
+            Some(StoredDocument::from_advisory(advisory, source_document, connection).await?)
+        }
+        _ => None,
+    })
+}
+
+/// Find a page of SBOMs and advisories which might match the format, and carry the labels
+async fn find_page<C: ConnectionTrait>(
+    format: Format,
+    labels: Labels,
+    after: Option<ReingestCursor>,
+    limit: u64,
+    connection: &C,
+) -> Result<Vec<StoredDocument>, Error> {
+    let mut result = vec![];
+
+    if !matches!(format, Format::Advisory) {
+        let mut query = sbom::Entity::find().find_also_related(source_document::Entity);
+        if !labels.is_empty() {
+            query = query.filter(Expr::col(sbom::Column::Labels).contains(labels.clone()));
+        }
+        if let Some(after) = &after {
+            query = query.filter(after.condition());
+        }
+
+        let page = query
+            .order_by_desc(source_document::Column::Ingested)
+            .order_by_desc(source_document::Column::Id)
+            .limit(limit)
+            .all(connection)
+            .await?;
+
+        for (sbom, source_document) in page {
+            if let Some(source_document) = source_document {
+                result.push(StoredDocument::from_sbom(sbom, source_document));
+            }
+        }
+    }
+
+    if !matches!(format, Format::SBOM) {
+        let mut query = advisory::Entity::find().find_also_related(source_document::Entity);
+        if !labels.is_empty() {
+            query = query.filter(Expr::col(advisory::Column::Labels).contains(labels));
+        }
+        if let Some(after) = &after {
+            query = query.filter(after.condition());
+        }
+
+        let page = query
+            .order_by_desc(source_document::Column::Ingested)
+            .order_by_desc(source_document::Column::Id)
+            .limit(limit)
+            .all(connection)
+            .await?;
+
+        for (advisory, source_document) in page {
+            if let Some(source_document) = source_document {
+                result.push(
+                    StoredDocument::from_advisory(advisory, source_document, connection).await?,
+                );
+            }
+        }
+    }
+
+    // merge both kinds, and keep the page
+    result.sort_by_key(|doc| std::cmp::Reverse(ReingestCursor::of(&doc.source_document)));
+    result.truncate(limit as usize);
+
+    Ok(result)
+}
+
+/// The associations of an SBOM, which are not part of its document
+struct SbomAssociations {
+    /// The ID of the SBOM
+    id: Uuid,
+    /// The product versions linked to the SBOM
+    product_versions: Vec<Uuid>,
+    /// The groups the SBOM is assigned to
+    groups: Vec<Uuid>,
+}
+
+impl SbomAssociations {
+    /// Detach the associations from the SBOM, so that they survive its deletion.
+    async fn detach<C: ConnectionTrait>(id: Uuid, connection: &C) -> Result<Self, Error> {
+        // product versions would get deleted with the SBOM, so unlink them
+        let product_versions = connection
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "UPDATE product_version SET sbom_id = NULL WHERE sbom_id = $1 RETURNING id",
+                [id.into()],
+            ))
+            .await?
+            .iter()
+            .map(|row| row.try_get_by_index::<Uuid>(0))
+            .collect::<Result<_, _>>()?;
+
+        let groups = sbom_group_assignment::Entity::find()
+            .select_only()
+            .column(sbom_group_assignment::Column::GroupId)
+            .filter(sbom_group_assignment::Column::SbomId.eq(id))
+            .into_tuple()
+            .all(connection)
+            .await?;
+
+        Ok(Self {
+            id,
+            product_versions,
+            groups,
+        })
+    }
+
+    /// Attach the associations to the SBOM replacing the original one.
+    async fn attach<C: ConnectionTrait>(self, id: Uuid, connection: &C) -> Result<(), Error> {
+        // unless ingesting the document linked the product version to another SBOM already
+        connection
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "UPDATE product_version SET sbom_id = $1 WHERE id = ANY($2) AND sbom_id IS NULL",
+                [id.into(), self.product_versions.into()],
+            ))
+            .await?;
+
+        connection
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"
+INSERT INTO sbom_group_assignment (sbom_id, group_id) SELECT $1, unnest($2::uuid[])
+ON CONFLICT DO NOTHING
+"#,
+                [id.into(), self.groups.into()],
+            ))
+            .await?;
+
+        connection
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "UPDATE sbom_external_node SET target_sbom_id = $1 WHERE target_sbom_id = $2",
+                [id.into(), self.id.into()],
+            ))
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl StoredDocument {
+    fn from_sbom(sbom: sbom::Model, source_document: source_document::Model) -> Self {
+        Self {
+            kind: Kind::Sbom,
+            id: sbom.sbom_id,
+            labels: sbom.labels,
+            issuer: None,
+            source_document,
+        }
+    }
+
+    /// Create from an advisory, passing on the name of its issuer in case the document lacks it
+    async fn from_advisory<C: ConnectionTrait>(
+        advisory: advisory::Model,
+        source_document: source_document::Model,
+        connection: &C,
+    ) -> Result<Self, Error> {
+        let issuer = match advisory.issuer_id {
+            Some(issuer_id) => organization::Entity::find_by_id(issuer_id)
+                .one(connection)
+                .await?
+                .map(|organization| organization.name),
+            None => None,
+        };
+
+        Ok(Self {
+            kind: Kind::Advisory,
+            id: advisory.id,
+            labels: advisory.labels,
+            issuer,
+            source_document,
+        })
+    }
+}
+
+/// Retrieve the content of a source document from the storage
+async fn retrieve(
+    ingestor: &IngestorService,
+    doc: &source_document::Model,
+) -> Result<Option<Vec<u8>>, Error> {
+    let Some(stream) = ingestor
+        .storage()
+        .retrieve(StorageKey::from_sha256(&doc.sha256))
+        .await
+        .map_err(Error::Storage)?
+    else {
+        return Ok(None);
+    };
+
+    let data = stream
+        .map_err(Error::Storage)
+        .try_fold(Vec::new(), |mut data, chunk| async move {
+            data.extend_from_slice(&chunk);
+            Ok(data)
+        })
+        .await?;
+
+    Ok(Some(data))
+}
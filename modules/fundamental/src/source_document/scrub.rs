@@ -0,0 +1,210 @@
+use crate::Error;
+use futures_util::StreamExt;
+use hex::ToHex;
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use std::{collections::BTreeMap, pin::pin};
+use tracing::instrument;
+use trustify_common::hashing::{Contexts, Digests};
+use trustify_entity::source_document;
+use trustify_module_storage::service::{StorageBackend, StorageKey};
+
+/// The number of source documents loaded from the database at once
+const BATCH_SIZE: u64 = 100;
+
+/// The outcome of checking the integrity of the storage
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize)]
+pub struct ScrubReport {
+    /// The number of source documents checked
+    pub checked: u64,
+    /// The total size of the checked content
+    pub checked_bytes: u64,
+    /// The SHA256 digests of source documents missing from the storage
+    pub missing: Vec<String>,
+    /// The SHA256 digests of source documents with corrupted content, with the reason
+    pub corrupted: BTreeMap<String, String>,
+    /// The SHA256 digests of source documents which failed to be checked, with the reason
+    pub errors: BTreeMap<String, String>,
+}
+
+impl ScrubReport {
+    /// Check if all source documents were found intact
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty() && self.corrupted.is_empty() && self.errors.is_empty()
+    }
+}
+
+/// Re-hash the content of all source documents, and compare it with the recorded digests.
+///
+/// This detects blobs which went missing from the storage, or got corrupted over time. The
+/// content is read through the storage, so that failing to decompress or decrypt it also counts as
+/// a corruption.
+#[instrument(skip(connection, storage), err(level=tracing::Level::INFO))]
+pub async fn scrub<C, S>(connection: &C, storage: &S) -> Result<ScrubReport, Error>
+where
+    C: ConnectionTrait,
+    S: StorageBackend,
+{
+    let mut report = ScrubReport::default();
+    let mut last = None;
+
+    loop {
+        let mut query = source_document::Entity::find()
+            .order_by_asc(source_document::Column::Id)
+            .limit(BATCH_SIZE);
+        if let Some(last) = last {
+            query = query.filter(source_document::Column::Id.gt(last));
+        }
+
+        let batch = query.all(connection).await?;
+        let Some(tail) = batch.last() else {
+            break;
+        };
+        last = Some(tail.id);
+
+        for doc in &batch {
+            check(doc, storage, &mut report).await;
+        }
+    }
+
+    Ok(report)
+}
+
+/// Scrub the storage, logging the outcome.
+pub async fn run<C, S>(connection: &C, storage: &S)
+where
+    C: ConnectionTrait,
+    S: StorageBackend,
+{
+    match scrub(connection, storage).await {
+        Ok(report) if report.is_clean() => log::info!(
+            "Scrubbed {} source documents ({} bytes), no problems found",
+            report.checked,
+            report.checked_bytes
+        ),
+        Ok(report) => log::warn!(
+            "Scrubbing found {} missing and {} corrupted source documents: {}",
+            report.missing.len(),
+            report.corrupted.len(),
+            serde_json::to_string(&report).unwrap_or_default(),
+        ),
+        Err(err) => log::warn!("Failed to scrub the storage: {err}"),
+    }
+}
+
+/// Check a single source document, recording the outcome in the report
+async fn check<S: StorageBackend>(
+    doc: &source_document::Model,
+    storage: &S,
+    report: &mut ScrubReport,
+) {
+    report.checked += 1;
+
+    let stream = match storage.retrieve(StorageKey::from_sha256(&doc.sha256)).await {
+        Ok(Some(stream)) => stream,
+        Ok(None) => {
+            log::warn!("Missing source document: {}", doc.sha256);
+            report.missing.push(doc.sha256.clone());
+            return;
+        }
+        Err(err) => {
+            report.errors.insert(doc.sha256.clone(), err.to_string());
+            return;
+        }
+    };
+
+    let mut stream = pin!(stream);
+    let mut contexts = Contexts::new();
+    while let Some(chunk) = stream.next().await {
+        match chunk {
+            Ok(chunk) => contexts.update(&chunk),
+            Err(err) => {
+                log::warn!("Unreadable source document: {}: {err}", doc.sha256);
+                report
+                    .corrupted
+                    .insert(doc.sha256.clone(), format!("failed to read: {err}"));
+                return;
+            }
+        }
+    }
+
+    let digests = contexts.finish();
+    report.checked_bytes += digests.size;
+
+    if let Some(mismatch) = mismatch(doc, &digests) {
+        log::warn!("Corrupted source document: {}: {mismatch}", doc.sha256);
+        report.corrupted.insert(doc.sha256.clone(), mismatch);
+    }
+}
+
+/// Compare the recorded digests with the actual ones, describing the first mismatch
+fn mismatch(doc: &source_document::Model, digests: &Digests) -> Option<String> {
+    if digests.size != doc.size as u64 {
+        return Some(format!("size is {} instead of {}", digests.size, doc.size));
+    }
+
+    let actual: [(&str, String, Option<&String>); 4] = [
+        ("sha256", digests.sha256.encode_hex(), Some(&doc.sha256)),
+        ("sha384", digests.sha384.encode_hex(), Some(&doc.sha384)),
+        ("sha512", digests.sha512.encode_hex(), Some(&doc.sha512)),
+        (
+            "blake3",
+            digests.blake3.to_hex().to_string(),
+            doc.blake3.as_ref(),
+        ),
+    ];
+
+    actual
+        .into_iter()
+        .find(|(_, actual, expected)| expected.is_some_and(|expected| expected != actual))
+        .map(|(name, actual, _)| format!("{name} digest is {actual}"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sea_orm::{ActiveModelTrait, Set};
+    use test_context::test_context;
+    use test_log::test;
+    use trustify_test_context::TrustifyContext;
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn scrub_storage(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        ctx.ingest_document("ubi9-9.2-755.1697625012.json").await?;
+        ctx.ingest_document("zookeeper-3.9.2-cyclonedx.json")
+            .await?;
+
+        let report = scrub(&ctx.db, &ctx.storage).await?;
+        assert_eq!(report.checked, 2);
+        assert!(report.is_clean());
+
+        let mut docs = source_document::Entity::find()
+            .order_by_asc(source_document::Column::Id)
+            .all(&ctx.db)
+            .await?
+            .into_iter();
+
+        // a recorded digest not matching the content
+        let corrupted = docs.next().expect("must have a document");
+        let mut model: source_document::ActiveModel = corrupted.clone().into();
+        model.sha512 = Set("00".repeat(64));
+        model.update(&ctx.db).await?;
+
+        // content gone from the storage
+        let missing = docs.next().expect("must have a document");
+        ctx.storage
+            .delete(StorageKey::from_sha256(&missing.sha256))
+            .await?;
+
+        let report = scrub(&ctx.db, &ctx.storage).await?;
+        assert_eq!(report.checked, 2);
+        assert_eq!(report.missing, vec![missing.sha256]);
+        assert_eq!(
+            report.corrupted.keys().collect::<Vec<_>>(),
+            vec![&corrupted.sha256]
+        );
+        assert!(report.corrupted[&corrupted.sha256].starts_with("sha512 digest is "));
+
+        Ok(())
+    }
+}
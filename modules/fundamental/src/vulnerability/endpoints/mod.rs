@@ -4,10 +4,14 @@ mod test;
 use crate::common::model::Severity;
 use crate::{
     endpoints::Deprecation,
+    exception::{
+        endpoints::ExceptedParams,
+        service::{ExceptionService, suppress_sboms},
+    },
     vulnerability::{
         model::{
-            AnalysisRequest, AnalysisResponseV3, VulnerabilityDetails, VulnerabilitySummary,
-            v2::AnalysisResponse,
+            AnalysisRequest, AnalysisResponseV3, VulnerabilityAliases, VulnerabilityDetails,
+            VulnerabilitySummary, v2::AnalysisResponse,
         },
         service::VulnerabilityService,
     },
@@ -35,14 +39,17 @@ pub fn configure(
     db: db::ReadOnly,
     cache: PaginationCache,
 ) {
-    let service = VulnerabilityService::new(cache);
+    let service = VulnerabilityService::new(cache.clone());
+    let exception_service = ExceptionService::new(cache);
     config
         .app_data(web::Data::new(service))
+        .app_data(web::Data::new(exception_service))
         .app_data(web::Data::new(db))
         .service(all)
         .service(analyze) // Must be before `get` to avoid {id} matching "analyze"
         .service(analyze_v3)
-        .service(get);
+        .service(get)
+        .service(get_aliases);
 }
 
 #[allow(dead_code)]
@@ -72,6 +79,8 @@ struct VulnerabilityQuery {
 )]
 #[get("/v3/vulnerability")]
 /// List vulnerabilities
+///
+/// Exceptions are scoped to products, so vulnerabilities with an active exception are still listed.
 pub async fn all(
     state: web::Data<VulnerabilityService>,
     db: web::Data<db::ReadOnly>,
@@ -94,6 +103,7 @@ pub async fn all(
     params(
         ("id", Path, description = "ID of the vulnerability"),
         VulnerabilityGetParams,
+        ExceptedParams,
     ),
     responses(
         (status = 200, description = "Specified vulnerability", body = VulnerabilityDetails),
@@ -101,26 +111,65 @@ pub async fn all(
     ),
 )]
 #[get("/v3/vulnerability/{id}")]
+#[allow(clippy::too_many_arguments)]
 /// Retrieve vulnerability details
+///
+/// SBOMs belonging to a product with an active exception for the vulnerability are omitted,
+/// unless requested.
 pub async fn get(
     state: web::Data<VulnerabilityService>,
+    exceptions: web::Data<ExceptionService>,
     db: web::Data<db::ReadOnly>,
     id: web::Path<String>,
     web::Query(Deprecation { deprecated }): web::Query<Deprecation>,
     web::Query(VulnerabilityGetParams { scores }): web::Query<VulnerabilityGetParams>,
+    web::Query(ExceptedParams { excepted }): web::Query<ExceptedParams>,
     _: Require<ReadAdvisory>,
 ) -> actix_web::Result<impl Responder> {
     let tx = db.begin().await?;
     let vuln = state
         .fetch_vulnerability(&id, deprecated, scores, &tx)
         .await?;
-    if let Some(vuln) = vuln {
+    if let Some(mut vuln) = vuln {
+        if !excepted {
+            let suppressed = exceptions
+                .suppressed_sboms(&vuln.head.identifier, &tx)
+                .await?;
+            suppress_sboms(&mut vuln.advisories, &suppressed);
+        }
         Ok(HttpResponse::Ok().json(vuln))
     } else {
         Ok(HttpResponse::NotFound().finish())
     }
 }
 
+#[utoipa::path(
+    tag = "vulnerability",
+    operation_id = "getVulnerabilityAliases",
+    params(
+        ("id", Path, description = "ID of the vulnerability, or any of its aliases"),
+    ),
+    responses(
+        (status = 200, description = "The vulnerability and all its aliases", body = VulnerabilityAliases),
+        (status = 404, description = "The vulnerability could not be found"),
+    ),
+)]
+#[get("/v3/vulnerability/{id}/aliases")]
+/// Resolve an identifier to its vulnerability and all of its aliases
+pub async fn get_aliases(
+    state: web::Data<VulnerabilityService>,
+    db: web::Data<db::ReadOnly>,
+    id: web::Path<String>,
+    _: Require<ReadAdvisory>,
+) -> actix_web::Result<impl Responder> {
+    let tx = db.begin().await?;
+    if let Some(aliases) = state.resolve_aliases(&id, &tx).await? {
+        Ok(HttpResponse::Ok().json(aliases))
+    } else {
+        Ok(HttpResponse::NotFound().finish())
+    }
+}
+
 #[utoipa::path(
   operation_id = "v2/analyze",
   tag = "vulnerability",
@@ -371,3 +371,84 @@ async fn base_score_after_ingestion(
 
     Ok(())
 }
+
+/// Verifies that the aggregate score is part of the details only, not affecting the base score.
+#[test_context(TrustifyContext)]
+#[test_log::test(actix_web::test)]
+async fn aggregate_score_details_only(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    ctx.ingest_documents(["cve/CVE-2024-29025.json", "csaf/rhsa-2024-2705.json"])
+        .await?;
+
+    // the details carry the aggregate score, next to the base score
+
+    let vuln = get_vulnerability(ctx, "/api/v3/vulnerability/CVE-2024-29025").await?;
+    assert!(vuln["aggregate_score"]["value"].is_number());
+    assert!(
+        !vuln["aggregate_score"]["components"]
+            .as_array()
+            .expect("must be an array")
+            .is_empty()
+    );
+    assert_eq!(
+        vuln["base_score"],
+        json!({"type": "3.1", "score": 5.3, "severity": "medium"})
+    );
+
+    // the summaries don't
+
+    let response = get_vulnerability(ctx, "/api/v3/vulnerability").await?;
+    let summary = response["items"]
+        .as_array()
+        .expect("must be an array")
+        .iter()
+        .find(|v| v["identifier"] == "CVE-2024-29025")
+        .expect("CVE-2024-29025 must be in the list");
+    assert!(summary.get("aggregate_score").is_none());
+    assert_eq!(summary["base_score"], vuln["base_score"]);
+
+    Ok(())
+}
+
+#[test_context(TrustifyContext)]
+#[test_log::test(actix_web::test)]
+async fn resolve_aliases(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    ctx.ingest_documents(["osv/RUSTSEC-2021-0079.json", "csaf/RHBA-2024_1440.json"])
+        .await?;
+
+    // OSV: any of the IDs resolve to the same vulnerability
+
+    for id in ["CVE-2021-32714", "GHSA-5h46-h7hh-c6x9", "RUSTSEC-2021-0079"] {
+        let response =
+            get_vulnerability(ctx, &format!("/api/v3/vulnerability/{id}/aliases")).await?;
+        assert_eq!(
+            response,
+            json!({
+                "identifier": "CVE-2021-32714",
+                "aliases": ["GHSA-5h46-h7hh-c6x9", "RUSTSEC-2021-0079"],
+            })
+        );
+    }
+
+    // CSAF: the IDs of a vulnerability are aliases too
+
+    let response = get_vulnerability(ctx, "/api/v3/vulnerability/2253330/aliases").await?;
+    assert_eq!(
+        response,
+        json!({
+            "identifier": "CVE-2023-39326",
+            "aliases": ["2253330"],
+        })
+    );
+
+    let app = caller(ctx).await?;
+    let response = app
+        .call_service(
+            TestRequest::get()
+                .uri("/api/v3/vulnerability/GHSA-xxxx-xxxx-xxxx/aliases")
+                .to_request(),
+        )
+        .await;
+    assert_eq!(response.status(), 404);
+
+    Ok(())
+}
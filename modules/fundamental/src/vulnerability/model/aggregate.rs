@@ -0,0 +1,201 @@
+use crate::common::model::{Score, ScoreType, Severity};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use trustify_entity::{advisory, advisory_vulnerability_score};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// A score aggregated from the scores of all advisories, weighted by the trust in their source.
+///
+/// It is calculated when reading the details of a vulnerability only. Summaries, lists, and
+/// sorting keep using the `base_score` of the vulnerability, which is stored with it.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema, PartialEq)]
+pub struct AggregateScore {
+    /// The weighted average of all component scores, rounded to one decimal place.
+    pub value: f64,
+    /// The severity band derived from the aggregated value, using the CVSS v3.1 bands.
+    pub severity: Severity,
+    /// The scores which contributed to the aggregate.
+    pub components: Vec<ScoreComponent>,
+}
+
+/// A single score contributing to an [`AggregateScore`].
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema, PartialEq)]
+pub struct ScoreComponent {
+    /// The advisory providing the score.
+    pub advisory_id: Uuid,
+    /// The type of source of the advisory (e.g. `cve`, `nvd`, `csaf`, `osv`), if known.
+    pub source: Option<String>,
+    /// The score of the advisory. If it provides more than one, the most recent scoring system is used.
+    pub score: Score,
+    /// The weight of the score, based on the trust in its source.
+    pub weight: f64,
+}
+
+/// The trust in a source of advisories, based on its type.
+///
+/// The CVE record is considered authoritative, followed by the NVD analysis of it. Vendor
+/// advisories come next, aggregated sources last.
+pub fn source_trust(source: Option<&str>) -> f64 {
+    match source {
+        Some("cve") => 1.0,
+        Some("nvd") => 0.9,
+        Some("csaf") => 0.8,
+        Some("osv") => 0.6,
+        _ => 0.5,
+    }
+}
+
+impl AggregateScore {
+    /// Aggregate the scores of the provided advisories.
+    ///
+    /// Scores of withdrawn or deprecated advisories, as well as of advisories which are not
+    /// provided, are ignored. Returns `None` if no score remains.
+    pub fn from_scores(
+        advisories: &[advisory::Model],
+        scores: &[advisory_vulnerability_score::Model],
+    ) -> Option<Self> {
+        // the most recent scoring system of each advisory
+        let mut latest = BTreeMap::<Uuid, &advisory_vulnerability_score::Model>::new();
+        for score in scores {
+            latest
+                .entry(score.advisory_id)
+                .and_modify(|current| {
+                    if score.r#type > current.r#type {
+                        *current = score;
+                    }
+                })
+                .or_insert(score);
+        }
+
+        let components: Vec<_> = latest
+            .into_iter()
+            .filter_map(|(advisory_id, score)| {
+                let advisory = advisories
+                    .iter()
+                    .find(|advisory| advisory.id == advisory_id)?;
+                if advisory.withdrawn.is_some() || advisory.deprecated {
+                    return None;
+                }
+
+                let source = advisory.labels.0.get("type").cloned();
+                let weight = source_trust(source.as_deref());
+                Some(ScoreComponent {
+                    advisory_id,
+                    source,
+                    score: Score::from(score.clone()),
+                    weight,
+                })
+            })
+            .collect();
+
+        let weights: f64 = components.iter().map(|c| c.weight).sum();
+        if components.is_empty() || weights <= 0.0 {
+            return None;
+        }
+
+        let value = components
+            .iter()
+            .map(|c| c.score.value * c.weight)
+            .sum::<f64>()
+            / weights;
+        let value = (value * 10.0).round() / 10.0;
+
+        Some(Self {
+            value,
+            severity: Severity::from((ScoreType::V3_1, value)),
+            components,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use advisory_vulnerability_score::{ScoreType as EntityScoreType, Severity as EntitySeverity};
+    use time::OffsetDateTime;
+    use trustify_entity::labels::Labels;
+
+    fn advisory(id: u128, source: &str, withdrawn: bool) -> advisory::Model {
+        advisory::Model {
+            id: Uuid::from_u128(id),
+            identifier: format!("ADV-{id}"),
+            version: None,
+            document_id: format!("ADV-{id}"),
+            deprecated: false,
+            issuer_id: None,
+            published: None,
+            modified: None,
+            withdrawn: withdrawn.then(OffsetDateTime::now_utc),
+            title: None,
+            labels: Labels::new().add("type", source),
+            source_document_id: Uuid::nil(),
+        }
+    }
+
+    fn score(
+        advisory: u128,
+        r#type: EntityScoreType,
+        score: f32,
+    ) -> advisory_vulnerability_score::Model {
+        advisory_vulnerability_score::Model {
+            id: Uuid::now_v7(),
+            advisory_id: Uuid::from_u128(advisory),
+            vulnerability_id: "CVE-2024-0001".into(),
+            r#type,
+            vector: String::new(),
+            score,
+            severity: EntitySeverity::from((score, r#type)),
+        }
+    }
+
+    #[test]
+    fn weighted_by_source() {
+        let advisories = [advisory(1, "cve", false), advisory(2, "osv", false)];
+        let scores = [
+            score(1, EntityScoreType::V3_1, 9.0),
+            score(2, EntityScoreType::V3_1, 5.0),
+        ];
+
+        let aggregate =
+            AggregateScore::from_scores(&advisories, &scores).expect("must have a score");
+        // (9.0 * 1.0 + 5.0 * 0.6) / 1.6
+        assert_eq!(aggregate.value, 7.5);
+        assert_eq!(aggregate.severity, Severity::High);
+        assert_eq!(aggregate.components.len(), 2);
+    }
+
+    #[test]
+    fn ignore_withdrawn() {
+        let advisories = [advisory(1, "cve", false), advisory(2, "csaf", true)];
+        let scores = [
+            score(1, EntityScoreType::V3_1, 4.0),
+            score(2, EntityScoreType::V3_1, 10.0),
+        ];
+
+        let aggregate =
+            AggregateScore::from_scores(&advisories, &scores).expect("must have a score");
+        assert_eq!(aggregate.value, 4.0);
+        assert_eq!(aggregate.components.len(), 1);
+
+        assert_eq!(
+            AggregateScore::from_scores(&advisories[1..], &scores[1..]),
+            None
+        );
+    }
+
+    #[test]
+    fn prefer_latest_score_type() {
+        let advisories = [advisory(1, "csaf", false)];
+        let scores = [
+            score(1, EntityScoreType::V2_0, 2.0),
+            score(1, EntityScoreType::V4_0, 8.0),
+            score(1, EntityScoreType::V3_1, 6.0),
+        ];
+
+        let aggregate =
+            AggregateScore::from_scores(&advisories, &scores).expect("must have a score");
+        assert_eq!(aggregate.value, 8.0);
+        assert_eq!(aggregate.components[0].score.r#type, ScoreType::V4);
+    }
+}
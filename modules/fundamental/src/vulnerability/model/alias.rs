@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// A vulnerability, along with all the identifiers it is known by.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema, PartialEq, Eq)]
+pub struct VulnerabilityAliases {
+    /// The identifier of the vulnerability the requested identifier resolved to.
+    pub identifier: String,
+    /// The other identifiers of the vulnerability, like GHSA, RUSTSEC, or distribution specific
+    /// ones, as stated by advisories.
+    pub aliases: Vec<String>,
+}
@@ -2,17 +2,25 @@ mod vulnerability_advisory;
 
 pub use vulnerability_advisory::*;
 
-use crate::{Error, common::model::ScoredVector, vulnerability::model::VulnerabilityHead};
+use crate::{
+    Error,
+    common::model::ScoredVector,
+    vulnerability::model::{AggregateScore, EpssScore, KevEntry, VulnerabilityHead},
+};
 use isx::IsDefault;
 use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, ModelTrait, QueryFilter};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 use tracing::{info_span, instrument};
 use tracing_futures::Instrument;
 use trustify_common::{
     memo::Memo,
     requested_field::{BoolRequestedField, RequestedField},
 };
-use trustify_entity::{advisory_vulnerability, advisory_vulnerability_score, vulnerability};
+use trustify_entity::{
+    advisory, advisory_vulnerability, advisory_vulnerability_score, vulnerability,
+    vulnerability_alias, vulnerability_epss, vulnerability_kev,
+};
 use trustify_module_ingestor::common::{Deprecation, DeprecationForExt};
 use utoipa::ToSchema;
 
@@ -28,6 +36,26 @@ pub struct VulnerabilityDetails {
     /// Only present when the `scores` query parameter is set to `true`.
     #[serde(default, skip_serializing_if = "IsDefault::is_default")]
     pub scores: RequestedField<Vec<ScoredVector>>,
+
+    /// The score aggregated from all advisories, weighted by the trust in their source, along
+    /// with the scores it was calculated from. Withdrawn advisories are ignored, and rejected
+    /// vulnerabilities don't have an aggregated score. Only part of the details, not of the
+    /// summaries.
+    pub aggregate_score: Option<AggregateScore>,
+
+    /// Alternative identifiers of this vulnerability (e.g. `GHSA-…`), as stated by advisories.
+    pub aliases: Vec<String>,
+
+    /// The EPSS score of this vulnerability, if known.
+    #[schema(required)]
+    pub epss_score: Option<EpssScore>,
+
+    /// If the vulnerability is listed in the CISA catalog of known exploited vulnerabilities.
+    pub known_exploited: bool,
+
+    /// The entry of the CISA catalog of known exploited vulnerabilities, if listed.
+    #[schema(required)]
+    pub kev: Option<KevEntry>,
 }
 
 impl VulnerabilityDetails {
@@ -69,6 +97,40 @@ impl VulnerabilityDetails {
             })
         });
 
+        let aggregate_score = match vulnerability.withdrawn {
+            Some(_) => None,
+            None => {
+                let advisory_ids: BTreeSet<_> = scores.iter().map(|s| s.advisory_id).collect();
+                let scored_advisories = advisory::Entity::find()
+                    .filter(advisory::Column::Id.is_in(advisory_ids))
+                    .all(tx)
+                    .instrument(info_span!("find scored advisories"))
+                    .await?;
+                AggregateScore::from_scores(&scored_advisories, &scores)
+            }
+        };
+
+        let aliases: BTreeSet<_> = vulnerability
+            .find_related(vulnerability_alias::Entity)
+            .all(tx)
+            .instrument(info_span!("find aliases"))
+            .await?
+            .into_iter()
+            .map(|alias| alias.alias)
+            .collect();
+
+        let epss_score = vulnerability_epss::Entity::find_by_id(&vulnerability.id)
+            .one(tx)
+            .instrument(info_span!("find EPSS score"))
+            .await?
+            .map(EpssScore::from);
+
+        let kev = vulnerability_kev::Entity::find_by_id(&vulnerability.id)
+            .one(tx)
+            .instrument(info_span!("find KEV entry"))
+            .await?
+            .map(KevEntry::from);
+
         let advisories = VulnerabilityAdvisorySummary::from_entities(
             vulnerability,
             &advisory_vulnerabilities,
@@ -86,6 +148,11 @@ impl VulnerabilityDetails {
             .await?,
             advisories,
             scores: authoritative_scores,
+            aggregate_score,
+            aliases: aliases.into_iter().collect(),
+            epss_score,
+            known_exploited: kev.is_some(),
+            kev,
         })
     }
 }
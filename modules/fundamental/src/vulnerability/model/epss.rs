@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use trustify_entity::vulnerability_epss;
+use utoipa::ToSchema;
+
+/// The EPSS (Exploit Prediction Scoring System) score of a vulnerability.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema, PartialEq)]
+pub struct EpssScore {
+    /// The probability (0 to 1) of exploitation activity in the next 30 days.
+    pub probability: f64,
+    /// The percentile of the probability, compared to all other scored vulnerabilities.
+    pub percentile: f64,
+    /// The date (in RFC3339 format) the score was calculated for, if known.
+    #[schema(required)]
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub date: Option<OffsetDateTime>,
+}
+
+impl From<vulnerability_epss::Model> for EpssScore {
+    fn from(value: vulnerability_epss::Model) -> Self {
+        Self {
+            probability: value.probability,
+            percentile: value.percentile,
+            date: value.date,
+        }
+    }
+}
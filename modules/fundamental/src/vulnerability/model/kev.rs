@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use trustify_entity::vulnerability_kev;
+use utoipa::ToSchema;
+
+/// An entry of the CISA catalog of known exploited vulnerabilities (KEV).
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema, PartialEq, Eq)]
+pub struct KevEntry {
+    /// The date (in RFC3339 format) the vulnerability was added to the catalog.
+    #[serde(with = "time::serde::rfc3339")]
+    pub date_added: OffsetDateTime,
+    /// The date (in RFC3339 format) by which the required action must be applied, if any.
+    #[schema(required)]
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub due_date: Option<OffsetDateTime>,
+    /// The action required to remediate the vulnerability, if any.
+    #[schema(required)]
+    pub required_action: Option<String>,
+    /// If the vulnerability is known to be used in ransomware campaigns.
+    pub known_ransomware_campaign_use: bool,
+}
+
+impl From<vulnerability_kev::Model> for KevEntry {
+    fn from(value: vulnerability_kev::Model) -> Self {
+        Self {
+            date_added: value.date_added,
+            due_date: value.due_date,
+            required_action: value.required_action,
+            known_ransomware_campaign_use: value.known_ransomware_campaign_use,
+        }
+    }
+}
@@ -1,10 +1,18 @@
+mod aggregate;
+mod alias;
 mod analyze;
 mod details;
+mod epss;
+mod kev;
 mod summary;
 pub mod v2;
 
+pub use aggregate::*;
+pub use alias::*;
 pub use analyze::*;
 pub use details::*;
+pub use epss::*;
+pub use kev::*;
 pub use summary::*;
 
 use crate::{
@@ -1,14 +1,25 @@
-use crate::{Error, vulnerability::model::VulnerabilityHead};
+use crate::{
+    Error,
+    vulnerability::model::{KevEntry, VulnerabilityHead},
+};
 use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, LoaderTrait, QueryFilter};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use trustify_common::memo::Memo;
-use trustify_entity::{vulnerability, vulnerability_description};
+use trustify_entity::{vulnerability, vulnerability_description, vulnerability_kev};
 use utoipa::ToSchema;
 
 #[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 pub struct VulnerabilitySummary {
     #[serde(flatten)]
     pub head: VulnerabilityHead,
+
+    /// If the vulnerability is listed in the CISA catalog of known exploited vulnerabilities.
+    pub known_exploited: bool,
+
+    /// The entry of the CISA catalog of known exploited vulnerabilities, if listed.
+    #[schema(required)]
+    pub kev: Option<KevEntry>,
 }
 
 impl VulnerabilitySummary {
@@ -27,9 +38,21 @@ impl VulnerabilitySummary {
             )
             .await?;
 
+        let mut kev: HashMap<_, _> = vulnerability_kev::Entity::find()
+            .filter(
+                vulnerability_kev::Column::VulnerabilityId
+                    .is_in(vulnerabilities.iter().map(|vuln| vuln.id.clone())),
+            )
+            .all(tx)
+            .await?
+            .into_iter()
+            .map(|entry| (entry.vulnerability_id.clone(), KevEntry::from(entry)))
+            .collect();
+
         let mut summaries = Vec::new();
 
         for (vuln, description) in vulnerabilities.iter().zip(descriptions.iter()) {
+            let kev = kev.remove(&vuln.id);
             summaries.push(VulnerabilitySummary {
                 head: VulnerabilityHead::from_vulnerability_entity(
                     vuln,
@@ -37,6 +60,8 @@ impl VulnerabilitySummary {
                     tx,
                 )
                 .await?,
+                known_exploited: kev.is_some(),
+                kev,
             });
         }
 
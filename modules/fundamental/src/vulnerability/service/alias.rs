@@ -0,0 +1,54 @@
+use super::VulnerabilityService;
+use crate::{Error, vulnerability::model::VulnerabilityAliases};
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, ModelTrait, QueryFilter, QueryOrder};
+use std::collections::BTreeSet;
+use trustify_entity::{vulnerability, vulnerability_alias};
+
+impl VulnerabilityService {
+    /// Resolve an identifier to the vulnerability it refers to, along with all of its aliases.
+    pub async fn resolve_aliases<C: ConnectionTrait>(
+        &self,
+        identifier: &str,
+        connection: &C,
+    ) -> Result<Option<VulnerabilityAliases>, Error> {
+        let Some(vulnerability) = resolve(identifier, connection).await? else {
+            return Ok(None);
+        };
+
+        let aliases: BTreeSet<_> = vulnerability
+            .find_related(vulnerability_alias::Entity)
+            .all(connection)
+            .await?
+            .into_iter()
+            .map(|alias| alias.alias)
+            .collect();
+
+        Ok(Some(VulnerabilityAliases {
+            identifier: vulnerability.id,
+            aliases: aliases.into_iter().collect(),
+        }))
+    }
+}
+
+/// Find the vulnerability an identifier refers to, either as its ID or as one of its aliases.
+///
+/// Should an alias be stated for more than one vulnerability, the first one (by ID) is chosen, so
+/// that lookups through the same alias always converge on the same vulnerability.
+pub(super) async fn resolve<C: ConnectionTrait>(
+    identifier: &str,
+    connection: &C,
+) -> Result<Option<vulnerability::Model>, Error> {
+    if let Some(vulnerability) = vulnerability::Entity::find_by_id(identifier)
+        .one(connection)
+        .await?
+    {
+        return Ok(Some(vulnerability));
+    }
+
+    Ok(vulnerability::Entity::find()
+        .inner_join(vulnerability_alias::Entity)
+        .filter(vulnerability_alias::Column::Alias.eq(identifier))
+        .order_by_asc(vulnerability::Column::Id)
+        .one(connection)
+        .await?)
+}
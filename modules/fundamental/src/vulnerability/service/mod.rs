@@ -1,3 +1,4 @@
+mod alias;
 #[cfg(test)]
 mod test;
 
@@ -111,10 +112,8 @@ impl VulnerabilityService {
         include_scores: bool,
         connection: &C,
     ) -> Result<Option<VulnerabilityDetails>, Error> {
-        if let Some(vulnerability) = vulnerability::Entity::find_by_id(identifier)
-            .one(connection)
-            .await?
-        {
+        // the identifier may also be an alias, like a GHSA identifier of a CVE
+        if let Some(vulnerability) = alias::resolve(identifier, connection).await? {
             Ok(Some(
                 VulnerabilityDetails::from_entity(
                     &vulnerability,
@@ -8,6 +8,7 @@ use serde_json::json;
 use std::str::FromStr;
 use test_context::test_context;
 use test_log::test;
+use time::macros::datetime;
 use trustify_common::{
     db::{
         pagination_cache::PaginationCache,
@@ -98,6 +99,93 @@ async fn statuses(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+#[test_context(TrustifyContext)]
+#[test(tokio::test)]
+async fn fetch_by_alias(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    let service = VulnerabilityService::new(PaginationCache::for_test());
+
+    ctx.ingest_documents(["osv/GHSA-2ccf-ffrj-m4qw.json"])
+        .await?;
+
+    let by_cve = service
+        .fetch_vulnerability("CVE-2023-29020", Default::default(), false, &ctx.db)
+        .await?
+        .expect("must find the vulnerability by its ID");
+    assert_eq!(by_cve.aliases, vec!["GHSA-2ccf-ffrj-m4qw".to_string()]);
+    assert_eq!(
+        by_cve.advisories[0]
+            .head
+            .head
+            .labels
+            .0
+            .get("github_reviewed")
+            .map(String::as_str),
+        Some("true")
+    );
+
+    let by_ghsa = service
+        .fetch_vulnerability("GHSA-2ccf-ffrj-m4qw", Default::default(), false, &ctx.db)
+        .await?
+        .expect("must find the vulnerability by its alias");
+    assert_eq!(by_ghsa.head.identifier, "CVE-2023-29020");
+
+    Ok(())
+}
+
+#[test_context(TrustifyContext)]
+#[test(tokio::test)]
+async fn fetch_with_epss(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    let service = VulnerabilityService::new(PaginationCache::for_test());
+
+    ctx.ingest_documents(["osv/GHSA-2ccf-ffrj-m4qw.json", "epss/epss_scores.csv"])
+        .await?;
+
+    let vuln = service
+        .fetch_vulnerability("CVE-2023-29020", Default::default(), false, &ctx.db)
+        .await?
+        .expect("must find the vulnerability");
+    let epss = vuln.epss_score.expect("must have an EPSS score");
+    assert_eq!(epss.probability, 0.00113);
+    assert_eq!(epss.percentile, 0.30624);
+    assert!(!vuln.known_exploited);
+
+    Ok(())
+}
+
+#[test_context(TrustifyContext)]
+#[test(tokio::test)]
+async fn known_exploited(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    let service = VulnerabilityService::new(PaginationCache::for_test());
+
+    ctx.ingest_documents([
+        "osv/GHSA-2ccf-ffrj-m4qw.json",
+        "kev/known_exploited_vulnerabilities.json",
+    ])
+    .await?;
+
+    let vuln = service
+        .fetch_vulnerability("CVE-2023-29020", Default::default(), false, &ctx.db)
+        .await?
+        .expect("must find the vulnerability");
+    assert!(vuln.known_exploited);
+    let kev = vuln.kev.expect("must have a KEV entry");
+    assert_eq!(kev.date_added, datetime!(2025-10-01 0:00 UTC));
+    assert_eq!(kev.due_date, Some(datetime!(2025-10-22 0:00 UTC)));
+
+    let vulns = service
+        .fetch_vulnerabilities(
+            q("CVE-2023-29020"),
+            Paginated::default(),
+            Default::default(),
+            &ctx.db,
+        )
+        .await?;
+    assert_eq!(vulns.items.len(), 1);
+    assert!(vulns.items[0].known_exploited);
+
+    Ok(())
+}
+
 #[test_context(TrustifyContext)]
 #[test(tokio::test)]
 async fn statuses_too(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
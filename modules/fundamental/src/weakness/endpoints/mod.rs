@@ -1,10 +1,22 @@
-use crate::{license::model::LicenseSummary, weakness::service::WeaknessService};
+use crate::{
+    license::model::LicenseSummary,
+    vulnerability::model::VulnerabilitySummary,
+    weakness::{model::WeaknessHierarchy, service::WeaknessService},
+};
 use actix_web::{HttpResponse, Responder, get, web};
-use trustify_auth::{ReadWeakness, authorizer::Require};
+use trustify_auth::{ReadAdvisory, ReadWeakness, authorizer::Require};
 use trustify_common::{
     db::{self, pagination_cache::PaginationCache, query::Query},
     model::{Paginated, PaginatedResults},
 };
+use utoipa::IntoParams;
+
+#[derive(Clone, Debug, PartialEq, Eq, Default, serde::Deserialize, IntoParams)]
+pub struct WeaknessVulnerabilitiesParams {
+    /// Also include vulnerabilities tagged with any descendant of the weakness.
+    #[serde(default)]
+    pub descendants: bool,
+}
 
 pub fn configure(
     config: &mut utoipa_actix_web::service_config::ServiceConfig,
@@ -17,7 +29,9 @@ pub fn configure(
         .app_data(web::Data::new(db))
         .app_data(web::Data::new(weakness_service))
         .service(list_weaknesses)
-        .service(get_weakness);
+        .service(get_weakness)
+        .service(get_weakness_hierarchy)
+        .service(list_weakness_vulnerabilities);
 }
 
 #[utoipa::path(
@@ -68,5 +82,72 @@ pub async fn get_weakness(
     }
 }
 
+#[utoipa::path(
+    tag = "weakness",
+    operation_id = "getWeaknessHierarchy",
+    params(
+        ("id", Path, description = "ID of the weakness, like `CWE-79`"),
+    ),
+    responses(
+        (status = 200, description = "The weakness with its related weaknesses", body = WeaknessHierarchy),
+        (status = 404, description = "The weakness could not be found"),
+    ),
+)]
+#[get("/v3/weakness/{id}/hierarchy")]
+/// Retrieve the parents, children, and related weaknesses of a weakness
+pub async fn get_weakness_hierarchy(
+    state: web::Data<WeaknessService>,
+    db: web::Data<db::ReadOnly>,
+    id: web::Path<String>,
+    _: Require<ReadWeakness>,
+) -> actix_web::Result<impl Responder> {
+    let tx = db.begin().await?;
+    if let Some(hierarchy) = state.get_weakness_hierarchy(&id, &tx).await? {
+        Ok(HttpResponse::Ok().json(hierarchy))
+    } else {
+        Ok(HttpResponse::NotFound().finish())
+    }
+}
+
+#[utoipa::path(
+    tag = "weakness",
+    operation_id = "listWeaknessVulnerabilities",
+    params(
+        ("id", Path, description = "ID of the weakness, like `CWE-79`"),
+        WeaknessVulnerabilitiesParams,
+        Query,
+        Paginated,
+    ),
+    responses(
+        (status = 200, description = "Vulnerabilities tagged with the weakness", body = PaginatedResults<VulnerabilitySummary>),
+        (status = 404, description = "The weakness could not be found"),
+    ),
+)]
+#[get("/v3/weakness/{id}/vulnerabilities")]
+#[allow(clippy::too_many_arguments)]
+/// List vulnerabilities tagged with a weakness
+pub async fn list_weakness_vulnerabilities(
+    state: web::Data<WeaknessService>,
+    db: web::Data<db::ReadOnly>,
+    id: web::Path<String>,
+    web::Query(WeaknessVulnerabilitiesParams { descendants }): web::Query<
+        WeaknessVulnerabilitiesParams,
+    >,
+    web::Query(search): web::Query<Query>,
+    web::Query(paginated): web::Query<Paginated>,
+    _: Require<ReadWeakness>,
+    _: Require<ReadAdvisory>,
+) -> actix_web::Result<impl Responder> {
+    let tx = db.begin().await?;
+    if let Some(result) = state
+        .list_vulnerabilities(&id, descendants, search, paginated, &tx)
+        .await?
+    {
+        Ok(HttpResponse::Ok().json(result))
+    } else {
+        Ok(HttpResponse::NotFound().finish())
+    }
+}
+
 #[cfg(test)]
 mod test;
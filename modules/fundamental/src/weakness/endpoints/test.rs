@@ -1,7 +1,9 @@
 use crate::test::caller;
-use crate::weakness::model::{WeaknessDetails, WeaknessSummary};
+use crate::vulnerability::model::VulnerabilitySummary;
+use crate::weakness::model::{WeaknessDetails, WeaknessHierarchy, WeaknessSummary};
 use actix_http::StatusCode;
 use actix_web::test::TestRequest;
+use serde_json::{Value, json};
 use test_context::test_context;
 use test_log::test;
 use trustify_common::model::PaginatedResults;
@@ -99,3 +101,147 @@ async fn get_weakness(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
 
     Ok(())
 }
+
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn get_weakness_attack_patterns(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    let zip = document_read("cwec_latest.xml.zip")?;
+
+    let mut archive = ZipArchive::new(zip)?;
+
+    let entry = archive.by_index(0)?;
+
+    ctx.ingest_read(entry).await?;
+    ctx.ingest_document("capec/capec_excerpt.xml").await?;
+
+    let app = caller(ctx).await?;
+
+    let uri = "/api/v3/weakness/CWE-276";
+
+    let request = TestRequest::get().uri(uri).to_request();
+
+    let response: WeaknessDetails = app.call_and_read_body_json(request).await;
+
+    assert_eq!(response.attack_patterns.len(), 1);
+    assert_eq!(response.attack_patterns[0].id, "CAPEC-1");
+
+    Ok(())
+}
+
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn get_weakness_hierarchy(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    let zip = document_read("cwec_latest.xml.zip")?;
+
+    let mut archive = ZipArchive::new(zip)?;
+
+    let entry = archive.by_index(0)?;
+
+    ctx.ingest_read(entry).await?;
+
+    let app = caller(ctx).await?;
+
+    // parents are only listed once, even if related in multiple views
+
+    let uri = "/api/v3/weakness/CWE-770/hierarchy";
+    let request = TestRequest::get().uri(uri).to_request();
+    let response: WeaknessHierarchy = app.call_and_read_body_json(request).await;
+
+    let parents: Vec<_> = response.parents.iter().map(|w| w.id.as_str()).collect();
+    assert_eq!(parents, ["CWE-400", "CWE-665"]);
+    assert!(response.parents[0].description.is_some());
+
+    // children are found through their parent relationship
+
+    let uri = "/api/v3/weakness/CWE-400/hierarchy";
+    let request = TestRequest::get().uri(uri).to_request();
+    let response: WeaknessHierarchy = app.call_and_read_body_json(request).await;
+
+    let children: Vec<_> = response.children.iter().map(|w| w.id.as_str()).collect();
+    assert!(children.contains(&"CWE-770"));
+    assert!(children.contains(&"CWE-920"));
+
+    // other relationships
+
+    let uri = "/api/v3/weakness/CWE-1265/hierarchy";
+    let request = TestRequest::get().uri(uri).to_request();
+    let response: Value = app.call_and_read_body_json(request).await;
+
+    assert_eq!(
+        response["related"],
+        json!([
+            {
+                "id": "CWE-416",
+                "description": response["related"][0]["description"],
+                "nature": "CanPrecede",
+            },
+            {
+                "id": "CWE-663",
+                "description": response["related"][1]["description"],
+                "nature": "PeerOf",
+            },
+        ])
+    );
+
+    let uri = "/api/v3/weakness/CWE-FOO/hierarchy";
+    let request = TestRequest::get().uri(uri).to_request();
+    let response = app.call_service(request).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    Ok(())
+}
+
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn list_weakness_vulnerabilities(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+    let zip = document_read("cwec_latest.xml.zip")?;
+
+    let mut archive = ZipArchive::new(zip)?;
+
+    let entry = archive.by_index(0)?;
+
+    ctx.ingest_read(entry).await?;
+
+    // tagged with CWE-400
+    ctx.ingest_document("cve/CVE-2023-44487.json").await?;
+    // tagged with CWE-770, a child of CWE-400
+    ctx.ingest_document("cve/CVE-2024-26308.json").await?;
+
+    let app = caller(ctx).await?;
+
+    let ids = |response: PaginatedResults<VulnerabilitySummary>| {
+        let mut ids: Vec<_> = response
+            .items
+            .into_iter()
+            .map(|v| v.head.identifier)
+            .collect();
+        ids.sort();
+        ids
+    };
+
+    let uri = "/api/v3/weakness/CWE-400/vulnerabilities";
+    let request = TestRequest::get().uri(uri).to_request();
+    let response: PaginatedResults<VulnerabilitySummary> =
+        app.call_and_read_body_json(request).await;
+    assert_eq!(ids(response), ["CVE-2023-44487"]);
+
+    let uri = "/api/v3/weakness/CWE-400/vulnerabilities?descendants=true&total=true";
+    let request = TestRequest::get().uri(uri).to_request();
+    let response: PaginatedResults<VulnerabilitySummary> =
+        app.call_and_read_body_json(request).await;
+    assert_eq!(response.total, Some(2));
+    assert_eq!(ids(response), ["CVE-2023-44487", "CVE-2024-26308"]);
+
+    let uri = "/api/v3/weakness/CWE-770/vulnerabilities?descendants=true";
+    let request = TestRequest::get().uri(uri).to_request();
+    let response: PaginatedResults<VulnerabilitySummary> =
+        app.call_and_read_body_json(request).await;
+    assert_eq!(ids(response), ["CVE-2024-26308"]);
+
+    let uri = "/api/v3/weakness/CWE-FOO/vulnerabilities";
+    let request = TestRequest::get().uri(uri).to_request();
+    let response = app.call_service(request).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use crate::Error;
 use serde::{Deserialize, Serialize};
-use trustify_entity::weakness;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use trustify_entity::{attack_pattern, weakness};
 use utoipa::ToSchema;
 
 #[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
@@ -9,6 +10,15 @@ pub struct WeaknessHead {
     pub description: Option<String>,
 }
 
+impl WeaknessHead {
+    pub fn from_entity(entity: &weakness::Model) -> Self {
+        Self {
+            id: entity.id.clone(),
+            description: entity.description.clone(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
 pub struct WeaknessSummary {
     #[serde(flatten)]
@@ -49,10 +59,15 @@ pub struct WeaknessDetails {
     pub requires: Option<Vec<String>>,
     pub can_also_be: Option<Vec<String>>,
     pub peer_of: Option<Vec<String>>,
+    /// CAPEC attack patterns exploiting this weakness.
+    pub attack_patterns: Vec<AttackPatternHead>,
 }
 
 impl WeaknessDetails {
-    pub async fn from_entity(entity: &weakness::Model) -> Result<Self, Error> {
+    pub async fn from_entity(
+        entity: &weakness::Model,
+        attack_patterns: &[attack_pattern::Model],
+    ) -> Result<Self, Error> {
         Ok(Self {
             head: WeaknessHead {
                 id: entity.id.clone(),
@@ -68,6 +83,149 @@ impl WeaknessDetails {
             requires: entity.requires.clone(),
             can_also_be: entity.can_also_be.clone(),
             peer_of: entity.peer_of.clone(),
+            attack_patterns: attack_patterns
+                .iter()
+                .map(AttackPatternHead::from_entity)
+                .collect(),
         })
     }
 }
+
+/// A weakness with the weaknesses it is related to.
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
+pub struct WeaknessHierarchy {
+    #[serde(flatten)]
+    pub head: WeaknessHead,
+    /// The weaknesses this weakness is a child of.
+    pub parents: Vec<WeaknessHead>,
+    /// The weaknesses which are children of this weakness.
+    pub children: Vec<WeaknessHead>,
+    /// Weaknesses related through other relationships, like `PeerOf` or `CanPrecede`.
+    pub related: Vec<RelatedWeakness>,
+}
+
+impl WeaknessHierarchy {
+    /// Build the hierarchy of a weakness.
+    ///
+    /// The `referenced` weaknesses are used to look up the descriptions of parents and related
+    /// weaknesses. References to weaknesses which are not known will only carry their ID.
+    pub fn from_entities(
+        entity: &weakness::Model,
+        children: &[weakness::Model],
+        referenced: &HashMap<String, weakness::Model>,
+    ) -> Self {
+        let head = |id: &String| {
+            referenced
+                .get(id)
+                .map(WeaknessHead::from_entity)
+                .unwrap_or_else(|| WeaknessHead {
+                    id: id.clone(),
+                    description: None,
+                })
+        };
+
+        // the catalog lists relationships once for each view, so they need to be de-duplicated
+
+        let parents = entity
+            .child_of
+            .iter()
+            .flatten()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(head)
+            .collect();
+
+        let mut all_children = children
+            .iter()
+            .map(|child| (child.id.clone(), WeaknessHead::from_entity(child)))
+            .collect::<BTreeMap<_, _>>();
+        for id in entity.parent_of.iter().flatten() {
+            all_children.entry(id.clone()).or_insert_with(|| head(id));
+        }
+
+        let related = WeaknessRelationship::ALL
+            .into_iter()
+            .flat_map(|nature| {
+                nature
+                    .targets(entity)
+                    .iter()
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
+                    .map(move |id| (nature, id))
+            })
+            .map(|(nature, id)| RelatedWeakness {
+                head: head(id),
+                nature,
+            })
+            .collect();
+
+        Self {
+            head: WeaknessHead::from_entity(entity),
+            parents,
+            children: all_children.into_values().collect(),
+            related,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
+pub struct RelatedWeakness {
+    #[serde(flatten)]
+    pub head: WeaknessHead,
+    pub nature: WeaknessRelationship,
+}
+
+/// The nature of a relationship between two weaknesses, as used by the CWE catalog.
+#[derive(Serialize, Deserialize, ToSchema, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WeaknessRelationship {
+    StartsWith,
+    CanFollow,
+    CanPrecede,
+    RequiredBy,
+    Requires,
+    CanAlsoBe,
+    PeerOf,
+}
+
+impl WeaknessRelationship {
+    pub const ALL: [Self; 7] = [
+        Self::StartsWith,
+        Self::CanFollow,
+        Self::CanPrecede,
+        Self::RequiredBy,
+        Self::Requires,
+        Self::CanAlsoBe,
+        Self::PeerOf,
+    ];
+
+    /// Get the weaknesses of this relationship from a weakness.
+    pub fn targets(self, entity: &weakness::Model) -> &[String] {
+        match self {
+            Self::StartsWith => &entity.starts_with,
+            Self::CanFollow => &entity.can_follow,
+            Self::CanPrecede => &entity.can_precede,
+            Self::RequiredBy => &entity.required_by,
+            Self::Requires => &entity.requires,
+            Self::CanAlsoBe => &entity.can_also_be,
+            Self::PeerOf => &entity.peer_of,
+        }
+        .as_deref()
+        .unwrap_or_default()
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
+pub struct AttackPatternHead {
+    /// The CAPEC ID, like `CAPEC-1`.
+    pub id: String,
+    pub name: Option<String>,
+}
+
+impl AttackPatternHead {
+    pub fn from_entity(entity: &attack_pattern::Model) -> Self {
+        Self {
+            id: entity.id.clone(),
+            name: entity.name.clone(),
+        }
+    }
+}
@@ -1,8 +1,11 @@
 use crate::{
     Error,
-    weakness::model::{WeaknessDetails, WeaknessSummary},
+    vulnerability::model::VulnerabilitySummary,
+    weakness::model::{WeaknessDetails, WeaknessHierarchy, WeaknessRelationship, WeaknessSummary},
 };
-use sea_orm::{ConnectionTrait, EntityTrait};
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use sea_query::{Expr, PgFunc, extension::postgres::PgBinOper};
+use std::collections::{BTreeSet, HashMap};
 use trustify_common::{
     db::{
         limiter::{LimitedResult, LimiterTrait},
@@ -11,7 +14,7 @@ use trustify_common::{
     },
     model::{PaginatedResults, Pagination},
 };
-use trustify_entity::weakness;
+use trustify_entity::{attack_pattern, vulnerability, weakness};
 
 pub struct WeaknessService {
     cache: PaginationCache,
@@ -45,16 +48,145 @@ impl WeaknessService {
         })
     }
 
-    /// Gets a single weakness by ID.
+    /// Gets a single weakness by ID, along with the attack patterns exploiting it.
     pub async fn get_weakness(
         &self,
         id: &str,
         connection: &impl ConnectionTrait,
     ) -> Result<Option<WeaknessDetails>, Error> {
         if let Some(found) = weakness::Entity::find_by_id(id).one(connection).await? {
-            Ok(Some(WeaknessDetails::from_entity(&found).await?))
+            let attack_patterns = attack_pattern::Entity::find()
+                .filter(Expr::val(found.id.as_str()).eq(PgFunc::any(Expr::col(
+                    attack_pattern::Column::RelatedWeaknesses,
+                ))))
+                .order_by_asc(attack_pattern::Column::Id)
+                .all(connection)
+                .await?;
+
+            Ok(Some(
+                WeaknessDetails::from_entity(&found, &attack_patterns).await?,
+            ))
         } else {
             Ok(None)
         }
     }
+
+    /// Gets a single weakness by ID, along with its parents, children, and otherwise related
+    /// weaknesses.
+    pub async fn get_weakness_hierarchy(
+        &self,
+        id: &str,
+        connection: &impl ConnectionTrait,
+    ) -> Result<Option<WeaknessHierarchy>, Error> {
+        let Some(found) = weakness::Entity::find_by_id(id).one(connection).await? else {
+            return Ok(None);
+        };
+
+        let children = weakness::Entity::find()
+            .filter(
+                Expr::val(found.id.as_str()).eq(PgFunc::any(Expr::col(weakness::Column::ChildOf))),
+            )
+            .order_by_asc(weakness::Column::Id)
+            .all(connection)
+            .await?;
+
+        let referenced = found
+            .child_of
+            .iter()
+            .chain(&found.parent_of)
+            .flatten()
+            .chain(
+                WeaknessRelationship::ALL
+                    .into_iter()
+                    .flat_map(|nature| nature.targets(&found)),
+            )
+            .map(String::as_str)
+            .collect::<BTreeSet<_>>();
+
+        let referenced = weakness::Entity::find()
+            .filter(weakness::Column::Id.is_in(referenced))
+            .all(connection)
+            .await?
+            .into_iter()
+            .map(|weakness| (weakness.id.clone(), weakness))
+            .collect::<HashMap<_, _>>();
+
+        Ok(Some(WeaknessHierarchy::from_entities(
+            &found,
+            &children,
+            &referenced,
+        )))
+    }
+
+    /// Lists vulnerabilities tagged with a weakness and, if requested, any of its descendants.
+    ///
+    /// Returns `None` if the weakness could not be found.
+    pub async fn list_vulnerabilities<C: ConnectionTrait>(
+        &self,
+        id: &str,
+        descendants: bool,
+        query: Query,
+        paginated: impl Pagination,
+        connection: &C,
+    ) -> Result<Option<PaginatedResults<VulnerabilitySummary>>, Error> {
+        if weakness::Entity::find_by_id(id)
+            .one(connection)
+            .await?
+            .is_none()
+        {
+            return Ok(None);
+        }
+
+        let weaknesses = match descendants {
+            true => Self::descendants(id, connection).await?,
+            false => vec![id.to_string()],
+        };
+
+        let limiter = vulnerability::Entity::find()
+            .filter(
+                Expr::col(vulnerability::Column::Cwes)
+                    .binary(PgBinOper::Overlap, Expr::val(weaknesses)),
+            )
+            .filtering(query)?
+            .limiting(connection, paginated, &self.cache)?;
+
+        let LimitedResult { items, total } = limiter.fetch().await?;
+        let total = total.requested(paginated.total()).await?;
+
+        Ok(Some(PaginatedResults {
+            items: VulnerabilitySummary::from_entities(&items, connection).await?,
+            total,
+        }))
+    }
+
+    /// Collect the IDs of a weakness and all its descendants, walking down the hierarchy one level
+    /// at a time.
+    async fn descendants(
+        id: &str,
+        connection: &impl ConnectionTrait,
+    ) -> Result<Vec<String>, Error> {
+        let mut result = BTreeSet::from([id.to_string()]);
+        let mut current = vec![id.to_string()];
+
+        while !current.is_empty() {
+            let children: Vec<String> = weakness::Entity::find()
+                .select_only()
+                .column(weakness::Column::Id)
+                .filter(
+                    Expr::col(weakness::Column::ChildOf)
+                        .binary(PgBinOper::Overlap, Expr::val(current)),
+                )
+                .into_tuple()
+                .all(connection)
+                .await?;
+
+            // the catalog may contain cycles, so only continue with weaknesses not seen before
+            current = children
+                .into_iter()
+                .filter(|child| result.insert(child.clone()))
+                .collect();
+        }
+
+        Ok(result.into_iter().collect())
+    }
 }
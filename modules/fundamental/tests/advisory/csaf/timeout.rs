@@ -6,7 +6,7 @@ use trustify_common::{
     model::Paginated,
 };
 use trustify_module_fundamental::advisory::service::AdvisoryService;
-use trustify_module_ingestor::common::Deprecation;
+use trustify_module_ingestor::common::{Deprecation, Withdrawal};
 use trustify_test_context::TrustifyContext;
 
 #[test_context(TrustifyContext)]
@@ -39,6 +39,7 @@ async fn timeout(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
                 total: true,
             },
             Deprecation::Consider,
+            Withdrawal::Consider,
             &ctx.db,
         )
         .await?;
@@ -241,7 +241,7 @@ async fn nhc_same_content(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
         .transaction(async |tx| {
             ctx.ingestor
                 .ingest(
-                    bytes,
+                    bytes.clone(),
                     Format::SBOM,
                     ("source", "test"),
                     None,
@@ -1,5 +1,5 @@
 use super::service::{Error, ImporterService, PatchError};
-use crate::model::{Importer, ImporterConfiguration, ImporterReport};
+use crate::model::{Importer, ImporterConfiguration, ImporterReport, SecretKey};
 use actix_web::{
     HttpResponse, Responder, delete, get,
     guard::{self, Guard, GuardContext},
@@ -15,23 +15,38 @@ use trustify_common::{
     endpoints::extract_revision,
     model::{Paginated, PaginatedResults, Revisioned},
 };
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    /// The key for encrypting the secrets of importer configurations
+    pub secret_key: Option<SecretKey>,
+}
 
 /// Mount the "importer" module.
 pub fn configure(
     svc: &mut utoipa_actix_web::service_config::ServiceConfig,
+    config: Config,
     db: db::ReadWrite,
     cache: PaginationCache,
 ) {
-    svc.app_data(web::Data::new(ImporterService::new(db, cache)))
-        .service(list)
-        .service(create)
-        .service(read)
-        .service(update)
-        .service(patch_json_merge)
-        .service(delete)
-        .service(get_reports)
-        .service(set_enabled)
-        .service(force);
+    svc.app_data(web::Data::new(
+        ImporterService::new(db, cache).with_secret_key(config.secret_key),
+    ))
+    .service(list)
+    .service(create)
+    .service(read)
+    .service(update)
+    .service(patch_json_merge)
+    .service(delete)
+    .service(get_reports)
+    .service(get_report)
+    .service(set_enabled)
+    .service(force)
+    .service(run_now)
+    .service(pause)
+    .service(resume)
+    .service(cancel);
 }
 
 #[utoipa::path(
@@ -47,7 +62,11 @@ async fn list(
     service: web::Data<ImporterService>,
     _: Require<ReadImporter>,
 ) -> Result<impl Responder, Error> {
-    Ok(web::Json(service.list().await?))
+    let mut result = service.list().await?;
+    for importer in &mut result {
+        importer.data.configuration.redact_secrets();
+    }
+    Ok(web::Json(result))
 }
 
 #[utoipa::path(
@@ -97,14 +116,21 @@ async fn read(
     name: web::Path<String>,
     _: Require<ReadImporter>,
 ) -> Result<Option<impl Responder>, Error> {
-    Ok(service
-        .read(&name)
-        .await?
-        .map(|Revisioned { value, revision }| {
-            HttpResponse::Ok()
-                .append_header((header::ETAG, ETag(EntityTag::new_strong(revision))))
-                .json(value)
-        }))
+    let Some(Revisioned {
+        mut value,
+        revision,
+    }) = service.read(&name).await?
+    else {
+        return Ok(None);
+    };
+
+    value.data.configuration.redact_secrets();
+
+    Ok(Some(
+        HttpResponse::Ok()
+            .append_header((header::ETAG, ETag(EntityTag::new_strong(revision))))
+            .json(value),
+    ))
 }
 
 #[utoipa::path(
@@ -254,6 +280,111 @@ async fn force(
     Ok(HttpResponse::NoContent().finish())
 }
 
+#[utoipa::path(
+    tag = "importer",
+    operation_id = "runImporter",
+    params(
+        ("name", Path, description = "The name of the importer"),
+        ("if-match"=Option<String>, Header, description = "The revision to update"),
+    ),
+    responses(
+        (status = 204, description = "Requested a run of the importer"),
+        (status = 404, description = "The importer could not be found"),
+        (status = 409, description = "The importer is already running"),
+        (status = 412, description = "The provided if-match header did not match the stored revision"),
+    )
+)]
+#[post("/v3/importer/{name}/run")]
+/// Run an importer as soon as possible, continuing from where its last run stopped
+async fn run_now(
+    service: web::Data<ImporterService>,
+    name: web::Path<String>,
+    web::Header(if_match): web::Header<IfMatch>,
+    _: Require<UpdateImporter>,
+) -> Result<impl Responder, Error> {
+    service.run_now(&name, extract_revision(&if_match)).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    tag = "importer",
+    operation_id = "pauseImporter",
+    params(
+        ("name", Path, description = "The name of the importer"),
+        ("if-match"=Option<String>, Header, description = "The revision to update"),
+    ),
+    responses(
+        (status = 204, description = "Requested the importer to pause"),
+        (status = 404, description = "The importer could not be found"),
+        (status = 412, description = "The provided if-match header did not match the stored revision"),
+    )
+)]
+#[post("/v3/importer/{name}/pause")]
+/// Pause an importer, stopping a running import after the current document
+async fn pause(
+    service: web::Data<ImporterService>,
+    name: web::Path<String>,
+    web::Header(if_match): web::Header<IfMatch>,
+    _: Require<UpdateImporter>,
+) -> Result<impl Responder, Error> {
+    service.pause(&name, extract_revision(&if_match)).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    tag = "importer",
+    operation_id = "resumeImporter",
+    params(
+        ("name", Path, description = "The name of the importer"),
+        ("if-match"=Option<String>, Header, description = "The revision to update"),
+    ),
+    responses(
+        (status = 204, description = "Resumed the importer"),
+        (status = 404, description = "The importer could not be found"),
+        (status = 412, description = "The provided if-match header did not match the stored revision"),
+    )
+)]
+#[post("/v3/importer/{name}/resume")]
+/// Resume a paused importer, continuing where it stopped
+async fn resume(
+    service: web::Data<ImporterService>,
+    name: web::Path<String>,
+    web::Header(if_match): web::Header<IfMatch>,
+    _: Require<UpdateImporter>,
+) -> Result<impl Responder, Error> {
+    service.resume(&name, extract_revision(&if_match)).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    tag = "importer",
+    operation_id = "cancelImporter",
+    params(
+        ("name", Path, description = "The name of the importer"),
+        ("if-match"=Option<String>, Header, description = "The revision to update"),
+    ),
+    responses(
+        (status = 204, description = "Requested the current run to be canceled"),
+        (status = 404, description = "The importer could not be found"),
+        (status = 412, description = "The provided if-match header did not match the stored revision"),
+    )
+)]
+#[post("/v3/importer/{name}/cancel")]
+/// Cancel the current run of an importer
+async fn cancel(
+    service: web::Data<ImporterService>,
+    name: web::Path<String>,
+    web::Header(if_match): web::Header<IfMatch>,
+    _: Require<UpdateImporter>,
+) -> Result<impl Responder, Error> {
+    service.cancel(&name, extract_revision(&if_match)).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
 #[utoipa::path(
     tag = "importer",
     operation_id = "deleteImporter",
@@ -306,6 +437,29 @@ async fn get_reports(
     ))
 }
 
+#[utoipa::path(
+    tag = "importer",
+    operation_id = "getImporterReport",
+    params(
+        ("name", Path, description = "The name of the importer"),
+        ("id", Path, description = "The ID of the report"),
+    ),
+    responses(
+        (status = 200, description = "Retrieved importer report", body = ImporterReport),
+        (status = 404, description = "The importer or report could not be found")
+    )
+)]
+#[get("/v3/importer/{name}/report/{id}")]
+/// Get a single report of an importer
+async fn get_report(
+    service: web::Data<ImporterService>,
+    path: web::Path<(String, Uuid)>,
+    _: Require<ReadImporter>,
+) -> Result<Option<impl Responder>, Error> {
+    let (name, id) = path.into_inner();
+    Ok(service.get_report(&name, id).await?.map(web::Json))
+}
+
 mod guards {
     use super::*;
 
@@ -56,6 +56,55 @@ impl ClearlyDefinedPackageType {
     pub fn matches(&self, other: &str) -> bool {
         self.to_str() == other
     }
+
+    /// The type of package URLs for packages of this type
+    pub fn purl_type(&self) -> &str {
+        match self {
+            ClearlyDefinedPackageType::Composer => "composer",
+            ClearlyDefinedPackageType::Crate => "cargo",
+            ClearlyDefinedPackageType::Deb => "deb",
+            ClearlyDefinedPackageType::Gem => "gem",
+            ClearlyDefinedPackageType::Git => "github",
+            ClearlyDefinedPackageType::Go => "golang",
+            ClearlyDefinedPackageType::Maven => "maven",
+            ClearlyDefinedPackageType::Npm => "npm",
+            ClearlyDefinedPackageType::NuGet => "nuget",
+            ClearlyDefinedPackageType::Pod => "cocoapods",
+            ClearlyDefinedPackageType::Pypi => "pypi",
+        }
+    }
+
+    /// The default provider of packages of this type
+    pub fn provider(&self) -> &str {
+        match self {
+            ClearlyDefinedPackageType::Composer => "packagist",
+            ClearlyDefinedPackageType::Crate => "cratesio",
+            ClearlyDefinedPackageType::Deb => "debian",
+            ClearlyDefinedPackageType::Gem => "rubygems",
+            ClearlyDefinedPackageType::Git => "github",
+            ClearlyDefinedPackageType::Go => "golang",
+            ClearlyDefinedPackageType::Maven => "mavencentral",
+            ClearlyDefinedPackageType::Npm => "npmjs",
+            ClearlyDefinedPackageType::NuGet => "nuget",
+            ClearlyDefinedPackageType::Pod => "cocoapods",
+            ClearlyDefinedPackageType::Pypi => "pypi",
+        }
+    }
+
+    /// The ClearlyDefined coordinates of a package, like `crate/cratesio/-/serde/1.0.0`
+    pub fn coordinates(&self, namespace: Option<&str>, name: &str, revision: &str) -> String {
+        let namespace = match namespace {
+            // the namespace of Go modules is a path, which must be a single segment
+            Some(namespace) => namespace.replace('/', "%2f"),
+            None => "-".to_string(),
+        };
+
+        format!(
+            "{}/{}/{namespace}/{name}/{revision}",
+            self.to_str(),
+            self.provider()
+        )
+    }
 }
 
 #[derive(
@@ -21,6 +21,10 @@ pub struct ClearlyDefinedCurationImporter {
 
     #[serde(default, skip_serializing_if = "HashSet::is_empty")]
     pub types: HashSet<ClearlyDefinedPackageType>,
+
+    /// Credentials for accessing a private repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credentials: Option<GitCredentials>,
 }
 
 pub const DEFAULT_SOURCE_CLEARLY_DEFINED_CURATION: &str =
@@ -0,0 +1,56 @@
+use super::*;
+use std::collections::HashSet;
+
+/// Harvest the ClearlyDefined definitions of packages used by ingested SBOMs.
+///
+/// The coordinates are derived from the package URLs referenced by SBOMs. Their definitions are
+/// requested from the ClearlyDefined API, and ingested along with their license information.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    ToSchema,
+    schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ClearlyDefinedHarvestImporter {
+    #[serde(flatten)]
+    pub common: CommonImporter,
+
+    /// The base URL of the ClearlyDefined API
+    #[serde(default = "default::source")]
+    pub source: String,
+
+    /// The package types to harvest, all supported types if empty
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub types: HashSet<ClearlyDefinedPackageType>,
+
+    /// The number of definitions to request at once
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_size: Option<usize>,
+}
+
+pub const DEFAULT_SOURCE_CLEARLY_DEFINED_HARVEST: &str = "https://api.clearlydefined.io";
+
+mod default {
+    pub fn source() -> String {
+        super::DEFAULT_SOURCE_CLEARLY_DEFINED_HARVEST.into()
+    }
+}
+
+impl Deref for ClearlyDefinedHarvestImporter {
+    type Target = CommonImporter;
+
+    fn deref(&self) -> &Self::Target {
+        &self.common
+    }
+}
+
+impl DerefMut for ClearlyDefinedHarvestImporter {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.common
+    }
+}
@@ -0,0 +1,99 @@
+use super::*;
+use crate::runner::common::walker::Credentials;
+
+/// Credentials for accessing a private git repository
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    ToSchema,
+    schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct GitCredentials {
+    /// The user name. Defaults to the one of the repository URL, or `git`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+
+    /// A token (or password), for repositories accessed using HTTPS
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<Secret>,
+
+    /// The path to a private SSH key, on the host running the importer, for repositories accessed
+    /// using SSH
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh_key: Option<String>,
+
+    /// The passphrase of the private SSH key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh_passphrase: Option<Secret>,
+}
+
+impl GitCredentials {
+    fn secrets_mut(&mut self) -> impl Iterator<Item = &mut Secret> {
+        [&mut self.token, &mut self.ssh_passphrase]
+            .into_iter()
+            .flatten()
+    }
+
+    /// Check if all secrets are encrypted.
+    pub fn is_encrypted(&self) -> bool {
+        [&self.token, &self.ssh_passphrase]
+            .into_iter()
+            .flatten()
+            .all(Secret::is_encrypted)
+    }
+
+    /// Encrypt all secrets, which are not yet encrypted.
+    pub fn encrypt(&mut self, key: Option<&SecretKey>) -> Result<(), SecretError> {
+        for secret in self.secrets_mut() {
+            secret.encrypt(key)?;
+        }
+        Ok(())
+    }
+
+    /// Replace all secrets with the redacted placeholder.
+    pub fn redact(&mut self) {
+        for secret in self.secrets_mut() {
+            *secret = Secret::redacted();
+        }
+    }
+
+    /// Replace redacted placeholders with the secrets of the currently stored credentials.
+    ///
+    /// Placeholders without a stored secret are kept, and get rejected when encrypting.
+    pub fn restore(&mut self, current: &GitCredentials) {
+        for (secret, current) in [
+            (&mut self.token, &current.token),
+            (&mut self.ssh_passphrase, &current.ssh_passphrase),
+        ] {
+            if let (Some(secret), Some(current)) = (secret, current)
+                && secret.is_redacted()
+            {
+                *secret = current.clone();
+            }
+        }
+    }
+
+    /// Get the credentials for the walker, decrypting all secrets.
+    pub fn reveal(&self, key: Option<&SecretKey>) -> Result<Credentials, SecretError> {
+        Ok(Credentials {
+            username: self.username.clone(),
+            token: self
+                .token
+                .as_ref()
+                .map(|token| token.reveal(key))
+                .transpose()?,
+            ssh_key: self.ssh_key.as_ref().map(Into::into),
+            ssh_passphrase: self
+                .ssh_passphrase
+                .as_ref()
+                .map(|passphrase| passphrase.reveal(key))
+                .transpose()?,
+        })
+    }
+}
@@ -28,6 +28,11 @@ pub struct CsafImporter {
 
     #[serde(default, skip_serializing_if = "is_default")]
     pub ignore_missing: bool,
+
+    /// Discover the provider metadata using the `security.txt` of the source, a domain or the URL
+    /// of a site, instead of the well-known locations.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub security_txt: bool,
 }
 
 impl Deref for CsafImporter {
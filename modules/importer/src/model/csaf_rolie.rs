@@ -0,0 +1,38 @@
+use super::*;
+
+/// Import CSAF documents of a provider, using its ROLIE feeds.
+///
+/// The feeds are discovered from the provider metadata. Each run only processes the entries which
+/// were updated since the previous run, tracking the latest update per feed.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    ToSchema,
+    schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct CsafRolieImporter {
+    #[serde(flatten)]
+    pub common: CommonImporter,
+
+    /// The URL of the provider metadata (`provider-metadata.json`)
+    pub source: String,
+}
+
+impl Deref for CsafRolieImporter {
+    type Target = CommonImporter;
+
+    fn deref(&self) -> &Self::Target {
+        &self.common
+    }
+}
+
+impl DerefMut for CsafRolieImporter {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.common
+    }
+}
@@ -1,5 +1,6 @@
 use super::*;
 use std::collections::HashSet;
+use trustify_common::serde::is_default;
 
 #[derive(
     Clone,
@@ -19,11 +20,44 @@ pub struct CveImporter {
     #[serde(default = "default::source")]
     pub source: String,
 
+    /// An optional branch. Will use the default branch otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+
+    /// An optional tag to pin to, taking precedence over the branch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+
+    /// An optional commit (full ID) to pin to, taking precedence over the tag and the branch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+
     #[serde(default, skip_serializing_if = "HashSet::is_empty")]
     pub years: HashSet<u16>,
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub start_year: Option<u16>,
+
+    /// Skip CVE records which are in the state "rejected".
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub skip_rejected: bool,
+
+    /// The handling of CVE records, which got deleted from the repository.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub on_deleted: UpstreamDeletion,
+
+    /// The maximum number of documents ingested concurrently. Defaults to one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<usize>,
+
+    /// The depth for cloning and fetching the repository, zero or less fetches the full history.
+    /// Defaults to a shallow clone, of depth one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub depth: Option<i32>,
+
+    /// Credentials for accessing a private repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credentials: Option<GitCredentials>,
 }
 
 pub const DEFAULT_SOURCE_CVEPROJECT: &str = "https://github.com/CVEProject/cvelistV5";
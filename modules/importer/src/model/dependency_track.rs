@@ -0,0 +1,43 @@
+use super::*;
+
+/// Import projects from a Dependency-Track export archive.
+///
+/// The archive is a ZIP file with the following layout:
+///
+/// * `projects.json`: an array of projects, as returned by `/api/v1/project`
+/// * `projects/<uuid>/bom.json`: the CycloneDX BOM of a project, as returned by
+///   `/api/v1/bom/cyclonedx/project/<uuid>`
+/// * `projects/<uuid>/findings.json` (optional): the findings of a project, including the audit
+///   decisions, as returned by `/api/v1/finding/project/<uuid>`
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    ToSchema,
+    schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyTrackImporter {
+    #[serde(flatten)]
+    pub common: CommonImporter,
+
+    /// The location of the export archive, either an HTTP(S) URL or a local path
+    pub source: String,
+}
+
+impl Deref for DependencyTrackImporter {
+    type Target = CommonImporter;
+
+    fn deref(&self) -> &Self::Target {
+        &self.common
+    }
+}
+
+impl DerefMut for DependencyTrackImporter {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.common
+    }
+}
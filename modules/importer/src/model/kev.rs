@@ -0,0 +1,48 @@
+use super::*;
+
+/// Import the CISA catalog of known exploited vulnerabilities (KEV).
+///
+/// Vulnerabilities which are already known, and get added to the catalog, are reported as
+/// [`crate::runner::report::Event::KnownExploited`].
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    ToSchema,
+    schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct KevImporter {
+    #[serde(flatten)]
+    pub common: CommonImporter,
+
+    /// The URL of the catalog (JSON format)
+    #[serde(default = "default::source")]
+    pub source: String,
+}
+
+pub const DEFAULT_SOURCE_KEV: &str =
+    "https://www.cisa.gov/sites/default/files/feeds/known_exploited_vulnerabilities.json";
+
+mod default {
+    pub fn source() -> String {
+        super::DEFAULT_SOURCE_KEV.into()
+    }
+}
+
+impl Deref for KevImporter {
+    type Target = CommonImporter;
+
+    fn deref(&self) -> &Self::Target {
+        &self.common
+    }
+}
+
+impl DerefMut for KevImporter {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.common
+    }
+}
@@ -1,22 +1,40 @@
 mod clearly_defined_curation;
+mod clearly_defined_harvest;
 
 mod clearly_defined;
+mod credentials;
 mod csaf;
+mod csaf_rolie;
 mod cve;
 mod cwe;
+mod dependency_track;
+mod kev;
 mod osv;
 mod quay;
+mod red_hat_vex;
 mod sbom;
+mod schedule;
+mod secret;
+mod template;
 
 use crate::runner::{common::heartbeat::Heart, report::Report};
 pub use clearly_defined::*;
 pub use clearly_defined_curation::*;
+pub use clearly_defined_harvest::*;
+pub use credentials::*;
 pub use csaf::*;
+pub use csaf_rolie::*;
 pub use cve::*;
 pub use cwe::*;
+pub use dependency_track::*;
+pub use kev::*;
 pub use osv::*;
 pub use quay::*;
+pub use red_hat_vex::*;
 pub use sbom::*;
+pub use schedule::*;
+pub use secret::*;
+pub use template::*;
 
 use num_traits::cast::ToPrimitive;
 use std::{
@@ -24,7 +42,10 @@ use std::{
     time::Duration,
 };
 use time::OffsetDateTime;
-use trustify_common::model::Revisioned;
+use trustify_common::{
+    model::{BinaryByteSize, Revisioned},
+    serde::is_default,
+};
 use trustify_entity::{
     importer::{self, Model},
     importer_report,
@@ -53,9 +74,23 @@ impl Importer {
 
     /// check if we need to run or skip the importer
     pub fn is_due(&self) -> bool {
-        match self.data.last_run {
-            Some(t) => (OffsetDateTime::now_utc() - t) > self.data.configuration.period,
-            None => true,
+        self.is_due_at(OffsetDateTime::now_utc())
+    }
+
+    fn is_due_at(&self, now: OffsetDateTime) -> bool {
+        let Some(last_run) = self.data.last_run else {
+            return true;
+        };
+
+        match &self.data.configuration.schedule {
+            Some(schedule) => match schedule.next_after(last_run) {
+                Ok(next) => next.is_some_and(|next| next <= now),
+                Err(err) => {
+                    log::warn!("Invalid schedule of importer '{}': {err}", self.name);
+                    false
+                }
+            },
+            None => (now - last_run) > self.data.configuration.period,
         }
     }
 
@@ -70,6 +105,11 @@ impl Importer {
     pub fn is_enabled(&self) -> bool {
         !self.data.configuration.disabled
     }
+
+    /// check if paused, which prevents new runs until resumed
+    pub fn is_paused(&self) -> bool {
+        self.data.control == Some(Control::Pause)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, ToSchema)]
@@ -97,6 +137,34 @@ impl From<State> for importer::State {
     }
 }
 
+/// A requested change of the lifecycle of an importer
+#[derive(Copy, Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum Control {
+    /// Stop the current run after the current document, and don't start new runs until resumed
+    Pause,
+    /// Abort the current run
+    Cancel,
+}
+
+impl From<importer::Control> for Control {
+    fn from(value: importer::Control) -> Self {
+        match value {
+            importer::Control::Pause => Self::Pause,
+            importer::Control::Cancel => Self::Cancel,
+        }
+    }
+}
+
+impl From<Control> for importer::Control {
+    fn from(value: Control) -> Self {
+        match value {
+            Control::Pause => Self::Pause,
+            Control::Cancel => Self::Cancel,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ImporterData {
@@ -105,6 +173,10 @@ pub struct ImporterData {
     /// The current state of the importer
     pub state: State,
 
+    /// A requested, but not yet completed, change of the lifecycle
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub control: Option<Control>,
+
     /// The last state change
     #[serde(with = "time::serde::rfc3339")]
     pub last_change: OffsetDateTime,
@@ -135,10 +207,16 @@ pub struct ImporterData {
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ProgressDetails {
-    /// The current processed items.
+    /// The current processed items, including failed and skipped ones.
     pub current: u32,
-    /// The total number of items to be processed.
+    /// The total number of items discovered, to be processed.
     pub total: u32,
+    /// The processed items which failed.
+    #[serde(default)]
+    pub failed: u32,
+    /// The processed items which were skipped.
+    #[serde(default)]
+    pub skipped: u32,
     /// Progress in percent (0..=1)
     pub percent: f32,
     /// The average processing rate (per second).
@@ -181,8 +259,13 @@ pub enum ImporterConfiguration {
     Cve(CveImporter),
     ClearlyDefined(ClearlyDefinedImporter),
     ClearlyDefinedCuration(ClearlyDefinedCurationImporter),
+    ClearlyDefinedHarvest(ClearlyDefinedHarvestImporter),
     Cwe(CweImporter),
     Quay(QuayImporter),
+    DependencyTrack(DependencyTrackImporter),
+    RedHatVex(RedHatVexImporter),
+    CsafRolie(CsafRolieImporter),
+    Kev(KevImporter),
 }
 
 impl Deref for ImporterConfiguration {
@@ -196,8 +279,67 @@ impl Deref for ImporterConfiguration {
             Self::Cve(importer) => &importer.common,
             Self::ClearlyDefined(importer) => &importer.common,
             Self::ClearlyDefinedCuration(importer) => &importer.common,
+            Self::ClearlyDefinedHarvest(importer) => &importer.common,
             Self::Cwe(importer) => &importer.common,
             Self::Quay(importer) => &importer.common,
+            Self::DependencyTrack(importer) => &importer.common,
+            Self::RedHatVex(importer) => &importer.common,
+            Self::CsafRolie(importer) => &importer.common,
+            Self::Kev(importer) => &importer.common,
+        }
+    }
+}
+
+impl ImporterConfiguration {
+    /// The credentials for the git repository, if the importer uses one
+    pub fn git_credentials(&self) -> Option<&GitCredentials> {
+        match self {
+            Self::Osv(importer) => importer.credentials.as_ref(),
+            Self::Cve(importer) => importer.credentials.as_ref(),
+            Self::ClearlyDefinedCuration(importer) => importer.credentials.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// The credentials for the git repository, if the importer uses one
+    pub fn git_credentials_mut(&mut self) -> Option<&mut GitCredentials> {
+        match self {
+            Self::Osv(importer) => importer.credentials.as_mut(),
+            Self::Cve(importer) => importer.credentials.as_mut(),
+            Self::ClearlyDefinedCuration(importer) => importer.credentials.as_mut(),
+            _ => None,
+        }
+    }
+
+    /// Check if all secrets of the configuration are encrypted.
+    pub fn secrets_encrypted(&self) -> bool {
+        self.git_credentials()
+            .is_none_or(|credentials| credentials.is_encrypted())
+    }
+
+    /// Encrypt all secrets of the configuration, which are not yet encrypted.
+    ///
+    /// Without a key, configurations carrying plain text secrets are rejected.
+    pub fn encrypt_secrets(&mut self, key: Option<&SecretKey>) -> Result<(), SecretError> {
+        if let Some(credentials) = self.git_credentials_mut() {
+            credentials.encrypt(key)?;
+        }
+        Ok(())
+    }
+
+    /// Replace all secrets of the configuration with the redacted placeholder.
+    pub fn redact_secrets(&mut self) {
+        if let Some(credentials) = self.git_credentials_mut() {
+            credentials.redact();
+        }
+    }
+
+    /// Keep the stored secrets of the current configuration, for which the placeholder was sent.
+    pub fn restore_secrets(&mut self, current: &ImporterConfiguration) {
+        if let (Some(credentials), Some(current)) =
+            (self.git_credentials_mut(), current.git_credentials())
+        {
+            credentials.restore(current);
         }
     }
 }
@@ -211,8 +353,13 @@ impl DerefMut for ImporterConfiguration {
             Self::Cve(importer) => &mut importer.common,
             Self::ClearlyDefined(importer) => &mut importer.common,
             Self::ClearlyDefinedCuration(importer) => &mut importer.common,
+            Self::ClearlyDefinedHarvest(importer) => &mut importer.common,
             Self::Cwe(importer) => &mut importer.common,
             Self::Quay(importer) => &mut importer.common,
+            Self::DependencyTrack(importer) => &mut importer.common,
+            Self::RedHatVex(importer) => &mut importer.common,
+            Self::CsafRolie(importer) => &mut importer.common,
+            Self::Kev(importer) => &mut importer.common,
         }
     }
 }
@@ -239,15 +386,182 @@ pub struct CommonImporter {
     #[schemars(with = "HumantimeSerde")]
     pub period: Duration,
 
+    /// A cron-style schedule, which takes precedence over the period.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<Schedule>,
+
+    /// Only report what would be ingested, without ingesting anything.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub dry_run: bool,
+
+    /// Limits for the rate documents are retrieved with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimit>,
+
+    /// Retrying of operations which failed for a transient reason.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryPolicy>,
+
+    /// The proxy to use for reaching the source, overriding the global one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<Proxy>,
+
+    /// Only import documents with a path matching one of those regular expressions. Imports all
+    /// documents if empty.
+    ///
+    /// For git repositories, the path is relative to the directory searched for documents, and for
+    /// Red Hat VEX it is relative to the source. Otherwise, it is the path of the document's URL.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+
+    /// Skip documents with a path matching one of those regular expressions.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+
     /// A description for users.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
 
     /// Labels which will be applied to the ingested documents.
+    ///
+    /// Values may use template variables derived from the path of a document, like
+    /// `{{year}}`. See [`render_label_templates`] for the available variables.
     #[serde(default, skip_serializing_if = "Labels::is_empty")]
     pub labels: Labels,
 }
 
+/// Limits for the rate an importer retrieves documents with
+///
+/// The limits are applied per document, after it was retrieved. Cloning git repositories is not
+/// limited.
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    ToSchema,
+    schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimit {
+    /// The maximum number of documents per second.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_per_second: Option<u32>,
+
+    /// The maximum number of bytes per second.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes_per_second: Option<BinaryByteSize>,
+}
+
+/// A policy for retrying operations which failed for a transient reason
+///
+/// This covers network and database errors, but not invalid documents.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    ToSchema,
+    schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first one.
+    #[serde(default = "default::max_attempts")]
+    pub max_attempts: u32,
+
+    /// The delay before the first retry, doubled with every further retry.
+    #[serde(default = "default::backoff", with = "humantime_serde")]
+    #[schemars(with = "HumantimeSerde")]
+    pub backoff: Duration,
+
+    /// The upper limit of the delay between two attempts.
+    #[serde(default = "default::max_backoff", with = "humantime_serde")]
+    #[schemars(with = "HumantimeSerde")]
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: default::max_attempts(),
+            backoff: default::backoff(),
+            max_backoff: default::max_backoff(),
+        }
+    }
+}
+
+mod default {
+    use std::time::Duration;
+
+    pub fn max_attempts() -> u32 {
+        3
+    }
+
+    pub fn backoff() -> Duration {
+        Duration::from_secs(1)
+    }
+
+    pub fn max_backoff() -> Duration {
+        Duration::from_secs(60)
+    }
+}
+
+/// A proxy for reaching the source of an importer
+///
+/// This applies to cloning git repositories and to HTTP requests. Besides HTTP(S) proxies, HTTP
+/// requests also support SOCKS proxies, like `socks5://proxy.example.com:1080`.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    ToSchema,
+    schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct Proxy {
+    /// The URL of the proxy, like `http://proxy.example.com:3128`.
+    pub url: String,
+
+    /// Hosts which are reached directly, without the proxy.
+    ///
+    /// An entry matches the host itself and all of its subdomains, `*` matches all hosts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub no_proxy: Vec<String>,
+}
+
+/// The handling of documents, which got deleted from their upstream source
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    ToSchema,
+    schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum UpstreamDeletion {
+    /// Keep the documents as they are.
+    #[default]
+    Keep,
+    /// Add the label `upstreamDeleted` to the documents, with the time of the deletion.
+    Label,
+    /// Mark the documents as withdrawn, unless they already are.
+    Withdraw,
+}
+
 // Just here to create a schema for humantime_serde.
 #[derive(schemars::JsonSchema)]
 struct HumantimeSerde(#[allow(unused)] String);
@@ -267,8 +581,11 @@ impl TryFrom<Model> for Importer {
             progress_current,
             progress_total,
             progress_message,
+            progress_failed,
+            progress_skipped,
             continuation,
             heartbeat,
+            control,
             revision: _,
         }: Model,
     ) -> Result<Self, Self::Error> {
@@ -277,6 +594,7 @@ impl TryFrom<Model> for Importer {
             heartbeat: heartbeat.and_then(|d| d.to_i128()),
             data: ImporterData {
                 state: state.into(),
+                control: control.map(Into::into),
                 last_change,
                 last_success,
                 last_run,
@@ -286,6 +604,8 @@ impl TryFrom<Model> for Importer {
                     OffsetDateTime::now_utc(),
                     progress_current,
                     progress_total,
+                    progress_failed,
+                    progress_skipped,
                     progress_message,
                 ),
                 continuation: continuation.unwrap_or_default(),
@@ -339,11 +659,13 @@ fn into_progress(
     now: OffsetDateTime,
     current: Option<i32>,
     total: Option<i32>,
+    failed: Option<i32>,
+    skipped: Option<i32>,
     message: Option<String>,
 ) -> Progress {
     Progress {
         message,
-        details: into_progress_details(start, now, current, total),
+        details: into_progress_details(start, now, current, total, failed, skipped),
     }
 }
 
@@ -353,6 +675,8 @@ fn into_progress_details(
     now: OffsetDateTime,
     current: Option<i32>,
     total: Option<i32>,
+    failed: Option<i32>,
+    skipped: Option<i32>,
 ) -> Option<ProgressDetails> {
     // elapsed time in seconds
     let elapsed = (now - start).as_seconds_f32();
@@ -375,6 +699,8 @@ fn into_progress_details(
     Some(ProgressDetails {
         current,
         total,
+        failed: failed.unwrap_or_default() as u32,
+        skipped: skipped.unwrap_or_default() as u32,
         percent: current as f32 / total_f,
         rate,
         estimated_seconds_remaining,
@@ -391,6 +717,15 @@ mod test {
         heartbeat: Option<i128>,
         last_run: Option<OffsetDateTime>,
         disabled: bool,
+    ) -> Importer {
+        create_scheduled_test_importer(heartbeat, last_run, disabled, None)
+    }
+
+    fn create_scheduled_test_importer(
+        heartbeat: Option<i128>,
+        last_run: Option<OffsetDateTime>,
+        disabled: bool,
+        schedule: Option<Schedule>,
     ) -> Importer {
         let now = OffsetDateTime::now_utc();
         Importer {
@@ -401,6 +736,13 @@ mod test {
                     common: CommonImporter {
                         disabled,
                         period: Duration::from_secs(30),
+                        schedule,
+                        dry_run: false,
+                        rate_limit: None,
+                        retry: None,
+                        proxy: None,
+                        include: vec![],
+                        exclude: vec![],
                         description: None,
                         labels: Default::default(),
                     },
@@ -413,6 +755,7 @@ mod test {
                     ignore_missing: false,
                 }),
                 state: State::Waiting,
+                control: None,
                 last_change: now,
                 last_success: None,
                 last_run,
@@ -428,12 +771,14 @@ mod test {
         let start = datetime!(2024-01-01 00:00:00 UTC);
         let now = datetime!(2024-01-01 00:00:10 UTC);
         assert_eq!(
-            into_progress(start, now, Some(15), Some(100), None),
+            into_progress(start, now, Some(15), Some(100), Some(2), Some(3), None),
             Progress {
                 message: None,
                 details: Some(ProgressDetails {
                     current: 15,
                     total: 100,
+                    failed: 2,
+                    skipped: 3,
                     percent: 0.15,
                     rate: 1.5,
                     estimated_seconds_remaining: 56,
@@ -448,24 +793,24 @@ mod test {
         let start = datetime!(2024-01-01 00:00:00 UTC);
         let now = datetime!(2024-01-01 00:00:10 UTC);
         assert_eq!(
-            into_progress(start, now, None, None, None),
+            into_progress(start, now, None, None, None, None, None),
             Progress::default()
         );
         assert_eq!(
-            into_progress(start, now, Some(1), None, None),
+            into_progress(start, now, Some(1), None, None, None, None),
             Progress::default()
         );
         assert_eq!(
-            into_progress(start, now, None, Some(1), None),
+            into_progress(start, now, None, Some(1), None, None, None),
             Progress::default()
         );
 
         assert_eq!(
-            into_progress(start, now, Some(10), Some(1), None),
+            into_progress(start, now, Some(10), Some(1), None, None, None),
             Progress::default()
         );
         assert_eq!(
-            into_progress(start, now, Some(0), Some(0), None),
+            into_progress(start, now, Some(0), Some(0), None, None, None),
             Progress::default()
         );
     }
@@ -480,6 +825,19 @@ mod test {
         assert!(!importer.is_due());
     }
 
+    #[test]
+    fn importer_is_due_scheduled() {
+        let schedule = Schedule::new("0 30 2 * * *").timezone("Europe/Berlin");
+        let importer = create_scheduled_test_importer(
+            None,
+            Some(datetime!(2024-01-01 12:00 UTC)),
+            false,
+            Some(schedule),
+        );
+        assert!(!importer.is_due_at(datetime!(2024-01-02 01:00 UTC)));
+        assert!(importer.is_due_at(datetime!(2024-01-02 01:30 UTC)));
+    }
+
     #[test]
     fn importer_is_running() {
         let now = OffsetDateTime::now_utc();
@@ -1,5 +1,6 @@
 use super::*;
 use std::collections::HashSet;
+use trustify_common::serde::is_default;
 
 #[derive(
     Clone,
@@ -23,6 +24,14 @@ pub struct OsvImporter {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub branch: Option<String>,
 
+    /// An optional tag to pin to, taking precedence over the branch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+
+    /// An optional commit (full ID) to pin to, taking precedence over the tag and the branch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+
     /// An optional path to start searching for documents. Will use the root of the repository otherwise.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub path: Option<String>,
@@ -32,6 +41,41 @@ pub struct OsvImporter {
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub start_year: Option<u16>,
+
+    /// Only import advisories which were modified since this timestamp (RFC 3339).
+    ///
+    /// This limits the initial import of a fresh deployment to recent advisories. Older ones can
+    /// be backfilled later, by removing the option and forcing a full run of the importer.
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schemars(with = "Option<String>")]
+    pub modified_since: Option<OffsetDateTime>,
+
+    /// Only import advisories affecting packages of one of those ecosystems (like `crates.io`
+    /// or `PyPI`). Ecosystems with a suffix (like `Debian:11`) can be selected as a whole by
+    /// their name (like `Debian`). Imports all ecosystems if empty.
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub ecosystems: HashSet<String>,
+
+    /// The handling of advisories, which got deleted from the repository.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub on_deleted: UpstreamDeletion,
+
+    /// The maximum number of documents ingested concurrently. Defaults to one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<usize>,
+
+    /// The depth for cloning and fetching the repository, zero or less fetches the full history.
+    /// Defaults to a shallow clone, of depth one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub depth: Option<i32>,
+
+    /// Credentials for accessing a private repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credentials: Option<GitCredentials>,
 }
 
 impl Deref for OsvImporter {
@@ -41,6 +41,11 @@ pub struct QuayImporter {
     /// Whether the scheme used is 'http' [true] or 'https' [false]
     #[serde(default)]
     pub unencrypted: bool,
+
+    /// Whether to also discover SBOMs attached to images as OCI referrers, in addition to cosign
+    /// attachments
+    #[serde(default)]
+    pub referrers: bool,
 }
 
 pub const DEFAULT_SOURCE_QUAY: &str = "quay.io";
@@ -0,0 +1,47 @@
+use super::*;
+
+/// Import Red Hat's CSAF VEX files, using the published archive and its incremental changes.
+///
+/// The first run imports the latest full archive (`archive_latest.txt`), following runs only
+/// process the documents listed as changed (`changes.csv`) since the last run.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    ToSchema,
+    schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct RedHatVexImporter {
+    #[serde(flatten)]
+    pub common: CommonImporter,
+
+    /// The base URL of the VEX data
+    #[serde(default = "default::source")]
+    pub source: String,
+}
+
+pub const DEFAULT_SOURCE_RED_HAT_VEX: &str = "https://security.access.redhat.com/data/csaf/v2/vex/";
+
+mod default {
+    pub fn source() -> String {
+        super::DEFAULT_SOURCE_RED_HAT_VEX.into()
+    }
+}
+
+impl Deref for RedHatVexImporter {
+    type Target = CommonImporter;
+
+    fn deref(&self) -> &Self::Target {
+        &self.common
+    }
+}
+
+impl DerefMut for RedHatVexImporter {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.common
+    }
+}
@@ -72,6 +72,13 @@ mod test {
                 common: CommonImporter {
                     disabled: false,
                     period: Duration::from_secs(30),
+                    schedule: None,
+                    dry_run: false,
+                    rate_limit: None,
+                    retry: None,
+                    proxy: None,
+                    include: vec![],
+                    exclude: vec![],
                     description: None,
                     labels: Default::default(),
                 },
@@ -0,0 +1,128 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use std::str::FromStr;
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ScheduleError {
+    #[error("invalid cron expression '{0}': {1}")]
+    Cron(String, String),
+    #[error("unknown timezone '{0}'")]
+    Timezone(String),
+}
+
+/// A cron-style schedule for running an importer
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    ToSchema,
+    schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct Schedule {
+    /// The cron expression, including seconds (e.g. `0 30 2 * * *` for daily at 02:30).
+    pub cron: String,
+
+    /// The IANA name of the timezone the expression is evaluated in. Defaults to UTC.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+}
+
+impl Schedule {
+    pub fn new(cron: impl Into<String>) -> Self {
+        Self {
+            cron: cron.into(),
+            timezone: None,
+        }
+    }
+
+    pub fn timezone(mut self, timezone: impl Into<String>) -> Self {
+        self.timezone = Some(timezone.into());
+        self
+    }
+
+    fn parse(&self) -> Result<(cron::Schedule, Tz), ScheduleError> {
+        let schedule = cron::Schedule::from_str(&self.cron)
+            .map_err(|err| ScheduleError::Cron(self.cron.clone(), err.to_string()))?;
+
+        let timezone = match &self.timezone {
+            Some(timezone) => {
+                Tz::from_str(timezone).map_err(|_| ScheduleError::Timezone(timezone.clone()))?
+            }
+            None => Tz::UTC,
+        };
+
+        Ok((schedule, timezone))
+    }
+
+    /// Check if the cron expression and the timezone are valid.
+    pub fn validate(&self) -> Result<(), ScheduleError> {
+        self.parse().map(|_| ())
+    }
+
+    /// Get the first scheduled time after the provided one.
+    ///
+    /// Returns `None` if the schedule has no upcoming time.
+    pub fn next_after(
+        &self,
+        after: OffsetDateTime,
+    ) -> Result<Option<OffsetDateTime>, ScheduleError> {
+        let (schedule, timezone) = self.parse()?;
+
+        let Some(after) =
+            DateTime::<Utc>::from_timestamp(after.unix_timestamp(), after.nanosecond())
+        else {
+            return Ok(None);
+        };
+
+        Ok(schedule
+            .after(&after.with_timezone(&timezone))
+            .next()
+            .and_then(|next| OffsetDateTime::from_unix_timestamp(next.timestamp()).ok()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use time::macros::datetime;
+
+    #[test]
+    fn next_after() {
+        let schedule = Schedule::new("0 30 2 * * *");
+        assert_eq!(
+            schedule
+                .next_after(datetime!(2024-01-01 12:00 UTC))
+                .unwrap(),
+            Some(datetime!(2024-01-02 02:30 UTC))
+        );
+
+        // evaluated in local time, which is UTC+1 in winter
+        let schedule = schedule.timezone("Europe/Berlin");
+        assert_eq!(
+            schedule
+                .next_after(datetime!(2024-01-01 12:00 UTC))
+                .unwrap(),
+            Some(datetime!(2024-01-02 01:30 UTC))
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(matches!(
+            Schedule::new("every day").validate(),
+            Err(ScheduleError::Cron(..))
+        ));
+        assert!(matches!(
+            Schedule::new("0 30 2 * * *")
+                .timezone("Mars/Olympus_Mons")
+                .validate(),
+            Err(ScheduleError::Timezone(_))
+        ));
+    }
+}
@@ -0,0 +1,244 @@
+use base64::{Engine, prelude::BASE64_STANDARD};
+use ring::{
+    aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey},
+    rand::{SecureRandom, SystemRandom},
+};
+use std::{fmt, str::FromStr, sync::Arc};
+use utoipa::ToSchema;
+
+/// The prefix of an encrypted secret
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+/// The value replacing secrets when returning configurations
+const REDACTED: &str = "********";
+
+#[derive(Debug, thiserror::Error)]
+pub enum SecretError {
+    #[error("invalid secret key: {0}")]
+    Key(String),
+    #[error("secret is encrypted, but no secret key is configured")]
+    MissingKey,
+    #[error("secrets require a secret key to be configured (IMPORTER_SECRET_KEY)")]
+    Unencrypted,
+    #[error("redacted secret without a stored value")]
+    Redacted,
+    #[error("failed to encrypt secret")]
+    Encrypt,
+    #[error("failed to decrypt secret")]
+    Decrypt,
+}
+
+/// A secret value of an importer configuration, like a token or password.
+///
+/// Secrets get encrypted before the configuration is stored, which requires a secret key to be
+/// configured. Encrypted secrets are prefixed with `enc:v1:`. Configurations returned by the API
+/// carry a redacted placeholder instead, which can be sent back to keep the stored value.
+#[derive(
+    Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, ToSchema, schemars::JsonSchema,
+)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(***)")
+    }
+}
+
+impl Secret {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    /// The placeholder for a secret, hiding its value
+    pub fn redacted() -> Self {
+        Self(REDACTED.into())
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.0.starts_with(ENCRYPTED_PREFIX)
+    }
+
+    pub fn is_redacted(&self) -> bool {
+        self.0 == REDACTED
+    }
+
+    /// Encrypt the secret, unless it already is.
+    ///
+    /// Fails for the redacted placeholder, as well as for plain text when there is no key. A value
+    /// which already is encrypted must decrypt using the key, so that it can be used later on.
+    pub fn encrypt(&mut self, key: Option<&SecretKey>) -> Result<(), SecretError> {
+        if self.is_redacted() {
+            return Err(SecretError::Redacted);
+        }
+        match self.0.strip_prefix(ENCRYPTED_PREFIX) {
+            Some(encrypted) => {
+                key.ok_or(SecretError::MissingKey)?.decrypt(encrypted)?;
+            }
+            None => {
+                let key = key.ok_or(SecretError::Unencrypted)?;
+                self.0 = format!("{ENCRYPTED_PREFIX}{}", key.encrypt(self.0.as_bytes())?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the plain text of the secret, decrypting it.
+    ///
+    /// Secrets are never stored in plain text, so such a value is rejected.
+    pub fn reveal(&self, key: Option<&SecretKey>) -> Result<String, SecretError> {
+        match self.0.strip_prefix(ENCRYPTED_PREFIX) {
+            Some(encrypted) => key.ok_or(SecretError::MissingKey)?.decrypt(encrypted),
+            None => Err(SecretError::Unencrypted),
+        }
+    }
+}
+
+/// The key for encrypting secrets, a base64 encoded 256-bit AES key.
+#[derive(Clone)]
+pub struct SecretKey(Arc<LessSafeKey>);
+
+impl fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretKey(***)")
+    }
+}
+
+impl FromStr for SecretKey {
+    type Err = SecretError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let key = BASE64_STANDARD
+            .decode(s.trim())
+            .map_err(|err| SecretError::Key(err.to_string()))?;
+        let key = UnboundKey::new(&AES_256_GCM, &key)
+            .map_err(|_| SecretError::Key("must be 32 bytes".into()))?;
+        Ok(Self(Arc::new(LessSafeKey::new(key))))
+    }
+}
+
+impl SecretKey {
+    /// Encrypt data, returning the base64 encoded nonce and ciphertext.
+    fn encrypt(&self, data: &[u8]) -> Result<String, SecretError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| SecretError::Encrypt)?;
+
+        let mut data = data.to_vec();
+        self.0
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+            .map_err(|_| SecretError::Encrypt)?;
+
+        Ok(BASE64_STANDARD.encode([nonce.as_slice(), &data].concat()))
+    }
+
+    /// Decrypt the output of [`Self::encrypt`].
+    fn decrypt(&self, encrypted: &str) -> Result<String, SecretError> {
+        let mut data = BASE64_STANDARD
+            .decode(encrypted)
+            .map_err(|_| SecretError::Decrypt)?;
+        if data.len() < NONCE_LEN {
+            return Err(SecretError::Decrypt);
+        }
+
+        let mut ciphertext = data.split_off(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(&data).map_err(|_| SecretError::Decrypt)?;
+        let plain = self
+            .0
+            .open_in_place(nonce, Aad::empty(), &mut ciphertext)
+            .map_err(|_| SecretError::Decrypt)?;
+
+        String::from_utf8(plain.to_vec()).map_err(|_| SecretError::Decrypt)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const KEY: &str = "MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=";
+    const OTHER_KEY: &str = "ZmVkY2JhOTg3NjU0MzIxMGZlZGNiYTk4NzY1NDMyMTA=";
+
+    #[test]
+    fn roundtrip() {
+        let key: SecretKey = KEY.parse().expect("must parse");
+
+        let mut secret = Secret::new("s3cr3t");
+        secret.encrypt(Some(&key)).expect("must encrypt");
+        assert!(secret.is_encrypted());
+        assert!(!secret.0.contains("s3cr3t"));
+
+        // encrypting again must not change anything
+        let encrypted = secret.clone();
+        secret.encrypt(Some(&key)).expect("must encrypt");
+        assert_eq!(secret, encrypted);
+
+        assert_eq!(secret.reveal(Some(&key)).expect("must decrypt"), "s3cr3t");
+    }
+
+    #[test]
+    fn reveal() {
+        let key: SecretKey = KEY.parse().expect("must parse");
+        let other: SecretKey = OTHER_KEY.parse().expect("must parse");
+
+        // plain text is never revealed
+        assert!(matches!(
+            Secret::new("plain").reveal(Some(&key)),
+            Err(SecretError::Unencrypted)
+        ));
+
+        let mut secret = Secret::new("s3cr3t");
+        secret.encrypt(Some(&key)).expect("must encrypt");
+        assert!(matches!(secret.reveal(None), Err(SecretError::MissingKey)));
+        assert!(matches!(
+            secret.reveal(Some(&other)),
+            Err(SecretError::Decrypt)
+        ));
+    }
+
+    #[test]
+    fn reject() {
+        let key: SecretKey = KEY.parse().expect("must parse");
+
+        // plain text requires a key
+        assert!(matches!(
+            Secret::new("s3cr3t").encrypt(None),
+            Err(SecretError::Unencrypted)
+        ));
+        // the placeholder is never stored
+        assert!(matches!(
+            Secret::redacted().encrypt(Some(&key)),
+            Err(SecretError::Redacted)
+        ));
+    }
+
+    #[test]
+    fn reject_undecryptable() {
+        let key: SecretKey = KEY.parse().expect("must parse");
+        let other: SecretKey = OTHER_KEY.parse().expect("must parse");
+
+        let mut secret = Secret::new("s3cr3t");
+        secret.encrypt(Some(&key)).expect("must encrypt");
+
+        // an encrypted value must be usable with the configured key
+        assert!(matches!(
+            secret.clone().encrypt(None),
+            Err(SecretError::MissingKey)
+        ));
+        assert!(matches!(
+            secret.clone().encrypt(Some(&other)),
+            Err(SecretError::Decrypt)
+        ));
+        assert!(matches!(
+            Secret::new("enc:v1:garbage").encrypt(Some(&key)),
+            Err(SecretError::Decrypt)
+        ));
+    }
+
+    #[test]
+    fn invalid_key() {
+        assert!("not base64!".parse::<SecretKey>().is_err());
+        assert!("c2hvcnQ=".parse::<SecretKey>().is_err());
+    }
+}
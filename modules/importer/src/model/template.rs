@@ -0,0 +1,149 @@
+use std::path::Path;
+use trustify_entity::labels::Labels;
+
+#[derive(Debug, thiserror::Error)]
+pub enum LabelTemplateError {
+    #[error("unknown variable '{1}' in the value of label '{0}'")]
+    UnknownVariable(String, String),
+    #[error("unterminated variable in the value of label '{0}'")]
+    Unterminated(String),
+}
+
+/// The variables available to label templates, derived from the path of a document
+const VARIABLES: &[&str] = &["file", "dir", "name", "year"];
+
+/// Check that the values of labels only use known template variables.
+pub fn validate_label_templates(labels: &Labels) -> Result<(), LabelTemplateError> {
+    for (key, value) in labels.iter() {
+        substitute(key, value, |name| {
+            VARIABLES.contains(&name).then(String::new)
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Render the template variables in the values of labels, like `tier={{year}}`.
+///
+/// The variables are derived from the path of the document, taken from its `file` label, falling
+/// back to its `source` label:
+///
+/// * `file`: the path
+/// * `dir`: the directory of the path
+/// * `name`: the file name of the path
+/// * `year`: the first four-digit number of the path, like `2024` in `2024/CVE-2024-1234.json`
+///
+/// Variables which can't be derived from the path render as an empty string. Values with an
+/// invalid template are kept as they are.
+pub fn render_label_templates(mut labels: Labels) -> Labels {
+    if !labels.values().any(|value| value.contains("{{")) {
+        return labels;
+    }
+
+    let path = labels
+        .get("file")
+        .or_else(|| labels.get("source"))
+        .cloned()
+        .unwrap_or_default();
+
+    let lookup = |name: &str| {
+        let path = Path::new(&path);
+        let value = match name {
+            "file" => path.to_string_lossy().into_owned(),
+            "dir" => path
+                .parent()
+                .map(|dir| dir.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            "name" => path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            "year" => path
+                .to_string_lossy()
+                .split(|c: char| !c.is_ascii_digit())
+                .find(|part| part.len() == 4)
+                .unwrap_or_default()
+                .to_string(),
+            _ => return None,
+        };
+        Some(value)
+    };
+
+    for (key, value) in labels.iter_mut() {
+        match substitute(key, value, lookup) {
+            Ok(rendered) => *value = rendered,
+            Err(err) => log::debug!("Keeping label value: {err}"),
+        }
+    }
+
+    labels
+}
+
+/// Replace all `{{variable}}` occurrences of a label value.
+fn substitute(
+    key: &str,
+    value: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String, LabelTemplateError> {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find("{{") {
+        result.push_str(&rest[..start]);
+        rest = &rest[start + 2..];
+
+        let end = rest
+            .find("}}")
+            .ok_or_else(|| LabelTemplateError::Unterminated(key.to_string()))?;
+        let name = rest[..end].trim();
+
+        let variable = lookup(name).ok_or_else(|| {
+            LabelTemplateError::UnknownVariable(key.to_string(), name.to_string())
+        })?;
+        result.push_str(&variable);
+
+        rest = &rest[end + 2..];
+    }
+
+    result.push_str(rest);
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn render() {
+        let labels = render_label_templates(
+            Labels::new()
+                .add("file", "cves/2024/1xxx/CVE-2024-1234.json")
+                .add("source", "osv")
+                .add("tier", "{{year}}")
+                .add("origin", "{{ dir }}/{{name}}")
+                .add("broken", "{{year"),
+        );
+
+        assert_eq!(labels.get("source").map(String::as_str), Some("osv"));
+        assert_eq!(labels.get("tier").map(String::as_str), Some("2024"));
+        assert_eq!(
+            labels.get("origin").map(String::as_str),
+            Some("cves/2024/1xxx/CVE-2024-1234.json")
+        );
+        assert_eq!(labels.get("broken").map(String::as_str), Some("{{year"));
+    }
+
+    #[test]
+    fn validate() {
+        assert!(validate_label_templates(&Labels::new().add("tier", "{{year}}-x")).is_ok());
+        assert!(matches!(
+            validate_label_templates(&Labels::new().add("tier", "{{month}}")),
+            Err(LabelTemplateError::UnknownVariable(key, name)) if key == "tier" && name == "month"
+        ));
+        assert!(matches!(
+            validate_label_templates(&Labels::new().add("tier", "{{year")),
+            Err(LabelTemplateError::Unterminated(_))
+        ));
+    }
+}
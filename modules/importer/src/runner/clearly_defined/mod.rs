@@ -3,13 +3,11 @@ mod walker;
 use crate::model::ClearlyDefinedImporter;
 use crate::runner::clearly_defined::walker::ClearlyDefinedWalker;
 use crate::runner::context::RunContext;
-use crate::runner::report::{ReportBuilder, ScannerError};
+use crate::runner::report::ScannerError;
 use crate::server::RunOutput;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tracing::instrument;
-use trustify_module_ingestor::graph::Graph;
-use trustify_module_ingestor::service::IngestorService;
 
 impl super::ImportRunner {
     #[instrument(skip(self), err(level=tracing::Level::INFO))]
@@ -19,10 +17,8 @@ impl super::ImportRunner {
         clearly_defined: ClearlyDefinedImporter,
         continuation: serde_json::Value,
     ) -> Result<RunOutput, ScannerError> {
-        let ingestor =
-            IngestorService::new(Graph::new(), self.storage.clone(), self.analysis.clone());
-
-        let report = Arc::new(Mutex::new(ReportBuilder::new()));
+        let (ingestor, report) = self.ingestor(context.name(), &clearly_defined);
+        let report = Arc::new(Mutex::new(report));
         let continuation = serde_json::from_value(continuation).unwrap_or_default();
 
         let progress = context.progress(format!(
@@ -30,6 +26,8 @@ impl super::ImportRunner {
             clearly_defined.source
         ));
 
+        let client = self.http_client(&clearly_defined)?;
+
         let walker = ClearlyDefinedWalker::new(
             clearly_defined.source.clone(),
             ingestor,
@@ -37,6 +35,7 @@ impl super::ImportRunner {
             report.clone(),
             progress,
         )
+        .client(client)
         .continuation(continuation);
 
         match walker.run().await {
@@ -1,4 +1,4 @@
-use crate::runner::common::Error;
+use crate::runner::common::{Error, ingestor::Ingestor};
 use crate::runner::progress::{Progress, ProgressInstance};
 use crate::runner::report::{Phase, ReportBuilder};
 use serde::{Deserialize, Serialize};
@@ -9,12 +9,12 @@ use tokio::sync::Mutex;
 use tokio_util::bytes::Buf;
 use trustify_common::db::ReadWrite;
 use trustify_entity::labels::Labels;
-use trustify_module_ingestor::service::{Cache, Format, IngestorService};
+use trustify_module_ingestor::service::{Cache, Format};
 
 pub struct ClearlyDefinedWalker<P: Progress + Send + 'static> {
     continuation: ClearlyDefinedItemContinuation,
     source: String,
-    ingestor: IngestorService,
+    ingestor: Ingestor,
     db: ReadWrite,
     progress: P,
     report: Arc<Mutex<ReportBuilder>>,
@@ -25,7 +25,7 @@ pub struct ClearlyDefinedWalker<P: Progress + Send + 'static> {
 impl<P: Progress + Send + 'static> ClearlyDefinedWalker<P> {
     pub fn new(
         source: impl Into<String>,
-        ingestor: IngestorService,
+        ingestor: impl Into<Ingestor>,
         db: ReadWrite,
         report: Arc<Mutex<ReportBuilder>>,
         progress: P,
@@ -33,7 +33,7 @@ impl<P: Progress + Send + 'static> ClearlyDefinedWalker<P> {
         Self {
             continuation: Default::default(),
             source: source.into(),
-            ingestor,
+            ingestor: ingestor.into(),
             db,
             progress,
             report,
@@ -42,6 +42,12 @@ impl<P: Progress + Send + 'static> ClearlyDefinedWalker<P> {
         }
     }
 
+    /// Set the HTTP client for fetching the documents.
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
     pub fn continuation(mut self, continuation: ClearlyDefinedItemContinuation) -> Self {
         self.continuation = continuation;
         self
@@ -65,10 +71,13 @@ impl<P: Progress + Send + 'static> ClearlyDefinedWalker<P> {
         let mut high = None;
 
         for date in filtered_notices {
-            if self.load_changes(&date).await.is_ok() {
-                high.replace(date);
+            match self.load_changes(&date).await {
+                Ok(()) => {
+                    high.replace(date);
+                    progress.tick().await;
+                }
+                Err(_) => progress.fail().await,
             }
-            progress.tick().await;
         }
 
         Ok(ClearlyDefinedItemContinuation { high })
@@ -115,7 +124,7 @@ impl<P: Progress + Send + 'static> ClearlyDefinedWalker<P> {
             .transaction(async |tx| {
                 self.ingestor
                     .ingest(
-                        &body,
+                        body,
                         Format::ClearlyDefined,
                         Labels::default(),
                         Some("ClearlyDefined".to_string()),
@@ -3,7 +3,7 @@ use crate::{
     model::ClearlyDefinedPackageType,
     runner::common::{
         processing_error::ProcessingError,
-        walker::{CallbackError, Callbacks, Handler, HandlerError},
+        walker::{CallbackError, Callbacks, Continuation, Handler, HandlerError},
     },
 };
 use std::collections::HashSet;
@@ -50,6 +50,15 @@ where
 
         Ok(())
     }
+
+    fn checkpoint(&self, continuation: &Continuation) -> Result<(), HandlerError<Self::Error>> {
+        self.callbacks
+            .checkpoint(continuation)
+            .map_err(|err| match err {
+                CallbackError::Processing(err) => HandlerError::Processing(Error::Processing(err)),
+                CallbackError::Canceled => HandlerError::Canceled,
+            })
+    }
 }
 
 impl<C> ClearlyDefinedHandler<C>
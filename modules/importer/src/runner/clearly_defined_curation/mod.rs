@@ -4,7 +4,11 @@ use crate::{
     model::ClearlyDefinedCurationImporter,
     runner::{
         RunOutput,
-        common::walker::{CallbackError, Callbacks, GitWalker},
+        common::{
+            filter::PathFilter,
+            ingestor::Ingestor,
+            walker::{CallbackError, Callbacks, Continuation, GitWalker},
+        },
         context::RunContext,
         report::{Phase, ReportBuilder, ScannerError},
     },
@@ -13,26 +17,23 @@ use handler::ClearlyDefinedHandler;
 use parking_lot::Mutex;
 use std::{path::Path, path::PathBuf, sync::Arc};
 use tokio::runtime::Handle;
+use tokio_util::bytes::Bytes;
 use tracing::instrument;
 use trustify_common::db::ReadWrite;
 use trustify_entity::labels::Labels;
-use trustify_module_ingestor::service::Cache;
-use trustify_module_ingestor::{
-    graph::Graph,
-    service::{Format, IngestorService},
-};
+use trustify_module_ingestor::service::{Cache, Format};
 
 struct Context<C: RunContext + 'static> {
     context: C,
     source: String,
     labels: Labels,
     report: Arc<Mutex<ReportBuilder>>,
-    ingestor: IngestorService,
+    ingestor: Ingestor,
     db: ReadWrite,
 }
 
 impl<C: RunContext> Context<C> {
-    fn store(&self, path: &Path, data: Vec<u8>) -> anyhow::Result<()> {
+    fn store(&self, path: &Path, data: Bytes) -> anyhow::Result<()> {
         self.report.lock().tick();
 
         Handle::current().block_on(async {
@@ -40,7 +41,7 @@ impl<C: RunContext> Context<C> {
                 .transaction(async |tx| {
                     self.ingestor
                         .ingest(
-                            &data,
+                            data.clone(),
                             Format::ClearlyDefinedCuration,
                             Labels::new()
                                 .add("source", &self.source)
@@ -68,7 +69,7 @@ impl<C: RunContext> Callbacks<Vec<u8>> for Context<C> {
     }
 
     fn process(&self, path: &Path, curation: Vec<u8>) -> Result<(), CallbackError> {
-        if let Err(err) = self.store(path, curation) {
+        if let Err(err) = self.store(path, curation.into()) {
             self.report
                 .lock()
                 .add_error(Phase::Upload, path.to_string_lossy(), err.to_string());
@@ -77,6 +78,18 @@ impl<C: RunContext> Callbacks<Vec<u8>> for Context<C> {
         self.context.check_canceled_sync(|| CallbackError::Canceled)
     }
 
+    fn checkpoint(&self, continuation: &Continuation) -> Result<(), CallbackError> {
+        if self.ingestor.is_dry_run() {
+            return Ok(());
+        }
+
+        let continuation = serde_json::to_value(continuation)
+            .map_err(|err| CallbackError::Processing(err.into()))?;
+        self.context.checkpoint_sync(continuation);
+
+        Ok(())
+    }
+
     fn is_canceled(&self) -> bool {
         self.context.is_canceled_sync()
     }
@@ -90,10 +103,8 @@ impl super::ImportRunner {
         clearly_defined: ClearlyDefinedCurationImporter,
         continuation: serde_json::Value,
     ) -> Result<RunOutput, ScannerError> {
-        let ingestor =
-            IngestorService::new(Graph::new(), self.storage.clone(), self.analysis.clone());
-
-        let report = Arc::new(Mutex::new(ReportBuilder::new()));
+        let (ingestor, report) = self.ingestor(context.name(), &clearly_defined);
+        let report = Arc::new(Mutex::new(report));
         let continuation = serde_json::from_value(continuation).unwrap_or_default();
 
         // working dir
@@ -109,6 +120,10 @@ impl super::ImportRunner {
             clearly_defined.source
         ));
 
+        let proxy = self.proxy(&clearly_defined);
+        let filter = PathFilter::from_config(&clearly_defined)?;
+        let credentials = self.git_credentials(clearly_defined.credentials.as_ref())?;
+
         // run the walker
 
         let walker = GitWalker::new(
@@ -127,6 +142,9 @@ impl super::ImportRunner {
         )
         .path(Some("curations"))
         .continuation(continuation)
+        .proxy(proxy)
+        .filter(filter)
+        .credentials(credentials)
         .progress(progress);
 
         let continuation = match working_dir {
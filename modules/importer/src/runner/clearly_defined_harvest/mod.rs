@@ -0,0 +1,51 @@
+mod walker;
+
+use crate::model::ClearlyDefinedHarvestImporter;
+use crate::runner::{
+    RunOutput, clearly_defined_harvest::walker::HarvestWalker, context::RunContext,
+    report::ScannerError,
+};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::instrument;
+
+impl super::ImportRunner {
+    #[instrument(skip(self), err(level=tracing::Level::INFO))]
+    pub async fn run_once_clearly_defined_harvest(
+        &self,
+        context: impl RunContext + 'static,
+        clearly_defined: ClearlyDefinedHarvestImporter,
+    ) -> Result<RunOutput, ScannerError> {
+        let (ingestor, report) = self.ingestor(context.name(), &clearly_defined);
+        let report = Arc::new(Mutex::new(report));
+
+        // no working-dir required, and every run harvests all coordinates again
+
+        let client = self.http_client(&clearly_defined)?;
+
+        let walker = HarvestWalker::new(
+            clearly_defined,
+            ingestor,
+            self.db.clone(),
+            report.clone(),
+            context,
+        )
+        .client(client);
+
+        match walker.run().await {
+            Ok(()) => {
+                // extract the report
+                let report = match Arc::try_unwrap(report) {
+                    Ok(report) => report.into_inner(),
+                    Err(report) => report.lock().await.clone(),
+                }
+                .build();
+                Ok(report.into())
+            }
+            Err(err) => Err(ScannerError::Normal {
+                err: err.into(),
+                output: report.lock().await.clone().build().into(),
+            }),
+        }
+    }
+}
@@ -0,0 +1,355 @@
+use crate::{
+    model::{ClearlyDefinedHarvestImporter, ClearlyDefinedPackageType},
+    runner::{
+        common::{Error, ingestor::Ingestor},
+        context::RunContext,
+        progress::{Progress, ProgressInstance},
+        report::{Message, Phase, ReportBuilder},
+    },
+};
+use anyhow::anyhow;
+use sea_orm::{
+    ColumnTrait, EntityTrait, JoinType, QueryFilter, QueryOrder, QuerySelect, QueryTrait,
+    RelationTrait,
+};
+use std::{collections::BTreeMap, sync::Arc};
+use tokio::sync::Mutex;
+use tracing::instrument;
+use trustify_common::db::ReadWrite;
+use trustify_entity::{base_purl, labels::Labels, qualified_purl, versioned_purl};
+use trustify_module_ingestor::service::{Cache, Format};
+use url::Url;
+
+/// The number of definitions requested at once, by default
+const DEFAULT_BATCH_SIZE: usize = 100;
+
+pub struct HarvestWalker<C: RunContext> {
+    importer: ClearlyDefinedHarvestImporter,
+    ingestor: Ingestor,
+    db: ReadWrite,
+    report: Arc<Mutex<ReportBuilder>>,
+    context: C,
+    client: reqwest::Client,
+}
+
+impl<C: RunContext> HarvestWalker<C> {
+    pub fn new(
+        importer: ClearlyDefinedHarvestImporter,
+        ingestor: impl Into<Ingestor>,
+        db: ReadWrite,
+        report: Arc<Mutex<ReportBuilder>>,
+        context: C,
+    ) -> Self {
+        Self {
+            importer,
+            ingestor: ingestor.into(),
+            db,
+            report,
+            context,
+            client: Default::default(),
+        }
+    }
+
+    /// Set the HTTP client for fetching the definitions.
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Run the walker
+    ///
+    /// Requests the definitions of all coordinates derived from the packages of SBOMs, in
+    /// batches. Definitions without license information are skipped.
+    #[instrument(skip(self), err(level=tracing::Level::INFO))]
+    pub async fn run(self) -> Result<(), Error> {
+        let url = self.definitions_url()?;
+        let coordinates = self.coordinates().await?;
+
+        log::info!(
+            "Harvesting {} ClearlyDefined definitions",
+            coordinates.len()
+        );
+
+        let progress = self
+            .context
+            .progress(format!("Harvest ClearlyDefined definitions: {url}"));
+        let mut progress = progress.start(coordinates.len());
+
+        let batch_size = self
+            .importer
+            .batch_size
+            .unwrap_or(DEFAULT_BATCH_SIZE)
+            .max(1);
+
+        for batch in coordinates.chunks(batch_size) {
+            let definitions = match self.fetch(&url, batch).await {
+                Ok(definitions) => definitions,
+                Err(err) => {
+                    let mut report = self.report.lock().await;
+                    for coordinate in batch {
+                        report.add_error(Phase::Retrieval, coordinate, err.to_string());
+                        progress.fail().await;
+                    }
+                    continue;
+                }
+            };
+
+            for coordinate in batch {
+                match definitions.get(coordinate) {
+                    Some(definition) if has_license(definition) => {
+                        match self.store(coordinate, definition).await {
+                            true => progress.tick().await,
+                            false => progress.fail().await,
+                        }
+                    }
+                    _ => {
+                        log::debug!("No license information for: {coordinate}");
+                        progress.skip().await;
+                    }
+                }
+            }
+
+            if self.context.is_canceled().await {
+                return Err(Error::Canceled);
+            }
+        }
+
+        progress.finish().await;
+
+        Ok(())
+    }
+
+    /// The URL of the endpoint for requesting definitions
+    fn definitions_url(&self) -> Result<Url, Error> {
+        let mut source = self.importer.source.clone();
+        if !source.ends_with('/') {
+            source.push('/');
+        }
+
+        Url::parse(&source)
+            .and_then(|url| url.join("definitions"))
+            .map_err(|err| Error::Processing(anyhow!("invalid source URL '{source}': {err}")))
+    }
+
+    /// The coordinates of all packages referenced by SBOMs, for the configured types
+    async fn coordinates(&self) -> Result<Vec<String>, Error> {
+        let types = match self.importer.types.is_empty() {
+            true => ClearlyDefinedPackageType::all(),
+            false => self.importer.types.clone(),
+        };
+        let types: BTreeMap<_, _> = types
+            .into_iter()
+            .map(|r#type| (r#type.purl_type().to_string(), r#type))
+            .collect();
+
+        let packages: Vec<(String, Option<String>, String, String)> =
+            versioned_purl::Entity::find()
+                .select_only()
+                .column(base_purl::Column::Type)
+                .column(base_purl::Column::Namespace)
+                .column(base_purl::Column::Name)
+                .column(versioned_purl::Column::Version)
+                .distinct()
+                .join(
+                    JoinType::InnerJoin,
+                    versioned_purl::Relation::BasePurl.def(),
+                )
+                .filter(base_purl::Column::Type.is_in(types.keys().map(String::as_str)))
+                .filter(
+                    versioned_purl::Column::Id.in_subquery(
+                        qualified_purl::Entity::find()
+                            .select_only()
+                            .column(qualified_purl::Column::VersionedPurlId)
+                            .join(
+                                JoinType::InnerJoin,
+                                qualified_purl::Relation::SbomNode.def(),
+                            )
+                            .into_query(),
+                    ),
+                )
+                .order_by_asc(base_purl::Column::Type)
+                .order_by_asc(base_purl::Column::Namespace)
+                .order_by_asc(base_purl::Column::Name)
+                .order_by_asc(versioned_purl::Column::Version)
+                .into_tuple()
+                .all(&self.db)
+                .await?;
+
+        Ok(packages
+            .into_iter()
+            .filter_map(|(r#type, namespace, name, version)| {
+                types
+                    .get(&r#type)
+                    .map(|r#type| r#type.coordinates(namespace.as_deref(), &name, &version))
+            })
+            .collect())
+    }
+
+    /// Request the definitions of a batch of coordinates
+    async fn fetch(
+        &self,
+        url: &Url,
+        coordinates: &[String],
+    ) -> Result<BTreeMap<String, serde_json::Value>, Error> {
+        Ok(self
+            .client
+            .post(url.clone())
+            .json(coordinates)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    /// Store a definition, returning `true` if it got stored.
+    async fn store(&self, coordinate: &str, definition: &serde_json::Value) -> bool {
+        // definitions of the API might lack the ID, which is required for ingesting them
+        let mut definition = definition.clone();
+        if let Some(definition) = definition.as_object_mut() {
+            definition.entry("_id").or_insert_with(|| coordinate.into());
+        }
+
+        let data = match serde_json::to_vec(&definition) {
+            Ok(data) => data,
+            Err(err) => {
+                self.report
+                    .lock()
+                    .await
+                    .add_error(Phase::Validation, coordinate, err.to_string());
+                return false;
+            }
+        };
+
+        let labels = Labels::new()
+            .add("source", &self.importer.source)
+            .add("importer", "ClearlyDefined harvest")
+            .add("coordinate", coordinate)
+            .extend(self.importer.labels.0.clone());
+
+        let result = self
+            .db
+            .transaction(async |tx| {
+                self.ingestor
+                    .ingest(
+                        data,
+                        Format::ClearlyDefined,
+                        labels,
+                        Some("ClearlyDefined".to_string()),
+                        Cache::Skip,
+                        tx,
+                    )
+                    .await
+            })
+            .await;
+
+        let mut report = self.report.lock().await;
+        match result {
+            Ok(result) => {
+                report.tick();
+                report.extend_messages(
+                    Phase::Upload,
+                    coordinate,
+                    result.warnings.iter().map(Message::from),
+                );
+                true
+            }
+            Err(err) => {
+                log::warn!("Error importing {coordinate}: {err}");
+                report.add_error(Phase::Upload, coordinate, err.to_string());
+                false
+            }
+        }
+    }
+}
+
+/// Check if a definition carries license information
+fn has_license(definition: &serde_json::Value) -> bool {
+    definition
+        .pointer("/licensed/declared")
+        .and_then(|license| license.as_str())
+        .is_some_and(|license| !license.is_empty())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        model::CommonImporter,
+        runner::common::test::{build_report, new_report},
+    };
+    use std::collections::HashSet;
+    use test_context::test_context;
+    use test_log::test;
+    use trustify_entity::sbom;
+    use trustify_test_context::{TrustifyContext, document_bytes};
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{body_json, method, path},
+    };
+
+    #[test]
+    fn coordinates() {
+        assert_eq!(
+            ClearlyDefinedPackageType::Crate.coordinates(None, "serde", "1.0.0"),
+            "crate/cratesio/-/serde/1.0.0"
+        );
+        assert_eq!(
+            ClearlyDefinedPackageType::Go.coordinates(Some("github.com/gorilla"), "mux", "v1.8.0"),
+            "go/golang/github.com%2fgorilla/mux/v1.8.0"
+        );
+    }
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn harvest(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        ctx.ingest_document("cyclonedx/ghsa_test.json").await?;
+
+        let mut definition: serde_json::Value = serde_json::from_slice(
+            &document_bytes("clearly-defined/aspnet.mvc-4.0.40804.json").await?,
+        )?;
+        definition
+            .as_object_mut()
+            .expect("must be an object")
+            .remove("_id");
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/definitions"))
+            .and(body_json(["crate/cratesio/-/Deno/1.32.0"]))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "crate/cratesio/-/Deno/1.32.0": definition,
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let report = new_report();
+        let walker = HarvestWalker::new(
+            ClearlyDefinedHarvestImporter {
+                common: CommonImporter::default(),
+                source: server.uri(),
+                types: HashSet::from([ClearlyDefinedPackageType::Crate]),
+                batch_size: None,
+            },
+            ctx.ingestor.clone(),
+            ReadWrite::new(ctx.db.clone()),
+            report.clone(),
+            (),
+        );
+
+        walker.run().await?;
+
+        let report = build_report(report);
+        assert_eq!(report.number_of_items, 1);
+        assert!(report.messages.is_empty(), "{:?}", report.messages);
+
+        let harvested = sbom::Entity::find()
+            .filter(sbom::Column::DocumentId.eq("crate/cratesio/-/Deno/1.32.0"))
+            .one(&ctx.db)
+            .await?;
+        assert!(harvested.is_some());
+
+        Ok(())
+    }
+}
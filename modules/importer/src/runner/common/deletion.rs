@@ -0,0 +1,130 @@
+use crate::model::UpstreamDeletion;
+use sea_orm::{ConnectionTrait, DbErr, Statement};
+use serde_json::json;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+/// The label added to advisories, which got deleted upstream
+pub const LABEL_UPSTREAM_DELETED: &str = "upstreamDeleted";
+
+/// Apply the handling of a file, which got deleted upstream, to the advisories ingested from it.
+///
+/// Advisories are located by their `importer` and `file` labels. Returns the number of updated
+/// advisories.
+pub async fn apply(
+    handling: UpstreamDeletion,
+    importer: &str,
+    file: &str,
+    connection: &impl ConnectionTrait,
+) -> Result<u64, DbErr> {
+    let now = OffsetDateTime::now_utc();
+    let origin = json!({
+        "importer": importer,
+        "file": file,
+    });
+
+    let stmt = match handling {
+        UpstreamDeletion::Keep => return Ok(0),
+        UpstreamDeletion::Label => Statement::from_sql_and_values(
+            connection.get_database_backend(),
+            r#"UPDATE advisory SET labels = labels || $2 WHERE labels @> $1"#,
+            [
+                origin.into(),
+                json!({
+                    LABEL_UPSTREAM_DELETED: now.format(&Rfc3339).unwrap_or_default(),
+                })
+                .into(),
+            ],
+        ),
+        UpstreamDeletion::Withdraw => Statement::from_sql_and_values(
+            connection.get_database_backend(),
+            r#"UPDATE advisory SET withdrawn = $2 WHERE labels @> $1 AND withdrawn IS NULL"#,
+            [origin.into(), now.into()],
+        ),
+    };
+
+    Ok(connection.execute(stmt).await?.rows_affected())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sea_orm::EntityTrait;
+    use test_context::test_context;
+    use test_log::test;
+    use trustify_entity::{advisory, labels::Labels};
+    use trustify_module_ingestor::service::Format;
+    use trustify_test_context::TrustifyContext;
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn deletion(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        ctx.ingest_document_as(
+            "csaf/cve-2023-0044.json",
+            Format::CSAF,
+            Labels::new()
+                .add("importer", "test")
+                .add("file", "2023/cve-2023-0044.json"),
+        )
+        .await?;
+
+        let advisory = || async { advisory::Entity::find().one(&ctx.db).await };
+
+        // a different file must not match
+
+        assert_eq!(
+            apply(UpstreamDeletion::Label, "test", "other.json", &ctx.db).await?,
+            0
+        );
+        assert_eq!(
+            apply(
+                UpstreamDeletion::Keep,
+                "test",
+                "2023/cve-2023-0044.json",
+                &ctx.db
+            )
+            .await?,
+            0
+        );
+
+        // label
+
+        assert_eq!(
+            apply(
+                UpstreamDeletion::Label,
+                "test",
+                "2023/cve-2023-0044.json",
+                &ctx.db
+            )
+            .await?,
+            1
+        );
+        let advisory_model = advisory().await?.expect("must exist");
+        assert!(advisory_model.labels.0.contains_key(LABEL_UPSTREAM_DELETED));
+
+        // withdraw, only once
+
+        assert_eq!(
+            apply(
+                UpstreamDeletion::Withdraw,
+                "test",
+                "2023/cve-2023-0044.json",
+                &ctx.db
+            )
+            .await?,
+            1
+        );
+        assert!(advisory().await?.expect("must exist").withdrawn.is_some());
+        assert_eq!(
+            apply(
+                UpstreamDeletion::Withdraw,
+                "test",
+                "2023/cve-2023-0044.json",
+                &ctx.db
+            )
+            .await?,
+            0
+        );
+
+        Ok(())
+    }
+}
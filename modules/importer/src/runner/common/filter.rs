@@ -1,4 +1,4 @@
-use crate::runner::report::ScannerError;
+use crate::{model::CommonImporter, runner::report::ScannerError};
 use regex::Regex;
 use std::str::FromStr;
 use walker_common::utils::url::Urlify;
@@ -10,20 +10,64 @@ mod sbom {
     pub use sbom_walker::discover::{DiscoveredContext, DiscoveredSbom, DiscoveredVisitor};
 }
 
+/// Include and exclude patterns for the paths of documents
+#[derive(Clone, Debug, Default)]
+pub struct PathFilter {
+    include: Vec<Regex>,
+    exclude: Vec<Regex>,
+}
+
+impl PathFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self, regex::Error> {
+        let compile = |patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|pattern| Regex::from_str(pattern))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        Ok(Self {
+            include: compile(include)?,
+            exclude: compile(exclude)?,
+        })
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub fn from_config(common: &CommonImporter) -> Result<Self, ScannerError> {
+        Self::new(&common.include, &common.exclude)
+            .map_err(|err| ScannerError::Critical(err.into()))
+    }
+
+    /// Check if a path passes the filter.
+    ///
+    /// It must match one of the include patterns, if there are any, and none of the exclude
+    /// patterns.
+    pub fn matches(&self, path: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|pattern| pattern.is_match(path)))
+            && !self.exclude.iter().any(|pattern| pattern.is_match(path))
+    }
+}
+
 pub struct Filter<T> {
     pub only_patterns: Vec<Regex>,
+    pub paths: PathFilter,
     pub next: T,
 }
 
 impl<T> Filter<T> {
     #[allow(clippy::result_large_err)]
-    pub fn from_config(next: T, only_patterns: Vec<String>) -> Result<Self, ScannerError> {
+    pub fn from_config(
+        next: T,
+        only_patterns: Vec<String>,
+        paths: PathFilter,
+    ) -> Result<Self, ScannerError> {
         Ok(Self {
             only_patterns: only_patterns
                 .into_iter()
                 .map(|r| Regex::from_str(&r))
                 .collect::<Result<_, _>>()
                 .map_err(|err| ScannerError::Critical(err.into()))?,
+            paths,
             next,
         })
     }
@@ -32,7 +76,9 @@ impl<T> Filter<T> {
     ///
     /// return `true` of the document should be skipped, `false` otherwise
     fn skip(&self, document: &impl Urlify) -> bool {
-        if self.only_patterns.is_empty() {
+        if !self.paths.matches(document.url().path()) {
+            true
+        } else if self.only_patterns.is_empty() {
             false
         } else {
             let url = document.url();
@@ -190,6 +236,7 @@ mod test {
 
         let filter = Filter {
             only_patterns: vec![],
+            paths: Default::default(),
             next: mock.clone(),
         };
 
@@ -210,6 +257,7 @@ mod test {
 
         let filter = Filter {
             only_patterns: vec![Regex::from_str(r#".*\.json$"#).unwrap()],
+            paths: Default::default(),
             next: mock.clone(),
         };
 
@@ -230,4 +278,28 @@ mod test {
             ]
         );
     }
+
+    #[test(tokio::test)]
+    async fn filter_paths() {
+        let mock = MockVisitor::default();
+
+        let filter = Filter {
+            only_patterns: vec![],
+            paths: PathFilter::new(&["^/foo/".into()], &["/vendor/".into()]).unwrap(),
+            next: mock.clone(),
+        };
+
+        for i in [
+            "https://foo/foo/baz.json",
+            "https://foo/foo/vendor/baz.json",
+            "https://foo/bar/baz.json",
+        ] {
+            filter.visit_sbom(&(), mock_sbom(i)).await.unwrap();
+        }
+
+        assert_eq!(
+            *mock.found.lock(),
+            vec!["https://foo/foo/baz.json".to_string()]
+        );
+    }
 }
@@ -1,6 +1,6 @@
 use std::future::Future;
 
-use super::Error;
+use super::{Error, lock::ImporterLock};
 use crate::model::Importer;
 use sea_orm::{QueryFilter, entity::*, prelude::*};
 use time::OffsetDateTime;
@@ -32,21 +32,41 @@ impl Heart {
         !self.handle.is_finished()
     }
 
-    // Attempts to acquire exclusive optimistic lock. Upon success,
-    // spawns the future, and regularly updates the lock until the
-    // future completes.
+    // Attempts to acquire the distributed lock of the importer, so
+    // that it runs on a single instance only, and then the exclusive
+    // optimistic lock. Upon success, spawns the future, and regularly
+    // updates the lock until the future completes.
     async fn pump<F>(importer: Importer, db: ReadWrite, future: F, token: CancellationToken)
     where
         F: Future + 'static,
     {
         let name = importer.name.clone();
+        let lock = match ImporterLock::try_acquire(&name, &db).await {
+            Ok(Some(lock)) => lock,
+            Ok(None) => {
+                log::debug!("'{name}' is running on another instance");
+                return;
+            }
+            Err(e) => {
+                log::warn!("Unable to acquire distributed lock for '{name}': {e}");
+                return;
+            }
+        };
         if let Ok(importer) = Heart::beat(&importer, &db).await {
             log::debug!("Acquired lock; running '{name}'");
             let work = spawn_local(future);
             let mut interval = interval(Heart::RATE);
             let mut importer = importer;
             loop {
-                interval.tick().await;
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    _ = lock.lost() => {
+                        log::error!("Aborting '{name}': lost the distributed lock");
+                        token.cancel();
+                        work.abort();
+                        break;
+                    }
+                }
                 match Self::beat(&importer, &db).await {
                     Ok(i) => {
                         log::debug!("{name}: {:#?}", i.data.progress);
@@ -67,6 +87,9 @@ impl Heart {
         } else {
             log::debug!("Unable to acquire lock to run '{name}'");
         }
+        if let Err(e) = lock.release().await {
+            log::warn!("Failed to release distributed lock for '{name}': {e}");
+        }
     }
 
     // Employs an optimistic locking strategy to update the importer
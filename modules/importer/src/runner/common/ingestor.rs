@@ -0,0 +1,208 @@
+use crate::{
+    model::render_label_templates,
+    runner::{
+        common::{
+            metrics::IngestMetrics,
+            retry::{Retry, Retryable},
+            throttle::Throttle,
+        },
+        report::DryRunSummary,
+    },
+};
+use parking_lot::Mutex;
+use sea_orm::{ConnectionTrait, TransactionTrait};
+use std::{
+    fmt::{Debug, Display},
+    sync::Arc,
+    time::Instant,
+};
+use tokio_util::bytes::Bytes;
+use trustify_entity::labels::Labels;
+use trustify_module_ingestor::{
+    model::IngestResult,
+    service::{Cache, Error, Format, IngestorService},
+};
+
+/// The ingestor used by the importer runners
+///
+/// For a dry run, documents are only recorded in a summary, instead of being ingested. With a
+/// [`Throttle`], documents are paced according to its limits. With a [`Retry`], operations run
+/// through [`Self::retrying`] are retried in case of transient errors. With [`IngestMetrics`],
+/// the outcome of ingesting documents gets recorded.
+#[derive(Clone)]
+pub struct Ingestor {
+    target: Target,
+    throttle: Option<Throttle>,
+    retry: Retry,
+    metrics: Option<IngestMetrics>,
+}
+
+#[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
+enum Target {
+    Service(IngestorService),
+    DryRun(Arc<Mutex<DryRunSummary>>),
+}
+
+impl From<IngestorService> for Ingestor {
+    fn from(value: IngestorService) -> Self {
+        Self {
+            target: Target::Service(value),
+            throttle: None,
+            retry: Default::default(),
+            metrics: None,
+        }
+    }
+}
+
+impl Ingestor {
+    /// Create an ingestor which only records documents in the provided summary.
+    pub fn dry_run(summary: Arc<Mutex<DryRunSummary>>) -> Self {
+        Self {
+            target: Target::DryRun(summary),
+            throttle: None,
+            retry: Default::default(),
+            metrics: None,
+        }
+    }
+
+    /// Pace the ingested documents using the provided throttle.
+    pub fn throttle(mut self, throttle: Throttle) -> Self {
+        self.throttle = Some(throttle);
+        self
+    }
+
+    /// Retry operations according to the provided retry.
+    pub fn retry(mut self, retry: Retry) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Record the ingested documents in the provided metrics.
+    pub fn metrics(mut self, metrics: IngestMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Run an operation, like the ingestion of a document in a transaction, retrying it in case
+    /// of a transient error.
+    pub async fn retrying<T, E, F>(&self, f: F) -> Result<T, E>
+    where
+        F: AsyncFnMut() -> Result<T, E>,
+        E: Retryable + Display,
+    {
+        self.retry.run(f).await
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        matches!(self.target, Target::DryRun(_))
+    }
+
+    /// Ingest a document, see [`IngestorService::ingest`].
+    ///
+    /// Template variables in the values of the labels get rendered, see
+    /// [`render_label_templates`]. For a dry run, the document is recorded by its `file` label,
+    /// falling back to its `source` label.
+    pub async fn ingest(
+        &self,
+        bytes: impl Into<Bytes>,
+        format: Format,
+        labels: impl Into<Labels> + Debug,
+        issuer: Option<String>,
+        cache: Cache,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<IngestResult, Error> {
+        let bytes = bytes.into();
+        let len = bytes.len();
+
+        if let Some(throttle) = &self.throttle {
+            throttle.acquire(len).await;
+        }
+
+        let labels = render_label_templates(labels.into());
+
+        match &self.target {
+            Target::Service(service) => {
+                let start = Instant::now();
+                let result = service
+                    .ingest(bytes, format, labels, issuer, cache, tx)
+                    .await;
+
+                if let Some(metrics) = &self.metrics {
+                    metrics.record(len, start.elapsed(), result.is_ok());
+                }
+
+                result
+            }
+            Target::DryRun(summary) => {
+                let path = labels
+                    .0
+                    .get("file")
+                    .or_else(|| labels.0.get("source"))
+                    .cloned()
+                    .unwrap_or_default();
+
+                summary.lock().record(path, len);
+
+                Ok(IngestResult {
+                    id: String::new(),
+                    document_id: None,
+                    warnings: vec![],
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sea_orm::{EntityTrait, PaginatorTrait};
+    use test_context::test_context;
+    use test_log::test;
+    use trustify_test_context::{TrustifyContext, document_bytes};
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn dry_run(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let summary = Arc::new(Mutex::new(DryRunSummary::default()));
+        let ingestor = Ingestor::dry_run(summary.clone());
+
+        let data = document_bytes("csaf/cve-2023-0044.json").await?;
+        ctx.db
+            .transaction(async |tx| {
+                ingestor
+                    .ingest(
+                        data.clone(),
+                        Format::CSAF,
+                        Labels::new()
+                            .add("source", "https://example.com")
+                            .add("file", "2023/cve-2023-0044.json"),
+                        None,
+                        Cache::Skip,
+                        tx,
+                    )
+                    .await
+            })
+            .await?;
+
+        assert_eq!(
+            *summary.lock(),
+            DryRunSummary {
+                documents: 1,
+                bytes: data.len() as u64,
+                samples: vec!["2023/cve-2023-0044.json".into()],
+            }
+        );
+
+        // nothing must have been ingested
+        assert_eq!(
+            trustify_entity::advisory::Entity::find()
+                .count(&ctx.db)
+                .await?,
+            0
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,225 @@
+use sea_orm::{ConnectionTrait, DbBackend, DbErr, Statement};
+use tokio::{
+    task::JoinHandle,
+    time::{Duration, Instant, sleep},
+};
+use tokio_util::sync::CancellationToken;
+use trustify_common::db::ReadWrite;
+use uuid::Uuid;
+
+/// A lock for running an importer, held across all instances sharing the database.
+///
+/// The lock is a lease, recorded in the database. It expires after [`Self::DURATION`], unless it
+/// gets renewed, which happens in the background every [`Self::RENEWAL`]. Once the lease is lost,
+/// because it couldn't be renewed in time or was taken over, [`Self::lost`] completes, and the
+/// work guarded by the lock must be aborted.
+///
+/// Holding the lock doesn't require a database connection between renewals.
+///
+/// ## Why not an advisory lock
+///
+/// A Postgres advisory lock is owned by the connection (session level) or the transaction
+/// (transaction level) which took it:
+///
+/// * A transaction level lock requires keeping a transaction open for the whole run, which pins a
+///   pooled connection and keeps an idle transaction around for hours.
+/// * A session level lock stays with the connection, not with the importer run. The pool doesn't
+///   let us hold on to a connection outside a transaction. Once that connection goes back to the
+///   pool, the lock survives there: some unrelated user of the connection would "hold" it, and it
+///   would only be released when the pool happens to recycle the connection. If the pool drops the
+///   connection while the run is still going, the lock is silently released instead.
+///
+/// A lease is independent of any connection, and so survives such pool churn.
+///
+/// ## Expiry and long-running imports
+///
+/// The lease doesn't limit how long an import may run. It gets renewed every [`Self::RENEWAL`],
+/// for as long as the run goes on, and only expires if it couldn't be renewed for
+/// [`Self::DURATION`], e.g. because the instance lost its database connection or got stuck.
+/// Only then may another instance take it over. The previous holder notices that on its next
+/// renewal attempt, or once it has failed renewing for [`Self::DURATION`], and must abort its run
+/// when [`Self::lost`] completes. So two instances may overlap for at most one renewal interval
+/// after a takeover, which the ingestion tolerates, as documents are de-duplicated by digest.
+pub struct ImporterLock {
+    name: String,
+    holder: Uuid,
+    db: ReadWrite,
+    lost: CancellationToken,
+    renewal: JoinHandle<()>,
+}
+
+impl ImporterLock {
+    /// The duration a lease is valid for, unless renewed.
+    pub const DURATION: Duration = Duration::from_secs(60);
+    /// The interval a lease is renewed in.
+    pub const RENEWAL: Duration = Duration::from_secs(15);
+
+    /// Try to acquire the lock for an importer, returning `None` if another instance holds it.
+    pub async fn try_acquire(name: &str, db: &ReadWrite) -> Result<Option<Self>, DbErr> {
+        let holder = Uuid::new_v4();
+
+        // take the lease, unless someone else holds an unexpired one
+        let acquired = db
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"
+INSERT INTO lease (name, holder, expires) VALUES ($1, $2, now() + make_interval(secs => $3))
+ON CONFLICT (name) DO UPDATE SET holder = excluded.holder, expires = excluded.expires
+    WHERE lease.expires < now()
+RETURNING holder
+"#,
+                [
+                    name.into(),
+                    holder.into(),
+                    (Self::DURATION.as_secs() as f64).into(),
+                ],
+            ))
+            .await?
+            .is_some();
+
+        if !acquired {
+            return Ok(None);
+        }
+
+        let lost = CancellationToken::new();
+        let renewal = tokio::spawn(Self::renew_until_lost(
+            name.to_string(),
+            holder,
+            db.clone(),
+            lost.clone(),
+        ));
+
+        Ok(Some(Self {
+            name: name.to_string(),
+            holder,
+            db: db.clone(),
+            lost,
+            renewal,
+        }))
+    }
+
+    /// Complete once the lock is lost.
+    pub async fn lost(&self) {
+        self.lost.cancelled().await
+    }
+
+    /// Release the lock.
+    pub async fn release(self) -> Result<(), DbErr> {
+        self.renewal.abort();
+
+        self.db
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "DELETE FROM lease WHERE name = $1 AND holder = $2",
+                [self.name.clone().into(), self.holder.into()],
+            ))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Renew the lease, returning `false` if it is no longer held.
+    async fn renew(name: &str, holder: Uuid, db: &ReadWrite) -> Result<bool, DbErr> {
+        let result = db
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"
+UPDATE lease SET expires = now() + make_interval(secs => $3)
+    WHERE name = $1 AND holder = $2 AND expires >= now()
+"#,
+                [
+                    name.into(),
+                    holder.into(),
+                    (Self::DURATION.as_secs() as f64).into(),
+                ],
+            ))
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Renew the lease, until it is lost.
+    ///
+    /// Failing to renew is retried, as long as the lease hasn't expired yet.
+    async fn renew_until_lost(name: String, holder: Uuid, db: ReadWrite, lost: CancellationToken) {
+        let mut renewed = Instant::now();
+
+        loop {
+            sleep(Self::RENEWAL).await;
+
+            match Self::renew(&name, holder, &db).await {
+                Ok(true) => renewed = Instant::now(),
+                Ok(false) => {
+                    log::warn!("Lost the lock for '{name}', it was taken over or expired");
+                    break;
+                }
+                Err(err) if renewed.elapsed() >= Self::DURATION => {
+                    log::warn!("Lost the lock for '{name}', failed to renew it: {err}");
+                    break;
+                }
+                Err(err) => {
+                    log::info!("Failed to renew the lock for '{name}', retrying: {err}");
+                }
+            }
+        }
+
+        lost.cancel();
+    }
+}
+
+impl Drop for ImporterLock {
+    fn drop(&mut self) {
+        // the lease expires on its own, if not released
+        self.renewal.abort();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use test_context::test_context;
+    use test_log::test;
+    use trustify_test_context::TrustifyContext;
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn exclusive(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let db = ReadWrite::new(ctx.db.clone());
+
+        let lock = ImporterLock::try_acquire("foo", &db).await?;
+        assert!(lock.is_some());
+
+        // held for "foo", but not for others
+        assert!(ImporterLock::try_acquire("foo", &db).await?.is_none());
+        let other = ImporterLock::try_acquire("bar", &db).await?;
+        assert!(other.is_some());
+
+        // available again once released
+        lock.expect("must be acquired").release().await?;
+        assert!(ImporterLock::try_acquire("foo", &db).await?.is_some());
+
+        Ok(())
+    }
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn expired(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let db = ReadWrite::new(ctx.db.clone());
+
+        let lock = ImporterLock::try_acquire("foo", &db)
+            .await?
+            .expect("must be acquired");
+        assert!(ImporterLock::renew(&lock.name, lock.holder, &db).await?);
+
+        // let the lease expire, so that someone else can take it over
+        db.execute_unprepared("UPDATE lease SET expires = now() - interval '1 second'")
+            .await?;
+        let other = ImporterLock::try_acquire("foo", &db).await?;
+        assert!(other.is_some());
+
+        // the previous holder can't renew it anymore
+        assert!(!ImporterLock::renew(&lock.name, lock.holder, &db).await?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,58 @@
+use opentelemetry::{
+    KeyValue, global,
+    metrics::{Counter, Histogram},
+};
+use std::{sync::Arc, time::Duration};
+
+/// Metrics of the documents ingested by an importer
+///
+/// All metrics carry the name of the importer as the `importer` attribute, so that the
+/// throughput (documents and bytes per second) and the error rate can be derived per importer.
+#[derive(Clone)]
+pub struct IngestMetrics {
+    attributes: Arc<[KeyValue]>,
+    documents: Counter<u64>,
+    bytes: Counter<u64>,
+    errors: Counter<u64>,
+    duration: Histogram<f64>,
+}
+
+impl IngestMetrics {
+    pub fn new(importer: &str) -> Self {
+        let meter = global::meter("importer::Ingestor");
+        Self {
+            attributes: Arc::new([KeyValue::new("importer", importer.to_string())]),
+            documents: meter
+                .u64_counter("importer_documents")
+                .with_description("Documents ingested by an importer")
+                .build(),
+            bytes: meter
+                .u64_counter("importer_bytes")
+                .with_description("Bytes of the documents ingested by an importer")
+                .with_unit("By")
+                .build(),
+            errors: meter
+                .u64_counter("importer_errors")
+                .with_description("Documents an importer failed to ingest")
+                .build(),
+            duration: meter
+                .f64_histogram("importer_ingest_duration")
+                .with_description("Duration of ingesting a single document")
+                .with_unit("s")
+                .build(),
+        }
+    }
+
+    /// Record the outcome of ingesting a document.
+    pub fn record(&self, bytes: usize, duration: Duration, success: bool) {
+        match success {
+            true => {
+                self.documents.add(1, &self.attributes);
+                self.bytes.add(bytes as u64, &self.attributes);
+            }
+            false => self.errors.add(1, &self.attributes),
+        }
+        self.duration
+            .record(duration.as_secs_f64(), &self.attributes);
+    }
+}
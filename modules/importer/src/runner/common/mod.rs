@@ -2,10 +2,19 @@ use sea_orm::DbErr;
 use tokio::task::JoinError;
 use trustify_common::id::IdError;
 
+pub mod deletion;
 pub mod filter;
 pub mod heartbeat;
+pub mod ingestor;
+pub mod lock;
+pub mod metrics;
+pub mod pool;
 pub mod processing_error;
+pub mod proxy;
+pub mod retry;
 pub mod storage;
+pub mod test;
+pub mod throttle;
 pub mod validation;
 pub mod walker;
 
@@ -0,0 +1,175 @@
+use futures::StreamExt;
+use std::{future::Future, sync::Arc};
+use tokio::{
+    runtime::Handle,
+    sync::{mpsc, watch},
+};
+
+/// Create a bounded pool of workers, processing submitted items concurrently.
+///
+/// Items get submitted using the [`Submitter`], e.g. from the callbacks of a walker, and are
+/// processed by the [`Workers`], running up to `concurrency` items at a time. Submitting waits
+/// while all workers are busy, which provides backpressure to the producer.
+pub fn worker_pool<T>(concurrency: usize) -> (Submitter<T>, Workers<T>) {
+    let concurrency = concurrency.max(1);
+    let (sender, receiver) = mpsc::channel(concurrency);
+    let pending = Arc::new(watch::Sender::new(0));
+    (
+        Submitter {
+            sender,
+            pending: pending.clone(),
+        },
+        Workers {
+            receiver,
+            concurrency,
+            pending,
+        },
+    )
+}
+
+/// Submits items to a worker pool
+#[derive(Clone, Debug)]
+pub struct Submitter<T> {
+    sender: mpsc::Sender<T>,
+    /// The number of submitted items which are not yet processed
+    pending: Arc<watch::Sender<usize>>,
+}
+
+impl<T> Submitter<T> {
+    /// Submit an item, waiting for a free slot.
+    ///
+    /// Returns `false` if the workers are gone.
+    pub async fn submit(&self, item: T) -> bool {
+        self.pending.send_modify(|pending| *pending += 1);
+        let result = self.sender.send(item).await.is_ok();
+        if !result {
+            self.pending.send_modify(|pending| *pending -= 1);
+        }
+        result
+    }
+
+    /// Sync version of [`Self::submit`].
+    ///
+    /// **NOTE:** Must not be called from an async context.
+    pub fn submit_sync(&self, item: T) -> bool {
+        self.pending.send_modify(|pending| *pending += 1);
+        let result = self.sender.blocking_send(item).is_ok();
+        if !result {
+            self.pending.send_modify(|pending| *pending -= 1);
+        }
+        result
+    }
+
+    /// Wait until all items submitted so far have been processed.
+    ///
+    /// Returns `false` if the workers are gone.
+    pub async fn flush(&self) -> bool {
+        let mut pending = self.pending.subscribe();
+        tokio::select! {
+            result = pending.wait_for(|pending| *pending == 0) => result.is_ok(),
+            _ = self.sender.closed() => false,
+        }
+    }
+
+    /// Sync version of [`Self::flush`].
+    ///
+    /// **NOTE:** Requires to be called from a Tokio context, but not from an async context.
+    pub fn flush_sync(&self) -> bool {
+        Handle::current().block_on(self.flush())
+    }
+}
+
+/// The workers of a worker pool
+#[derive(Debug)]
+pub struct Workers<T> {
+    receiver: mpsc::Receiver<T>,
+    concurrency: usize,
+    pending: Arc<watch::Sender<usize>>,
+}
+
+impl<T> Workers<T> {
+    /// Process items, until all submitters are dropped.
+    pub async fn run<F, Fut>(self, f: F)
+    where
+        F: Fn(T) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let Self {
+            receiver,
+            concurrency,
+            pending,
+        } = self;
+
+        futures::stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|item| (item, receiver))
+        })
+        .for_each_concurrent(concurrency, |item| {
+            let processing = f(item);
+            let pending = &pending;
+            async move {
+                processing.await;
+                pending.send_modify(|pending| *pending -= 1);
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{cell::Cell, time::Duration};
+
+    #[test_log::test(tokio::test(start_paused = true))]
+    async fn bounded() {
+        let (submitter, workers) = worker_pool(3);
+
+        let producer = async move {
+            for i in 0..10 {
+                assert!(submitter.submit(i).await);
+            }
+        };
+
+        let running = Cell::new(0);
+        let max = Cell::new(0);
+        let done = Cell::new(0);
+
+        let consumer = workers.run(|_| async {
+            running.set(running.get() + 1);
+            max.set(max.get().max(running.get()));
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            running.set(running.get() - 1);
+            done.set(done.get() + 1);
+        });
+
+        tokio::join!(producer, consumer);
+
+        assert_eq!(done.get(), 10);
+        assert_eq!(max.get(), 3);
+    }
+
+    #[test_log::test(tokio::test(start_paused = true))]
+    async fn flush() {
+        let (submitter, workers) = worker_pool(3);
+
+        let done = Cell::new(0);
+        let done = &done;
+
+        let producer = async move {
+            for i in 0..5 {
+                assert!(submitter.submit(i).await);
+            }
+            assert!(submitter.flush().await);
+            done.get()
+        };
+
+        let consumer = workers.run(|_| async {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            done.set(done.get() + 1);
+        });
+
+        let (flushed, ()) = tokio::join!(producer, consumer);
+
+        assert_eq!(flushed, 5);
+    }
+}
@@ -0,0 +1,109 @@
+use crate::model::Proxy;
+use git2::ProxyOptions;
+use url::Url;
+
+impl Proxy {
+    /// Check if the host of the URL must be reached directly, bypassing the proxy.
+    pub fn bypasses(&self, url: &str) -> bool {
+        let Some(host) = host(url) else {
+            return false;
+        };
+
+        self.no_proxy.iter().any(|entry| {
+            let entry = entry.trim().trim_start_matches('.');
+            entry == "*"
+                || host.eq_ignore_ascii_case(entry)
+                || host
+                    .to_ascii_lowercase()
+                    .ends_with(&format!(".{}", entry.to_ascii_lowercase()))
+        })
+    }
+}
+
+/// The host of a URL, also supporting the scp-like syntax of git (`git@host:path`).
+fn host(url: &str) -> Option<String> {
+    if let Ok(url) = Url::parse(url)
+        && let Some(host) = url.host_str()
+    {
+        return Some(host.to_string());
+    }
+
+    let (authority, _) = url.split_once(':')?;
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    (!host.is_empty()).then(|| host.to_string())
+}
+
+/// Create an HTTP client builder, using the proxy (if any).
+///
+/// Without a proxy, the client falls back to the proxy configured through the environment.
+pub fn http_client(proxy: Option<&Proxy>) -> Result<reqwest::ClientBuilder, reqwest::Error> {
+    let builder = reqwest::Client::builder();
+
+    Ok(match proxy {
+        Some(proxy) => builder.proxy(
+            reqwest::Proxy::all(&proxy.url)?
+                .no_proxy(reqwest::NoProxy::from_string(&proxy.no_proxy.join(","))),
+        ),
+        None => builder,
+    })
+}
+
+/// Create the proxy options for reaching a git repository.
+///
+/// Without a proxy, or if the proxy is bypassed for the repository, git detects the proxy from
+/// its configuration and the environment.
+pub fn git_proxy<'a>(proxy: Option<&'a Proxy>, source: &str) -> ProxyOptions<'a> {
+    let mut options = ProxyOptions::new();
+
+    match proxy {
+        Some(proxy) if !proxy.bypasses(source) => {
+            options.url(&proxy.url);
+        }
+        _ => {
+            options.auto();
+        }
+    }
+
+    options
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bypass() {
+        let proxy = Proxy {
+            url: "http://proxy:3128".into(),
+            no_proxy: vec!["example.com".into(), ".internal".into()],
+        };
+
+        assert!(proxy.bypasses("https://example.com/repo.git"));
+        assert!(proxy.bypasses("https://git.Example.com/repo.git"));
+        assert!(proxy.bypasses("git@git.internal:repo.git"));
+        assert!(!proxy.bypasses("https://github.com/CVEProject/cvelistV5"));
+        assert!(!proxy.bypasses("https://notexample.com"));
+
+        let proxy = Proxy {
+            url: "http://proxy:3128".into(),
+            no_proxy: vec!["*".into()],
+        };
+
+        assert!(proxy.bypasses("https://github.com/CVEProject/cvelistV5"));
+    }
+
+    #[test]
+    fn socks() {
+        let proxy = Proxy {
+            url: "socks5h://proxy:1080".into(),
+            no_proxy: vec!["example.com".into()],
+        };
+
+        http_client(Some(&proxy))
+            .expect("must accept a SOCKS proxy")
+            .build()
+            .expect("must build the client");
+    }
+}
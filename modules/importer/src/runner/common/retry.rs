@@ -0,0 +1,236 @@
+use crate::{model::RetryPolicy, runner::common::Error};
+use sea_orm::DbErr;
+use std::{fmt::Display, io::ErrorKind, time::Duration};
+use trustify_module_ingestor::service;
+
+/// Classification of errors which might go away when trying again
+pub trait Retryable {
+    fn is_retryable(&self) -> bool;
+}
+
+impl Retryable for std::io::Error {
+    fn is_retryable(&self) -> bool {
+        matches!(
+            self.kind(),
+            ErrorKind::TimedOut
+                | ErrorKind::Interrupted
+                | ErrorKind::ConnectionRefused
+                | ErrorKind::ConnectionReset
+                | ErrorKind::ConnectionAborted
+                | ErrorKind::BrokenPipe
+                | ErrorKind::UnexpectedEof
+        )
+    }
+}
+
+impl Retryable for reqwest::Error {
+    fn is_retryable(&self) -> bool {
+        self.is_timeout()
+            || self.is_connect()
+            || self.status().is_some_and(|status| {
+                status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            })
+    }
+}
+
+impl Retryable for git2::Error {
+    fn is_retryable(&self) -> bool {
+        use git2::ErrorClass;
+
+        matches!(
+            self.class(),
+            ErrorClass::Net | ErrorClass::Http | ErrorClass::Ssh | ErrorClass::Ssl | ErrorClass::Os
+        )
+    }
+}
+
+impl Retryable for DbErr {
+    fn is_retryable(&self) -> bool {
+        matches!(self, DbErr::ConnectionAcquire(_) | DbErr::Conn(_))
+    }
+}
+
+impl Retryable for service::Error {
+    fn is_retryable(&self) -> bool {
+        match self {
+            Self::Db(err) => err.is_retryable(),
+            Self::Io(err) => err.is_retryable(),
+            Self::Storage(_) | Self::Unavailable => true,
+            _ => false,
+        }
+    }
+}
+
+impl Retryable for Error {
+    fn is_retryable(&self) -> bool {
+        match self {
+            Self::Git(err) => err.is_retryable(),
+            Self::Http(err) => err.is_retryable(),
+            Self::Io(err) => err.is_retryable(),
+            _ => false,
+        }
+    }
+}
+
+/// Retry operations according to a [`RetryPolicy`]
+///
+/// The default doesn't retry at all.
+#[derive(Clone, Debug)]
+pub struct Retry {
+    max_attempts: u32,
+    backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl Default for Retry {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+        }
+    }
+}
+
+impl Retry {
+    pub fn new(policy: &RetryPolicy) -> Self {
+        Self {
+            max_attempts: policy.max_attempts.max(1),
+            backoff: policy.backoff,
+            max_backoff: policy.max_backoff,
+        }
+    }
+
+    /// The delay after the provided (failed) attempt, starting with `1`.
+    fn delay(&self, attempt: u32) -> Duration {
+        self.backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff)
+    }
+
+    /// Check if the error of the provided attempt should be retried, returning the delay if so.
+    fn next<E: Retryable + Display>(&self, attempt: u32, err: &E) -> Option<Duration> {
+        if attempt >= self.max_attempts || !err.is_retryable() {
+            return None;
+        }
+
+        let delay = self.delay(attempt);
+        log::info!(
+            "Attempt {attempt} of {} failed, retrying in {}: {err}",
+            self.max_attempts,
+            humantime::Duration::from(delay)
+        );
+        Some(delay)
+    }
+
+    /// Run an operation, retrying it in case of a retryable error.
+    pub async fn run<T, E, F>(&self, mut f: F) -> Result<T, E>
+    where
+        F: AsyncFnMut() -> Result<T, E>,
+        E: Retryable + Display,
+    {
+        let mut attempt = 1;
+        loop {
+            match f().await {
+                Ok(result) => return Ok(result),
+                Err(err) => match self.next(attempt, &err) {
+                    Some(delay) => tokio::time::sleep(delay).await,
+                    None => return Err(err),
+                },
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Run a blocking operation, retrying it in case of a retryable error.
+    pub fn run_sync<T, E, F>(&self, mut f: F) -> Result<T, E>
+    where
+        F: FnMut() -> Result<T, E>,
+        E: Retryable + Display,
+    {
+        let mut attempt = 1;
+        loop {
+            match f() {
+                Ok(result) => return Ok(result),
+                Err(err) => match self.next(attempt, &err) {
+                    Some(delay) => std::thread::sleep(delay),
+                    None => return Err(err),
+                },
+            }
+            attempt += 1;
+        }
+    }
+}
+
+/// The number of retries for the fetcher of a walker.
+///
+/// An explicitly configured number of retries takes precedence over the retry policy.
+pub fn fetcher_retries(fetch_retries: Option<usize>, policy: Option<&RetryPolicy>) -> usize {
+    fetch_retries
+        .or_else(|| policy.map(|policy| policy.max_attempts.saturating_sub(1) as usize))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io;
+    use tokio::time::Instant;
+
+    #[test_log::test(tokio::test(start_paused = true))]
+    async fn backoff() {
+        let retry = Retry::new(&RetryPolicy {
+            max_attempts: 4,
+            backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(3),
+        });
+
+        let start = Instant::now();
+        let mut attempts = 0;
+        let result: Result<(), io::Error> = retry
+            .run(async || {
+                attempts += 1;
+                Err(io::Error::from(ErrorKind::TimedOut))
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts, 4);
+        // 1s + 2s + 3s (capped)
+        assert_eq!(start.elapsed(), Duration::from_secs(6));
+    }
+
+    #[test_log::test(tokio::test(start_paused = true))]
+    async fn not_retryable() {
+        let retry = Retry::new(&RetryPolicy::default());
+
+        let mut attempts = 0;
+        let result: Result<(), io::Error> = retry
+            .run(async || {
+                attempts += 1;
+                Err(io::Error::from(ErrorKind::NotFound))
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+
+    #[test_log::test(tokio::test(start_paused = true))]
+    async fn recover() {
+        let retry = Retry::new(&RetryPolicy::default());
+
+        let mut attempts = 0;
+        let result = retry
+            .run(async || {
+                attempts += 1;
+                match attempts {
+                    1 => Err(io::Error::from(ErrorKind::ConnectionReset)),
+                    _ => Ok(attempts),
+                }
+            })
+            .await;
+
+        assert_eq!(result.unwrap(), 2);
+    }
+}
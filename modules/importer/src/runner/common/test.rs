@@ -0,0 +1,33 @@
+#![cfg(test)]
+
+//! Utilities for testing walkers against a mock HTTP server
+
+use crate::runner::report::{Report, ReportBuilder};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{self, method},
+};
+
+/// Serve a response for `GET` requests of the path
+pub async fn mock_get(server: &MockServer, path: &str, response: ResponseTemplate) {
+    Mock::given(method("GET"))
+        .and(matchers::path(path))
+        .respond_with(response)
+        .mount(server)
+        .await;
+}
+
+/// Create a new report, to be shared with a walker
+pub fn new_report() -> Arc<Mutex<ReportBuilder>> {
+    Arc::new(Mutex::new(ReportBuilder::new()))
+}
+
+/// Build the report, once the walker sharing it is gone
+pub fn build_report(report: Arc<Mutex<ReportBuilder>>) -> Report {
+    Arc::try_unwrap(report)
+        .expect("walker is gone")
+        .into_inner()
+        .build()
+}
@@ -0,0 +1,103 @@
+use crate::{model::RateLimit, runner::report::ThrottleSummary};
+use std::{sync::Arc, time::Duration};
+use tokio::time::Instant;
+
+/// Pace the retrieval of documents, according to a [`RateLimit`]
+///
+/// Each document is accounted for after it was retrieved, delaying the next retrieval. Concurrent
+/// callers are served one after the other.
+#[derive(Clone, Debug)]
+pub struct Throttle {
+    requests_per_second: Option<u32>,
+    bytes_per_second: Option<u64>,
+    next: Arc<tokio::sync::Mutex<Option<Instant>>>,
+    summary: Arc<parking_lot::Mutex<ThrottleSummary>>,
+}
+
+impl Throttle {
+    pub fn new(limit: &RateLimit) -> Self {
+        Self {
+            requests_per_second: limit.requests_per_second.filter(|rps| *rps > 0),
+            bytes_per_second: limit
+                .bytes_per_second
+                .map(|bps| bps.0.as_u64())
+                .filter(|bps| *bps > 0),
+            next: Default::default(),
+            summary: Default::default(),
+        }
+    }
+
+    /// The summary of the throttling applied so far
+    pub fn summary(&self) -> Arc<parking_lot::Mutex<ThrottleSummary>> {
+        self.summary.clone()
+    }
+
+    /// The time a document of the provided size occupies the limits.
+    fn cost(&self, bytes: usize) -> Duration {
+        let requests = self
+            .requests_per_second
+            .map(|rps| Duration::from_secs_f64(1.0 / rps as f64))
+            .unwrap_or_default();
+        let bytes = self
+            .bytes_per_second
+            .map(|bps| Duration::from_secs_f64(bytes as f64 / bps as f64))
+            .unwrap_or_default();
+
+        requests.max(bytes)
+    }
+
+    /// Wait until the limits allow processing the next document, and account for it.
+    pub async fn acquire(&self, bytes: usize) {
+        let mut next = self.next.lock().await;
+
+        let now = Instant::now();
+        let start = match *next {
+            Some(next) if next > now => {
+                let delay = next - now;
+                log::debug!("Throttling for {}", humantime::Duration::from(delay));
+                self.summary.lock().record(delay);
+                tokio::time::sleep_until(next).await;
+                next
+            }
+            _ => now,
+        };
+
+        *next = Some(start + self.cost(bytes));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bytesize::ByteSize;
+    use trustify_common::model::BinaryByteSize;
+
+    #[test_log::test(tokio::test(start_paused = true))]
+    async fn pace() {
+        let throttle = Throttle::new(&RateLimit {
+            requests_per_second: Some(10),
+            bytes_per_second: Some(BinaryByteSize(ByteSize::kib(1))),
+        });
+
+        let start = Instant::now();
+
+        // limited by the number of requests
+        for _ in 0..3 {
+            throttle.acquire(10).await;
+        }
+        assert_eq!(start.elapsed(), Duration::from_millis(200));
+
+        // limited by the bytes, the next one must wait for 2s
+        throttle.acquire(2048).await;
+        throttle.acquire(10).await;
+        assert_eq!(start.elapsed(), Duration::from_millis(2300));
+
+        assert_eq!(
+            *throttle.summary().lock(),
+            ThrottleSummary {
+                delays: 4,
+                duration: Duration::from_millis(2300),
+            }
+        );
+    }
+}
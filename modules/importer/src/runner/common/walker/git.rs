@@ -1,8 +1,13 @@
-use crate::runner::common::walker::WorkingDirectory;
+use crate::{
+    model::Proxy,
+    runner::common::{
+        filter::PathFilter, proxy::git_proxy, retry::Retry, walker::WorkingDirectory,
+    },
+};
 use anyhow::anyhow;
 use git2::{
-    Cred, ErrorClass, ErrorCode, FetchOptions, RemoteCallbacks, Repository, ResetType,
-    build::RepoBuilder,
+    Cred, CredentialType, Delta, ErrorClass, ErrorCode, FetchOptions, Oid, RemoteCallbacks,
+    Repository, ResetType, build::RepoBuilder,
 };
 use std::{
     borrow::Cow,
@@ -28,6 +33,21 @@ pub trait Handler: Send + 'static {
     type Error: Display + Debug;
 
     fn process(&self, path: &Path, relative_path: &Path) -> Result<(), HandlerError<Self::Error>>;
+
+    /// Handle a file which was deleted since the last run.
+    #[allow(unused)]
+    fn deleted(&self, relative_path: &Path) -> Result<(), HandlerError<Self::Error>> {
+        Ok(())
+    }
+
+    /// Record a checkpoint, from which an interrupted walk can be resumed.
+    ///
+    /// All files walked before the checkpoint have been passed to [`Self::process`].
+    #[allow(unused)]
+    fn checkpoint(&self, continuation: &Continuation) -> Result<(), HandlerError<Self::Error>> {
+        Ok(())
+    }
+
     fn is_canceled(&self) -> bool;
 }
 
@@ -42,6 +62,66 @@ impl Handler for () {
     }
 }
 
+/// Credentials for accessing a private repository
+#[derive(Clone, Default)]
+pub struct Credentials {
+    /// The user name, falling back to the one of the URL, or `git`
+    pub username: Option<String>,
+    /// A token (or password), for HTTPS
+    pub token: Option<String>,
+    /// The path to a private SSH key
+    pub ssh_key: Option<PathBuf>,
+    /// The passphrase of the private SSH key
+    pub ssh_passphrase: Option<String>,
+}
+
+impl Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credentials")
+            .field("username", &self.username)
+            .field("token", &self.token.as_ref().map(|_| "***"))
+            .field("ssh_key", &self.ssh_key)
+            .field(
+                "ssh_passphrase",
+                &self.ssh_passphrase.as_ref().map(|_| "***"),
+            )
+            .finish()
+    }
+}
+
+impl Credentials {
+    /// Create credentials for an authentication request of git.
+    fn create(
+        &self,
+        username_from_url: Option<&str>,
+        allowed_types: CredentialType,
+    ) -> Result<Cred, git2::Error> {
+        let username = self
+            .username
+            .as_deref()
+            .or(username_from_url)
+            .unwrap_or("git");
+
+        if allowed_types.contains(CredentialType::SSH_KEY)
+            && let Some(key) = &self.ssh_key
+        {
+            return Cred::ssh_key(username, None, key, self.ssh_passphrase.as_deref());
+        }
+        if allowed_types.contains(CredentialType::USER_PASS_PLAINTEXT)
+            && let Some(token) = &self.token
+        {
+            return Cred::userpass_plaintext(username, token);
+        }
+        if allowed_types.contains(CredentialType::USERNAME) {
+            return Cred::username(username);
+        }
+
+        Err(git2::Error::from_str(
+            "no credentials configured for the requested authentication",
+        ))
+    }
+}
+
 pub struct GitWalker<H, T, P>
 where
     T: WorkingDirectory + Send + 'static,
@@ -54,6 +134,12 @@ where
     /// The branch to check out
     pub branch: Option<String>,
 
+    /// A tag to check out, taking precedence over the branch
+    pub tag: Option<String>,
+
+    /// A commit to check out, taking precedence over the tag and the branch
+    pub commit: Option<String>,
+
     /// A path inside the cloned repository to start searching for files
     pub path: Option<String>,
 
@@ -69,7 +155,21 @@ where
     pub progress: P,
 
     /// Fetch depth, <=0 means get everything
+    ///
+    /// This applies to the initial clone, unless there is a continuation, and to fetching updates.
     pub depth: i32,
+
+    /// Retrying of cloning and fetching the repository
+    pub retry: Retry,
+
+    /// The proxy for reaching the repository
+    pub proxy: Option<Proxy>,
+
+    /// Filter for the paths of files, relative to the base dir
+    pub filter: PathFilter,
+
+    /// Credentials for accessing the repository, falling back to the SSH keys of the user
+    pub credentials: Option<Credentials>,
 }
 
 impl<H> GitWalker<H, (), ()>
@@ -83,12 +183,18 @@ where
         Self {
             source: source.into(),
             branch: None,
+            tag: None,
+            commit: None,
             path: None,
             continuation: Default::default(),
             working_dir: (),
             handler,
             progress: (),
-            depth: 1, // shallow clone, by default
+            depth: DEFAULT_DEPTH, // shallow clone, by default
+            retry: Default::default(),
+            proxy: None,
+            filter: Default::default(),
+            credentials: None,
         }
     }
 }
@@ -103,12 +209,18 @@ where
         GitWalker {
             source: self.source,
             branch: self.branch,
+            tag: self.tag,
+            commit: self.commit,
             path: self.path,
             continuation: self.continuation,
             working_dir: self.working_dir,
             handler,
             progress: self.progress,
             depth: self.depth,
+            retry: self.retry,
+            proxy: self.proxy,
+            filter: self.filter,
+            credentials: self.credentials,
         }
     }
 
@@ -116,12 +228,18 @@ where
         GitWalker {
             source: self.source,
             branch: self.branch,
+            tag: self.tag,
+            commit: self.commit,
             path: self.path,
             continuation: self.continuation,
             working_dir: self.working_dir,
             handler: self.handler,
             progress,
             depth: self.depth,
+            retry: self.retry,
+            proxy: self.proxy,
+            filter: self.filter,
+            credentials: self.credentials,
         }
     }
 
@@ -140,30 +258,74 @@ where
         GitWalker {
             source: self.source,
             branch: self.branch,
+            tag: self.tag,
+            commit: self.commit,
             path: self.path,
             continuation: self.continuation,
             working_dir,
             handler: self.handler,
             progress: self.progress,
             depth: self.depth,
+            retry: self.retry,
+            proxy: self.proxy,
+            filter: self.filter,
+            credentials: self.credentials,
         }
     }
 
+    /// Track the head of a branch, instead of the default branch.
     pub fn branch(mut self, branch: Option<impl Into<String>>) -> Self {
         self.branch = branch.map(|s| s.into());
         self
     }
 
+    /// Pin to a tag.
+    pub fn tag(mut self, tag: Option<impl Into<String>>) -> Self {
+        self.tag = tag.map(|s| s.into());
+        self
+    }
+
+    /// Pin to a commit, by its full ID.
+    pub fn commit(mut self, commit: Option<impl Into<String>>) -> Self {
+        self.commit = commit.map(|s| s.into());
+        self
+    }
+
     pub fn path(mut self, path: Option<impl Into<String>>) -> Self {
         self.path = path.map(|s| s.into());
         self
     }
 
+    /// Set the fetch depth, <=0 means get everything.
     pub fn depth(mut self, depth: i32) -> Self {
         self.depth = depth;
         self
     }
 
+    /// Set the retry for cloning and fetching the repository.
+    pub fn retry(mut self, retry: Retry) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Set the proxy for reaching the repository.
+    pub fn proxy(mut self, proxy: Option<Proxy>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    /// Set the filter for the paths of files.
+    pub fn filter(mut self, filter: PathFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Set the credentials for accessing the repository.
+    pub fn credentials(mut self, credentials: Option<Credentials>) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
     /// Set a continuation token from a previous run.
     pub fn continuation(mut self, continuation: Continuation) -> Self {
         self.continuation = continuation;
@@ -189,12 +351,17 @@ where
         let path = working_dir.as_ref();
 
         // clone or open repository
-        let repo = self.clone_or_update_repo(path)?;
+        let repo = self.retry.run_sync(|| self.clone_or_update_repo(path))?;
         log::info!("Repository cloned or updated");
 
-        // discover files between "then" and now
+        // resume an interrupted walk, or discover files between "then" and now
+        let resume = self.resume(&repo)?;
         let changes = self.find_changes(&repo)?;
 
+        let head = repo.head()?;
+        let commit = head.peel_to_commit()?.id();
+        log::info!("Most recent commit: {commit}");
+
         // discover and process files
 
         let mut path = Cow::Borrowed(path);
@@ -212,11 +379,16 @@ where
             path = new_path.into();
         }
 
-        self.walk(&path, &changes)?;
+        if let Some(changes) = &changes {
+            self.deleted(&changes.deleted)?;
+        }
 
-        let head = repo.head()?;
-        let commit = head.peel_to_commit()?.id();
-        log::info!("Most recent commit: {commit}");
+        self.walk(
+            &path,
+            changes.as_ref().map(|changes| &changes.modified),
+            &commit.to_string(),
+            resume,
+        )?;
 
         // only drop when we are done, as this might delete the working directory
 
@@ -224,12 +396,48 @@ where
 
         // return result
 
-        Ok(Continuation(Some(commit.to_string())))
+        Ok(Continuation::new(commit.to_string()))
+    }
+
+    /// Reset the repository to the commit of an interrupted walk, if there is a checkpoint.
+    ///
+    /// Returns the last file processed before the interruption. If the commit is missing, the
+    /// checkpoint gets ignored.
+    fn resume(&self, repo: &Repository) -> Result<Option<&Path>, Error> {
+        let Some(checkpoint) = &self.continuation.checkpoint else {
+            return Ok(None);
+        };
+
+        let target = &checkpoint.target;
+        let commit = match Oid::from_str(target).and_then(|id| repo.find_commit(id)) {
+            Ok(commit) => commit,
+            Err(err) if err.code() == ErrorCode::NotFound => {
+                log::info!("Commit {target} not found, unable to resume from checkpoint");
+                return Ok(None);
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        log::info!(
+            "Resuming walk of {target} after: {}",
+            checkpoint.position.display()
+        );
+        repo.reset(commit.as_object(), ResetType::Hard, None)?;
+
+        Ok(Some(&checkpoint.position))
     }
 
     fn clone_or_update_repo(&self, path: &Path) -> Result<Repository, Error> {
         match self.clone_repo(path) {
-            Ok(repo) => Ok(repo),
+            Ok(repo) => {
+                // a pinned tag or commit might not be part of what we cloned
+                if self.tag.is_some() || self.commit.is_some() {
+                    self.fetch(&repo)?;
+                    self.reset(&repo)?;
+                }
+
+                Ok(repo)
+            }
             Err(err) if err.code() == ErrorCode::Exists && err.class() == ErrorClass::Invalid => {
                 log::info!("Already exists, opening ...");
                 let repo = info_span!("open repository").in_scope(|| Repository::open(path))?;
@@ -237,38 +445,28 @@ where
                 let repo = info_span!("fetching updates").in_scope(move || {
                     self.progress
                         .message_sync(format!("Fetching updates: {}", self.source));
-                    {
-                        let mut remote = repo.find_remote("origin")?;
-                        let mut fo = self.create_fetch_options();
 
-                        match remote.fetch(&[] as &[&str], Some(&mut fo), None) {
-                            Ok(()) => {}
-                            Err(err)
-                                if err.code() == ErrorCode::NotFound
-                                    && err.class() == ErrorClass::Odb =>
-                            {
-                                // delete repo
+                    match self.fetch(&repo) {
+                        Ok(()) => {}
+                        Err(err)
+                            if err.code() == ErrorCode::NotFound
+                                && err.class() == ErrorClass::Odb =>
+                        {
+                            // delete repo
 
-                                remove_dir_all(path)?;
+                            drop(repo);
+                            remove_dir_all(path)?;
 
-                                // clone repo
+                            // clone repo
 
-                                return Ok(self.clone_repo(path)?);
-                            }
-                            err => err?,
+                            return self.clone_or_update_repo(path);
                         }
-                        remote.disconnect()?;
+                        err => err?,
                     }
 
                     log::info!("Fetched, resetting");
 
-                    {
-                        let head = repo.find_reference("FETCH_HEAD")?;
-                        let head = head.peel_to_commit()?;
-
-                        // reset to the most recent commit
-                        repo.reset(head.as_object(), ResetType::Hard, None)?;
-                    }
+                    self.reset(&repo)?;
 
                     Ok::<_, Error>(repo)
                 })?;
@@ -286,6 +484,61 @@ where
         }
     }
 
+    /// Fetch the revision we track.
+    fn fetch(&self, repo: &Repository) -> Result<(), git2::Error> {
+        if let Some(commit) = &self.commit
+            && repo.find_commit(Oid::from_str(commit)?).is_ok()
+        {
+            log::debug!("Pinned commit {commit} is already present");
+            return Ok(());
+        }
+
+        let mut remote = repo.find_remote("origin")?;
+        let mut fo = self.create_fetch_options();
+        if let Some(depth) = self.fetch_depth(repo) {
+            fo.depth(depth);
+        }
+
+        // only fetch what we track, FETCH_HEAD must point to it, unless it's a tag
+        let refspecs: Vec<String> = match (&self.commit, &self.tag, &self.branch) {
+            (Some(commit), _, _) => vec![commit.clone()],
+            (None, Some(tag), _) => vec![format!("+refs/tags/{tag}:refs/tags/{tag}")],
+            (None, None, branch) => branch.iter().cloned().collect(),
+        };
+
+        remote.fetch(&refspecs, Some(&mut fo), None)?;
+        remote.disconnect()?;
+
+        Ok(())
+    }
+
+    /// Reset the working tree to the revision we track.
+    fn reset(&self, repo: &Repository) -> Result<(), git2::Error> {
+        let commit = match (&self.commit, &self.tag) {
+            (Some(commit), _) => repo.find_commit(Oid::from_str(commit)?)?,
+            (None, Some(tag)) => repo
+                .find_reference(&format!("refs/tags/{tag}"))?
+                .peel_to_commit()?,
+            (None, None) => repo.find_reference("FETCH_HEAD")?.peel_to_commit()?,
+        };
+
+        log::info!("Resetting to: {}", commit.id());
+        repo.reset(commit.as_object(), ResetType::Hard, None)
+    }
+
+    /// The depth for fetching updates.
+    ///
+    /// When fetching everything, a shallow repository gets "unshallowed".
+    fn fetch_depth(&self, repo: &Repository) -> Option<i32> {
+        if self.depth > 0 {
+            Some(self.depth)
+        } else if repo.is_shallow() {
+            Some(i32::MAX)
+        } else {
+            None
+        }
+    }
+
     #[instrument(skip(self), err)]
     fn clone_repo(&self, path: &Path) -> Result<Repository, git2::Error> {
         self.progress
@@ -297,26 +550,26 @@ where
             builder.branch(branch);
         }
 
+        // with a continuation, we need the history to find the changes since then
         let mut fo = self.create_fetch_options();
-        if self.continuation.0.is_none() {
+        if self.continuation.commit.is_none() {
             fo.depth(self.depth);
         }
 
         builder.fetch_options(fo).clone(&self.source, path)
     }
 
-    fn find_changes(&self, repo: &Repository) -> Result<Option<HashSet<PathBuf>>, Error> {
-        let result = match &self.continuation.0 {
+    fn find_changes(&self, repo: &Repository) -> Result<Option<Changes>, Error> {
+        let result = match &self.continuation.commit {
             Some(commit) => {
                 log::info!("Continuing from: {commit}");
 
                 info_span!("continue from", commit).in_scope(|| {
-                    let start = match repo.find_commit(repo.revparse_single(commit)?.id()) {
+                    // the commit might be missing, e.g. beyond the history of a shallow clone
+                    let start = match Oid::from_str(commit).and_then(|id| repo.find_commit(id)) {
                         Ok(start) => start,
-                        Err(err)
-                            if err.code() == ErrorCode::NotFound
-                                && err.class() == ErrorClass::Odb =>
-                        {
+                        Err(err) if err.code() == ErrorCode::NotFound => {
+                            log::info!("Commit {commit} not found, unable to detect changes");
                             return Ok::<_, Error>(None);
                         }
                         err => err?,
@@ -328,7 +581,10 @@ where
 
                     let diff = repo.diff_tree_to_tree(Some(&start), Some(&end), None)?;
 
-                    let mut files = HashSet::with_capacity(diff.deltas().len());
+                    let mut changes = Changes {
+                        modified: HashSet::with_capacity(diff.deltas().len()),
+                        deleted: vec![],
+                    };
 
                     for delta in diff.deltas() {
                         if let Some(path) = delta.new_file().path() {
@@ -343,13 +599,18 @@ where
                             };
 
                             if let Some(path) = path {
-                                log::debug!("Record {} as changed file", path.display());
-                                files.insert(path);
+                                if delta.status() == Delta::Deleted {
+                                    log::debug!("Record {} as deleted file", path.display());
+                                    changes.deleted.push(path);
+                                } else {
+                                    log::debug!("Record {} as changed file", path.display());
+                                    changes.modified.insert(path);
+                                }
                             }
                         }
                     }
 
-                    Ok(Some(files))
+                    Ok(Some(changes))
                 })?
             }
             _ => None,
@@ -357,7 +618,11 @@ where
 
         match &result {
             Some(result) => {
-                log::info!("Detected {} changed files", result.len());
+                log::info!(
+                    "Detected {} changed and {} deleted files",
+                    result.modified.len(),
+                    result.deleted.len()
+                );
             }
             None => {
                 log::info!("Ingesting all files");
@@ -388,8 +653,16 @@ where
         });
         cb.sideband_progress(|_| !self.handler.is_canceled());
 
-        let home = env::var("HOME").ok();
-        if let Some(home) = home {
+        if let Some(credentials) = &self.credentials {
+            // git asks again if the credentials got rejected, so only provide them once
+            let mut provided = false;
+            cb.credentials(move |_url, username_from_url, allowed_types| {
+                if std::mem::replace(&mut provided, true) {
+                    return Err(git2::Error::from_str("credentials got rejected"));
+                }
+                credentials.create(username_from_url, allowed_types)
+            });
+        } else if let Ok(home) = env::var("HOME") {
             for key in &["id_rsa", "id_ed25519"] {
                 let key = Path::new(&home).join(".ssh").join(key);
                 if key.exists() {
@@ -403,12 +676,34 @@ where
 
         let mut fo = FetchOptions::new();
         fo.remote_callbacks(cb);
-        fo.depth(i32::MAX);
+        fo.proxy_options(git_proxy(self.proxy.as_ref(), &self.source));
         fo
     }
 
+    /// Report files which were deleted since the last run.
+    fn deleted(&self, deleted: &[PathBuf]) -> Result<(), Error> {
+        for path in deleted {
+            if !self.filter.matches(&path.to_string_lossy()) {
+                continue;
+            }
+
+            self.handler.deleted(path).map_err(|err| match err {
+                HandlerError::Canceled => Error::Canceled,
+                HandlerError::Processing(err) => Error::Processing(anyhow!("{err}")),
+            })?;
+        }
+
+        Ok(())
+    }
+
     #[instrument(skip(self, changes), err)]
-    fn walk(&self, base: &Path, changes: &Option<HashSet<PathBuf>>) -> Result<(), Error> {
+    fn walk(
+        &self,
+        base: &Path,
+        changes: Option<&HashSet<PathBuf>>,
+        target: &str,
+        resume: Option<&Path>,
+    ) -> Result<(), Error> {
         let mut collected = vec![];
 
         for entry in WalkDir::new(base)
@@ -435,13 +730,28 @@ where
                 continue;
             }
 
+            if !self.filter.matches(&path.to_string_lossy()) {
+                log::trace!("Skipping {}, as it is filtered out", path.display());
+                continue;
+            }
+
+            if let Some(resume) = resume
+                && path <= resume
+            {
+                log::trace!("Skipping {}, as it was processed before", path.display());
+                continue;
+            }
+
             let path = path.to_path_buf();
             collected.push((entry, path));
         }
 
+        // walk in a stable order, so that a checkpoint marks all files before it
+        collected.sort_by(|(_, a), (_, b)| a.cmp(b));
+
         let mut progress = self.progress.start(collected.len());
 
-        for (entry, path) in collected {
+        for (n, (entry, path)) in collected.into_iter().enumerate() {
             self.handler
                 .process(entry.path(), &path)
                 .map_err(|err| match err {
@@ -450,6 +760,22 @@ where
                 })?;
 
             progress.tick_sync();
+
+            if (n + 1) % CHECKPOINT_INTERVAL == 0 {
+                let continuation = Continuation {
+                    commit: self.continuation.commit.clone(),
+                    checkpoint: Some(Checkpoint {
+                        target: target.to_string(),
+                        position: path,
+                    }),
+                };
+                self.handler
+                    .checkpoint(&continuation)
+                    .map_err(|err| match err {
+                        HandlerError::Canceled => Error::Canceled,
+                        HandlerError::Processing(err) => Error::Processing(anyhow!("{err}")),
+                    })?;
+            }
         }
 
         progress.finish_sync();
@@ -458,8 +784,88 @@ where
     }
 }
 
-#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
-pub struct Continuation(Option<String>);
+/// The default fetch depth, a shallow clone
+pub const DEFAULT_DEPTH: i32 = 1;
+
+/// The number of files processed between two checkpoints
+const CHECKPOINT_INTERVAL: usize = 100;
+
+/// The state of the walker, to continue with by the next run
+///
+/// Without a checkpoint, this serializes to just the commit, which is compatible with
+/// continuations stored before checkpoints were introduced.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(from = "ContinuationState", into = "ContinuationState")]
+pub struct Continuation {
+    /// The last commit which was walked completely
+    commit: Option<String>,
+    /// The position of an interrupted walk
+    checkpoint: Option<Checkpoint>,
+}
+
+impl Continuation {
+    /// A continuation after walking the commit completely
+    pub fn new(commit: impl Into<String>) -> Self {
+        Self {
+            commit: Some(commit.into()),
+            checkpoint: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Checkpoint {
+    /// The commit being walked
+    target: String,
+    /// The last file processed, relative to the base dir
+    position: PathBuf,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+enum ContinuationState {
+    Commit(Option<String>),
+    Checkpoint {
+        commit: Option<String>,
+        checkpoint: Checkpoint,
+    },
+}
+
+impl From<ContinuationState> for Continuation {
+    fn from(value: ContinuationState) -> Self {
+        match value {
+            ContinuationState::Commit(commit) => Self {
+                commit,
+                checkpoint: None,
+            },
+            ContinuationState::Checkpoint { commit, checkpoint } => Self {
+                commit,
+                checkpoint: Some(checkpoint),
+            },
+        }
+    }
+}
+
+impl From<Continuation> for ContinuationState {
+    fn from(value: Continuation) -> Self {
+        match value.checkpoint {
+            None => Self::Commit(value.commit),
+            Some(checkpoint) => Self::Checkpoint {
+                commit: value.commit,
+                checkpoint,
+            },
+        }
+    }
+}
+
+/// Files changed since the last run
+struct Changes {
+    /// Files which were added or modified
+    modified: HashSet<PathBuf>,
+    /// Files which were deleted
+    deleted: Vec<PathBuf>,
+}
 
 fn is_hidden(entry: &DirEntry) -> bool {
     entry
@@ -474,9 +880,18 @@ use crate::runner::progress::{Progress, ProgressInstance};
 
 #[cfg(test)]
 mod test {
-    use super::{Continuation, GitWalker};
-    use git2::{Repository, ResetType};
-    use std::path::{Path, PathBuf};
+    use super::{
+        CHECKPOINT_INTERVAL, Checkpoint, Continuation, Credentials, GitWalker, Handler,
+        HandlerError,
+    };
+    use crate::runner::common::filter::PathFilter;
+    use git2::{CredentialType, IndexAddOption, Repository, ResetType, Signature};
+    use parking_lot::Mutex;
+    use std::{
+        convert::Infallible,
+        path::{Path, PathBuf},
+        sync::Arc,
+    };
 
     /// reset a git repository to the spec and return the commit as continuation
     pub(crate) fn git_reset(path: &Path, spec: &str) -> anyhow::Result<Continuation> {
@@ -487,7 +902,318 @@ mod test {
 
         let commit = r#ref.peel_to_commit()?.id().to_string();
 
-        Ok(Continuation(Some(commit)))
+        Ok(Continuation::new(commit))
+    }
+
+    /// commit all files of the repository's working tree
+    fn commit(repo: &Repository) -> anyhow::Result<()> {
+        let mut index = repo.index()?;
+        index.add_all(["*"], IndexAddOption::DEFAULT, None)?;
+        index.update_all(["*"], None)?;
+        index.write()?;
+
+        let tree = repo.find_tree(index.write_tree()?)?;
+        let signature = Signature::now("Test", "test@example.com")?;
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "update",
+            &tree,
+            &parent.iter().collect::<Vec<_>>(),
+        )?;
+
+        Ok(())
+    }
+
+    /// records the files processed and deleted, and the checkpoints
+    #[derive(Clone, Default)]
+    struct Recorder {
+        processed: Arc<Mutex<Vec<PathBuf>>>,
+        deleted: Arc<Mutex<Vec<PathBuf>>>,
+        checkpoints: Arc<Mutex<Vec<Continuation>>>,
+        /// cancel the walk after processing this number of files
+        cancel_after: Option<usize>,
+    }
+
+    impl Handler for Recorder {
+        type Error = Infallible;
+
+        fn process(&self, _: &Path, relative_path: &Path) -> Result<(), HandlerError<Infallible>> {
+            let mut processed = self.processed.lock();
+            if self.cancel_after.is_some_and(|n| processed.len() >= n) {
+                return Err(HandlerError::Canceled);
+            }
+            processed.push(relative_path.to_path_buf());
+            Ok(())
+        }
+
+        fn checkpoint(&self, continuation: &Continuation) -> Result<(), HandlerError<Infallible>> {
+            self.checkpoints.lock().push(continuation.clone());
+            Ok(())
+        }
+
+        fn deleted(&self, relative_path: &Path) -> Result<(), HandlerError<Infallible>> {
+            self.deleted.lock().push(relative_path.to_path_buf());
+            Ok(())
+        }
+
+        fn is_canceled(&self) -> bool {
+            false
+        }
+    }
+
+    /// an update must only process the changes since the continuation
+    #[test_log::test(tokio::test)]
+    async fn test_walker_update() -> Result<(), anyhow::Error> {
+        let tmp = tempfile::tempdir()?;
+        let source = tmp.path().join("source");
+        let path = tmp.path().join("clone");
+
+        let repo = Repository::init(&source)?;
+        std::fs::write(source.join("a.json"), "{}")?;
+        std::fs::write(source.join("b.json"), "{}")?;
+        commit(&repo)?;
+
+        let walker = |continuation, recorder| {
+            GitWalker::new(source.to_string_lossy(), recorder)
+                .depth(0)
+                .continuation(continuation)
+                .working_dir(path.clone())
+        };
+
+        // initial run
+
+        let recorder = Recorder::default();
+        let cont = walker(Continuation::default(), recorder.clone())
+            .run()
+            .await?;
+        recorder.processed.lock().sort();
+        assert_eq!(
+            *recorder.processed.lock(),
+            vec![PathBuf::from("a.json"), PathBuf::from("b.json")]
+        );
+
+        // change upstream
+
+        std::fs::write(source.join("b.json"), "[]")?;
+        std::fs::write(source.join("c.json"), "{}")?;
+        std::fs::remove_file(source.join("a.json"))?;
+        commit(&repo)?;
+
+        let recorder = Recorder::default();
+        walker(cont, recorder.clone()).run().await?;
+        recorder.processed.lock().sort();
+        assert_eq!(
+            *recorder.processed.lock(),
+            vec![PathBuf::from("b.json"), PathBuf::from("c.json")]
+        );
+        assert_eq!(*recorder.deleted.lock(), vec![PathBuf::from("a.json")]);
+
+        // an unknown continuation must process everything
+
+        let recorder = Recorder::default();
+        let cont = Continuation::new("0123456789abcdef0123456789abcdef01234567");
+        walker(cont, recorder.clone()).run().await?;
+        recorder.processed.lock().sort();
+        assert_eq!(
+            *recorder.processed.lock(),
+            vec![PathBuf::from("b.json"), PathBuf::from("c.json")]
+        );
+        assert!(recorder.deleted.lock().is_empty());
+
+        Ok(())
+    }
+
+    /// only files passing the filter must be processed
+    #[test_log::test(tokio::test)]
+    async fn test_walker_filter() -> Result<(), anyhow::Error> {
+        let tmp = tempfile::tempdir()?;
+        let source = tmp.path().join("source");
+
+        let repo = Repository::init(&source)?;
+        for dir in ["a", "b", "a/vendor"] {
+            std::fs::create_dir_all(source.join(dir))?;
+            std::fs::write(source.join(dir).join("x.json"), "{}")?;
+        }
+        commit(&repo)?;
+
+        let recorder = Recorder::default();
+        GitWalker::new(source.to_string_lossy(), recorder.clone())
+            .filter(PathFilter::new(&["^a/".into()], &["/vendor/".into()])?)
+            .depth(0)
+            .working_dir(tmp.path().join("clone"))
+            .run()
+            .await?;
+
+        assert_eq!(*recorder.processed.lock(), vec![PathBuf::from("a/x.json")]);
+
+        Ok(())
+    }
+
+    /// an interrupted walk must resume after its last checkpoint, at the same commit
+    #[test_log::test(tokio::test)]
+    async fn test_walker_resume() -> Result<(), anyhow::Error> {
+        let tmp = tempfile::tempdir()?;
+        let source = tmp.path().join("source");
+        let path = tmp.path().join("clone");
+
+        let files = CHECKPOINT_INTERVAL + 20;
+
+        let repo = Repository::init(&source)?;
+        for i in 0..files {
+            std::fs::write(source.join(format!("{i:04}.json")), "{}")?;
+        }
+        commit(&repo)?;
+        let target = repo.head()?.peel_to_commit()?.id().to_string();
+
+        let walker = |continuation, recorder| {
+            GitWalker::new(source.to_string_lossy(), recorder)
+                .depth(0)
+                .continuation(continuation)
+                .working_dir(path.clone())
+        };
+
+        // interrupted run
+
+        let recorder = Recorder {
+            cancel_after: Some(CHECKPOINT_INTERVAL + 10),
+            ..Default::default()
+        };
+        let result = walker(Continuation::default(), recorder.clone())
+            .run()
+            .await;
+        assert!(result.is_err());
+
+        let checkpoint = recorder
+            .checkpoints
+            .lock()
+            .last()
+            .cloned()
+            .expect("must have one");
+        assert_eq!(
+            checkpoint.checkpoint,
+            Some(Checkpoint {
+                target: target.clone(),
+                position: PathBuf::from(format!("{:04}.json", CHECKPOINT_INTERVAL - 1)),
+            })
+        );
+
+        // the checkpoint must survive a round trip through its stored form
+
+        let checkpoint: Continuation = serde_json::from_value(serde_json::to_value(&checkpoint)?)?;
+
+        // change upstream in the meantime
+
+        std::fs::write(source.join("new.json"), "{}")?;
+        commit(&repo)?;
+
+        // resume at the interrupted commit
+
+        let recorder = Recorder::default();
+        let cont = walker(checkpoint, recorder.clone()).run().await?;
+        assert_eq!(cont, Continuation::new(target));
+        assert_eq!(recorder.processed.lock().len(), files - CHECKPOINT_INTERVAL);
+        assert_eq!(
+            recorder.processed.lock().first(),
+            Some(&PathBuf::from(format!("{CHECKPOINT_INTERVAL:04}.json")))
+        );
+
+        // the next run picks up the upstream change
+
+        let recorder = Recorder::default();
+        walker(cont, recorder.clone()).run().await?;
+        assert_eq!(*recorder.processed.lock(), vec![PathBuf::from("new.json")]);
+
+        Ok(())
+    }
+
+    /// a continuation without a checkpoint must keep its original form
+    #[test]
+    fn continuation_format() -> Result<(), anyhow::Error> {
+        let cont = Continuation::new("abc");
+        assert_eq!(serde_json::to_value(&cont)?, serde_json::json!("abc"));
+        assert_eq!(
+            serde_json::from_value::<Continuation>(serde_json::json!("abc"))?,
+            cont
+        );
+        assert_eq!(
+            serde_json::from_value::<Continuation>(serde_json::Value::Null)?,
+            Continuation::default()
+        );
+
+        Ok(())
+    }
+
+    /// credentials must only be provided for the authentication methods they support
+    #[test]
+    fn credentials() {
+        let token = Credentials {
+            token: Some("token".into()),
+            ..Default::default()
+        };
+        assert!(
+            token
+                .create(None, CredentialType::USER_PASS_PLAINTEXT)
+                .is_ok()
+        );
+        assert!(token.create(None, CredentialType::SSH_KEY).is_err());
+
+        let ssh = Credentials {
+            ssh_key: Some("/path/to/id_ed25519".into()),
+            ..Default::default()
+        };
+        assert!(ssh.create(Some("git"), CredentialType::SSH_KEY).is_ok());
+        assert!(
+            ssh.create(None, CredentialType::USER_PASS_PLAINTEXT)
+                .is_err()
+        );
+    }
+
+    /// pinning to a tag or commit must check out that revision
+    #[test_log::test(tokio::test)]
+    async fn test_walker_pinned() -> Result<(), anyhow::Error> {
+        let tmp = tempfile::tempdir()?;
+        let source = tmp.path().join("source");
+
+        let repo = Repository::init(&source)?;
+        std::fs::write(source.join("a.json"), "{}")?;
+        commit(&repo)?;
+        let first = repo.head()?.peel_to_commit()?;
+        repo.tag_lightweight("v1", first.as_object(), false)?;
+
+        std::fs::write(source.join("b.json"), "{}")?;
+        commit(&repo)?;
+
+        let run = async |walker: GitWalker<Recorder, (), ()>, clone: &str| {
+            let recorder = walker.handler.clone();
+            walker
+                .depth(0)
+                .working_dir(tmp.path().join(clone))
+                .run()
+                .await?;
+            Ok::<_, anyhow::Error>(recorder.processed.lock().clone())
+        };
+
+        // the head
+
+        let walker = GitWalker::new(source.to_string_lossy(), Recorder::default());
+        assert_eq!(run(walker, "head").await?.len(), 2);
+
+        // the tag
+
+        let walker = GitWalker::new(source.to_string_lossy(), Recorder::default()).tag(Some("v1"));
+        assert_eq!(run(walker, "tag").await?, vec![PathBuf::from("a.json")]);
+
+        // the commit
+
+        let walker = GitWalker::new(source.to_string_lossy(), Recorder::default())
+            .commit(Some(first.id().to_string()));
+        assert_eq!(run(walker, "commit").await?, vec![PathBuf::from("a.json")]);
+
+        Ok(())
     }
 
     #[test_log::test(tokio::test)]
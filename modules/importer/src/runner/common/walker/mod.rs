@@ -24,6 +24,18 @@ pub trait Callbacks<T>: Send + 'static {
         Ok(())
     }
 
+    /// Handle a file which was deleted upstream.
+    #[allow(unused)]
+    fn deleted(&self, path: &Path) -> Result<(), CallbackError> {
+        Ok(())
+    }
+
+    /// Persist a checkpoint of the walk, so that an interrupted run can resume from it.
+    #[allow(unused)]
+    fn checkpoint(&self, continuation: &Continuation) -> Result<(), CallbackError> {
+        Ok(())
+    }
+
     fn is_canceled(&self) -> bool {
         false
     }
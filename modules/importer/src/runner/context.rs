@@ -40,6 +40,24 @@ pub trait RunContext: Debug + Send {
         }
     }
 
+    /// Persist a continuation, from which an interrupted run can resume.
+    ///
+    /// This stores the continuation before the run is finished, so that a crashed or restarted
+    /// instance doesn't need to start over.
+    fn checkpoint(
+        &self,
+        #[allow(unused)] continuation: serde_json::Value,
+    ) -> impl Future<Output = ()> {
+        async {}
+    }
+
+    /// A sync version of [`Self::checkpoint`].
+    ///
+    /// **NOTE:** Requires to be called from a Tokio context.
+    fn checkpoint_sync(&self, continuation: serde_json::Value) {
+        Handle::current().block_on(self.checkpoint(continuation))
+    }
+
     fn progress(&self, #[allow(unused)] message: String) -> impl Progress + Send + 'static {}
 }
 
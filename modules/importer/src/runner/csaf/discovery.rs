@@ -0,0 +1,99 @@
+use crate::runner::common::Error;
+use anyhow::anyhow;
+use url::Url;
+
+/// The locations of the `security.txt` file, in the order of preference (RFC 9116)
+const SECURITY_TXT: &[&str] = &[".well-known/security.txt", "security.txt"];
+
+/// Discover the URL of the CSAF provider metadata, using the `security.txt` of a site.
+pub async fn discover(client: &reqwest::Client, base: &Url) -> Result<Url, Error> {
+    for location in SECURITY_TXT {
+        let url = base
+            .join(location)
+            .map_err(|err| Error::Processing(anyhow!("invalid base URL '{base}': {err}")))?;
+
+        log::debug!("Looking for CSAF provider metadata in: {url}");
+
+        let response = client.get(url).send().await?;
+        if !response.status().is_success() {
+            continue;
+        }
+
+        if let Some(metadata) = provider_metadata(&response.text().await?) {
+            log::info!("Discovered CSAF provider metadata: {metadata}");
+            return Ok(metadata);
+        }
+    }
+
+    Err(Error::Processing(anyhow!(
+        "no CSAF provider metadata found in the security.txt of '{base}'"
+    )))
+}
+
+/// Extract the first provider metadata URL from the `CSAF` fields of a `security.txt` file
+fn provider_metadata(security_txt: &str) -> Option<Url> {
+    security_txt
+        .lines()
+        .map(|line| line.trim())
+        // lines of a signed file might be dash-escaped
+        .map(|line| line.strip_prefix("- ").unwrap_or(line))
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once(':'))
+        .filter(|(field, _)| field.trim().eq_ignore_ascii_case("CSAF"))
+        .filter_map(|(_, value)| Url::parse(value.trim()).ok())
+        .find(|url| url.scheme() == "https")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use test_log::test;
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{method, path},
+    };
+
+    #[test]
+    fn parse_security_txt() {
+        let security_txt = r#"
+# Our security contacts
+Contact: mailto:secalert@example.com
+Expires: 2030-01-01T00:00:00.000Z
+csaf: http://example.com/insecure/provider-metadata.json
+CSAF: https://example.com/.well-known/csaf/provider-metadata.json
+CSAF: https://example.com/other/provider-metadata.json
+"#;
+
+        assert_eq!(
+            provider_metadata(security_txt).map(String::from),
+            Some("https://example.com/.well-known/csaf/provider-metadata.json".into())
+        );
+        assert_eq!(provider_metadata("Contact: mailto:foo@example.com"), None);
+    }
+
+    #[test(tokio::test)]
+    async fn discover_fallback() -> Result<(), anyhow::Error> {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/.well-known/security.txt"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/security.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                "CSAF: https://example.com/.well-known/csaf/provider-metadata.json\n",
+            ))
+            .mount(&server)
+            .await;
+
+        let metadata = discover(&Default::default(), &Url::parse(&server.uri())?).await?;
+        assert_eq!(
+            metadata.as_str(),
+            "https://example.com/.well-known/csaf/provider-metadata.json"
+        );
+
+        Ok(())
+    }
+}
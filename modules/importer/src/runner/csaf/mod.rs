@@ -1,3 +1,4 @@
+mod discovery;
 mod report;
 pub mod storage;
 
@@ -5,10 +6,13 @@ use crate::{
     model::CsafImporter,
     runner::{
         RunOutput,
-        common::{filter::Filter, validation},
+        common::{
+            filter::{Filter, PathFilter},
+            validation,
+        },
         context::RunContext,
-        csaf::report::CsafReportVisitor,
-        report::{ReportBuilder, ReportVisitor, ScannerError},
+        csaf::{discovery::discover, report::CsafReportVisitor},
+        report::{ReportVisitor, ScannerError},
     },
     server::context::WalkerProgress,
 };
@@ -24,9 +28,7 @@ use reqwest::StatusCode;
 use std::collections::HashSet;
 use std::{sync::Arc, time::SystemTime};
 use tracing::instrument;
-use trustify_module_ingestor::{graph::Graph, service::IngestorService};
 use url::Url;
-use walker_common::fetcher::{Fetcher, FetcherOptions};
 
 impl super::ImportRunner {
     #[instrument(skip_all, err)]
@@ -49,22 +51,34 @@ impl super::ImportRunner {
             only_patterns,
             fetch_retries,
             ignore_missing,
+            security_txt,
         } = importer;
 
-        let report = Arc::new(Mutex::new(ReportBuilder::new()));
+        let (ingestor, report) = self.ingestor(context.name(), &common);
+        let paths = PathFilter::from_config(&common)?;
+        let report = Arc::new(Mutex::new(report));
 
-        let fetcher =
-            Fetcher::new(FetcherOptions::new().retries(fetch_retries.unwrap_or_default())).await?;
+        let fetcher = self.fetcher(&common, fetch_retries).await?;
         let options = HttpOptions::new().since(last_success);
 
-        let source = match Url::parse(&source) {
-            Ok(url) => HttpSource::new(url, fetcher, options),
-            Err(_) => HttpSource::new(MetadataRetriever::new(source.clone()), fetcher, options),
+        let source = match (Url::parse(&source), security_txt) {
+            (Ok(url), false) => HttpSource::new(url, fetcher, options),
+            (Err(_), false) => {
+                HttpSource::new(MetadataRetriever::new(source.clone()), fetcher, options)
+            }
+            (url, true) => {
+                let base = url
+                    .or_else(|_| Url::parse(&format!("https://{source}/")))
+                    .map_err(|err| ScannerError::Critical(err.into()))?;
+                let client = self.http_client(&common)?;
+                let metadata = discover(&client, &base)
+                    .await
+                    .map_err(|err| ScannerError::Critical(err.into()))?;
+                HttpSource::new(metadata, fetcher, options)
+            }
         };
 
         // storage (called by validator)
-        let ingestor =
-            IngestorService::new(Graph::new(), self.storage.clone(), self.analysis.clone());
 
         let storage = storage::StorageVisitor {
             context,
@@ -95,7 +109,7 @@ impl super::ImportRunner {
 
         // filter
 
-        let filter = Filter::from_config(visitor, only_patterns)?;
+        let filter = Filter::from_config(visitor, only_patterns, paths)?;
 
         // walker
 
@@ -1,4 +1,8 @@
-use crate::runner::{common::storage::StorageError, context::RunContext, report::ReportBuilder};
+use crate::runner::{
+    common::{ingestor::Ingestor, storage::StorageError},
+    context::RunContext,
+    report::ReportBuilder,
+};
 use csaf_walker::{
     source::Source,
     validation::{ValidatedAdvisory, ValidatedVisitor, ValidationContext, ValidationError},
@@ -7,12 +11,12 @@ use parking_lot::Mutex;
 use std::sync::Arc;
 use trustify_common::db::ReadWrite;
 use trustify_entity::labels::Labels;
-use trustify_module_ingestor::service::{Cache, Format, IngestorService};
+use trustify_module_ingestor::service::{Cache, Format};
 use walker_common::utils::url::Urlify;
 
 pub struct StorageVisitor<C: RunContext> {
     pub context: C,
-    pub ingestor: IngestorService,
+    pub ingestor: Ingestor,
     pub db: ReadWrite,
     /// the report to report our messages to
     pub report: Arc<Mutex<ReportBuilder>>,
@@ -36,21 +40,25 @@ impl<C: RunContext, S: Source> ValidatedVisitor<S> for StorageVisitor<C> {
         let location = doc.context.url().to_string();
         let file = doc.possibly_relative_url();
 
-        self.db
-            .transaction(async |tx| {
-                self.ingestor
-                    .ingest(
-                        &doc.data,
-                        Format::CSAF,
-                        Labels::new()
-                            .add("source", &location)
-                            .add("importer", self.context.name())
-                            .add("file", file)
-                            .extend(self.labels.0.clone()),
-                        None, /* CSAF tracks issuer internally */
-                        Cache::Skip,
-                        tx,
-                    )
+        self.ingestor
+            .retrying(async || {
+                self.db
+                    .transaction(async |tx| {
+                        self.ingestor
+                            .ingest(
+                                doc.data.clone(),
+                                Format::CSAF,
+                                Labels::new()
+                                    .add("source", &location)
+                                    .add("importer", self.context.name())
+                                    .add("file", &file)
+                                    .extend(self.labels.0.clone()),
+                                None, /* CSAF tracks issuer internally */
+                                Cache::Skip,
+                                tx,
+                            )
+                            .await
+                    })
                     .await
             })
             .await
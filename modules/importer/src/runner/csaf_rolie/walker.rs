@@ -0,0 +1,413 @@
+use crate::{
+    model::CsafRolieImporter,
+    runner::{
+        common::{Error, filter::PathFilter, ingestor::Ingestor},
+        context::RunContext,
+        progress::{Progress, ProgressInstance},
+        report::{Message, Phase, ReportBuilder, Severity},
+    },
+};
+use anyhow::anyhow;
+use std::{collections::BTreeMap, sync::Arc};
+use time::OffsetDateTime;
+use tokio::sync::Mutex;
+use tokio_util::bytes::Bytes;
+use tracing::instrument;
+use trustify_common::db::ReadWrite;
+use trustify_entity::labels::Labels;
+use trustify_module_ingestor::service::{Cache, Format};
+use url::Url;
+
+/// The most recent update which was processed, by the URL of its feed
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FeedUpdates(BTreeMap<String, Updated>);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct Updated(#[serde(with = "time::serde::rfc3339")] OffsetDateTime);
+
+/// The parts of the provider metadata required to discover the ROLIE feeds
+#[derive(Clone, Debug, serde::Deserialize)]
+struct ProviderMetadata {
+    #[serde(default)]
+    distributions: Vec<Distribution>,
+}
+
+#[derive(Clone, Debug, serde::Deserialize)]
+struct Distribution {
+    #[serde(default)]
+    rolie: Option<Rolie>,
+}
+
+#[derive(Clone, Debug, serde::Deserialize)]
+struct Rolie {
+    #[serde(default)]
+    feeds: Vec<RolieFeed>,
+}
+
+#[derive(Clone, Debug, serde::Deserialize)]
+struct RolieFeed {
+    url: String,
+}
+
+/// A ROLIE feed document
+#[derive(Clone, Debug, serde::Deserialize)]
+struct FeedDocument {
+    feed: Feed,
+}
+
+#[derive(Clone, Debug, serde::Deserialize)]
+struct Feed {
+    #[serde(default)]
+    entry: Vec<Entry>,
+}
+
+#[derive(Clone, Debug, serde::Deserialize)]
+struct Entry {
+    id: String,
+    #[serde(with = "time::serde::rfc3339")]
+    updated: OffsetDateTime,
+    #[serde(default)]
+    content: Option<Content>,
+    #[serde(default)]
+    link: Vec<Link>,
+}
+
+#[derive(Clone, Debug, serde::Deserialize)]
+struct Content {
+    src: String,
+}
+
+#[derive(Clone, Debug, serde::Deserialize)]
+struct Link {
+    rel: String,
+    href: String,
+}
+
+impl Entry {
+    /// The location of the document, preferring the content over the `self` link.
+    fn source(&self) -> Option<&str> {
+        self.content
+            .as_ref()
+            .map(|content| content.src.as_str())
+            .or_else(|| {
+                self.link
+                    .iter()
+                    .find(|link| link.rel == "self")
+                    .map(|link| link.href.as_str())
+            })
+    }
+}
+
+pub struct RolieWalker<C: RunContext> {
+    continuation: FeedUpdates,
+    importer: CsafRolieImporter,
+    ingestor: Ingestor,
+    db: ReadWrite,
+    report: Arc<Mutex<ReportBuilder>>,
+    context: C,
+    client: reqwest::Client,
+    filter: PathFilter,
+}
+
+impl<C: RunContext> RolieWalker<C> {
+    pub fn new(
+        importer: CsafRolieImporter,
+        ingestor: impl Into<Ingestor>,
+        db: ReadWrite,
+        report: Arc<Mutex<ReportBuilder>>,
+        context: C,
+    ) -> Self {
+        Self {
+            continuation: FeedUpdates::default(),
+            importer,
+            ingestor: ingestor.into(),
+            db,
+            report,
+            context,
+            client: Default::default(),
+            filter: Default::default(),
+        }
+    }
+
+    /// Set the HTTP client for fetching the documents.
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Set the filter for the paths of documents.
+    pub fn filter(mut self, filter: PathFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Set a continuation token from a previous run.
+    pub fn continuation(mut self, continuation: FeedUpdates) -> Self {
+        self.continuation = continuation;
+        self
+    }
+
+    /// Run the walker
+    ///
+    /// Feeds which cannot be retrieved are reported, and will be processed again by the next run.
+    #[instrument(skip(self), err(level=tracing::Level::INFO))]
+    pub async fn run(self) -> Result<FeedUpdates, Error> {
+        let source = Url::parse(&self.importer.source).map_err(|err| {
+            Error::Processing(anyhow!(
+                "invalid source URL '{}': {err}",
+                self.importer.source
+            ))
+        })?;
+
+        let metadata: ProviderMetadata = serde_json::from_slice(&self.fetch(&source).await?)?;
+
+        let mut updates = self.continuation.clone();
+
+        let feeds = metadata
+            .distributions
+            .into_iter()
+            .flat_map(|distribution| distribution.rolie)
+            .flat_map(|rolie| rolie.feeds);
+
+        for feed in feeds {
+            let since = updates.0.get(&feed.url).map(|updated| updated.0);
+
+            let entries = match self.entries(&source, &feed.url, since).await {
+                Ok(entries) => entries,
+                Err(err) => {
+                    self.report.lock().await.add_error(
+                        Phase::Retrieval,
+                        &feed.url,
+                        err.to_string(),
+                    );
+                    continue;
+                }
+            };
+
+            let progress = self
+                .context
+                .progress(format!("Import CSAF ROLIE feed: {}", feed.url));
+            let mut progress = progress.start(entries.len());
+
+            let mut latest = since;
+            for (url, entry) in entries {
+                let stored = match self.fetch(&url).await {
+                    Ok(data) => self.store(&feed.url, &entry.id, data).await,
+                    Err(err) => {
+                        self.report
+                            .lock()
+                            .await
+                            .add_error(Phase::Retrieval, url, err.to_string());
+                        false
+                    }
+                };
+
+                latest = latest.max(Some(entry.updated));
+
+                match stored {
+                    true => progress.tick().await,
+                    false => progress.fail().await,
+                }
+                if self.context.is_canceled().await {
+                    return Err(Error::Canceled);
+                }
+            }
+
+            progress.finish().await;
+
+            if let Some(latest) = latest {
+                updates.0.insert(feed.url, Updated(latest));
+            }
+        }
+
+        Ok(updates)
+    }
+
+    async fn fetch(&self, url: &Url) -> Result<Bytes, Error> {
+        Ok(self
+            .client
+            .get(url.clone())
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?)
+    }
+
+    /// Get the entries of a feed which were updated after the provided timestamp, along with
+    /// the URL of their document.
+    async fn entries(
+        &self,
+        source: &Url,
+        feed: &str,
+        since: Option<OffsetDateTime>,
+    ) -> Result<Vec<(Url, Entry)>, Error> {
+        let feed = source
+            .join(feed)
+            .map_err(|err| Error::Processing(anyhow!("invalid feed URL '{feed}': {err}")))?;
+
+        let document: FeedDocument = serde_json::from_slice(&self.fetch(&feed).await?)?;
+
+        let mut entries = vec![];
+        for entry in document.feed.entry {
+            if since.is_some_and(|since| entry.updated <= since) {
+                continue;
+            }
+
+            let url = entry
+                .source()
+                .ok_or_else(|| anyhow!("entry has no document"))
+                .and_then(|src| Ok(feed.join(src)?));
+
+            match url {
+                Ok(url) if !self.filter.matches(url.path()) => {}
+                Ok(url) => entries.push((url, entry)),
+                Err(err) => {
+                    self.report.lock().await.add_message(
+                        Phase::Retrieval,
+                        &entry.id,
+                        Severity::Warning,
+                        format!("Invalid feed entry: {err}"),
+                    );
+                }
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Store a document, returning `true` if it got stored.
+    async fn store(&self, feed: &str, id: &str, data: Bytes) -> bool {
+        let labels = Labels::new()
+            .add("source", &self.importer.source)
+            .add("importer", "CSAF ROLIE")
+            .add("feed", feed)
+            .add("file", id)
+            .extend(self.importer.labels.0.clone());
+
+        let result = self
+            .db
+            .transaction(async |tx| {
+                self.ingestor
+                    .ingest(data, Format::CSAF, labels, None, Cache::Skip, tx)
+                    .await
+            })
+            .await;
+
+        let mut report = self.report.lock().await;
+        match result {
+            Ok(result) => {
+                log::debug!("Imported {id}");
+                report.tick();
+                report.extend_messages(
+                    Phase::Upload,
+                    id,
+                    result.warnings.iter().map(Message::from),
+                );
+                true
+            }
+            Err(err) => {
+                log::warn!("Error importing {id}: {err}");
+                report.add_error(Phase::Upload, id, err.to_string());
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        model::CommonImporter,
+        runner::common::test::{build_report, mock_get, new_report},
+    };
+    use serde_json::json;
+    use test_context::test_context;
+    use test_log::test;
+    use time::macros::datetime;
+    use trustify_test_context::{TrustifyContext, document_bytes};
+    use wiremock::{MockServer, ResponseTemplate};
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn import_feed(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let server = MockServer::start().await;
+
+        mock_get(
+            &server,
+            "/.well-known/csaf/provider-metadata.json",
+            ResponseTemplate::new(200).set_body_json(json!({
+                "distributions": [{
+                    "rolie": {
+                        "feeds": [{
+                            "tlp_label": "WHITE",
+                            "url": format!("{}/csaf/feed-tlp-white.json", server.uri()),
+                        }]
+                    }
+                }]
+            })),
+        )
+        .await;
+        mock_get(
+            &server,
+            "/csaf/feed-tlp-white.json",
+            ResponseTemplate::new(200).set_body_json(json!({
+                "feed": {
+                    "id": "example-csaf-feed-tlp-white",
+                    "entry": [
+                        {
+                            "id": "cve-2023-0044",
+                            "updated": "2024-07-08T10:00:00Z",
+                            "content": { "type": "application/json", "src": "2023/cve-2023-0044.json" }
+                        },
+                        {
+                            "id": "cve-2023-33201",
+                            "updated": "2024-07-01T10:00:00Z",
+                            "link": [{ "rel": "self", "href": "2023/cve-2023-33201.json" }]
+                        }
+                    ]
+                }
+            })),
+        )
+        .await;
+        mock_get(
+            &server,
+            "/csaf/2023/cve-2023-0044.json",
+            ResponseTemplate::new(200)
+                .set_body_bytes(document_bytes("csaf/cve-2023-0044.json").await?.to_vec()),
+        )
+        .await;
+
+        let report = new_report();
+        let feed = format!("{}/csaf/feed-tlp-white.json", server.uri());
+        let walker = RolieWalker::new(
+            CsafRolieImporter {
+                common: CommonImporter::default(),
+                source: format!("{}/.well-known/csaf/provider-metadata.json", server.uri()),
+            },
+            ctx.ingestor.clone(),
+            ReadWrite::new(ctx.db.clone()),
+            report.clone(),
+            (),
+        )
+        .continuation(FeedUpdates(BTreeMap::from([(
+            feed.clone(),
+            Updated(datetime!(2024-07-05 0:00 UTC)),
+        )])));
+
+        let continuation = walker.run().await?;
+        assert_eq!(
+            continuation.0.get(&feed),
+            Some(&Updated(datetime!(2024-07-08 10:00 UTC)))
+        );
+
+        // the entry updated before the last run must not be fetched again
+        let report = build_report(report);
+        assert_eq!(report.number_of_items, 1);
+        assert!(report.messages.is_empty(), "{:?}", report.messages);
+
+        Ok(())
+    }
+}
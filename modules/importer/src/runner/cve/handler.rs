@@ -1,7 +1,7 @@
 use crate::runner::common::Error;
 use crate::runner::common::{
     processing_error::ProcessingError,
-    walker::{CallbackError, Callbacks, Handler, HandlerError},
+    walker::{CallbackError, Callbacks, Continuation, Handler, HandlerError},
 };
 use std::fs::File;
 use std::io::Read;
@@ -14,6 +14,7 @@ where
     pub callbacks: C,
     pub years: HashSet<u16>,
     pub start_year: Option<u16>,
+    pub skip_rejected: bool,
 }
 
 impl<C> Handler for CveHandler<C>
@@ -63,6 +64,42 @@ where
             }
         }
     }
+
+    fn deleted(&self, relative_path: &Path) -> Result<(), HandlerError<Self::Error>> {
+        self.callbacks
+            .deleted(relative_path)
+            .map_err(|err| match err {
+                CallbackError::Processing(err) => HandlerError::Processing(Error::Processing(err)),
+                CallbackError::Canceled => HandlerError::Canceled,
+            })
+    }
+
+    fn checkpoint(&self, continuation: &Continuation) -> Result<(), HandlerError<Self::Error>> {
+        self.callbacks
+            .checkpoint(continuation)
+            .map_err(|err| match err {
+                CallbackError::Processing(err) => HandlerError::Processing(Error::Processing(err)),
+                CallbackError::Canceled => HandlerError::Canceled,
+            })
+    }
+}
+
+/// The parts of a CVE record required to determine its state
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CveState {
+    cve_metadata: CveMetadata,
+}
+
+#[derive(serde::Deserialize)]
+struct CveMetadata {
+    state: String,
+}
+
+/// Check if the CVE record is in the state "rejected".
+fn is_rejected(cve: &[u8]) -> Result<bool, serde_json::Error> {
+    let cve: CveState = serde_json::from_slice(cve)?;
+    Ok(cve.cve_metadata.state.eq_ignore_ascii_case("REJECTED"))
 }
 
 impl<C> CveHandler<C>
@@ -82,6 +119,11 @@ where
             }
         };
 
+        if self.skip_rejected && is_rejected(&cve)? {
+            log::debug!("Skipping rejected CVE: {}", rel_path.display());
+            return Ok(());
+        }
+
         self.callbacks
             .process(rel_path, cve)
             .map_err(|err| match err {
@@ -92,3 +134,21 @@ where
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rejected() -> Result<(), serde_json::Error> {
+        assert!(is_rejected(
+            br#"{"cveMetadata": {"cveId": "CVE-2023-0001", "state": "REJECTED"}}"#
+        )?);
+        assert!(!is_rejected(
+            br#"{"cveMetadata": {"cveId": "CVE-2023-0002", "state": "PUBLISHED"}}"#
+        )?);
+        assert!(is_rejected(b"{}").is_err());
+
+        Ok(())
+    }
+}
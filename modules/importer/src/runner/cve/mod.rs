@@ -1,60 +1,111 @@
 mod handler;
 
 use crate::{
-    model::CveImporter,
+    model::{CveImporter, UpstreamDeletion},
     runner::{
         RunOutput,
-        common::walker::{CallbackError, Callbacks, GitWalker},
+        common::{
+            deletion,
+            filter::PathFilter,
+            ingestor::Ingestor,
+            pool::{Submitter, worker_pool},
+            retry::Retry,
+            walker::{CallbackError, Callbacks, Continuation, DEFAULT_DEPTH, GitWalker},
+        },
         context::RunContext,
         report::{Phase, ReportBuilder, ScannerError},
     },
 };
+use anyhow::anyhow;
 use handler::CveHandler;
 use parking_lot::Mutex;
 use std::{path::Path, path::PathBuf, sync::Arc};
 use tokio::runtime::Handle;
+use tokio_util::bytes::Bytes;
 use tracing::instrument;
 use trustify_common::db::ReadWrite;
 use trustify_entity::labels::Labels;
-use trustify_module_ingestor::service::Cache;
-use trustify_module_ingestor::{
-    graph::Graph,
-    service::{Format, IngestorService},
-};
+use trustify_module_ingestor::service::{Cache, Format};
+
+/// A document, to be stored by the workers
+struct Document {
+    path: PathBuf,
+    data: Bytes,
+}
 
 struct Context<C: RunContext + 'static> {
     context: C,
+    on_deleted: UpstreamDeletion,
+    report: Arc<Mutex<ReportBuilder>>,
+    ingestor: Ingestor,
+    db: ReadWrite,
+    documents: Submitter<Document>,
+}
+
+/// Stores the documents, running in the workers
+struct Storage {
     source: String,
+    importer: String,
     labels: Labels,
     report: Arc<Mutex<ReportBuilder>>,
-    ingestor: IngestorService,
+    ingestor: Ingestor,
     db: ReadWrite,
 }
 
-impl<C: RunContext> Context<C> {
-    fn store(&self, path: &Path, data: Vec<u8>) -> anyhow::Result<()> {
+impl Storage {
+    async fn store(&self, Document { path, data }: Document) {
         self.report.lock().tick();
 
-        Handle::current().block_on(async {
-            self.db
-                .transaction(async |tx| {
-                    self.ingestor
-                        .ingest(
-                            &data,
-                            Format::CVE,
-                            Labels::new()
-                                .add("source", &self.source)
-                                .add("importer", self.context.name())
-                                .add("file", path.to_string_lossy())
-                                .extend(self.labels.0.clone()),
-                            None,
-                            Cache::Skip,
-                            tx,
-                        )
-                        .await
-                })
-                .await
-        })?;
+        let file = path.to_string_lossy();
+        let result = self
+            .ingestor
+            .retrying(async || {
+                self.db
+                    .transaction(async |tx| {
+                        self.ingestor
+                            .ingest(
+                                data.clone(),
+                                Format::CVE,
+                                Labels::new()
+                                    .add("source", &self.source)
+                                    .add("importer", &self.importer)
+                                    .add("file", file.clone())
+                                    .extend(self.labels.0.clone()),
+                                None,
+                                Cache::Skip,
+                                tx,
+                            )
+                            .await
+                    })
+                    .await
+            })
+            .await;
+
+        if let Err(err) = result {
+            self.report
+                .lock()
+                .add_error(Phase::Upload, file, err.to_string());
+        }
+    }
+}
+
+impl<C: RunContext> Context<C> {
+    fn delete(&self, path: &Path) -> anyhow::Result<()> {
+        if self.ingestor.is_dry_run() {
+            return Ok(());
+        }
+
+        let file = path.to_string_lossy();
+        let updated = Handle::current().block_on(deletion::apply(
+            self.on_deleted,
+            self.context.name(),
+            &file,
+            &self.db,
+        ))?;
+
+        if updated > 0 {
+            log::info!("Handled upstream deletion of {file}: {updated} advisories");
+        }
 
         Ok(())
     }
@@ -68,7 +119,21 @@ impl<C: RunContext> Callbacks<Vec<u8>> for Context<C> {
     }
 
     fn process(&self, path: &Path, cve: Vec<u8>) -> Result<(), CallbackError> {
-        if let Err(err) = self.store(path, cve) {
+        let document = Document {
+            path: path.to_path_buf(),
+            data: cve.into(),
+        };
+        if !self.documents.submit_sync(document) {
+            return Err(CallbackError::Processing(anyhow!(
+                "storage workers stopped"
+            )));
+        }
+
+        self.context.check_canceled_sync(|| CallbackError::Canceled)
+    }
+
+    fn deleted(&self, path: &Path) -> Result<(), CallbackError> {
+        if let Err(err) = self.delete(path) {
             self.report
                 .lock()
                 .add_error(Phase::Upload, path.to_string_lossy(), err.to_string());
@@ -77,6 +142,25 @@ impl<C: RunContext> Callbacks<Vec<u8>> for Context<C> {
         self.context.check_canceled_sync(|| CallbackError::Canceled)
     }
 
+    fn checkpoint(&self, continuation: &Continuation) -> Result<(), CallbackError> {
+        if self.ingestor.is_dry_run() {
+            return Ok(());
+        }
+
+        // the documents before the checkpoint must be stored first
+        if !self.documents.flush_sync() {
+            return Err(CallbackError::Processing(anyhow!(
+                "storage workers stopped"
+            )));
+        }
+
+        let continuation = serde_json::to_value(continuation)
+            .map_err(|err| CallbackError::Processing(err.into()))?;
+        self.context.checkpoint_sync(continuation);
+
+        Ok(())
+    }
+
     fn is_canceled(&self) -> bool {
         self.context.is_canceled_sync()
     }
@@ -90,46 +174,75 @@ impl super::ImportRunner {
         cve: CveImporter,
         continuation: serde_json::Value,
     ) -> Result<RunOutput, ScannerError> {
-        let ingestor =
-            IngestorService::new(Graph::new(), self.storage.clone(), self.analysis.clone());
-
-        let report = Arc::new(Mutex::new(ReportBuilder::new()));
+        let (ingestor, report) = self.ingestor(context.name(), &cve);
+        let report = Arc::new(Mutex::new(report));
         let continuation = serde_json::from_value(continuation).unwrap_or_default();
 
         // working dir
 
         let working_dir = self.create_working_dir("cve", &cve.source).await?;
+        let retry = cve.retry.as_ref().map(Retry::new).unwrap_or_default();
+        let proxy = self.proxy(&cve);
+        let filter = PathFilter::from_config(&cve)?;
+        let credentials = self.git_credentials(cve.credentials.as_ref())?;
 
         // progress reporting
 
         let progress = context.progress(format!("Import CVE: {}", cve.source));
 
+        // storage workers
+
+        let (documents, workers) = worker_pool(cve.concurrency.unwrap_or(1));
+        let storage = Storage {
+            source: cve.source.clone(),
+            importer: context.name().to_string(),
+            labels: cve.common.labels,
+            report: report.clone(),
+            ingestor: ingestor.clone(),
+            db: self.db.clone(),
+        };
+
         // run the walker
 
         let walker = GitWalker::new(
-            cve.source.clone(),
+            cve.source,
             CveHandler {
                 callbacks: Context {
                     context,
-                    source: cve.source,
-                    labels: cve.common.labels,
+                    on_deleted: cve.on_deleted,
                     report: report.clone(),
                     ingestor,
                     db: self.db.clone(),
+                    documents,
                 },
                 years: cve.years,
                 start_year: cve.start_year,
+                skip_rejected: cve.skip_rejected,
             },
         )
         .path(Some("cves"))
+        .branch(cve.branch)
+        .tag(cve.tag)
+        .commit(cve.commit)
         .continuation(continuation)
+        .proxy(proxy)
+        .filter(filter)
+        .credentials(credentials)
+        .depth(cve.depth.unwrap_or(DEFAULT_DEPTH))
+        .retry(retry)
         .progress(progress);
 
-        let continuation = match working_dir {
-            Some(working_dir) => walker.working_dir(working_dir).run().await,
-            None => walker.run().await,
-        }
-        .map_err(|err| ScannerError::Normal {
+        let (result, ()) = tokio::join!(
+            async {
+                match working_dir {
+                    Some(working_dir) => walker.working_dir(working_dir).run().await,
+                    None => walker.run().await,
+                }
+            },
+            workers.run(|document| storage.store(document))
+        );
+
+        let continuation = result.map_err(|err| ScannerError::Normal {
             err: err.into(),
             output: RunOutput {
                 report: report.lock().clone().build(),
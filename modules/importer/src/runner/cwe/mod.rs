@@ -1,16 +1,10 @@
 mod walker;
 
 use crate::model::CweImporter;
-use crate::runner::{
-    RunOutput,
-    context::RunContext,
-    cwe::walker::CweWalker,
-    report::{ReportBuilder, ScannerError},
-};
+use crate::runner::{RunOutput, context::RunContext, cwe::walker::CweWalker, report::ScannerError};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tracing::instrument;
-use trustify_module_ingestor::{graph::Graph, service::IngestorService};
 
 impl super::ImportRunner {
     #[instrument(skip(self), err(level=tracing::Level::INFO))]
@@ -20,10 +14,8 @@ impl super::ImportRunner {
         cwe_catalog: CweImporter,
         continuation: serde_json::Value,
     ) -> Result<RunOutput, ScannerError> {
-        let ingestor =
-            IngestorService::new(Graph::new(), self.storage.clone(), self.analysis.clone());
-
-        let report = Arc::new(Mutex::new(ReportBuilder::new()));
+        let (ingestor, report) = self.ingestor(context.name(), &cwe_catalog);
+        let report = Arc::new(Mutex::new(report));
         let continuation = serde_json::from_value(continuation).unwrap_or_default();
 
         // no working-dir required
@@ -32,12 +24,15 @@ impl super::ImportRunner {
 
         // run the walker
 
+        let client = self.http_client(&cwe_catalog)?;
+
         let walker = CweWalker::new(
             cwe_catalog.source.clone(),
             ingestor,
             self.db.clone(),
             report.clone(),
         )
+        .client(client)
         .continuation(continuation);
 
         match walker.run().await {
@@ -1,4 +1,4 @@
-use crate::runner::common::Error;
+use crate::runner::common::{Error, ingestor::Ingestor};
 use crate::runner::report::{Phase, ReportBuilder};
 use std::io::{Cursor, Read};
 use std::sync::Arc;
@@ -7,7 +7,7 @@ use tokio_util::bytes::Buf;
 use tracing::instrument;
 use trustify_common::db::ReadWrite;
 use trustify_entity::labels::Labels;
-use trustify_module_ingestor::service::{Cache, Format, IngestorService};
+use trustify_module_ingestor::service::{Cache, Format};
 use zip::ZipArchive;
 
 #[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
@@ -16,27 +16,35 @@ pub struct LastModified(Option<String>);
 pub struct CweWalker {
     continuation: LastModified,
     source: String,
-    ingestor: IngestorService,
+    ingestor: Ingestor,
     db: ReadWrite,
     report: Arc<Mutex<ReportBuilder>>,
+    client: reqwest::Client,
 }
 
 impl CweWalker {
     pub fn new(
         source: impl Into<String>,
-        ingestor: IngestorService,
+        ingestor: impl Into<Ingestor>,
         db: ReadWrite,
         report: Arc<Mutex<ReportBuilder>>,
     ) -> Self {
         Self {
             continuation: LastModified(None),
             source: source.into(),
-            ingestor,
+            ingestor: ingestor.into(),
             db,
             report,
+            client: Default::default(),
         }
     }
 
+    /// Set the HTTP client for fetching the documents.
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
     /// Set a continuation token from a previous run.
     pub fn continuation(mut self, continuation: LastModified) -> Self {
         self.continuation = continuation;
@@ -46,7 +54,7 @@ impl CweWalker {
     /// Run the walker
     #[instrument(skip(self), err(level=tracing::Level::INFO))]
     pub async fn run(self) -> Result<LastModified, Error> {
-        let response = reqwest::get(&self.source).await?;
+        let response = self.client.get(&self.source).send().await?;
 
         let last_modified = response
             .headers()
@@ -85,7 +93,7 @@ impl CweWalker {
             .transaction(async |tx| {
                 self.ingestor
                     .ingest(
-                        &content,
+                        content,
                         Format::CweCatalog,
                         Labels::new()
                             .add("source", &self.source)
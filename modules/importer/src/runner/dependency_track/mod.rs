@@ -0,0 +1,58 @@
+mod walker;
+
+use crate::model::DependencyTrackImporter;
+use crate::runner::{
+    RunOutput, context::RunContext, dependency_track::walker::DependencyTrackWalker,
+    report::ScannerError,
+};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::instrument;
+
+impl super::ImportRunner {
+    #[instrument(skip_all, err(level=tracing::Level::INFO))]
+    pub async fn run_once_dependency_track(
+        &self,
+        context: impl RunContext + 'static,
+        dependency_track: DependencyTrackImporter,
+        continuation: serde_json::Value,
+    ) -> Result<RunOutput, ScannerError> {
+        let (ingestor, report) = self.ingestor(context.name(), &dependency_track);
+        let report = Arc::new(Mutex::new(report));
+        let continuation = serde_json::from_value(continuation).unwrap_or_default();
+
+        let client = self.http_client(&dependency_track)?;
+
+        let walker = DependencyTrackWalker::new(
+            dependency_track,
+            ingestor,
+            self.db.clone(),
+            report.clone(),
+            context,
+        )
+        .client(client)
+        .continuation(continuation);
+
+        match walker.run().await {
+            Ok(continuation) => {
+                // extract the report
+                let report = match Arc::try_unwrap(report) {
+                    Ok(report) => report.into_inner(),
+                    Err(report) => report.lock().await.clone(),
+                }
+                .build();
+                Ok(RunOutput {
+                    report,
+                    continuation: serde_json::to_value(continuation).ok(),
+                })
+            }
+            Err(err) => Err(ScannerError::Normal {
+                err: err.into(),
+                output: RunOutput {
+                    report: report.lock().await.clone().build(),
+                    continuation: None,
+                },
+            }),
+        }
+    }
+}
@@ -0,0 +1,445 @@
+use crate::{
+    model::DependencyTrackImporter,
+    runner::{
+        common::{Error, ingestor::Ingestor},
+        context::RunContext,
+        progress::{Progress, ProgressInstance},
+        report::{Message, Phase, ReportBuilder, Severity},
+    },
+};
+use anyhow::anyhow;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, Set, TransactionTrait,
+};
+use serde::Deserialize;
+use std::{
+    io::{Cursor, Read},
+    sync::Arc,
+};
+use time::OffsetDateTime;
+use tokio::sync::Mutex;
+use tracing::instrument;
+use trustify_common::db::ReadWrite;
+use trustify_entity::{
+    labels::Labels,
+    vulnerability_exception::{self, ExceptionKind},
+};
+use trustify_module_ingestor::{
+    graph::{Graph, product::ProductInformation, vulnerability::creator::VulnerabilityCreator},
+    model::Warning,
+    service::{self, Cache, Format},
+};
+use uuid::Uuid;
+use zip::{ZipArchive, result::ZipError};
+
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct LastModified(Option<String>);
+
+/// A project, as exported by Dependency-Track
+#[derive(Clone, Debug, Deserialize)]
+pub struct Project {
+    pub uuid: Uuid,
+    pub name: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Tag {
+    pub name: String,
+}
+
+/// A finding of a project, including its audit decision
+#[derive(Clone, Debug, Deserialize)]
+pub struct Finding {
+    pub vulnerability: FindingVulnerability,
+    #[serde(default)]
+    pub analysis: Option<Analysis>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindingVulnerability {
+    pub vuln_id: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Analysis {
+    #[serde(default, alias = "analysisState")]
+    pub state: Option<String>,
+    #[serde(default, alias = "analysisJustification")]
+    pub justification: Option<String>,
+    #[serde(default, alias = "analysisDetails")]
+    pub details: Option<String>,
+    #[serde(default)]
+    pub is_suppressed: bool,
+}
+
+impl Analysis {
+    /// Map the audit decision to an exception, if it is one
+    pub fn kind(&self) -> Option<ExceptionKind> {
+        match self.state.as_deref() {
+            Some("FALSE_POSITIVE" | "NOT_AFFECTED") => Some(ExceptionKind::FalsePositive),
+            _ if self.is_suppressed => Some(ExceptionKind::Accepted),
+            _ => None,
+        }
+    }
+
+    /// The justification of the decision, falling back to the state
+    pub fn justification(&self) -> String {
+        [self.details.as_deref(), self.justification.as_deref()]
+            .into_iter()
+            .flatten()
+            .map(str::trim)
+            .find(|value| !value.is_empty())
+            .map(ToString::to_string)
+            .unwrap_or_else(|| {
+                format!(
+                    "Imported from Dependency-Track ({})",
+                    self.state.as_deref().unwrap_or("suppressed")
+                )
+            })
+    }
+}
+
+pub struct DependencyTrackWalker<C: RunContext> {
+    continuation: LastModified,
+    importer: DependencyTrackImporter,
+    ingestor: Ingestor,
+    db: ReadWrite,
+    report: Arc<Mutex<ReportBuilder>>,
+    context: C,
+    client: reqwest::Client,
+}
+
+impl<C: RunContext> DependencyTrackWalker<C> {
+    pub fn new(
+        importer: DependencyTrackImporter,
+        ingestor: impl Into<Ingestor>,
+        db: ReadWrite,
+        report: Arc<Mutex<ReportBuilder>>,
+        context: C,
+    ) -> Self {
+        Self {
+            continuation: LastModified(None),
+            importer,
+            ingestor: ingestor.into(),
+            db,
+            report,
+            context,
+            client: Default::default(),
+        }
+    }
+
+    /// Set the HTTP client for fetching the documents.
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Set a continuation token from a previous run.
+    pub fn continuation(mut self, continuation: LastModified) -> Self {
+        self.continuation = continuation;
+        self
+    }
+
+    /// Run the walker
+    #[instrument(skip(self), err(level=tracing::Level::INFO))]
+    pub async fn run(self) -> Result<LastModified, Error> {
+        let source = &self.importer.source;
+
+        let (last_modified, content) =
+            if source.starts_with("http://") || source.starts_with("https://") {
+                let response = self.client.get(source).send().await?.error_for_status()?;
+
+                let last_modified = response
+                    .headers()
+                    .get("Last-Modified")
+                    .map(|inner| inner.to_str())
+                    .transpose()?
+                    .map(|inner| inner.to_string());
+
+                if let (Some(cont), Some(last_mod)) = (&self.continuation.0, &last_modified)
+                    && cont == last_mod
+                {
+                    // no change, just keep the same continuation
+                    return Ok(self.continuation);
+                }
+
+                (last_modified, response.bytes().await?.to_vec())
+            } else {
+                (None, tokio::fs::read(source).await?)
+            };
+
+        self.process(content).await?;
+
+        Ok(LastModified(last_modified))
+    }
+
+    /// Process the content of an export archive
+    pub async fn process(&self, content: Vec<u8>) -> Result<(), Error> {
+        let mut archive = ZipArchive::new(Cursor::new(content))?;
+
+        let projects = read_entry(&mut archive, "projects.json")?.ok_or_else(|| {
+            Error::Processing(anyhow!("export archive is missing 'projects.json'"))
+        })?;
+        let projects: Vec<Project> = serde_json::from_slice(&projects)?;
+
+        let progress = self.context.progress(format!(
+            "Import Dependency-Track projects from: {}",
+            self.importer.source
+        ));
+        let mut progress = progress.start(projects.len());
+
+        for project in projects {
+            let file = format!("projects/{}", project.uuid);
+
+            match read_entry(&mut archive, &format!("{file}/bom.json"))? {
+                Some(bom) => {
+                    let findings = match read_entry(&mut archive, &format!("{file}/findings.json"))?
+                    {
+                        Some(findings) => serde_json::from_slice(&findings)?,
+                        None => vec![],
+                    };
+                    match self.store(&file, &project, bom, &findings).await {
+                        true => progress.tick().await,
+                        false => progress.fail().await,
+                    }
+                }
+                None => {
+                    self.report.lock().await.add_message(
+                        Phase::Retrieval,
+                        file,
+                        Severity::Warning,
+                        "Project has no BOM, skipping",
+                    );
+                    progress.skip().await;
+                }
+            }
+
+            if self.context.is_canceled().await {
+                return Err(Error::Canceled);
+            }
+        }
+
+        progress.finish().await;
+
+        Ok(())
+    }
+
+    /// Store a project, returning `true` if it got stored.
+    async fn store(
+        &self,
+        file: &str,
+        project: &Project,
+        bom: Vec<u8>,
+        findings: &[Finding],
+    ) -> bool {
+        let result = self
+            .db
+            .transaction(async |tx| self.import(project, bom, findings, tx).await)
+            .await;
+
+        let mut report = self.report.lock().await;
+        match result {
+            Ok(warnings) => {
+                log::debug!("Imported {file}");
+                report.tick();
+                report.extend_messages(Phase::Upload, file, warnings.iter().map(Message::from));
+                true
+            }
+            Err(err) => {
+                log::warn!("Error importing {file}: {err}");
+                report.add_error(Phase::Upload, file, err.to_string());
+                false
+            }
+        }
+    }
+
+    /// Import a single project, returning the ingestion warnings
+    async fn import(
+        &self,
+        project: &Project,
+        bom: Vec<u8>,
+        findings: &[Finding],
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<Vec<Warning>, service::Error> {
+        let mut labels = Labels::new()
+            .add("source", &self.importer.source)
+            .add("importer", "Dependency-Track")
+            .add("dependencyTrack/project", &project.name)
+            .add("dependencyTrack/uuid", project.uuid.to_string());
+        if let Some(version) = &project.version {
+            labels = labels.add("dependencyTrack/version", version);
+        }
+        let labels = labels
+            .extend(
+                project
+                    .tags
+                    .iter()
+                    .map(|tag| (format!("dependencyTrack/tag/{}", tag.name), "")),
+            )
+            .extend(self.importer.labels.0.clone());
+
+        let result = self
+            .ingestor
+            .ingest(bom, Format::CycloneDX, labels, None, Cache::Skip, tx)
+            .await?;
+        if self.ingestor.is_dry_run() {
+            return Ok(result.warnings);
+        }
+
+        let sbom_id = Uuid::parse_str(&result.id)
+            .map_err(|err| service::Error::Generic(anyhow!("invalid SBOM ID: {err}")))?;
+
+        // recreate the project as product, so that audit decisions can be attached
+
+        let graph = Graph::new();
+        let product = graph
+            .ingest_product(&project.name, ProductInformation::default(), tx)
+            .await?;
+        product
+            .ingest_product_version(
+                project
+                    .version
+                    .clone()
+                    .unwrap_or_else(|| "unspecified".into()),
+                Some(sbom_id),
+                tx,
+            )
+            .await?;
+
+        let decisions: Vec<_> = findings
+            .iter()
+            .filter_map(|finding| {
+                let analysis = finding.analysis.as_ref()?;
+                Some((&finding.vulnerability.vuln_id, analysis.kind()?, analysis))
+            })
+            .collect();
+
+        let mut vulnerabilities = VulnerabilityCreator::new();
+        for (id, _, _) in &decisions {
+            vulnerabilities.add(*id, ());
+        }
+        vulnerabilities.create(tx).await?;
+
+        for (id, kind, analysis) in decisions {
+            // don't override decisions made in trustify, or imported by a previous run
+            let existing = vulnerability_exception::Entity::find()
+                .filter(vulnerability_exception::Column::ProductId.eq(product.product.id))
+                .filter(vulnerability_exception::Column::VulnerabilityId.eq(id.as_str()))
+                .one(tx)
+                .await?;
+            if existing.is_some() {
+                continue;
+            }
+
+            vulnerability_exception::ActiveModel {
+                id: Set(Uuid::now_v7()),
+                product_id: Set(product.product.id),
+                vulnerability_id: Set(id.clone()),
+                kind: Set(kind),
+                justification: Set(analysis.justification()),
+                expires: Set(None),
+                created: Set(OffsetDateTime::now_utc()),
+            }
+            .insert(tx)
+            .await?;
+        }
+
+        Ok(result.warnings)
+    }
+}
+
+/// Read an entry of the archive, returning `None` if it doesn't exist
+fn read_entry<R: Read + std::io::Seek>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+) -> Result<Option<Vec<u8>>, Error> {
+    let mut entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(ZipError::FileNotFound) => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut data = Vec::new();
+    entry.read_to_end(&mut data)?;
+    Ok(Some(data))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::CommonImporter;
+    use serde_json::json;
+    use std::io::Write;
+    use test_context::test_context;
+    use test_log::test;
+    use trustify_test_context::{TrustifyContext, document_bytes};
+    use zip::write::{FileOptions, ZipWriter};
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn import_archive(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let uuid = Uuid::new_v4();
+
+        let mut data = vec![];
+        let mut archive = ZipWriter::new(Cursor::new(&mut data));
+        archive.start_file("projects.json", FileOptions::<()>::default())?;
+        archive.write_all(&serde_json::to_vec(&json!([{
+            "uuid": uuid,
+            "name": "simple",
+            "version": "1.0",
+            "tags": [{ "name": "team-a" }],
+        }]))?)?;
+        archive.start_file(
+            format!("projects/{uuid}/bom.json"),
+            FileOptions::<()>::default(),
+        )?;
+        archive.write_all(&document_bytes("cyclonedx/simple.json").await?)?;
+        archive.start_file(
+            format!("projects/{uuid}/findings.json"),
+            FileOptions::<()>::default(),
+        )?;
+        archive.write_all(&serde_json::to_vec(&json!([
+            {
+                "vulnerability": { "vulnId": "CVE-2024-0001", "source": "NVD" },
+                "analysis": { "state": "NOT_AFFECTED", "isSuppressed": true },
+            },
+            {
+                "vulnerability": { "vulnId": "CVE-2024-0002", "source": "NVD" },
+                "analysis": { "state": "EXPLOITABLE", "isSuppressed": false },
+            },
+        ]))?)?;
+        archive.finish()?;
+
+        let walker = DependencyTrackWalker::new(
+            DependencyTrackImporter {
+                common: CommonImporter::default(),
+                source: "export.zip".into(),
+            },
+            ctx.ingestor.clone(),
+            ReadWrite::new(ctx.db.clone()),
+            Arc::new(Mutex::new(ReportBuilder::new())),
+            (),
+        );
+
+        // running twice must not duplicate anything
+        walker.process(data.clone()).await?;
+        walker.process(data).await?;
+
+        let exceptions = vulnerability_exception::Entity::find().all(&ctx.db).await?;
+        assert_eq!(exceptions.len(), 1);
+        assert_eq!(exceptions[0].vulnerability_id, "CVE-2024-0001");
+        assert_eq!(exceptions[0].kind, ExceptionKind::FalsePositive);
+        assert_eq!(
+            exceptions[0].justification,
+            "Imported from Dependency-Track (NOT_AFFECTED)"
+        );
+
+        Ok(())
+    }
+}
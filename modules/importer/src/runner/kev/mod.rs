@@ -0,0 +1,60 @@
+mod walker;
+
+use crate::model::KevImporter;
+use crate::runner::{RunOutput, context::RunContext, kev::walker::KevWalker, report::ScannerError};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::instrument;
+
+impl super::ImportRunner {
+    #[instrument(skip(self), err(level=tracing::Level::INFO))]
+    pub async fn run_once_kev(
+        &self,
+        context: impl RunContext + 'static,
+        kev: KevImporter,
+        continuation: serde_json::Value,
+    ) -> Result<RunOutput, ScannerError> {
+        let (ingestor, report) = self.ingestor(context.name(), &kev);
+        let report = Arc::new(Mutex::new(report));
+        let continuation = serde_json::from_value(continuation).unwrap_or_default();
+
+        // no working-dir required
+
+        // one file, no progress to care about.
+
+        // run the walker
+
+        let client = self.http_client(&kev)?;
+
+        let walker = KevWalker::new(
+            kev.source.clone(),
+            ingestor,
+            self.db.clone(),
+            report.clone(),
+        )
+        .client(client)
+        .continuation(continuation);
+
+        match walker.run().await {
+            Ok(continuation) => {
+                // extract the report
+                let report = match Arc::try_unwrap(report) {
+                    Ok(report) => report.into_inner(),
+                    Err(report) => report.lock().await.clone(),
+                }
+                .build();
+                Ok(RunOutput {
+                    report,
+                    continuation: serde_json::to_value(continuation).ok(),
+                })
+            }
+            Err(err) => Err(ScannerError::Normal {
+                err: err.into(),
+                output: RunOutput {
+                    report: report.lock().await.clone().build(),
+                    continuation: None,
+                },
+            }),
+        }
+    }
+}
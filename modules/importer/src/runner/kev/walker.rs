@@ -0,0 +1,247 @@
+use crate::runner::common::{Error, ingestor::Ingestor};
+use crate::runner::report::{Event, Message, Phase, ReportBuilder};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::instrument;
+use trustify_common::db::ReadWrite;
+use trustify_entity::{labels::Labels, vulnerability, vulnerability_kev};
+use trustify_module_ingestor::service::{Cache, Format, kev::Catalog};
+
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct LastModified(Option<String>);
+
+pub struct KevWalker {
+    continuation: LastModified,
+    source: String,
+    ingestor: Ingestor,
+    db: ReadWrite,
+    report: Arc<Mutex<ReportBuilder>>,
+    client: reqwest::Client,
+}
+
+impl KevWalker {
+    pub fn new(
+        source: impl Into<String>,
+        ingestor: impl Into<Ingestor>,
+        db: ReadWrite,
+        report: Arc<Mutex<ReportBuilder>>,
+    ) -> Self {
+        Self {
+            continuation: LastModified(None),
+            source: source.into(),
+            ingestor: ingestor.into(),
+            db,
+            report,
+            client: Default::default(),
+        }
+    }
+
+    /// Set the HTTP client for fetching the documents.
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Set a continuation token from a previous run.
+    pub fn continuation(mut self, continuation: LastModified) -> Self {
+        self.continuation = continuation;
+        self
+    }
+
+    /// Run the walker
+    ///
+    /// Entries which were not part of the catalog before are detected while ingesting it. For
+    /// each of them, which refers to an already known vulnerability, an
+    /// [`Event::KnownExploited`] is added to the report. When no catalog was ingested before,
+    /// no events are created.
+    #[instrument(skip(self), err(level=tracing::Level::INFO))]
+    pub async fn run(self) -> Result<LastModified, Error> {
+        let response = self
+            .client
+            .get(&self.source)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let last_modified = response
+            .headers()
+            .get("Last-Modified")
+            .map(|inner| inner.to_str())
+            .transpose()?
+            .map(|inner| inner.to_string());
+
+        match (&self.continuation.0, &last_modified) {
+            (Some(cont), Some(last_mod)) if cont.eq(last_mod) => {
+                // no change, just keep the same continuation
+                return Ok(self.continuation);
+            }
+            _ => {
+                // fall-through, process, return new last-modified as continuation
+            }
+        }
+
+        let content = response.bytes().await?;
+        let catalog: Catalog = serde_json::from_slice(&content)?;
+
+        let result = self
+            .db
+            .transaction(async |tx| {
+                let known: HashSet<String> = vulnerability_kev::Entity::find()
+                    .select_only()
+                    .column(vulnerability_kev::Column::VulnerabilityId)
+                    .into_tuple()
+                    .all(tx)
+                    .await?
+                    .into_iter()
+                    .collect();
+
+                let added: Vec<_> = catalog
+                    .vulnerabilities
+                    .iter()
+                    .filter(|entry| !known.contains(&entry.cve_id))
+                    .collect();
+
+                let result = self
+                    .ingestor
+                    .ingest(
+                        content.clone(),
+                        Format::Kev,
+                        Labels::new()
+                            .add("source", &self.source)
+                            .add("importer", "KEV"),
+                        None,
+                        Cache::Skip,
+                        tx,
+                    )
+                    .await?;
+
+                let existing: HashSet<String> = match known.is_empty() || self.ingestor.is_dry_run()
+                {
+                    // the initial load, nothing became known exploited
+                    true => HashSet::new(),
+                    false => vulnerability::Entity::find()
+                        .select_only()
+                        .column(vulnerability::Column::Id)
+                        .filter(
+                            vulnerability::Column::Id
+                                .is_in(added.iter().map(|entry| entry.cve_id.as_str())),
+                        )
+                        .into_tuple()
+                        .all(tx)
+                        .await?
+                        .into_iter()
+                        .collect(),
+                };
+
+                // take the date from what was stored, entries with invalid dates got skipped
+                let events: Vec<_> = vulnerability_kev::Entity::find()
+                    .filter(vulnerability_kev::Column::VulnerabilityId.is_in(existing))
+                    .order_by_asc(vulnerability_kev::Column::VulnerabilityId)
+                    .all(tx)
+                    .await?
+                    .into_iter()
+                    .map(|entry| Event::KnownExploited {
+                        vulnerability_id: entry.vulnerability_id,
+                        date_added: entry.date_added,
+                    })
+                    .collect();
+
+                Ok::<_, trustify_module_ingestor::service::Error>((result, added.len(), events))
+            })
+            .await;
+
+        let mut report = self.report.lock().await;
+        match result {
+            Ok((result, added, events)) => {
+                log::info!(
+                    "Imported KEV catalog: {added} new entries, {} newly known exploited",
+                    events.len()
+                );
+                for _ in 0..added {
+                    report.tick();
+                }
+                report.extend_messages(
+                    Phase::Upload,
+                    &self.source,
+                    result.warnings.iter().map(Message::from),
+                );
+                for event in events {
+                    report.add_event(event);
+                }
+            }
+            Err(err) => {
+                report.add_error(Phase::Upload, &self.source, err.to_string());
+
+                // had an error, keep the old continuation as active.
+                return Ok(self.continuation);
+            }
+        }
+
+        Ok(LastModified(last_modified))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::runner::common::test::{build_report, mock_get, new_report};
+    use test_context::test_context;
+    use test_log::test;
+    use time::macros::datetime;
+    use trustify_test_context::{TrustifyContext, document_bytes};
+    use wiremock::{MockServer, ResponseTemplate};
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn detect_known_exploited(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        // a previous version of the catalog, and a known vulnerability
+
+        let previous = serde_json::to_vec(&serde_json::json!({
+            "catalogVersion": "2025.10.13",
+            "vulnerabilities": [{
+                "cveID": "CVE-2021-44228",
+                "dateAdded": "2021-12-10",
+            }],
+        }))?;
+        ctx.ingest_read(previous.as_slice()).await?;
+        ctx.graph
+            .ingest_vulnerability("CVE-2023-29020", (), &ctx.db)
+            .await?;
+
+        let server = MockServer::start().await;
+        mock_get(
+            &server,
+            "/kev.json",
+            ResponseTemplate::new(200).set_body_bytes(
+                document_bytes("kev/known_exploited_vulnerabilities.json")
+                    .await?
+                    .to_vec(),
+            ),
+        )
+        .await;
+
+        let report = new_report();
+        KevWalker::new(
+            format!("{}/kev.json", server.uri()),
+            ctx.ingestor.clone(),
+            ReadWrite::new(ctx.db.clone()),
+            report.clone(),
+        )
+        .run()
+        .await?;
+
+        let report = build_report(report);
+        assert_eq!(report.number_of_items, 1);
+        assert_eq!(
+            report.events,
+            vec![Event::KnownExploited {
+                vulnerability_id: "CVE-2023-29020".into(),
+                date_added: datetime!(2025-10-01 0:00 UTC),
+            }]
+        );
+
+        Ok(())
+    }
+}
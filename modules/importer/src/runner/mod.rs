@@ -1,28 +1,46 @@
 pub mod clearly_defined_curation;
+pub mod clearly_defined_harvest;
 
 pub mod clearly_defined;
 pub mod common;
 pub mod context;
 pub mod csaf;
+pub mod csaf_rolie;
 pub mod cve;
 pub mod cwe;
+pub mod dependency_track;
+pub mod kev;
 pub mod osv;
 pub mod progress;
 pub mod quay;
+pub mod red_hat_vex;
 pub mod report;
 pub mod sbom;
 
 use crate::{
-    model::ImporterConfiguration,
-    runner::{context::RunContext, report::ScannerError},
+    model::{CommonImporter, GitCredentials, ImporterConfiguration, Proxy, SecretKey},
+    runner::{
+        common::{
+            ingestor::Ingestor,
+            metrics::IngestMetrics,
+            proxy,
+            retry::{Retry, fetcher_retries},
+            throttle::Throttle,
+            walker::Credentials,
+        },
+        context::RunContext,
+        report::{DryRunSummary, ReportBuilder, ScannerError},
+    },
     server::RunOutput,
 };
-use std::path::PathBuf;
+use std::{path::PathBuf, sync::Arc};
 use time::OffsetDateTime;
 use tracing::instrument;
 use trustify_common::db::ReadWrite;
 use trustify_module_analysis::service::AnalysisService;
+use trustify_module_ingestor::{graph::Graph, service::IngestorService};
 use trustify_module_storage::service::dispatch::DispatchBackend;
+use walker_common::fetcher::{Fetcher, FetcherOptions};
 
 #[derive(Clone)]
 pub struct ImportRunner {
@@ -30,6 +48,10 @@ pub struct ImportRunner {
     pub storage: DispatchBackend,
     pub working_dir: Option<PathBuf>,
     pub analysis: Option<AnalysisService>,
+    /// The proxy for all importers, unless they configure their own
+    pub proxy: Option<Proxy>,
+    /// The key for decrypting the secrets of importer configurations
+    pub secret_key: Option<SecretKey>,
 }
 
 impl ImportRunner {
@@ -60,15 +82,113 @@ impl ImportRunner {
                 self.run_once_clearly_defined_curation(context, clearly_defined, continuation)
                     .await
             }
+            ImporterConfiguration::ClearlyDefinedHarvest(clearly_defined) => {
+                self.run_once_clearly_defined_harvest(context, clearly_defined)
+                    .await
+            }
             ImporterConfiguration::Cwe(cwe) => {
                 self.run_once_cwe_catalog(context, cwe, continuation).await
             }
             ImporterConfiguration::Quay(quay) => {
                 self.run_once_quay(context, quay, continuation).await
             }
+            ImporterConfiguration::DependencyTrack(dependency_track) => {
+                self.run_once_dependency_track(context, dependency_track, continuation)
+                    .await
+            }
+            ImporterConfiguration::RedHatVex(red_hat_vex) => {
+                self.run_once_red_hat_vex(context, red_hat_vex, continuation)
+                    .await
+            }
+            ImporterConfiguration::CsafRolie(csaf_rolie) => {
+                self.run_once_csaf_rolie(context, csaf_rolie, continuation)
+                    .await
+            }
+            ImporterConfiguration::Kev(kev) => self.run_once_kev(context, kev, continuation).await,
         }
     }
 
+    /// Create the ingestor and the report builder for a run of the named importer.
+    ///
+    /// For a dry run, the ingestor only records the documents in the report. Otherwise, it records
+    /// the ingested documents in the metrics of the importer. If the importer has rate limits, the
+    /// ingestor paces the documents, and the delays get recorded in the report. If the importer
+    /// has a retry policy, the ingestor retries operations accordingly.
+    fn ingestor(&self, name: &str, common: &CommonImporter) -> (Ingestor, ReportBuilder) {
+        let (ingestor, report) = match common.dry_run {
+            true => {
+                let summary = Arc::new(parking_lot::Mutex::new(DryRunSummary::default()));
+                (
+                    Ingestor::dry_run(summary.clone()),
+                    ReportBuilder::new().dry_run(summary),
+                )
+            }
+            false => (
+                Ingestor::from(IngestorService::new(
+                    Graph::new(),
+                    self.storage.clone(),
+                    self.analysis.clone(),
+                ))
+                .metrics(IngestMetrics::new(name)),
+                ReportBuilder::new(),
+            ),
+        };
+
+        let ingestor = ingestor.retry(common.retry.as_ref().map(Retry::new).unwrap_or_default());
+
+        match &common.rate_limit {
+            Some(limit) => {
+                let throttle = Throttle::new(limit);
+                let report = report.throttle(throttle.summary());
+                (ingestor.throttle(throttle), report)
+            }
+            None => (ingestor, report),
+        }
+    }
+
+    /// The proxy of an importer, falling back to the global one.
+    fn proxy(&self, common: &CommonImporter) -> Option<Proxy> {
+        common.proxy.clone().or_else(|| self.proxy.clone())
+    }
+
+    /// Create an HTTP client for an importer, using its proxy.
+    fn http_client(&self, common: &CommonImporter) -> anyhow::Result<reqwest::Client> {
+        Ok(proxy::http_client(self.proxy(common).as_ref())?.build()?)
+    }
+
+    /// Create the fetcher of a walker, using the proxy of an importer.
+    ///
+    /// A fetcher using a custom HTTP client can't be configured, so with a proxy it uses the default
+    /// number of retries.
+    async fn fetcher(
+        &self,
+        common: &CommonImporter,
+        fetch_retries: Option<usize>,
+    ) -> Result<Fetcher, ScannerError> {
+        if self.proxy(common).is_some() {
+            if fetch_retries.is_some() || common.retry.is_some() {
+                log::warn!("Using the default number of retries for fetching through a proxy");
+            }
+            return Ok(Fetcher::from(self.http_client(common)?));
+        }
+
+        Fetcher::new(
+            FetcherOptions::new().retries(fetcher_retries(fetch_retries, common.retry.as_ref())),
+        )
+        .await
+        .map_err(ScannerError::Critical)
+    }
+
+    /// The credentials for a git repository, with their secrets decrypted.
+    fn git_credentials(
+        &self,
+        credentials: Option<&GitCredentials>,
+    ) -> anyhow::Result<Option<Credentials>> {
+        Ok(credentials
+            .map(|credentials| credentials.reveal(self.secret_key.as_ref()))
+            .transpose()?)
+    }
+
     async fn create_working_dir(
         &self,
         r#type: &str,
@@ -1,7 +1,7 @@
 use crate::runner::common::Error;
 use crate::runner::common::{
     processing_error::ProcessingError,
-    walker::{CallbackError, Callbacks, Handler, HandlerError},
+    walker::{CallbackError, Callbacks, Continuation, Handler, HandlerError},
 };
 use std::fs::File;
 use std::io::Read;
@@ -35,6 +35,20 @@ where
             }
         }
     }
+
+    fn deleted(&self, relative_path: &Path) -> Result<(), HandlerError<Self::Error>> {
+        self.0.deleted(relative_path).map_err(|err| match err {
+            CallbackError::Processing(err) => HandlerError::Processing(Error::Processing(err)),
+            CallbackError::Canceled => HandlerError::Canceled,
+        })
+    }
+
+    fn checkpoint(&self, continuation: &Continuation) -> Result<(), HandlerError<Self::Error>> {
+        self.0.checkpoint(continuation).map_err(|err| match err {
+            CallbackError::Processing(err) => HandlerError::Processing(Error::Processing(err)),
+            CallbackError::Canceled => HandlerError::Canceled,
+        })
+    }
 }
 
 impl<C> OsvHandler<C>
@@ -1,47 +1,84 @@
 mod handler;
 
 use crate::{
-    model::OsvImporter,
+    model::{OsvImporter, UpstreamDeletion},
     runner::{
         RunOutput,
-        common::walker::{CallbackError, Callbacks, GitWalker},
+        common::{
+            deletion,
+            filter::PathFilter,
+            ingestor::Ingestor,
+            pool::{Submitter, worker_pool},
+            retry::Retry,
+            walker::{CallbackError, Callbacks, Continuation, DEFAULT_DEPTH, GitWalker},
+        },
         context::RunContext,
         report::{Phase, ReportBuilder, ScannerError},
     },
 };
+use anyhow::anyhow;
 use chrono::Datelike;
 use handler::OsvHandler;
+use osv::schema::Vulnerability;
 use parking_lot::Mutex;
 use std::collections::HashSet;
 use std::{path::Path, path::PathBuf, sync::Arc};
+use time::OffsetDateTime;
 use tokio::runtime::Handle;
+use tokio_util::bytes::Bytes;
 use tracing::instrument;
 use trustify_common::db::ReadWrite;
 use trustify_entity::labels::Labels;
-use trustify_module_ingestor::service::Cache;
-use trustify_module_ingestor::{
-    graph::Graph,
-    service::{Format, IngestorService, advisory::osv::parse},
-};
+use trustify_module_ingestor::service::{Cache, Format, advisory::osv::parse};
+
+/// A document, to be stored by the workers
+struct Document {
+    path: PathBuf,
+    data: Bytes,
+}
 
 struct Context<C: RunContext + 'static> {
     context: C,
+    on_deleted: UpstreamDeletion,
+    report: Arc<Mutex<ReportBuilder>>,
+    ingestor: Ingestor,
+    db: ReadWrite,
+    documents: Submitter<Document>,
+}
+
+/// Stores the documents, running in the workers
+struct Storage {
     source: String,
+    importer: String,
     labels: Labels,
     years: HashSet<u16>,
     start_year: Option<u16>,
+    modified_since: Option<OffsetDateTime>,
+    ecosystems: HashSet<String>,
     report: Arc<Mutex<ReportBuilder>>,
-    ingestor: IngestorService,
+    ingestor: Ingestor,
     db: ReadWrite,
 }
 
-impl<C: RunContext> Context<C> {
-    fn store(&self, path: &Path, data: Vec<u8>) -> anyhow::Result<()> {
+impl Storage {
+    async fn process(&self, Document { path, data }: Document) {
         self.report.lock().tick();
 
-        // apply year based filter, we need to parse
-        if !self.years.is_empty() || self.start_year.is_some() {
-            let osv = parse(&data)?;
+        if let Err(err) = self.store(&path, &data).await {
+            self.report
+                .lock()
+                .add_error(Phase::Upload, path.to_string_lossy(), err.to_string());
+        }
+    }
+
+    async fn store(&self, path: &Path, data: &Bytes) -> anyhow::Result<()> {
+        // apply year, modification, and ecosystem based filters, we need to parse
+        if !self.years.is_empty()
+            || self.start_year.is_some()
+            || self.modified_since.is_some()
+            || !self.ecosystems.is_empty()
+        {
+            let osv = parse(data)?;
 
             let year = osv
                 .published
@@ -60,33 +97,95 @@ impl<C: RunContext> Context<C> {
             {
                 return Ok(());
             }
+
+            // check the modification timestamp
+            if let Some(since) = self.modified_since
+                && !modified_since(&osv, since)
+            {
+                return Ok(());
+            }
+
+            // check the set of ecosystems
+            if !self.ecosystems.is_empty() && !affects_ecosystems(&osv, &self.ecosystems) {
+                return Ok(());
+            }
         }
 
-        Handle::current().block_on(async {
-            self.db
-                .transaction(async |tx| {
-                    self.ingestor
-                        .ingest(
-                            &data,
-                            Format::OSV,
-                            Labels::new()
-                                .add("source", &self.source)
-                                .add("importer", self.context.name())
-                                .add("file", path.to_string_lossy())
-                                .extend(self.labels.0.clone()),
-                            None,
-                            Cache::Skip,
-                            tx,
-                        )
-                        .await
-                })
-                .await
-        })?;
+        self.ingestor
+            .retrying(async || {
+                self.db
+                    .transaction(async |tx| {
+                        self.ingestor
+                            .ingest(
+                                data.clone(),
+                                Format::OSV,
+                                Labels::new()
+                                    .add("source", &self.source)
+                                    .add("importer", &self.importer)
+                                    .add("file", path.to_string_lossy())
+                                    .extend(self.labels.0.clone()),
+                                None,
+                                Cache::Skip,
+                                tx,
+                            )
+                            .await
+                    })
+                    .await
+            })
+            .await?;
 
         Ok(())
     }
 }
 
+impl<C: RunContext> Context<C> {
+    fn delete(&self, path: &Path) -> anyhow::Result<()> {
+        if self.ingestor.is_dry_run() {
+            return Ok(());
+        }
+
+        let file = path.to_string_lossy();
+        let updated = Handle::current().block_on(deletion::apply(
+            self.on_deleted,
+            self.context.name(),
+            &file,
+            &self.db,
+        ))?;
+
+        if updated > 0 {
+            log::info!("Handled upstream deletion of {file}: {updated} advisories");
+        }
+
+        Ok(())
+    }
+}
+
+/// Check if the advisory was modified at or after the provided timestamp.
+fn modified_since(osv: &Vulnerability, since: OffsetDateTime) -> bool {
+    osv.modified.timestamp() >= since.unix_timestamp()
+}
+
+/// Check if the advisory affects a package of one of the ecosystems.
+///
+/// An ecosystem matches by its full name (like `Debian:11`) as well as by its name without the
+/// suffix (like `Debian`).
+fn affects_ecosystems(osv: &Vulnerability, ecosystems: &HashSet<String>) -> bool {
+    osv.affected
+        .iter()
+        .flatten()
+        .filter_map(|affected| affected.package.as_ref())
+        .filter_map(|package| match serde_json::to_value(&package.ecosystem) {
+            Ok(serde_json::Value::String(ecosystem)) => Some(ecosystem),
+            _ => None,
+        })
+        .any(|ecosystem| {
+            ecosystems.contains(&ecosystem)
+                || ecosystem
+                    .split_once(':')
+                    .is_some_and(|(name, _)| ecosystems.contains(name))
+        })
+}
+
 impl<C: RunContext> Callbacks<Vec<u8>> for Context<C> {
     fn loading_error(&self, path: PathBuf, message: String) {
         self.report
@@ -95,7 +194,21 @@ impl<C: RunContext> Callbacks<Vec<u8>> for Context<C> {
     }
 
     fn process(&self, path: &Path, osv: Vec<u8>) -> Result<(), CallbackError> {
-        if let Err(err) = self.store(path, osv) {
+        let document = Document {
+            path: path.to_path_buf(),
+            data: osv.into(),
+        };
+        if !self.documents.submit_sync(document) {
+            return Err(CallbackError::Processing(anyhow!(
+                "storage workers stopped"
+            )));
+        }
+
+        self.context.check_canceled_sync(|| CallbackError::Canceled)
+    }
+
+    fn deleted(&self, path: &Path) -> Result<(), CallbackError> {
+        if let Err(err) = self.delete(path) {
             self.report
                 .lock()
                 .add_error(Phase::Upload, path.to_string_lossy(), err.to_string());
@@ -104,6 +217,25 @@ impl<C: RunContext> Callbacks<Vec<u8>> for Context<C> {
         self.context.check_canceled_sync(|| CallbackError::Canceled)
     }
 
+    fn checkpoint(&self, continuation: &Continuation) -> Result<(), CallbackError> {
+        if self.ingestor.is_dry_run() {
+            return Ok(());
+        }
+
+        // the documents before the checkpoint must be stored first
+        if !self.documents.flush_sync() {
+            return Err(CallbackError::Processing(anyhow!(
+                "storage workers stopped"
+            )));
+        }
+
+        let continuation = serde_json::to_value(continuation)
+            .map_err(|err| CallbackError::Processing(err.into()))?;
+        self.context.checkpoint_sync(continuation);
+
+        Ok(())
+    }
+
     fn is_canceled(&self) -> bool {
         self.context.is_canceled_sync()
     }
@@ -117,45 +249,74 @@ impl super::ImportRunner {
         osv: OsvImporter,
         continuation: serde_json::Value,
     ) -> Result<RunOutput, ScannerError> {
-        let ingestor =
-            IngestorService::new(Graph::new(), self.storage.clone(), self.analysis.clone());
-
-        let report = Arc::new(Mutex::new(ReportBuilder::new()));
+        let (ingestor, report) = self.ingestor(context.name(), &osv);
+        let report = Arc::new(Mutex::new(report));
         let continuation = serde_json::from_value(continuation).unwrap_or_default();
 
         // working dir
 
         let working_dir = self.create_working_dir("osv", &osv.source).await?;
+        let retry = osv.retry.as_ref().map(Retry::new).unwrap_or_default();
+        let proxy = self.proxy(&osv);
+        let filter = PathFilter::from_config(&osv)?;
+        let credentials = self.git_credentials(osv.credentials.as_ref())?;
 
         // progress reporting
 
         let progress = context.progress(format!("Import OSV: {}", osv.source));
 
+        // storage workers
+
+        let (documents, workers) = worker_pool(osv.concurrency.unwrap_or(1));
+        let storage = Storage {
+            source: osv.source.clone(),
+            importer: context.name().to_string(),
+            labels: osv.common.labels,
+            years: osv.years,
+            start_year: osv.start_year,
+            modified_since: osv.modified_since,
+            ecosystems: osv.ecosystems,
+            report: report.clone(),
+            ingestor: ingestor.clone(),
+            db: self.db.clone(),
+        };
+
         // run the walker
 
         let walker = GitWalker::new(
-            osv.source.clone(),
+            osv.source,
             OsvHandler(Context {
                 context,
-                source: osv.source,
-                labels: osv.common.labels,
-                years: osv.years,
-                start_year: osv.start_year,
+                on_deleted: osv.on_deleted,
                 report: report.clone(),
                 ingestor,
                 db: self.db.clone(),
+                documents,
             }),
         )
         .continuation(continuation)
+        .proxy(proxy)
+        .filter(filter)
+        .credentials(credentials)
+        .depth(osv.depth.unwrap_or(DEFAULT_DEPTH))
+        .retry(retry)
         .branch(osv.branch)
+        .tag(osv.tag)
+        .commit(osv.commit)
         .path(osv.path)
         .progress(progress);
 
-        let continuation = match working_dir {
-            Some(working_dir) => walker.working_dir(working_dir).run().await,
-            None => walker.run().await,
-        }
-        .map_err(|err| ScannerError::Critical(err.into()))?;
+        let (result, ()) = tokio::join!(
+            async {
+                match working_dir {
+                    Some(working_dir) => walker.working_dir(working_dir).run().await,
+                    None => walker.run().await,
+                }
+            },
+            workers.run(|document| storage.process(document))
+        );
+
+        let continuation = result.map_err(|err| ScannerError::Critical(err.into()))?;
 
         // extract the report
 
@@ -173,3 +334,49 @@ impl super::ImportRunner {
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+    use time::macros::datetime;
+
+    #[test]
+    fn filter_ecosystems() -> anyhow::Result<()> {
+        let osv: Vulnerability = serde_json::from_value(json!({
+            "id": "TEST-1",
+            "modified": "2024-01-01T00:00:00Z",
+            "affected": [
+                { "package": { "ecosystem": "crates.io", "name": "foo" } },
+                { "package": { "ecosystem": "Debian:11", "name": "bar" } },
+            ]
+        }))?;
+
+        let filter = |ecosystems: &[&str]| {
+            affects_ecosystems(&osv, &ecosystems.iter().map(ToString::to_string).collect())
+        };
+
+        assert!(filter(&["crates.io"]));
+        assert!(filter(&["PyPI", "crates.io"]));
+        assert!(filter(&["Debian"]));
+        assert!(filter(&["Debian:11"]));
+        assert!(!filter(&["Debian:12"]));
+        assert!(!filter(&["PyPI"]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn filter_modified_since() -> anyhow::Result<()> {
+        let osv: Vulnerability = serde_json::from_value(json!({
+            "id": "TEST-1",
+            "modified": "2024-01-01T00:00:00Z",
+        }))?;
+
+        assert!(modified_since(&osv, datetime!(2023-12-31 0:00 UTC)));
+        assert!(modified_since(&osv, datetime!(2024-01-01 0:00 UTC)));
+        assert!(!modified_since(&osv, datetime!(2024-01-01 0:00:01 UTC)));
+
+        Ok(())
+    }
+}
@@ -46,6 +46,20 @@ pub trait ProgressInstance: Sized {
 
     fn increment(&mut self, work: usize) -> impl Future<Output = ()>;
 
+    /// Record a single item which failed to be processed.
+    ///
+    /// The item counts as processed too.
+    fn fail(&mut self) -> impl Future<Output = ()> {
+        self.tick()
+    }
+
+    /// Record a single item which was skipped.
+    ///
+    /// The item counts as processed too.
+    fn skip(&mut self) -> impl Future<Output = ()> {
+        self.tick()
+    }
+
     fn finish(self) -> impl Future<Output = ()>;
 
     fn finish_sync(self) {
@@ -65,6 +79,8 @@ pub struct TracingProgressInstance {
 
     total: usize,
     current: usize,
+    failed: usize,
+    skipped: usize,
 
     start: Instant,
     period: Duration,
@@ -81,6 +97,8 @@ impl Progress for TracingProgress {
             name: self.name.clone(),
             total: work,
             current: 0,
+            failed: 0,
+            skipped: 0,
             start: Instant::now(),
             period: self.period,
             // first report with update
@@ -105,6 +123,8 @@ impl ProgressInstance for TracingProgressInstance {
             tracing::info!(
                 current = self.current,
                 total = self.total,
+                failed = self.failed,
+                skipped = self.skipped,
                 percent = p * 100.0,
                 "{}: {:.2}% (ETA: {})",
                 self.name,
@@ -122,8 +142,24 @@ impl ProgressInstance for TracingProgressInstance {
         }
     }
 
+    async fn fail(&mut self) {
+        self.failed += 1;
+        self.increment(1).await;
+    }
+
+    async fn skip(&mut self) {
+        self.skipped += 1;
+        self.increment(1).await;
+    }
+
     async fn finish(self) {
-        tracing::info!(percent = 100f64, "{}: 100% (complete)", self.name);
+        tracing::info!(
+            percent = 100f64,
+            failed = self.failed,
+            skipped = self.skipped,
+            "{}: 100% (complete)",
+            self.name
+        );
     }
 }
 
@@ -204,4 +240,22 @@ mod test {
 
         assert_eq!(updates, vec![5, 10, 15, 20, 25, 30, 35, 40, 45, 50]);
     }
+
+    #[test_log::test(tokio::test)]
+    async fn counters() {
+        let progress = TracingProgress {
+            name: "test".into(),
+            period: Duration::from_secs(60),
+        };
+        let mut progress = progress.start(10);
+
+        progress.tick().await;
+        progress.fail().await;
+        progress.skip().await;
+        progress.skip().await;
+
+        assert_eq!(progress.current, 4);
+        assert_eq!(progress.failed, 1);
+        assert_eq!(progress.skipped, 2);
+    }
 }
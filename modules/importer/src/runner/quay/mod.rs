@@ -3,15 +3,11 @@ mod walker;
 
 use crate::model::QuayImporter;
 use crate::runner::{
-    RunOutput,
-    context::RunContext,
-    quay::walker::QuayWalker,
-    report::{ReportBuilder, ScannerError},
+    RunOutput, context::RunContext, quay::walker::QuayWalker, report::ScannerError,
 };
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tracing::instrument;
-use trustify_module_ingestor::{graph::Graph, service::IngestorService};
 
 impl super::ImportRunner {
     #[instrument(skip_all, err(level=tracing::Level::INFO))]
@@ -21,10 +17,8 @@ impl super::ImportRunner {
         quay: QuayImporter,
         continuation: serde_json::Value,
     ) -> Result<RunOutput, ScannerError> {
-        let ingestor =
-            IngestorService::new(Graph::new(), self.storage.clone(), self.analysis.clone());
-
-        let report = Arc::new(Mutex::new(ReportBuilder::new()));
+        let (ingestor, report) = self.ingestor(context.name(), &quay);
+        let report = Arc::new(Mutex::new(report));
         let continuation = serde_json::from_value(continuation).unwrap_or_default();
 
         let walker = QuayWalker::new(
@@ -33,6 +27,7 @@ impl super::ImportRunner {
             self.db.clone(),
             report.clone(),
             context,
+            self.proxy(&quay).as_ref(),
         )
         .map_err(|e| ScannerError::Critical(e.into()))?
         .continuation(continuation);
@@ -1,25 +1,34 @@
 pub use oci_client::Reference;
 
-use crate::runner::common::Error;
+use crate::{model::Proxy, runner::common::Error};
+use anyhow::anyhow;
 use oci_client::{
     Client as OciClient,
     client::{ClientConfig, ClientProtocol},
     secrets::RegistryAuth,
 };
 
+/// The artifact types of SBOMs, attached to an image as referrers
+const SBOM_ARTIFACT_TYPES: &[&str] = &["application/spdx+json", "application/vnd.cyclonedx+json"];
+
 pub struct Client {
     client: OciClient,
     auth: RegistryAuth,
 }
 
 impl Client {
-    pub fn new(unencrypted: bool) -> Self {
+    pub fn new(unencrypted: bool, proxy: Option<&Proxy>) -> Self {
         let config = ClientConfig {
             protocol: if unencrypted {
                 ClientProtocol::Http
             } else {
                 ClientProtocol::Https
             },
+            http_proxy: proxy.map(|proxy| proxy.url.clone()),
+            https_proxy: proxy.map(|proxy| proxy.url.clone()),
+            no_proxy: proxy
+                .filter(|proxy| !proxy.no_proxy.is_empty())
+                .map(|proxy| proxy.no_proxy.join(",")),
             ..Default::default()
         };
         Self {
@@ -28,16 +37,51 @@ impl Client {
         }
     }
 
-    pub async fn fetch(&self, reference: &Reference) -> Result<Vec<u8>, Error> {
+    /// Fetch an SBOM attachment, failing if it exceeds the size limit (None is unlimited).
+    pub async fn fetch(&self, reference: &Reference, limit: Option<u64>) -> Result<Vec<u8>, Error> {
         let mut out: Vec<u8> = Vec::new();
         let (manifest, _) = self
             .client
             .pull_image_manifest(reference, &self.auth)
             .await?;
         // per cosign source, sbom attachments should only have one layer
-        self.client
-            .pull_blob(reference, &manifest.layers[0], &mut out)
-            .await?;
+        let layer = manifest
+            .layers
+            .first()
+            .ok_or_else(|| Error::Processing(anyhow!("attachment has no layers")))?;
+        if let Some(limit) = limit
+            && u64::try_from(layer.size).unwrap_or_default() > limit
+        {
+            return Err(Error::Processing(anyhow!(
+                "attachment exceeds the size limit of {limit} bytes"
+            )));
+        }
+        self.client.pull_blob(reference, layer, &mut out).await?;
         Ok(out)
     }
+
+    /// Get the SBOMs attached to an image as referrers
+    pub async fn referrers(&self, image: &Reference) -> Result<Vec<Reference>, Error> {
+        self.client
+            .store_auth_if_needed(image.resolve_registry(), &self.auth)
+            .await;
+        let index = self.client.pull_referrers(image, None).await?;
+        Ok(index
+            .manifests
+            .into_iter()
+            .filter(|entry| {
+                entry
+                    .artifact_type
+                    .as_deref()
+                    .is_some_and(|r#type| SBOM_ARTIFACT_TYPES.contains(&r#type))
+            })
+            .map(|entry| {
+                Reference::with_digest(
+                    image.registry().to_string(),
+                    image.repository().to_string(),
+                    entry.digest,
+                )
+            })
+            .collect())
+    }
 }
@@ -1,8 +1,8 @@
 use super::oci::Reference;
 use crate::{
-    model::QuayImporter,
+    model::{Proxy, QuayImporter},
     runner::{
-        common::Error,
+        common::{Error, ingestor::Ingestor, proxy},
         context::RunContext,
         progress::{Progress, ProgressInstance},
         quay::oci,
@@ -13,13 +13,17 @@ use anyhow::anyhow;
 use futures::{Stream, TryStreamExt, stream};
 use reqwest::header;
 use serde::Deserialize;
-use std::{collections::HashMap, future, sync::Arc};
+use std::{
+    collections::{BTreeSet, HashMap},
+    future,
+    sync::Arc,
+};
 use time::OffsetDateTime;
 use tokio::sync::Mutex;
 use tracing::instrument;
 use trustify_common::db::ReadWrite;
 use trustify_entity::labels::Labels;
-use trustify_module_ingestor::service::{Cache, Format, IngestorService};
+use trustify_module_ingestor::service::{Cache, Format};
 
 /// Max number of concurrent repository fetches
 const DEFAULT_CONCURRENCY: usize = 32;
@@ -30,7 +34,7 @@ pub struct LastModified(Option<i64>);
 pub struct QuayWalker<C: RunContext> {
     continuation: LastModified,
     importer: QuayImporter,
-    ingestor: IngestorService,
+    ingestor: Ingestor,
     db: ReadWrite,
     report: Arc<Mutex<ReportBuilder>>,
     client: reqwest::Client,
@@ -41,23 +45,25 @@ pub struct QuayWalker<C: RunContext> {
 impl<C: RunContext> QuayWalker<C> {
     pub fn new(
         importer: QuayImporter,
-        ingestor: IngestorService,
+        ingestor: impl Into<Ingestor>,
         db: ReadWrite,
         report: Arc<Mutex<ReportBuilder>>,
         context: C,
+        proxy: Option<&Proxy>,
     ) -> Result<Self, Error> {
+        let client = proxy::http_client(proxy)?;
         let client = match importer.api_token {
-            Some(ref token) => authorized_client(token)?,
+            Some(ref token) => authorized_client(client, token)?,
             None => {
                 log::warn!("Quay API token not configured; results may be limited");
-                Default::default()
+                client.build()?
             }
         };
-        let oci = oci::Client::new(importer.unencrypted);
+        let oci = oci::Client::new(importer.unencrypted, proxy);
         Ok(Self {
             continuation: LastModified(None),
             importer,
-            ingestor,
+            ingestor: ingestor.into(),
             db,
             report,
             client,
@@ -87,14 +93,18 @@ impl<C: RunContext> QuayWalker<C> {
             ))
             .await;
 
-        let references = self.sboms().await?;
-        let mut progress = progress.start(references.len());
-
-        for reference in references {
-            if let Some(bytes) = self.fetch(&reference).await {
-                self.store(&reference, &bytes).await;
+        let sboms = self.sboms().await?;
+        let mut progress = progress.start(sboms.len());
+
+        for sbom in sboms {
+            let stored = match self.fetch(&sbom.reference).await {
+                Some(bytes) => self.store(&sbom, bytes).await,
+                None => false,
+            };
+            match stored {
+                true => progress.tick().await,
+                false => progress.fail().await,
             }
-            progress.tick().await;
             if self.context.is_canceled().await {
                 return Err(Error::Canceled);
             }
@@ -108,7 +118,8 @@ impl<C: RunContext> QuayWalker<C> {
 
     async fn fetch(&self, reference: &Reference) -> Option<Vec<u8>> {
         log::debug!("Fetching reference: {reference}");
-        match self.oci.fetch(reference).await {
+        let limit = self.importer.size_limit.map(|limit| limit.as_u64());
+        match self.oci.fetch(reference, limit).await {
             Ok(bytes) => Some(bytes),
             Err(err) => {
                 log::warn!("Error fetching {reference}: {err}");
@@ -119,23 +130,24 @@ impl<C: RunContext> QuayWalker<C> {
         }
     }
 
-    async fn store(&self, file: impl std::fmt::Display, data: &[u8]) {
+    /// Store a document, returning `true` if it got stored.
+    async fn store(&self, sbom: &Sbom, data: Vec<u8>) -> bool {
+        let file = &sbom.reference;
+        let mut labels = Labels::new()
+            .add("source", &self.importer.source)
+            .add("importer", "Quay")
+            .add("file", file.to_string())
+            .add("repository", &sbom.repository);
+        if let Some(digest) = &sbom.digest {
+            labels = labels.add("digest", digest);
+        }
+        let labels = labels.extend(self.importer.labels.0.clone());
+
         let result = self
             .db
             .transaction(async |tx| {
                 self.ingestor
-                    .ingest(
-                        data,
-                        Format::SBOM,
-                        Labels::new()
-                            .add("source", &self.importer.source)
-                            .add("importer", "Quay")
-                            .add("file", file.to_string())
-                            .extend(self.importer.labels.0.clone()),
-                        None,
-                        Cache::Skip,
-                        tx,
-                    )
+                    .ingest(data, Format::SBOM, labels, None, Cache::Skip, tx)
                     .await
             })
             .await;
@@ -147,34 +159,69 @@ impl<C: RunContext> QuayWalker<C> {
                 report.extend_messages(
                     Phase::Upload,
                     file.to_string(),
-                    result.warnings.iter().map(Message::warning),
+                    result.warnings.iter().map(Message::from),
                 );
+                true
             }
             Err(err) => {
                 log::warn!("Error storing {file}: {err}");
                 report.add_error(Phase::Upload, file.to_string(), err.to_string());
+                false
             }
         }
     }
 
-    async fn sboms(&self) -> Result<Vec<Reference>, Error> {
+    async fn sboms(&self) -> Result<Vec<Sbom>, Error> {
         self.repositories(Some(String::new()))
             .try_filter(|repo| future::ready(self.ingestible(repo)))
-            .map_ok(|repo| self.repository_details(repo))
+            .map_ok(|repo| self.repository_sboms(repo))
             .try_buffer_unordered(self.importer.concurrency.unwrap_or(DEFAULT_CONCURRENCY))
-            .map_ok(|repo| {
+            .map_ok(|sboms| {
                 stream::iter(
-                    repo.sboms(&self.importer.source)
-                        .into_iter()
-                        .map(Ok::<_, Error>), // try_flatten expects Results
+                    sboms.into_iter().map(Ok::<_, Error>), // try_flatten expects Results
                 )
             })
             .try_flatten()
-            .try_filter_map(|sbom| future::ready(Ok(self.valid(&sbom).then_some(sbom.reference))))
+            .try_filter(|sbom| future::ready(self.valid(sbom)))
             .try_collect()
             .await
     }
 
+    /// The SBOMs of a repository, attached using cosign and, if enabled, as referrers
+    async fn repository_sboms(&self, repo: Repository) -> Result<Vec<Sbom>, Error> {
+        let repo = self.repository_details(repo).await?;
+        let mut sboms = repo.sboms(&self.importer.source);
+        if self.importer.referrers {
+            for (image, digest) in repo.images(&self.importer.source) {
+                sboms.extend(self.referrers(&repo, &image, digest).await);
+            }
+        }
+        Ok(sboms)
+    }
+
+    /// The SBOMs attached to an image as referrers
+    async fn referrers(&self, repo: &Repository, image: &Reference, digest: String) -> Vec<Sbom> {
+        log::debug!("Fetching referrers of: {image}");
+        match self.oci.referrers(image).await {
+            Ok(references) => references
+                .into_iter()
+                .map(|reference| Sbom {
+                    reference,
+                    // the size is only known once the manifest is fetched
+                    size: 0,
+                    repository: repo.path(),
+                    digest: Some(digest.clone()),
+                })
+                .collect(),
+            Err(err) => {
+                log::warn!("Error fetching referrers of {image}: {err}");
+                let mut report = self.report.lock().await;
+                report.add_error(Phase::Retrieval, image.to_string(), err.to_string());
+                vec![]
+            }
+        }
+    }
+
     fn repositories(&self, page: Option<String>) -> impl Stream<Item = Result<Repository, Error>> {
         stream::try_unfold(page, async |state| match state {
             Some(page) => {
@@ -250,15 +297,16 @@ impl<C: RunContext> QuayWalker<C> {
     }
 }
 
-fn authorized_client(token: &str) -> Result<reqwest::Client, Error> {
+fn authorized_client(
+    client: reqwest::ClientBuilder,
+    token: &str,
+) -> Result<reqwest::Client, Error> {
     let token = format!("Bearer {token}");
     let mut auth_value = header::HeaderValue::from_str(&token)?;
     auth_value.set_sensitive(true);
     let mut headers = header::HeaderMap::new();
     headers.insert(header::AUTHORIZATION, auth_value);
-    Ok(reqwest::Client::builder()
-        .default_headers(headers)
-        .build()?)
+    Ok(client.default_headers(headers).build()?)
 }
 
 #[derive(Debug, Deserialize)]
@@ -271,6 +319,16 @@ struct Repository {
 }
 
 impl Repository {
+    /// The path of the repository in the registry, including the namespace
+    fn path(&self) -> String {
+        format!(
+            "{}/{}",
+            self.namespace.as_deref().unwrap_or_default(),
+            self.name.as_deref().unwrap_or_default()
+        )
+    }
+
+    /// The SBOMs attached using cosign, tagged like `sha256-<image digest>.sbom`
     fn sboms(&self, registry: &str) -> Vec<Sbom> {
         match &self.tags {
             Some(tags) => tags
@@ -279,19 +337,45 @@ impl Repository {
                 .map(|t| Sbom {
                     reference: Reference::with_tag(
                         registry.to_string(),
-                        format!(
-                            "{}/{}",
-                            self.namespace.clone().unwrap_or_default(),
-                            self.name.clone().unwrap_or_default()
-                        ),
+                        self.path(),
                         t.name.clone(),
                     ),
                     size: t.size.unwrap_or(u64::MAX),
+                    repository: self.path(),
+                    digest: attached_to(&t.name),
                 })
                 .collect(),
             None => vec![],
         }
     }
+
+    /// The images of the repository, along with their digest, excluding cosign attachments
+    fn images(&self, registry: &str) -> Vec<(Reference, String)> {
+        let digests: BTreeSet<_> = self
+            .tags
+            .iter()
+            .flat_map(|tags| tags.values())
+            .filter(|t| attached_to(&t.name).is_none())
+            .filter_map(|t| t.manifest_digest.clone())
+            .collect();
+
+        digests
+            .into_iter()
+            .map(|digest| {
+                (
+                    Reference::with_digest(registry.to_string(), self.path(), digest.clone()),
+                    digest,
+                )
+            })
+            .collect()
+    }
+}
+
+/// The digest of the image a cosign attachment belongs to, taken from its tag
+fn attached_to(tag: &str) -> Option<String> {
+    let (digest, _) = tag.split_once('.')?;
+    let digest = digest.strip_prefix("sha256-")?;
+    Some(format!("sha256:{digest}"))
 }
 
 #[derive(Debug, Deserialize)]
@@ -304,20 +388,27 @@ struct Batch {
 struct Tag {
     name: String,
     size: Option<u64>,
+    manifest_digest: Option<String>,
 }
 
 #[derive(Debug)]
 struct Sbom {
     reference: Reference,
     size: u64,
+    /// The repository of the image
+    repository: String,
+    /// The digest of the image the SBOM is attached to
+    digest: Option<String>,
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use sea_orm::EntityTrait;
     use test_context::test_context;
     use test_log::test;
     use trustify_common::db::ReadWrite;
+    use trustify_entity::sbom;
     use trustify_test_context::TrustifyContext;
     use wiremock::{
         Mock, MockServer, ResponseTemplate,
@@ -338,6 +429,7 @@ mod test {
             ReadWrite::new(ctx.db.clone()),
             Arc::new(Mutex::new(ReportBuilder::new())),
             (),
+            None,
         )?
         .continuation(LastModified(Some(
             OffsetDateTime::now_utc().unix_timestamp(),
@@ -398,6 +490,7 @@ mod test {
             ReadWrite::new(ctx.db.clone()),
             report.clone(),
             (),
+            None,
         )?;
         walker.run().await?;
 
@@ -405,9 +498,33 @@ mod test {
         assert_eq!(8, report.number_of_items);
         assert_eq!(0, report.messages.len());
 
+        // the labels record the image
+        for sbom in sbom::Entity::find().all(&ctx.db).await? {
+            assert!(
+                sbom.labels
+                    .get("repository")
+                    .is_some_and(|repo| repo.starts_with("redhat-user-workloads/"))
+            );
+            assert!(
+                sbom.labels
+                    .get("digest")
+                    .is_some_and(|digest| digest.starts_with("sha256:"))
+            );
+        }
+
         Ok(())
     }
 
+    #[test]
+    fn cosign_attachment() {
+        assert_eq!(
+            attached_to("sha256-1e52a468c89471a4.sbom").as_deref(),
+            Some("sha256:1e52a468c89471a4")
+        );
+        assert_eq!(attached_to("latest"), None);
+        assert_eq!(attached_to("v1.0.0"), None);
+    }
+
     #[test_context(TrustifyContext)]
     #[test(tokio::test)]
     async fn missing_repo_and_sboms(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
@@ -442,6 +559,7 @@ mod test {
             ReadWrite::new(ctx.db.clone()),
             report.clone(),
             (),
+            None,
         )?;
         walker.run().await?;
 
@@ -465,6 +583,7 @@ mod test {
             ReadWrite::new(ctx.db.clone()),
             Arc::new(Mutex::new(ReportBuilder::new())),
             (),
+            None,
         )?;
         assert!(walker.run().await.is_err());
 
@@ -0,0 +1,60 @@
+mod walker;
+
+use crate::model::RedHatVexImporter;
+use crate::runner::{
+    RunOutput, common::filter::PathFilter, context::RunContext,
+    red_hat_vex::walker::RedHatVexWalker, report::ScannerError,
+};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::instrument;
+
+impl super::ImportRunner {
+    #[instrument(skip_all, err(level=tracing::Level::INFO))]
+    pub async fn run_once_red_hat_vex(
+        &self,
+        context: impl RunContext + 'static,
+        red_hat_vex: RedHatVexImporter,
+        continuation: serde_json::Value,
+    ) -> Result<RunOutput, ScannerError> {
+        let (ingestor, report) = self.ingestor(context.name(), &red_hat_vex);
+        let report = Arc::new(Mutex::new(report));
+        let continuation = serde_json::from_value(continuation).unwrap_or_default();
+
+        let client = self.http_client(&red_hat_vex)?;
+        let filter = PathFilter::from_config(&red_hat_vex)?;
+
+        let walker = RedHatVexWalker::new(
+            red_hat_vex,
+            ingestor,
+            self.db.clone(),
+            report.clone(),
+            context,
+        )
+        .client(client)
+        .filter(filter)
+        .continuation(continuation);
+
+        match walker.run().await {
+            Ok(continuation) => {
+                // extract the report
+                let report = match Arc::try_unwrap(report) {
+                    Ok(report) => report.into_inner(),
+                    Err(report) => report.lock().await.clone(),
+                }
+                .build();
+                Ok(RunOutput {
+                    report,
+                    continuation: serde_json::to_value(continuation).ok(),
+                })
+            }
+            Err(err) => Err(ScannerError::Normal {
+                err: err.into(),
+                output: RunOutput {
+                    report: report.lock().await.clone().build(),
+                    continuation: None,
+                },
+            }),
+        }
+    }
+}
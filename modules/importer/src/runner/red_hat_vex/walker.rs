@@ -0,0 +1,390 @@
+use crate::{
+    model::RedHatVexImporter,
+    runner::{
+        common::{Error, filter::PathFilter, ingestor::Ingestor},
+        context::RunContext,
+        progress::{Progress, ProgressInstance},
+        report::{Message, Phase, ReportBuilder, Severity},
+    },
+};
+use anyhow::anyhow;
+use std::{io::Read, sync::Arc};
+use time::{
+    Date, OffsetDateTime,
+    format_description::well_known::{Iso8601, Rfc3339},
+};
+use tokio::{
+    runtime::Handle,
+    sync::{Mutex, mpsc},
+};
+use tokio_util::bytes::{Buf, Bytes};
+use tracing::instrument;
+use trustify_common::db::ReadWrite;
+use trustify_entity::labels::Labels;
+use trustify_module_ingestor::service::{Cache, Format};
+use url::Url;
+
+/// The file containing the name of the latest full archive
+const ARCHIVE_LATEST: &str = "archive_latest.txt";
+/// The file listing all changed documents, along with their modification timestamp
+const CHANGES: &str = "changes.csv";
+
+/// The number of archive entries buffered between reading the archive and ingesting them
+const BUFFER: usize = 16;
+
+/// The most recent change which was processed
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct LastChange(
+    #[serde(default, with = "time::serde::rfc3339::option")] Option<OffsetDateTime>,
+);
+
+pub struct RedHatVexWalker<C: RunContext> {
+    continuation: LastChange,
+    importer: RedHatVexImporter,
+    ingestor: Ingestor,
+    db: ReadWrite,
+    report: Arc<Mutex<ReportBuilder>>,
+    context: C,
+    client: reqwest::Client,
+    filter: PathFilter,
+}
+
+impl<C: RunContext> RedHatVexWalker<C> {
+    pub fn new(
+        importer: RedHatVexImporter,
+        ingestor: impl Into<Ingestor>,
+        db: ReadWrite,
+        report: Arc<Mutex<ReportBuilder>>,
+        context: C,
+    ) -> Self {
+        Self {
+            continuation: LastChange(None),
+            importer,
+            ingestor: ingestor.into(),
+            db,
+            report,
+            context,
+            client: Default::default(),
+            filter: Default::default(),
+        }
+    }
+
+    /// Set the HTTP client for fetching the documents.
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Set the filter for the paths of documents.
+    pub fn filter(mut self, filter: PathFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Set a continuation token from a previous run.
+    pub fn continuation(mut self, continuation: LastChange) -> Self {
+        self.continuation = continuation;
+        self
+    }
+
+    /// Run the walker
+    ///
+    /// Without a previous change, the latest full archive is imported first. Afterward, all
+    /// documents which changed since then are imported individually.
+    #[instrument(skip(self), err(level=tracing::Level::INFO))]
+    pub async fn run(self) -> Result<LastChange, Error> {
+        let base = self.base()?;
+
+        let since = match self.continuation.0 {
+            Some(since) => Some(since),
+            None => self.process_archive(&base).await?,
+        };
+
+        let changes = self.changes(&base, since).await?;
+
+        let progress = self
+            .context
+            .progress(format!("Import Red Hat VEX changes from: {base}"));
+        let mut progress = progress.start(changes.len());
+
+        let mut last_change = since;
+        for (path, modified) in changes {
+            let stored = match self.fetch(&base, &path).await {
+                Ok(data) => self.store(&path, data).await,
+                Err(err) => {
+                    self.report
+                        .lock()
+                        .await
+                        .add_error(Phase::Retrieval, &path, err.to_string());
+                    false
+                }
+            };
+
+            last_change = last_change.max(Some(modified));
+
+            match stored {
+                true => progress.tick().await,
+                false => progress.fail().await,
+            }
+            if self.context.is_canceled().await {
+                return Err(Error::Canceled);
+            }
+        }
+
+        progress.finish().await;
+
+        Ok(LastChange(last_change))
+    }
+
+    /// The base URL, which is a directory
+    fn base(&self) -> Result<Url, Error> {
+        let mut source = self.importer.source.clone();
+        if !source.ends_with('/') {
+            source.push('/');
+        }
+
+        Url::parse(&source)
+            .map_err(|err| Error::Processing(anyhow!("invalid source URL '{source}': {err}")))
+    }
+
+    async fn fetch(&self, base: &Url, path: &str) -> Result<Bytes, Error> {
+        let url = base
+            .join(path)
+            .map_err(|err| Error::Processing(anyhow!("invalid path '{path}': {err}")))?;
+
+        Ok(self
+            .client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?)
+    }
+
+    /// Import the latest full archive, returning the date it was created, if known.
+    async fn process_archive(&self, base: &Url) -> Result<Option<OffsetDateTime>, Error> {
+        let name = self.fetch(base, ARCHIVE_LATEST).await?;
+        let name = std::str::from_utf8(&name)?.trim().to_string();
+
+        log::info!("Importing Red Hat VEX archive: {name}");
+
+        let date = archive_date(&name);
+        let data = self.fetch(base, &name).await?;
+
+        let (tx, mut rx) = mpsc::channel(BUFFER);
+        let reader = Handle::current().spawn_blocking(move || read_archive(data, tx));
+
+        while let Some((path, data)) = rx.recv().await {
+            if self.filter.matches(&path) {
+                self.store(&path, data.into()).await;
+            }
+
+            if self.context.is_canceled().await {
+                return Err(Error::Canceled);
+            }
+        }
+
+        reader.await??;
+
+        Ok(date)
+    }
+
+    /// Get the documents which changed after the provided timestamp
+    async fn changes(
+        &self,
+        base: &Url,
+        since: Option<OffsetDateTime>,
+    ) -> Result<Vec<(String, OffsetDateTime)>, Error> {
+        let data = self.fetch(base, CHANGES).await?;
+
+        let mut changes = vec![];
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(data.reader());
+
+        for record in reader.deserialize::<(String, String)>() {
+            let (path, modified) = record.map_err(|err| Error::Processing(err.into()))?;
+
+            let modified = match OffsetDateTime::parse(&modified, &Rfc3339) {
+                Ok(modified) => modified,
+                Err(err) => {
+                    self.report.lock().await.add_message(
+                        Phase::Retrieval,
+                        path,
+                        Severity::Warning,
+                        format!("Invalid modification timestamp '{modified}': {err}"),
+                    );
+                    continue;
+                }
+            };
+
+            if since.is_some_and(|since| modified <= since) || !self.filter.matches(&path) {
+                continue;
+            }
+
+            changes.push((path, modified));
+        }
+
+        Ok(changes)
+    }
+
+    /// Store a document, returning `true` if it got stored.
+    async fn store(&self, path: &str, data: Bytes) -> bool {
+        let labels = Labels::new()
+            .add("source", &self.importer.source)
+            .add("importer", "Red Hat VEX")
+            .add("file", path)
+            .extend(self.importer.labels.0.clone());
+
+        let result = self
+            .db
+            .transaction(async |tx| {
+                self.ingestor
+                    .ingest(data, Format::CSAF, labels, None, Cache::Skip, tx)
+                    .await
+            })
+            .await;
+
+        let mut report = self.report.lock().await;
+        match result {
+            Ok(result) => {
+                log::debug!("Imported {path}");
+                report.tick();
+                report.extend_messages(
+                    Phase::Upload,
+                    path,
+                    result.warnings.iter().map(Message::from),
+                );
+                true
+            }
+            Err(err) => {
+                log::warn!("Error importing {path}: {err}");
+                report.add_error(Phase::Upload, path, err.to_string());
+                false
+            }
+        }
+    }
+}
+
+/// Extract the date from the name of an archive, like `csaf_vex_2024-07-07.tar.zst`
+fn archive_date(name: &str) -> Option<OffsetDateTime> {
+    let date = name.strip_prefix("csaf_vex_")?.strip_suffix(".tar.zst")?;
+    let date = Date::parse(date, &Iso8601::DATE).ok()?;
+    Some(date.midnight().assume_utc())
+}
+
+/// Read all JSON documents of a (zstd compressed) archive, sending them to the channel
+fn read_archive(data: Bytes, tx: mpsc::Sender<(String, Vec<u8>)>) -> Result<(), Error> {
+    let mut archive = tar::Archive::new(zstd::stream::read::Decoder::new(data.reader())?);
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let path = entry.path()?.display().to_string();
+        if !path.ends_with(".json") {
+            continue;
+        }
+
+        let mut data = Vec::with_capacity(entry.size() as _);
+        entry.read_to_end(&mut data)?;
+
+        if tx.blocking_send((path, data)).is_err() {
+            // the receiver is gone, no need to continue
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        model::CommonImporter,
+        runner::common::test::{build_report, mock_get, new_report},
+    };
+    use test_context::test_context;
+    use test_log::test;
+    use time::macros::datetime;
+    use trustify_test_context::{TrustifyContext, document_bytes};
+    use wiremock::{MockServer, ResponseTemplate};
+
+    #[test]
+    fn parse_archive_date() {
+        assert_eq!(
+            archive_date("csaf_vex_2024-07-07.tar.zst"),
+            Some(datetime!(2024-07-07 0:00 UTC))
+        );
+        assert_eq!(archive_date("csaf_vex.tar.zst"), None);
+    }
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn import_archive_and_changes(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let server = MockServer::start().await;
+
+        let mut archive = tar::Builder::new(vec![]);
+        let data = document_bytes("csaf/cve-2023-0044.json").await?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as _);
+        header.set_mode(0o644);
+        archive.append_data(&mut header, "2023/cve-2023-0044.json", &*data)?;
+        let archive = zstd::encode_all(&*archive.into_inner()?, 3)?;
+
+        mock_get(
+            &server,
+            "/vex/archive_latest.txt",
+            ResponseTemplate::new(200).set_body_string("csaf_vex_2024-07-07.tar.zst\n"),
+        )
+        .await;
+        mock_get(
+            &server,
+            "/vex/csaf_vex_2024-07-07.tar.zst",
+            ResponseTemplate::new(200).set_body_bytes(archive),
+        )
+        .await;
+        mock_get(
+            &server,
+            "/vex/changes.csv",
+            ResponseTemplate::new(200).set_body_string(
+                "\"2023/cve-2023-33201.json\",\"2024-07-08T10:00:00+00:00\"\n\
+                 \"2023/cve-2023-0044.json\",\"2024-07-01T10:00:00+00:00\"\n",
+            ),
+        )
+        .await;
+        mock_get(
+            &server,
+            "/vex/2023/cve-2023-33201.json",
+            ResponseTemplate::new(200)
+                .set_body_bytes(document_bytes("csaf/cve-2023-33201.json").await?.to_vec()),
+        )
+        .await;
+
+        let report = new_report();
+        let walker = RedHatVexWalker::new(
+            RedHatVexImporter {
+                common: CommonImporter::default(),
+                source: format!("{}/vex", server.uri()),
+            },
+            ctx.ingestor.clone(),
+            ReadWrite::new(ctx.db.clone()),
+            report.clone(),
+            (),
+        );
+
+        let continuation = walker.run().await?;
+        assert_eq!(continuation.0, Some(datetime!(2024-07-08 10:00 UTC)));
+
+        // the change before the archive date must not be fetched again
+        let report = build_report(report);
+        assert_eq!(report.number_of_items, 2);
+        assert!(report.messages.is_empty(), "{:?}", report.messages);
+
+        Ok(())
+    }
+}
@@ -1,8 +1,9 @@
 use crate::server::RunOutput;
 use parking_lot::Mutex;
 use schemars::JsonSchema;
-use std::{collections::BTreeMap, iter, sync::Arc};
+use std::{collections::BTreeMap, iter, sync::Arc, time::Duration};
 use time::OffsetDateTime;
+use trustify_module_ingestor::model::{Warning, WarningSeverity};
 
 /// The phase of processing
 #[derive(
@@ -62,6 +63,82 @@ pub struct Report {
     /// Messages emitted during processing
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     pub messages: BTreeMap<Phase, BTreeMap<String, Vec<Message>>>,
+    /// Events detected during processing
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<Event>,
+    /// What would have been ingested, if this was a dry run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dry_run: Option<DryRunSummary>,
+    /// How much processing was delayed by the rate limits
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub throttled: Option<ThrottleSummary>,
+}
+
+/// A summary of the documents a dry run would have ingested
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize, utoipa::ToSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunSummary {
+    /// Number of documents
+    pub documents: u64,
+    /// Total size of the documents, in bytes
+    pub bytes: u64,
+    /// The paths of the first documents
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub samples: Vec<String>,
+}
+
+impl DryRunSummary {
+    /// The maximum number of sample paths recorded
+    pub const MAX_SAMPLES: usize = 20;
+
+    /// Record a document which would have been ingested.
+    pub fn record(&mut self, path: impl Into<String>, size: usize) {
+        self.documents += 1;
+        self.bytes += size as u64;
+        if self.samples.len() < Self::MAX_SAMPLES {
+            self.samples.push(path.into());
+        }
+    }
+}
+
+/// A summary of the delays caused by rate limiting
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize, utoipa::ToSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ThrottleSummary {
+    /// Number of times processing was delayed
+    pub delays: u64,
+    /// Total duration of the delays
+    #[serde(with = "humantime_serde")]
+    #[schema(value_type = String)]
+    pub duration: Duration,
+}
+
+impl ThrottleSummary {
+    /// Record a single delay.
+    pub fn record(&mut self, delay: Duration) {
+        self.delays += 1;
+        self.duration += delay;
+    }
+}
+
+/// An event detected during an import run, for consumption by notification hooks
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum Event {
+    /// A vulnerability, which was already known, got added to the catalog of known exploited
+    /// vulnerabilities
+    #[serde(rename_all = "camelCase")]
+    KnownExploited {
+        /// The ID of the vulnerability
+        vulnerability_id: String,
+        /// The date it was added to the catalog
+        #[serde(with = "time::serde::rfc3339")]
+        date_added: OffsetDateTime,
+    },
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize, utoipa::ToSchema)]
@@ -88,9 +165,23 @@ impl Message {
     }
 }
 
+impl From<&Warning> for Message {
+    fn from(value: &Warning) -> Self {
+        Self {
+            severity: match value.severity {
+                WarningSeverity::Warning => Severity::Warning,
+                WarningSeverity::Error => Severity::Error,
+            },
+            message: value.to_string(),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ReportBuilder {
     report: Report,
+    dry_run: Option<Arc<Mutex<DryRunSummary>>>,
+    throttle: Option<Arc<Mutex<ThrottleSummary>>>,
 }
 
 impl ReportBuilder {
@@ -101,10 +192,27 @@ impl ReportBuilder {
                 end_date: OffsetDateTime::now_utc(),
                 number_of_items: 0,
                 messages: Default::default(),
+                events: vec![],
+                dry_run: None,
+                throttled: None,
             },
+            dry_run: None,
+            throttle: None,
         }
     }
 
+    /// Include the summary of a dry run, once the report gets built.
+    pub fn dry_run(mut self, summary: Arc<Mutex<DryRunSummary>>) -> Self {
+        self.dry_run = Some(summary);
+        self
+    }
+
+    /// Include the summary of the rate limiting, once the report gets built.
+    pub fn throttle(mut self, summary: Arc<Mutex<ThrottleSummary>>) -> Self {
+        self.throttle = Some(summary);
+        self
+    }
+
     pub fn tick(&mut self) {
         self.report.number_of_items += 1;
     }
@@ -160,8 +268,18 @@ impl ReportBuilder {
             .extend(iter::once(first).chain(messages));
     }
 
+    /// Add an event
+    pub fn add_event(&mut self, event: Event) {
+        self.report.events.push(event);
+    }
+
     pub fn build(mut self) -> Report {
         self.report.end_date = OffsetDateTime::now_utc();
+        self.report.dry_run = self.dry_run.map(|summary| summary.lock().clone());
+        self.report.throttled = self
+            .throttle
+            .map(|summary| summary.lock().clone())
+            .filter(|summary| summary.delays > 0);
         self.report
     }
 }
@@ -185,6 +303,7 @@ impl<V> ReportVisitor<V> {
 
 /// Fail a scanner process.
 #[derive(Debug, thiserror::Error)]
+#[allow(clippy::large_enum_variant)]
 pub enum ScannerError {
     /// A critical error occurred, we don't even have a report.
     #[error(transparent)]
@@ -5,9 +5,12 @@ use crate::{
     model::SbomImporter,
     runner::{
         RunOutput,
-        common::{filter::Filter, validation},
+        common::{
+            filter::{Filter, PathFilter},
+            validation,
+        },
         context::RunContext,
-        report::{ReportBuilder, ReportVisitor, ScannerError},
+        report::{ReportVisitor, ScannerError},
         sbom::report::SbomReportVisitor,
     },
     server::context::WalkerProgress,
@@ -23,9 +26,7 @@ use sbom_walker::{
 use std::collections::HashSet;
 use std::{sync::Arc, time::SystemTime};
 use tracing::instrument;
-use trustify_module_ingestor::{graph::Graph, service::IngestorService};
 use url::Url;
-use walker_common::fetcher::{Fetcher, FetcherOptions};
 
 impl super::ImportRunner {
     #[instrument(skip_all, err)]
@@ -39,10 +40,6 @@ impl super::ImportRunner {
 
         let progress = context.progress(format!("Import SBOM: {}", importer.source));
 
-        // report
-
-        let report = Arc::new(Mutex::new(ReportBuilder::new()));
-
         let SbomImporter {
             common,
             source,
@@ -54,19 +51,23 @@ impl super::ImportRunner {
             ignore_missing,
         } = importer;
 
+        // report
+
+        let (ingestor, report) = self.ingestor(context.name(), &common);
+        let paths = PathFilter::from_config(&common)?;
+        let report = Arc::new(Mutex::new(report));
+
         let url = Url::parse(&source).map_err(|err| ScannerError::Critical(err.into()))?;
 
         let keys = keys.into_iter().map(|key| key.into()).collect::<Vec<_>>();
         let http_source = HttpSource::new(
             url,
-            Fetcher::new(FetcherOptions::new().retries(fetch_retries.unwrap_or_default())).await?,
+            self.fetcher(&common, fetch_retries).await?,
             HttpOptions::new().since(last_success).keys(keys),
         );
 
         // storage (called by validator)
 
-        let ingestor =
-            IngestorService::new(Graph::new(), self.storage.clone(), self.analysis.clone());
         let storage = storage::StorageVisitor {
             context,
             source,
@@ -99,7 +100,7 @@ impl super::ImportRunner {
 
         // filter
 
-        let filter = Filter::from_config(visitor, only_patterns)?;
+        let filter = Filter::from_config(visitor, only_patterns, paths)?;
 
         // walker
 
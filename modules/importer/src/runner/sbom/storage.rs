@@ -1,5 +1,5 @@
 use crate::runner::{
-    common::storage::StorageError,
+    common::{ingestor::Ingestor, storage::StorageError},
     context::RunContext,
     report::Severity,
     report::{Message, Phase, ReportBuilder},
@@ -12,7 +12,7 @@ use sbom_walker::{
 use std::sync::Arc;
 use trustify_common::db::ReadWrite;
 use trustify_entity::labels::Labels;
-use trustify_module_ingestor::service::{Cache, Format, IngestorService};
+use trustify_module_ingestor::service::{Cache, Format};
 use walker_common::utils::url::Urlify;
 use walker_common::{compression::decompress_opt, validate::ValidationError};
 
@@ -21,7 +21,7 @@ pub struct StorageVisitor<C: RunContext> {
     pub source: String,
     pub max_size: Option<u64>,
     pub labels: Labels,
-    pub ingestor: IngestorService,
+    pub ingestor: Ingestor,
     pub db: ReadWrite,
     /// the report to report our messages to
     pub report: Arc<Mutex<ReportBuilder>>,
@@ -70,21 +70,25 @@ impl<C: RunContext> ValidatedVisitor<HttpSource> for StorageVisitor<C> {
         };
 
         let result = self
-            .db
-            .transaction(async |tx| {
-                self.ingestor
-                    .ingest(
-                        &data,
-                        Format::SBOM,
-                        Labels::new()
-                            .add("source", &self.source)
-                            .add("importer", self.context.name())
-                            .add("file", &file)
-                            .extend(self.labels.0.clone()),
-                        None,
-                        Cache::Skip,
-                        tx,
-                    )
+            .ingestor
+            .retrying(async || {
+                self.db
+                    .transaction(async |tx| {
+                        self.ingestor
+                            .ingest(
+                                data.clone(),
+                                Format::SBOM,
+                                Labels::new()
+                                    .add("source", &self.source)
+                                    .add("importer", self.context.name())
+                                    .add("file", &file)
+                                    .extend(self.labels.0.clone()),
+                                None,
+                                Cache::Skip,
+                                tx,
+                            )
+                            .await
+                    })
                     .await
             })
             .await
@@ -93,7 +97,7 @@ impl<C: RunContext> ValidatedVisitor<HttpSource> for StorageVisitor<C> {
         self.report.lock().extend_messages(
             Phase::Upload,
             file,
-            result.warnings.into_iter().map(Message::warning),
+            result.warnings.iter().map(Message::from),
         );
 
         self.context.check_canceled(|| StorageError::Canceled).await
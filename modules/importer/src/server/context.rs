@@ -47,6 +47,16 @@ impl RunContext for ServiceRunContext {
         self.state.lock().await.check().await
     }
 
+    async fn checkpoint(&self, continuation: serde_json::Value) {
+        if let Err(err) = self
+            .service
+            .update_continuation(&self.name, None, continuation)
+            .await
+        {
+            log::warn!("Failed to store checkpoint of {}: {err}", self.name);
+        }
+    }
+
     fn progress(&self, _message: String) -> impl Progress + Send + 'static {
         ServiceProgress::new(self.name.clone(), self.service.clone())
     }
@@ -101,10 +111,12 @@ impl CheckCancellation {
     async fn perform_check(&self) -> anyhow::Result<bool> {
         let importer = self.service.read(&self.importer_name).await?;
 
-        // If we have a record, return its state.
+        // If we have a record, return its state. Being paused or canceled both stop the run.
         // If we don't have a record, we must have been deleted. Which also means we're canceled.
         Ok(importer
-            .map(|importer| importer.value.data.configuration.disabled)
+            .map(|importer| {
+                importer.value.data.configuration.disabled || importer.value.data.control.is_some()
+            })
             .unwrap_or(true))
     }
 }
@@ -2,7 +2,7 @@ pub mod context;
 pub(crate) mod progress;
 
 use crate::{
-    model::{Importer, State},
+    model::{Importer, Proxy, SecretKey, State},
     runner::{
         ImportRunner,
         common::heartbeat::Heart,
@@ -11,7 +11,7 @@ use crate::{
     server::context::ServiceRunContext,
     service::{Error, ImporterService},
 };
-use opentelemetry::global;
+use opentelemetry::{KeyValue, global};
 use std::{path::PathBuf, time::Duration};
 use time::OffsetDateTime;
 use tokio::{task::LocalSet, time::MissedTickBehavior};
@@ -24,12 +24,15 @@ use trustify_module_storage::service::dispatch::DispatchBackend;
 /// Run the importer loop.
 ///
 /// When `read_only` is true, the loop stays alive but no imports are started.
+#[allow(clippy::too_many_arguments)]
 pub async fn importer(
     db: ReadWrite,
     cache: PaginationCache,
     storage: DispatchBackend,
     working_dir: Option<PathBuf>,
     analysis: Option<AnalysisService>,
+    proxy: Option<Proxy>,
+    secret_key: Option<SecretKey>,
     concurrency: usize,
     read_only: bool,
 ) -> anyhow::Result<()> {
@@ -39,6 +42,8 @@ pub async fn importer(
         storage,
         working_dir,
         analysis,
+        proxy,
+        secret_key,
         concurrency,
         read_only,
     }
@@ -68,6 +73,8 @@ struct Server {
     storage: DispatchBackend,
     working_dir: Option<PathBuf>,
     analysis: Option<AnalysisService>,
+    proxy: Option<Proxy>,
+    secret_key: Option<SecretKey>,
     concurrency: usize,
     read_only: bool,
 }
@@ -84,12 +91,19 @@ impl Server {
         let meter = global::meter("importer::Server");
         let running_importers = meter.u64_gauge("running_importers").build();
 
-        let service = ImporterService::new(self.db.clone(), self.cache.clone());
+        let service = ImporterService::new(self.db.clone(), self.cache.clone())
+            .with_secret_key(self.secret_key.clone());
+        if !self.read_only {
+            service.encrypt_stored_secrets().await?;
+        }
+
         let runner = ImportRunner {
             db: self.db.clone(),
             storage: self.storage.clone(),
             working_dir: self.working_dir.clone(),
             analysis: self.analysis.clone(),
+            proxy: self.proxy.clone(),
+            secret_key: self.secret_key.clone(),
         };
         let mut interval = tokio::time::interval(Duration::from_secs(1));
         interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
@@ -121,7 +135,7 @@ impl Server {
             runs.extend(
                 importers
                     .into_iter()
-                    .filter(|i| i.is_enabled() && i.is_due() && !i.is_running())
+                    .filter(|i| i.is_enabled() && !i.is_paused() && i.is_due() && !i.is_running())
                     .take(self.concurrency - count)
                     .map(|importer| {
                         let token = CancellationToken::new();
@@ -154,12 +168,12 @@ async fn import(
 
     let context = ServiceRunContext::new(service.clone(), importer.name.clone(), cancel);
 
-    let (last_error, report, continuation) = match runner
+    let (mut last_error, report, mut continuation) = match runner
         .run_once(
             context,
             importer.data.configuration,
             importer.data.last_success,
-            importer.data.continuation,
+            importer.data.continuation.clone(),
         )
         .await
     {
@@ -177,8 +191,28 @@ async fn import(
         Err(ScannerError::Critical(err)) => (Some(err.to_string()), None, None),
     };
 
+    // a failed or paused run must be resumable, so keep the stored continuation, unless we got a
+    // new one. It might be a checkpoint of this run.
+
+    let current = service.read(&importer.name).await?;
+    if last_error.is_some() {
+        if current
+            .as_ref()
+            .is_some_and(|current| current.value.is_paused())
+        {
+            last_error = Some("Import paused".into());
+        }
+        if continuation.is_none() {
+            continuation = current
+                .map(|current| current.value.data.continuation)
+                .filter(|continuation| !continuation.is_null());
+        }
+    }
+
     log::info!("Import run complete: {last_error:?}");
 
+    record_run(&importer.name, last_run, last_error.is_none());
+
     service
         .update_finish(
             &importer.name,
@@ -193,6 +227,30 @@ async fn import(
     Ok(())
 }
 
+/// Record the outcome and duration of a run in the metrics
+fn record_run(name: &str, start: OffsetDateTime, success: bool) {
+    let meter = global::meter("importer::Server");
+    let attributes = [
+        KeyValue::new("importer", name.to_string()),
+        KeyValue::new("outcome", if success { "success" } else { "failure" }),
+    ];
+
+    meter
+        .u64_counter("importer_runs")
+        .with_description("Runs of an importer, by their outcome")
+        .build()
+        .add(1, &attributes);
+    meter
+        .f64_histogram("importer_run_duration")
+        .with_description("Duration of an importer run")
+        .with_unit("s")
+        .build()
+        .record(
+            (OffsetDateTime::now_utc() - start).as_seconds_f64(),
+            &attributes,
+        );
+}
+
 async fn reap(importers: &[Importer], service: &ImporterService) -> anyhow::Result<()> {
     for importer in importers
         .iter()
@@ -209,7 +267,8 @@ async fn reap(importers: &[Importer], service: &ImporterService) -> anyhow::Resu
                 None,
                 importer.data.last_run.unwrap_or(importer.data.last_change),
                 Some("Import aborted".into()),
-                None,
+                // keep the continuation, which might be a checkpoint of the aborted run
+                Some(importer.data.continuation.clone()).filter(|c| !c.is_null()),
                 None,
             )
             .await?;
@@ -38,6 +38,8 @@ impl Progress for ServiceProgress {
             service: self.service.clone(),
             current: 0,
             total: work,
+            failed: 0,
+            skipped: 0,
             last_flush: Instant::now() - FLUSH_PERIOD,
             tracing: self.tracing.start(work),
         }
@@ -58,6 +60,8 @@ pub struct ServiceProgressInstance {
     service: ImporterService,
     current: usize,
     total: usize,
+    failed: usize,
+    skipped: usize,
     last_flush: Instant,
     tracing: TracingProgressInstance,
 }
@@ -71,26 +75,51 @@ impl ServiceProgressInstance {
             importer = self.name,
             current,
             total = self.total,
+            failed = self.failed,
+            skipped = self.skipped,
             "Updating progress"
         );
 
         let _ = self
             .service
-            .update_progress(&self.name, None, current as u32, self.total as u32)
+            .update_progress(
+                &self.name,
+                None,
+                current as u32,
+                self.total as u32,
+                self.failed as u32,
+                self.skipped as u32,
+            )
             .await;
     }
-}
-
-impl ProgressInstance for ServiceProgressInstance {
-    async fn increment(&mut self, work: usize) {
-        self.tracing.increment(work).await;
 
+    /// advance the processed items, flushing if due
+    async fn advance(&mut self, work: usize) {
         self.current += work;
         if self.last_flush.elapsed() > FLUSH_PERIOD {
             self.last_flush = Instant::now();
             self.flush().await;
         }
     }
+}
+
+impl ProgressInstance for ServiceProgressInstance {
+    async fn increment(&mut self, work: usize) {
+        self.tracing.increment(work).await;
+        self.advance(work).await;
+    }
+
+    async fn fail(&mut self) {
+        self.tracing.fail().await;
+        self.failed += 1;
+        self.advance(1).await;
+    }
+
+    async fn skip(&mut self) {
+        self.tracing.skip().await;
+        self.skipped += 1;
+        self.advance(1).await;
+    }
 
     async fn finish(mut self) {
         self.current = self.total;
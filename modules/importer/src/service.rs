@@ -1,8 +1,12 @@
-use crate::model::{Importer, ImporterConfiguration, ImporterReport};
+use crate::model::{
+    Importer, ImporterConfiguration, ImporterReport, LabelTemplateError, ScheduleError,
+    SecretError, SecretKey, validate_label_templates,
+};
+use crate::runner::common::filter::PathFilter;
 use actix_web::{HttpResponse, ResponseError, body::BoxBody};
 use sea_orm::{
     ActiveModelTrait, ActiveValue::Set, ColumnTrait, ConnectionTrait, EntityTrait, PaginatorTrait,
-    QueryFilter, QueryOrder, TransactionTrait,
+    QueryFilter, QueryOrder, QuerySelect, TransactionTrait,
 };
 use sea_query::{Alias, Expr, Nullable, SimpleExpr};
 use std::fmt::{Debug, Display};
@@ -27,6 +31,8 @@ pub enum Error {
     AlreadyExists,
     #[error("importer '{0}' not found")]
     NotFound(String),
+    #[error("importer '{0}' is already running")]
+    AlreadyRunning(String),
     #[error("mid air collision")]
     MidAirCollision,
     #[error("unavailable")]
@@ -40,6 +46,14 @@ pub enum Error {
     #[error(transparent)]
     Label(#[from] labels::Error),
     #[error(transparent)]
+    Schedule(#[from] ScheduleError),
+    #[error(transparent)]
+    LabelTemplate(#[from] LabelTemplateError),
+    #[error("invalid path filter: {0}")]
+    PathFilter(#[from] regex::Error),
+    #[error(transparent)]
+    Secret(#[from] SecretError),
+    #[error(transparent)]
     Limit(#[from] trustify_common::db::pagination_cache::LimitError),
 }
 
@@ -88,6 +102,11 @@ impl ResponseError for Error {
                 message: self.to_string(),
                 details: None,
             }),
+            Self::AlreadyRunning(_) => HttpResponse::Conflict().json(ErrorInformation {
+                error: "AlreadyRunning".into(),
+                message: self.to_string(),
+                details: None,
+            }),
             Self::MidAirCollision => HttpResponse::PreconditionFailed().json(ErrorInformation {
                 error: "MidAirCollision".into(),
                 message: self.to_string(),
@@ -98,6 +117,19 @@ impl ResponseError for Error {
                 message: self.to_string(),
                 details: None,
             }),
+            Self::Schedule(_)
+            | Self::LabelTemplate(_)
+            | Self::PathFilter(_)
+            | Self::Secret(
+                SecretError::Unencrypted
+                | SecretError::Redacted
+                | SecretError::MissingKey
+                | SecretError::Decrypt,
+            ) => HttpResponse::BadRequest().json(ErrorInformation {
+                error: "BadRequest".into(),
+                message: self.to_string(),
+                details: None,
+            }),
             Self::Limit(err) => err.error_response(),
             _ => HttpResponse::InternalServerError().json(ErrorInformation {
                 error: "Internal".into(),
@@ -128,12 +160,71 @@ where
 pub struct ImporterService {
     db: ReadWrite,
     cache: PaginationCache,
+    secret_key: Option<SecretKey>,
 }
 
 impl ImporterService {
     /// Creates a new importer service backed by the given read-write connection.
     pub fn new(db: ReadWrite, cache: PaginationCache) -> Self {
-        Self { db, cache }
+        Self {
+            db,
+            cache,
+            secret_key: None,
+        }
+    }
+
+    /// Set the key for encrypting the secrets of configurations, before storing them.
+    pub fn with_secret_key(mut self, secret_key: Option<SecretKey>) -> Self {
+        self.secret_key = secret_key;
+        self
+    }
+
+    /// Validate a configuration and prepare it for being stored
+    fn prepare(&self, configuration: &mut ImporterConfiguration) -> Result<(), Error> {
+        validate(configuration)?;
+        configuration.encrypt_secrets(self.secret_key.as_ref())?;
+        Ok(())
+    }
+
+    /// Encrypt the secrets of stored configurations, which are still in plain text.
+    ///
+    /// Such configurations may have been stored before secrets got encrypted. Without a key, they
+    /// can't be encrypted and are only reported, as their importers fail until being saved again.
+    pub async fn encrypt_stored_secrets(&self) -> Result<(), Error> {
+        for model in importer::Entity::find().all(&self.db).await? {
+            let Revisioned { value, revision } = Importer::from_revisioned(model)?;
+            let mut configuration = value.data.configuration;
+            if configuration.secrets_encrypted() {
+                continue;
+            }
+
+            if let Err(err) = configuration.encrypt_secrets(self.secret_key.as_ref()) {
+                log::error!(
+                    "Importer '{}' has secrets stored in plain text, which can't be encrypted ({err}). Configure a secret key and save the configuration of the importer again.",
+                    value.name
+                );
+                continue;
+            }
+
+            match self
+                .update(
+                    &self.db,
+                    &value.name,
+                    Some(&revision),
+                    vec![(
+                        importer::Column::Configuration,
+                        Expr::value(serde_json::to_value(configuration)?),
+                    )],
+                )
+                .await
+            {
+                Ok(()) => log::info!("Encrypted the stored secrets of importer '{}'", value.name),
+                // modified in the meantime, e.g. by another instance encrypting it
+                Err(Error::MidAirCollision | Error::NotFound(_)) => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
     }
 
     pub async fn list(&self) -> Result<Vec<Importer>, Error> {
@@ -152,7 +243,7 @@ impl ImporterService {
         name: String,
         mut configuration: ImporterConfiguration,
     ) -> Result<(), Error> {
-        configuration.labels.validate_mut()?;
+        self.prepare(&mut configuration)?;
 
         let entity = importer::ActiveModel {
             name: Set(name.clone()),
@@ -168,12 +259,15 @@ impl ImporterService {
             progress_current: Set(None),
             progress_total: Set(None),
             progress_message: Set(None),
+            progress_failed: Set(None),
+            progress_skipped: Set(None),
 
             continuation: Set(None),
 
             configuration: Set(serde_json::to_value(configuration)?),
 
             heartbeat: Set(None),
+            control: Set(None),
         };
 
         entity.insert(&self.db).await?;
@@ -218,15 +312,14 @@ impl ImporterService {
 
         // apply mutation
 
-        let mut configuration =
-            f(current.value.data.configuration).map_err(PatchError::Transform)?;
+        let stored = current.value.data.configuration;
+        let mut configuration = f(stored.clone()).map_err(PatchError::Transform)?;
+        configuration.restore_secrets(&stored);
 
         // validate
 
-        configuration
-            .labels
-            .validate_mut()
-            .map_err(|err| PatchError::Common(err.into()))?;
+        self.prepare(&mut configuration)
+            .map_err(PatchError::Common)?;
 
         // store
 
@@ -252,7 +345,12 @@ impl ImporterService {
         expected_revision: Option<&str>,
         mut configuration: ImporterConfiguration,
     ) -> Result<(), Error> {
-        configuration.labels.validate_mut()?;
+        // keep the stored secrets, for which only the placeholder was sent back
+        if let Some(current) = self.read(name).await? {
+            configuration.restore_secrets(&current.value.data.configuration);
+        }
+
+        self.prepare(&mut configuration)?;
 
         self.update(
             &self.db,
@@ -286,6 +384,7 @@ impl ImporterService {
                     importer::Column::State,
                     Expr::value(importer::State::Running),
                 ),
+                (importer::Column::Control, clear_cancel()),
             ],
         )
         .await
@@ -314,12 +413,15 @@ impl ImporterService {
             ),
             (importer::Column::ProgressCurrent, Expr::value(i32::null())),
             (importer::Column::ProgressTotal, Expr::value(i32::null())),
+            (importer::Column::ProgressFailed, Expr::value(i32::null())),
+            (importer::Column::ProgressSkipped, Expr::value(i32::null())),
             (
                 importer::Column::ProgressMessage,
                 Expr::value(String::null()),
             ),
             (importer::Column::LastChange, Expr::value(now)),
             (importer::Column::Continuation, Expr::value(continuation)),
+            (importer::Column::Control, clear_cancel()),
         ];
         if successful {
             // we use the `start` marker, so that `last_success` can be used as the next `since`
@@ -403,6 +505,8 @@ impl ImporterService {
                 ),
                 (importer::Column::ProgressCurrent, Expr::value(i32::null())),
                 (importer::Column::ProgressTotal, Expr::value(i32::null())),
+                (importer::Column::ProgressFailed, Expr::value(i32::null())),
+                (importer::Column::ProgressSkipped, Expr::value(i32::null())),
                 (
                     importer::Column::ProgressMessage,
                     Expr::value(String::null()),
@@ -423,6 +527,8 @@ impl ImporterService {
         expected_revision: Option<&str>,
         current: u32,
         total: u32,
+        failed: u32,
+        skipped: u32,
     ) -> Result<(), Error> {
         self.update(
             &self.db,
@@ -431,6 +537,8 @@ impl ImporterService {
             vec![
                 (importer::Column::ProgressCurrent, Expr::value(current)),
                 (importer::Column::ProgressTotal, Expr::value(total)),
+                (importer::Column::ProgressFailed, Expr::value(failed)),
+                (importer::Column::ProgressSkipped, Expr::value(skipped)),
                 (
                     importer::Column::ProgressMessage,
                     Expr::value(String::null()),
@@ -440,6 +548,23 @@ impl ImporterService {
         .await
     }
 
+    /// Store the continuation of a running importer, to resume from in case it gets interrupted
+    #[instrument(skip(self, continuation))]
+    pub async fn update_continuation(
+        &self,
+        name: &str,
+        expected_revision: Option<&str>,
+        continuation: serde_json::Value,
+    ) -> Result<(), Error> {
+        self.update(
+            &self.db,
+            name,
+            expected_revision,
+            vec![(importer::Column::Continuation, Expr::value(continuation))],
+        )
+        .await
+    }
+
     #[instrument(skip(self))]
     pub async fn set_progress_message(
         &self,
@@ -456,6 +581,99 @@ impl ImporterService {
         .await
     }
 
+    /// Pause an importer
+    ///
+    /// A running importer stops after the current document, keeping its continuation. No new runs
+    /// will be started until the importer gets resumed.
+    #[instrument(skip(self))]
+    pub async fn pause(&self, name: &str, expected_revision: Option<&str>) -> Result<(), Error> {
+        self.update(
+            &self.db,
+            name,
+            expected_revision,
+            vec![(
+                importer::Column::Control,
+                Expr::value(importer::Control::Pause),
+            )],
+        )
+        .await
+    }
+
+    /// Resume a paused importer
+    ///
+    /// The importer will be run as soon as possible, continuing from its stored continuation.
+    #[instrument(skip(self))]
+    pub async fn resume(&self, name: &str, expected_revision: Option<&str>) -> Result<(), Error> {
+        self.update(
+            &self.db,
+            name,
+            expected_revision,
+            vec![
+                (
+                    importer::Column::Control,
+                    Expr::value(None::<importer::Control>),
+                ),
+                (
+                    importer::Column::LastRun,
+                    Expr::value(None::<OffsetDateTime>),
+                ),
+            ],
+        )
+        .await
+    }
+
+    /// Request a run of an importer as soon as possible, regardless of its schedule
+    ///
+    /// The run continues from the stored continuation. Fails if the importer is currently
+    /// running.
+    #[instrument(skip(self))]
+    pub async fn run_now(&self, name: &str, expected_revision: Option<&str>) -> Result<(), Error> {
+        let tx = self.db.begin().await?;
+
+        let importer = importer::Entity::find_by_id(name)
+            .lock_exclusive()
+            .one(&tx)
+            .await?
+            .ok_or_else(|| Error::NotFound(name.to_string()))?;
+
+        if importer.state == importer::State::Running {
+            return Err(Error::AlreadyRunning(name.to_string()));
+        }
+
+        self.update(
+            &tx,
+            name,
+            expected_revision,
+            vec![(
+                importer::Column::LastRun,
+                Expr::value(None::<OffsetDateTime>),
+            )],
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Cancel the current run of an importer
+    ///
+    /// The run is aborted cooperatively. The request is dropped once the run finished, or when
+    /// the next run starts.
+    #[instrument(skip(self))]
+    pub async fn cancel(&self, name: &str, expected_revision: Option<&str>) -> Result<(), Error> {
+        self.update(
+            &self.db,
+            name,
+            expected_revision,
+            vec![(
+                importer::Column::Control,
+                Expr::value(importer::Control::Cancel),
+            )],
+        )
+        .await
+    }
+
     #[instrument(skip(self))]
     pub async fn delete(&self, name: &str, expected_revision: Option<&str>) -> Result<bool, Error> {
         let mut delete = importer::Entity::delete_many().filter(importer::Column::Name.eq(name));
@@ -495,4 +713,35 @@ impl ImporterService {
             items: items.into_iter().map(ImporterReport::from).collect(),
         })
     }
+
+    #[instrument(skip(self))]
+    pub async fn get_report(&self, name: &str, id: Uuid) -> Result<Option<ImporterReport>, Error> {
+        let result = importer_report::Entity::find_by_id(id)
+            .filter(importer_report::Column::Importer.eq(name))
+            .one(&self.db)
+            .await?;
+
+        Ok(result.map(ImporterReport::from))
+    }
+}
+
+/// Drop a request to cancel, but keep a request to pause.
+fn clear_cancel() -> SimpleExpr {
+    Expr::case(
+        importer::Column::Control.eq(importer::Control::Cancel),
+        Expr::value(None::<importer::Control>),
+    )
+    .finally(Expr::col(importer::Column::Control))
+    .into()
+}
+
+/// Validate, and normalize, an importer configuration before storing it.
+fn validate(configuration: &mut ImporterConfiguration) -> Result<(), Error> {
+    configuration.labels.validate_mut()?;
+    validate_label_templates(&configuration.labels)?;
+    PathFilter::new(&configuration.include, &configuration.exclude)?;
+    if let Some(schedule) = &configuration.schedule {
+        schedule.validate()?;
+    }
+    Ok(())
 }
@@ -1,8 +1,10 @@
 #![cfg(test)]
 
 use super::model::{
-    CommonImporter, Importer, ImporterConfiguration, ImporterData, SbomImporter, State,
+    CommonImporter, Control, Importer, ImporterConfiguration, ImporterData, ImporterReport,
+    SbomImporter, Schedule, SecretKey, State,
 };
+use crate::{runner::report::Phase, service::ImporterService};
 use actix_http::{Request, body::BoxBody};
 use actix_web::{
     App,
@@ -14,15 +16,27 @@ use serde_json::json;
 use std::time::Duration;
 use test_context::test_context;
 use test_log::test;
-use trustify_common::db::{self, pagination_cache::PaginationCache};
+use time::format_description::well_known::Rfc3339;
+use trustify_common::{
+    db::{self, pagination_cache::PaginationCache},
+    model::PaginatedResults,
+};
 use trustify_test_context::{ReadOnly, TrustifyContext, app::TestApp};
 use utoipa_actix_web::AppExt;
+use uuid::Uuid;
 
 fn mock_configuration(source: impl Into<String>) -> ImporterConfiguration {
     ImporterConfiguration::Sbom(SbomImporter {
         common: CommonImporter {
             disabled: false,
             period: Duration::from_secs(30),
+            schedule: None,
+            dry_run: false,
+            rate_limit: None,
+            retry: None,
+            proxy: None,
+            include: vec![],
+            exclude: vec![],
             description: None,
             labels: Default::default(),
         },
@@ -45,6 +59,7 @@ fn mock_importer(result: &Importer, source: impl Into<String>) -> Importer {
         data: ImporterData {
             configuration: mock_configuration(source),
             state: State::Waiting,
+            control: None,
             last_change: result.data.last_change, // we can't predict timestamps
             last_success: None,
             last_error: None,
@@ -57,17 +72,22 @@ fn mock_importer(result: &Importer, source: impl Into<String>) -> Importer {
 
 async fn app(
     ctx: &TrustifyContext,
+) -> impl Service<Request, Response = ServiceResponse<BoxBody>, Error = actix_web::Error> {
+    app_with_config(ctx, Default::default()).await
+}
+
+async fn app_with_config(
+    ctx: &TrustifyContext,
+    config: super::endpoints::Config,
 ) -> impl Service<Request, Response = ServiceResponse<BoxBody>, Error = actix_web::Error> {
     let db = db::ReadWrite::new(ctx.db.clone());
     actix::init_service(
         App::new()
             .into_utoipa_app()
             .add_test_authorizer()
-            .service(
-                utoipa_actix_web::scope("/api").configure(|svc| {
-                    super::endpoints::configure(svc, db, PaginationCache::for_test())
-                }),
-            )
+            .service(utoipa_actix_web::scope("/api").configure(|svc| {
+                super::endpoints::configure(svc, config, db, PaginationCache::for_test())
+            }))
             .into_app(),
     )
     .await
@@ -106,6 +126,7 @@ async fn default(ctx: TrustifyContext) {
             data: ImporterData {
                 configuration: mock_configuration("bar"),
                 state: State::Waiting,
+                control: None,
                 last_change: result[0].data.last_change, // we can't predict timestamps
                 last_success: None,
                 last_run: None,
@@ -381,6 +402,385 @@ async fn patch(ctx: TrustifyContext) {
     assert_eq!(resp.status(), StatusCode::NOT_FOUND);
 }
 
+#[test_context(TrustifyContext, skip_teardown)]
+#[test(actix_web::test)]
+async fn lifecycle(ctx: TrustifyContext) {
+    let app = app(&ctx).await;
+
+    let req = actix::TestRequest::post()
+        .uri("/api/v3/importer/foo")
+        .set_json(mock_configuration("bar"))
+        .to_request();
+
+    let resp = actix::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    for (action, expected) in [
+        ("pause", Some(Control::Pause)),
+        ("resume", None),
+        ("cancel", Some(Control::Cancel)),
+    ] {
+        let req = actix::TestRequest::post()
+            .uri(&format!("/api/v3/importer/foo/{action}"))
+            .to_request();
+
+        let resp = actix::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT, "{action}");
+
+        let req = actix::TestRequest::get()
+            .uri("/api/v3/importer/foo")
+            .to_request();
+
+        let result: Importer = actix::call_and_read_body_json(&app, req).await;
+        assert_eq!(result.data.control, expected, "{action}");
+        assert_eq!(result.is_paused(), expected == Some(Control::Pause));
+    }
+
+    // unknown importers
+
+    let req = actix::TestRequest::post()
+        .uri("/api/v3/importer/baz/pause")
+        .to_request();
+
+    let resp = actix::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[test_context(TrustifyContext, skip_teardown)]
+#[test(actix_web::test)]
+async fn run_now(ctx: TrustifyContext) {
+    let app = app(&ctx).await;
+    let service = ImporterService::new(
+        db::ReadWrite::new(ctx.db.clone()),
+        PaginationCache::for_test(),
+    );
+
+    let req = actix::TestRequest::post()
+        .uri("/api/v3/importer/foo")
+        .set_json(mock_configuration("bar"))
+        .to_request();
+
+    let resp = actix::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    // pretend a run finished, keeping its continuation
+
+    let start = time::OffsetDateTime::now_utc();
+    service
+        .update_finish("foo", None, start, None, Some(json!({"a": 1})), None)
+        .await
+        .expect("must update");
+
+    let run = async || {
+        let req = actix::TestRequest::post()
+            .uri("/api/v3/importer/foo/run")
+            .to_request();
+        actix::call_service(&app, req).await.status()
+    };
+
+    assert_eq!(run().await, StatusCode::NO_CONTENT);
+
+    let importer = service
+        .read("foo")
+        .await
+        .expect("must read")
+        .expect("must exist");
+    assert_eq!(importer.value.data.last_run, None);
+    assert_eq!(importer.value.data.continuation, json!({"a": 1}));
+    assert!(importer.value.is_due());
+
+    // not while running
+
+    service
+        .update_start("foo", None)
+        .await
+        .expect("must update");
+    assert_eq!(run().await, StatusCode::CONFLICT);
+
+    // unknown importers
+
+    let req = actix::TestRequest::post()
+        .uri("/api/v3/importer/baz/run")
+        .to_request();
+
+    let resp = actix::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[test_context(TrustifyContext, skip_teardown)]
+#[test(actix_web::test)]
+async fn reports(ctx: TrustifyContext) {
+    let app = app(&ctx).await;
+    let service = ImporterService::new(
+        db::ReadWrite::new(ctx.db.clone()),
+        PaginationCache::for_test(),
+    );
+
+    let req = actix::TestRequest::post()
+        .uri("/api/v3/importer/foo")
+        .set_json(mock_configuration("bar"))
+        .to_request();
+
+    let resp = actix::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    // finish a failed run, with a report
+
+    let start = time::OffsetDateTime::now_utc();
+    service
+        .update_finish(
+            "foo",
+            None,
+            start,
+            Some("Failed".into()),
+            None,
+            Some(json!({
+                "startDate": start.format(&Rfc3339).unwrap(),
+                "endDate": start.format(&Rfc3339).unwrap(),
+                "numberOfItems": 1,
+                "messages": {
+                    "upload": {
+                        "2023/cve-2023-0044.json": [{"severity": "error", "message": "Broken"}]
+                    }
+                }
+            })),
+        )
+        .await
+        .expect("must update");
+
+    // list
+
+    let req = actix::TestRequest::get()
+        .uri("/api/v3/importer/foo/report?total=true")
+        .to_request();
+    let result: PaginatedResults<ImporterReport> = actix::call_and_read_body_json(&app, req).await;
+    assert_eq!(result.total, Some(1));
+    let id = result.items[0].id.clone();
+
+    // detail
+
+    let req = actix::TestRequest::get()
+        .uri(&format!("/api/v3/importer/foo/report/{id}"))
+        .to_request();
+    let result: ImporterReport = actix::call_and_read_body_json(&app, req).await;
+    assert_eq!(result.error.as_deref(), Some("Failed"));
+    let report = result.report.expect("must have a report");
+    assert_eq!(report.number_of_items, 1);
+    assert_eq!(
+        report.messages[&Phase::Upload]["2023/cve-2023-0044.json"].len(),
+        1
+    );
+
+    // the report must belong to the importer
+
+    let req = actix::TestRequest::get()
+        .uri(&format!("/api/v3/importer/bar/report/{id}"))
+        .to_request();
+    let resp = actix::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    let req = actix::TestRequest::get()
+        .uri(&format!("/api/v3/importer/foo/report/{}", Uuid::new_v4()))
+        .to_request();
+    let resp = actix::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[test_context(TrustifyContext, skip_teardown)]
+#[test(actix_web::test)]
+async fn invalid_schedule(ctx: TrustifyContext) {
+    let app = app(&ctx).await;
+
+    let mut configuration = mock_configuration("bar");
+    configuration.schedule = Some(Schedule::new("0 30 2 * * *").timezone("UTC+2"));
+
+    let req = actix::TestRequest::post()
+        .uri("/api/v3/importer/foo")
+        .set_json(configuration)
+        .to_request();
+
+    let resp = actix::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test_context(TrustifyContext, skip_teardown)]
+#[test(actix_web::test)]
+async fn secrets(ctx: TrustifyContext) {
+    let configuration = json!({
+        "osv": {
+            "period": "30s",
+            "source": "https://example.com/osv.git",
+            "credentials": {
+                "token": "s3cr3t",
+            },
+        },
+    });
+
+    // without a secret key, secrets are rejected
+
+    let app = app(&ctx).await;
+
+    let req = actix::TestRequest::post()
+        .uri("/api/v3/importer/foo")
+        .set_json(&configuration)
+        .to_request();
+
+    let resp = actix::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    // with a secret key, they get stored encrypted
+
+    let key: SecretKey = "MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY="
+        .parse()
+        .expect("must parse");
+    let app = app_with_config(
+        &ctx,
+        super::endpoints::Config {
+            secret_key: Some(key.clone()),
+        },
+    )
+    .await;
+
+    let req = actix::TestRequest::post()
+        .uri("/api/v3/importer/foo")
+        .set_json(&configuration)
+        .to_request();
+
+    let resp = actix::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    // but are redacted when reading the configuration
+
+    let req = actix::TestRequest::get()
+        .uri("/api/v3/importer/foo")
+        .to_request();
+
+    let resp = actix::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let result: serde_json::Value = actix::read_body_json(resp).await;
+    let mut configuration = result["configuration"].clone();
+    assert_eq!(configuration["osv"]["credentials"]["token"], "********");
+
+    let req = actix::TestRequest::get()
+        .uri("/api/v3/importer")
+        .to_request();
+
+    let resp = actix::call_service(&app, req).await;
+    let result: serde_json::Value = actix::read_body_json(resp).await;
+    assert_eq!(
+        result[0]["configuration"]["osv"]["credentials"]["token"],
+        "********"
+    );
+
+    // sending the placeholder back keeps the stored secret
+
+    configuration["osv"]["source"] = json!("https://example.com/other.git");
+
+    let req = actix::TestRequest::put()
+        .uri("/api/v3/importer/foo")
+        .set_json(&configuration)
+        .to_request();
+
+    let resp = actix::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    let service = ImporterService::new(
+        db::ReadWrite::new(ctx.db.clone()),
+        PaginationCache::for_test(),
+    );
+    let stored = service
+        .read("foo")
+        .await
+        .expect("must read")
+        .expect("must exist")
+        .value
+        .data
+        .configuration;
+    let ImporterConfiguration::Osv(osv) = stored else {
+        panic!("must be an OSV importer");
+    };
+    let token = osv
+        .credentials
+        .and_then(|credentials| credentials.token)
+        .expect("must have a token");
+    assert!(token.is_encrypted());
+    assert_eq!(token.reveal(Some(&key)).expect("must reveal"), "s3cr3t");
+}
+
+#[test_context(TrustifyContext, skip_teardown)]
+#[test(tokio::test)]
+async fn encrypt_stored_secrets(ctx: TrustifyContext) {
+    use sea_orm::{ActiveValue::Set, EntityTrait};
+    use trustify_entity::importer;
+
+    let key: SecretKey = "MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY="
+        .parse()
+        .expect("must parse");
+    let service = ImporterService::new(
+        db::ReadWrite::new(ctx.db.clone()),
+        PaginationCache::for_test(),
+    );
+
+    // a configuration stored before secrets got encrypted
+
+    service
+        .create("foo".into(), mock_configuration("bar"))
+        .await
+        .expect("must create");
+    importer::Entity::update(importer::ActiveModel {
+        name: Set("foo".into()),
+        configuration: Set(json!({
+            "osv": {
+                "period": "30s",
+                "source": "https://example.com/osv.git",
+                "credentials": {
+                    "token": "s3cr3t",
+                },
+            },
+        })),
+        ..Default::default()
+    })
+    .exec(&ctx.db)
+    .await
+    .expect("must update");
+
+    let token = async || {
+        let configuration = service
+            .read("foo")
+            .await
+            .expect("must read")
+            .expect("must exist")
+            .value
+            .data
+            .configuration;
+        configuration
+            .git_credentials()
+            .and_then(|credentials| credentials.token.clone())
+            .expect("must have a token")
+    };
+
+    // without a key, it is only reported
+
+    service
+        .encrypt_stored_secrets()
+        .await
+        .expect("must not fail");
+    assert!(!token().await.is_encrypted());
+
+    // with a key, it gets encrypted
+
+    service
+        .clone()
+        .with_secret_key(Some(key.clone()))
+        .encrypt_stored_secrets()
+        .await
+        .expect("must encrypt");
+    let encrypted = token().await;
+    assert!(encrypted.is_encrypted());
+    assert_eq!(encrypted.reveal(Some(&key)).expect("must reveal"), "s3cr3t");
+}
+
 #[test_context(ReadOnly<TrustifyContext>)]
 #[test(actix_web::test)]
 async fn read_only(ctx: &mut ReadOnly<TrustifyContext>) {
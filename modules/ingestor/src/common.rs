@@ -35,6 +35,27 @@ impl Deprecation {
     }
 }
 
+#[derive(
+    Copy, Clone, PartialEq, Eq, Debug, Default, ToSchema, serde::Deserialize, serde::Serialize,
+)]
+pub enum Withdrawal {
+    /// Ignore withdrawn documents
+    #[default]
+    Ignore,
+    /// Consider withdrawn documents
+    Consider,
+}
+
+impl Withdrawal {
+    pub fn filter(&self, advisories: Select<advisory::Entity>) -> Select<advisory::Entity> {
+        // rule out withdrawn advisories, like rejected CVEs, if requested to
+        match self {
+            Withdrawal::Ignore => advisories.filter(advisory::Column::Withdrawn.is_null()),
+            Withdrawal::Consider => advisories,
+        }
+    }
+}
+
 /// Extend advisory queries with deprecation.
 pub trait DeprecationExt {
     /// Apply deprecation filtering to e.g. [`Select`].
@@ -61,3 +82,15 @@ where
         deprecation.filter_for(self)
     }
 }
+
+/// Extend advisory queries with withdrawal.
+pub trait WithdrawalExt {
+    /// Apply withdrawal filtering to e.g. [`Select`].
+    fn with_withdrawal(self, withdrawal: Withdrawal) -> Self;
+}
+
+impl WithdrawalExt for Select<advisory::Entity> {
+    fn with_withdrawal(self, withdrawal: Withdrawal) -> Self {
+        withdrawal.filter(self)
+    }
+}
@@ -1,10 +1,18 @@
 use crate::{
     graph::Graph,
-    service::{Error, IngestorService},
+    model::ValidationResult,
+    service::{
+        Error, Format, IngestorService, ValidationLevel,
+        archive::ArchiveIngestResult,
+        image::ImageIngestResult,
+        limits::Limits,
+        loader::LoaderRegistry,
+        quota::{self, DEFAULT_LABEL, Quotas, Usage},
+    },
 };
-use actix_web::{HttpResponse, Responder, post, web};
+use actix_web::{HttpResponse, Responder, get, http::header, post, web};
 use sea_orm::TransactionTrait;
-use trustify_auth::{UploadDataset, authorizer::Require};
+use trustify_auth::{ExportDataset, ReadSystemInformation, UploadDataset, authorizer::Require};
 use trustify_common::{db, model::BinaryData};
 use trustify_entity::labels::Labels;
 use trustify_module_analysis::service::AnalysisService;
@@ -19,18 +27,32 @@ pub fn configure(
     storage: impl Into<DispatchBackend>,
     analysis: Option<AnalysisService>,
 ) {
-    let ingestor_service = IngestorService::new(Graph::new(), storage, analysis);
+    let ingestor_service = IngestorService::new(Graph::new(), storage, analysis)
+        .with_limits(config.limits)
+        .with_loaders(config.loaders.clone())
+        .with_quotas(config.quotas.clone());
 
     svc.app_data(web::Data::new(ingestor_service))
         .app_data(web::Data::new(config))
         .app_data(web::Data::new(db))
-        .service(upload_dataset);
+        .service(upload_dataset)
+        .service(export_dataset)
+        .service(upload_archive)
+        .service(ingest_image)
+        .service(validate)
+        .service(storage_usage);
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Default)]
 pub struct Config {
-    /// Limit of a single content entry (after decompression).
+    /// Limit of a single content entry (after decompression) of a dataset or archive.
     pub dataset_entry_limit: usize,
+    /// Limits applied when loading a single document.
+    pub limits: Limits,
+    /// Loaders for custom document formats.
+    pub loaders: LoaderRegistry,
+    /// Storage quotas, if any.
+    pub quotas: Option<Quotas>,
 }
 
 #[derive(
@@ -72,3 +94,225 @@ pub async fn upload_dataset(
 
     Ok(HttpResponse::Created().json(result))
 }
+
+#[utoipa::path(
+    tag = "dataset",
+    operation_id = "uploadArchive",
+    request_body = inline(BinaryData),
+    params(UploadParams),
+    responses(
+        (status = 201, description = "Processed the archive", body = ArchiveIngestResult),
+        (status = 400, description = "The file could not be parsed as an archive"),
+    )
+)]
+#[post("/v3/archive")]
+/// Upload an archive (zip, tar, or tar.gz) of documents
+///
+/// The format of each document is detected individually. The result reports the documents
+/// which were ingested, as well as those which failed.
+pub async fn upload_archive(
+    service: web::Data<IngestorService>,
+    config: web::Data<Config>,
+    db: web::Data<db::ReadWrite>,
+    web::Query(UploadParams { labels }): web::Query<UploadParams>,
+    bytes: web::Bytes,
+    _: Require<UploadDataset>,
+) -> Result<impl Responder, Error> {
+    let tx = db.begin().await?;
+    let result = service
+        .ingest_archive(bytes, labels, config.dataset_entry_limit, &tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(HttpResponse::Created().json(result))
+}
+
+#[derive(
+    IntoParams, Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize,
+)]
+struct ImageParams {
+    /// The reference of the image, like `quay.io/org/image:tag`.
+    reference: String,
+    /// Optional labels.
+    ///
+    /// Only use keys with a prefix of `labels.`
+    #[serde(flatten, with = "trustify_entity::labels::prefixed")]
+    labels: Labels,
+}
+
+#[utoipa::path(
+    tag = "dataset",
+    operation_id = "ingestImage",
+    params(ImageParams),
+    responses(
+        (status = 201, description = "Processed the artifacts attached to the image", body = ImageIngestResult),
+        (status = 400, description = "The image reference is invalid"),
+        (status = 502, description = "The image could not be retrieved from its registry"),
+    )
+)]
+#[post("/v3/image")]
+/// Ingest the SBOMs and attestations attached to an OCI image
+///
+/// Attached artifacts are discovered using the OCI referrers API and cosign attachments. The
+/// result reports the artifacts which were ingested, as well as those which failed.
+pub async fn ingest_image(
+    service: web::Data<IngestorService>,
+    config: web::Data<Config>,
+    db: web::Data<db::ReadWrite>,
+    web::Query(ImageParams { reference, labels }): web::Query<ImageParams>,
+    _: Require<UploadDataset>,
+) -> Result<impl Responder, Error> {
+    let tx = db.begin().await?;
+    let result = service
+        .ingest_image(&reference, labels, config.dataset_entry_limit, &tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(HttpResponse::Created().json(result))
+}
+
+#[derive(IntoParams, Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+struct ValidateParams {
+    /// Optional issuer if it cannot be determined from the document contents.
+    #[serde(default)]
+    issuer: Option<String>,
+    /// Optional labels.
+    ///
+    /// Only use keys with a prefix of `labels.`
+    #[serde(flatten, with = "trustify_entity::labels::prefixed")]
+    labels: Labels,
+    /// The format of the document, detected if not provided.
+    #[serde(default = "default_format")]
+    #[param(inline)]
+    format: Format,
+    /// How to deal with documents violating their specification.
+    #[serde(default)]
+    #[param(inline)]
+    level: ValidationLevel,
+}
+
+const fn default_format() -> Format {
+    Format::Unknown
+}
+
+#[utoipa::path(
+    tag = "dataset",
+    operation_id = "validateDocument",
+    request_body = inline(BinaryData),
+    params(ValidateParams),
+    responses(
+        (status = 200, description = "The document is valid", body = ValidationResult),
+        (status = 400, description = "The document could not be parsed or loaded"),
+    )
+)]
+#[post("/v3/validate")]
+/// Validate a document, without ingesting it
+///
+/// The document is processed like a regular upload, but neither stored nor added to the graph.
+/// The result carries the warnings a regular upload would produce.
+pub async fn validate(
+    service: web::Data<IngestorService>,
+    db: web::Data<db::ReadWrite>,
+    web::Query(ValidateParams {
+        issuer,
+        labels,
+        format,
+        level,
+    }): web::Query<ValidateParams>,
+    bytes: web::Bytes,
+    _: Require<UploadDataset>,
+) -> Result<impl Responder, Error> {
+    let result = service
+        .validate(bytes, format, labels, issuer, level, db.get_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(result))
+}
+
+#[utoipa::path(
+    tag = "dataset",
+    operation_id = "exportDataset",
+    responses(
+        (status = 200, description = "A dataset archive of all documents", body = inline(BinaryData), content_type = "application/zip"),
+    )
+)]
+#[get("/v3/dataset")]
+/// Export all documents, including their labels and provenance, as a dataset
+pub async fn export_dataset(
+    service: web::Data<IngestorService>,
+    db: web::Data<db::ReadWrite>,
+    _: Require<ExportDataset>,
+) -> Result<impl Responder, Error> {
+    let tx = db.begin().await?;
+    let result = service.export_dataset(&tx).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/zip")
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            r#"attachment; filename="dataset.zip""#,
+        ))
+        .body(result))
+}
+
+#[derive(IntoParams, Clone, Debug, PartialEq, Eq, serde::Deserialize)]
+struct UsageParams {
+    /// The label to group the documents by, defaults to the quota label
+    #[serde(default)]
+    label: Option<String>,
+}
+
+/// The storage used per value of a label
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, utoipa::ToSchema)]
+pub struct StorageUsage {
+    /// The label the documents are grouped by
+    pub label: String,
+    pub usage: Vec<LabelUsage>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, utoipa::ToSchema)]
+pub struct LabelUsage {
+    #[serde(flatten)]
+    pub usage: Usage,
+    /// The quota for this value of the label, in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota: Option<u64>,
+}
+
+#[utoipa::path(
+    tag = "dataset",
+    operation_id = "getStorageUsage",
+    params(UsageParams),
+    responses(
+        (status = 200, description = "The storage used per value of the label", body = StorageUsage),
+    )
+)]
+#[get("/v3/storage/usage")]
+/// Get the number and size of the stored documents, per value of a label
+pub async fn storage_usage(
+    config: web::Data<Config>,
+    db: web::Data<db::ReadWrite>,
+    web::Query(UsageParams { label }): web::Query<UsageParams>,
+    _: Require<ReadSystemInformation>,
+) -> Result<impl Responder, Error> {
+    let quotas = config
+        .quotas
+        .as_ref()
+        .filter(|quotas| label.as_ref().is_none_or(|label| *label == quotas.label));
+    let label = label
+        .or_else(|| quotas.map(|quotas| quotas.label.clone()))
+        .unwrap_or_else(|| DEFAULT_LABEL.into());
+
+    let usage = quota::usage(&label, None, db.get_ref())
+        .await?
+        .into_iter()
+        .map(|usage| LabelUsage {
+            quota: quotas
+                .zip(usage.value.as_deref())
+                .and_then(|(quotas, value)| quotas.limit(value)),
+            usage,
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(StorageUsage { label, usage }))
+}
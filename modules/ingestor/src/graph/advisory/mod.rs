@@ -17,7 +17,10 @@ use semver::Version;
 use std::fmt::{Debug, Formatter};
 use time::OffsetDateTime;
 use tracing::instrument;
-use trustify_common::{db::UpdateDeprecatedAdvisory, hashing::Digests};
+use trustify_common::{
+    db::{UpdateDeprecatedAdvisory, chunk::EntityChunkedIter},
+    hashing::Digests,
+};
 use trustify_entity::{self as entity, advisory, labels::Labels, source_document};
 use uuid::Uuid;
 
@@ -47,6 +50,14 @@ pub struct AdvisoryVulnerabilityInformation {
     pub cwes: Option<Vec<String>>,
 }
 
+/// An entry of the revision history of an advisory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AdvisoryRevision {
+    pub number: String,
+    pub date: OffsetDateTime,
+    pub summary: String,
+}
+
 impl AdvisoryInformation {
     pub fn has_data(&self) -> bool {
         self.title.is_some()
@@ -254,6 +265,36 @@ impl<'g> AdvisoryContext<'g> {
         self.advisory.withdrawn
     }
 
+    /// Record the revision history of the advisory.
+    ///
+    /// Entries with a number already recorded are ignored.
+    #[instrument(skip_all, err(level=tracing::Level::INFO))]
+    pub async fn add_revisions<C: ConnectionTrait>(
+        &self,
+        revisions: impl IntoIterator<Item = AdvisoryRevision>,
+        connection: &C,
+    ) -> Result<(), Error> {
+        let advisory_id = self.advisory.id;
+        let models = revisions
+            .into_iter()
+            .map(|revision| entity::advisory_revision::ActiveModel {
+                advisory_id: Set(advisory_id),
+                number: Set(revision.number),
+                date: Set(revision.date),
+                summary: Set(revision.summary),
+            });
+
+        for batch in &models.chunked() {
+            entity::advisory_revision::Entity::insert_many(batch)
+                .on_conflict(OnConflict::new().do_nothing().to_owned())
+                .do_nothing()
+                .exec_without_returning(connection)
+                .await?;
+        }
+
+        Ok(())
+    }
+
     #[instrument(skip(self, connection), err(level=tracing::Level::INFO))]
     pub async fn get_vulnerability<C: ConnectionTrait>(
         &self,
@@ -0,0 +1,161 @@
+use crate::graph::{CreateOutcome, Graph, Outcome, error::Error};
+use sea_orm::{
+    ActiveValue::Set, ColumnTrait, Condition, ConnectionTrait, EntityTrait, JoinType, QueryFilter,
+    QuerySelect, RelationTrait, TransactionTrait,
+};
+use sea_query::{Expr, OnConflict, Query};
+use std::fmt::Debug;
+use time::OffsetDateTime;
+use tracing::instrument;
+use trustify_common::{db::chunk::EntityChunkedIter, hashing::Digests};
+use trustify_entity::{
+    attestation::{self, Material, Materials},
+    attestation_subject,
+    labels::Labels,
+    sbom, sbom_node_checksum, source_document,
+};
+use uuid::Uuid;
+
+/// Information about a provenance attestation
+#[derive(Clone, Debug, Default)]
+pub struct AttestationInformation {
+    pub predicate_type: String,
+    pub builder_id: Option<String>,
+    pub build_type: Option<String>,
+    pub invocation_id: Option<String>,
+    pub started_on: Option<OffsetDateTime>,
+    pub finished_on: Option<OffsetDateTime>,
+    pub materials: Vec<Material>,
+    /// The subjects, as tuple of name, digest algorithm, and digest value
+    pub subjects: Vec<(String, String, String)>,
+}
+
+impl Graph {
+    pub async fn get_attestation_by_digest<C: ConnectionTrait>(
+        &self,
+        digest: &str,
+        connection: &C,
+    ) -> Result<Option<attestation::Model>, Error> {
+        Ok(attestation::Entity::find()
+            .join(JoinType::Join, attestation::Relation::SourceDocument.def())
+            .filter(
+                Condition::any()
+                    .add(source_document::Column::Sha256.eq(digest.to_string()))
+                    .add(source_document::Column::Sha384.eq(digest.to_string()))
+                    .add(source_document::Column::Sha512.eq(digest.to_string())),
+            )
+            .one(connection)
+            .await?)
+    }
+
+    /// Find all attestations having a subject which is either the SBOM document itself, or one of
+    /// the artifacts it describes, matched by their digests.
+    #[instrument(skip(self, connection), err(level=tracing::Level::INFO))]
+    pub async fn get_attestations_for_sbom<C: ConnectionTrait>(
+        &self,
+        sbom_id: Uuid,
+        connection: &C,
+    ) -> Result<Vec<attestation::Model>, Error> {
+        let node_digests = Query::select()
+            .column(sbom_node_checksum::Column::Value)
+            .from(sbom_node_checksum::Entity)
+            .and_where(sbom_node_checksum::Column::SbomId.eq(sbom_id))
+            .to_owned();
+
+        let document_digests = Query::select()
+            .column((source_document::Entity, source_document::Column::Sha256))
+            .from(source_document::Entity)
+            .inner_join(
+                sbom::Entity,
+                Expr::col((sbom::Entity, sbom::Column::SourceDocumentId))
+                    .equals((source_document::Entity, source_document::Column::Id)),
+            )
+            .and_where(Expr::col((sbom::Entity, sbom::Column::SbomId)).eq(sbom_id))
+            .to_owned();
+
+        let subjects = Query::select()
+            .column(attestation_subject::Column::AttestationId)
+            .from(attestation_subject::Entity)
+            .cond_where(
+                Condition::any()
+                    .add(attestation_subject::Column::Digest.in_subquery(node_digests))
+                    .add(attestation_subject::Column::Digest.in_subquery(document_digests)),
+            )
+            .to_owned();
+
+        Ok(attestation::Entity::find()
+            .filter(attestation::Column::Id.in_subquery(subjects))
+            .all(connection)
+            .await?)
+    }
+
+    #[instrument(skip(self, connection, information), err(level=tracing::Level::INFO))]
+    pub async fn ingest_attestation<C>(
+        &self,
+        labels: impl Into<Labels> + Debug,
+        digests: &Digests,
+        information: AttestationInformation,
+        connection: &C,
+    ) -> Result<Outcome<attestation::Model>, Error>
+    where
+        C: ConnectionTrait + TransactionTrait,
+    {
+        let AttestationInformation {
+            predicate_type,
+            builder_id,
+            build_type,
+            invocation_id,
+            started_on,
+            finished_on,
+            materials,
+            subjects,
+        } = information;
+
+        let source_document_id = match self
+            .create_doc(digests, connection, async |sha256| {
+                self.get_attestation_by_digest(&sha256, connection).await
+            })
+            .await?
+        {
+            CreateOutcome::Exists(attestation) => return Ok(Outcome::Existed(attestation)),
+            CreateOutcome::Created(new_id) => new_id,
+        };
+
+        let id = Uuid::now_v7();
+        let model = attestation::ActiveModel {
+            id: Set(id),
+            source_document_id: Set(source_document_id),
+            labels: Set(labels.into().validate()?),
+            predicate_type: Set(predicate_type),
+            builder_id: Set(builder_id),
+            build_type: Set(build_type),
+            invocation_id: Set(invocation_id),
+            started_on: Set(started_on),
+            finished_on: Set(finished_on),
+            materials: Set(Materials(materials)),
+        };
+
+        let result = attestation::Entity::insert(model)
+            .exec_with_returning(connection)
+            .await?;
+
+        let subjects = subjects.into_iter().map(|(name, algorithm, digest)| {
+            attestation_subject::ActiveModel {
+                attestation_id: Set(id),
+                name: Set(name),
+                algorithm: Set(algorithm),
+                digest: Set(digest.to_ascii_lowercase()),
+            }
+        });
+
+        for batch in &subjects.chunked() {
+            attestation_subject::Entity::insert_many(batch)
+                .on_conflict(OnConflict::new().do_nothing().to_owned())
+                .do_nothing()
+                .exec_without_returning(connection)
+                .await?;
+        }
+
+        Ok(Outcome::Added(result))
+    }
+}
@@ -1,4 +1,5 @@
 pub mod advisory;
+pub mod attestation;
 pub mod cpe;
 pub mod cvss;
 pub mod db_context;
@@ -65,6 +66,7 @@ impl Graph {
             sha256: Set(digests.sha256.encode_hex()),
             sha384: Set(digests.sha384.encode_hex()),
             sha512: Set(digests.sha512.encode_hex()),
+            blake3: Set(Some(digests.blake3.to_hex().to_string())),
             size: Set(digests.size as i64),
             ingested: Set(OffsetDateTime::now_utc()),
         };
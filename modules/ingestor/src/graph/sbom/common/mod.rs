@@ -10,6 +10,7 @@ mod node;
 mod package;
 mod reference;
 mod relationship;
+mod vex;
 
 pub use checksum::*;
 pub use cryptographic_asset::*;
@@ -22,3 +23,4 @@ pub use machine_learning_model::*;
 pub use package::*;
 pub use reference::*;
 pub use relationship::*;
+pub use vex::*;
@@ -0,0 +1,155 @@
+use crate::graph::{error::Error, vulnerability::creator::VulnerabilityCreator};
+use sea_orm::{
+    ActiveValue::Set, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QuerySelect,
+};
+use sea_query::OnConflict;
+use serde_json::Value;
+use std::{collections::HashSet, str::FromStr};
+use trustify_common::db::chunk::EntityChunkedIter;
+use trustify_entity::{
+    sbom_node,
+    sbom_vex::{self, VexState},
+};
+use uuid::Uuid;
+
+/// Creator of VEX statements embedded in an SBOM
+pub struct VexCreator {
+    sbom_id: Uuid,
+    vulnerabilities: VulnerabilityCreator,
+    models: Vec<sbom_vex::ActiveModel>,
+}
+
+impl VexCreator {
+    pub fn new(sbom_id: Uuid) -> Self {
+        Self {
+            sbom_id,
+            vulnerabilities: VulnerabilityCreator::new(),
+            models: Vec::new(),
+        }
+    }
+
+    /// Add a CycloneDX vulnerability, in its JSON form.
+    ///
+    /// Creates a statement for each component it affects. Vulnerabilities without an ID are
+    /// ignored.
+    pub fn add_cyclonedx(&mut self, vulnerability: &Value) {
+        let Some(id) = vulnerability["id"].as_str() else {
+            return;
+        };
+
+        let analysis = &vulnerability["analysis"];
+        let state = analysis["state"]
+            .as_str()
+            .and_then(|state| VexState::from_str(state).ok());
+        let justification = analysis["justification"].as_str().map(ToString::to_string);
+        let response: Vec<String> = analysis["response"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(ToString::to_string)
+            .collect();
+        let detail = analysis["detail"].as_str().map(ToString::to_string);
+
+        let mut affected = false;
+        for r#ref in vulnerability["affects"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|affects| affects["ref"].as_str())
+        {
+            affected = true;
+            self.models.push(sbom_vex::ActiveModel {
+                sbom_id: Set(self.sbom_id),
+                node_id: Set(bom_ref(r#ref).to_string()),
+                vulnerability_id: Set(id.to_string()),
+                state: Set(state),
+                justification: Set(justification.clone()),
+                response: Set(response.clone()),
+                detail: Set(detail.clone()),
+            });
+        }
+
+        if affected {
+            self.vulnerabilities.add(id, ());
+        }
+    }
+
+    pub async fn create(self, db: &impl ConnectionTrait) -> Result<(), Error> {
+        // only create vulnerabilities which don't exist yet, don't override existing information
+        self.vulnerabilities.create(db).await?;
+
+        // statements can only reference components of the SBOM
+
+        let refs: HashSet<&str> = self
+            .models
+            .iter()
+            .filter_map(|model| match &model.node_id {
+                Set(node_id) => Some(node_id.as_str()),
+                _ => None,
+            })
+            .collect();
+        let nodes: HashSet<String> = sbom_node::Entity::find()
+            .select_only()
+            .column(sbom_node::Column::NodeId)
+            .filter(sbom_node::Column::SbomId.eq(self.sbom_id))
+            .filter(sbom_node::Column::NodeId.is_in(refs))
+            .into_tuple()
+            .all(db)
+            .await?
+            .into_iter()
+            .collect();
+
+        let models = self
+            .models
+            .into_iter()
+            .filter(|model| match &model.node_id {
+                Set(node_id) if nodes.contains(node_id) => true,
+                node_id => {
+                    log::debug!("Ignoring VEX statement for unknown component: {node_id:?}");
+                    false
+                }
+            });
+
+        for batch in &models.chunked() {
+            sbom_vex::Entity::insert_many(batch)
+                .on_conflict(
+                    OnConflict::columns([
+                        sbom_vex::Column::SbomId,
+                        sbom_vex::Column::NodeId,
+                        sbom_vex::Column::VulnerabilityId,
+                    ])
+                    .do_nothing()
+                    .to_owned(),
+                )
+                .do_nothing()
+                .exec_without_returning(db)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Strip a BOM-Link (`urn:cdx:<serial>/<version>#<bom-ref>`) down to the bom-ref.
+fn bom_ref(r#ref: &str) -> &str {
+    match r#ref.strip_prefix("urn:cdx:") {
+        Some(link) => link.split_once('#').map_or(r#ref, |(_, bom_ref)| bom_ref),
+        None => r#ref,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn bom_links() {
+        assert_eq!(bom_ref("pkg:npm/foo@1.0.0"), "pkg:npm/foo@1.0.0");
+        assert_eq!(
+            bom_ref("urn:cdx:3e671687-395b-41f5-a30f-a58921a69b79/1#pkg:npm/foo@1.0.0"),
+            "pkg:npm/foo@1.0.0"
+        );
+    }
+}
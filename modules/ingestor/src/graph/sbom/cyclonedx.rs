@@ -7,7 +7,7 @@ use crate::{
             CryptographicAssetCreator, CycloneDx as CycloneDxProcessor, LicenseCreator,
             LicenseInfo, MachineLearningModelCreator, NodeInfoParam, PackageCreator,
             PackageLicensenInfo, PackageReference, References, RelationshipCreator, SbomContext,
-            SbomInformation, populate_expanded_license,
+            SbomInformation, VexCreator, populate_expanded_license,
             processor::{
                 InitContext, PostContext, Processor, RedHatProductComponentRelationships,
                 RunProcessors,
@@ -239,6 +239,14 @@ impl SbomContext {
 
         creator.create(connection, &mut processors).await?;
 
+        // record embedded vulnerabilities (VEX)
+
+        let mut vex = VexCreator::new(self.sbom.sbom_id);
+        for vulnerability in sbom.vulnerabilities.iter().flatten() {
+            vex.add_cyclonedx(&serde_json::to_value(vulnerability)?);
+        }
+        vex.create(connection).await?;
+
         // done
 
         Ok(())
@@ -0,0 +1,58 @@
+use crate::graph::error::Error;
+use sea_orm::{ActiveValue::Set, ConnectionTrait, EntityTrait};
+use sea_query::OnConflict;
+use std::collections::BTreeSet;
+use tracing::instrument;
+use trustify_common::db::chunk::EntityChunkedIter;
+use trustify_entity::vulnerability_alias;
+use uuid::Uuid;
+
+/// Creator for batch insertion of vulnerability aliases, stated by a single advisory
+pub struct AliasCreator {
+    advisory_id: Uuid,
+    entries: BTreeSet<(String, String)>,
+}
+
+impl AliasCreator {
+    pub fn new(advisory_id: Uuid) -> Self {
+        Self {
+            advisory_id,
+            entries: Default::default(),
+        }
+    }
+
+    /// Add an alias of a vulnerability. Aliases of a vulnerability to itself are ignored.
+    pub fn add(&mut self, vulnerability_id: impl Into<String>, alias: impl Into<String>) {
+        let vulnerability_id = vulnerability_id.into();
+        let alias = alias.into();
+
+        if vulnerability_id != alias {
+            self.entries.insert((vulnerability_id, alias));
+        }
+    }
+
+    #[instrument(skip_all, fields(num = self.entries.len()), err(level=tracing::Level::INFO))]
+    pub async fn create<C>(self, connection: &C) -> Result<(), Error>
+    where
+        C: ConnectionTrait,
+    {
+        let advisory_id = self.advisory_id;
+        let models = self.entries.into_iter().map(|(vulnerability_id, alias)| {
+            vulnerability_alias::ActiveModel {
+                vulnerability_id: Set(vulnerability_id),
+                alias: Set(alias),
+                advisory_id: Set(advisory_id),
+            }
+        });
+
+        for batch in &models.chunked() {
+            vulnerability_alias::Entity::insert_many(batch)
+                .on_conflict(OnConflict::new().do_nothing().to_owned())
+                .do_nothing()
+                .exec_without_returning(connection)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 //! Support for CVEs.
 
+pub mod alias;
 pub mod creator;
 
 use crate::{
@@ -1,3 +1,5 @@
+use crate::service::Format;
+use std::fmt::{Display, Formatter};
 use trustify_common::id::Id;
 
 /// The result of the ingestion process
@@ -10,5 +12,124 @@ pub struct IngestResult {
     pub document_id: Option<String>,
     /// Warnings that occurred during the import process
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub warnings: Vec<String>,
+    pub warnings: Vec<Warning>,
+}
+
+/// The result of validating a document, without ingesting it
+#[derive(Clone, Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct ValidationResult {
+    /// The detected format of the document
+    pub format: Format,
+    /// The name of the registered loader, for custom formats
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loader: Option<String>,
+    /// The ID declared by the document
+    pub document_id: Option<String>,
+    /// Warnings that would occur during the import process
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<Warning>,
+}
+
+/// A warning which occurred during the import process
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize, utoipa::ToSchema)]
+pub struct Warning {
+    /// The kind of warning
+    pub code: WarningCode,
+    /// The severity of the warning
+    pub severity: WarningSeverity,
+    /// The location the warning refers to, if known.
+    ///
+    /// For documents, this is a JSON pointer into the document. For archives and datasets, this
+    /// is the name of the entry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// A human-readable message
+    pub message: String,
+}
+
+impl Warning {
+    pub fn new(code: WarningCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            severity: WarningSeverity::Warning,
+            location: None,
+            message: message.into(),
+        }
+    }
+
+    /// Set the severity of the warning
+    pub fn severity(mut self, severity: WarningSeverity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// Set the location the warning refers to
+    pub fn at(mut self, location: impl Into<String>) -> Self {
+        self.location = Some(location.into());
+        self
+    }
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)?;
+        if let Some(location) = &self.location {
+            write!(f, " (at {location})")?;
+        }
+        Ok(())
+    }
+}
+
+/// The kind of warning
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    serde::Deserialize,
+    serde::Serialize,
+    strum::Display,
+    utoipa::ToSchema,
+)]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "camelCase")]
+pub enum WarningCode {
+    /// The document violates its specification
+    SpecViolation,
+    /// A value of the document is invalid and was skipped
+    InvalidValue,
+    /// An entry of an archive or dataset is not supported
+    UnsupportedEntry,
+    /// An entry of an archive or dataset failed to load
+    InvalidEntry,
+    /// The content of an entry doesn't match its recorded digest
+    DigestMismatch,
+}
+
+/// The severity of a warning
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    serde::Deserialize,
+    serde::Serialize,
+    strum::Display,
+    utoipa::ToSchema,
+)]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "camelCase")]
+pub enum WarningSeverity {
+    /// The content was processed, but may be incomplete
+    Warning,
+    /// The content is erroneous, and was processed as good as possible
+    Error,
 }
@@ -2,16 +2,19 @@ use crate::{
     graph::{
         Graph,
         advisory::{
-            AdvisoryContext, AdvisoryInformation, AdvisoryVulnerabilityInformation,
-            advisory_vulnerability::AdvisoryVulnerabilityContext,
+            AdvisoryContext, AdvisoryInformation, AdvisoryRevision,
+            AdvisoryVulnerabilityInformation, advisory_vulnerability::AdvisoryVulnerabilityContext,
         },
         cvss::ScoreCreator,
-        vulnerability::creator::VulnerabilityCreator,
+        vulnerability::{alias::AliasCreator, creator::VulnerabilityCreator},
     },
-    model::IngestResult,
+    model::{IngestResult, Warning, WarningCode},
     service::{
-        Error, Warnings,
-        advisory::csaf::{RemediationCreator, StatusCreator, extract_scores, util::gen_identifier},
+        Error, ValidationLevel, Warnings,
+        advisory::csaf::{
+            RemediationCreator, StatusCreator, extract_scores,
+            util::{gen_identifier, walk_product_tree_branches},
+        },
     },
 };
 use csaf::{
@@ -21,7 +24,7 @@ use csaf::{
 use hex::ToHex;
 use sea_orm::{ConnectionTrait, TransactionTrait};
 use semver::Version;
-use std::{fmt::Debug, str::FromStr};
+use std::{collections::HashSet, fmt::Debug, str::FromStr};
 use time::OffsetDateTime;
 use tracing::instrument;
 use trustify_common::hashing::Digests;
@@ -73,13 +76,72 @@ fn parse_csaf_version(csaf: &Csaf) -> Option<Version> {
     }
 }
 
+/// Check that all products referenced by the status of vulnerabilities are defined in the
+/// product tree.
+fn check_product_references(csaf: &Csaf, warnings: &Warnings) {
+    let mut defined = HashSet::new();
+
+    walk_product_tree_branches(&csaf.product_tree, |_, branch| {
+        if let Some(product) = &branch.product {
+            defined.insert(product.product_id.0.as_str());
+        }
+    });
+    if let Some(product_tree) = &csaf.product_tree {
+        for product in product_tree.full_product_names.iter().flatten() {
+            defined.insert(product.product_id.0.as_str());
+        }
+        for relationship in product_tree.relationships.iter().flatten() {
+            defined.insert(relationship.full_product_name.product_id.0.as_str());
+        }
+    }
+
+    for (index, vulnerability) in csaf.vulnerabilities.iter().flatten().enumerate() {
+        let Some(status) = &vulnerability.product_status else {
+            continue;
+        };
+
+        for (field, products) in [
+            ("first_affected", &status.first_affected),
+            ("first_fixed", &status.first_fixed),
+            ("fixed", &status.fixed),
+            ("known_affected", &status.known_affected),
+            ("known_not_affected", &status.known_not_affected),
+            ("last_affected", &status.last_affected),
+            ("recommended", &status.recommended),
+            ("under_investigation", &status.under_investigation),
+        ] {
+            for product in products.iter().flatten() {
+                if !defined.contains(product.0.as_str()) {
+                    warnings.add(
+                        Warning::new(
+                            WarningCode::SpecViolation,
+                            format!("Undefined product ID: {}", product.0),
+                        )
+                        .at(format!("/vulnerabilities/{index}/product_status/{field}")),
+                    );
+                }
+            }
+        }
+    }
+}
+
 pub struct CsafLoader<'g> {
     graph: &'g Graph,
+    level: ValidationLevel,
 }
 
 impl<'g> CsafLoader<'g> {
     pub fn new(graph: &'g Graph) -> Self {
-        Self { graph }
+        Self {
+            graph,
+            level: Default::default(),
+        }
+    }
+
+    /// Set the validation level, defaults to lenient.
+    pub fn level(mut self, level: ValidationLevel) -> Self {
+        self.level = level;
+        self
     }
 
     #[instrument(skip(self, csaf, tx), err(level=tracing::Level::INFO))]
@@ -92,6 +154,9 @@ impl<'g> CsafLoader<'g> {
     ) -> Result<IngestResult, Error> {
         let warnings = Warnings::new();
 
+        check_product_references(&csaf, &warnings);
+        warnings.enforce(self.level)?;
+
         let advisory_id = gen_identifier(&csaf);
         let labels = labels.into().add("type", "csaf");
 
@@ -110,14 +175,37 @@ impl<'g> CsafLoader<'g> {
             .ingest_advisory(&advisory_id, labels, digests, Information(&csaf), tx)
             .await?;
 
-        // Batch create all vulnerabilities first
+        advisory
+            .add_revisions(
+                csaf.document
+                    .tracking
+                    .revision_history
+                    .iter()
+                    .filter_map(|revision| {
+                        Some(AdvisoryRevision {
+                            number: revision.number.clone(),
+                            date: OffsetDateTime::from_unix_timestamp(revision.date.timestamp())
+                                .ok()?,
+                            summary: revision.summary.clone(),
+                        })
+                    }),
+                tx,
+            )
+            .await?;
+
+        // Batch create all vulnerabilities first, along with the other IDs they are known by
         let mut vuln_creator = VulnerabilityCreator::new();
+        let mut alias_creator = AliasCreator::new(advisory.advisory.id);
         for vuln in csaf.vulnerabilities.iter().flatten() {
             if let Some(cve_id) = &vuln.cve {
                 vuln_creator.add(cve_id, ());
+                for id in vuln.ids.iter().flatten() {
+                    alias_creator.add(cve_id, &id.text);
+                }
             }
         }
         vuln_creator.create(tx).await?;
+        alias_creator.create(tx).await?;
 
         // Then process each vulnerability for linking and product status
         for vuln in csaf.vulnerabilities.iter().flatten() {
@@ -209,6 +297,7 @@ impl<'g> CsafLoader<'g> {
         creator.add_all(&product_status.fixed, "fixed");
         creator.add_all(&product_status.known_not_affected, "not_affected");
         creator.add_all(&product_status.known_affected, "affected");
+        creator.add_all(&product_status.under_investigation, "under_investigation");
 
         let product_id_mapping = creator.create(self.graph, connection).await?;
 
@@ -238,7 +327,10 @@ mod test {
     use super::*;
     use crate::{
         graph::Graph,
-        service::advisory::test::{AssertScore, assert_scores},
+        service::{
+            Cache, Format, IngestOptions,
+            advisory::test::{AssertScore, assert_scores},
+        },
     };
     use hex::ToHex;
     use test_context::test_context;
@@ -553,4 +645,90 @@ mod test {
 
         Ok(())
     }
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn strict_undefined_product(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let graph = Graph::new();
+
+        let (mut csaf, digests): (serde_json::Value, _) =
+            document("csaf/CVE-2023-20862.json").await?;
+        csaf["vulnerabilities"][0]["product_status"]["known_affected"]
+            .as_array_mut()
+            .expect("must be an array")
+            .push("undefined-product".into());
+        let csaf: Csaf = serde_json::from_value(csaf)?;
+
+        let result = ctx
+            .db
+            .transaction(async |tx| {
+                CsafLoader::new(&graph)
+                    .level(ValidationLevel::Strict)
+                    .load((), csaf.clone(), &digests, tx)
+                    .await
+            })
+            .await;
+        assert!(
+            matches!(&result, Err(Error::SpecViolation(violations)) if violations.len() == 1),
+            "{result:?}"
+        );
+
+        let result = ctx
+            .db
+            .transaction(async |tx| CsafLoader::new(&graph).load((), csaf, &digests, tx).await)
+            .await?;
+        assert_eq!(result.warnings.len(), 1);
+        assert_eq!(
+            result.warnings[0].location.as_deref(),
+            Some("/vulnerabilities/0/product_status/known_affected")
+        );
+
+        Ok(())
+    }
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn ingest_strict(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let (mut csaf, _): (serde_json::Value, _) = document("csaf/CVE-2023-20862.json").await?;
+        csaf["vulnerabilities"][0]["product_status"]["known_affected"]
+            .as_array_mut()
+            .expect("must be an array")
+            .push("undefined-product".into());
+        let data = serde_json::to_vec(&csaf)?;
+
+        let result = ctx
+            .db
+            .transaction(async |tx| {
+                ctx.ingestor
+                    .ingest_with(
+                        data.clone(),
+                        Format::CSAF,
+                        (),
+                        None,
+                        Cache::Skip,
+                        IngestOptions::default().level(ValidationLevel::Strict),
+                        tx,
+                    )
+                    .await
+            })
+            .await;
+        assert!(
+            matches!(&result, Err(Error::SpecViolation(_))),
+            "{result:?}"
+        );
+
+        // the default options are lenient
+
+        let result = ctx
+            .db
+            .transaction(async |tx| {
+                ctx.ingestor
+                    .ingest(data.clone(), Format::CSAF, (), None, Cache::Skip, tx)
+                    .await
+            })
+            .await?;
+        assert_eq!(result.warnings.len(), 1);
+
+        Ok(())
+    }
 }
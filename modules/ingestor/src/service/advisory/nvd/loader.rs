@@ -0,0 +1,199 @@
+use crate::{
+    graph::{
+        Graph,
+        advisory::{AdvisoryInformation, AdvisoryVulnerabilityInformation},
+        cvss::ScoreCreator,
+        vulnerability::creator::VulnerabilityCreator,
+    },
+    model::IngestResult,
+    service::{
+        Error, Warnings,
+        advisory::nvd::{NvdResponse, extract_scores},
+    },
+};
+use anyhow::anyhow;
+use sea_orm::{ConnectionTrait, TransactionTrait};
+use std::fmt::Debug;
+use tracing::instrument;
+use trustify_common::hashing::Digests;
+use trustify_entity::labels::Labels;
+
+/// Loader capable of parsing a response of the NVD CVE API.
+///
+/// The result is an advisory issued by NVD, next to the one of the CVE record itself. It carries
+/// the CVSS scores assigned by NVD, as well as those it aggregates from the CNA and ADPs (like
+/// CISA). In contrast to the CVE record, it never is the authoritative source of the
+/// vulnerability.
+pub struct NvdLoader<'g> {
+    graph: &'g Graph,
+}
+
+impl<'g> NvdLoader<'g> {
+    pub fn new(graph: &'g Graph) -> Self {
+        Self { graph }
+    }
+
+    #[instrument(skip(self, nvd, tx), err(level=tracing::Level::INFO))]
+    pub async fn load(
+        &self,
+        labels: impl Into<Labels> + Debug,
+        nvd: NvdResponse,
+        digests: &Digests,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<IngestResult, Error> {
+        let warnings = Warnings::new();
+
+        let cve = match <[_; 1]>::try_from(nvd.vulnerabilities) {
+            Ok([vulnerability]) => vulnerability.cve,
+            Err(vulnerabilities) => {
+                return Err(Error::InvalidContent(anyhow!(
+                    "NVD document must contain exactly one CVE, found {}",
+                    vulnerabilities.len()
+                )));
+            }
+        };
+
+        let id = cve.id.as_str();
+        let labels = labels.into().add("type", "nvd");
+
+        let published = cve.published();
+        let modified = cve.modified();
+
+        // Only ensure the vulnerability exists, its information is owned by the CVE record
+        let mut vuln_creator = VulnerabilityCreator::new();
+        vuln_creator.add(id, ());
+        vuln_creator.create(tx).await?;
+
+        let advisory = self
+            .graph
+            .ingest_advisory(
+                id,
+                labels,
+                digests,
+                AdvisoryInformation {
+                    id: id.to_string(),
+                    title: None,
+                    version: None,
+                    issuer: Some("NVD".to_string()),
+                    published,
+                    modified,
+                    withdrawn: cve.is_rejected().then_some(modified).flatten(),
+                },
+                tx,
+            )
+            .await?;
+
+        advisory
+            .link_to_vulnerability(
+                id,
+                Some(AdvisoryVulnerabilityInformation {
+                    title: None,
+                    summary: None,
+                    description: cve.description().map(ToString::to_string),
+                    reserved_date: None,
+                    discovery_date: None,
+                    release_date: published,
+                    cwes: cve.cwes(),
+                }),
+                tx,
+            )
+            .await?;
+
+        let mut score_creator = ScoreCreator::new(advisory.advisory.id);
+        extract_scores(&cve, &mut score_creator);
+        score_creator.create(tx).await?;
+
+        Graph::drop_vulnerability_descriptions_for_advisory(advisory.advisory.id, tx).await?;
+        Graph::add_vulnerability_descriptions(
+            id,
+            advisory.advisory.id,
+            cve.descriptions
+                .iter()
+                .map(|d| (d.lang.as_str(), d.value.as_str())),
+            tx,
+        )
+        .await?;
+
+        Ok(IngestResult {
+            id: advisory.advisory.id.to_string(),
+            document_id: Some(id.to_string()),
+            warnings: warnings.into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::service::advisory::test::{AssertScore, assert_scores};
+    use hex::ToHex;
+    use test_context::test_context;
+    use test_log::test;
+    use trustify_entity::advisory_vulnerability_score::{ScoreType, Severity};
+    use trustify_test_context::{TrustifyContext, document};
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn nvd_loader(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let graph = Graph::new();
+
+        let (nvd, digests): (NvdResponse, _) = document("nvd/CVE-2021-44228.json").await?;
+
+        let loader = NvdLoader::new(&graph);
+        ctx.db
+            .transaction(async |tx| {
+                loader
+                    .load(("file", "CVE-2021-44228.json"), nvd, &digests, tx)
+                    .await
+            })
+            .await?;
+
+        let advisory = graph
+            .get_advisory_by_digest(&digests.sha256.encode_hex::<String>(), &ctx.db)
+            .await?
+            .expect("must be ingested");
+        assert_eq!(advisory.advisory.identifier, "CVE-2021-44228");
+        assert_eq!(
+            advisory.advisory.labels.0.get("type").map(String::as_str),
+            Some("nvd")
+        );
+
+        let vulnerability = graph
+            .get_vulnerability("CVE-2021-44228", &ctx.db)
+            .await?
+            .expect("must be created");
+        // NVD must not claim to be the authoritative source
+        assert_eq!(vulnerability.vulnerability.authoritative_advisory_id, None);
+
+        assert_scores(
+            &ctx.db,
+            advisory.advisory.id,
+            [
+                AssertScore {
+                    vulnerability_id: "CVE-2021-44228",
+                    r#type: ScoreType::V2_0,
+                    severity: Severity::High,
+                    vector: "AV:N/AC:M/Au:N/C:C/I:C/A:C",
+                    score: 9.3,
+                },
+                AssertScore {
+                    vulnerability_id: "CVE-2021-44228",
+                    r#type: ScoreType::V3_1,
+                    severity: Severity::Critical,
+                    vector: "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:C/C:H/I:H/A:H",
+                    score: 10.0,
+                },
+                AssertScore {
+                    vulnerability_id: "CVE-2021-44228",
+                    r#type: ScoreType::V3_1,
+                    severity: Severity::Critical,
+                    vector: "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:C/C:H/I:H/A:H",
+                    score: 10.0,
+                },
+            ],
+        )
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,197 @@
+//! Support for the JSON format of the NVD CVE API (2.0)
+//!
+//! See: <https://nvd.nist.gov/developers/vulnerabilities>
+
+use crate::graph::cvss::ScoreCreator;
+use cvss::{Cvss, v2_0::CvssV2, v3::CvssV3, v4_0::CvssV4};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use time::{OffsetDateTime, PrimitiveDateTime, format_description::well_known::Iso8601};
+
+pub mod loader;
+
+/// The value of the `format` field of an NVD CVE API response.
+pub const FORMAT: &str = "NVD_CVE";
+
+/// The source identifier used by CISA for its ADP (authorized data publisher) content.
+pub const CISA_ADP: &str = "134c704f-9b21-4f2e-91b3-4a467353bcc0";
+
+/// A response of the NVD CVE API
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NvdResponse {
+    pub format: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub vulnerabilities: Vec<NvdVulnerability>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NvdVulnerability {
+    pub cve: NvdCve,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NvdCve {
+    pub id: String,
+    #[serde(default)]
+    pub source_identifier: Option<String>,
+    #[serde(default)]
+    pub published: Option<String>,
+    #[serde(default)]
+    pub last_modified: Option<String>,
+    #[serde(default)]
+    pub vuln_status: Option<String>,
+    #[serde(default)]
+    pub descriptions: Vec<LangString>,
+    #[serde(default)]
+    pub metrics: Metrics,
+    #[serde(default)]
+    pub weaknesses: Vec<Weakness>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LangString {
+    pub lang: String,
+    pub value: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Metrics {
+    #[serde(default)]
+    pub cvss_metric_v2: Vec<Metric>,
+    #[serde(default)]
+    pub cvss_metric_v30: Vec<Metric>,
+    #[serde(default)]
+    pub cvss_metric_v31: Vec<Metric>,
+    #[serde(default)]
+    pub cvss_metric_v40: Vec<Metric>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Metric {
+    /// The source of the metric, either an e-mail address or a UUID (like [`CISA_ADP`]).
+    pub source: String,
+    /// Either `Primary` or `Secondary`.
+    #[serde(default)]
+    pub r#type: Option<String>,
+    pub cvss_data: Value,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Weakness {
+    pub source: String,
+    #[serde(default)]
+    pub description: Vec<LangString>,
+}
+
+impl NvdCve {
+    pub fn published(&self) -> Option<OffsetDateTime> {
+        self.published.as_deref().and_then(timestamp)
+    }
+
+    pub fn modified(&self) -> Option<OffsetDateTime> {
+        self.last_modified.as_deref().and_then(timestamp)
+    }
+
+    pub fn is_rejected(&self) -> bool {
+        self.vuln_status.as_deref() == Some("Rejected")
+    }
+
+    /// The English description, falling back to the first one.
+    pub fn description(&self) -> Option<&str> {
+        self.descriptions
+            .iter()
+            .find(|d| d.lang == "en")
+            .or_else(|| self.descriptions.first())
+            .map(|d| d.value.as_str())
+    }
+
+    /// The CWE identifiers of all weaknesses, ignoring placeholders like `NVD-CWE-noinfo`.
+    pub fn cwes(&self) -> Option<Vec<String>> {
+        let mut cwes = self
+            .weaknesses
+            .iter()
+            .flat_map(|w| w.description.iter())
+            .map(|d| d.value.clone())
+            .filter(|value| value.starts_with("CWE-"))
+            .collect::<Vec<_>>();
+        cwes.sort();
+        cwes.dedup();
+
+        (!cwes.is_empty()).then_some(cwes)
+    }
+}
+
+/// Parse an NVD timestamp.
+///
+/// The NVD API reports timestamps without an offset, which are in UTC.
+fn timestamp(value: &str) -> Option<OffsetDateTime> {
+    OffsetDateTime::parse(value, &Iso8601::DEFAULT)
+        .or_else(|_| PrimitiveDateTime::parse(value, &Iso8601::DEFAULT).map(|t| t.assume_utc()))
+        .ok()
+}
+
+/// Parses the CVSS data of a metric, returning `None` if it doesn't match the expected version.
+fn cvss_data<T: DeserializeOwned>(metric: &Metric) -> Option<T> {
+    serde_json::from_value(metric.cvss_data.clone()).ok()
+}
+
+/// Extracts all CVSS scores of an NVD CVE record and registers them with the given [`ScoreCreator`].
+///
+/// This includes the scores from NVD itself, from the CNA, and from ADPs like CISA. Metrics which
+/// cannot be parsed are skipped.
+pub fn extract_scores(cve: &NvdCve, creator: &mut ScoreCreator) {
+    let Metrics {
+        cvss_metric_v2,
+        cvss_metric_v30,
+        cvss_metric_v31,
+        cvss_metric_v40,
+    } = &cve.metrics;
+
+    let scores = cvss_metric_v2
+        .iter()
+        .filter_map(|m| cvss_data(m).map(|cvss: CvssV2| Cvss::V2(cvss)))
+        .chain(
+            cvss_metric_v30
+                .iter()
+                .filter_map(|m| cvss_data(m).map(|cvss: CvssV3| Cvss::V3_0(cvss))),
+        )
+        .chain(
+            cvss_metric_v31
+                .iter()
+                .filter_map(|m| cvss_data(m).map(|cvss: CvssV3| Cvss::V3_1(cvss))),
+        )
+        .chain(
+            cvss_metric_v40
+                .iter()
+                .filter_map(|m| cvss_data(m).map(|cvss: CvssV4| Cvss::V4(cvss))),
+        );
+
+    for score in scores {
+        creator.add((cve.id.clone(), score));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use time::macros::datetime;
+
+    #[test]
+    fn parse_timestamp() {
+        assert_eq!(
+            timestamp("2021-12-10T10:15:09.143"),
+            Some(datetime!(2021-12-10 10:15:09.143 UTC))
+        );
+        assert_eq!(
+            timestamp("2021-12-10T10:15:09Z"),
+            Some(datetime!(2021-12-10 10:15:09 UTC))
+        );
+        assert_eq!(timestamp("yesterday"), None);
+    }
+}
@@ -12,21 +12,22 @@ use crate::{
             creator::PurlCreator,
             status_creator::{PurlStatusCreator, PurlStatusEntry},
         },
-        vulnerability::creator::VulnerabilityCreator,
+        vulnerability::{alias::AliasCreator, creator::VulnerabilityCreator},
     },
     model::IngestResult,
     service::{
         Error, Warnings,
         advisory::osv::{
-            extract_scores, extract_vulnerability_ids, prefix::get_well_known_prefixes, translate,
+            extract_aliases, extract_github, extract_scores, extract_vulnerability_ids,
+            prefix::get_well_known_prefixes, translate,
         },
     },
 };
 use osv::schema::{Ecosystem, Event, Range, RangeType, ReferenceType, Vulnerability};
 use sea_orm::{ConnectionTrait, TransactionTrait};
-use std::{collections::HashSet, fmt::Debug, str::FromStr};
+use std::{cmp::Ordering, collections::HashSet, fmt::Debug, str::FromStr};
 use tracing::instrument;
-use trustify_common::{hashing::Digests, purl::Purl, time::ChronoExt};
+use trustify_common::{hashing::Digests, purl::Purl, time::ChronoExt, version::Scheme};
 use trustify_entity::{labels::Labels, version_scheme::VersionScheme};
 
 pub struct OsvLoader<'g> {
@@ -49,7 +50,15 @@ impl<'g> OsvLoader<'g> {
     ) -> Result<IngestResult, Error> {
         let warnings = Warnings::new();
 
-        let labels = labels.into().add("type", "osv");
+        let mut labels = labels.into().add("type", "osv");
+
+        let github = extract_github(&osv);
+        if let Some(reviewed) = github.as_ref().and_then(|github| github.github_reviewed) {
+            labels = labels.add("github_reviewed", reviewed.to_string());
+        }
+        let cwes = github
+            .map(|github| github.cwe_ids)
+            .filter(|cwes| !cwes.is_empty());
 
         let issuer = issuer.or(detect_organization(&osv));
 
@@ -95,6 +104,7 @@ impl<'g> OsvLoader<'g> {
         let mut purl_status_creator = PurlStatusCreator::new();
         let mut base_purls = HashSet::new();
         let mut score_creator = ScoreCreator::new(advisory.advisory.id);
+        let mut alias_creator = AliasCreator::new(advisory.advisory.id);
 
         extract_scores(&osv, &mut score_creator);
 
@@ -111,12 +121,16 @@ impl<'g> OsvLoader<'g> {
                         reserved_date: None,
                         discovery_date: None,
                         release_date: None,
-                        cwes: None,
+                        cwes: cwes.clone(),
                     }),
                     tx,
                 )
                 .await?;
 
+            for alias in extract_aliases(&osv) {
+                alias_creator.add(cve_id, alias);
+            }
+
             for affected in osv.affected.iter().flatten() {
                 // we only process it when we have a package
 
@@ -298,6 +312,7 @@ impl<'g> OsvLoader<'g> {
                                     &purl,
                                     range,
                                     affected.versions.iter().flatten(),
+                                    ecosystem_ordering(&package.ecosystem),
                                 ) {
                                     purl_status_creator.add(entry);
                                 }
@@ -310,6 +325,7 @@ impl<'g> OsvLoader<'g> {
 
         purl_creator.create(tx).await?;
         score_creator.create(tx).await?;
+        alias_creator.create(tx).await?;
 
         // Create base PURLs for range-based status entries
         purl::batch_create_base_purls(base_purls, tx).await?;
@@ -332,64 +348,65 @@ fn build_package_status(
     version_scheme: VersionScheme,
 ) -> Vec<PurlStatusEntry> {
     let mut entries = Vec::new();
-    let parsed_range = events_to_range(&range.events);
-
-    let spec = match &parsed_range {
-        (Some(start), None) => Some(VersionSpec::Range(
-            Version::Inclusive(start.clone()),
-            Version::Unbounded,
-        )),
-        (None, Some((end, false))) => Some(VersionSpec::Range(
-            Version::Unbounded,
-            Version::Exclusive(end.clone()),
-        )),
-        (None, Some((end, true))) => Some(VersionSpec::Range(
-            Version::Unbounded,
-            Version::Inclusive(end.clone()),
-        )),
-        (Some(start), Some((end, false))) => Some(VersionSpec::Range(
-            Version::Inclusive(start.clone()),
-            Version::Exclusive(end.clone()),
-        )),
-        (Some(start), Some((end, true))) => Some(VersionSpec::Range(
-            Version::Inclusive(start.clone()),
-            Version::Inclusive(end.clone()),
-        )),
-        (None, None) => None,
-    };
 
-    if let Some(spec) = spec {
-        entries.push(PurlStatusEntry {
-            advisory_id: advisory_vuln.advisory.advisory.id,
-            vulnerability_id: advisory_vuln
-                .advisory_vulnerability
-                .vulnerability_id
-                .clone(),
-            purl: purl.clone(),
-            status: "affected".to_string(),
-            version_info: VersionInfo {
-                scheme: version_scheme,
-                spec,
-            },
-            context_cpe: None,
-        });
-    }
+    for parsed_range in events_to_ranges(&range.events, version_scheme.ordering()) {
+        let spec = match &parsed_range {
+            (Some(start), None) => Some(VersionSpec::Range(
+                Version::Inclusive(start.clone()),
+                Version::Unbounded,
+            )),
+            (None, Some((end, false))) => Some(VersionSpec::Range(
+                Version::Unbounded,
+                Version::Exclusive(end.clone()),
+            )),
+            (None, Some((end, true))) => Some(VersionSpec::Range(
+                Version::Unbounded,
+                Version::Inclusive(end.clone()),
+            )),
+            (Some(start), Some((end, false))) => Some(VersionSpec::Range(
+                Version::Inclusive(start.clone()),
+                Version::Exclusive(end.clone()),
+            )),
+            (Some(start), Some((end, true))) => Some(VersionSpec::Range(
+                Version::Inclusive(start.clone()),
+                Version::Inclusive(end.clone()),
+            )),
+            (None, None) => None,
+        };
 
-    if let (_, Some((fixed, false))) = &parsed_range {
-        entries.push(PurlStatusEntry {
-            advisory_id: advisory_vuln.advisory.advisory.id,
-            vulnerability_id: advisory_vuln
-                .advisory_vulnerability
-                .vulnerability_id
-                .clone(),
-            purl: purl.clone(),
-            status: "fixed".to_string(),
-            version_info: VersionInfo {
-                scheme: version_scheme,
-                spec: VersionSpec::Exact(fixed.clone()),
-            },
-            context_cpe: None,
-        });
+        if let Some(spec) = spec {
+            entries.push(PurlStatusEntry {
+                advisory_id: advisory_vuln.advisory.advisory.id,
+                vulnerability_id: advisory_vuln
+                    .advisory_vulnerability
+                    .vulnerability_id
+                    .clone(),
+                purl: purl.clone(),
+                status: "affected".to_string(),
+                version_info: VersionInfo {
+                    scheme: version_scheme,
+                    spec,
+                },
+                context_cpe: None,
+            });
+        }
+
+        if let (_, Some((fixed, false))) = &parsed_range {
+            entries.push(PurlStatusEntry {
+                advisory_id: advisory_vuln.advisory.advisory.id,
+                vulnerability_id: advisory_vuln
+                    .advisory_vulnerability
+                    .vulnerability_id
+                    .clone(),
+                purl: purl.clone(),
+                status: "fixed".to_string(),
+                version_info: VersionInfo {
+                    scheme: version_scheme,
+                    spec: VersionSpec::Exact(fixed.clone()),
+                },
+                context_cpe: None,
+            });
+        }
     }
 
     entries
@@ -401,13 +418,14 @@ fn build_package_status_versions<'a>(
     purl: &Purl,
     range: &Range,
     versions: impl IntoIterator<Item = &'a String>,
+    ordering: Option<Scheme>,
 ) -> Vec<PurlStatusEntry> {
     // the list of versions, sorted by the range type
     let versions = versions.into_iter().cloned().collect::<Vec<_>>();
     let mut entries = Vec::new();
 
     let mut start = None;
-    for event in &range.events {
+    for event in sort_events(&range.events, ordering) {
         match event {
             Event::Introduced(version) => {
                 start = Some(version);
@@ -421,6 +439,7 @@ fn build_package_status_versions<'a>(
                         start,
                         Some(version),
                         &versions,
+                        ordering,
                     ));
                 }
 
@@ -454,6 +473,7 @@ fn build_package_status_versions<'a>(
             start,
             None,
             &versions,
+            ordering,
         ));
     }
 
@@ -469,8 +489,9 @@ fn build_range_from(
     // exclusive end
     end: Option<&str>,
     versions: &[impl AsRef<str>],
+    ordering: Option<Scheme>,
 ) -> Vec<PurlStatusEntry> {
-    let matched_versions = match_versions(versions, start, end);
+    let matched_versions = match_versions(versions, start, end, ordering);
 
     matched_versions
         .into_iter()
@@ -497,12 +518,27 @@ fn build_range_from(
 /// right order. So we search through this list, by start and end events. Translating this into
 /// exact version matches.
 ///
+/// If the versions of the ecosystem can be ordered, all listed versions from the start up to the
+/// end are matched instead, no matter their position in the list.
+///
 /// See: <https://ossf.github.io/osv-schema/#affectedrangestype-field>
 fn match_versions<'v>(
     versions: &'v [impl AsRef<str>],
     start: &str,
     end: Option<&str>,
+    ordering: Option<Scheme>,
 ) -> Vec<&'v str> {
+    if let Some(scheme) = ordering {
+        return versions
+            .iter()
+            .map(AsRef::as_ref)
+            .filter(|version| {
+                compare_event_versions(scheme, version, start).is_ge()
+                    && end.is_none_or(|end| compare_event_versions(scheme, version, end).is_lt())
+            })
+            .collect();
+    }
+
     let mut matches = None;
 
     for version in versions {
@@ -539,26 +575,91 @@ fn detect_organization(osv: &Vulnerability) -> Option<String> {
     None
 }
 
-fn events_to_range(events: &[Event]) -> (Option<String>, Option<(String, bool)>) {
-    let start = events.iter().find_map(|e| {
-        if let Event::Introduced(version) = e {
-            Some(version.clone())
-        } else {
-            None
+/// The ordering of versions of ecosystems which are not matched by a version scheme of our own
+fn ecosystem_ordering(ecosystem: &Ecosystem) -> Option<Scheme> {
+    match ecosystem {
+        Ecosystem::Debian(_) | Ecosystem::Ubuntu { .. } => Some(Scheme::Deb),
+        Ecosystem::AlmaLinux(_)
+        | Ecosystem::Mageia(_)
+        | Ecosystem::OpenEuler
+        | Ecosystem::OpenSUSE(_)
+        | Ecosystem::RedHat(_)
+        | Ecosystem::RockyLinux(_)
+        | Ecosystem::SUSE(_) => Some(Scheme::Rpm),
+        _ => None,
+    }
+}
+
+/// Compare the versions of two events, the introduced version `0` being the earliest of all.
+fn compare_event_versions(scheme: Scheme, a: &str, b: &str) -> Ordering {
+    match (a == "0", b == "0") {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => scheme.compare(a, b),
+    }
+}
+
+/// Sort the events of a range by their version.
+///
+/// OSV doesn't require events to be sorted. Events are kept in their order if their versions
+/// can't be ordered, like commits of a git range.
+fn sort_events(events: &[Event], ordering: Option<Scheme>) -> Vec<&Event> {
+    fn version(event: &Event) -> Option<&str> {
+        match event {
+            Event::Introduced(version)
+            | Event::Fixed(version)
+            | Event::LastAffected(version)
+            | Event::Limit(version) => Some(version),
+            _ => None,
         }
-    });
-
-    let end = events.iter().find_map(|e| {
-        if let Event::Fixed(version) = e {
-            Some((version.clone(), false))
-        } else if let Event::LastAffected(version) = e {
-            Some((version.clone(), true))
-        } else {
-            None
+    }
+
+    let mut events = events.iter().collect::<Vec<_>>();
+    if let Some(scheme) = ordering {
+        events.sort_by(|a, b| match (version(a), version(b)) {
+            (Some(a), Some(b)) => compare_event_versions(scheme, a, b),
+            _ => Ordering::Equal,
+        });
+    }
+    events
+}
+
+/// An interval of affected versions: its start, and its end with whether that end is inclusive.
+type AffectedRange = (Option<String>, Option<(String, bool)>);
+
+/// Turn the events of a range into the intervals of affected versions they describe.
+///
+/// Each interval has an inclusive start, and an end which is inclusive for a last affected
+/// version, and exclusive for a fixed one. A range can consist of several intervals, for
+/// different branches of a project.
+fn events_to_ranges(events: &[Event], ordering: Option<Scheme>) -> Vec<AffectedRange> {
+    let mut result = vec![];
+    let mut start = None;
+
+    for event in sort_events(events, ordering) {
+        let end = match event {
+            Event::Introduced(version) => {
+                // already affected, a later introduced version doesn't change that
+                start.get_or_insert_with(|| version.clone());
+                continue;
+            }
+            Event::Fixed(version) => (version.clone(), false),
+            Event::LastAffected(version) => (version.clone(), true),
+            _ => continue,
+        };
+
+        // an end without a start only makes sense as the first event
+        if start.is_some() || result.is_empty() {
+            result.push((start.take(), Some(end)));
         }
-    });
+    }
+
+    if start.is_some() {
+        result.push((start, None));
+    }
 
-    (start, end)
+    result
 }
 
 #[cfg(test)]
@@ -822,6 +923,41 @@ mod test {
     #[test_log::test]
     fn test_matches(#[case] start: &str, #[case] end: Option<&str>, #[case] result: Vec<&str>) {
         const INPUT: &[&str] = &["a", "b", "c", "d", "e", "f", "g"];
-        assert_eq!(match_versions(INPUT, start, end), result);
+        assert_eq!(match_versions(INPUT, start, end, None), result);
+    }
+
+    #[rstest]
+    #[case("0", Some("2.36-9+deb12u4"), vec!["2.36-9", "2.36-9+deb12u1"])]
+    #[case("2.36-9+deb12u1", None, vec!["2.36-9+deb12u1", "2.36-9+deb12u4", "2.36-10"])]
+    #[test_log::test]
+    fn test_matches_ordered(
+        #[case] start: &str,
+        #[case] end: Option<&str>,
+        #[case] result: Vec<&str>,
+    ) {
+        const INPUT: &[&str] = &["2.36-10", "2.36-9+deb12u4", "2.36-9", "2.36-9+deb12u1"];
+        let mut matches = match_versions(INPUT, start, end, Some(Scheme::Deb));
+        matches.sort_by(|a, b| Scheme::Deb.compare(a, b));
+        assert_eq!(matches, result);
+    }
+
+    #[test_log::test]
+    fn test_events_to_ranges() {
+        let events = [
+            Event::Introduced("2.0.0".into()),
+            Event::Fixed("2.3.1".into()),
+            Event::Introduced("0".into()),
+            Event::Fixed("1.10.2".into()),
+            Event::Introduced("3.0.0".into()),
+        ];
+
+        assert_eq!(
+            events_to_ranges(&events, Some(Scheme::Semver)),
+            vec![
+                (Some("0".into()), Some(("1.10.2".into(), false))),
+                (Some("2.0.0".into()), Some(("2.3.1".into(), false))),
+                (Some("3.0.0".into()), None),
+            ]
+        );
     }
 }
@@ -45,6 +45,33 @@ pub fn extract_vulnerability_ids(osv: &Vulnerability) -> impl IntoIterator<Item
         .map(|s| s.as_str())
 }
 
+/// extract aliases: all identifiers of the document, which are not vulnerability IDs
+pub fn extract_aliases(osv: &Vulnerability) -> impl IntoIterator<Item = &str> {
+    std::iter::once(osv.id.as_str())
+        .chain(osv.aliases.iter().flatten().map(|s| s.as_str()))
+        .filter(|id| !id.starts_with("CVE-"))
+}
+
+/// The GitHub specific information of a GitHub Security Advisory
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+pub struct GithubDatabaseSpecific {
+    #[serde(default)]
+    pub cwe_ids: Vec<String>,
+    #[serde(default)]
+    pub github_reviewed: Option<bool>,
+}
+
+/// extract the GitHub extensions, if the document is a GitHub Security Advisory
+pub fn extract_github(osv: &Vulnerability) -> Option<GithubDatabaseSpecific> {
+    if !osv.id.starts_with("GHSA-") {
+        return None;
+    }
+
+    osv.database_specific
+        .as_ref()
+        .and_then(|value| serde_json::from_value(value.clone()).ok())
+}
+
 /// extract scores from OSV
 pub fn extract_scores(osv: &Vulnerability, creator: &mut ScoreCreator) {
     // Get all vulnerability IDs upfront
@@ -0,0 +1,404 @@
+//! Loader for archives (zip, tar, tar.gz) of arbitrary documents
+
+use crate::{
+    graph::Graph,
+    model::IngestResult,
+    service::{Error, Format, ValidationLevel, limits::Limits, lock, quota::Quotas},
+};
+use bytes::Bytes;
+use flate2::read::GzDecoder;
+use sea_orm::{ConnectionTrait, TransactionTrait};
+use std::{
+    collections::BTreeMap,
+    io::{Cursor, Read},
+};
+use tokio::{sync::mpsc, task::spawn_blocking};
+use tracing::instrument;
+use trustify_entity::labels::Labels;
+use trustify_module_storage::service::dispatch::DispatchBackend;
+
+/// The label carrying the name of the entry in the archive a document was loaded from.
+pub const LABEL_ARCHIVE_FILE: &str = "archiveFile";
+
+/// The result of ingesting an archive
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+pub struct ArchiveIngestResult {
+    /// Successfully ingested documents, by their name in the archive
+    pub files: BTreeMap<String, IngestResult>,
+    /// Documents which failed to ingest, by their name in the archive
+    pub failures: BTreeMap<String, String>,
+}
+
+/// The default maximum number of entries of an archive.
+pub const DEFAULT_MAX_ENTRIES: usize = 10_000;
+
+/// The default maximum size of all entries of an archive, after decompression.
+pub const DEFAULT_MAX_TOTAL: u64 = 10 * 1024 * 1024 * 1024;
+
+pub struct ArchiveLoader<'g> {
+    graph: &'g Graph,
+    storage: &'g DispatchBackend,
+    limit: usize,
+    max_entries: usize,
+    max_total: u64,
+    limits: Limits,
+    quotas: Option<&'g Quotas>,
+}
+
+impl<'g> ArchiveLoader<'g> {
+    pub fn new(graph: &'g Graph, storage: &'g DispatchBackend, limit: usize) -> Self {
+        Self {
+            graph,
+            storage,
+            limit,
+            max_entries: DEFAULT_MAX_ENTRIES,
+            max_total: DEFAULT_MAX_TOTAL,
+            limits: Limits::default(),
+            quotas: None,
+        }
+    }
+
+    /// Set the maximum number of entries of the archive, including directories.
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Set the maximum size of all entries of the archive, after decompression.
+    pub fn max_total(mut self, max_total: u64) -> Self {
+        self.max_total = max_total;
+        self
+    }
+
+    /// Set the limits applied when loading each document.
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Set the storage quotas, rejecting documents which exceed them.
+    pub fn quotas(mut self, quotas: Option<&'g Quotas>) -> Self {
+        self.quotas = quotas;
+        self
+    }
+
+    /// Ingest all documents of a zip, tar, or tar.gz archive.
+    ///
+    /// The format of each entry is detected individually. Entries which fail to ingest are
+    /// reported, but don't fail the archive as a whole. Exceeding the maximum number of entries,
+    /// or their maximum total size, does.
+    ///
+    /// The archive is extracted on a blocking thread, handing over one entry at a time.
+    #[instrument(skip(self, buffer, tx), err(level=tracing::Level::INFO))]
+    pub async fn load(
+        &self,
+        labels: Labels,
+        buffer: Bytes,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<ArchiveIngestResult, Error> {
+        let (sender, mut receiver) = mpsc::channel(1);
+        let extractor = Extractor {
+            limit: self.limit,
+            max_entries: self.max_entries,
+            max_total: self.max_total,
+            entries: 0,
+            total: 0,
+            sender,
+        };
+        let extraction = spawn_blocking(move || extractor.run(&buffer));
+
+        let mut result = ArchiveIngestResult::default();
+
+        while let Some((name, data)) = receiver.recv().await {
+            let data = match data {
+                Ok(data) => data,
+                Err(err) => {
+                    result.failures.insert(name, err.to_string());
+                    continue;
+                }
+            };
+
+            log::debug!("Processing archive entry: {name}");
+
+            let format = match Format::from_bytes(&data) {
+                Ok(format) => format,
+                Err(err) => {
+                    result.failures.insert(name, err.to_string());
+                    continue;
+                }
+            };
+
+            let labels = labels.clone().add(LABEL_ARCHIVE_FILE, &name);
+
+            if let Some(quotas) = self.quotas
+                && let Err(err) = quotas.check(&labels, data.len() as u64, tx).await
+            {
+                result.failures.insert(name, err.to_string());
+                continue;
+            }
+
+            let digests = lock::store_for_reference(self.storage, &data, tx).await?;
+
+            // We need to box it, to work around async recursion limits
+            let loaded = Box::pin(async move {
+                format
+                    .load(
+                        self.graph,
+                        labels,
+                        None,
+                        &digests,
+                        &data,
+                        ValidationLevel::default(),
+                        &self.limits,
+                        tx,
+                    )
+                    .await
+            })
+            .await;
+
+            match loaded {
+                Ok(loaded) => {
+                    result.files.insert(name, loaded);
+                }
+                Err(err) => {
+                    result.failures.insert(name, err.to_string());
+                }
+            }
+        }
+
+        // fails the archive if it is invalid, or exceeds its limits
+        extraction.await??;
+
+        Ok(result)
+    }
+}
+
+/// An entry of an archive, failing if it exceeds the limit.
+type Entry = (String, Result<Vec<u8>, Error>);
+
+/// Extracts the files of an archive, sending them one at a time.
+///
+/// Extraction stops when the receiving side is gone.
+struct Extractor {
+    limit: usize,
+    max_entries: usize,
+    max_total: u64,
+    entries: usize,
+    total: u64,
+    sender: mpsc::Sender<Entry>,
+}
+
+impl Extractor {
+    /// Extract the files of the archive, detecting the type of archive by its magic bytes.
+    fn run(mut self, buffer: &[u8]) -> Result<(), Error> {
+        match buffer {
+            [0x50, 0x4B, 0x03, 0x04, ..] => self.zip(buffer),
+            [0x1F, 0x8B, ..] => self.tar(GzDecoder::new(buffer)),
+            _ if buffer.get(257..262) == Some(&b"ustar"[..]) => self.tar(buffer),
+            _ => Err(Error::UnsupportedFormat(
+                "only zip, tar, and tar.gz archives are supported".into(),
+            )),
+        }
+    }
+
+    fn zip(&mut self, buffer: &[u8]) -> Result<(), Error> {
+        let mut zip = zip::ZipArchive::new(Cursor::new(buffer))?;
+
+        for i in 0..zip.len() {
+            self.count()?;
+
+            let file = zip.by_index(i)?;
+            if !file.is_file() {
+                continue;
+            }
+            let Some(name) = file.enclosed_name() else {
+                continue;
+            };
+            let name = name.display().to_string();
+            if skip(&name) {
+                continue;
+            }
+
+            if !self.send(name, file)? {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn tar(&mut self, reader: impl Read) -> Result<(), Error> {
+        let mut tar = tar::Archive::new(reader);
+
+        for entry in tar.entries()? {
+            self.count()?;
+
+            let entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let name = entry.path()?.display().to_string();
+            if skip(&name) {
+                continue;
+            }
+
+            if !self.send(name, entry)? {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Count an entry, failing if there are too many.
+    fn count(&mut self) -> Result<(), Error> {
+        self.entries += 1;
+        if self.entries > self.max_entries {
+            return Err(Error::LimitExceeded(format!(
+                "archive has more than {} entries",
+                self.max_entries
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Read and send an entry, returning whether extraction should continue.
+    fn send(&mut self, name: String, entry: impl Read) -> Result<bool, Error> {
+        let data = self.read(entry);
+
+        if let Ok(data) = &data {
+            self.total += data.len() as u64;
+            if self.total > self.max_total {
+                return Err(Error::LimitExceeded(format!(
+                    "archive exceeds the total size of {} bytes",
+                    self.max_total
+                )));
+            }
+        }
+
+        Ok(self.sender.blocking_send((name, data)).is_ok())
+    }
+
+    /// Read an entry, up to the limit.
+    fn read(&self, entry: impl Read) -> Result<Vec<u8>, Error> {
+        let mut data = vec![];
+        entry.take(self.limit as u64 + 1).read_to_end(&mut data)?;
+
+        if data.len() > self.limit {
+            return Err(Error::PayloadTooLarge);
+        }
+
+        Ok(data)
+    }
+}
+
+/// Files created by operating systems, which don't need to be reported.
+fn skip(name: &str) -> bool {
+    name == ".DS_Store" || name.ends_with("/.DS_Store") || name.contains("__MACOSX/")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::service::IngestorService;
+    use flate2::{Compression, write::GzEncoder};
+    use std::io::Write;
+    use test_context::test_context;
+    use test_log::test;
+    use trustify_test_context::{TrustifyContext, document_bytes};
+    use zip::{ZipWriter, write::FileOptions};
+
+    const DOCUMENTS: [&str; 2] = ["zookeeper-3.9.2-cyclonedx.json", "csaf/cve-2023-0044.json"];
+
+    async fn ingest(
+        ctx: &TrustifyContext,
+        data: Vec<u8>,
+    ) -> Result<ArchiveIngestResult, anyhow::Error> {
+        let ingestor = IngestorService::new(Graph::new(), ctx.storage.clone(), None);
+        Ok(ctx
+            .db
+            .transaction(async |tx| {
+                ingestor
+                    .ingest_archive(data.clone(), ("source", "test"), 1024 * 1024, tx)
+                    .await
+            })
+            .await?)
+    }
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn ingest_tar_gz(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let mut tar = tar::Builder::new(GzEncoder::new(vec![], Compression::default()));
+        for name in DOCUMENTS {
+            let data = document_bytes(name).await?;
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as _);
+            header.set_mode(0o644);
+            tar.append_data(&mut header, name, &*data)?;
+        }
+        let mut header = tar::Header::new_gnu();
+        header.set_size(7);
+        header.set_mode(0o644);
+        tar.append_data(&mut header, "README.txt", &b"nothing"[..])?;
+        let data = tar.into_inner()?.finish()?;
+
+        let result = ingest(ctx, data).await?;
+
+        assert_eq!(result.files.len(), 2);
+        assert!(result.files.contains_key("csaf/cve-2023-0044.json"));
+        assert_eq!(result.failures.len(), 1);
+        assert!(result.failures.contains_key("README.txt"));
+
+        Ok(())
+    }
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn ingest_zip(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let mut zip = ZipWriter::new(Cursor::new(vec![]));
+        for name in DOCUMENTS {
+            zip.start_file(name, FileOptions::<()>::default())?;
+            zip.write_all(&document_bytes(name).await?)?;
+        }
+        let data = zip.finish()?.into_inner();
+
+        let result = ingest(ctx, data).await?;
+
+        assert_eq!(result.files.len(), 2);
+        assert!(result.failures.is_empty(), "{:?}", result.failures);
+
+        Ok(())
+    }
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn reject_too_many_entries(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let mut zip = ZipWriter::new(Cursor::new(vec![]));
+        for name in DOCUMENTS {
+            zip.start_file(name, FileOptions::<()>::default())?;
+            zip.write_all(&document_bytes(name).await?)?;
+        }
+        let data = Bytes::from(zip.finish()?.into_inner());
+
+        let graph = Graph::new();
+        let storage = DispatchBackend::from(ctx.storage.clone());
+        let tx = ctx.db.begin().await?;
+
+        let result = ArchiveLoader::new(&graph, &storage, 1024 * 1024)
+            .max_entries(1)
+            .load(Labels::new(), data.clone(), &tx)
+            .await;
+        assert!(matches!(result, Err(Error::LimitExceeded(_))), "{result:?}");
+
+        let result = ArchiveLoader::new(&graph, &storage, 1024 * 1024)
+            .max_total(1024)
+            .load(Labels::new(), data, &tx)
+            .await;
+        assert!(matches!(result, Err(Error::LimitExceeded(_))), "{result:?}");
+
+        tx.rollback().await?;
+
+        Ok(())
+    }
+}
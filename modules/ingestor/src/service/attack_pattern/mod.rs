@@ -0,0 +1,176 @@
+use crate::{
+    model::IngestResult,
+    service::{Error, weakness::gather_content},
+};
+use hex::ToHex;
+use roxmltree::{Document, Node};
+use sea_orm::{ConnectionTrait, EntityTrait, Iterable, Set, TransactionTrait};
+use sea_query::OnConflict;
+use std::str::from_utf8;
+use tracing::instrument;
+use trustify_common::{db::chunk::EntityChunkedIter, hashing::Digests};
+use trustify_entity::{attack_pattern, labels::Labels};
+
+/// Loader for the CAPEC (Common Attack Pattern Enumeration and Classification) catalog
+///
+/// Attack patterns are linked to weaknesses through their `Related_Weaknesses`.
+#[derive(Default)]
+pub struct CapecCatalogLoader {}
+
+impl CapecCatalogLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[instrument(skip(self, buffer, tx), err(level=tracing::Level::INFO))]
+    pub async fn load_bytes(
+        &self,
+        labels: Labels,
+        buffer: &[u8],
+        digests: &Digests,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<IngestResult, Error> {
+        let xml = from_utf8(buffer)?;
+
+        let document = Document::parse(xml)?;
+
+        self.load(labels, &document, digests, tx).await
+    }
+
+    #[instrument(skip(self, doc, tx), err(level=tracing::Level::INFO))]
+    pub async fn load<'x>(
+        &self,
+        _labels: Labels,
+        doc: &Document<'x>,
+        digests: &Digests,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<IngestResult, Error> {
+        let batch = doc
+            .root_element()
+            .children()
+            .filter(|e| e.has_tag_name("Attack_Patterns"))
+            .flat_map(|e| e.children())
+            .filter(|e| e.has_tag_name("Attack_Pattern"))
+            .filter_map(|e| attack_pattern(&e));
+
+        for chunk in &batch.chunked() {
+            attack_pattern::Entity::insert_many(chunk)
+                .on_conflict(
+                    OnConflict::column(attack_pattern::Column::Id)
+                        .update_columns(attack_pattern::Column::iter())
+                        .to_owned(),
+                )
+                .exec(tx)
+                .await?;
+        }
+
+        Ok(IngestResult {
+            // Like the CWE catalog, this doesn't result in a source document. So we return the
+            // digest.
+            id: digests.sha512.encode_hex(),
+            document_id: Some("CAPEC".to_string()),
+            warnings: vec![],
+        })
+    }
+}
+
+fn attack_pattern(node: &Node) -> Option<attack_pattern::ActiveModel> {
+    let id = node.attribute("ID")?;
+
+    let text = |name: &str| {
+        node.children()
+            .find(|e| e.has_tag_name(name))
+            .map(|e| gather_content(&e))
+            .filter(|text| !text.is_empty())
+    };
+
+    let mut child_of = vec![];
+    let mut parent_of = vec![];
+    for related in node
+        .children()
+        .filter(|e| e.has_tag_name("Related_Attack_Patterns"))
+        .flat_map(|e| e.children())
+        .filter(|e| e.has_tag_name("Related_Attack_Pattern"))
+    {
+        if let Some(target) = related.attribute("CAPEC_ID")
+            && let Some(dest) = match related.attribute("Nature") {
+                Some("ChildOf") => Some(&mut child_of),
+                Some("ParentOf") => Some(&mut parent_of),
+                _ => None,
+            }
+        {
+            dest.push(format!("CAPEC-{target}"));
+        }
+    }
+
+    let related_weaknesses = node
+        .children()
+        .filter(|e| e.has_tag_name("Related_Weaknesses"))
+        .flat_map(|e| e.children())
+        .filter(|e| e.has_tag_name("Related_Weakness"))
+        .filter_map(|e| e.attribute("CWE_ID"))
+        .map(|id| format!("CWE-{id}"))
+        .collect();
+
+    Some(attack_pattern::ActiveModel {
+        id: Set(format!("CAPEC-{id}")),
+        name: Set(node.attribute("Name").map(ToString::to_string)),
+        description: Set(text("Description")),
+        likelihood: Set(text("Likelihood_Of_Attack")),
+        severity: Set(text("Typical_Severity")),
+        child_of: Set(normalize(child_of)),
+        parent_of: Set(normalize(parent_of)),
+        related_weaknesses: Set(normalize(related_weaknesses)),
+    })
+}
+
+fn normalize(vec: Vec<String>) -> Option<Vec<String>> {
+    if vec.is_empty() { None } else { Some(vec) }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use test_context::test_context;
+    use test_log::test;
+    use trustify_test_context::{TrustifyContext, document_bytes};
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn load_capec(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let data = document_bytes("capec/capec_excerpt.xml").await?;
+        let digests = Digests::digest(&data);
+
+        // should work twice without error/conflict.
+
+        for _ in 0..2 {
+            ctx.db
+                .transaction(async |tx| {
+                    CapecCatalogLoader::new()
+                        .load_bytes(Labels::default(), &data, &digests, tx)
+                        .await
+                })
+                .await?;
+        }
+
+        let pattern = attack_pattern::Entity::find_by_id("CAPEC-1")
+            .one(&ctx.db)
+            .await?
+            .expect("must be loaded");
+        assert_eq!(
+            pattern.name.as_deref(),
+            Some("Accessing Functionality Not Properly Constrained by ACLs")
+        );
+        assert_eq!(pattern.child_of, Some(vec!["CAPEC-122".to_string()]));
+        assert_eq!(
+            pattern.related_weaknesses,
+            Some(vec![
+                "CWE-276".to_string(),
+                "CWE-285".to_string(),
+                "CWE-434".to_string()
+            ])
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,281 @@
+//! Ingestion of in-toto attestations carrying SLSA provenance, wrapped in a DSSE envelope.
+//!
+//! See: <https://github.com/secure-systems-lab/dsse> and <https://slsa.dev/spec/v1.0/provenance>
+//!
+//! Only the structure of the envelope is verified, not the signatures.
+
+use crate::{
+    graph::{Graph, attestation::AttestationInformation},
+    model::IngestResult,
+    service::Error,
+};
+use anyhow::anyhow;
+use base64::{Engine, prelude::BASE64_STANDARD};
+use sea_orm::{ConnectionTrait, TransactionTrait};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use tracing::instrument;
+use trustify_common::hashing::Digests;
+use trustify_entity::{attestation::Material, labels::Labels};
+
+/// The payload type of in-toto statements
+pub const IN_TOTO_PAYLOAD_TYPE: &str = "application/vnd.in-toto+json";
+
+/// A DSSE envelope
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Envelope {
+    pub payload_type: String,
+    /// The base64 encoded payload
+    pub payload: String,
+    pub signatures: Vec<Signature>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Signature {
+    #[serde(default)]
+    pub keyid: Option<String>,
+    /// The base64 encoded signature
+    pub sig: String,
+}
+
+/// An in-toto statement
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Statement {
+    #[serde(rename = "_type")]
+    pub r#type: String,
+    pub subject: Vec<Subject>,
+    pub predicate_type: String,
+    #[serde(default)]
+    pub predicate: Value,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Subject {
+    #[serde(default)]
+    pub name: String,
+    pub digest: BTreeMap<String, String>,
+}
+
+impl Envelope {
+    /// Verify the structure of the envelope, and extract the statement.
+    pub fn statement(&self) -> Result<Statement, Error> {
+        if self.payload_type != IN_TOTO_PAYLOAD_TYPE {
+            return Err(invalid(format!(
+                "unsupported payload type: {}",
+                self.payload_type
+            )));
+        }
+
+        if self.signatures.is_empty() {
+            return Err(invalid("envelope has no signatures"));
+        }
+        for signature in &self.signatures {
+            match BASE64_STANDARD.decode(&signature.sig) {
+                Ok(sig) if !sig.is_empty() => {}
+                _ => return Err(invalid("envelope has an invalid signature")),
+            }
+        }
+
+        let payload = BASE64_STANDARD
+            .decode(&self.payload)
+            .map_err(|err| invalid(format!("invalid payload encoding: {err}")))?;
+        let statement: Statement = serde_json::from_slice(&payload)?;
+
+        if !statement
+            .r#type
+            .starts_with("https://in-toto.io/Statement/")
+        {
+            return Err(invalid(format!(
+                "unsupported statement type: {}",
+                statement.r#type
+            )));
+        }
+        if statement.subject.is_empty() {
+            return Err(invalid("statement has no subject"));
+        }
+
+        Ok(statement)
+    }
+}
+
+impl Statement {
+    /// Extract the provenance information, supporting SLSA provenance v0.2 and v1.
+    pub fn provenance(&self) -> Result<AttestationInformation, Error> {
+        let p = &self.predicate;
+        let text = |pointer: &str| p.pointer(pointer).and_then(Value::as_str);
+        let string = |pointer: &str| text(pointer).map(ToString::to_string);
+        let timestamp = |pointer: &str| {
+            text(pointer).and_then(|value| OffsetDateTime::parse(value, &Rfc3339).ok())
+        };
+
+        let mut info = match self.predicate_type.as_str() {
+            "https://slsa.dev/provenance/v1" => AttestationInformation {
+                builder_id: string("/runDetails/builder/id"),
+                build_type: string("/buildDefinition/buildType"),
+                invocation_id: string("/runDetails/metadata/invocationId"),
+                started_on: timestamp("/runDetails/metadata/startedOn"),
+                finished_on: timestamp("/runDetails/metadata/finishedOn"),
+                materials: materials(p.pointer("/buildDefinition/resolvedDependencies")),
+                ..Default::default()
+            },
+            "https://slsa.dev/provenance/v0.2" => AttestationInformation {
+                builder_id: string("/builder/id"),
+                build_type: string("/buildType"),
+                invocation_id: string("/metadata/buildInvocationId"),
+                started_on: timestamp("/metadata/buildStartedOn"),
+                finished_on: timestamp("/metadata/buildFinishedOn"),
+                materials: materials(p.pointer("/materials")),
+                ..Default::default()
+            },
+            other => {
+                return Err(invalid(format!("unsupported predicate type: {other}")));
+            }
+        };
+
+        info.predicate_type = self.predicate_type.clone();
+        info.subjects = self
+            .subject
+            .iter()
+            .flat_map(|subject| {
+                subject
+                    .digest
+                    .iter()
+                    .map(|(alg, digest)| (subject.name.clone(), alg.clone(), digest.clone()))
+            })
+            .collect();
+
+        Ok(info)
+    }
+}
+
+fn materials(value: Option<&Value>) -> Vec<Material> {
+    value
+        .cloned()
+        .and_then(|value| serde_json::from_value::<Vec<Material>>(value).ok())
+        .unwrap_or_default()
+}
+
+fn invalid(message: impl Into<String>) -> Error {
+    Error::InvalidContent(anyhow!("{}", message.into()))
+}
+
+pub struct AttestationLoader<'g> {
+    graph: &'g Graph,
+}
+
+impl<'g> AttestationLoader<'g> {
+    pub fn new(graph: &'g Graph) -> Self {
+        Self { graph }
+    }
+
+    #[instrument(skip(self, buffer, tx), err(level=tracing::Level::INFO))]
+    pub async fn load(
+        &self,
+        labels: Labels,
+        buffer: &[u8],
+        digests: &Digests,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<IngestResult, Error> {
+        let envelope: Envelope = serde_json::from_slice(buffer)?;
+        let statement = envelope.statement()?;
+        let information = statement.provenance()?;
+        let document_id = information.subjects.first().map(|(name, ..)| name.clone());
+
+        let labels = labels.add("type", "attestation");
+        let attestation = self
+            .graph
+            .ingest_attestation(labels, digests, information, tx)
+            .await?;
+
+        Ok(IngestResult {
+            id: attestation.id.to_string(),
+            document_id,
+            warnings: vec![],
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+    use test_context::test_context;
+    use test_log::test;
+    use trustify_test_context::TrustifyContext;
+    use uuid::Uuid;
+
+    fn envelope(statement: Value) -> Envelope {
+        Envelope {
+            payload_type: IN_TOTO_PAYLOAD_TYPE.into(),
+            payload: BASE64_STANDARD.encode(statement.to_string()),
+            signatures: vec![Signature {
+                keyid: None,
+                sig: BASE64_STANDARD.encode("signature"),
+            }],
+        }
+    }
+
+    #[test]
+    fn verify_structure() {
+        let statement = json!({
+            "_type": "https://in-toto.io/Statement/v1",
+            "subject": [{ "name": "foo", "digest": { "sha256": "abcd" } }],
+            "predicateType": "https://slsa.dev/provenance/v1",
+            "predicate": {},
+        });
+
+        assert!(envelope(statement.clone()).statement().is_ok());
+
+        let mut unsigned = envelope(statement.clone());
+        unsigned.signatures.clear();
+        assert!(matches!(
+            unsigned.statement(),
+            Err(Error::InvalidContent(_))
+        ));
+
+        let mut other = envelope(statement);
+        other.payload_type = "text/plain".into();
+        assert!(matches!(other.statement(), Err(Error::InvalidContent(_))));
+
+        let no_subject = envelope(json!({
+            "_type": "https://in-toto.io/Statement/v1",
+            "subject": [],
+            "predicateType": "https://slsa.dev/provenance/v1",
+        }));
+        assert!(matches!(
+            no_subject.statement(),
+            Err(Error::InvalidContent(_))
+        ));
+    }
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn link_to_sbom(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let sbom = ctx
+            .ingest_document("zookeeper-3.9.2-cyclonedx.json")
+            .await?;
+        let attestation = ctx
+            .ingest_document("attestation/zookeeper.intoto.json")
+            .await?;
+
+        let graph = Graph::new();
+        let sbom_id = Uuid::parse_str(&sbom.id)?;
+        let attestations = graph.get_attestations_for_sbom(sbom_id, &ctx.db).await?;
+
+        assert_eq!(attestations.len(), 1);
+        assert_eq!(attestations[0].id.to_string(), attestation.id);
+        assert_eq!(
+            attestations[0].builder_id.as_deref(),
+            Some(
+                "https://github.com/slsa-framework/slsa-github-generator/.github/workflows/generator_generic_slsa3.yml@refs/tags/v1.9.0"
+            )
+        );
+        assert_eq!(attestations[0].materials.0.len(), 1);
+
+        Ok(())
+    }
+}
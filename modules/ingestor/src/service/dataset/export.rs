@@ -0,0 +1,112 @@
+//! Exporter for a full dataset (archive) file
+
+use crate::service::{
+    Error, Format,
+    dataset::{DocumentInformation, MANIFEST, Manifest},
+};
+use anyhow::anyhow;
+use futures::TryStreamExt;
+use sea_orm::{ConnectionTrait, EntityTrait};
+use std::io::{Cursor, Write};
+use tracing::instrument;
+use trustify_common::id::Id;
+use trustify_entity::{advisory, labels::Labels, sbom, source_document};
+use trustify_module_storage::service::{StorageBackend, StorageKey, dispatch::DispatchBackend};
+use zip::{ZipWriter, write::FileOptions};
+
+pub struct DatasetExporter<'g> {
+    storage: &'g DispatchBackend,
+}
+
+impl<'g> DatasetExporter<'g> {
+    pub fn new(storage: &'g DispatchBackend) -> Self {
+        Self { storage }
+    }
+
+    /// Export all original documents, along with their labels and provenance, as dataset archive.
+    ///
+    /// The result can be ingested again using the [`super::DatasetLoader`].
+    #[instrument(skip_all, err(level=tracing::Level::INFO))]
+    pub async fn export(&self, tx: &impl ConnectionTrait) -> Result<Vec<u8>, Error> {
+        let sboms = sbom::Entity::find()
+            .find_also_related(source_document::Entity)
+            .all(tx)
+            .await?
+            .into_iter()
+            .map(|(sbom, doc)| (Format::SBOM, sbom.labels, doc));
+        let advisories = advisory::Entity::find()
+            .find_also_related(source_document::Entity)
+            .all(tx)
+            .await?
+            .into_iter()
+            .map(|(advisory, doc)| (Format::Advisory, advisory.labels, doc));
+
+        let mut manifest = Manifest::default();
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+
+        for (format, labels, doc) in sboms.chain(advisories) {
+            let Some(doc) = doc else {
+                continue;
+            };
+
+            let Some(data) = self.retrieve(&doc).await? else {
+                log::warn!("Missing content for source document: {}", doc.sha256);
+                continue;
+            };
+
+            // store using the actual format, so that it doesn't need to be detected on import
+            let format = format.resolve(&data).unwrap_or(format);
+            let name = format!("{format}/{}", doc.sha256);
+
+            zip.start_file(&name, FileOptions::<()>::default())?;
+            zip.write_all(&data)?;
+
+            manifest
+                .documents
+                .insert(name, DocumentInformation::new(labels, doc));
+        }
+
+        zip.start_file(MANIFEST, FileOptions::<()>::default())?;
+        zip.write_all(&serde_json::to_vec_pretty(&manifest)?)?;
+
+        Ok(zip.finish()?.into_inner())
+    }
+
+    async fn retrieve(&self, doc: &source_document::Model) -> Result<Option<Vec<u8>>, Error> {
+        let key = StorageKey::try_from(Id::Sha256(doc.sha256.clone()))
+            .map_err(|err| Error::Storage(anyhow!("{err}")))?;
+
+        let Some(stream) = self
+            .storage
+            .retrieve(key)
+            .await
+            .map_err(|err| Error::Storage(anyhow!("{err}")))?
+        else {
+            return Ok(None);
+        };
+
+        let data = stream
+            .map_err(|err| Error::Storage(anyhow!("{err}")))
+            .try_fold(Vec::new(), |mut data, chunk| async move {
+                data.extend_from_slice(&chunk);
+                Ok(data)
+            })
+            .await?;
+
+        Ok(Some(data))
+    }
+}
+
+impl DocumentInformation {
+    fn new(labels: Labels, doc: source_document::Model) -> Self {
+        Self {
+            labels,
+            ingested: doc.ingested,
+            sha256: doc.sha256,
+            sha384: doc.sha384,
+            sha512: doc.sha512,
+            blake3: doc.blake3,
+            size: doc.size,
+        }
+    }
+}
@@ -1,9 +1,12 @@
 //! Loader for a full dataset (archive) file
 
+mod export;
+pub use export::*;
+
 use crate::{
     graph::Graph,
-    model::IngestResult,
-    service::{Error, Format, Warnings},
+    model::{IngestResult, Warning, WarningCode},
+    service::{Error, Format, ValidationLevel, Warnings, limits::Limits, lock, quota::Quotas},
 };
 use anyhow::anyhow;
 use bytes::Bytes;
@@ -14,16 +17,46 @@ use std::{
     io::{Cursor, Read},
     str::FromStr,
 };
+use time::OffsetDateTime;
 use tokio::runtime::Handle;
 use tracing::instrument;
 use trustify_common::hashing::Digests;
 use trustify_entity::labels::Labels;
-use trustify_module_storage::{service::StorageBackend, service::dispatch::DispatchBackend};
+use trustify_module_storage::service::dispatch::DispatchBackend;
+use zip::result::ZipError;
+
+/// The name of the (optional) manifest in the root of a dataset archive
+pub const MANIFEST: &str = "dataset.json";
+
+/// Additional information about the documents of a dataset
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct Manifest {
+    /// Documents, by their name in the archive
+    #[serde(default)]
+    pub documents: BTreeMap<String, DocumentInformation>,
+}
+
+/// Labels and provenance of a document, when it was exported
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct DocumentInformation {
+    #[serde(default)]
+    pub labels: Labels,
+    #[serde(with = "time::serde::rfc3339")]
+    pub ingested: OffsetDateTime,
+    pub sha256: String,
+    pub sha384: String,
+    pub sha512: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blake3: Option<String>,
+    pub size: i64,
+}
 
 pub struct DatasetLoader<'g> {
     graph: &'g Graph,
     storage: &'g DispatchBackend,
     limit: usize,
+    limits: Limits,
+    quotas: Option<&'g Quotas>,
 }
 
 impl<'g> DatasetLoader<'g> {
@@ -32,9 +65,23 @@ impl<'g> DatasetLoader<'g> {
             graph,
             storage,
             limit,
+            limits: Limits::default(),
+            quotas: None,
         }
     }
 
+    /// Set the limits applied when loading each document.
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Set the storage quotas, rejecting documents which exceed them.
+    pub fn quotas(mut self, quotas: Option<&'g Quotas>) -> Self {
+        self.quotas = quotas;
+        self
+    }
+
     #[instrument(skip(self, buffer, tx), err(level=tracing::Level::INFO))]
     pub async fn load(
         &self,
@@ -47,6 +94,16 @@ impl<'g> DatasetLoader<'g> {
 
         let mut zip = zip::ZipArchive::new(Cursor::new(buffer))?;
 
+        let manifest: Manifest = match zip.by_name(MANIFEST) {
+            Ok(mut file) => {
+                let mut data = Vec::with_capacity(file.size() as _);
+                file.read_to_end(&mut data)?;
+                serde_json::from_slice(&data)?
+            }
+            Err(ZipError::FileNotFound) => Default::default(),
+            Err(err) => return Err(err.into()),
+        };
+
         for i in 0..zip.len() {
             let mut file = zip.by_index(i)?;
 
@@ -55,7 +112,10 @@ impl<'g> DatasetLoader<'g> {
             if !file.is_file() {
                 continue;
             }
-            if file.name() == ".DS_Store" || file.name().ends_with("/.DS_Store") {
+            if file.name() == MANIFEST
+                || file.name() == ".DS_Store"
+                || file.name().ends_with("/.DS_Store")
+            {
                 continue;
             }
 
@@ -76,7 +136,13 @@ impl<'g> DatasetLoader<'g> {
                 );
                 match Format::from_str(loader) {
                     Err(_err) => {
-                        warnings.add(format!("Unknown dataset file type: {loader}"));
+                        warnings.add(
+                            Warning::new(
+                                WarningCode::UnsupportedEntry,
+                                format!("Unknown dataset file type: {loader}"),
+                            )
+                            .at(&full_name),
+                        );
                     }
                     Ok(format) => {
                         let mut data = Vec::with_capacity(file.size() as _);
@@ -103,12 +169,58 @@ impl<'g> DatasetLoader<'g> {
                             })
                             .await??;
 
-                        let labels = labels.clone().add("datasetFile", &full_name);
+                        // vague formats are fine, as long as they can be resolved
+                        let format = match format.resolve(&data) {
+                            Ok(format) => format,
+                            Err(err) => {
+                                warnings.add(
+                                    Warning::new(
+                                        WarningCode::InvalidEntry,
+                                        format!("Error loading dataset file: {err}"),
+                                    )
+                                    .at(&full_name),
+                                );
+                                continue;
+                            }
+                        };
 
-                        self.storage
-                            .store(&*data)
-                            .await
-                            .map_err(|err| Error::Storage(anyhow!("{err}")))?;
+                        // restore the labels of exported documents, labels of the upload take precedence
+                        let labels = match manifest.documents.get(&full_name) {
+                            Some(info) => {
+                                let sha256 = hex::encode(Digests::digest(&data).sha256);
+                                if sha256 != info.sha256 {
+                                    warnings.add(
+                                        Warning::new(
+                                            WarningCode::DigestMismatch,
+                                            format!(
+                                                "Digest mismatch of dataset file: expected {}, found {sha256}",
+                                                info.sha256
+                                            ),
+                                        )
+                                        .at(&full_name),
+                                    );
+                                    continue;
+                                }
+                                info.labels.clone().extend(labels.0.clone())
+                            }
+                            None => labels.clone(),
+                        }
+                        .add("datasetFile", &full_name);
+
+                        if let Some(quotas) = self.quotas
+                            && let Err(err) = quotas.check(&labels, data.len() as u64, tx).await
+                        {
+                            warnings.add(
+                                Warning::new(
+                                    WarningCode::InvalidEntry,
+                                    format!("Error loading dataset file: {err}"),
+                                )
+                                .at(&full_name),
+                            );
+                            continue;
+                        }
+
+                        let digests = lock::store_for_reference(self.storage, &data, tx).await?;
 
                         // We need to box it, to work around async recursion limits
                         let result = Box::pin({
@@ -118,8 +230,10 @@ impl<'g> DatasetLoader<'g> {
                                         self.graph,
                                         labels,
                                         None,
-                                        &Digests::digest(&data),
+                                        &digests,
                                         &data,
+                                        ValidationLevel::default(),
+                                        &self.limits,
                                         tx,
                                     )
                                     .await
@@ -132,18 +246,25 @@ impl<'g> DatasetLoader<'g> {
                                 results.insert(full_name, result);
                             }
                             Err(err) => {
-                                warnings.add(format!(
-                                    "Error loading dataset file ({full_name}): {err}"
-                                ));
+                                warnings.add(
+                                    Warning::new(
+                                        WarningCode::InvalidEntry,
+                                        format!("Error loading dataset file: {err}"),
+                                    )
+                                    .at(&full_name),
+                                );
                             }
                         }
                     }
                 }
             } else {
-                warnings.add(format!(
-                    "Unknown dataset file name structure: {}",
-                    name.display()
-                ));
+                warnings.add(
+                    Warning::new(
+                        WarningCode::UnsupportedEntry,
+                        "Unknown dataset file name structure",
+                    )
+                    .at(name.display().to_string()),
+                );
             }
         }
 
@@ -156,6 +277,6 @@ impl<'g> DatasetLoader<'g> {
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 pub struct DatasetIngestResult {
-    pub warnings: Vec<String>,
+    pub warnings: Vec<Warning>,
     pub files: BTreeMap<String, IngestResult>,
 }
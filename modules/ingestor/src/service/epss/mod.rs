@@ -0,0 +1,260 @@
+//! Support for EPSS (Exploit Prediction Scoring System) scores
+//!
+//! Scores can be loaded from the daily CSV dump, or from a response of the FIRST EPSS API.
+//!
+//! See: <https://www.first.org/epss/data_stats>
+
+use crate::{model::IngestResult, service::Error};
+use anyhow::anyhow;
+use hex::ToHex;
+use sea_orm::{ConnectionTrait, EntityTrait, Iterable, Set, TransactionTrait};
+use sea_query::OnConflict;
+use serde::{Deserialize, Deserializer, de};
+use serde_json::Value;
+use std::io::{BufRead, Read};
+use time::{Date, OffsetDateTime, format_description::well_known::Iso8601};
+use tracing::instrument;
+use trustify_common::{db::chunk::EntityChunkedIter, hashing::Digests};
+use trustify_entity::{labels::Labels, vulnerability_epss};
+
+/// A single EPSS score
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct EpssEntry {
+    pub cve: String,
+    #[serde(deserialize_with = "number")]
+    pub epss: f64,
+    #[serde(deserialize_with = "number")]
+    pub percentile: f64,
+    #[serde(default)]
+    pub date: Option<String>,
+}
+
+/// A response of the FIRST EPSS API
+#[derive(Clone, Debug, Deserialize)]
+pub struct EpssResponse {
+    pub data: Vec<EpssEntry>,
+}
+
+/// A set of EPSS scores
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Epss {
+    pub model_version: Option<String>,
+    /// The date of the scores, if it is provided for the whole set.
+    pub score_date: Option<OffsetDateTime>,
+    pub entries: Vec<EpssEntry>,
+}
+
+impl Epss {
+    pub fn from_bytes(buffer: &[u8]) -> Result<Self, Error> {
+        if buffer.trim_ascii_start().starts_with(b"{") {
+            Self::from_json(buffer)
+        } else {
+            Self::from_csv(buffer)
+        }
+    }
+
+    pub fn from_json(buffer: &[u8]) -> Result<Self, Error> {
+        let response: EpssResponse = serde_json::from_slice(buffer)?;
+
+        Ok(Self {
+            model_version: None,
+            score_date: None,
+            entries: response.data,
+        })
+    }
+
+    /// Parse the CSV format, which starts with a comment line carrying the model version and
+    /// score date, followed by the header.
+    pub fn from_csv(mut buffer: &[u8]) -> Result<Self, Error> {
+        let mut result = Self::default();
+
+        if buffer.starts_with(b"#") {
+            let mut comment = String::new();
+            buffer.read_line(&mut comment)?;
+
+            for (key, value) in comment
+                .trim_start_matches('#')
+                .trim()
+                .split(',')
+                .filter_map(|item| item.split_once(':'))
+            {
+                match key {
+                    "model_version" => result.model_version = Some(value.to_string()),
+                    "score_date" => {
+                        result.score_date = OffsetDateTime::parse(value, &Iso8601::DEFAULT).ok()
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        result.entries = csv::Reader::from_reader(buffer)
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .map_err(|err| Error::InvalidContent(anyhow!("failed to parse EPSS CSV: {err}")))?;
+
+        Ok(result)
+    }
+
+    /// Check if the buffer is an EPSS CSV file.
+    ///
+    /// API responses are not detected, as they lack any distinct marker.
+    pub fn is_epss(buffer: &[u8]) -> bool {
+        let mut header = String::new();
+        if Read::take(buffer, 1024).read_line(&mut header).is_err() {
+            return false;
+        }
+
+        header.starts_with("#model_version:") || header.trim() == "cve,epss,percentile"
+    }
+}
+
+#[derive(Default)]
+pub struct EpssLoader {}
+
+impl EpssLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[instrument(skip(self, buffer, tx), err(level=tracing::Level::INFO))]
+    pub async fn load_bytes(
+        &self,
+        labels: Labels,
+        buffer: &[u8],
+        digests: &Digests,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<IngestResult, Error> {
+        let epss = Epss::from_bytes(buffer)?;
+        self.load(labels, epss, digests, tx).await
+    }
+
+    #[instrument(skip(self, epss, tx), err(level=tracing::Level::INFO))]
+    pub async fn load(
+        &self,
+        _labels: Labels,
+        epss: Epss,
+        digests: &Digests,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<IngestResult, Error> {
+        let Epss {
+            model_version,
+            score_date,
+            entries,
+        } = epss;
+
+        let batch = entries.into_iter().map(|entry| {
+            let date = entry
+                .date
+                .as_deref()
+                .and_then(|date| Date::parse(date, &Iso8601::DEFAULT).ok())
+                .map(|date| date.midnight().assume_utc())
+                .or(score_date);
+
+            vulnerability_epss::ActiveModel {
+                vulnerability_id: Set(entry.cve),
+                probability: Set(entry.epss),
+                percentile: Set(entry.percentile),
+                date: Set(date),
+                model_version: Set(model_version.clone()),
+            }
+        });
+
+        for chunk in &batch.chunked() {
+            vulnerability_epss::Entity::insert_many(chunk)
+                .on_conflict(
+                    OnConflict::column(vulnerability_epss::Column::VulnerabilityId)
+                        .update_columns(vulnerability_epss::Column::iter())
+                        .to_owned(),
+                )
+                .exec(tx)
+                .await?;
+        }
+
+        Ok(IngestResult {
+            // Like the CWE catalog, this doesn't result in a source document. So we return the
+            // digest.
+            id: digests.sha512.encode_hex(),
+            document_id: Some("EPSS".to_string()),
+            warnings: vec![],
+        })
+    }
+}
+
+/// Deserialize a number, which may also be encoded as string (like the EPSS API does).
+fn number<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Value::deserialize(deserializer)? {
+        Value::Number(n) => n
+            .as_f64()
+            .ok_or_else(|| de::Error::custom("number out of range")),
+        Value::String(s) => s.parse().map_err(de::Error::custom),
+        other => Err(de::Error::custom(format!(
+            "expected number, found: {other}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use test_context::test_context;
+    use test_log::test;
+    use time::macros::datetime;
+    use trustify_test_context::{TrustifyContext, document_bytes};
+
+    #[test]
+    fn parse_csv() -> Result<(), anyhow::Error> {
+        let epss = Epss::from_bytes(
+            b"#model_version:v2025.03.14,score_date:2025-10-15T12:55:00Z\ncve,epss,percentile\nCVE-2021-44228,0.94358,0.99957\n",
+        )?;
+
+        assert_eq!(epss.model_version.as_deref(), Some("v2025.03.14"));
+        assert_eq!(epss.score_date, Some(datetime!(2025-10-15 12:55:00 UTC)));
+        assert_eq!(epss.entries.len(), 1);
+        assert_eq!(epss.entries[0].cve, "CVE-2021-44228");
+        assert_eq!(epss.entries[0].epss, 0.94358);
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_json() -> Result<(), anyhow::Error> {
+        let epss = Epss::from_bytes(
+            br#"{"status":"OK","data":[{"cve":"CVE-2021-44228","epss":"0.943580000","percentile":"0.999570000","date":"2025-10-15"}]}"#,
+        )?;
+
+        assert_eq!(epss.entries.len(), 1);
+        assert_eq!(epss.entries[0].percentile, 0.99957);
+        assert_eq!(epss.entries[0].date.as_deref(), Some("2025-10-15"));
+
+        Ok(())
+    }
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn load_epss(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let data = document_bytes("epss/epss_scores.csv").await?;
+        let digests = Digests::digest(&data);
+
+        ctx.db
+            .transaction(async |tx| {
+                EpssLoader::new()
+                    .load_bytes(Labels::default(), &data, &digests, tx)
+                    .await
+            })
+            .await?;
+
+        let score = vulnerability_epss::Entity::find_by_id("CVE-2021-44228")
+            .one(&ctx.db)
+            .await?
+            .expect("must be loaded");
+        assert_eq!(score.probability, 0.94358);
+        assert_eq!(score.model_version.as_deref(), Some("v2025.03.14"));
+        assert_eq!(score.date, Some(datetime!(2025-10-15 12:55:00 UTC)));
+
+        Ok(())
+    }
+}
@@ -2,26 +2,43 @@ use crate::{
     graph::{Graph, sbom::clearly_defined::Curation},
     model::IngestResult,
     service::{
-        Error,
-        advisory::{csaf::loader::CsafLoader, cve::loader::CveLoader, osv::loader::OsvLoader},
+        Error, ValidationLevel,
+        advisory::{
+            csaf::loader::CsafLoader, cve::loader::CveLoader, nvd::loader::NvdLoader,
+            osv::loader::OsvLoader,
+        },
+        attack_pattern::CapecCatalogLoader,
+        attestation::AttestationLoader,
+        epss::{Epss, EpssLoader},
+        kev::KevLoader,
+        limits::Limits,
         sbom::{
             clearly_defined::ClearlyDefinedLoader,
-            clearly_defined_curation::ClearlyDefinedCurationLoader, cyclonedx::CyclonedxLoader,
+            clearly_defined_curation::ClearlyDefinedCurationLoader,
+            cyclonedx::CyclonedxLoader,
+            lockfile::{self, LockfileLoader},
             spdx::SpdxLoader,
+            spdx3::Spdx3Loader,
+            syft::SyftLoader,
+            trivy::TrivyLoader,
         },
+        stream::parse_json,
         weakness::CweCatalogLoader,
     },
 };
+use anyhow::anyhow;
+use bytes::Bytes;
 use csaf::Csaf;
 use cve::Cve;
+use futures::{Stream, TryStreamExt};
 use jsn::{Format as JsnFormat, TokenReader, mask::*};
 use quick_xml::{Reader, events::Event};
 use sea_orm::{ConnectionTrait, TransactionTrait};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
-use std::{io::Cursor, str::FromStr};
+use std::{fmt::Display, io::Cursor, str::FromStr};
 use tracing::instrument;
-use trustify_common::hashing::Digests;
+use trustify_common::{hashing::Digests, sbom::spdx::LenientSpdx};
 use trustify_entity::labels::Labels;
 
 #[derive(
@@ -42,11 +59,22 @@ pub enum Format {
     OSV,
     CSAF,
     CVE,
+    NvdCve,
     SPDX,
+    SPDX3,
     CycloneDX,
+    Syft,
+    Trivy,
+    Lockfile,
     ClearlyDefinedCuration,
     ClearlyDefined,
     CweCatalog,
+    CapecCatalog,
+    Epss,
+    Kev,
+    Attestation,
+    /// A format provided by a registered [`super::loader::DocumentLoader`]
+    Custom,
     // These should be resolved to one of the above before loading
     Advisory,
     SBOM,
@@ -54,7 +82,15 @@ pub enum Format {
 }
 
 impl Format {
+    /// Load a document.
+    ///
+    /// The validation level is applied by the loaders of formats which check the document
+    /// against its specification (CSAF, SPDX, and CycloneDX).
+    ///
+    /// The size and nesting depth of the document are checked against the limits before parsing
+    /// it, and loading fails if it exceeds the maximum duration.
     #[instrument(skip_all)]
+    #[allow(clippy::too_many_arguments)]
     pub async fn load(
         &self,
         graph: &'_ Graph,
@@ -62,12 +98,31 @@ impl Format {
         issuer: Option<String>,
         digests: &Digests,
         buffer: &[u8],
+        level: ValidationLevel,
+        limits: &Limits,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<IngestResult, Error> {
+        limits.check(buffer)?;
+        limits
+            .timeout(self.load_unchecked(graph, labels, issuer, digests, buffer, level, tx))
+            .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn load_unchecked(
+        &self,
+        graph: &'_ Graph,
+        labels: Labels,
+        issuer: Option<String>,
+        digests: &Digests,
+        buffer: &[u8],
+        level: ValidationLevel,
         tx: &(impl ConnectionTrait + TransactionTrait),
     ) -> Result<IngestResult, Error> {
         match self {
             Format::CSAF => {
                 // issuer is internal as publisher of the document.
-                let loader = CsafLoader::new(graph);
+                let loader = CsafLoader::new(graph).level(level);
                 let csaf: Csaf = serde_json::from_slice(buffer)?;
                 loader.load(labels, csaf, digests, tx).await
             }
@@ -83,13 +138,35 @@ impl Format {
                 let cve: Cve = serde_json::from_slice(buffer)?;
                 loader.load(labels, cve, digests, tx).await
             }
+            Format::NvdCve => {
+                // issuer is always NVD
+                let loader = NvdLoader::new(graph);
+                let nvd = serde_json::from_slice(buffer)?;
+                loader.load(labels, nvd, digests, tx).await
+            }
             Format::SPDX => {
-                let loader = SpdxLoader::new(graph);
+                let loader = SpdxLoader::new(graph).level(level);
                 let v: Value = serde_json::from_slice(buffer)?;
                 loader.load(labels, v, digests, tx).await
             }
+            Format::SPDX3 => {
+                let loader = Spdx3Loader::new(graph);
+                loader.load(labels, buffer, digests, tx).await
+            }
             Format::CycloneDX => {
-                let loader = CyclonedxLoader::new(graph);
+                let loader = CyclonedxLoader::new(graph).level(level);
+                loader.load(labels, buffer, digests, tx).await
+            }
+            Format::Syft => {
+                let loader = SyftLoader::new(graph);
+                loader.load(labels, buffer, digests, tx).await
+            }
+            Format::Trivy => {
+                let loader = TrivyLoader::new(graph);
+                loader.load(labels, buffer, digests, tx).await
+            }
+            Format::Lockfile => {
+                let loader = LockfileLoader::new(graph);
                 loader.load(labels, buffer, digests, tx).await
             }
             Format::ClearlyDefined => {
@@ -106,18 +183,100 @@ impl Format {
                 let loader = CweCatalogLoader::new();
                 loader.load_bytes(labels, buffer, digests, tx).await
             }
+            Format::CapecCatalog => {
+                let loader = CapecCatalogLoader::new();
+                loader.load_bytes(labels, buffer, digests, tx).await
+            }
+            Format::Epss => {
+                let loader = EpssLoader::new();
+                loader.load_bytes(labels, buffer, digests, tx).await
+            }
+            Format::Kev => {
+                let loader = KevLoader::new();
+                loader.load_bytes(labels, buffer, digests, tx).await
+            }
+            Format::Attestation => {
+                let loader = AttestationLoader::new(graph);
+                loader.load(labels, buffer, digests, tx).await
+            }
             f => Err(Error::UnsupportedFormat(format!(
                 "Must resolve {f:?} to an actual format"
             ))),
         }
     }
 
+    /// Load a document from a stream of chunks.
+    ///
+    /// SPDX and CycloneDX documents are parsed incrementally while reading the stream, so that the
+    /// raw content never needs to be held in memory as a whole. All other formats collect the
+    /// content and use [`Self::load`].
+    ///
+    /// Streamed documents are limited in the time it takes to load them, and in their nesting
+    /// depth, which is checked while parsing them. Their size is not known upfront.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn load_stream<S, E>(
+        &self,
+        graph: &'_ Graph,
+        labels: Labels,
+        issuer: Option<String>,
+        digests: &Digests,
+        stream: S,
+        level: ValidationLevel,
+        limits: &Limits,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<IngestResult, Error>
+    where
+        S: Stream<Item = Result<Bytes, E>>,
+        E: Display,
+    {
+        match self {
+            Format::SPDX => {
+                limits
+                    .timeout(async {
+                        let loader = SpdxLoader::new(graph).level(level);
+                        let spdx: LenientSpdx = parse_json(stream, limits).await?;
+                        loader.load_parsed(labels, spdx, digests, tx).await
+                    })
+                    .await
+            }
+            Format::CycloneDX => {
+                limits
+                    .timeout(async {
+                        let loader = CyclonedxLoader::new(graph).level(level);
+                        let cdx = parse_json(stream, limits).await.map_err(|err| match err {
+                            Error::Json(err) => {
+                                Error::UnsupportedFormat(format!("Failed to parse: {err}"))
+                            }
+                            err => err,
+                        })?;
+                        loader.load_parsed(labels, cdx, digests, tx).await
+                    })
+                    .await
+            }
+            _ => {
+                let buffer = stream
+                    .map_err(|err| Error::Storage(anyhow!("{err}")))
+                    .try_fold(Vec::new(), |mut buffer, chunk| async move {
+                        buffer.extend_from_slice(&chunk);
+                        Ok(buffer)
+                    })
+                    .await?;
+                self.load(graph, labels, issuer, digests, &buffer, level, limits, tx)
+                    .await
+            }
+        }
+    }
+
     #[instrument(skip_all, ret(level=tracing::Level::DEBUG))]
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
         match Self::advisory_from_bytes(bytes) {
             Err(Error::UnsupportedFormat(ea)) => match Self::sbom_from_bytes(bytes) {
                 Err(Error::UnsupportedFormat(es)) => match Self::is_cwe_catalog(bytes) {
                     Ok(true) => Ok(Self::CweCatalog),
+                    _ if Self::is_capec_catalog(bytes)? => Ok(Self::CapecCatalog),
+                    _ if Epss::is_epss(bytes) => Ok(Self::Epss),
+                    _ if Self::is_kev(bytes)? => Ok(Self::Kev),
+                    _ if Self::is_attestation(bytes)? => Ok(Self::Attestation),
                     _ => Err(Error::UnsupportedFormat(format!("{ea}\n{es}"))),
                 },
                 x => x,
@@ -132,11 +291,14 @@ impl Format {
             Ok(Format::CSAF)
         } else if Self::is_cve(bytes)? {
             Ok(Format::CVE)
+        } else if Self::is_nvd_cve(bytes)? {
+            Ok(Format::NvdCve)
         } else if Self::is_osv(bytes)? {
             Ok(Format::OSV)
         } else {
             Err(Error::UnsupportedFormat(
-                "Unable to detect advisory format; only CSAF, CVE, and OSV are supported".into(),
+                "Unable to detect advisory format; only CSAF, CVE, NVD CVE, and OSV are supported"
+                    .into(),
             ))
         }
     }
@@ -145,13 +307,22 @@ impl Format {
     pub fn sbom_from_bytes(bytes: &[u8]) -> Result<Self, Error> {
         if Self::is_spdx(bytes)? {
             Ok(Format::SPDX)
+        } else if Self::is_spdx3(bytes)? {
+            Ok(Format::SPDX3)
         } else if Self::is_cyclonedx(bytes)? {
             Ok(Format::CycloneDX)
+        } else if Self::is_syft(bytes)? {
+            Ok(Format::Syft)
+        } else if Self::is_trivy(bytes)? {
+            Ok(Format::Trivy)
         } else if Self::is_clearly_defined(bytes)? {
             Ok(Format::ClearlyDefinedCuration)
+        } else if Self::is_lockfile(bytes)? {
+            Ok(Format::Lockfile)
         } else {
             Err(Error::UnsupportedFormat(
-                "Unable to detect SBOM format; only SPDX and CycloneDX are supported".into(),
+                "Unable to detect SBOM format; only SPDX, SPDX 3, CycloneDX, Syft, Trivy, and lockfiles are supported"
+                    .into(),
             ))
         }
     }
@@ -173,6 +344,13 @@ impl Format {
         }
     }
 
+    pub fn is_nvd_cve(bytes: &[u8]) -> Result<bool, Error> {
+        match masked(depth(1).and(key("format")), bytes) {
+            Ok(Some(x)) => Ok(x == super::advisory::nvd::FORMAT),
+            Err(_) | Ok(None) => Ok(false),
+        }
+    }
+
     pub fn is_osv(bytes: &[u8]) -> Result<bool, Error> {
         Ok(Self::is_osv_json(bytes)? || Self::is_osv_yaml(bytes)?)
     }
@@ -199,6 +377,14 @@ impl Format {
         }
     }
 
+    /// SPDX 3 documents (JSON-LD) reference the SPDX 3 context.
+    pub fn is_spdx3(bytes: &[u8]) -> Result<bool, Error> {
+        match masked(depth(1).and(key("@context")), bytes) {
+            Ok(Some(x)) => Ok(x.contains("spdx.org/rdf/3.")),
+            Err(_) | Ok(None) => Ok(false),
+        }
+    }
+
     pub fn is_cyclonedx(bytes: &[u8]) -> Result<bool, Error> {
         match masked(depth(1).and(key("specVersion")), bytes) {
             Ok(Some(x)) if matches!(x.as_str(), "1.3" | "1.4" | "1.5" | "1.6") => Ok(true),
@@ -209,6 +395,27 @@ impl Format {
         }
     }
 
+    pub fn is_syft(bytes: &[u8]) -> Result<bool, Error> {
+        match masked(key("descriptor").and(key("name")).and(depth(2)), bytes) {
+            Ok(Some(x)) => Ok(x == "syft"),
+            Err(_) | Ok(None) => Ok(false),
+        }
+    }
+
+    pub fn is_trivy(bytes: &[u8]) -> Result<bool, Error> {
+        match masked(depth(1).and(key("SchemaVersion")), bytes) {
+            Ok(Some(_)) => Ok(matches!(
+                masked(depth(1).and(key("ArtifactName")), bytes),
+                Ok(Some(_))
+            )),
+            Err(_) | Ok(None) => Ok(false),
+        }
+    }
+
+    pub fn is_lockfile(bytes: &[u8]) -> Result<bool, Error> {
+        Ok(lockfile::Kind::detect(bytes).is_some())
+    }
+
     pub fn is_clearly_defined(bytes: &[u8]) -> Result<bool, Error> {
         // first just try to get some YAML.
         if let Ok(candidate) = serde_yml::from_slice::<'_, serde_yml::Value>(bytes) {
@@ -221,6 +428,20 @@ impl Format {
         Ok(false)
     }
 
+    pub fn is_kev(bytes: &[u8]) -> Result<bool, Error> {
+        match masked(depth(1).and(key("catalogVersion")), bytes) {
+            Ok(Some(_)) => Ok(true),
+            Err(_) | Ok(None) => Ok(false),
+        }
+    }
+
+    pub fn is_attestation(bytes: &[u8]) -> Result<bool, Error> {
+        match masked(depth(1).and(key("payloadType")), bytes) {
+            Ok(Some(x)) => Ok(x == super::attestation::IN_TOTO_PAYLOAD_TYPE),
+            Err(_) | Ok(None) => Ok(false),
+        }
+    }
+
     pub fn is_cwe_catalog(bytes: &[u8]) -> Result<bool, Error> {
         let xml = Cursor::new(bytes);
         let mut reader = Reader::from_reader(xml);
@@ -259,6 +480,22 @@ impl Format {
         }
     }
 
+    pub fn is_capec_catalog(bytes: &[u8]) -> Result<bool, Error> {
+        let mut reader = Reader::from_reader(Cursor::new(bytes));
+
+        let mut buf = Vec::new();
+        loop {
+            // the first tag must be the catalog
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(event)) => {
+                    return Ok(event.local_name().into_inner() == b"Attack_Pattern_Catalog");
+                }
+                Err(_) | Ok(Event::Eof) => return Ok(false),
+                _ => buf.clear(),
+            }
+        }
+    }
+
     /// Resolve one of the "vague" formats (like "SBOM") by inspecting the payload.
     ///
     /// If the format is one of the vague formats, it will try to detect the format
@@ -335,6 +572,9 @@ mod test {
         let cve = document_bytes("mitre/CVE-2024-27088.json").await?;
         assert!(matches!(Format::from_bytes(&cve), Ok(Format::CVE)));
 
+        let nvd = document_bytes("nvd/CVE-2021-44228.json").await?;
+        assert!(matches!(Format::from_bytes(&nvd), Ok(Format::NvdCve)));
+
         let cyclone = document_bytes("zookeeper-3.9.2-cyclonedx.json").await?;
         assert!(matches!(
             Format::from_bytes(&cyclone),
@@ -350,6 +590,21 @@ mod test {
         let spdx = document_bytes("ubi9-9.2-755.1697625012.json").await?;
         assert!(matches!(Format::from_bytes(&spdx), Ok(Format::SPDX)));
 
+        let spdx3 = document_bytes("spdx3/alpine.spdx3.json").await?;
+        assert!(matches!(Format::from_bytes(&spdx3), Ok(Format::SPDX3)));
+
+        let syft = document_bytes("syft/alpine.syft.json").await?;
+        assert!(matches!(Format::from_bytes(&syft), Ok(Format::Syft)));
+
+        let trivy = document_bytes("trivy/alpine-3.19.json").await?;
+        assert!(matches!(Format::from_bytes(&trivy), Ok(Format::Trivy)));
+
+        let lockfile = document_bytes("lockfile/go.sum").await?;
+        assert!(matches!(
+            Format::from_bytes(&lockfile),
+            Ok(Format::Lockfile)
+        ));
+
         let indigestable = document_bytes("indigestable.json").await?;
         assert!(Format::from_bytes(&indigestable).is_err());
 
@@ -360,6 +615,24 @@ mod test {
         cwe.read_to_end(&mut xml)?;
         assert!(matches!(Format::from_bytes(&xml), Ok(Format::CweCatalog)));
 
+        let capec = document_bytes("capec/capec_excerpt.xml").await?;
+        assert!(matches!(
+            Format::from_bytes(&capec),
+            Ok(Format::CapecCatalog)
+        ));
+
+        let epss = document_bytes("epss/epss_scores.csv").await?;
+        assert!(matches!(Format::from_bytes(&epss), Ok(Format::Epss)));
+
+        let kev = document_bytes("kev/known_exploited_vulnerabilities.json").await?;
+        assert!(matches!(Format::from_bytes(&kev), Ok(Format::Kev)));
+
+        let attestation = document_bytes("attestation/zookeeper.intoto.json").await?;
+        assert!(matches!(
+            Format::from_bytes(&attestation),
+            Ok(Format::Attestation)
+        ));
+
         Ok(())
     }
 
@@ -0,0 +1,278 @@
+//! Loader for SBOMs and attestations attached to OCI images
+//!
+//! Attached artifacts are discovered using the OCI referrers API, as well as the tag scheme used
+//! by cosign (`sha256-<digest>.sbom` and `sha256-<digest>.att`).
+
+use crate::{
+    graph::Graph,
+    model::IngestResult,
+    service::{Error, Format, ValidationLevel, limits::Limits, lock, quota::Quotas},
+};
+use anyhow::anyhow;
+use oci_client::{
+    Client, Reference, client::ClientConfig, manifest::OciDescriptor, secrets::RegistryAuth,
+};
+use sea_orm::{ConnectionTrait, TransactionTrait};
+use std::collections::BTreeMap;
+use tracing::instrument;
+use trustify_entity::labels::Labels;
+use trustify_module_storage::service::dispatch::DispatchBackend;
+
+/// The label carrying the (digest) reference of the image an artifact is attached to.
+pub const LABEL_IMAGE: &str = "image";
+
+/// The tag suffixes cosign uses for attaching SBOMs and attestations.
+const COSIGN_SUFFIXES: [&str; 2] = ["sbom", "att"];
+
+/// The result of ingesting the artifacts attached to an image
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+pub struct ImageIngestResult {
+    /// The image, referenced by its digest
+    pub image: String,
+    /// Successfully ingested artifacts, by their digest
+    pub artifacts: BTreeMap<String, IngestResult>,
+    /// Artifacts which failed to ingest, by their digest
+    pub failures: BTreeMap<String, String>,
+}
+
+pub struct ImageLoader<'g> {
+    graph: &'g Graph,
+    storage: &'g DispatchBackend,
+    client: Client,
+    auth: RegistryAuth,
+    limit: usize,
+    limits: Limits,
+    quotas: Option<&'g Quotas>,
+}
+
+impl<'g> ImageLoader<'g> {
+    pub fn new(graph: &'g Graph, storage: &'g DispatchBackend, limit: usize) -> Self {
+        Self {
+            graph,
+            storage,
+            client: Client::new(ClientConfig::default()),
+            auth: RegistryAuth::Anonymous,
+            limit,
+            limits: Limits::default(),
+            quotas: None,
+        }
+    }
+
+    /// Set the limits applied when loading each document.
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Set the storage quotas, rejecting documents which exceed them.
+    pub fn quotas(mut self, quotas: Option<&'g Quotas>) -> Self {
+        self.quotas = quotas;
+        self
+    }
+
+    /// Ingest all SBOMs and attestations attached to an image.
+    ///
+    /// The format of each artifact is detected individually. Artifacts which fail to ingest are
+    /// reported, but don't fail the image as a whole.
+    #[instrument(skip(self, tx), err(level=tracing::Level::INFO))]
+    pub async fn load(
+        &self,
+        labels: Labels,
+        reference: &str,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<ImageIngestResult, Error> {
+        let reference: Reference = reference
+            .parse()
+            .map_err(|err| Error::InvalidContent(anyhow!("invalid image reference: {err}")))?;
+
+        let digest = self
+            .client
+            .fetch_manifest_digest(&reference, &self.auth)
+            .await?;
+        let image = Reference::with_digest(
+            reference.registry().to_string(),
+            reference.repository().to_string(),
+            digest.clone(),
+        );
+
+        let mut result = ImageIngestResult {
+            image: image.whole(),
+            ..Default::default()
+        };
+
+        let labels = labels
+            .add("source", reference.whole())
+            .add(LABEL_IMAGE, image.whole());
+
+        for artifact in self.artifacts(&image, &digest).await {
+            let (manifest, _) = match self.client.pull_image_manifest(&artifact, &self.auth).await {
+                Ok(manifest) => manifest,
+                Err(err) => {
+                    result.failures.insert(artifact.whole(), err.to_string());
+                    continue;
+                }
+            };
+
+            for layer in manifest.layers {
+                let name = layer.digest.clone();
+                match self.load_layer(&artifact, &layer, labels.clone(), tx).await {
+                    Ok(loaded) => {
+                        result.artifacts.insert(name, loaded);
+                    }
+                    Err(err) => {
+                        result.failures.insert(name, err.to_string());
+                    }
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Collect the references of all artifacts attached to the image.
+    ///
+    /// Registries may support neither the referrers API nor carry cosign attachments, so a
+    /// failure to look up either one is not an error.
+    async fn artifacts(&self, image: &Reference, digest: &str) -> Vec<Reference> {
+        let mut artifacts = vec![];
+
+        match self.client.pull_referrers(image, None).await {
+            Ok(index) => {
+                artifacts.extend(index.manifests.into_iter().map(|entry| {
+                    Reference::with_digest(
+                        image.registry().to_string(),
+                        image.repository().to_string(),
+                        entry.digest,
+                    )
+                }));
+            }
+            Err(err) => {
+                log::debug!("Unable to list referrers of {image}: {err}");
+            }
+        }
+
+        for suffix in COSIGN_SUFFIXES {
+            let attachment = Reference::with_tag(
+                image.registry().to_string(),
+                image.repository().to_string(),
+                cosign_tag(digest, suffix),
+            );
+            match self
+                .client
+                .fetch_manifest_digest(&attachment, &self.auth)
+                .await
+            {
+                Ok(_) => artifacts.push(attachment),
+                Err(err) => {
+                    log::debug!("No cosign attachment {attachment}: {err}");
+                }
+            }
+        }
+
+        artifacts
+    }
+
+    async fn load_layer(
+        &self,
+        artifact: &Reference,
+        layer: &OciDescriptor,
+        labels: Labels,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<IngestResult, Error> {
+        if layer.size < 0 || layer.size as usize > self.limit {
+            return Err(Error::PayloadTooLarge);
+        }
+
+        let mut data = Vec::with_capacity(layer.size as usize);
+        self.client.pull_blob(artifact, layer, &mut data).await?;
+
+        log::debug!("Processing artifact layer: {}", layer.digest);
+
+        self.load_artifact(data, labels, tx).await
+    }
+
+    /// Ingest the content of an artifact, detecting its format.
+    async fn load_artifact(
+        &self,
+        data: Vec<u8>,
+        labels: Labels,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<IngestResult, Error> {
+        let format = Format::from_bytes(&data)?;
+
+        if let Some(quotas) = self.quotas {
+            quotas.check(&labels, data.len() as u64, tx).await?;
+        }
+
+        let digests = lock::store_for_reference(self.storage, &data, tx).await?;
+
+        // We need to box it, to work around async recursion limits
+        Box::pin(async move {
+            format
+                .load(
+                    self.graph,
+                    labels,
+                    None,
+                    &digests,
+                    &data,
+                    ValidationLevel::default(),
+                    &self.limits,
+                    tx,
+                )
+                .await
+        })
+        .await
+    }
+}
+
+/// The tag cosign uses for an attachment of the image with the provided digest.
+fn cosign_tag(digest: &str, suffix: &str) -> String {
+    format!("{}.{suffix}", digest.replacen(':', "-", 1))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sea_orm::TransactionTrait;
+    use test_context::test_context;
+    use test_log::test;
+    use trustify_test_context::{TrustifyContext, document_bytes};
+
+    #[test]
+    fn cosign_tags() {
+        assert_eq!(
+            cosign_tag("sha256:0123456789abcdef", "sbom"),
+            "sha256-0123456789abcdef.sbom"
+        );
+        assert_eq!(cosign_tag("sha512:ab", "att"), "sha512-ab.att");
+    }
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn reject_exceeding_quota(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let ubi = document_bytes("ubi9-9.2-755.1697625012.json").await?;
+        let zookeeper = document_bytes("zookeeper-3.9.2-cyclonedx.json").await?;
+
+        let graph = Graph::new();
+        let storage = DispatchBackend::from(ctx.storage.clone());
+        let quotas = Quotas {
+            default: Some(ubi.len() as u64),
+            ..Default::default()
+        };
+        let loader = ImageLoader::new(&graph, &storage, 10 * 1024 * 1024).quotas(Some(&quotas));
+        let labels = Labels::new().add("source", "quay.io/org/image:tag");
+
+        let tx = ctx.db.begin().await?;
+        loader
+            .load_artifact(ubi.to_vec(), labels.clone(), &tx)
+            .await?;
+        // the quota of the image source is used up
+        assert!(matches!(
+            loader.load_artifact(zookeeper.to_vec(), labels, &tx).await,
+            Err(Error::LimitExceeded(_))
+        ));
+        tx.rollback().await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,209 @@
+//! Support for the CISA catalog of known exploited vulnerabilities (KEV)
+//!
+//! See: <https://www.cisa.gov/known-exploited-vulnerabilities-catalog>
+
+use crate::{
+    model::{IngestResult, Warning, WarningCode},
+    service::Error,
+};
+use hex::ToHex;
+use sea_orm::{ConnectionTrait, EntityTrait, Iterable, Set, TransactionTrait};
+use sea_query::OnConflict;
+use serde::Deserialize;
+use time::{Date, OffsetDateTime, format_description::well_known::Iso8601};
+use tracing::instrument;
+use trustify_common::{db::chunk::EntityChunkedIter, hashing::Digests};
+use trustify_entity::{labels::Labels, vulnerability_kev};
+
+/// The KEV catalog (JSON format)
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Catalog {
+    #[serde(default)]
+    pub catalog_version: Option<String>,
+    #[serde(default)]
+    pub vulnerabilities: Vec<CatalogEntry>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogEntry {
+    #[serde(rename = "cveID")]
+    pub cve_id: String,
+    #[serde(default)]
+    pub vendor_project: Option<String>,
+    #[serde(default)]
+    pub product: Option<String>,
+    #[serde(default)]
+    pub vulnerability_name: Option<String>,
+    pub date_added: String,
+    #[serde(default)]
+    pub required_action: Option<String>,
+    #[serde(default)]
+    pub due_date: Option<String>,
+    /// Either `Known` or `Unknown`
+    #[serde(default)]
+    pub known_ransomware_campaign_use: Option<String>,
+}
+
+#[derive(Default)]
+pub struct KevLoader {}
+
+impl KevLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[instrument(skip(self, buffer, tx), err(level=tracing::Level::INFO))]
+    pub async fn load_bytes(
+        &self,
+        labels: Labels,
+        buffer: &[u8],
+        digests: &Digests,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<IngestResult, Error> {
+        let catalog: Catalog = serde_json::from_slice(buffer)?;
+        self.load(labels, catalog, digests, tx).await
+    }
+
+    #[instrument(skip(self, catalog, tx), err(level=tracing::Level::INFO))]
+    pub async fn load(
+        &self,
+        _labels: Labels,
+        catalog: Catalog,
+        digests: &Digests,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<IngestResult, Error> {
+        let Catalog {
+            catalog_version,
+            vulnerabilities,
+        } = catalog;
+
+        let mut warnings = vec![];
+        let mut batch = vec![];
+
+        for (index, entry) in vulnerabilities.into_iter().enumerate() {
+            let Some(date_added) = date(&entry.date_added) else {
+                warnings.push(
+                    Warning::new(
+                        WarningCode::InvalidValue,
+                        format!(
+                            "Invalid 'dateAdded' of {}: {}",
+                            entry.cve_id, entry.date_added
+                        ),
+                    )
+                    .at(format!("/vulnerabilities/{index}/dateAdded")),
+                );
+                continue;
+            };
+
+            batch.push(vulnerability_kev::ActiveModel {
+                vulnerability_id: Set(entry.cve_id),
+                date_added: Set(date_added),
+                due_date: Set(entry.due_date.as_deref().and_then(date)),
+                vendor_project: Set(entry.vendor_project),
+                product: Set(entry.product),
+                name: Set(entry.vulnerability_name),
+                required_action: Set(entry.required_action),
+                known_ransomware_campaign_use: Set(
+                    entry.known_ransomware_campaign_use.as_deref() == Some("Known")
+                ),
+                catalog_version: Set(catalog_version.clone()),
+            });
+        }
+
+        for chunk in &batch.chunked() {
+            vulnerability_kev::Entity::insert_many(chunk)
+                .on_conflict(
+                    OnConflict::column(vulnerability_kev::Column::VulnerabilityId)
+                        .update_columns(vulnerability_kev::Column::iter())
+                        .to_owned(),
+                )
+                .exec(tx)
+                .await?;
+        }
+
+        Ok(IngestResult {
+            // Like the CWE catalog, this doesn't result in a source document. So we return the
+            // digest.
+            id: digests.sha512.encode_hex(),
+            document_id: Some("KEV".to_string()),
+            warnings,
+        })
+    }
+}
+
+/// Parse a KEV date (like `2021-12-10`), as midnight UTC.
+fn date(value: &str) -> Option<OffsetDateTime> {
+    Date::parse(value, &Iso8601::DEFAULT)
+        .ok()
+        .map(|date| date.midnight().assume_utc())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use test_context::test_context;
+    use test_log::test;
+    use time::macros::datetime;
+    use trustify_test_context::{TrustifyContext, document_bytes};
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn load_kev(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let data = document_bytes("kev/known_exploited_vulnerabilities.json").await?;
+        let digests = Digests::digest(&data);
+
+        let result = ctx
+            .db
+            .transaction(async |tx| {
+                KevLoader::new()
+                    .load_bytes(Labels::default(), &data, &digests, tx)
+                    .await
+            })
+            .await?;
+        assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+
+        let entry = vulnerability_kev::Entity::find_by_id("CVE-2021-44228")
+            .one(&ctx.db)
+            .await?
+            .expect("must be loaded");
+        assert_eq!(entry.date_added, datetime!(2021-12-10 0:00 UTC));
+        assert_eq!(entry.due_date, Some(datetime!(2021-12-24 0:00 UTC)));
+        assert!(entry.known_ransomware_campaign_use);
+        assert_eq!(entry.catalog_version.as_deref(), Some("2025.10.14"));
+
+        Ok(())
+    }
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn invalid_date(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let data = serde_json::to_vec(&serde_json::json!({
+            "catalogVersion": "2025.10.14",
+            "vulnerabilities": [{
+                "cveID": "CVE-2021-44228",
+                "dateAdded": "yesterday",
+            }],
+        }))?;
+        let digests = Digests::digest(&data);
+
+        let result = ctx
+            .db
+            .transaction(async |tx| {
+                KevLoader::new()
+                    .load_bytes(Labels::default(), &data, &digests, tx)
+                    .await
+            })
+            .await?;
+
+        assert_eq!(result.warnings.len(), 1);
+        let warning = &result.warnings[0];
+        assert_eq!(warning.code, WarningCode::InvalidValue);
+        assert_eq!(
+            warning.location.as_deref(),
+            Some("/vulnerabilities/0/dateAdded")
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,191 @@
+//! Limits protecting the ingestion process from pathological documents
+
+use crate::service::Error;
+use std::time::Duration;
+
+/// Limits applied when loading a single document
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Limits {
+    /// The maximum size of a document, in bytes, after decompression.
+    pub max_bytes: usize,
+    /// The maximum nesting depth of a JSON document.
+    pub max_depth: usize,
+    /// The maximum time parsing and loading a document may take.
+    pub max_duration: Duration,
+}
+
+impl Limits {
+    pub const DEFAULT_MAX_BYTES: usize = 1024 * 1024 * 1024;
+    pub const DEFAULT_MAX_DEPTH: usize = 128;
+    pub const DEFAULT_MAX_DURATION: Duration = Duration::from_secs(30 * 60);
+
+    /// Check the content of a document, before it gets parsed.
+    ///
+    /// The nesting depth is only checked for JSON documents. Content which doesn't look like JSON
+    /// is left for the actual parser to deal with.
+    pub fn check(&self, buffer: &[u8]) -> Result<(), Error> {
+        if buffer.len() > self.max_bytes {
+            return Err(Error::PayloadTooLarge);
+        }
+
+        if let Some(depth) = json_depth(buffer, self.max_depth)
+            && depth > self.max_depth
+        {
+            return Err(self.depth_exceeded());
+        }
+
+        Ok(())
+    }
+
+    /// The error reported for a JSON document exceeding the nesting depth.
+    pub(crate) fn depth_exceeded(&self) -> Error {
+        Error::LimitExceeded(format!(
+            "nesting depth of JSON document exceeds {}",
+            self.max_depth
+        ))
+    }
+
+    /// Run the loading of a document, failing if it takes longer than allowed.
+    ///
+    /// The loading process can only be aborted at an `.await` point. Long-running, synchronous
+    /// parts will exceed the limit before being stopped.
+    pub async fn timeout<T>(&self, f: impl Future<Output = Result<T, Error>>) -> Result<T, Error> {
+        tokio::time::timeout(self.max_duration, f)
+            .await
+            .map_err(|_| {
+                Error::LimitExceeded(format!(
+                    "loading the document took longer than {}",
+                    humantime::Duration::from(self.max_duration)
+                ))
+            })?
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_bytes: Self::DEFAULT_MAX_BYTES,
+            max_depth: Self::DEFAULT_MAX_DEPTH,
+            max_duration: Self::DEFAULT_MAX_DURATION,
+        }
+    }
+}
+
+/// Evaluate the nesting depth of a JSON document, without parsing it.
+///
+/// Returns `None` if the content doesn't start like a JSON object or array. Scanning stops as soon
+/// as the depth exceeds `max`, so that the result will be at most `max + 1`.
+fn json_depth(buffer: &[u8], max: usize) -> Option<usize> {
+    let start = buffer.iter().position(|b| !b.is_ascii_whitespace())?;
+    if !matches!(buffer[start], b'{' | b'[') {
+        return None;
+    }
+
+    Some(DepthScanner::default().scan(&buffer[start..], max))
+}
+
+/// Tracks the nesting depth of a JSON document, which is provided in chunks.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct DepthScanner {
+    depth: usize,
+    deepest: usize,
+    in_string: bool,
+    escaped: bool,
+}
+
+impl DepthScanner {
+    /// Scan the next chunk of the document, returning the deepest nesting seen so far.
+    ///
+    /// Scanning stops as soon as the depth exceeds `max`, so that the result will be at most
+    /// `max + 1`.
+    pub(crate) fn scan(&mut self, chunk: &[u8], max: usize) -> usize {
+        for b in chunk {
+            if self.deepest > max {
+                break;
+            }
+
+            if self.in_string {
+                match b {
+                    _ if self.escaped => self.escaped = false,
+                    b'\\' => self.escaped = true,
+                    b'"' => self.in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+
+            match b {
+                b'"' => self.in_string = true,
+                b'{' | b'[' => {
+                    self.depth += 1;
+                    self.deepest = self.deepest.max(self.depth);
+                }
+                b'}' | b']' => self.depth = self.depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+
+        self.deepest
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn depth() {
+        assert_eq!(json_depth(br#"{}"#, 10), Some(1));
+        assert_eq!(json_depth(br#" [{"a": [1, 2]}, {}]"#, 10), Some(3));
+        assert_eq!(json_depth(br#"{"a": "[[[{\"["}"#, 10), Some(1));
+        assert_eq!(json_depth(b"<xml/>", 10), None);
+        assert_eq!(json_depth(b"", 10), None);
+        assert_eq!(json_depth(&[b'['; 1000], 10), Some(11));
+    }
+
+    #[test]
+    fn depth_chunked() {
+        let mut scanner = DepthScanner::default();
+        assert_eq!(scanner.scan(br#"{"a": "[[\"#, 10), 1);
+        assert_eq!(scanner.scan(br#""", "b": [{"#, 10), 3);
+        assert_eq!(scanner.scan(br#"}], "c": []}"#, 10), 3);
+        assert_eq!(scanner.scan(&[b'{'; 100], 10), 11);
+    }
+
+    #[test]
+    fn check() {
+        let limits = Limits {
+            max_bytes: 16,
+            max_depth: 2,
+            ..Default::default()
+        };
+
+        assert!(limits.check(br#"{"a": [1]}"#).is_ok());
+        assert!(matches!(
+            limits.check(br#"{"a": [{}]}"#),
+            Err(Error::LimitExceeded(_))
+        ));
+        assert!(matches!(
+            limits.check(br#"{"a": "too long to be accepted"}"#),
+            Err(Error::PayloadTooLarge)
+        ));
+    }
+
+    #[test(tokio::test)]
+    async fn timeout() {
+        let limits = Limits {
+            max_duration: Duration::from_millis(10),
+            ..Default::default()
+        };
+
+        let result = limits
+            .timeout(async {
+                tokio::time::sleep(Duration::from_secs(10)).await;
+                Ok(())
+            })
+            .await;
+
+        assert!(matches!(result, Err(Error::LimitExceeded(_))));
+    }
+}
@@ -0,0 +1,272 @@
+//! Registry of loaders for document formats which are not built into the ingestor
+//!
+//! Downstream users can register [`DocumentLoader`] implementations when creating the
+//! [`super::IngestorService`]. Whenever the format of a document needs to be detected, the
+//! registered loaders are asked first, in the order they were registered. Documents no registered
+//! loader accepts are handled by the built-in formats.
+
+use crate::{
+    graph::Graph,
+    model::IngestResult,
+    service::{Error, Format, ValidationLevel, limits::Limits},
+};
+use sea_orm::{
+    ConnectionTrait, DatabaseTransaction, TransactionTrait, prelude::async_trait::async_trait,
+};
+use std::{fmt::Debug, sync::Arc};
+use trustify_common::hashing::Digests;
+use trustify_entity::labels::Labels;
+
+/// A loader for a custom document format
+#[async_trait]
+pub trait DocumentLoader: Send + Sync + 'static {
+    /// The name of the format
+    fn name(&self) -> &str;
+
+    /// Check if the document is of the format handled by this loader.
+    fn detect(&self, buffer: &[u8]) -> bool;
+
+    /// Load the document.
+    ///
+    /// The document has already been stored, and the limits have been checked. The transaction is
+    /// nested in the transaction of the ingestion process.
+    async fn load(
+        &self,
+        graph: &Graph,
+        labels: Labels,
+        issuer: Option<String>,
+        digests: &Digests,
+        buffer: &[u8],
+        tx: &DatabaseTransaction,
+    ) -> Result<IngestResult, Error>;
+}
+
+/// The registered loaders for custom formats
+#[derive(Clone, Default)]
+pub struct LoaderRegistry {
+    loaders: Vec<Arc<dyn DocumentLoader>>,
+}
+
+impl LoaderRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a loader, which will be asked after all previously registered ones.
+    pub fn register(mut self, loader: impl DocumentLoader) -> Self {
+        self.loaders.push(Arc::new(loader));
+        self
+    }
+
+    /// Find the first registered loader accepting the document.
+    pub fn detect(&self, buffer: &[u8]) -> Option<&Arc<dyn DocumentLoader>> {
+        self.loaders.iter().find(|loader| loader.detect(buffer))
+    }
+
+    /// Resolve the format of a document.
+    ///
+    /// Registered loaders are only considered when the format needs to be detected, or if the
+    /// format was explicitly requested as [`Format::Custom`].
+    pub(crate) fn resolve(&self, format: Format, buffer: &[u8]) -> Result<Resolved, Error> {
+        match format {
+            Format::Unknown | Format::Advisory | Format::SBOM => match self.detect(buffer) {
+                Some(loader) => Ok(Resolved::Custom(loader.clone())),
+                None => Ok(Resolved::Builtin(format.resolve(buffer)?)),
+            },
+            Format::Custom => self
+                .detect(buffer)
+                .map(|loader| Resolved::Custom(loader.clone()))
+                .ok_or_else(|| {
+                    Error::UnsupportedFormat("No registered loader accepts the document".into())
+                }),
+            format => Ok(Resolved::Builtin(format)),
+        }
+    }
+}
+
+impl Debug for LoaderRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.loaders.iter().map(|loader| loader.name()))
+            .finish()
+    }
+}
+
+/// Registries are equal if they contain the same loaders, in the same order.
+impl PartialEq for LoaderRegistry {
+    fn eq(&self, other: &Self) -> bool {
+        self.loaders.len() == other.loaders.len()
+            && self
+                .loaders
+                .iter()
+                .zip(&other.loaders)
+                .all(|(a, b)| Arc::ptr_eq(a, b))
+    }
+}
+
+impl Eq for LoaderRegistry {}
+
+/// The resolved format of a document
+#[derive(Clone)]
+pub(crate) enum Resolved {
+    Builtin(Format),
+    Custom(Arc<dyn DocumentLoader>),
+}
+
+impl Resolved {
+    /// The format, which is [`Format::Custom`] for registered loaders.
+    pub fn format(&self) -> Format {
+        match self {
+            Self::Builtin(format) => *format,
+            Self::Custom(_) => Format::Custom,
+        }
+    }
+
+    /// The name of the registered loader, if any.
+    pub fn loader(&self) -> Option<String> {
+        match self {
+            Self::Builtin(_) => None,
+            Self::Custom(loader) => Some(loader.name().to_string()),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn load(
+        &self,
+        graph: &Graph,
+        labels: Labels,
+        issuer: Option<String>,
+        digests: &Digests,
+        buffer: &[u8],
+        level: ValidationLevel,
+        limits: &Limits,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<IngestResult, Error> {
+        match self {
+            Self::Builtin(format) => {
+                format
+                    .load(graph, labels, issuer, digests, buffer, level, limits, tx)
+                    .await
+            }
+            Self::Custom(loader) => {
+                limits.check(buffer)?;
+                limits
+                    .timeout(async {
+                        let tx = tx.begin().await?;
+                        let result = loader
+                            .load(graph, labels, issuer, digests, buffer, &tx)
+                            .await?;
+                        tx.commit().await?;
+                        Ok(result)
+                    })
+                    .await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::service::IngestorService;
+    use bytes::Bytes;
+    use test_context::test_context;
+    use test_log::test;
+    use trustify_common::id::Id;
+    use trustify_test_context::{TrustifyContext, document_bytes};
+
+    struct Proprietary;
+
+    #[async_trait]
+    impl DocumentLoader for Proprietary {
+        fn name(&self) -> &str {
+            "proprietary"
+        }
+
+        fn detect(&self, buffer: &[u8]) -> bool {
+            buffer.starts_with(b"PROPRIETARY\n")
+        }
+
+        async fn load(
+            &self,
+            _graph: &Graph,
+            _labels: Labels,
+            _issuer: Option<String>,
+            digests: &Digests,
+            buffer: &[u8],
+            _tx: &DatabaseTransaction,
+        ) -> Result<IngestResult, Error> {
+            let document_id = std::str::from_utf8(&buffer[12..])?.trim().to_string();
+            Ok(IngestResult {
+                id: Id::sha256(&digests.sha256).to_string(),
+                document_id: Some(document_id),
+                warnings: vec![],
+            })
+        }
+    }
+
+    #[test]
+    fn resolve() -> Result<(), anyhow::Error> {
+        let registry = LoaderRegistry::new().register(Proprietary);
+
+        let resolved = registry.resolve(Format::Unknown, b"PROPRIETARY\nFOO-1")?;
+        assert_eq!(resolved.format(), Format::Custom);
+        assert_eq!(resolved.loader().as_deref(), Some("proprietary"));
+
+        // an explicit, built-in format is not detected
+        let resolved = registry.resolve(Format::CSAF, b"PROPRIETARY\nFOO-1")?;
+        assert_eq!(resolved.format(), Format::CSAF);
+
+        assert!(matches!(
+            registry.resolve(Format::Custom, b"{}"),
+            Err(Error::UnsupportedFormat(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn ingest_custom(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let ingestor = IngestorService::new(Graph::new(), ctx.storage.clone(), None)
+            .with_loaders(LoaderRegistry::new().register(Proprietary));
+
+        let result = ctx
+            .db
+            .transaction(async |tx| {
+                ingestor
+                    .ingest(
+                        Bytes::from_static(b"PROPRIETARY\nFOO-1\n"),
+                        Format::Unknown,
+                        (),
+                        None,
+                        Default::default(),
+                        tx,
+                    )
+                    .await
+            })
+            .await?;
+        assert_eq!(result.document_id.as_deref(), Some("FOO-1"));
+
+        // built-in formats are still detected
+        let data = document_bytes("csaf/cve-2023-0044.json").await?;
+        let result = ctx
+            .db
+            .transaction(async |tx| {
+                ingestor
+                    .ingest(
+                        data.clone(),
+                        Format::Unknown,
+                        (),
+                        None,
+                        Default::default(),
+                        tx,
+                    )
+                    .await
+            })
+            .await?;
+        assert_eq!(result.document_id.as_deref(), Some("CVE-2023-0044"));
+
+        Ok(())
+    }
+}
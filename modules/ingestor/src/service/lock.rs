@@ -0,0 +1,136 @@
+//! Locks serializing the ingestion of stored documents with their removal, and with each other.
+//!
+//! A document is stored only once, keyed by its digest, and referenced by all source documents
+//! carrying that digest. Storing a document and committing the source document referencing it
+//! are two steps, just like checking a document for references and removing it from the storage.
+//! Without a lock, a document could be removed right after an ingestion stored it again, but
+//! before the ingestion committed its reference.
+//!
+//! The locks are Postgres advisory locks, held until the end of the transaction. Ingestions take a
+//! shared lock, before storing the document. Removals try to take an exclusive lock, and skip
+//! documents which are currently being ingested.
+//!
+//! Checking the storage quota of a tenant and recording a new document are two steps as well.
+//! Ingestions for the same tenant take an exclusive lock for the tenant before checking the quota,
+//! so that concurrent ingestions can't exceed the quota together.
+
+use crate::service::Error;
+use anyhow::anyhow;
+use hex::ToHex;
+use sea_orm::{ConnectionTrait, DbBackend, DbErr, Statement};
+use trustify_common::hashing::Digests;
+use trustify_module_storage::service::{StorageBackend, dispatch::DispatchBackend};
+
+/// The class of advisory locks held for stored documents, keeping them apart from other advisory
+/// locks
+const LOCK_CLASS: i32 = 0x646f_6373;
+
+/// The class of advisory locks held for the quotas of tenants
+const QUOTA_LOCK_CLASS: i32 = 0x7175_6f74;
+
+/// Lock a stored document for referencing it, waiting for an ongoing removal of it.
+///
+/// The lock must be taken in the transaction recording the reference, before storing the
+/// document.
+pub async fn lock_for_reference(digest: &str, tx: &impl ConnectionTrait) -> Result<(), DbErr> {
+    tx.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT pg_advisory_xact_lock_shared($1, hashtext($2))",
+        [LOCK_CLASS.into(), digest.into()],
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Lock the quota of a tenant, waiting for ongoing ingestions for the same tenant.
+///
+/// The lock must be taken in the transaction recording the document, before checking the quota.
+pub async fn lock_for_quota(
+    label: &str,
+    tenant: &str,
+    tx: &impl ConnectionTrait,
+) -> Result<(), DbErr> {
+    tx.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT pg_advisory_xact_lock($1, hashtext($2))",
+        [QUOTA_LOCK_CLASS.into(), format!("{label}={tenant}").into()],
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Store a document, after locking it for referencing it.
+///
+/// The reference must be recorded in the same transaction.
+pub async fn store_for_reference(
+    storage: &DispatchBackend,
+    data: &[u8],
+    tx: &impl ConnectionTrait,
+) -> Result<Digests, Error> {
+    let digest = Digests::digest(data).sha256.encode_hex::<String>();
+    lock_for_reference(&digest, tx).await?;
+
+    Ok(storage
+        .store(data)
+        .await
+        .map_err(|err| Error::Storage(anyhow!("{err}")))?
+        .digests)
+}
+
+/// Try to lock stored documents for removing them, returning the digests of the locked ones.
+///
+/// Documents which are currently being ingested are not locked, as they will be referenced. The
+/// locks must be taken before checking for references, and the transaction must only end after
+/// removing the documents from the storage.
+pub async fn try_lock_for_removal(
+    digests: Vec<String>,
+    tx: &impl ConnectionTrait,
+) -> Result<Vec<String>, DbErr> {
+    if digests.is_empty() {
+        return Ok(digests);
+    }
+
+    tx.query_all(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT digest FROM unnest($2::text[]) AS digest WHERE pg_try_advisory_xact_lock($1, hashtext(digest))",
+        [LOCK_CLASS.into(), digests.into()],
+    ))
+    .await?
+    .into_iter()
+    .map(|row| row.try_get::<String>("", "digest"))
+    .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sea_orm::TransactionTrait;
+    use test_context::test_context;
+    use test_log::test;
+    use trustify_test_context::TrustifyContext;
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn removal_skips_referencing(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let ingestion = ctx.db.begin().await?;
+        lock_for_reference("foo", &ingestion).await?;
+
+        // "foo" is being ingested, "bar" is not
+        let removal = ctx.db.begin().await?;
+        let locked =
+            try_lock_for_removal(vec!["foo".to_string(), "bar".to_string()], &removal).await?;
+        assert_eq!(locked, vec!["bar".to_string()]);
+        removal.rollback().await?;
+
+        // available again once the ingestion is done
+        ingestion.commit().await?;
+        let removal = ctx.db.begin().await?;
+        let locked = try_lock_for_removal(vec!["foo".to_string()], &removal).await?;
+        assert_eq!(locked, vec!["foo".to_string()]);
+        removal.rollback().await?;
+
+        Ok(())
+    }
+}
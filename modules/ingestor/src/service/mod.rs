@@ -1,29 +1,68 @@
 pub mod advisory;
+pub mod archive;
+pub mod attack_pattern;
+pub mod attestation;
 pub mod dataset;
+pub mod epss;
+pub mod image;
+pub mod kev;
+pub mod limits;
+pub mod loader;
+pub mod lock;
+pub mod quota;
 pub mod sbom;
+pub mod signature;
+pub mod stream;
 pub mod weakness;
 
 mod format;
+
 pub use format::Format;
 
 use crate::graph::Graph;
 use crate::{
-    model::IngestResult,
-    service::dataset::{DatasetIngestResult, DatasetLoader},
+    model::{IngestResult, ValidationResult, Warning, WarningCode, WarningSeverity},
+    service::{
+        archive::{ArchiveIngestResult, ArchiveLoader},
+        dataset::{DatasetExporter, DatasetIngestResult, DatasetLoader},
+        image::{ImageIngestResult, ImageLoader},
+        limits::Limits,
+        loader::{LoaderRegistry, Resolved},
+        quota::Quotas,
+        signature::{Signature, TrustRoot},
+    },
 };
 use actix_web::{HttpResponse, ResponseError, body::BoxBody};
 use anyhow::anyhow;
+use bytes::Bytes;
+use hex::ToHex;
 use parking_lot::Mutex;
 use sbom_walker::report::ReportSink;
 use sea_orm::error::DbErr;
 use sea_orm::{ConnectionTrait, TransactionTrait};
-use std::{fmt::Debug, sync::Arc, time::Instant};
-use tokio::task::JoinError;
+use std::{
+    fmt::Debug,
+    io::{self, Cursor},
+    sync::Arc,
+    time::Instant,
+};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt},
+    task::JoinError,
+};
 use tracing::instrument;
-use trustify_common::{db::DatabaseErrors, error::ErrorInformation, id::IdError};
+use trustify_common::{
+    db::DatabaseErrors,
+    decompress::{self, LimitedReader, decompress_async},
+    error::ErrorInformation,
+    hashing::Digests,
+    id::IdError,
+};
 use trustify_entity::labels::Labels;
 use trustify_module_analysis::service::AnalysisService;
-use trustify_module_storage::service::{StorageBackend, dispatch::DispatchBackend};
+use trustify_module_storage::service::{
+    StorageBackend, StorageKey, StoreError, dispatch::DispatchBackend,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -57,8 +96,16 @@ pub enum Error {
     Join(#[from] JoinError),
     #[error(transparent)]
     Zip(#[from] zip::result::ZipError),
+    #[error("registry error: {0}")]
+    Registry(#[from] oci_client::errors::OciDistributionError),
+    #[error("document violates its specification ({} issues)", .0.len())]
+    SpecViolation(Vec<Warning>),
+    #[error("signature verification failed: {0}")]
+    Signature(String),
     #[error("payload too large")]
     PayloadTooLarge,
+    #[error("limit exceeded: {0}")]
+    LimitExceeded(String),
     #[error("unavailable")]
     Unavailable,
 }
@@ -151,11 +198,37 @@ impl ResponseError for Error {
                 message: inner.to_string(),
                 details: None,
             }),
+            Self::Registry(inner) => HttpResponse::BadGateway().json(ErrorInformation {
+                error: "Registry".into(),
+                message: inner.to_string(),
+                details: None,
+            }),
+            Self::SpecViolation(violations) => HttpResponse::BadRequest().json(ErrorInformation {
+                error: "SpecViolation".into(),
+                message: self.to_string(),
+                details: Some(
+                    violations
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                ),
+            }),
+            Self::Signature(message) => HttpResponse::BadRequest().json(ErrorInformation {
+                error: "Signature".into(),
+                message: message.clone(),
+                details: None,
+            }),
             Self::PayloadTooLarge => HttpResponse::PayloadTooLarge().json(ErrorInformation {
                 error: "PayloadTooLarge".into(),
                 message: self.to_string(),
                 details: None,
             }),
+            Self::LimitExceeded(_) => HttpResponse::UnprocessableEntity().json(ErrorInformation {
+                error: "LimitExceeded".into(),
+                message: self.to_string(),
+                details: None,
+            }),
             Self::Unavailable => HttpResponse::ServiceUnavailable().json(ErrorInformation {
                 error: "Unavailable".into(),
                 message: self.to_string(),
@@ -187,11 +260,71 @@ impl From<Cache> for Option<bool> {
     }
 }
 
+/// How to deal with documents violating their specification
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
+    Default,
+    serde::Deserialize,
+    serde::Serialize,
+    utoipa::ToSchema,
+)]
+#[serde(rename_all = "camelCase")]
+#[schema(rename_all = "camelCase")]
+pub enum ValidationLevel {
+    /// Ingest what can be ingested, and record violations as warnings
+    #[default]
+    Lenient,
+    /// Reject documents violating their specification
+    Strict,
+}
+
+/// Options for ingesting a document, see [`IngestorService::ingest_with`].
+#[derive(Clone, Debug, Default)]
+pub struct IngestOptions {
+    /// How to deal with documents violating their specification.
+    pub level: ValidationLevel,
+    /// A signature, which must be valid for the document to be ingested.
+    pub signature: Option<Signature>,
+}
+
+impl IngestOptions {
+    /// Set the validation level, defaults to lenient.
+    pub fn level(mut self, level: ValidationLevel) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Require the document to be signed, see [`IngestorService::with_trust_root`].
+    pub fn signature(mut self, signature: impl Into<Option<Signature>>) -> Self {
+        self.signature = signature.into();
+        self
+    }
+}
+
+/// Map an error reading a document, failing if the document exceeds its limit.
+fn read_error(err: io::Error) -> Error {
+    match err.kind() {
+        io::ErrorKind::FileTooLarge => Error::PayloadTooLarge,
+        _ => Error::Io(err),
+    }
+}
+
+/// The number of bytes used for detecting the format when ingesting a stream.
+pub const DETECTION_PREFIX: u64 = 64 * 1024;
+
 #[derive(Clone)]
 pub struct IngestorService {
     graph: Graph,
     storage: DispatchBackend,
     analysis: Option<AnalysisService>,
+    limits: Limits,
+    loaders: LoaderRegistry,
+    quotas: Option<Quotas>,
+    trust_root: TrustRoot,
 }
 
 impl IngestorService {
@@ -204,59 +337,278 @@ impl IngestorService {
             graph,
             storage: storage.into(),
             analysis,
+            limits: Limits::default(),
+            loaders: LoaderRegistry::default(),
+            quotas: None,
+            trust_root: TrustRoot::default(),
         }
     }
 
+    /// Set the limits applied when loading a document.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Set the loaders for custom formats.
+    pub fn with_loaders(mut self, loaders: LoaderRegistry) -> Self {
+        self.loaders = loaders;
+        self
+    }
+
+    /// Set the storage quotas, rejecting documents which exceed them.
+    pub fn with_quotas(mut self, quotas: Option<Quotas>) -> Self {
+        self.quotas = quotas;
+        self
+    }
+
+    /// Set the trust root signatures of documents are verified against.
+    pub fn with_trust_root(mut self, trust_root: TrustRoot) -> Self {
+        self.trust_root = trust_root;
+        self
+    }
+
     pub fn storage(&self) -> &DispatchBackend {
         &self.storage
     }
 
-    #[instrument(skip_all, err(level=tracing::Level::INFO))]
+    /// Ingest a document, using the default [`IngestOptions`].
     pub async fn ingest(
         &self,
-        bytes: &[u8],
+        bytes: impl Into<Bytes>,
         format: Format,
         labels: impl Into<Labels> + Debug,
         issuer: Option<String>,
         cache: Cache,
         tx: &(impl ConnectionTrait + TransactionTrait),
     ) -> Result<IngestResult, Error> {
+        self.ingest_with(
+            bytes,
+            format,
+            labels,
+            issuer,
+            cache,
+            IngestOptions::default(),
+            tx,
+        )
+        .await
+    }
+
+    /// Ingest a document, using the provided options.
+    #[instrument(skip_all, err(level=tracing::Level::INFO))]
+    #[allow(clippy::too_many_arguments)]
+    pub async fn ingest_with(
+        &self,
+        bytes: impl Into<Bytes>,
+        format: Format,
+        labels: impl Into<Labels> + Debug,
+        issuer: Option<String>,
+        cache: Cache,
+        options: IngestOptions,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<IngestResult, Error> {
+        let IngestOptions { level, signature } = options;
+
         let start = Instant::now();
 
+        // Compressed documents are stored and loaded in their decompressed form.
+        let bytes = &*self.decompress(bytes.into()).await?;
+
         // We want to resolve the format first to avoid storing a
         // document that we can't subsequently retrieve and load into
         // the database.
-        let fmt = match format {
-            Format::Advisory => Format::advisory_from_bytes(bytes)?,
-            Format::SBOM => Format::sbom_from_bytes(bytes)?,
-            Format::Unknown => Format::from_bytes(bytes)?,
-            v => v,
+        let fmt = self.loaders.resolve(format, bytes)?;
+
+        // The signer label must only be set from a verified signature.
+        let mut labels = labels.into();
+        labels.remove(signature::LABEL_SIGNER);
+        if let Some(signature) = signature {
+            let identity = signature.verify(bytes, &Digests::digest(bytes), &self.trust_root)?;
+            log::debug!("Verified signature of: {}", identity.subject);
+            labels = identity.apply(labels);
+        }
+
+        // keep the document from being removed, until the reference to it is committed
+        let tx = tx.begin().await?;
+        self.check_quota(&labels, bytes.len() as u64, &tx).await?;
+        let digests = lock::store_for_reference(&self.storage, bytes, &tx).await?;
+
+        let result = fmt
+            .load(
+                &self.graph,
+                labels,
+                issuer,
+                &digests,
+                bytes,
+                level,
+                &self.limits,
+                &tx,
+            )
+            .await?;
+
+        tx.commit().await?;
+
+        if let Some(wait) = cache.into() {
+            self.load_graph_cache(fmt.format(), &result, wait).await;
+        }
+
+        let duration = start.elapsed();
+        log::debug!(
+            "Ingested: {} ({:?}): took {}",
+            result.id,
+            result.document_id,
+            humantime::Duration::from(duration),
+        );
+
+        Ok(result)
+    }
+
+    /// Validate a document, without ingesting it.
+    ///
+    /// The document is detected, parsed, and loaded the same way [`Self::ingest`] does. However,
+    /// it is not stored, and all changes to the database are rolled back. The result carries the
+    /// warnings a real ingestion would produce, errors are returned as such.
+    #[instrument(skip_all, err(level=tracing::Level::INFO))]
+    pub async fn validate(
+        &self,
+        bytes: impl Into<Bytes>,
+        format: Format,
+        labels: impl Into<Labels> + Debug,
+        issuer: Option<String>,
+        level: ValidationLevel,
+        db: &impl TransactionTrait,
+    ) -> Result<ValidationResult, Error> {
+        let bytes = &*self.decompress(bytes.into()).await?;
+
+        let fmt = self.loaders.resolve(format, bytes)?;
+
+        let tx = db.begin().await?;
+        let result = fmt
+            .load(
+                &self.graph,
+                labels.into(),
+                issuer,
+                &Digests::digest(bytes),
+                bytes,
+                level,
+                &self.limits,
+                &tx,
+            )
+            .await;
+        tx.rollback().await?;
+
+        let result = result?;
+
+        Ok(ValidationResult {
+            format: fmt.format(),
+            loader: fmt.loader(),
+            document_id: result.document_id,
+            warnings: result.warnings,
+        })
+    }
+
+    /// Ingest a document from a reader, without buffering it in memory as a whole.
+    ///
+    /// The content is streamed into the storage first, and then parsed incrementally from there.
+    /// If the format needs to be detected, only the first [`DETECTION_PREFIX`] bytes are used
+    /// for that. Documents which have their identifying fields beyond that (e.g. SPDX documents
+    /// with sorted keys) require the format to be provided explicitly.
+    ///
+    /// Only SPDX and CycloneDX documents are parsed incrementally. Documents of other formats, of
+    /// registered loaders, or carrying a signature (which must be verified before storing them)
+    /// are collected, up to [`Limits::max_bytes`], and ingested using [`Self::ingest_with`].
+    #[instrument(skip_all, err(level=tracing::Level::INFO))]
+    #[allow(clippy::too_many_arguments)]
+    pub async fn ingest_stream<R>(
+        &self,
+        reader: R,
+        format: Format,
+        labels: impl Into<Labels> + Debug,
+        issuer: Option<String>,
+        cache: Cache,
+        options: IngestOptions,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<IngestResult, Error>
+    where
+        R: AsyncRead + Unpin + Send,
+    {
+        let start = Instant::now();
+
+        let max_bytes = self.limits.max_bytes as u64;
+        let mut reader = LimitedReader::new(reader, max_bytes);
+
+        let mut prefix = Vec::new();
+        (&mut reader)
+            .take(DETECTION_PREFIX)
+            .read_to_end(&mut prefix)
+            .await
+            .map_err(read_error)?;
+
+        let fmt = match self.loaders.resolve(format, &prefix) {
+            Ok(Resolved::Builtin(fmt @ (Format::SPDX | Format::CycloneDX)))
+                if options.signature.is_none() =>
+            {
+                fmt
+            }
+            _ => {
+                let mut bytes = prefix;
+                reader.read_to_end(&mut bytes).await.map_err(read_error)?;
+                return self
+                    .ingest_with(bytes, format, labels, issuer, cache, options, tx)
+                    .await;
+            }
         };
 
         let result = self
             .storage
-            .store(bytes)
+            .store(Cursor::new(prefix).chain(reader))
             .await
-            .map_err(|err| Error::Storage(anyhow!("{err}")))?;
+            .map_err(|err| match err {
+                StoreError::Stream(err) => read_error(err),
+                err => Error::Storage(anyhow!("{err}")),
+            })?;
+
+        // the size is only known once the document is stored
+        let mut labels = labels.into();
+        labels.remove(signature::LABEL_SIGNER);
+
+        // keep the document from being removed, until the reference to it is committed. As the
+        // digest is only known once stored, it might have been removed in the meantime.
+        let tx = tx.begin().await?;
+        self.check_quota(&labels, result.digests.size, &tx).await?;
+        let digest = result.digests.sha256.encode_hex::<String>();
+        lock::lock_for_reference(&digest, &tx).await?;
+
+        let key = StorageKey::from_sha256(&digest);
+        let stream = self
+            .storage
+            .retrieve(key)
+            .await
+            .map_err(|err| Error::Storage(anyhow!("{err}")))?
+            .ok_or_else(|| Error::Storage(anyhow!("stored document is missing")))?;
 
         let result = fmt
-            .load(
+            .load_stream(
                 &self.graph,
-                labels.into(),
+                labels,
                 issuer,
                 &result.digests,
-                bytes,
-                tx,
+                stream,
+                options.level,
+                &self.limits,
+                &tx,
             )
             .await?;
 
+        tx.commit().await?;
+
         if let Some(wait) = cache.into() {
             self.load_graph_cache(fmt, &result, wait).await;
         }
 
         let duration = start.elapsed();
         log::debug!(
-            "Ingested: {} ({:?}): took {}",
+            "Ingested (streaming): {} ({:?}): took {}",
             result.id,
             result.document_id,
             humantime::Duration::from(duration),
@@ -265,6 +617,32 @@ impl IngestorService {
         Ok(result)
     }
 
+    /// Decompress a document, if it is compressed.
+    ///
+    /// The decompressed content is limited to [`Limits::max_bytes`], failing as soon as the limit
+    /// is exceeded, rather than after the document was inflated as a whole.
+    async fn decompress(&self, bytes: Bytes) -> Result<Bytes, Error> {
+        decompress_async(bytes, None, self.limits.max_bytes)
+            .await?
+            .map_err(|err| match err {
+                decompress::Error::PayloadTooLarge => Error::PayloadTooLarge,
+                err => Error::InvalidContent(err.into()),
+            })
+    }
+
+    /// Check a new document against the quota of its tenant, if there are quotas.
+    async fn check_quota(
+        &self,
+        labels: &Labels,
+        size: u64,
+        connection: &impl ConnectionTrait,
+    ) -> Result<(), Error> {
+        match &self.quotas {
+            Some(quotas) => quotas.check(labels, size, connection).await,
+            None => Ok(()),
+        }
+    }
+
     /// Ingest a dataset archive
     #[instrument(skip(self, bytes, tx), err(level=tracing::Level::INFO))]
     pub async fn ingest_dataset(
@@ -274,10 +652,51 @@ impl IngestorService {
         limit: usize,
         tx: &(impl ConnectionTrait + TransactionTrait),
     ) -> Result<DatasetIngestResult, Error> {
-        let loader = DatasetLoader::new(&self.graph, self.storage(), limit);
+        let loader = DatasetLoader::new(&self.graph, self.storage(), limit)
+            .limits(self.limits)
+            .quotas(self.quotas.as_ref());
         loader.load(labels.into(), bytes, tx).await
     }
 
+    /// Ingest an archive (zip, tar, or tar.gz) of documents, detecting the format of each
+    #[instrument(skip(self, bytes, tx), err(level=tracing::Level::INFO))]
+    pub async fn ingest_archive(
+        &self,
+        bytes: impl Into<Bytes>,
+        labels: impl Into<Labels> + Debug,
+        limit: usize,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<ArchiveIngestResult, Error> {
+        let loader = ArchiveLoader::new(&self.graph, self.storage(), limit)
+            .limits(self.limits)
+            .quotas(self.quotas.as_ref());
+        loader.load(labels.into(), bytes.into(), tx).await
+    }
+
+    /// Ingest the SBOMs and attestations attached to an OCI image
+    ///
+    /// The image is provided as reference (like `quay.io/org/image:tag`). Each attached artifact
+    /// is limited in size by `limit`.
+    #[instrument(skip(self, tx), err(level=tracing::Level::INFO))]
+    pub async fn ingest_image(
+        &self,
+        reference: &str,
+        labels: impl Into<Labels> + Debug,
+        limit: usize,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<ImageIngestResult, Error> {
+        let loader = ImageLoader::new(&self.graph, self.storage(), limit)
+            .limits(self.limits)
+            .quotas(self.quotas.as_ref());
+        loader.load(labels.into(), reference, tx).await
+    }
+
+    /// Export all documents as a dataset archive
+    #[instrument(skip_all, err(level=tracing::Level::INFO))]
+    pub async fn export_dataset(&self, tx: &impl ConnectionTrait) -> Result<Vec<u8>, Error> {
+        DatasetExporter::new(self.storage()).export(tx).await
+    }
+
     /// If appropriate, load result into analysis graph cache
     #[instrument(skip(self))]
     async fn load_graph_cache(&self, fmt: Format, result: &IngestResult, wait: bool) {
@@ -311,25 +730,46 @@ impl IngestorService {
 
 /// Capture warnings from the import process
 #[derive(Default)]
-pub(crate) struct Warnings(Arc<Mutex<Vec<String>>>);
+pub(crate) struct Warnings(Arc<Mutex<Vec<Warning>>>);
 
 impl Warnings {
     pub fn new() -> Self {
         Self::default()
     }
 
-    pub fn add(&self, msg: String) {
-        self.0.lock().push(msg);
+    pub fn add(&self, warning: Warning) {
+        self.0.lock().push(warning);
+    }
+
+    /// In strict mode, fail if any violation of the specification was recorded.
+    pub fn enforce(&self, level: ValidationLevel) -> Result<(), Error> {
+        if level == ValidationLevel::Lenient {
+            return Ok(());
+        }
+
+        let violations: Vec<_> = self
+            .0
+            .lock()
+            .iter()
+            .filter(|warning| warning.code == WarningCode::SpecViolation)
+            .cloned()
+            .collect();
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::SpecViolation(violations))
+        }
     }
 }
 
 impl ReportSink for Warnings {
     fn error(&self, msg: String) {
-        self.add(msg)
+        self.add(Warning::new(WarningCode::SpecViolation, msg).severity(WarningSeverity::Error))
     }
 }
 
-impl From<Warnings> for Vec<String> {
+impl From<Warnings> for Vec<Warning> {
     fn from(value: Warnings) -> Self {
         match Arc::try_unwrap(value.0) {
             Ok(warnings) => warnings.into_inner(),
@@ -0,0 +1,299 @@
+//! Storage accounting and quotas, per value of a label
+
+use crate::service::{Error, lock};
+use sea_orm::{ConnectionTrait, DbBackend, DbErr, FromQueryResult, Statement};
+use std::collections::BTreeMap;
+use trustify_entity::labels::Labels;
+
+/// The label identifying the tenant of a document, by default
+pub const DEFAULT_LABEL: &str = "source";
+
+/// Storage quotas for the tenants, identified by the value of a label.
+///
+/// Documents without the label don't belong to any tenant, and are not limited.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Quotas {
+    /// The label identifying the tenant of a document
+    pub label: String,
+    /// The quota of tenants without an explicit one, in bytes
+    pub default: Option<u64>,
+    /// The quotas of individual tenants, in bytes
+    pub limits: BTreeMap<String, u64>,
+}
+
+impl Default for Quotas {
+    fn default() -> Self {
+        Self {
+            label: DEFAULT_LABEL.into(),
+            default: None,
+            limits: Default::default(),
+        }
+    }
+}
+
+impl Quotas {
+    /// The quota of a tenant, if it has one.
+    pub fn limit(&self, tenant: &str) -> Option<u64> {
+        self.limits.get(tenant).copied().or(self.default)
+    }
+
+    /// Check if a new document of the given size fits into the quota of its tenant.
+    ///
+    /// This locks the quota of the tenant, so it must be called in the transaction recording the
+    /// document. Concurrent ingestions for the same tenant wait until that transaction ends.
+    pub async fn check<C: ConnectionTrait>(
+        &self,
+        labels: &Labels,
+        size: u64,
+        connection: &C,
+    ) -> Result<(), Error> {
+        let Some(tenant) = labels.get(&self.label) else {
+            return Ok(());
+        };
+        let Some(limit) = self.limit(tenant) else {
+            return Ok(());
+        };
+
+        lock::lock_for_quota(&self.label, tenant, connection).await?;
+
+        let used = usage(&self.label, Some(tenant), connection)
+            .await?
+            .into_iter()
+            .map(|usage| usage.bytes)
+            .sum::<u64>();
+
+        if used + size > limit {
+            return Err(Error::LimitExceeded(format!(
+                "storage quota of {}={tenant} ({limit} bytes) exceeded, using {used} bytes",
+                self.label
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// The storage used by the documents sharing the same value of a label
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, utoipa::ToSchema)]
+pub struct Usage {
+    /// The value of the label, missing for documents without the label
+    pub value: Option<String>,
+    /// The number of documents
+    pub documents: u64,
+    /// The total size of the documents, uncompressed
+    pub bytes: u64,
+}
+
+#[derive(FromQueryResult)]
+struct UsageRow {
+    value: Option<String>,
+    documents: i64,
+    bytes: i64,
+}
+
+/// Account the storage used by SBOMs and advisories, grouped by the value of a label.
+///
+/// Documents shared by several SBOMs or advisories with the same value are only counted once. If
+/// a value is provided, only documents with that value get accounted.
+pub async fn usage<C: ConnectionTrait>(
+    label: &str,
+    value: Option<&str>,
+    connection: &C,
+) -> Result<Vec<Usage>, DbErr> {
+    let rows = UsageRow::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"
+SELECT docs.value, count(*) AS documents, coalesce(sum(d.size), 0)::bigint AS bytes
+FROM (
+    SELECT source_document_id, labels->>$1 AS value FROM sbom
+    UNION
+    SELECT source_document_id, labels->>$1 AS value FROM advisory
+) docs
+    JOIN source_document d ON d.id = docs.source_document_id
+WHERE $2::text IS NULL OR docs.value = $2
+GROUP BY docs.value
+ORDER BY docs.value NULLS LAST
+"#,
+        [label.into(), value.map(ToString::to_string).into()],
+    ))
+    .all(connection)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| Usage {
+            value: row.value,
+            documents: row.documents as u64,
+            bytes: row.bytes as u64,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::Graph;
+    use crate::service::{Cache, Format, IngestorService};
+    use bytes::Bytes;
+    use sea_orm::TransactionTrait;
+    use std::{
+        io::{Cursor, Write},
+        time::Duration,
+    };
+    use test_context::test_context;
+    use test_log::test;
+    use trustify_test_context::{TrustifyContext, document_bytes};
+    use zip::{ZipWriter, write::FileOptions};
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn quota(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let ubi = document_bytes("ubi9-9.2-755.1697625012.json").await?;
+        let zookeeper = document_bytes("zookeeper-3.9.2-cyclonedx.json").await?;
+
+        let ingestor = IngestorService::new(Graph::new(), ctx.storage.clone(), None).with_quotas(
+            Some(Quotas {
+                default: Some(ubi.len() as u64),
+                ..Default::default()
+            }),
+        );
+
+        let ingest = async |data: &Bytes, source: &str| {
+            ingestor
+                .ingest(
+                    data.clone(),
+                    Format::SBOM,
+                    ("source", source),
+                    None,
+                    Cache::Skip,
+                    &ctx.db,
+                )
+                .await
+        };
+
+        ingest(&ubi, "a").await?;
+        // the quota of "a" is used up
+        assert!(matches!(
+            ingest(&zookeeper, "a").await,
+            Err(Error::LimitExceeded(_))
+        ));
+        // other tenants have their own quota
+        ingest(&zookeeper, "b").await?;
+
+        let usage = usage(DEFAULT_LABEL, None, &ctx.db).await?;
+        assert_eq!(
+            usage,
+            vec![
+                Usage {
+                    value: Some("a".into()),
+                    documents: 1,
+                    bytes: ubi.len() as u64,
+                },
+                Usage {
+                    value: Some("b".into()),
+                    documents: 1,
+                    bytes: zookeeper.len() as u64,
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn quota_dataset(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let ubi = document_bytes("ubi9-9.2-755.1697625012.json").await?;
+        let zookeeper = document_bytes("zookeeper-3.9.2-cyclonedx.json").await?;
+
+        let mut zip = ZipWriter::new(Cursor::new(vec![]));
+        zip.start_file("spdx/ubi.json", FileOptions::<()>::default())?;
+        zip.write_all(&ubi)?;
+        zip.start_file("cyclonedx/zookeeper.json", FileOptions::<()>::default())?;
+        zip.write_all(&zookeeper)?;
+        let data = zip.finish()?.into_inner();
+
+        let ingestor = IngestorService::new(Graph::new(), ctx.storage.clone(), None).with_quotas(
+            Some(Quotas {
+                default: Some(ubi.len() as u64),
+                ..Default::default()
+            }),
+        );
+
+        let result = ctx
+            .db
+            .transaction(async |tx| {
+                ingestor
+                    .ingest_dataset(&data, ("source", "a"), 10 * 1024 * 1024, tx)
+                    .await
+            })
+            .await?;
+
+        // the quota of "a" is used up by the first document
+        assert_eq!(
+            result.files.keys().collect::<Vec<_>>(),
+            vec!["spdx/ubi.json"]
+        );
+        assert_eq!(result.warnings.len(), 1);
+        assert_eq!(
+            result.warnings[0].location.as_deref(),
+            Some("cyclonedx/zookeeper.json")
+        );
+        assert!(result.warnings[0].message.contains("storage quota"));
+
+        Ok(())
+    }
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn quota_concurrent(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let ubi = document_bytes("ubi9-9.2-755.1697625012.json").await?;
+
+        let ingestor = IngestorService::new(Graph::new(), ctx.storage.clone(), None).with_quotas(
+            Some(Quotas {
+                default: Some(ubi.len() as u64),
+                ..Default::default()
+            }),
+        );
+        let ingest = async |source: &str, tx| {
+            ingestor
+                .ingest(
+                    ubi.clone(),
+                    Format::SBOM,
+                    ("source", source),
+                    None,
+                    Cache::Skip,
+                    tx,
+                )
+                .await
+        };
+
+        let first = ctx.db.begin().await?;
+        ingest("a", &first).await?;
+
+        // a concurrent ingestion for the same tenant waits for the first one to finish
+        let second = ctx.db.begin().await?;
+        let result = {
+            let pending = ingest("a", &second);
+            tokio::pin!(pending);
+            assert!(
+                tokio::time::timeout(Duration::from_millis(500), &mut pending)
+                    .await
+                    .is_err()
+            );
+
+            // but other tenants don't
+            let other = ctx.db.begin().await?;
+            ingest("b", &other).await?;
+            other.rollback().await?;
+
+            first.commit().await?;
+            pending.await
+        };
+        // and then sees the quota used up
+        assert!(matches!(result, Err(Error::LimitExceeded(_))));
+        second.rollback().await?;
+
+        Ok(())
+    }
+}
@@ -155,7 +155,7 @@ mod test {
             .transaction(async |tx| {
                 ingestor
                     .ingest(
-                        &data,
+                        data.clone(),
                         Format::ClearlyDefined,
                         ("source", "test"),
                         None,
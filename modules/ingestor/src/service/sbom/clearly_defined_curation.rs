@@ -69,7 +69,7 @@ mod test {
             .transaction(async |tx| {
                 ingestor
                     .ingest(
-                        &data,
+                        data.clone(),
                         Format::ClearlyDefinedCuration,
                         ("source", "test"),
                         None,
@@ -1,10 +1,10 @@
 use crate::{
     graph::{Graph, Outcome, sbom::cyclonedx},
     model::IngestResult,
-    service::{Error, Warnings},
+    service::{Error, ValidationLevel, Warnings},
 };
 use sea_orm::{ConnectionTrait, TransactionTrait};
-use serde_cyclonedx::cyclonedx::v_1_6::Component;
+use serde_cyclonedx::cyclonedx::v_1_6::{CycloneDx, MetadataItemLifecycles};
 use std::str::FromStr;
 use tracing::instrument;
 use trustify_common::hashing::Digests;
@@ -12,11 +12,21 @@ use trustify_entity::labels::Labels;
 
 pub struct CyclonedxLoader<'g> {
     graph: &'g Graph,
+    level: ValidationLevel,
 }
 
 impl<'g> CyclonedxLoader<'g> {
     pub fn new(graph: &'g Graph) -> Self {
-        Self { graph }
+        Self {
+            graph,
+            level: Default::default(),
+        }
+    }
+
+    /// Set the validation level, defaults to lenient.
+    pub fn level(mut self, level: ValidationLevel) -> Self {
+        self.level = level;
+        self
     }
 
     #[instrument(skip_all, err(level=tracing::Level::INFO))]
@@ -27,12 +37,21 @@ impl<'g> CyclonedxLoader<'g> {
         digests: &Digests,
         tx: &(impl ConnectionTrait + TransactionTrait),
     ) -> Result<IngestResult, Error> {
-        let warnings = Warnings::default();
-
         let cdx: Box<serde_cyclonedx::cyclonedx::v_1_6::CycloneDx> = serde_json::from_slice(buffer)
             .map_err(|err| Error::UnsupportedFormat(format!("Failed to parse: {err}")))?;
 
-        let labels_updated = extract_labels(cdx.components.as_ref(), labels);
+        self.load_parsed(labels, cdx, digests, tx).await
+    }
+
+    /// Load a parsed CycloneDX document, deriving the document ID from it.
+    pub async fn load_parsed(
+        &self,
+        labels: Labels,
+        cdx: Box<serde_cyclonedx::cyclonedx::v_1_6::CycloneDx>,
+        digests: &Digests,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<IngestResult, Error> {
+        let labels_updated = extract_labels(&cdx, labels);
 
         log::info!(
             "Storing - version: {:?}, serialNumber: {:?}",
@@ -48,10 +67,28 @@ impl<'g> CyclonedxLoader<'g> {
                 cdx.version.map(|v| v.to_string()) // If serial_number is None, just use version
             });
 
+        self.load_document(labels_updated, cdx, document_id, digests, tx)
+            .await
+    }
+
+    /// Load an already parsed CycloneDX document.
+    ///
+    /// This is used by loaders of other formats too, which translate their input into CycloneDX.
+    #[instrument(skip_all, err(level=tracing::Level::INFO))]
+    pub async fn load_document(
+        &self,
+        labels: Labels,
+        cdx: Box<serde_cyclonedx::cyclonedx::v_1_6::CycloneDx>,
+        document_id: Option<String>,
+        digests: &Digests,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<IngestResult, Error> {
+        let warnings = Warnings::default();
+
         let ctx = match self
             .graph
             .ingest_sbom(
-                labels_updated,
+                labels,
                 digests,
                 document_id.clone(),
                 cyclonedx::Information(&cdx),
@@ -62,6 +99,7 @@ impl<'g> CyclonedxLoader<'g> {
             Outcome::Existed(sbom) => sbom,
             Outcome::Added(sbom) => {
                 sbom.ingest_cyclonedx(cdx, &warnings, tx).await?;
+                warnings.enforce(self.level)?;
 
                 sbom
             }
@@ -101,10 +139,10 @@ impl FromStr for Kind {
     }
 }
 
-fn extract_labels(components: Option<&Vec<Component>>, labels_in: Labels) -> Labels {
+fn extract_labels(cdx: &CycloneDx, labels_in: Labels) -> Labels {
     let mut labels = Labels::new().add("type", "cyclonedx");
 
-    if let Some(components) = components {
+    if let Some(components) = &cdx.components {
         for component in components {
             if let Ok(kind) = Kind::from_str(&component.type_) {
                 labels = labels.add("kind", kind.as_str());
@@ -112,6 +150,10 @@ fn extract_labels(components: Option<&Vec<Component>>, labels_in: Labels) -> Lab
         }
     }
 
+    for lifecycle in lifecycles(cdx) {
+        labels = labels.add(format!("lifecycle/{lifecycle}"), "");
+    }
+
     if !labels_in.is_empty() {
         return labels.extend(labels_in.0);
     }
@@ -119,17 +161,75 @@ fn extract_labels(components: Option<&Vec<Component>>, labels_in: Labels) -> Lab
     labels
 }
 
+/// Extract the lifecycle phases (added with CycloneDX 1.6) from the metadata.
+///
+/// A lifecycle is either a pre-defined `phase`, or a custom one, identified by its `name`.
+fn lifecycles(cdx: &CycloneDx) -> Vec<String> {
+    cdx.metadata
+        .as_ref()
+        .and_then(|metadata| metadata.lifecycles.as_ref())
+        .into_iter()
+        .flatten()
+        .map(|lifecycle| match lifecycle {
+            MetadataItemLifecycles::Variant0(lifecycle) => lifecycle.phase.clone(),
+            MetadataItemLifecycles::Variant1(lifecycle) => lifecycle.name.clone(),
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
-    use crate::service::{Cache, IngestorService};
+    use super::*;
+    use crate::service::{Cache, IngestorService, ValidationLevel};
     use crate::{graph::Graph, service::Format};
     use sea_orm::EntityTrait;
+    use serde_json::json;
     use test_context::test_context;
     use test_log::test;
     use trustify_entity::sbom_ai;
     use trustify_entity::sbom_crypto;
+    use trustify_entity::sbom_vex::{self, VexState};
     use trustify_test_context::{TrustifyContext, document_bytes};
 
+    #[test]
+    fn lifecycle_labels() -> Result<(), anyhow::Error> {
+        let cdx: CycloneDx = serde_json::from_value(json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": "1.6",
+            "version": 1,
+            "metadata": {
+                "lifecycles": [
+                    { "phase": "build" },
+                    { "name": "platform-integration-testing", "description": "Integration testing specific to the runtime platform" }
+                ]
+            },
+            "components": [
+                {
+                    "type": "cryptographic-asset",
+                    "name": "SHA-256",
+                    "cryptoProperties": {
+                        "assetType": "algorithm",
+                        "algorithmProperties": { "primitive": "hash" }
+                    }
+                }
+            ]
+        }))?;
+
+        let labels = extract_labels(&cdx, Labels::new().add("source", "test"));
+
+        assert_eq!(labels.0.get("type").map(String::as_str), Some("cyclonedx"));
+        assert_eq!(labels.0.get("kind").map(String::as_str), Some("cbom"));
+        assert_eq!(labels.0.get("source").map(String::as_str), Some("test"));
+        assert!(labels.0.contains_key("lifecycle/build"));
+        assert!(
+            labels
+                .0
+                .contains_key("lifecycle/platform-integration-testing")
+        );
+
+        Ok(())
+    }
+
     #[test_context(TrustifyContext)]
     #[test(tokio::test)]
     async fn ingest_cyclonedx(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
@@ -142,7 +242,7 @@ mod test {
             .transaction(async |tx| {
                 ingestor
                     .ingest(
-                        &data,
+                        data.clone(),
                         Format::CycloneDX,
                         ("source", "test"),
                         None,
@@ -170,7 +270,7 @@ mod test {
             .transaction(async |tx| {
                 ingestor
                     .ingest(
-                        &data,
+                        data.clone(),
                         Format::CycloneDX,
                         [("type", "cyclonedx"), ("kind", "aibom")],
                         None,
@@ -188,7 +288,7 @@ mod test {
             .transaction(async |tx| {
                 ingestor
                     .ingest(
-                        &data,
+                        data.clone(),
                         Format::CycloneDX,
                         [("type", "cyclonedx"), ("kind", "aibom")],
                         None,
@@ -217,7 +317,7 @@ mod test {
             .transaction(async |tx| {
                 ingestor
                     .ingest(
-                        &data,
+                        data.clone(),
                         Format::CycloneDX,
                         [("type", "cyclonedx"), ("kind", "aibom")],
                         None,
@@ -231,6 +331,48 @@ mod test {
         Ok(())
     }
 
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn ingest_embedded_vex(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let graph = Graph::new();
+        let data = document_bytes("cyclonedx/vex/embedded.json").await?;
+
+        let ingestor = IngestorService::new(graph, ctx.storage.clone(), Default::default());
+
+        ctx.db
+            .transaction(async |tx| {
+                ingestor
+                    .ingest(data.clone(), Format::CycloneDX, (), None, Cache::Skip, tx)
+                    .await
+            })
+            .await?;
+
+        let statements = sbom_vex::Entity::find().all(&ctx.db).await?;
+        assert_eq!(statements.len(), 2);
+
+        let jackson = statements
+            .iter()
+            .find(|statement| statement.vulnerability_id == "CVE-2020-25649")
+            .expect("must have a statement for jackson-databind");
+        // the BOM-Link must be resolved to the component
+        assert_eq!(
+            jackson.node_id,
+            "pkg:maven/com.fasterxml.jackson.core/jackson-databind@2.10.0?type=jar"
+        );
+        assert_eq!(jackson.state, Some(VexState::NotAffected));
+        assert_eq!(jackson.justification.as_deref(), Some("code_not_reachable"));
+        assert_eq!(jackson.response, vec!["will_not_fix", "update"]);
+
+        let snakeyaml = statements
+            .iter()
+            .find(|statement| statement.vulnerability_id == "CVE-2022-1471")
+            .expect("must have a statement for snakeyaml");
+        assert_eq!(snakeyaml.state, Some(VexState::Exploitable));
+        assert_eq!(snakeyaml.detail, None);
+
+        Ok(())
+    }
+
     #[test_context(TrustifyContext)]
     #[test(tokio::test)]
     async fn ingest_cryptographic_cyclonedx(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
@@ -245,7 +387,7 @@ mod test {
             .transaction(async |tx| {
                 ingestor
                     .ingest(
-                        &data,
+                        data.clone(),
                         Format::CycloneDX,
                         [("type", "cyclonedx"), ("kind", "cbom")],
                         None,
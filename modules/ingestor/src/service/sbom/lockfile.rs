@@ -0,0 +1,643 @@
+//! Ingestion of dependency lockfiles (`Cargo.lock`, `package-lock.json`, `go.sum`).
+//!
+//! The locked packages are translated into a CycloneDX SBOM, so that they can be correlated with
+//! vulnerabilities like the packages of any other SBOM. Lockfiles don't carry a name for the
+//! project itself, unless the format provides one (like `package-lock.json` does).
+
+use crate::{
+    graph::Graph,
+    model::IngestResult,
+    service::{Error, advisory::osv::translate::to_purl, sbom::cyclonedx::CyclonedxLoader},
+};
+use osv::schema::Ecosystem;
+use sea_orm::{ConnectionTrait, TransactionTrait};
+use serde::Deserialize;
+use serde_cyclonedx::cyclonedx::v_1_6::CycloneDx;
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use tracing::instrument;
+use trustify_common::hashing::Digests;
+use trustify_entity::labels::Labels;
+
+/// The kind of lockfile
+#[derive(Copy, Clone, Debug, PartialEq, Eq, strum::Display)]
+pub enum Kind {
+    #[strum(to_string = "Cargo.lock")]
+    Cargo,
+    #[strum(to_string = "package-lock.json")]
+    Npm,
+    #[strum(to_string = "go.sum")]
+    Go,
+}
+
+impl Kind {
+    /// Detect the kind of lockfile, if it is one.
+    ///
+    /// `package-lock.json` files are only detected when they carry a `lockfileVersion`.
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        let text = std::str::from_utf8(bytes).ok()?;
+
+        if is_go_sum(text) {
+            Some(Self::Go)
+        } else if text.contains("[[package]]")
+            && toml::from_str::<CargoLock>(text).is_ok_and(|lock| !lock.package.is_empty())
+        {
+            Some(Self::Cargo)
+        } else if serde_json::from_str::<NpmLockVersion>(text)
+            .is_ok_and(|lock| lock.lockfile_version > 0)
+        {
+            Some(Self::Npm)
+        } else {
+            None
+        }
+    }
+
+    fn ecosystem(&self) -> Ecosystem {
+        match self {
+            Self::Cargo => Ecosystem::CratesIO,
+            Self::Npm => Ecosystem::Npm,
+            Self::Go => Ecosystem::Go,
+        }
+    }
+}
+
+/// Lines of `module version[/go.mod] hash`
+fn is_go_sum(text: &str) -> bool {
+    let mut lines = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .peekable();
+
+    lines.peek().is_some()
+        && lines.all(|line| {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            fields.len() == 3 && fields[2].starts_with("h1:")
+        })
+}
+
+/// A locked package
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Package {
+    pub name: String,
+    pub version: String,
+    /// Only set for packages from the default registry of the ecosystem
+    pub purl: Option<String>,
+    /// The references of the packages this package depends on
+    pub depends_on: BTreeSet<String>,
+}
+
+impl Package {
+    fn new(kind: Kind, name: &str, version: &str, registry: bool) -> Self {
+        let package = osv::schema::Package {
+            ecosystem: kind.ecosystem(),
+            name: name.to_string(),
+            purl: None,
+        };
+        let purl = match registry {
+            true => to_purl(&package).and_then(|mut purl| {
+                purl.with_version(version).ok()?;
+                Some(purl.to_string())
+            }),
+            false => None,
+        };
+
+        Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            purl,
+            depends_on: Default::default(),
+        }
+    }
+
+    /// The reference of the package in the SBOM
+    pub fn bom_ref(&self) -> String {
+        self.purl
+            .clone()
+            .unwrap_or_else(|| format!("{}@{}", self.name, self.version))
+    }
+}
+
+/// A parsed lockfile
+#[derive(Clone, Debug)]
+pub struct Lockfile {
+    pub kind: Kind,
+    /// The name of the project, if the lockfile provides it
+    pub name: Option<String>,
+    pub version: Option<String>,
+    /// The packages, by their reference
+    pub packages: BTreeMap<String, Package>,
+    /// The references of the packages the project directly depends on
+    pub direct: BTreeSet<String>,
+}
+
+#[derive(Deserialize)]
+struct CargoLock {
+    #[serde(default)]
+    package: Vec<CargoPackage>,
+}
+
+#[derive(Deserialize)]
+struct CargoPackage {
+    name: String,
+    version: String,
+    #[serde(default)]
+    source: Option<String>,
+    #[serde(default)]
+    dependencies: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NpmLockVersion {
+    #[serde(default)]
+    lockfile_version: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NpmLock {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    version: Option<String>,
+    /// The packages, by their location (v2, v3)
+    #[serde(default)]
+    packages: BTreeMap<String, NpmPackage>,
+    /// The packages, by their name, nested (v1)
+    #[serde(default)]
+    dependencies: BTreeMap<String, NpmDependency>,
+}
+
+#[derive(Deserialize)]
+struct NpmPackage {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    version: Option<String>,
+    #[serde(default)]
+    resolved: Option<String>,
+    #[serde(default)]
+    link: bool,
+    #[serde(default)]
+    dependencies: BTreeMap<String, String>,
+    #[serde(default, rename = "optionalDependencies")]
+    optional_dependencies: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct NpmDependency {
+    version: String,
+    #[serde(default)]
+    resolved: Option<String>,
+    #[serde(default)]
+    requires: BTreeMap<String, String>,
+    #[serde(default)]
+    dependencies: BTreeMap<String, NpmDependency>,
+}
+
+const CRATES_IO: [&str; 2] = [
+    "registry+https://github.com/rust-lang/crates.io-index",
+    "sparse+https://index.crates.io/",
+];
+const NPM_REGISTRY: &str = "https://registry.npmjs.org/";
+
+impl Lockfile {
+    /// Parse a lockfile, detecting its kind
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        match Kind::detect(bytes) {
+            Some(Kind::Cargo) => Self::from_cargo(std::str::from_utf8(bytes)?),
+            Some(Kind::Npm) => Self::from_npm(bytes),
+            Some(Kind::Go) => Ok(Self::from_go_sum(std::str::from_utf8(bytes)?)),
+            None => Err(Error::UnsupportedFormat(
+                "Unable to detect lockfile; only Cargo.lock, package-lock.json, and go.sum are supported"
+                    .into(),
+            )),
+        }
+    }
+
+    fn from_cargo(text: &str) -> Result<Self, Error> {
+        let lock: CargoLock =
+            toml::from_str(text).map_err(|err| Error::InvalidContent(err.into()))?;
+
+        let packages: Vec<_> = lock
+            .package
+            .iter()
+            .map(|package| {
+                let registry = package
+                    .source
+                    .as_deref()
+                    .is_some_and(|source| CRATES_IO.contains(&source));
+                Package::new(Kind::Cargo, &package.name, &package.version, registry)
+            })
+            .collect();
+
+        // references by name, and by name and version
+        let mut refs = HashMap::<&str, Vec<(&str, String)>>::new();
+        for package in &packages {
+            refs.entry(&package.name)
+                .or_default()
+                .push((&package.version, package.bom_ref()));
+        }
+
+        // entries are either "name", or "name version", or "name version (source)"
+        let resolve = |dependency: &str| -> Option<String> {
+            let mut parts = dependency.split_whitespace();
+            let candidates = refs.get(parts.next()?)?;
+            match parts.next() {
+                Some(version) => candidates
+                    .iter()
+                    .find(|(candidate, _)| *candidate == version)
+                    .map(|(_, r#ref)| r#ref.clone()),
+                None => candidates.first().map(|(_, r#ref)| r#ref.clone()),
+            }
+        };
+
+        let mut result = Self {
+            kind: Kind::Cargo,
+            name: None,
+            version: None,
+            packages: Default::default(),
+            direct: Default::default(),
+        };
+
+        for (mut package, locked) in packages.clone().into_iter().zip(&lock.package) {
+            package.depends_on = locked
+                .dependencies
+                .iter()
+                .filter_map(|dependency| resolve(dependency))
+                .collect();
+
+            // local packages (without a source) are the members of the project
+            if locked.source.is_none() {
+                result.direct.insert(package.bom_ref());
+            }
+
+            result.packages.insert(package.bom_ref(), package);
+        }
+
+        Ok(result)
+    }
+
+    fn from_npm(bytes: &[u8]) -> Result<Self, Error> {
+        let lock: NpmLock = serde_json::from_slice(bytes)?;
+
+        let mut result = Self {
+            kind: Kind::Npm,
+            name: lock.name,
+            version: lock.version,
+            packages: Default::default(),
+            direct: Default::default(),
+        };
+
+        if !lock.packages.is_empty() {
+            // v2 and v3: packages by their location in the "node_modules" tree
+
+            let mut refs = HashMap::<&str, String>::new();
+            for (location, package) in &lock.packages {
+                // the root package is the project itself, links point to other locations
+                if location.is_empty() || package.link {
+                    continue;
+                }
+                let Some(name) = npm_name(location, package) else {
+                    continue;
+                };
+                let Some(version) = &package.version else {
+                    continue;
+                };
+
+                let registry = package
+                    .resolved
+                    .as_deref()
+                    .is_none_or(|resolved| resolved.starts_with(NPM_REGISTRY));
+                let package = Package::new(Kind::Npm, name, version, registry);
+                refs.insert(location, package.bom_ref());
+                result.packages.insert(package.bom_ref(), package);
+            }
+
+            for (location, package) in &lock.packages {
+                let dependencies = package
+                    .dependencies
+                    .keys()
+                    .chain(package.optional_dependencies.keys())
+                    .filter_map(|name| npm_resolve(&refs, location, name));
+
+                match refs.get(location.as_str()) {
+                    Some(r#ref) => {
+                        let depends_on = dependencies.collect::<Vec<_>>();
+                        if let Some(package) = result.packages.get_mut(r#ref) {
+                            package.depends_on.extend(depends_on);
+                        }
+                    }
+                    // the root package
+                    None if location.is_empty() => result.direct.extend(dependencies),
+                    None => {}
+                }
+            }
+        } else {
+            // v1: nested dependencies by their name
+            fn collect(
+                result: &mut Lockfile,
+                dependencies: &BTreeMap<String, NpmDependency>,
+            ) -> BTreeMap<String, String> {
+                let mut refs = BTreeMap::new();
+                for (name, dependency) in dependencies {
+                    let registry = dependency
+                        .resolved
+                        .as_deref()
+                        .is_none_or(|resolved| resolved.starts_with(NPM_REGISTRY));
+                    let package = Package::new(Kind::Npm, name, &dependency.version, registry);
+                    refs.insert(name.clone(), package.bom_ref());
+                    result.packages.insert(package.bom_ref(), package);
+                }
+
+                for (name, dependency) in dependencies {
+                    // nested dependencies take precedence over the ones of the parent
+                    let mut scope = refs.clone();
+                    scope.extend(collect(result, &dependency.dependencies));
+
+                    let depends_on: BTreeSet<_> = dependency
+                        .requires
+                        .keys()
+                        .filter_map(|name| scope.get(name).cloned())
+                        .collect();
+                    if let Some(package) = refs
+                        .get(name)
+                        .and_then(|r#ref| result.packages.get_mut(r#ref))
+                    {
+                        package.depends_on.extend(depends_on);
+                    }
+                }
+
+                refs
+            }
+
+            // in v1, all top-level dependencies are considered direct ones
+            let direct = collect(&mut result, &lock.dependencies);
+            result.direct.extend(direct.into_values());
+        }
+
+        Ok(result)
+    }
+
+    fn from_go_sum(text: &str) -> Self {
+        let mut result = Self {
+            kind: Kind::Go,
+            name: None,
+            version: None,
+            packages: Default::default(),
+            direct: Default::default(),
+        };
+
+        for line in text.lines() {
+            let mut fields = line.split_whitespace();
+            let (Some(module), Some(version)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+            // entries for the "go.mod" file only are required for resolving, but not part of the build
+            if version.ends_with("/go.mod") {
+                continue;
+            }
+
+            let package = Package::new(Kind::Go, module, version, true);
+            result.direct.insert(package.bom_ref());
+            result.packages.insert(package.bom_ref(), package);
+        }
+
+        result
+    }
+
+    /// The document ID, the name of the project, or the kind of lockfile if unknown
+    pub fn document_id(&self) -> String {
+        match (&self.name, &self.version) {
+            (Some(name), Some(version)) => format!("{name}@{version}"),
+            (Some(name), None) => name.clone(),
+            _ => self.kind.to_string(),
+        }
+    }
+
+    /// Translate into a CycloneDX SBOM
+    pub fn into_cyclonedx(self) -> Result<CycloneDx, serde_json::Error> {
+        let root = self.document_id();
+
+        let mut metadata = json!({
+            "bom-ref": root,
+            "type": "application",
+            "name": self.name.as_deref().unwrap_or(&root),
+        });
+        if let Some(version) = &self.version {
+            metadata["version"] = json!(version);
+        }
+
+        let mut dependencies = vec![json!({ "ref": root, "dependsOn": self.direct })];
+        let components: Vec<Value> = self
+            .packages
+            .into_iter()
+            .map(|(bom_ref, package)| {
+                if !package.depends_on.is_empty() {
+                    dependencies.push(json!({ "ref": bom_ref, "dependsOn": package.depends_on }));
+                }
+
+                let mut component = json!({
+                    "bom-ref": bom_ref,
+                    "type": "library",
+                    "name": package.name,
+                    "version": package.version,
+                });
+                if let Some(purl) = package.purl {
+                    component["purl"] = json!(purl);
+                }
+                component
+            })
+            .collect();
+
+        serde_json::from_value(json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": "1.6",
+            "version": 1,
+            "metadata": { "component": metadata },
+            "components": components,
+            "dependencies": dependencies,
+        }))
+    }
+}
+
+/// The name of a package of a `package-lock.json` v2+, from its location
+fn npm_name<'a>(location: &'a str, package: &'a NpmPackage) -> Option<&'a str> {
+    if let Some(name) = &package.name {
+        return Some(name);
+    }
+
+    let (_, name) = location.rsplit_once("node_modules/")?;
+    Some(name)
+}
+
+/// Resolve a dependency the way node does, looking up the `node_modules` folders of all parents
+fn npm_resolve(refs: &HashMap<&str, String>, location: &str, name: &str) -> Option<String> {
+    let mut base = location;
+    loop {
+        let candidate = match base {
+            "" => format!("node_modules/{name}"),
+            base => format!("{base}/node_modules/{name}"),
+        };
+        if let Some(r#ref) = refs.get(candidate.as_str()) {
+            return Some(r#ref.clone());
+        }
+        if base.is_empty() {
+            return None;
+        }
+        base = match base.rfind("/node_modules/") {
+            Some(pos) => &base[..pos],
+            None => "",
+        };
+    }
+}
+
+pub struct LockfileLoader<'g> {
+    graph: &'g Graph,
+}
+
+impl<'g> LockfileLoader<'g> {
+    pub fn new(graph: &'g Graph) -> Self {
+        Self { graph }
+    }
+
+    #[instrument(skip_all, err(level=tracing::Level::INFO))]
+    pub async fn load(
+        &self,
+        labels: Labels,
+        buffer: &[u8],
+        digests: &Digests,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<IngestResult, Error> {
+        let lockfile = Lockfile::from_bytes(buffer)?;
+
+        log::info!(
+            "Storing - kind: {}, packages: {}",
+            lockfile.kind,
+            lockfile.packages.len()
+        );
+
+        let labels = Labels::new()
+            .add("type", "lockfile")
+            .add("lockfile", lockfile.kind.to_string())
+            .extend(labels.0);
+        let document_id = lockfile.document_id();
+
+        let cdx = Box::new(
+            lockfile
+                .into_cyclonedx()
+                .map_err(|err| Error::InvalidContent(err.into()))?,
+        );
+
+        CyclonedxLoader::new(self.graph)
+            .load_document(labels, cdx, Some(document_id), digests, tx)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::service::{Cache, Format, IngestorService};
+    use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+    use test_context::test_context;
+    use test_log::test;
+    use trustify_entity::sbom;
+    use trustify_test_context::{TrustifyContext, document_bytes};
+
+    #[test(tokio::test)]
+    async fn parse_cargo_lock() -> Result<(), anyhow::Error> {
+        let data = document_bytes("lockfile/Cargo.lock").await?;
+        assert_eq!(Kind::detect(&data), Some(Kind::Cargo));
+
+        let lockfile = Lockfile::from_bytes(&data)?;
+        assert_eq!(lockfile.packages.len(), 4);
+        assert_eq!(
+            lockfile.direct,
+            BTreeSet::from(["example@0.1.0".to_string()])
+        );
+
+        let example = &lockfile.packages["example@0.1.0"];
+        assert_eq!(example.purl, None);
+        assert_eq!(
+            example.depends_on,
+            BTreeSet::from([
+                "pkg:cargo/serde@1.0.210".to_string(),
+                "pkg:cargo/time@0.3.36".to_string()
+            ])
+        );
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn parse_package_lock() -> Result<(), anyhow::Error> {
+        let data = document_bytes("lockfile/package-lock.json").await?;
+        assert_eq!(Kind::detect(&data), Some(Kind::Npm));
+
+        let lockfile = Lockfile::from_bytes(&data)?;
+        assert_eq!(lockfile.document_id(), "example@1.0.0");
+        assert_eq!(lockfile.packages.len(), 4);
+        assert_eq!(
+            lockfile.direct,
+            BTreeSet::from([
+                "pkg:npm/%40babel/code-frame@7.24.7".to_string(),
+                "pkg:npm/lodash@4.17.20".to_string()
+            ])
+        );
+
+        // the nested version takes precedence
+        let code_frame = &lockfile.packages["pkg:npm/%40babel/code-frame@7.24.7"];
+        assert_eq!(
+            code_frame.depends_on,
+            BTreeSet::from(["pkg:npm/picocolors@1.0.1".to_string()])
+        );
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn parse_go_sum() -> Result<(), anyhow::Error> {
+        let data = document_bytes("lockfile/go.sum").await?;
+        assert_eq!(Kind::detect(&data), Some(Kind::Go));
+
+        let lockfile = Lockfile::from_bytes(&data)?;
+        assert_eq!(
+            lockfile.packages.keys().collect::<Vec<_>>(),
+            vec![
+                "pkg:golang/github.com/google/uuid@v1.6.0",
+                "pkg:golang/golang.org/x/net@v0.17.0"
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn ingest_lockfile(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let data = document_bytes("lockfile/package-lock.json").await?;
+        assert!(matches!(Format::from_bytes(&data), Ok(Format::Lockfile)));
+
+        let ingestor = IngestorService::new(Graph::new(), ctx.storage.clone(), None);
+        ctx.db
+            .transaction(async |tx| {
+                ingestor
+                    .ingest(data.clone(), Format::SBOM, (), None, Cache::Skip, tx)
+                    .await
+            })
+            .await?;
+
+        let sbom = sbom::Entity::find()
+            .filter(sbom::Column::DocumentId.eq("example@1.0.0"))
+            .one(&ctx.db)
+            .await?
+            .expect("must be ingested");
+        assert_eq!(
+            sbom.labels.0.get("lockfile").map(String::as_str),
+            Some("package-lock.json")
+        );
+
+        Ok(())
+    }
+}
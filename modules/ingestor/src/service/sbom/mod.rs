@@ -1,4 +1,8 @@
 pub mod clearly_defined;
 pub mod clearly_defined_curation;
 pub mod cyclonedx;
+pub mod lockfile;
 pub mod spdx;
+pub mod spdx3;
+pub mod syft;
+pub mod trivy;
@@ -4,21 +4,35 @@ use crate::{
         sbom::spdx::{self},
     },
     model::IngestResult,
-    service::{Error, Warnings},
+    service::{Error, ValidationLevel, Warnings},
 };
 use sea_orm::{ConnectionTrait, TransactionTrait};
 use serde_json::Value;
+use spdx_rs::models::SPDX;
 use tracing::instrument;
-use trustify_common::{hashing::Digests, sbom::spdx::parse_spdx};
+use trustify_common::{
+    hashing::Digests,
+    sbom::spdx::{LenientSpdx, parse_spdx},
+};
 use trustify_entity::labels::Labels;
 
 pub struct SpdxLoader<'g> {
     graph: &'g Graph,
+    level: ValidationLevel,
 }
 
 impl<'g> SpdxLoader<'g> {
     pub fn new(graph: &'g Graph) -> Self {
-        Self { graph }
+        Self {
+            graph,
+            level: Default::default(),
+        }
+    }
+
+    /// Set the validation level, defaults to lenient.
+    pub fn level(mut self, level: ValidationLevel) -> Self {
+        self.level = level;
+        self
     }
 
     #[instrument(skip_all, err(level=tracing::Level::INFO))]
@@ -30,8 +44,33 @@ impl<'g> SpdxLoader<'g> {
         tx: &(impl ConnectionTrait + TransactionTrait),
     ) -> Result<IngestResult, Error> {
         let warnings = Warnings::default();
-
         let (spdx, _) = parse_spdx(&warnings, json)?;
+        self.store(labels, spdx, warnings, digests, tx).await
+    }
+
+    /// Load a document, which was already parsed (e.g. from a stream).
+    #[instrument(skip_all, err(level=tracing::Level::INFO))]
+    pub async fn load_parsed(
+        &self,
+        labels: Labels,
+        spdx: LenientSpdx,
+        digests: &Digests,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<IngestResult, Error> {
+        let warnings = Warnings::default();
+        let (spdx, _) = spdx.into_spdx(&warnings);
+        self.store(labels, spdx, warnings, digests, tx).await
+    }
+
+    async fn store(
+        &self,
+        labels: Labels,
+        spdx: SPDX,
+        warnings: Warnings,
+        digests: &Digests,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<IngestResult, Error> {
+        warnings.enforce(self.level)?;
 
         log::info!(
             "Storing: {}",
@@ -59,6 +98,7 @@ impl<'g> SpdxLoader<'g> {
             Outcome::Existed(sbom) => sbom,
             Outcome::Added(sbom) => {
                 sbom.ingest_spdx(spdx, &warnings, tx).await?;
+                warnings.enforce(self.level)?;
                 sbom
             }
         };
@@ -73,7 +113,7 @@ impl<'g> SpdxLoader<'g> {
 
 #[cfg(test)]
 mod test {
-    use crate::service::{Cache, IngestorService};
+    use crate::service::{Cache, IngestorService, ValidationLevel};
     use crate::{graph::Graph, service::Format};
     use test_context::test_context;
     use test_log::test;
@@ -91,7 +131,7 @@ mod test {
             .transaction(async |tx| {
                 ingestor
                     .ingest(
-                        &data,
+                        data.clone(),
                         Format::SPDX,
                         ("source", "test"),
                         None,
@@ -0,0 +1,431 @@
+//! Ingestion of SPDX 3.0 documents, using the JSON-LD serialization.
+//!
+//! SPDX 3 replaced the document centric model of SPDX 2 with a flat graph of elements and
+//! relationships. The document gets translated into a CycloneDX SBOM, which is then processed
+//! the same way any other CycloneDX SBOM is.
+
+use crate::{
+    graph::Graph,
+    model::IngestResult,
+    service::{Error, sbom::cyclonedx::CyclonedxLoader},
+};
+use sea_orm::{ConnectionTrait, TransactionTrait};
+use serde::Deserialize;
+use serde_cyclonedx::cyclonedx::v_1_6::CycloneDx;
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use tracing::instrument;
+use trustify_common::hashing::Digests;
+use trustify_entity::labels::Labels;
+
+/// The subset of the SPDX 3 model we are interested in
+#[derive(Clone, Debug, Deserialize)]
+pub struct Spdx3 {
+    #[serde(rename = "@graph")]
+    pub graph: Vec<Element>,
+}
+
+/// An element of the graph.
+///
+/// All element types share the same struct, only the fields relevant for the type are set.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Element {
+    #[serde(rename = "type")]
+    pub r#type: String,
+    /// The ID of an element
+    #[serde(default)]
+    pub spdx_id: Option<String>,
+    /// The ID of a blank node, like a shared creation info
+    #[serde(rename = "@id", default)]
+    pub id: Option<String>,
+
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Either a reference to a creation info element, or an inline one
+    #[serde(default)]
+    pub creation_info: Option<Value>,
+    #[serde(default)]
+    pub root_element: Vec<String>,
+
+    // creation info
+    #[serde(default)]
+    pub created: Option<String>,
+    #[serde(default)]
+    pub created_by: Vec<String>,
+
+    // software package
+    #[serde(rename = "software_packageVersion", default)]
+    pub package_version: Option<String>,
+    #[serde(rename = "software_packageUrl", default)]
+    pub package_url: Option<String>,
+    #[serde(rename = "software_primaryPurpose", default)]
+    pub primary_purpose: Option<String>,
+    #[serde(default)]
+    pub external_identifier: Vec<ExternalIdentifier>,
+
+    // license expression
+    #[serde(rename = "simplelicensing_licenseExpression", default)]
+    pub license_expression: Option<String>,
+
+    // relationship
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub to: Vec<String>,
+    #[serde(default)]
+    pub relationship_type: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalIdentifier {
+    pub external_identifier_type: String,
+    pub identifier: String,
+}
+
+impl Element {
+    fn is(&self, r#type: &str) -> bool {
+        self.r#type == r#type
+    }
+
+    /// The purl, either from the dedicated field, or from the external identifiers
+    fn purl(&self) -> Option<&str> {
+        self.package_url.as_deref().or_else(|| {
+            self.external_identifier("packageUrl")
+                .map(|id| id.identifier.as_str())
+        })
+    }
+
+    fn cpe(&self) -> Option<&str> {
+        self.external_identifier("cpe23")
+            .or_else(|| self.external_identifier("cpe22"))
+            .map(|id| id.identifier.as_str())
+    }
+
+    fn external_identifier(&self, r#type: &str) -> Option<&ExternalIdentifier> {
+        self.external_identifier
+            .iter()
+            .find(|id| id.external_identifier_type == r#type)
+    }
+}
+
+impl Spdx3 {
+    fn document(&self) -> Option<&Element> {
+        self.graph.iter().find(|element| element.is("SpdxDocument"))
+    }
+
+    fn element(&self, id: &str) -> Option<&Element> {
+        self.graph.iter().find(|element| {
+            element.spdx_id.as_deref() == Some(id) || element.id.as_deref() == Some(id)
+        })
+    }
+
+    /// The document ID, which is the ID of the SPDX document element
+    pub fn document_id(&self) -> Option<String> {
+        self.document().and_then(|doc| doc.spdx_id.clone())
+    }
+
+    /// The creation info of the document, resolving references to shared ones
+    fn creation_info(&self) -> Option<Element> {
+        match self.document()?.creation_info.as_ref()? {
+            Value::String(id) => self.element(id).cloned(),
+            value => serde_json::from_value(value.clone()).ok(),
+        }
+    }
+
+    /// The root packages of the document.
+    ///
+    /// The root elements of the document are commonly an SBOM element, which again has root
+    /// elements. This gets followed until reaching packages.
+    fn roots(&self) -> Vec<&Element> {
+        let mut result = vec![];
+        let mut seen = BTreeSet::new();
+        let mut next: Vec<&str> = self
+            .document()
+            .map(|doc| doc.root_element.iter().map(String::as_str).collect())
+            .unwrap_or_default();
+
+        while let Some(id) = next.pop() {
+            if !seen.insert(id) {
+                continue;
+            }
+            let Some(element) = self.element(id) else {
+                continue;
+            };
+            if element.is("software_Package") {
+                result.push(element);
+            } else {
+                next.extend(element.root_element.iter().map(String::as_str));
+            }
+        }
+
+        result
+    }
+
+    /// Translate into a CycloneDX SBOM
+    pub fn into_cyclonedx(self) -> Result<CycloneDx, serde_json::Error> {
+        let relationships: Vec<(&str, &str, &str)> = self
+            .graph
+            .iter()
+            .filter(|element| element.is("Relationship"))
+            .filter_map(|rel| {
+                Some((
+                    rel.from.as_deref()?,
+                    rel.relationship_type.as_deref()?,
+                    &rel.to,
+                ))
+            })
+            .flat_map(|(from, r#type, to)| to.iter().map(move |to| (from, r#type, to.as_str())))
+            .collect();
+
+        // licenses, by the ID of the element they apply to
+        let mut licenses = HashMap::<&str, Vec<&str>>::new();
+        for (from, r#type, to) in &relationships {
+            if !matches!(*r#type, "hasConcludedLicense" | "hasDeclaredLicense") {
+                continue;
+            }
+            if let Some(expression) = self
+                .element(to)
+                .and_then(|license| license.license_expression.as_deref())
+            {
+                licenses.entry(*from).or_default().push(expression);
+            }
+        }
+
+        let component = |package: &Element| {
+            let id = package.spdx_id.as_deref().unwrap_or_default();
+            let mut component = json!({
+                "bom-ref": id,
+                "type": match package.primary_purpose.as_deref() {
+                    Some("container") => "container",
+                    Some("application") => "application",
+                    Some("framework") => "framework",
+                    Some("operatingSystem") => "operating-system",
+                    _ => "library",
+                },
+                "name": package.name.as_deref().unwrap_or(id),
+            });
+
+            if let Some(version) = &package.package_version {
+                component["version"] = json!(version);
+            }
+            if let Some(purl) = package.purl() {
+                component["purl"] = json!(purl);
+            }
+            if let Some(cpe) = package.cpe() {
+                component["cpe"] = json!(cpe);
+            }
+
+            match licenses.get(id).map(Vec::as_slice) {
+                None | Some([]) => {}
+                Some([license]) => component["licenses"] = json!([{ "expression": license }]),
+                Some(licenses) => {
+                    let expression = licenses
+                        .iter()
+                        .map(|license| format!("({license})"))
+                        .collect::<Vec<_>>()
+                        .join(" AND ");
+                    component["licenses"] = json!([{ "expression": expression }]);
+                }
+            }
+
+            component
+        };
+
+        let roots = self.roots();
+        let root = roots.first().copied();
+
+        let components: Vec<Value> = self
+            .graph
+            .iter()
+            .filter(|element| element.is("software_Package"))
+            .filter(|package| {
+                root.is_none_or(|root| root.spdx_id.is_none() || root.spdx_id != package.spdx_id)
+            })
+            .map(&component)
+            .collect();
+
+        // "A depends on B", and "A contains B" are both turned into "A depends on B"
+        let known: BTreeSet<&str> = self
+            .graph
+            .iter()
+            .filter(|element| element.is("software_Package"))
+            .filter_map(|package| package.spdx_id.as_deref())
+            .collect();
+        let mut dependencies = BTreeMap::<&str, Vec<&str>>::new();
+        for (from, r#type, to) in &relationships {
+            if matches!(*r#type, "dependsOn" | "contains")
+                && known.contains(from)
+                && known.contains(to)
+            {
+                dependencies.entry(*from).or_default().push(*to);
+            }
+        }
+        // additional roots are treated as dependencies of the first one
+        if let Some(root) = root.and_then(|root| root.spdx_id.as_deref()) {
+            dependencies.entry(root).or_default().extend(
+                roots
+                    .iter()
+                    .skip(1)
+                    .filter_map(|root| root.spdx_id.as_deref()),
+            );
+        }
+        let dependencies: Vec<Value> = dependencies
+            .into_iter()
+            .map(|(r#ref, depends_on)| json!({ "ref": r#ref, "dependsOn": depends_on }))
+            .collect();
+
+        let mut metadata = json!({});
+        if let Some(root) = root {
+            metadata["component"] = component(root);
+        } else if let Some(name) = self.document().and_then(|doc| doc.name.as_deref()) {
+            metadata["component"] = json!({
+                "type": "application",
+                "name": name,
+            });
+        }
+
+        if let Some(info) = self.creation_info() {
+            if let Some(created) = &info.created {
+                metadata["timestamp"] = json!(created);
+            }
+            let authors: Vec<Value> = info
+                .created_by
+                .iter()
+                .filter_map(|agent| self.element(agent)?.name.as_deref())
+                .map(|name| json!({ "name": name }))
+                .collect();
+            if !authors.is_empty() {
+                metadata["authors"] = json!(authors);
+            }
+        }
+
+        serde_json::from_value(json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": "1.6",
+            "version": 1,
+            "metadata": metadata,
+            "components": components,
+            "dependencies": dependencies,
+        }))
+    }
+}
+
+pub struct Spdx3Loader<'g> {
+    graph: &'g Graph,
+}
+
+impl<'g> Spdx3Loader<'g> {
+    pub fn new(graph: &'g Graph) -> Self {
+        Self { graph }
+    }
+
+    #[instrument(skip_all, err(level=tracing::Level::INFO))]
+    pub async fn load(
+        &self,
+        labels: Labels,
+        buffer: &[u8],
+        digests: &Digests,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<IngestResult, Error> {
+        let spdx: Spdx3 = serde_json::from_slice(buffer)
+            .map_err(|err| Error::UnsupportedFormat(format!("Failed to parse: {err}")))?;
+
+        let document_id = spdx.document_id();
+
+        log::info!("Storing - document: {document_id:?}");
+
+        let labels = Labels::new().add("type", "spdx3").extend(labels.0);
+
+        let cdx = Box::new(
+            spdx.into_cyclonedx()
+                .map_err(|err| Error::InvalidContent(err.into()))?,
+        );
+
+        CyclonedxLoader::new(self.graph)
+            .load_document(labels, cdx, document_id, digests, tx)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::service::{Cache, Format, IngestorService};
+    use test_context::test_context;
+    use test_log::test;
+    use trustify_test_context::{TrustifyContext, document_bytes};
+
+    #[test(tokio::test)]
+    async fn translate() -> Result<(), anyhow::Error> {
+        let data = document_bytes("spdx3/alpine.spdx3.json").await?;
+        let spdx: Spdx3 = serde_json::from_slice(&data)?;
+
+        assert_eq!(
+            spdx.document_id().as_deref(),
+            Some("https://example.com/trustify/spdx3/alpine-3.19")
+        );
+
+        let cdx = spdx.into_cyclonedx()?;
+
+        let root = cdx
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.component.as_ref())
+            .expect("must have a root component");
+        assert_eq!(root.name, "alpine");
+
+        let components = cdx.components.unwrap_or_default();
+        assert_eq!(components.len(), 3);
+        assert!(components.iter().any(|c| {
+            c.purl.as_deref() == Some("pkg:apk/alpine/musl@1.2.4_git20230717-r4?arch=x86_64")
+        }));
+        assert!(components.iter().any(|c| {
+            c.cpe.as_deref() == Some("cpe:2.3:a:busybox:busybox:1.36.1:*:*:*:*:*:*:*")
+        }));
+
+        let dependencies = cdx.dependencies.unwrap_or_default();
+        let root = dependencies
+            .iter()
+            .find(|dep| dep.ref_ == "https://example.com/trustify/package/alpine")
+            .expect("root must have dependencies");
+        assert_eq!(root.depends_on.as_ref().map(Vec::len), Some(3));
+
+        Ok(())
+    }
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn ingest_spdx3(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let graph = Graph::new();
+        let data = document_bytes("spdx3/alpine.spdx3.json").await?;
+
+        assert!(matches!(Format::from_bytes(&data), Ok(Format::SPDX3)));
+
+        let ingestor = IngestorService::new(graph, ctx.storage.clone(), Default::default());
+
+        let result = ctx
+            .db
+            .transaction(async |tx| {
+                ingestor
+                    .ingest(
+                        data.clone(),
+                        Format::SBOM,
+                        ("source", "test"),
+                        None,
+                        Cache::Skip,
+                        tx,
+                    )
+                    .await
+            })
+            .await?;
+
+        assert_eq!(
+            result.document_id.as_deref(),
+            Some("https://example.com/trustify/spdx3/alpine-3.19")
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,348 @@
+//! Ingestion of Syft's native JSON format (`syft -o syft-json`).
+//!
+//! The document gets translated into a CycloneDX SBOM, which is then processed the same way any
+//! other CycloneDX SBOM is.
+
+use crate::{
+    graph::Graph,
+    model::IngestResult,
+    service::{Error, sbom::cyclonedx::CyclonedxLoader},
+};
+use sea_orm::{ConnectionTrait, TransactionTrait};
+use serde::Deserialize;
+use serde_cyclonedx::cyclonedx::v_1_6::CycloneDx;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use tracing::instrument;
+use trustify_common::hashing::Digests;
+use trustify_entity::labels::Labels;
+
+/// The subset of the Syft JSON model we are interested in
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Syft {
+    #[serde(default)]
+    pub artifacts: Vec<Artifact>,
+    #[serde(default)]
+    pub artifact_relationships: Vec<ArtifactRelationship>,
+    pub source: Option<Source>,
+    pub descriptor: Descriptor,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Artifact {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub purl: Option<String>,
+    #[serde(default)]
+    pub cpes: Vec<SyftCpe>,
+    #[serde(default)]
+    pub licenses: Vec<SyftLicense>,
+}
+
+/// Older schema versions use plain strings, newer ones an object
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum SyftCpe {
+    Plain(String),
+    Object { cpe: String },
+}
+
+impl SyftCpe {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Plain(cpe) | Self::Object { cpe } => cpe,
+        }
+    }
+}
+
+/// Older schema versions use plain strings, newer ones an object
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum SyftLicense {
+    Plain(String),
+    #[serde(rename_all = "camelCase")]
+    Object {
+        value: String,
+        #[serde(default)]
+        spdx_expression: Option<String>,
+    },
+}
+
+impl SyftLicense {
+    /// Prefer the SPDX expression, fall back to the raw value
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Plain(value) => value,
+            Self::Object {
+                spdx_expression: Some(expression),
+                ..
+            } if !expression.is_empty() => expression,
+            Self::Object { value, .. } => value,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ArtifactRelationship {
+    pub parent: String,
+    pub child: String,
+    #[serde(rename = "type")]
+    pub r#type: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Source {
+    pub id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(rename = "type")]
+    pub r#type: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Descriptor {
+    pub name: String,
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+impl Syft {
+    /// The document ID, derived from the ID of the scanned source
+    pub fn document_id(&self) -> Option<String> {
+        self.source.as_ref().map(|source| match &source.name {
+            Some(name) => format!("{name}/{}", source.id),
+            None => source.id.clone(),
+        })
+    }
+
+    /// Translate into a CycloneDX SBOM
+    pub fn into_cyclonedx(self) -> Result<CycloneDx, serde_json::Error> {
+        let components: Vec<Value> = self
+            .artifacts
+            .iter()
+            .map(|artifact| {
+                let mut component = json!({
+                    "bom-ref": artifact.id,
+                    "type": "library",
+                    "name": artifact.name,
+                });
+
+                if let Some(version) = &artifact.version {
+                    component["version"] = json!(version);
+                }
+                if let Some(purl) = &artifact.purl {
+                    component["purl"] = json!(purl);
+                }
+                if let Some(cpe) = artifact.cpes.first() {
+                    component["cpe"] = json!(cpe.as_str());
+                }
+
+                let licenses: Vec<_> = artifact
+                    .licenses
+                    .iter()
+                    .map(SyftLicense::as_str)
+                    .filter(|license| !license.is_empty())
+                    .collect();
+                match licenses.as_slice() {
+                    [] => {}
+                    [license] => component["licenses"] = json!([{ "expression": license }]),
+                    licenses => {
+                        let expression = licenses
+                            .iter()
+                            .map(|license| format!("({license})"))
+                            .collect::<Vec<_>>()
+                            .join(" AND ");
+                        component["licenses"] = json!([{ "expression": expression }]);
+                    }
+                }
+
+                component
+            })
+            .collect();
+
+        // Syft records "A is a dependency of B", and "A contains B". Both are turned into
+        // "B depends on A", respectively "A depends on B".
+        let mut dependencies = BTreeMap::<&str, Vec<&str>>::new();
+        for rel in &self.artifact_relationships {
+            match rel.r#type.as_str() {
+                "dependency-of" => dependencies
+                    .entry(&rel.child)
+                    .or_default()
+                    .push(&rel.parent),
+                "contains" => dependencies
+                    .entry(&rel.parent)
+                    .or_default()
+                    .push(&rel.child),
+                _ => {}
+            }
+        }
+
+        // the scanned source depends on all artifacts which are not a dependency of another one
+        if let Some(source) = &self.source {
+            let roots = self
+                .artifacts
+                .iter()
+                .map(|artifact| artifact.id.as_str())
+                .filter(|id| {
+                    !self
+                        .artifact_relationships
+                        .iter()
+                        .any(|rel| rel.r#type == "dependency-of" && rel.parent == *id)
+                });
+            dependencies
+                .entry(source.id.as_str())
+                .or_default()
+                .extend(roots);
+        }
+
+        // drop references to things we didn't translate, like files
+        let known: Vec<&str> = self
+            .artifacts
+            .iter()
+            .map(|artifact| artifact.id.as_str())
+            .chain(self.source.iter().map(|source| source.id.as_str()))
+            .collect();
+        let dependencies: Vec<Value> = dependencies
+            .into_iter()
+            .filter(|(r#ref, _)| known.contains(r#ref))
+            .map(|(r#ref, mut depends_on)| {
+                depends_on.retain(|target| known.contains(target));
+                json!({ "ref": r#ref, "dependsOn": depends_on })
+            })
+            .collect();
+
+        let mut metadata = json!({});
+        if let Some(source) = &self.source {
+            let mut component = json!({
+                "bom-ref": source.id,
+                "type": match source.r#type.as_str() {
+                    "image" => "container",
+                    _ => "application",
+                },
+                "name": source.name.as_deref().unwrap_or(&source.id),
+            });
+            if let Some(version) = &source.version {
+                component["version"] = json!(version);
+            }
+            metadata["component"] = component;
+        }
+
+        serde_json::from_value(json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": "1.6",
+            "version": 1,
+            "metadata": metadata,
+            "components": components,
+            "dependencies": dependencies,
+        }))
+    }
+}
+
+pub struct SyftLoader<'g> {
+    graph: &'g Graph,
+}
+
+impl<'g> SyftLoader<'g> {
+    pub fn new(graph: &'g Graph) -> Self {
+        Self { graph }
+    }
+
+    #[instrument(skip_all, err(level=tracing::Level::INFO))]
+    pub async fn load(
+        &self,
+        labels: Labels,
+        buffer: &[u8],
+        digests: &Digests,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<IngestResult, Error> {
+        let syft: Syft = serde_json::from_slice(buffer)
+            .map_err(|err| Error::UnsupportedFormat(format!("Failed to parse: {err}")))?;
+
+        log::info!(
+            "Storing - descriptor: {} {:?}, source: {:?}",
+            syft.descriptor.name,
+            syft.descriptor.version,
+            syft.source.as_ref().map(|source| &source.id),
+        );
+
+        let labels = Labels::new().add("type", "syft").extend(labels.0);
+        let document_id = syft.document_id();
+
+        let cdx = Box::new(
+            syft.into_cyclonedx()
+                .map_err(|err| Error::InvalidContent(err.into()))?,
+        );
+
+        CyclonedxLoader::new(self.graph)
+            .load_document(labels, cdx, document_id, digests, tx)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::service::{Cache, Format, IngestorService};
+    use test_context::test_context;
+    use test_log::test;
+    use trustify_test_context::{TrustifyContext, document_bytes};
+
+    #[test(tokio::test)]
+    async fn translate() -> Result<(), anyhow::Error> {
+        let data = document_bytes("syft/alpine.syft.json").await?;
+        let syft: Syft = serde_json::from_slice(&data)?;
+
+        assert_eq!(
+            syft.document_id().as_deref(),
+            Some("alpine/d8b6b1f0c8c8b2c7a1c1f1d9f3e1e0e1c1e1a1b1c1d1e1f1a1b1c1d1e1f1a1b1")
+        );
+
+        let cdx = syft.into_cyclonedx()?;
+        let components = cdx.components.unwrap_or_default();
+        assert_eq!(components.len(), 3);
+        assert!(
+            components
+                .iter()
+                .any(|c| c.purl.as_deref() == Some("pkg:apk/alpine/musl@1.2.4-r2?arch=x86_64"))
+        );
+
+        Ok(())
+    }
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn ingest_syft(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let graph = Graph::new();
+        let data = document_bytes("syft/alpine.syft.json").await?;
+
+        assert!(matches!(Format::from_bytes(&data), Ok(Format::Syft)));
+
+        let ingestor = IngestorService::new(graph, ctx.storage.clone(), Default::default());
+
+        let result = ctx
+            .db
+            .transaction(async |tx| {
+                ingestor
+                    .ingest(
+                        data.clone(),
+                        Format::SBOM,
+                        ("source", "test"),
+                        None,
+                        Cache::Skip,
+                        tx,
+                    )
+                    .await
+            })
+            .await?;
+
+        assert!(result.document_id.is_some());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,439 @@
+//! Ingestion of Trivy JSON scan reports (`trivy image -f json`).
+//!
+//! The detected packages are translated into a CycloneDX SBOM, including the detected
+//! vulnerabilities as CycloneDX vulnerabilities affecting those packages. Those get recorded as
+//! VEX statements of the SBOM.
+
+use crate::{
+    graph::Graph,
+    model::IngestResult,
+    service::{Error, sbom::cyclonedx::CyclonedxLoader},
+};
+use sea_orm::{ConnectionTrait, TransactionTrait};
+use serde::Deserialize;
+use serde_cyclonedx::cyclonedx::v_1_6::CycloneDx;
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::instrument;
+use trustify_common::hashing::Digests;
+use trustify_entity::labels::Labels;
+
+/// The subset of the Trivy JSON report we are interested in
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Report {
+    pub schema_version: u32,
+    #[serde(default)]
+    pub created_at: Option<String>,
+    pub artifact_name: String,
+    #[serde(default)]
+    pub artifact_type: Option<String>,
+    #[serde(default)]
+    pub metadata: Option<Metadata>,
+    #[serde(default)]
+    pub results: Vec<ScanResult>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Metadata {
+    #[serde(default, rename = "ImageID")]
+    pub image_id: Option<String>,
+    #[serde(default)]
+    pub repo_digests: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ScanResult {
+    pub target: String,
+    #[serde(default)]
+    pub packages: Vec<Package>,
+    #[serde(default)]
+    pub vulnerabilities: Vec<DetectedVulnerability>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Package {
+    #[serde(default, rename = "ID")]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub identifier: Option<Identifier>,
+    #[serde(default)]
+    pub licenses: Vec<String>,
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Identifier {
+    #[serde(default, rename = "PURL")]
+    pub purl: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DetectedVulnerability {
+    #[serde(rename = "VulnerabilityID")]
+    pub vulnerability_id: String,
+    #[serde(default, rename = "PkgID")]
+    pub pkg_id: Option<String>,
+    pub pkg_name: String,
+    #[serde(default)]
+    pub pkg_identifier: Option<Identifier>,
+    #[serde(default)]
+    pub installed_version: Option<String>,
+    #[serde(default)]
+    pub fixed_version: Option<String>,
+    #[serde(default)]
+    pub severity: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl Report {
+    /// The document ID, derived from the scanned artifact
+    pub fn document_id(&self) -> String {
+        let digest = self
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.repo_digests.first().or(metadata.image_id.as_ref()));
+
+        match digest {
+            Some(digest) => format!("{}/{digest}", self.artifact_name),
+            None => self.artifact_name.clone(),
+        }
+    }
+
+    /// All vulnerability IDs found in the report
+    pub fn vulnerability_ids(&self) -> BTreeSet<&str> {
+        self.results
+            .iter()
+            .flat_map(|result| &result.vulnerabilities)
+            .map(|vuln| vuln.vulnerability_id.as_str())
+            .collect()
+    }
+
+    /// Translate into a CycloneDX SBOM
+    pub fn into_cyclonedx(self) -> Result<CycloneDx, serde_json::Error> {
+        let root = self.artifact_name.clone();
+
+        // components by bom-ref, and trivy's package IDs to bom-refs
+        let mut components = BTreeMap::<String, Value>::new();
+        let mut ids = BTreeMap::<&str, String>::new();
+        let mut dependencies = BTreeMap::<String, BTreeSet<String>>::new();
+
+        for result in &self.results {
+            for package in &result.packages {
+                let bom_ref = bom_ref(
+                    package.identifier.as_ref(),
+                    &package.name,
+                    package.version.as_deref(),
+                );
+                if let Some(id) = &package.id {
+                    ids.insert(id, bom_ref.clone());
+                }
+                components.entry(bom_ref.clone()).or_insert_with(|| {
+                    component(
+                        &bom_ref,
+                        &package.name,
+                        package.version.as_deref(),
+                        package.identifier.as_ref(),
+                        &package.licenses,
+                    )
+                });
+            }
+
+            // packages are only reported when running with `--list-all-pkgs`, so also add the
+            // ones we only know from vulnerabilities
+            for vuln in &result.vulnerabilities {
+                let bom_ref = bom_ref(
+                    vuln.pkg_identifier.as_ref(),
+                    &vuln.pkg_name,
+                    vuln.installed_version.as_deref(),
+                );
+                components.entry(bom_ref.clone()).or_insert_with(|| {
+                    component(
+                        &bom_ref,
+                        &vuln.pkg_name,
+                        vuln.installed_version.as_deref(),
+                        vuln.pkg_identifier.as_ref(),
+                        &[],
+                    )
+                });
+            }
+        }
+
+        for package in self.results.iter().flat_map(|result| &result.packages) {
+            let Some(source) = package.id.as_deref().and_then(|id| ids.get(id)) else {
+                continue;
+            };
+            for target in package
+                .depends_on
+                .iter()
+                .filter_map(|id| ids.get(id.as_str()))
+            {
+                dependencies
+                    .entry(source.clone())
+                    .or_default()
+                    .insert(target.clone());
+            }
+        }
+
+        // the scanned artifact depends on all components which are not a dependency of another one
+        let transitive: BTreeSet<String> = dependencies.values().flatten().cloned().collect();
+        dependencies.insert(
+            root.clone(),
+            components
+                .keys()
+                .filter(|bom_ref| !transitive.contains(*bom_ref))
+                .cloned()
+                .collect(),
+        );
+
+        // collect the findings per vulnerability
+        let mut findings = BTreeMap::<&str, (&DetectedVulnerability, BTreeSet<String>)>::new();
+        for vuln in self
+            .results
+            .iter()
+            .flat_map(|result| &result.vulnerabilities)
+        {
+            let bom_ref = bom_ref(
+                vuln.pkg_identifier.as_ref(),
+                &vuln.pkg_name,
+                vuln.installed_version.as_deref(),
+            );
+            findings
+                .entry(&vuln.vulnerability_id)
+                .or_insert_with(|| (vuln, Default::default()))
+                .1
+                .insert(bom_ref);
+        }
+
+        let vulnerabilities: Vec<Value> = findings
+            .into_iter()
+            .map(|(id, (vuln, affects))| {
+                let mut result = json!({
+                    "id": id,
+                    "source": { "name": "trivy" },
+                    "affects": affects
+                        .into_iter()
+                        .map(|r#ref| json!({ "ref": r#ref }))
+                        .collect::<Vec<_>>(),
+                });
+                if let Some(description) = &vuln.description {
+                    result["description"] = json!(description);
+                }
+                if let Some(severity) = vuln.severity.as_deref().and_then(severity) {
+                    result["ratings"] = json!([{ "severity": severity }]);
+                }
+                if let Some(fixed) = &vuln.fixed_version {
+                    result["recommendation"] = json!(format!("Upgrade to {fixed}"));
+                }
+                result
+            })
+            .collect();
+
+        let mut metadata = json!({
+            "component": {
+                "bom-ref": root,
+                "type": match self.artifact_type.as_deref() {
+                    Some("container_image") => "container",
+                    _ => "application",
+                },
+                "name": self.artifact_name,
+            }
+        });
+        if let Some(created) = &self.created_at {
+            metadata["timestamp"] = json!(created);
+        }
+
+        serde_json::from_value(json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": "1.6",
+            "version": 1,
+            "metadata": metadata,
+            "components": components.into_values().collect::<Vec<_>>(),
+            "dependencies": dependencies
+                .into_iter()
+                .map(|(r#ref, depends_on)| json!({ "ref": r#ref, "dependsOn": depends_on }))
+                .collect::<Vec<_>>(),
+            "vulnerabilities": vulnerabilities,
+        }))
+    }
+}
+
+fn bom_ref(identifier: Option<&Identifier>, name: &str, version: Option<&str>) -> String {
+    match identifier.and_then(|identifier| identifier.purl.as_ref()) {
+        Some(purl) => purl.clone(),
+        None => match version {
+            Some(version) => format!("{name}@{version}"),
+            None => name.to_string(),
+        },
+    }
+}
+
+fn component(
+    bom_ref: &str,
+    name: &str,
+    version: Option<&str>,
+    identifier: Option<&Identifier>,
+    licenses: &[String],
+) -> Value {
+    let mut component = json!({
+        "bom-ref": bom_ref,
+        "type": "library",
+        "name": name,
+    });
+    if let Some(version) = version {
+        component["version"] = json!(version);
+    }
+    if let Some(purl) = identifier.and_then(|identifier| identifier.purl.as_ref()) {
+        component["purl"] = json!(purl);
+    }
+    match licenses {
+        [] => {}
+        [license] => component["licenses"] = json!([{ "expression": license }]),
+        licenses => {
+            let expression = licenses
+                .iter()
+                .map(|license| format!("({license})"))
+                .collect::<Vec<_>>()
+                .join(" AND ");
+            component["licenses"] = json!([{ "expression": expression }]);
+        }
+    }
+    component
+}
+
+/// Map Trivy's severity to the CycloneDX severity
+fn severity(severity: &str) -> Option<&'static str> {
+    Some(match severity.to_ascii_uppercase().as_str() {
+        "CRITICAL" => "critical",
+        "HIGH" => "high",
+        "MEDIUM" => "medium",
+        "LOW" => "low",
+        "UNKNOWN" => "unknown",
+        _ => return None,
+    })
+}
+
+pub struct TrivyLoader<'g> {
+    graph: &'g Graph,
+}
+
+impl<'g> TrivyLoader<'g> {
+    pub fn new(graph: &'g Graph) -> Self {
+        Self { graph }
+    }
+
+    #[instrument(skip_all, err(level=tracing::Level::INFO))]
+    pub async fn load(
+        &self,
+        labels: Labels,
+        buffer: &[u8],
+        digests: &Digests,
+        tx: &(impl ConnectionTrait + TransactionTrait),
+    ) -> Result<IngestResult, Error> {
+        let report: Report = serde_json::from_slice(buffer)
+            .map_err(|err| Error::UnsupportedFormat(format!("Failed to parse: {err}")))?;
+
+        log::info!(
+            "Storing - schema: {}, artifact: {}",
+            report.schema_version,
+            report.artifact_name
+        );
+
+        let labels = Labels::new().add("type", "trivy").extend(labels.0);
+        let document_id = report.document_id();
+
+        let cdx = Box::new(
+            report
+                .into_cyclonedx()
+                .map_err(|err| Error::InvalidContent(err.into()))?,
+        );
+
+        CyclonedxLoader::new(self.graph)
+            .load_document(labels, cdx, Some(document_id), digests, tx)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::service::{Cache, Format, IngestorService};
+    use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+    use test_context::test_context;
+    use test_log::test;
+    use trustify_entity::{sbom_vex, vulnerability};
+    use trustify_test_context::{TrustifyContext, document_bytes};
+
+    #[test(tokio::test)]
+    async fn translate() -> Result<(), anyhow::Error> {
+        let data = document_bytes("trivy/alpine-3.19.json").await?;
+        let report: Report = serde_json::from_slice(&data)?;
+
+        assert_eq!(
+            report.document_id(),
+            "alpine:3.19/alpine@sha256:6457d53fb065d6f250e1504b9bc42d5b6c65941d57532c072d929dd0628977d0"
+        );
+        assert_eq!(
+            report.vulnerability_ids().into_iter().collect::<Vec<_>>(),
+            vec!["CVE-2023-42363", "CVE-2024-0727"]
+        );
+
+        let cdx = report.into_cyclonedx()?;
+        assert_eq!(cdx.components.unwrap_or_default().len(), 3);
+        assert_eq!(cdx.vulnerabilities.unwrap_or_default().len(), 2);
+
+        Ok(())
+    }
+
+    #[test_context(TrustifyContext)]
+    #[test(tokio::test)]
+    async fn ingest_trivy(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
+        let graph = Graph::new();
+        let data = document_bytes("trivy/alpine-3.19.json").await?;
+
+        assert!(matches!(Format::from_bytes(&data), Ok(Format::Trivy)));
+
+        let ingestor = IngestorService::new(graph, ctx.storage.clone(), Default::default());
+
+        ctx.db
+            .transaction(async |tx| {
+                ingestor
+                    .ingest(
+                        data.clone(),
+                        Format::SBOM,
+                        ("source", "test"),
+                        None,
+                        Cache::Skip,
+                        tx,
+                    )
+                    .await
+            })
+            .await?;
+
+        assert!(
+            vulnerability::Entity::find_by_id("CVE-2024-0727")
+                .one(&ctx.db)
+                .await?
+                .is_some()
+        );
+
+        // the findings are recorded as VEX statements, one for each affected package
+        let statements = sbom_vex::Entity::find()
+            .filter(sbom_vex::Column::VulnerabilityId.eq("CVE-2024-0727"))
+            .all(&ctx.db)
+            .await?;
+        assert_eq!(statements.len(), 2);
+        assert!(statements.iter().all(|statement| statement.state.is_none()));
+
+        Ok(())
+    }
+}
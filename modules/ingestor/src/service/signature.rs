@@ -0,0 +1,785 @@
+//! Verification of Sigstore signatures of ingested documents
+//!
+//! Signatures are only accepted if the signing certificate chains up to a configured
+//! [`TrustRoot`]. Sigstore bundles must also carry an entry of a trusted transparency log (like
+//! Rekor), which proves that the signature was logged while the short-lived signing certificate
+//! was valid.
+
+use crate::service::Error;
+use actix_web::http::header::HeaderMap;
+use base64::{Engine, prelude::BASE64_STANDARD};
+use hex::ToHex;
+use openssl::{
+    hash::{MessageDigest, hash},
+    pkey::{PKey, Public},
+    sign::Verifier,
+    stack::Stack,
+    x509::{X509, X509StoreContext, store::X509StoreBuilder, verify::X509VerifyParam},
+};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use trustify_common::hashing::Digests;
+use trustify_entity::labels::Labels;
+
+/// The label carrying the identity of the signer of a document.
+pub const LABEL_SIGNER: &str = "sigstore/signer";
+
+/// The request header carrying a Sigstore bundle, base64 encoded.
+pub const HEADER_BUNDLE: &str = "x-sigstore-bundle";
+/// The request header carrying a detached signature, base64 encoded.
+pub const HEADER_SIGNATURE: &str = "x-signature";
+/// The request header carrying the certificate of a detached signature, base64 encoded.
+pub const HEADER_CERTIFICATE: &str = "x-signing-certificate";
+
+/// Signature material to verify a document against.
+#[derive(Clone, Debug)]
+pub enum Signature {
+    /// A Sigstore bundle (JSON) carrying a message signature.
+    Bundle(Vec<u8>),
+    /// A detached signature, along with the signing certificate.
+    Detached {
+        /// The signature, either raw or base64 encoded.
+        signature: Vec<u8>,
+        /// The certificate, PEM or DER encoded.
+        certificate: Vec<u8>,
+    },
+}
+
+/// The identity of the signer, as stated by the signing certificate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignerIdentity {
+    /// The subject alternative name (e-mail or URI) of the certificate.
+    pub subject: String,
+}
+
+impl SignerIdentity {
+    /// Record the identity as labels of the document.
+    pub fn apply(&self, labels: Labels) -> Labels {
+        labels.add(LABEL_SIGNER, &self.subject)
+    }
+}
+
+/// The certificate authorities and transparency logs signatures are verified against.
+///
+/// An empty trust root rejects all signatures.
+#[derive(Clone, Debug, Default)]
+pub struct TrustRoot {
+    /// Certificates of the authorities issuing signing certificates (like Fulcio).
+    certificates: Vec<X509>,
+    /// Public keys of the transparency logs (like Rekor).
+    log_keys: Vec<PKey<Public>>,
+}
+
+impl TrustRoot {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the certificates of one or more authorities, PEM encoded.
+    pub fn add_certificates(mut self, pem: &[u8]) -> Result<Self, anyhow::Error> {
+        self.certificates.extend(X509::stack_from_pem(pem)?);
+        Ok(self)
+    }
+
+    /// Add the public key of a transparency log, PEM encoded.
+    pub fn add_log_key(mut self, pem: &[u8]) -> Result<Self, anyhow::Error> {
+        self.log_keys.push(PKey::public_key_from_pem(pem)?);
+        Ok(self)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.certificates.is_empty()
+    }
+
+    /// Verify that the certificate chains up to one of the authorities, at the provided time.
+    fn verify_certificate(
+        &self,
+        certificate: &X509,
+        intermediates: Vec<X509>,
+        time: i64,
+    ) -> Result<(), Error> {
+        if self.is_empty() {
+            return Err(invalid(
+                "no trust root is configured for verifying signatures",
+            ));
+        }
+
+        let chain = || -> Result<_, openssl::error::ErrorStack> {
+            let mut store = X509StoreBuilder::new()?;
+            for certificate in &self.certificates {
+                store.add_cert(certificate.clone())?;
+            }
+            let mut param = X509VerifyParam::new()?;
+            param.set_time(time as _);
+            store.set_param(&param)?;
+            let store = store.build();
+
+            let mut untrusted = Stack::new()?;
+            for certificate in intermediates {
+                untrusted.push(certificate)?;
+            }
+
+            X509StoreContext::new()?.init(&store, certificate, &untrusted, |context| {
+                Ok(context.verify_cert()?.then_some(()).ok_or(context.error()))
+            })
+        };
+
+        chain()
+            .map_err(|err| invalid(format!("failed to verify certificate: {err}")))?
+            .map_err(|err| invalid(format!("untrusted certificate: {err}")))
+    }
+
+    /// Find the key of the transparency log with the provided ID.
+    ///
+    /// The ID of a log is the SHA-256 digest of its public key (DER encoded).
+    fn log_key(&self, id: &[u8]) -> Option<&PKey<Public>> {
+        self.log_keys.iter().find(|key| {
+            key.public_key_to_der()
+                .and_then(|der| hash(MessageDigest::sha256(), &der))
+                .is_ok_and(|digest| *digest == *id)
+        })
+    }
+}
+
+/// Trust roots are equal if they contain the same certificates and keys, in the same order.
+impl PartialEq for TrustRoot {
+    fn eq(&self, other: &Self) -> bool {
+        fn der(certificates: &[X509], keys: &[PKey<Public>]) -> Option<Vec<Vec<u8>>> {
+            certificates
+                .iter()
+                .map(|certificate| certificate.to_der().ok())
+                .chain(keys.iter().map(|key| key.public_key_to_der().ok()))
+                .collect()
+        }
+
+        self.certificates.len() == other.certificates.len()
+            && der(&self.certificates, &self.log_keys) == der(&other.certificates, &other.log_keys)
+    }
+}
+
+impl Eq for TrustRoot {}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Bundle {
+    verification_material: VerificationMaterial,
+    message_signature: Option<MessageSignature>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VerificationMaterial {
+    /// The signing certificate (v0.3)
+    certificate: Option<RawBytes>,
+    /// The certificate chain, starting with the signing certificate (v0.1, v0.2)
+    x509_certificate_chain: Option<CertificateChain>,
+    /// The entries of the transparency log
+    #[serde(default)]
+    tlog_entries: Vec<TlogEntry>,
+}
+
+#[derive(Deserialize)]
+struct CertificateChain {
+    certificates: Vec<RawBytes>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawBytes {
+    raw_bytes: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MessageSignature {
+    message_digest: Option<MessageDigestInfo>,
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct MessageDigestInfo {
+    algorithm: String,
+    digest: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TlogEntry {
+    /// Integers are encoded as strings by the protobuf JSON mapping
+    log_index: serde_json::Value,
+    log_id: LogId,
+    integrated_time: serde_json::Value,
+    inclusion_promise: Option<InclusionPromise>,
+    canonicalized_body: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LogId {
+    key_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InclusionPromise {
+    signed_entry_timestamp: String,
+}
+
+/// The payload of a signed entry timestamp, serialized as canonical JSON.
+///
+/// The fields must be kept in lexicographical order.
+#[derive(Serialize)]
+struct SignedEntry<'a> {
+    body: &'a str,
+    #[serde(rename = "integratedTime")]
+    integrated_time: i64,
+    #[serde(rename = "logID")]
+    log_id: String,
+    #[serde(rename = "logIndex")]
+    log_index: i64,
+}
+
+/// The body of a `hashedrekord` log entry.
+#[derive(Deserialize)]
+struct HashedRekord {
+    kind: String,
+    spec: HashedRekordSpec,
+}
+
+#[derive(Deserialize)]
+struct HashedRekordSpec {
+    data: HashedRekordData,
+    signature: HashedRekordSignature,
+}
+
+#[derive(Deserialize)]
+struct HashedRekordData {
+    hash: HashedRekordHash,
+}
+
+#[derive(Deserialize)]
+struct HashedRekordHash {
+    algorithm: String,
+    value: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HashedRekordSignature {
+    content: String,
+    public_key: HashedRekordPublicKey,
+}
+
+#[derive(Deserialize)]
+struct HashedRekordPublicKey {
+    content: String,
+}
+
+fn invalid(message: impl Into<String>) -> Error {
+    Error::Signature(message.into())
+}
+
+impl Signature {
+    /// Get the signature material from the headers of a request, if there is any.
+    pub fn from_headers(headers: &HeaderMap) -> Result<Option<Self>, Error> {
+        let header = |name| {
+            headers
+                .get(name)
+                .map(|value| {
+                    value
+                        .to_str()
+                        .map_err(|err| invalid(format!("invalid header '{name}': {err}")))
+                })
+                .transpose()
+        };
+
+        if let Some(bundle) = header(HEADER_BUNDLE)? {
+            return Ok(Some(Self::Bundle(decode(bundle)?)));
+        }
+
+        match (header(HEADER_SIGNATURE)?, header(HEADER_CERTIFICATE)?) {
+            (Some(signature), Some(certificate)) => Ok(Some(Self::Detached {
+                signature: signature.as_bytes().to_vec(),
+                certificate: decode(certificate)?,
+            })),
+            (None, None) => Ok(None),
+            _ => Err(invalid(format!(
+                "a detached signature requires both headers, '{HEADER_SIGNATURE}' and '{HEADER_CERTIFICATE}'"
+            ))),
+        }
+    }
+
+    /// Verify the signature of the document, returning the identity of the signer.
+    ///
+    /// This checks that the signature was created over the document, using the key of the provided
+    /// certificate, and that the certificate was issued by one of the authorities of the trust
+    /// root.
+    ///
+    /// A bundle must carry an entry of one of the transparency logs of the trust root, covering
+    /// the signature. The certificate is verified at the time the entry was logged, so that
+    /// short-lived certificates (like those issued by Fulcio) are accepted after they expired.
+    ///
+    /// A detached signature can't be checked against a transparency log. Its certificate is
+    /// verified at the current time, which is only suitable for long-lived certificates.
+    pub fn verify(
+        &self,
+        data: &[u8],
+        digests: &Digests,
+        root: &TrustRoot,
+    ) -> Result<SignerIdentity, Error> {
+        match self {
+            Self::Bundle(bundle) => {
+                let bundle: Bundle = serde_json::from_slice(bundle)?;
+                let message = bundle
+                    .message_signature
+                    .ok_or_else(|| invalid("bundle has no message signature"))?;
+
+                if let Some(digest) = message.message_digest {
+                    if digest.algorithm != "SHA2_256" {
+                        return Err(invalid(format!(
+                            "unsupported digest algorithm: {}",
+                            digest.algorithm
+                        )));
+                    }
+                    if decode(&digest.digest)? != digests.sha256.as_ref() {
+                        return Err(invalid("digest of bundle doesn't match the document"));
+                    }
+                }
+
+                let material = bundle.verification_material;
+                let mut chain = match material.certificate {
+                    Some(certificate) => vec![certificate],
+                    None => material
+                        .x509_certificate_chain
+                        .map(|chain| chain.certificates)
+                        .unwrap_or_default(),
+                }
+                .into_iter()
+                .map(|certificate| {
+                    X509::from_der(&decode(&certificate.raw_bytes)?)
+                        .map_err(|err| invalid(format!("invalid certificate: {err}")))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+                if chain.is_empty() {
+                    return Err(invalid("bundle has no certificate"));
+                }
+                let certificate = chain.remove(0);
+
+                let signature = decode(&message.signature)?;
+                verify_signature(&certificate, &signature, data)?;
+
+                let integrated_time = material
+                    .tlog_entries
+                    .iter()
+                    .find_map(|entry| {
+                        verify_log_entry(entry, &certificate, &signature, digests, root).ok()
+                    })
+                    .ok_or_else(|| {
+                        invalid("bundle has no valid entry of a trusted transparency log")
+                    })?;
+
+                root.verify_certificate(&certificate, chain, integrated_time)?;
+
+                identity(&certificate)
+            }
+            Self::Detached {
+                signature,
+                certificate,
+            } => {
+                let signature = match std::str::from_utf8(signature) {
+                    Ok(encoded) => decode(encoded.trim())?,
+                    Err(_) => signature.clone(),
+                };
+                let certificate = X509::from_pem(certificate)
+                    .or_else(|_| X509::from_der(certificate))
+                    .map_err(|err| invalid(format!("invalid certificate: {err}")))?;
+
+                verify_signature(&certificate, &signature, data)?;
+
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|now| now.as_secs() as i64)
+                    .unwrap_or_default();
+                root.verify_certificate(&certificate, vec![], now)?;
+
+                identity(&certificate)
+            }
+        }
+    }
+}
+
+/// Verify that the signature was created over the data, using the key of the certificate.
+fn verify_signature(certificate: &X509, signature: &[u8], data: &[u8]) -> Result<(), Error> {
+    let key = certificate
+        .public_key()
+        .map_err(|err| invalid(format!("invalid public key: {err}")))?;
+    let valid = Verifier::new(MessageDigest::sha256(), &key)
+        .and_then(|mut verifier| verifier.verify_oneshot(signature, data))
+        .map_err(|err| invalid(format!("failed to verify signature: {err}")))?;
+
+    match valid {
+        true => Ok(()),
+        false => Err(invalid("signature doesn't match the document")),
+    }
+}
+
+/// Verify that an entry of a transparency log covers the signature, returning the time it was
+/// logged.
+///
+/// The signed entry timestamp (the promise of the log to include the entry) must be signed by
+/// one of the logs of the trust root, and the logged entry must match the signature, the
+/// certificate, and the digest of the document.
+fn verify_log_entry(
+    entry: &TlogEntry,
+    certificate: &X509,
+    signature: &[u8],
+    digests: &Digests,
+    root: &TrustRoot,
+) -> Result<i64, Error> {
+    let int64 = |value: &serde_json::Value| {
+        value
+            .as_i64()
+            .or_else(|| value.as_str().and_then(|value| value.parse().ok()))
+            .ok_or_else(|| invalid("invalid integer in transparency log entry"))
+    };
+
+    let log_id = decode(&entry.log_id.key_id)?;
+    let key = root
+        .log_key(&log_id)
+        .ok_or_else(|| invalid("entry of an untrusted transparency log"))?;
+
+    let promise = entry
+        .inclusion_promise
+        .as_ref()
+        .ok_or_else(|| invalid("transparency log entry has no inclusion promise"))?;
+    let integrated_time = int64(&entry.integrated_time)?;
+    let payload = serde_json::to_vec(&SignedEntry {
+        body: &entry.canonicalized_body,
+        integrated_time,
+        log_id: log_id.encode_hex(),
+        log_index: int64(&entry.log_index)?,
+    })?;
+
+    let timestamp = decode(&promise.signed_entry_timestamp)?;
+    let valid = Verifier::new(MessageDigest::sha256(), key)
+        .and_then(|mut verifier| verifier.verify_oneshot(&timestamp, &payload))
+        .map_err(|err| invalid(format!("failed to verify signed entry timestamp: {err}")))?;
+    if !valid {
+        return Err(invalid("invalid signed entry timestamp"));
+    }
+
+    let body: HashedRekord = serde_json::from_slice(&decode(&entry.canonicalized_body)?)?;
+    if body.kind != "hashedrekord" {
+        return Err(invalid(format!(
+            "unsupported kind of transparency log entry: {}",
+            body.kind
+        )));
+    }
+    if body.spec.data.hash.algorithm != "sha256"
+        || body.spec.data.hash.value != digests.sha256.encode_hex::<String>()
+    {
+        return Err(invalid("transparency log entry doesn't match the document"));
+    }
+    if decode(&body.spec.signature.content)? != signature {
+        return Err(invalid(
+            "transparency log entry doesn't match the signature",
+        ));
+    }
+    let logged = X509::from_pem(&decode(&body.spec.signature.public_key.content)?)
+        .and_then(|logged| logged.to_der())
+        .map_err(|err| invalid(format!("invalid certificate in log entry: {err}")))?;
+    if certificate.to_der().ok() != Some(logged) {
+        return Err(invalid(
+            "transparency log entry doesn't match the certificate",
+        ));
+    }
+
+    Ok(integrated_time)
+}
+
+/// The identity of the signer, from the subject alternative names of the certificate.
+fn identity(certificate: &X509) -> Result<SignerIdentity, Error> {
+    let subject = certificate
+        .subject_alt_names()
+        .into_iter()
+        .flatten()
+        .find_map(|name| name.email().or_else(|| name.uri()).map(ToString::to_string))
+        .ok_or_else(|| invalid("certificate has no subject alternative name"))?;
+
+    Ok(SignerIdentity { subject })
+}
+
+fn decode(value: &str) -> Result<Vec<u8>, Error> {
+    BASE64_STANDARD
+        .decode(value)
+        .map_err(|err| invalid(format!("invalid base64 encoding: {err}")))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use openssl::{
+        asn1::Asn1Time,
+        ec::{EcGroup, EcKey},
+        nid::Nid,
+        pkey::Private,
+        sign::Signer,
+        x509::{
+            X509Builder, X509Name,
+            extension::{BasicConstraints, KeyUsage, SubjectAlternativeName},
+        },
+    };
+    use serde_json::json;
+
+    fn key() -> Result<PKey<Private>, anyhow::Error> {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+        Ok(PKey::from_ec_key(EcKey::generate(&group)?)?)
+    }
+
+    fn now() -> i64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("must be after the epoch")
+            .as_secs() as i64
+    }
+
+    fn name(cn: &str) -> Result<X509Name, anyhow::Error> {
+        let mut name = X509Name::builder()?;
+        name.append_entry_by_nid(Nid::COMMONNAME, cn)?;
+        Ok(name.build())
+    }
+
+    /// Create a certificate, self-signed if there is no issuer.
+    fn certificate(
+        key: &PKey<Private>,
+        issuer: Option<(&X509, &PKey<Private>)>,
+    ) -> Result<X509, anyhow::Error> {
+        let mut builder = X509Builder::new()?;
+        builder.set_version(2)?;
+        builder.set_pubkey(key)?;
+        builder.set_not_before(Asn1Time::from_unix(now() - 60)?.as_ref())?;
+        builder.set_not_after(Asn1Time::days_from_now(1)?.as_ref())?;
+
+        match issuer {
+            Some((issuer, issuer_key)) => {
+                builder.set_subject_name(&name("signer")?)?;
+                builder.set_issuer_name(issuer.subject_name())?;
+                let san = SubjectAlternativeName::new()
+                    .email("signer@example.com")
+                    .build(&builder.x509v3_context(Some(issuer), None))?;
+                builder.append_extension(san)?;
+                builder.sign(issuer_key, MessageDigest::sha256())?;
+            }
+            None => {
+                builder.set_subject_name(&name("ca")?)?;
+                builder.set_issuer_name(&name("ca")?)?;
+                builder.append_extension(BasicConstraints::new().critical().ca().build()?)?;
+                builder.append_extension(KeyUsage::new().critical().key_cert_sign().build()?)?;
+                let san = SubjectAlternativeName::new()
+                    .email("signer@example.com")
+                    .build(&builder.x509v3_context(None, None))?;
+                builder.append_extension(san)?;
+                builder.sign(key, MessageDigest::sha256())?;
+            }
+        }
+
+        Ok(builder.build())
+    }
+
+    fn sign(key: &PKey<Private>, data: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+        Ok(Signer::new(MessageDigest::sha256(), key)?.sign_oneshot_to_vec(data)?)
+    }
+
+    struct Setup {
+        ca: X509,
+        ca_key: PKey<Private>,
+        log_key: PKey<Private>,
+        root: TrustRoot,
+    }
+
+    impl Setup {
+        fn new() -> Result<Self, anyhow::Error> {
+            let ca_key = key()?;
+            let ca = certificate(&ca_key, None)?;
+            let log_key = key()?;
+            let root = TrustRoot::new()
+                .add_certificates(&ca.to_pem()?)?
+                .add_log_key(&log_key.public_key_to_pem()?)?;
+
+            Ok(Self {
+                ca,
+                ca_key,
+                log_key,
+                root,
+            })
+        }
+
+        /// Create a bundle, signing the data with a certificate issued by the CA and logging it.
+        fn bundle(&self, data: &[u8]) -> Result<Signature, anyhow::Error> {
+            let key = key()?;
+            let cert = certificate(&key, Some((&self.ca, &self.ca_key)))?;
+            self.bundle_with(&key, &cert, &self.log_key, data)
+        }
+
+        fn bundle_with(
+            &self,
+            key: &PKey<Private>,
+            cert: &X509,
+            log_key: &PKey<Private>,
+            data: &[u8],
+        ) -> Result<Signature, anyhow::Error> {
+            let digests = Digests::digest(data);
+            let signature = sign(key, data)?;
+
+            let body = BASE64_STANDARD.encode(serde_json::to_vec(&json!({
+                "apiVersion": "0.0.1",
+                "kind": "hashedrekord",
+                "spec": {
+                    "data": {
+                        "hash": {
+                            "algorithm": "sha256",
+                            "value": digests.sha256.encode_hex::<String>(),
+                        },
+                    },
+                    "signature": {
+                        "content": BASE64_STANDARD.encode(&signature),
+                        "publicKey": { "content": BASE64_STANDARD.encode(cert.to_pem()?) },
+                    },
+                },
+            }))?);
+            let log_id = hash(MessageDigest::sha256(), &log_key.public_key_to_der()?)?;
+            let integrated_time = now();
+            let set = sign(
+                log_key,
+                &serde_json::to_vec(&SignedEntry {
+                    body: &body,
+                    integrated_time,
+                    log_id: log_id.encode_hex(),
+                    log_index: 42,
+                })?,
+            )?;
+
+            let bundle = json!({
+                "mediaType": "application/vnd.dev.sigstore.bundle.v0.3+json",
+                "verificationMaterial": {
+                    "certificate": { "rawBytes": BASE64_STANDARD.encode(cert.to_der()?) },
+                    "tlogEntries": [{
+                        "logIndex": "42",
+                        "logId": { "keyId": BASE64_STANDARD.encode(log_id) },
+                        "kindVersion": { "kind": "hashedrekord", "version": "0.0.1" },
+                        "integratedTime": integrated_time.to_string(),
+                        "inclusionPromise": {
+                            "signedEntryTimestamp": BASE64_STANDARD.encode(set),
+                        },
+                        "canonicalizedBody": body,
+                    }],
+                },
+                "messageSignature": {
+                    "messageDigest": {
+                        "algorithm": "SHA2_256",
+                        "digest": BASE64_STANDARD.encode(digests.sha256),
+                    },
+                    "signature": BASE64_STANDARD.encode(signature),
+                },
+            });
+
+            Ok(Signature::Bundle(serde_json::to_vec(&bundle)?))
+        }
+    }
+
+    #[test]
+    fn verify_bundle() -> Result<(), anyhow::Error> {
+        let setup = Setup::new()?;
+        let data = br#"{"document":"content"}"#;
+        let digests = Digests::digest(data);
+
+        let signature = setup.bundle(data)?;
+
+        let identity = signature.verify(data, &digests, &setup.root)?;
+        assert_eq!(identity.subject, "signer@example.com");
+
+        let other = b"other content";
+        assert!(matches!(
+            signature.verify(other, &Digests::digest(other), &setup.root),
+            Err(Error::Signature(_))
+        ));
+
+        // without a trust root, nothing is trusted
+        assert!(matches!(
+            signature.verify(data, &digests, &TrustRoot::default()),
+            Err(Error::Signature(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn reject_self_signed() -> Result<(), anyhow::Error> {
+        let setup = Setup::new()?;
+        let data = br#"{"document":"content"}"#;
+
+        let key = key()?;
+        let cert = certificate(&key, None)?;
+        let signature = setup.bundle_with(&key, &cert, &setup.log_key, data)?;
+
+        assert!(matches!(
+            signature.verify(data, &Digests::digest(data), &setup.root),
+            Err(Error::Signature(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn reject_untrusted_log() -> Result<(), anyhow::Error> {
+        let setup = Setup::new()?;
+        let data = br#"{"document":"content"}"#;
+
+        let key = key()?;
+        let cert = certificate(&key, Some((&setup.ca, &setup.ca_key)))?;
+        let signature = setup.bundle_with(&key, &cert, &self::key()?, data)?;
+
+        assert!(matches!(
+            signature.verify(data, &Digests::digest(data), &setup.root),
+            Err(Error::Signature(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn verify_detached() -> Result<(), anyhow::Error> {
+        let setup = Setup::new()?;
+        let data = b"some document";
+
+        let key = key()?;
+        let cert = certificate(&key, Some((&setup.ca, &setup.ca_key)))?;
+        let signature = Signature::Detached {
+            signature: BASE64_STANDARD.encode(sign(&key, data)?).into_bytes(),
+            certificate: cert.to_pem()?,
+        };
+
+        let identity = signature.verify(data, &Digests::digest(data), &setup.root)?;
+        assert_eq!(identity.subject, "signer@example.com");
+
+        let other = b"tampered document";
+        assert!(matches!(
+            signature.verify(other, &Digests::digest(other), &setup.root),
+            Err(Error::Signature(_))
+        ));
+
+        // a self-signed certificate isn't trusted
+
+        let cert = certificate(&key, None)?;
+        let signature = Signature::Detached {
+            signature: BASE64_STANDARD.encode(sign(&key, data)?).into_bytes(),
+            certificate: cert.to_pem()?,
+        };
+        assert!(matches!(
+            signature.verify(data, &Digests::digest(data), &setup.root),
+            Err(Error::Signature(_))
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,250 @@
+//! Incremental parsing of JSON documents from a stream of chunks, and reading such streams
+
+use crate::service::{
+    Error,
+    limits::{DepthScanner, Limits},
+};
+use bytes::{Buf, Bytes};
+use futures::{Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use std::{
+    fmt::Display,
+    io::{self, BufReader, Read},
+    pin::{Pin, pin},
+    task::{Context, Poll, ready},
+};
+use tokio::{
+    io::{AsyncRead, ReadBuf},
+    sync::mpsc,
+};
+
+/// The number of chunks buffered between the stream and the parser.
+///
+/// This, together with the size of the chunks, bounds the amount of raw content held in memory.
+const CHUNKS: usize = 16;
+
+/// Parse a JSON document from a stream of chunks, without collecting the stream first.
+///
+/// The parser runs on a blocking thread, reading chunks as they are provided by the stream. As
+/// the document can't be checked upfront, its nesting depth is checked while reading it.
+pub(crate) async fn parse_json<T, S, E>(stream: S, limits: &Limits) -> Result<T, Error>
+where
+    T: DeserializeOwned + Send + 'static,
+    S: Stream<Item = Result<Bytes, E>>,
+    E: Display,
+{
+    let (tx, reader) = ChannelReader::new();
+    let limits = *limits;
+
+    let parser = tokio::task::spawn_blocking(move || {
+        let mut reader = BufReader::new(DepthLimitedReader::new(reader, limits.max_depth));
+        let result = serde_json::from_reader::<_, T>(&mut reader);
+        match reader.get_ref().exceeded {
+            true => Err(limits.depth_exceeded()),
+            false => Ok(result?),
+        }
+    });
+
+    let ((), result) = tokio::join!(feed(stream, tx), parser);
+
+    result?
+}
+
+/// A reader, failing once the nesting depth of the JSON document it reads exceeds a limit.
+struct DepthLimitedReader<R> {
+    inner: R,
+    scanner: DepthScanner,
+    max_depth: usize,
+    exceeded: bool,
+}
+
+impl<R> DepthLimitedReader<R> {
+    fn new(inner: R, max_depth: usize) -> Self {
+        Self {
+            inner,
+            scanner: Default::default(),
+            max_depth,
+            exceeded: false,
+        }
+    }
+}
+
+impl<R: Read> Read for DepthLimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.inner.read(buf)?;
+
+        if self.scanner.scan(&buf[..len], self.max_depth) > self.max_depth {
+            self.exceeded = true;
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "nesting depth exceeded",
+            ));
+        }
+
+        Ok(len)
+    }
+}
+
+/// Run a function with a reader of a stream of chunks.
+///
+/// The reader is `Send`, even if the stream isn't (like the payload of a request). The stream is
+/// polled alongside the function, forwarding its chunks to the reader.
+pub async fn with_reader<S, E, T>(stream: S, f: impl AsyncFnOnce(ChannelReader) -> T) -> T
+where
+    S: Stream<Item = Result<Bytes, E>>,
+    E: Display,
+{
+    let (tx, reader) = ChannelReader::new();
+    let ((), result) = tokio::join!(feed(stream, tx), f(reader));
+    result
+}
+
+/// Forward the chunks of a stream to a channel, until either side is done.
+async fn feed<S, E>(stream: S, tx: mpsc::Sender<Result<Bytes, io::Error>>)
+where
+    S: Stream<Item = Result<Bytes, E>>,
+    E: Display,
+{
+    let mut stream = pin!(stream);
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|err| io::Error::other(err.to_string()));
+        let failed = chunk.is_err();
+        if tx.send(chunk).await.is_err() || failed {
+            // either the reader is gone, or there is nothing left to read
+            break;
+        }
+    }
+}
+
+/// A reader, consuming the chunks sent through a channel.
+///
+/// It can be used as blocking [`Read`], as well as [`AsyncRead`].
+pub struct ChannelReader {
+    rx: mpsc::Receiver<Result<Bytes, io::Error>>,
+    current: Bytes,
+}
+
+impl ChannelReader {
+    fn new() -> (mpsc::Sender<Result<Bytes, io::Error>>, Self) {
+        let (tx, rx) = mpsc::channel(CHUNKS);
+        (
+            tx,
+            Self {
+                rx,
+                current: Bytes::new(),
+            },
+        )
+    }
+}
+
+impl AsyncRead for ChannelReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        while self.current.is_empty() {
+            match ready!(self.rx.poll_recv(cx)) {
+                Some(chunk) => self.current = chunk?,
+                None => return Poll::Ready(Ok(())),
+            }
+        }
+
+        let len = buf.remaining().min(self.current.len());
+        buf.put_slice(&self.current[..len]);
+        self.current.advance(len);
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.current.is_empty() {
+            match self.rx.blocking_recv() {
+                Some(chunk) => self.current = chunk?,
+                None => return Ok(0),
+            }
+        }
+
+        let len = buf.len().min(self.current.len());
+        buf[..len].copy_from_slice(&self.current[..len]);
+        self.current.advance(len);
+
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::{Value, json};
+    use test_log::test;
+
+    #[test(tokio::test)]
+    async fn parse_chunks() -> Result<(), anyhow::Error> {
+        let data = serde_json::to_vec(&json!({
+            "name": "foo",
+            "items": (0..1000).collect::<Vec<_>>(),
+        }))?;
+
+        let chunks = data
+            .chunks(7)
+            .map(|chunk| Ok::<_, io::Error>(Bytes::copy_from_slice(chunk)))
+            .collect::<Vec<_>>();
+
+        let value: Value = parse_json(futures::stream::iter(chunks), &Default::default()).await?;
+        assert_eq!(value["name"], "foo");
+        assert_eq!(value["items"].as_array().map(Vec::len), Some(1000));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn stream_error() {
+        let chunks = vec![
+            Ok(Bytes::from_static(b"{\"name\":")),
+            Err(io::Error::other("broken")),
+        ];
+
+        let result: Result<Value, _> =
+            parse_json(futures::stream::iter(chunks), &Default::default()).await;
+        assert!(matches!(result, Err(Error::Json(_))));
+    }
+
+    #[test(tokio::test)]
+    async fn exceed_depth() {
+        let limits = Limits {
+            max_depth: 16,
+            ..Default::default()
+        };
+
+        let data = format!(r#"{{"a": {}1{}}}"#, "[".repeat(32), "]".repeat(32)).into_bytes();
+        let chunks = data
+            .chunks(7)
+            .map(|chunk| Ok::<_, io::Error>(Bytes::copy_from_slice(chunk)))
+            .collect::<Vec<_>>();
+
+        let result: Result<Value, _> = parse_json(futures::stream::iter(chunks), &limits).await;
+        assert!(matches!(result, Err(Error::LimitExceeded(_))));
+    }
+
+    #[test(tokio::test)]
+    async fn read_async() -> Result<(), anyhow::Error> {
+        use tokio::io::AsyncReadExt;
+
+        let chunks = vec![
+            Ok::<_, io::Error>(Bytes::from_static(b"foo")),
+            Ok(Bytes::from_static(b"bar")),
+        ];
+
+        let data = with_reader(futures::stream::iter(chunks), async |mut reader| {
+            let mut data = vec![];
+            reader.read_to_end(&mut data).await.map(|_| data)
+        })
+        .await?;
+        assert_eq!(data, b"foobar");
+
+        Ok(())
+    }
+}
@@ -160,7 +160,7 @@ fn canonicalize(vec: Vec<String>) -> Vec<String> {
     vec.iter().map(|e| format!("CWE-{e}")).collect()
 }
 
-fn gather_content(node: &Node) -> String {
+pub(crate) fn gather_content(node: &Node) -> String {
     let mut dest = String::new();
 
     let children = node.children();
@@ -0,0 +1,60 @@
+#![recursion_limit = "512"]
+
+use bytes::Bytes;
+use flate2::{Compression, write::GzEncoder};
+use std::io::Write;
+use test_context::test_context;
+use test_log::test;
+use trustify_module_ingestor::service::{Cache, Format};
+use trustify_test_context::{TrustifyContext, document_bytes, document_bytes_raw};
+
+async fn ingest_compressed(
+    ctx: &TrustifyContext,
+    path: &str,
+    data: impl Into<Bytes>,
+) -> anyhow::Result<()> {
+    let result = ctx
+        .db
+        .transaction(async |tx| {
+            ctx.ingestor
+                .ingest(data, Format::Unknown, (), None, Cache::Skip, tx)
+                .await
+        })
+        .await?;
+
+    assert!(result.document_id.is_some());
+
+    // the compressed document must be stored in its decompressed form
+
+    let again = ctx.ingest_document(path).await?;
+    assert_eq!(result.id, again.id);
+
+    Ok(())
+}
+
+#[test_context(TrustifyContext)]
+#[test(tokio::test)]
+async fn ingest_xz(ctx: &TrustifyContext) -> anyhow::Result<()> {
+    let path = "openshift-container-storage-4.8.z.json.xz";
+    ingest_compressed(ctx, path, document_bytes_raw(path).await?).await
+}
+
+#[test_context(TrustifyContext)]
+#[test(tokio::test)]
+async fn ingest_gzip(ctx: &TrustifyContext) -> anyhow::Result<()> {
+    let path = "zookeeper-3.9.2-cyclonedx.json";
+
+    let mut encoder = GzEncoder::new(vec![], Compression::default());
+    encoder.write_all(&document_bytes(path).await?)?;
+
+    ingest_compressed(ctx, path, encoder.finish()?).await
+}
+
+#[test_context(TrustifyContext)]
+#[test(tokio::test)]
+async fn ingest_zstd(ctx: &TrustifyContext) -> anyhow::Result<()> {
+    let path = "zookeeper-3.9.2-cyclonedx.json";
+    let data = zstd::encode_all(&*document_bytes(path).await?, 3)?;
+
+    ingest_compressed(ctx, path, data).await
+}
@@ -0,0 +1,77 @@
+#![recursion_limit = "512"]
+
+#[path = "common.rs"]
+mod common;
+
+use actix_web::test::TestRequest;
+use common::caller_with;
+use std::io::{Cursor, Read};
+use test_context::test_context;
+use test_log::test;
+use trustify_module_ingestor::{
+    endpoints::Config,
+    service::{
+        Format,
+        dataset::{MANIFEST, Manifest},
+    },
+};
+use trustify_test_context::{TrustifyContext, call::CallService};
+use zip::ZipArchive;
+
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn export_import_dataset(ctx: &TrustifyContext) -> anyhow::Result<()> {
+    let app = caller_with(ctx, Config::default()).await?;
+
+    ctx.ingest_document_as(
+        "zookeeper-3.9.2-cyclonedx.json",
+        Format::SBOM,
+        [("source", "test"), ("team", "a")],
+    )
+    .await?;
+    ctx.ingest_document_as(
+        "csaf/cve-2023-0044.json",
+        Format::Advisory,
+        [("source", "test")],
+    )
+    .await?;
+
+    let data = app
+        .call_and_read_body(TestRequest::get().uri("/api/v3/dataset").to_request())
+        .await;
+
+    let mut archive = ZipArchive::new(Cursor::new(data.to_vec()))?;
+    let manifest: Manifest = {
+        let mut file = archive.by_name(MANIFEST)?;
+        let mut data = vec![];
+        file.read_to_end(&mut data)?;
+        serde_json::from_slice(&data)?
+    };
+
+    assert_eq!(manifest.documents.len(), 2);
+    let (name, sbom) = manifest
+        .documents
+        .iter()
+        .find(|(name, _)| name.starts_with("cyclonedx/"))
+        .expect("must contain the SBOM");
+    assert_eq!(name, &format!("cyclonedx/{}", sbom.sha256));
+    assert_eq!(sbom.labels.0.get("team").map(String::as_str), Some("a"));
+    assert!(
+        manifest
+            .documents
+            .keys()
+            .any(|name| name.starts_with("csaf/"))
+    );
+
+    // importing it again must succeed, and restore the labels
+
+    let result = ctx
+        .db
+        .transaction(async |tx| ctx.ingestor.ingest_dataset(&data, (), 0, tx).await)
+        .await?;
+
+    assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+    assert_eq!(result.files.len(), 2);
+
+    Ok(())
+}
@@ -7,7 +7,7 @@ use common::caller_with;
 use std::io::{Cursor, Write};
 use test_context::test_context;
 use test_log::test;
-use trustify_module_ingestor::endpoints::Config;
+use trustify_module_ingestor::{endpoints::Config, service::limits::Limits};
 use trustify_test_context::{TrustifyContext, call::CallService, document_bytes_raw};
 use zip::write::FileOptions;
 
@@ -18,6 +18,7 @@ async fn upload_bomb_dataset(ctx: &TrustifyContext) -> anyhow::Result<()> {
         ctx,
         Config {
             dataset_entry_limit: 1024 * 1024,
+            ..Default::default()
         },
     )
     .await?;
@@ -39,3 +40,61 @@ async fn upload_bomb_dataset(ctx: &TrustifyContext) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn validate_deeply_nested(ctx: &TrustifyContext) -> anyhow::Result<()> {
+    let app = caller_with(
+        ctx,
+        Config {
+            limits: Limits {
+                max_depth: 16,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    let payload = format!(
+        r#"{{"bomFormat": "CycloneDX", "specVersion": "1.5", "metadata": {}1{}}}"#,
+        "[".repeat(32),
+        "]".repeat(32)
+    );
+
+    let request = TestRequest::post()
+        .uri("/api/v3/validate?format=cyclonedx")
+        .set_payload(payload)
+        .to_request();
+
+    let response = app.call_service(request).await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    Ok(())
+}
+
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn validate_bomb(ctx: &TrustifyContext) -> anyhow::Result<()> {
+    let app = caller_with(
+        ctx,
+        Config {
+            limits: Limits {
+                max_bytes: 1024 * 1024,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    let request = TestRequest::post()
+        .uri("/api/v3/validate")
+        .set_payload(document_bytes_raw("bomb.bz2").await?)
+        .to_request();
+
+    let response = app.call_service(request).await;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    Ok(())
+}
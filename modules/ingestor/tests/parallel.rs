@@ -47,7 +47,7 @@ async fn sbom_parallel(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
         tasks.push(async move {
             db.transaction(async |tx| {
                 service
-                    .ingest(&next, Format::SPDX, (), None, Cache::Skip, tx)
+                    .ingest(next.clone(), Format::SPDX, (), None, Cache::Skip, tx)
                     .await
             })
             .await?;
@@ -134,7 +134,7 @@ async fn csaf_parallel(ctx: &TrustifyContext) -> Result<(), anyhow::Error> {
         tasks.push(async move {
             db.transaction(async |tx| {
                 service
-                    .ingest(&next, Format::CSAF, (), None, Cache::Skip, tx)
+                    .ingest(next.clone(), Format::CSAF, (), None, Cache::Skip, tx)
                     .await
             })
             .await?;
@@ -299,7 +299,7 @@ async fn advisories_parallel(ctx: &TrustifyContext) -> Result<(), anyhow::Error>
         tasks.push(async move {
             db.transaction(async |tx| {
                 service
-                    .ingest(&next, Format::Advisory, (), None, Cache::Skip, tx)
+                    .ingest(next.clone(), Format::Advisory, (), None, Cache::Skip, tx)
                     .await
             })
             .await?;
@@ -341,7 +341,7 @@ async fn sbom_advisories_parallel(ctx: &TrustifyContext) -> Result<(), anyhow::E
         sbom_tasks.push(async move {
             db.transaction(async |tx| {
                 service
-                    .ingest(&next, Format::SBOM, (), None, Cache::Skip, tx)
+                    .ingest(next.clone(), Format::SBOM, (), None, Cache::Skip, tx)
                     .await
             })
             .await?;
@@ -358,7 +358,7 @@ async fn sbom_advisories_parallel(ctx: &TrustifyContext) -> Result<(), anyhow::E
         advisory_tasks.push(async move {
             db.transaction(async |tx| {
                 service
-                    .ingest(&next, Format::Advisory, (), None, Cache::Skip, tx)
+                    .ingest(next.clone(), Format::Advisory, (), None, Cache::Skip, tx)
                     .await
             })
             .await?;
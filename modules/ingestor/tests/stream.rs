@@ -0,0 +1,91 @@
+#![allow(clippy::expect_used)]
+#![recursion_limit = "512"]
+
+use test_context::test_context;
+use test_log::test;
+use trustify_module_ingestor::{
+    graph::Graph,
+    service::{Cache, Error, Format, IngestOptions, IngestorService, limits::Limits},
+};
+use trustify_test_context::TrustifyContext;
+
+async fn ingest_stream(ctx: &TrustifyContext, path: &str, format: Format) -> anyhow::Result<()> {
+    let file = tokio::fs::File::open(ctx.absolute_path(path)?).await?;
+
+    let result = ctx
+        .db
+        .transaction(async |tx| {
+            ctx.ingestor
+                .ingest_stream(
+                    file,
+                    format,
+                    (),
+                    None,
+                    Cache::Skip,
+                    IngestOptions::default(),
+                    tx,
+                )
+                .await
+        })
+        .await?;
+
+    assert!(result.document_id.is_some());
+
+    // ingesting the same content again, the regular way, must result in the same document
+
+    let again = ctx.ingest_document(path).await?;
+    assert_eq!(result.id, again.id);
+    assert_eq!(result.document_id, again.document_id);
+
+    Ok(())
+}
+
+#[test_context(TrustifyContext)]
+#[test(tokio::test)]
+async fn stream_spdx(ctx: &TrustifyContext) -> anyhow::Result<()> {
+    // the SPDX version is at the very end of the document, beyond the detection prefix
+    ingest_stream(ctx, "ubi9-9.2-755.1697625012.json", Format::SPDX).await
+}
+
+#[test_context(TrustifyContext)]
+#[test(tokio::test)]
+async fn stream_cyclonedx(ctx: &TrustifyContext) -> anyhow::Result<()> {
+    ingest_stream(ctx, "zookeeper-3.9.2-cyclonedx.json", Format::SBOM).await
+}
+
+#[test_context(TrustifyContext)]
+#[test(tokio::test)]
+async fn stream_exceed_depth(ctx: &TrustifyContext) -> anyhow::Result<()> {
+    let ingestor =
+        IngestorService::new(Graph::new(), ctx.storage.clone(), None).with_limits(Limits {
+            max_depth: 16,
+            ..Default::default()
+        });
+
+    let payload = format!(
+        r#"{{"bomFormat": "CycloneDX", "specVersion": "1.5", "metadata": {}1{}}}"#,
+        "[".repeat(32),
+        "]".repeat(32)
+    );
+
+    let result = ctx
+        .db
+        .transaction(async |tx| {
+            ingestor
+                .ingest_stream(
+                    payload.as_bytes(),
+                    Format::CycloneDX,
+                    (),
+                    None,
+                    Cache::Skip,
+                    IngestOptions::default(),
+                    tx,
+                )
+                .await
+        })
+        .await;
+
+    assert!(matches!(result, Err(Error::LimitExceeded(_))));
+
+    Ok(())
+}
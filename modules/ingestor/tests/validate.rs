@@ -0,0 +1,49 @@
+#[path = "common.rs"]
+mod common;
+
+use actix_http::StatusCode;
+use actix_web::test::TestRequest;
+use common::caller_with;
+use sea_orm::{EntityTrait, PaginatorTrait};
+use serde_json::Value;
+use test_context::test_context;
+use test_log::test;
+use trustify_entity::source_document;
+use trustify_module_ingestor::endpoints::Config;
+use trustify_test_context::{TrustifyContext, call::CallService, document_bytes};
+
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn validate_document(ctx: &TrustifyContext) -> anyhow::Result<()> {
+    let app = caller_with(ctx, Config::default()).await?;
+
+    let request = TestRequest::post()
+        .uri("/api/v3/validate")
+        .set_payload(document_bytes("zookeeper-3.9.2-cyclonedx.json").await?)
+        .to_request();
+
+    let response: Value = app.call_and_read_body_json(request).await;
+    assert_eq!(response["format"], "cyclonedx");
+
+    // nothing must be stored
+
+    assert_eq!(source_document::Entity::find().count(&ctx.db).await?, 0);
+
+    Ok(())
+}
+
+#[test_context(TrustifyContext)]
+#[test(actix_web::test)]
+async fn validate_invalid_document(ctx: &TrustifyContext) -> anyhow::Result<()> {
+    let app = caller_with(ctx, Config::default()).await?;
+
+    let request = TestRequest::post()
+        .uri("/api/v3/validate?format=cyclonedx")
+        .set_payload(r#"{"bomFormat": "CycloneDX", "specVersion": 1}"#)
+        .to_request();
+
+    let response = app.call_service(request).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
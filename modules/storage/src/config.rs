@@ -1,5 +1,6 @@
 use crate::service::{
-    Compression, dispatch::DispatchBackend, fs::FileSystemBackend, s3::S3Backend,
+    Compression, Encryption, MasterKey, StaticKeyProvider, cache::DiskCache,
+    dispatch::DispatchBackend, fs::FileSystemBackend, s3::S3Backend,
 };
 use anyhow::Context;
 use std::{
@@ -7,6 +8,7 @@ use std::{
     fs::create_dir_all,
     path::PathBuf,
 };
+use trustify_common::model::BinaryByteSize;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
 pub enum StorageStrategy {
@@ -53,6 +55,34 @@ pub struct StorageConfig {
     )]
     pub compression: Compression,
 
+    /// Master keys for encrypting stored documents (base64 encoded 256-bit AES keys).
+    ///
+    /// New documents get encrypted using the first key. Additional keys are only used for
+    /// decrypting existing documents, which allows rotating the master key.
+    #[arg(
+        id = "storage-encryption-key",
+        long,
+        env = "TRUSTD_STORAGE_ENCRYPTION_KEYS",
+        value_delimiter = ','
+    )]
+    pub encryption_keys: Vec<String>,
+
+    /// A local directory for caching documents retrieved from a remote storage.
+    ///
+    /// Only applies to remote storages, like S3. Repeated reads of a document are served from the
+    /// cache, instead of retrieving it again.
+    #[arg(id = "storage-cache-path", long, env = "TRUSTD_STORAGE_CACHE_PATH")]
+    pub cache_path: Option<PathBuf>,
+
+    /// The maximum size of the local storage cache, evicting the least recently used documents.
+    #[arg(
+        id = "storage-cache-size",
+        long,
+        env = "TRUSTD_STORAGE_CACHE_SIZE",
+        default_value = "1 GiB"
+    )]
+    pub cache_size: BinaryByteSize,
+
     #[command(flatten)]
     pub s3_config: S3Config,
 }
@@ -60,6 +90,8 @@ pub struct StorageConfig {
 impl StorageConfig {
     /// Create a storage backend from a storage config
     pub async fn into_storage(self, devmode: bool) -> anyhow::Result<DispatchBackend> {
+        let encryption = self.encryption()?;
+
         Ok(match self.storage_strategy {
             StorageStrategy::Fs => {
                 let storage = self
@@ -74,14 +106,49 @@ impl StorageConfig {
                     ))?;
                 }
                 DispatchBackend::Filesystem(
-                    FileSystemBackend::new(storage, self.compression).await?,
+                    FileSystemBackend::new(storage, self.compression)
+                        .await?
+                        .with_encryption(encryption),
                 )
             }
             StorageStrategy::S3 => {
-                DispatchBackend::S3(S3Backend::new(self.s3_config, self.compression).await?)
+                let cache = match &self.cache_path {
+                    Some(path) => Some(
+                        DiskCache::new(path, self.cache_size.as_u64())
+                            .await
+                            .with_context(|| {
+                                format!("Failed to create storage cache: {}", path.display())
+                            })?,
+                    ),
+                    None => None,
+                };
+                DispatchBackend::S3(
+                    S3Backend::new(self.s3_config, self.compression)
+                        .await?
+                        .with_encryption(encryption)
+                        .with_cache(cache),
+                )
             }
         })
     }
+
+    /// Create the encryption from the configured keys, if there are any
+    fn encryption(&self) -> anyhow::Result<Option<Encryption>> {
+        if self.encryption_keys.is_empty() {
+            return Ok(None);
+        }
+
+        let keys = self
+            .encryption_keys
+            .iter()
+            .map(|key| key.parse::<MasterKey>())
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to parse storage encryption keys")?;
+
+        log::info!("Encrypting stored documents using key: {}", keys[0].id());
+
+        Ok(Some(Encryption::new(StaticKeyProvider::new(keys)?)))
+    }
 }
 
 #[derive(Clone, Debug, Default, clap::Args)]
@@ -0,0 +1,382 @@
+use crate::service::Compression;
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    io::{self, SeekFrom},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::SystemTime,
+};
+use tokio::{
+    fs::{self, File, OpenOptions},
+    io::{AsyncRead, AsyncSeekExt, AsyncWriteExt},
+};
+
+/// The extension of files still being written to the cache
+const TEMP_EXTENSION: &str = "tmp";
+
+/// A local disk cache for the content of a remote storage, evicting the least recently used
+/// entries once it exceeds its capacity.
+///
+/// The content is cached as it was received from the remote storage, so compressed and
+/// encrypted in the same way. The compression is recorded as the extension of the file, like the
+/// filesystem backend does.
+#[derive(Clone, Debug)]
+pub struct DiskCache {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    dir: PathBuf,
+    capacity: u64,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    entries: HashMap<String, Entry>,
+    /// The total size of all entries
+    size: u64,
+    /// A counter, increased with every access, ordering the entries by recency
+    tick: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    size: u64,
+    used: u64,
+    compression: Compression,
+}
+
+impl DiskCache {
+    /// Create a new cache in the provided directory, limited to a size of `capacity` bytes.
+    ///
+    /// Content already present in the directory is picked up, using its modification time as the
+    /// initial recency.
+    pub async fn new(dir: impl Into<PathBuf>, capacity: u64) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).await?;
+
+        let mut found = vec![];
+        let mut entries = fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            let Some((key, extension)) = split(&path) else {
+                continue;
+            };
+
+            if extension == TEMP_EXTENSION {
+                // left over from an interrupted write
+                fs::remove_file(&path).await?;
+                continue;
+            }
+
+            let Ok(compression) = parse_compression(extension) else {
+                log::warn!("Ignoring unknown file in storage cache: {}", path.display());
+                continue;
+            };
+
+            let metadata = entry.metadata().await?;
+            found.push((
+                metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                key.to_string(),
+                metadata.len(),
+                compression,
+            ));
+        }
+
+        found.sort_unstable_by_key(|(modified, ..)| *modified);
+
+        let mut state = State::default();
+        for (_, key, size, compression) in found {
+            state.tick += 1;
+            state.size += size;
+            state.entries.insert(
+                key,
+                Entry {
+                    size,
+                    used: state.tick,
+                    compression,
+                },
+            );
+        }
+
+        log::info!(
+            "Using storage cache in {}, containing {} entries, {} of {capacity} bytes",
+            dir.display(),
+            state.entries.len(),
+            state.size
+        );
+
+        let result = Self {
+            inner: Arc::new(Inner {
+                dir,
+                capacity,
+                state: Mutex::new(state),
+            }),
+        };
+
+        let evicted = result.inner.state.lock().evict(capacity);
+        result.remove_files(evicted).await;
+
+        Ok(result)
+    }
+
+    /// Open the cached content of a key, if present.
+    pub async fn get(&self, key: &str) -> io::Result<Option<(File, Compression)>> {
+        let compression = {
+            let mut state = self.inner.state.lock();
+            state.tick += 1;
+            let tick = state.tick;
+            match state.entries.get_mut(key) {
+                Some(entry) => {
+                    entry.used = tick;
+                    entry.compression
+                }
+                None => return Ok(None),
+            }
+        };
+
+        match File::open(self.path(key, compression)).await {
+            Ok(file) => Ok(Some((file, compression))),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                // removed behind our back
+                self.inner.state.lock().remove(key);
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Add content to the cache, returning the content for reading it.
+    ///
+    /// Content exceeding the capacity of the cache is still returned, but not kept.
+    pub async fn insert<R>(
+        &self,
+        key: &str,
+        compression: Compression,
+        mut reader: R,
+    ) -> io::Result<File>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let temp = self
+            .inner
+            .dir
+            .join(format!("{key}.{}.{TEMP_EXTENSION}", uuid::Uuid::new_v4()));
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&temp)
+            .await?;
+
+        let size = match tokio::io::copy(&mut reader, &mut file).await {
+            Ok(size) => size,
+            Err(err) => {
+                let _ = fs::remove_file(&temp).await;
+                return Err(err);
+            }
+        };
+        file.flush().await?;
+        file.seek(SeekFrom::Start(0)).await?;
+
+        if size > self.inner.capacity {
+            log::debug!("Not caching {key}, size of {size} bytes exceeds the capacity");
+            fs::remove_file(&temp).await?;
+            return Ok(file);
+        }
+
+        // make room first, so that the new entry doesn't get evicted itself
+        let evicted = {
+            let mut state = self.inner.state.lock();
+            state.remove(key);
+            state.evict(self.inner.capacity - size)
+        };
+        self.remove_files(evicted).await;
+
+        fs::rename(&temp, self.path(key, compression)).await?;
+
+        let mut state = self.inner.state.lock();
+        state.tick += 1;
+        state.size += size;
+        let used = state.tick;
+        let previous = state.entries.insert(
+            key.to_string(),
+            Entry {
+                size,
+                used,
+                compression,
+            },
+        );
+        if let Some(previous) = previous {
+            // inserted concurrently, and replaced by us
+            state.size -= previous.size;
+        }
+
+        Ok(file)
+    }
+
+    /// Remove content from the cache, if present.
+    pub async fn remove(&self, key: &str) -> io::Result<()> {
+        let Some(entry) = self.inner.state.lock().remove(key) else {
+            return Ok(());
+        };
+
+        match fs::remove_file(self.path(key, entry.compression)).await {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    /// The total size of the cached content, in bytes.
+    pub fn size(&self) -> u64 {
+        self.inner.state.lock().size
+    }
+
+    fn path(&self, key: &str, compression: Compression) -> PathBuf {
+        let mut path = self.inner.dir.join(key);
+        if compression != Compression::None {
+            path.set_extension(compression.extension());
+        }
+        path
+    }
+
+    /// Remove the files of evicted entries. Failing to do so only wastes space, so it's logged.
+    async fn remove_files(&self, evicted: Vec<(String, Compression)>) {
+        for (key, compression) in evicted {
+            log::debug!("Evicting from storage cache: {key}");
+            let path = self.path(&key, compression);
+            if let Err(err) = fs::remove_file(&path).await
+                && err.kind() != io::ErrorKind::NotFound
+            {
+                log::warn!("Failed to remove {}: {err}", path.display());
+            }
+        }
+    }
+}
+
+impl State {
+    fn remove(&mut self, key: &str) -> Option<Entry> {
+        let entry = self.entries.remove(key)?;
+        self.size -= entry.size;
+        Some(entry)
+    }
+
+    /// Evict the least recently used entries, until the size is within the limit.
+    fn evict(&mut self, limit: u64) -> Vec<(String, Compression)> {
+        let mut result = vec![];
+
+        if self.size <= limit {
+            return result;
+        }
+
+        let mut candidates = self
+            .entries
+            .iter()
+            .map(|(key, entry)| (entry.used, key.clone()))
+            .collect::<Vec<_>>();
+        candidates.sort_unstable();
+
+        for (_, key) in candidates {
+            if self.size <= limit {
+                break;
+            }
+            if let Some(entry) = self.remove(&key) {
+                result.push((key, entry.compression));
+            }
+        }
+
+        result
+    }
+}
+
+/// Split the file name of a cache file into key and extension.
+fn split(path: &Path) -> Option<(&str, &str)> {
+    let name = path.file_name()?.to_str()?;
+    match name.split_once('.') {
+        Some((key, _)) if name.ends_with(&format!(".{TEMP_EXTENSION}")) => {
+            Some((key, TEMP_EXTENSION))
+        }
+        Some((key, extension)) => Some((key, extension)),
+        None => Some((name, "")),
+    }
+}
+
+/// The compression of a cache file, from its extension.
+fn parse_compression(extension: &str) -> Result<Compression, strum::ParseError> {
+    match extension {
+        "" => Ok(Compression::None),
+        extension => Compression::from_str(extension),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tempfile::tempdir;
+    use test_log::test;
+    use tokio::io::AsyncReadExt;
+
+    async fn read(mut file: File) -> String {
+        let mut result = String::new();
+        file.read_to_string(&mut result).await.unwrap();
+        result
+    }
+
+    #[test(tokio::test)]
+    async fn evict_least_recently_used() {
+        let dir = tempdir().unwrap();
+        let cache = DiskCache::new(dir.path(), 10).await.unwrap();
+
+        for key in ["a", "b", "c"] {
+            let file = cache
+                .insert(key, Compression::None, &b"1234"[..])
+                .await
+                .unwrap();
+            // the content is available while being inserted
+            assert_eq!(read(file).await, "1234");
+        }
+
+        // "a" was evicted, to make room for "c"
+        assert!(cache.get("a").await.unwrap().is_none());
+        assert_eq!(cache.size(), 8);
+
+        // use "b", so that "c" is the least recently used one
+        let (file, _) = cache.get("b").await.unwrap().unwrap();
+        assert_eq!(read(file).await, "1234");
+
+        cache
+            .insert("d", Compression::Zstd, &b"1234"[..])
+            .await
+            .unwrap();
+        assert!(cache.get("b").await.unwrap().is_some());
+        assert!(cache.get("c").await.unwrap().is_none());
+
+        let (_, compression) = cache.get("d").await.unwrap().unwrap();
+        assert_eq!(compression, Compression::Zstd);
+
+        // too large for the cache
+        let file = cache
+            .insert("e", Compression::None, &b"12345678901"[..])
+            .await
+            .unwrap();
+        assert_eq!(read(file).await, "12345678901");
+        assert!(cache.get("e").await.unwrap().is_none());
+
+        // the content is picked up again
+        drop(cache);
+        let cache = DiskCache::new(dir.path(), 10).await.unwrap();
+        assert_eq!(cache.size(), 8);
+        assert!(cache.get("b").await.unwrap().is_some());
+        assert!(cache.get("d").await.unwrap().is_some());
+
+        // and removed
+        cache.remove("b").await.unwrap();
+        assert!(cache.get("b").await.unwrap().is_none());
+        assert_eq!(cache.size(), 4);
+    }
+}
@@ -51,6 +51,45 @@ impl StorageBackend for DispatchBackend {
         }
     }
 
+    async fn retrieve_range(
+        &self,
+        key: StorageKey,
+        range: Range<u64>,
+    ) -> Result<Option<impl Stream<Item = Result<Bytes, Self::Error>> + use<>>, Self::Error>
+    where
+        Self: Sized,
+    {
+        match self {
+            Self::Filesystem(backend) => backend
+                .retrieve_range(key, range)
+                .await
+                .map(|stream| stream.map(|stream| stream.map_err(anyhow::Error::from).boxed()))
+                .map_err(anyhow::Error::from),
+            Self::S3(backend) => backend
+                .retrieve_range(key, range)
+                .await
+                .map(|stream| stream.map(|stream| stream.map_err(anyhow::Error::from).boxed()))
+                .map_err(anyhow::Error::from),
+        }
+    }
+
+    async fn list(
+        &self,
+    ) -> Result<impl Stream<Item = Result<StorageEntry, Self::Error>> + use<>, Self::Error> {
+        match self {
+            Self::Filesystem(backend) => backend
+                .list()
+                .await
+                .map(|stream| stream.map_err(anyhow::Error::from).boxed())
+                .map_err(anyhow::Error::from),
+            Self::S3(backend) => backend
+                .list()
+                .await
+                .map(|stream| stream.map_err(anyhow::Error::from).boxed())
+                .map_err(anyhow::Error::from),
+        }
+    }
+
     async fn delete(&self, key: StorageKey) -> Result<(), Self::Error> {
         match self {
             Self::Filesystem(backend) => backend.delete(key).await.map_err(anyhow::Error::from),
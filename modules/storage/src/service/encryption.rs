@@ -0,0 +1,517 @@
+use super::Compression;
+use async_trait::async_trait;
+use base64::{Engine, prelude::BASE64_STANDARD};
+use bytes::Bytes;
+use futures::stream;
+use ring::{
+    aead::{AES_256_GCM, Aad, LessSafeKey, MAX_TAG_LEN, NONCE_LEN, Nonce, UnboundKey},
+    digest::{SHA256, digest},
+    rand::{SecureRandom, SystemRandom},
+};
+use std::{
+    fmt::{Debug, Formatter},
+    io::Cursor,
+    str::FromStr,
+    sync::Arc,
+};
+use tokio::io::{self, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio_util::io::StreamReader;
+
+/// The magic bytes an encrypted document starts with
+const MAGIC: &[u8; 8] = b"\x89TENC\r\n\x1a";
+/// The version of the encrypted format
+const VERSION: u8 = 1;
+/// The size of the plain text chunks, which get encrypted individually
+const CHUNK_SIZE: usize = 64 * 1024;
+/// The length of the random nonce prefix, followed by the chunk counter and the last chunk flag
+const NONCE_PREFIX_LEN: usize = NONCE_LEN - 5;
+
+#[derive(Debug, thiserror::Error)]
+pub enum EncryptionError {
+    #[error("invalid encryption key: {0}")]
+    Key(String),
+    #[error("no encryption key configured")]
+    NoKey,
+    #[error("unknown encryption key: {0}")]
+    UnknownKey(String),
+    #[error("document is encrypted, but no encryption is configured")]
+    NotConfigured,
+    #[error("failed to wrap data key")]
+    Wrap,
+    #[error("failed to unwrap data key")]
+    Unwrap,
+    #[error("key provider error: {0}")]
+    Provider(#[source] anyhow::Error),
+}
+
+impl From<EncryptionError> for io::Error {
+    fn from(value: EncryptionError) -> Self {
+        io::Error::other(value)
+    }
+}
+
+/// A data key, wrapped (encrypted) by a master key
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WrappedKey {
+    /// The ID of the master key which wrapped the data key
+    pub key_id: String,
+    /// The encrypted data key
+    pub key: Vec<u8>,
+}
+
+/// A provider of master keys, wrapping and unwrapping the per-document data keys.
+///
+/// Master keys never get stored with the documents. Implementations can keep them locally, or
+/// delegate the operations to a key management service (KMS).
+#[async_trait]
+pub trait KeyProvider: Debug + Send + Sync {
+    /// Wrap a data key, using the current master key.
+    async fn wrap(&self, key: &[u8]) -> Result<WrappedKey, EncryptionError>;
+
+    /// Unwrap a data key, using the master key it was wrapped with.
+    async fn unwrap(&self, key: &WrappedKey) -> Result<Vec<u8>, EncryptionError>;
+}
+
+/// A master key, a base64 encoded 256-bit AES key.
+///
+/// The ID of the key is derived from the key itself.
+pub struct MasterKey {
+    id: String,
+    key: LessSafeKey,
+}
+
+impl Debug for MasterKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MasterKey")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FromStr for MasterKey {
+    type Err = EncryptionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let key = BASE64_STANDARD
+            .decode(s.trim())
+            .map_err(|err| EncryptionError::Key(err.to_string()))?;
+        let id = hex::encode(&digest(&SHA256, &key).as_ref()[..8]);
+        let key = UnboundKey::new(&AES_256_GCM, &key)
+            .map_err(|_| EncryptionError::Key("must be 32 bytes".into()))?;
+
+        Ok(Self {
+            id,
+            key: LessSafeKey::new(key),
+        })
+    }
+}
+
+impl MasterKey {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// A key provider using master keys from the configuration (or environment).
+///
+/// The first key is used for wrapping new data keys. All keys can be used for unwrapping, which
+/// allows rotating the master key.
+#[derive(Debug)]
+pub struct StaticKeyProvider {
+    keys: Vec<MasterKey>,
+}
+
+impl StaticKeyProvider {
+    pub fn new(keys: Vec<MasterKey>) -> Result<Self, EncryptionError> {
+        if keys.is_empty() {
+            return Err(EncryptionError::NoKey);
+        }
+        Ok(Self { keys })
+    }
+}
+
+#[async_trait]
+impl KeyProvider for StaticKeyProvider {
+    async fn wrap(&self, key: &[u8]) -> Result<WrappedKey, EncryptionError> {
+        let master = &self.keys[0];
+
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| EncryptionError::Wrap)?;
+
+        let mut data = key.to_vec();
+        master
+            .key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+            .map_err(|_| EncryptionError::Wrap)?;
+
+        Ok(WrappedKey {
+            key_id: master.id.clone(),
+            key: [nonce.as_slice(), &data].concat(),
+        })
+    }
+
+    async fn unwrap(&self, key: &WrappedKey) -> Result<Vec<u8>, EncryptionError> {
+        let master = self
+            .keys
+            .iter()
+            .find(|master| master.id == key.key_id)
+            .ok_or_else(|| EncryptionError::UnknownKey(key.key_id.clone()))?;
+
+        if key.key.len() < NONCE_LEN {
+            return Err(EncryptionError::Unwrap);
+        }
+        let (nonce, data) = key.key.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| EncryptionError::Unwrap)?;
+
+        let mut data = data.to_vec();
+        let len = master
+            .key
+            .open_in_place(nonce, Aad::empty(), &mut data)
+            .map_err(|_| EncryptionError::Unwrap)?
+            .len();
+        data.truncate(len);
+
+        Ok(data)
+    }
+}
+
+/// Envelope encryption of stored documents.
+///
+/// Each document gets encrypted (AES-256-GCM) with its own, random data key. The data key gets
+/// wrapped by the key provider and is stored in the header of the document. The content is
+/// encrypted in chunks, so that documents can be streamed without holding them in memory.
+///
+/// ## Format
+///
+/// ```text
+/// magic (8) | version (1) | key id length (2) | key id | wrapped key length (2) | wrapped key | nonce prefix (7)
+/// chunk 0 | chunk 1 | … | last chunk
+/// ```
+///
+/// Each chunk holds up to 64 KiB of plain text, followed by the authentication tag. The nonce of a
+/// chunk consists of the prefix, the chunk counter, and a flag marking the last chunk, which
+/// detects reordered and truncated content. The last chunk is always shorter than a full chunk,
+/// and might be empty.
+#[derive(Clone, Debug)]
+pub struct Encryption {
+    provider: Arc<dyn KeyProvider>,
+}
+
+impl Encryption {
+    pub fn new(provider: impl KeyProvider + 'static) -> Self {
+        Self {
+            provider: Arc::new(provider),
+        }
+    }
+
+    /// Write content, encrypted with a new data key.
+    pub async fn write<R, W>(&self, r: &mut R, w: &mut W) -> io::Result<u64>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let rng = SystemRandom::new();
+
+        let mut data_key = [0u8; 32];
+        rng.fill(&mut data_key)
+            .map_err(|_| io::Error::other("failed to generate data key"))?;
+        let mut prefix = [0u8; NONCE_PREFIX_LEN];
+        rng.fill(&mut prefix)
+            .map_err(|_| io::Error::other("failed to generate nonce"))?;
+
+        let wrapped = self.provider.wrap(&data_key).await?;
+        let key = data_key_cipher(&data_key)?;
+
+        let mut header = Vec::from(MAGIC.as_slice());
+        header.push(VERSION);
+        write_field(&mut header, wrapped.key_id.as_bytes())?;
+        write_field(&mut header, &wrapped.key)?;
+        header.extend_from_slice(&prefix);
+
+        w.write_all(&header).await?;
+        let mut written = header.len() as u64;
+
+        let mut buf = vec![0u8; CHUNK_SIZE];
+        let mut counter = 0u32;
+        loop {
+            let len = read_full(r, &mut buf).await?;
+            let last = len < CHUNK_SIZE;
+
+            let mut chunk = buf[..len].to_vec();
+            key.seal_in_place_append_tag(
+                chunk_nonce(&prefix, counter, last),
+                Aad::empty(),
+                &mut chunk,
+            )
+            .map_err(|_| io::Error::other("failed to encrypt chunk"))?;
+
+            w.write_all(&chunk).await?;
+            written += chunk.len() as u64;
+
+            if last {
+                break;
+            }
+            counter = counter
+                .checked_add(1)
+                .ok_or_else(|| io::Error::other("content too large"))?;
+        }
+
+        w.shutdown().await?;
+
+        Ok(written)
+    }
+
+    /// Create a reader decrypting the content, following the magic bytes.
+    async fn reader<R>(&self, mut r: R) -> io::Result<Box<dyn AsyncRead + Send + Unpin>>
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+    {
+        let version = r.read_u8().await?;
+        if version != VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported encryption format version: {version}"),
+            ));
+        }
+
+        let key_id = String::from_utf8(read_field(&mut r).await?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        let key = read_field(&mut r).await?;
+        let mut prefix = [0u8; NONCE_PREFIX_LEN];
+        r.read_exact(&mut prefix).await?;
+
+        let data_key = self.provider.unwrap(&WrappedKey { key_id, key }).await?;
+        let key = Arc::new(data_key_cipher(&data_key)?);
+
+        let chunks = stream::try_unfold((r, 0u32, false), move |(mut r, counter, done)| {
+            let key = key.clone();
+            async move {
+                if done {
+                    return Ok(None);
+                }
+
+                let mut chunk = vec![0u8; CHUNK_SIZE + MAX_TAG_LEN];
+                let len = read_full(&mut r, &mut chunk).await?;
+                let last = len < chunk.len();
+                chunk.truncate(len);
+
+                let len = key
+                    .open_in_place(
+                        chunk_nonce(&prefix, counter, last),
+                        Aad::empty(),
+                        &mut chunk,
+                    )
+                    .map_err(|_| {
+                        io::Error::new(io::ErrorKind::InvalidData, "failed to decrypt chunk")
+                    })?
+                    .len();
+                chunk.truncate(len);
+
+                Ok::<_, io::Error>(Some((
+                    Bytes::from(chunk),
+                    (r, counter.wrapping_add(1), last),
+                )))
+            }
+        });
+
+        Ok(Box::new(StreamReader::new(Box::pin(chunks))))
+    }
+}
+
+/// Create a reader for stored content, decrypting it if it is encrypted.
+///
+/// Content not starting with the magic bytes is passed through as is. This allows reading content
+/// which was stored before the encryption got enabled.
+pub async fn decrypt<R>(
+    encryption: Option<&Encryption>,
+    mut r: R,
+) -> io::Result<Box<dyn AsyncRead + Send + Unpin>>
+where
+    R: AsyncRead + Send + Unpin + 'static,
+{
+    let mut magic = [0u8; MAGIC.len()];
+    let len = read_full(&mut r, &mut magic).await?;
+
+    if &magic[..len] != MAGIC.as_slice() {
+        return Ok(Box::new(Cursor::new(magic[..len].to_vec()).chain(r)));
+    }
+
+    match encryption {
+        Some(encryption) => encryption.reader(r).await,
+        None => Err(EncryptionError::NotConfigured.into()),
+    }
+}
+
+/// Write content, compressed, and encrypted if an encryption is provided.
+pub async fn encode<R, W>(
+    r: &mut R,
+    w: &mut W,
+    compression: Compression,
+    encryption: Option<&Encryption>,
+) -> io::Result<u64>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let Some(encryption) = encryption else {
+        return compression.write(r, w).await;
+    };
+
+    // pipe the compressed content through the encryption
+
+    let (mut tx, mut rx) = io::duplex(CHUNK_SIZE);
+    let (_, written) =
+        tokio::try_join!(compression.write(r, &mut tx), encryption.write(&mut rx, w))?;
+
+    Ok(written)
+}
+
+fn data_key_cipher(key: &[u8]) -> io::Result<LessSafeKey> {
+    UnboundKey::new(&AES_256_GCM, key)
+        .map(LessSafeKey::new)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid data key"))
+}
+
+fn chunk_nonce(prefix: &[u8; NONCE_PREFIX_LEN], counter: u32, last: bool) -> Nonce {
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce[..NONCE_PREFIX_LEN].copy_from_slice(prefix);
+    nonce[NONCE_PREFIX_LEN..NONCE_LEN - 1].copy_from_slice(&counter.to_be_bytes());
+    nonce[NONCE_LEN - 1] = last.into();
+    Nonce::assume_unique_for_key(nonce)
+}
+
+/// Fill the buffer, returning fewer bytes only when reaching the end of the stream.
+async fn read_full<R>(r: &mut R, buf: &mut [u8]) -> io::Result<usize>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut len = 0;
+    while len < buf.len() {
+        match r.read(&mut buf[len..]).await? {
+            0 => break,
+            n => len += n,
+        }
+    }
+    Ok(len)
+}
+
+fn write_field(header: &mut Vec<u8>, value: &[u8]) -> io::Result<()> {
+    let len = u16::try_from(value.len()).map_err(|_| io::Error::other("header field too large"))?;
+    header.extend_from_slice(&len.to_be_bytes());
+    header.extend_from_slice(value);
+    Ok(())
+}
+
+async fn read_field<R>(r: &mut R) -> io::Result<Vec<u8>>
+where
+    R: AsyncRead + Unpin,
+{
+    let len = r.read_u16().await?;
+    let mut value = vec![0u8; len.into()];
+    r.read_exact(&mut value).await?;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+    use test_log::test;
+
+    const KEY: &str = "MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=";
+    const OTHER_KEY: &str = "ZmVkY2JhOTg3NjU0MzIxMGZlZGNiYTk4NzY1NDMyMTA=";
+
+    fn encryption(keys: &[&str]) -> Encryption {
+        Encryption::new(
+            StaticKeyProvider::new(keys.iter().map(|key| key.parse().unwrap()).collect()).unwrap(),
+        )
+    }
+
+    async fn encrypt(encryption: &Encryption, data: &[u8]) -> Vec<u8> {
+        let mut encrypted = vec![];
+        encryption
+            .write(&mut &data[..], &mut encrypted)
+            .await
+            .expect("must encrypt");
+        encrypted
+    }
+
+    async fn read(encryption: Option<&Encryption>, data: Vec<u8>) -> io::Result<Vec<u8>> {
+        let mut reader = decrypt(encryption, Cursor::new(data)).await?;
+        let mut result = vec![];
+        reader.read_to_end(&mut result).await?;
+        Ok(result)
+    }
+
+    #[test(tokio::test)]
+    #[rstest]
+    #[case::empty(0)]
+    #[case::small(11)]
+    #[case::chunk(CHUNK_SIZE)]
+    #[case::chunks(3 * CHUNK_SIZE + 17)]
+    async fn roundtrip(#[case] size: usize) {
+        let encryption = encryption(&[KEY]);
+        let data: Vec<u8> = (0..size).map(|i| i as u8).collect();
+
+        let encrypted = encrypt(&encryption, &data).await;
+        assert!(encrypted.starts_with(MAGIC));
+
+        let decrypted = read(Some(&encryption), encrypted).await.unwrap();
+        assert_eq!(decrypted, data);
+    }
+
+    #[test(tokio::test)]
+    async fn plain_text() {
+        let encryption = encryption(&[KEY]);
+
+        // content stored without encryption must still be readable
+        assert_eq!(
+            read(Some(&encryption), b"Hello World".to_vec())
+                .await
+                .unwrap(),
+            b"Hello World"
+        );
+        assert_eq!(read(None, b"Hi".to_vec()).await.unwrap(), b"Hi");
+    }
+
+    #[test(tokio::test)]
+    async fn rotation() {
+        let old = encryption(&[OTHER_KEY]);
+        let encrypted = encrypt(&old, b"Hello World").await;
+
+        // the new key is used for writing, the old one is still available for reading
+        let rotated = encryption(&[KEY, OTHER_KEY]);
+        assert_eq!(
+            read(Some(&rotated), encrypted.clone()).await.unwrap(),
+            b"Hello World"
+        );
+
+        // without the old key, or without any encryption, the content can't be read
+        assert!(
+            read(Some(&encryption(&[KEY])), encrypted.clone())
+                .await
+                .is_err()
+        );
+        assert!(read(None, encrypted).await.is_err());
+    }
+
+    #[test(tokio::test)]
+    async fn tampered() {
+        let encryption = encryption(&[KEY]);
+        let data = vec![0u8; 2 * CHUNK_SIZE];
+        let encrypted = encrypt(&encryption, &data).await;
+
+        // flipping a bit
+        let mut modified = encrypted.clone();
+        let len = modified.len();
+        modified[len - 20] ^= 1;
+        assert!(read(Some(&encryption), modified).await.is_err());
+
+        // truncating at a chunk boundary
+        let mut truncated = encrypted;
+        truncated.truncate(truncated.len() - MAX_TAG_LEN);
+        assert!(read(Some(&encryption), truncated).await.is_err());
+    }
+}
@@ -1,22 +1,29 @@
 use crate::service::{
-    StorageBackend, StorageKey, StorageResult, StoreError, compression::Compression, temp::TempFile,
+    StorageBackend, StorageEntry, StorageKey, StorageResult, StoreError,
+    compression::Compression,
+    encryption::{Encryption, decrypt, encode},
+    range,
+    temp::TempFile,
 };
 use anyhow::Context;
 use bytes::Bytes;
-use futures::Stream;
+use futures::{Stream, StreamExt, stream};
 use std::{
     fmt::Debug,
-    io::{ErrorKind, Result as IoResult},
+    io::{ErrorKind, Result as IoResult, SeekFrom},
+    ops::Range,
     path::{Path, PathBuf},
+    time::SystemTime,
 };
 use strum::IntoEnumIterator;
 use tempfile::{TempDir, tempdir};
 use tokio::{
-    fs::{File, create_dir_all, remove_file, try_exists},
-    io::{AsyncRead, AsyncWriteExt},
+    fs::{File, OpenOptions, ReadDir, create_dir_all, read_dir, remove_file, try_exists},
+    io::{AsyncRead, AsyncReadExt, AsyncSeekExt, AsyncWriteExt, copy, sink},
 };
 use tokio_util::io::ReaderStream;
 use tracing::instrument;
+use trustify_common::hashing::HashingRead;
 
 /// A filesystem backed store
 ///
@@ -47,6 +54,8 @@ pub struct FileSystemBackend {
     write_compression: Compression,
     /// The compression algorithm to detect files for
     read_compressions: Vec<Compression>,
+    /// The encryption of new files, if enabled
+    encryption: Option<Encryption>,
 }
 
 const NUM_LEVELS: usize = 2;
@@ -82,9 +91,16 @@ impl FileSystemBackend {
             content,
             write_compression: compression,
             read_compressions,
+            encryption: None,
         })
     }
 
+    /// Encrypt new files, and decrypt encrypted ones.
+    pub fn with_encryption(mut self, encryption: Option<Encryption>) -> Self {
+        self.encryption = encryption;
+        self
+    }
+
     /// Create a new storage for testing
     pub async fn for_test() -> anyhow::Result<(Self, TempDir)> {
         let dir = tempdir()?;
@@ -107,6 +123,27 @@ impl FileSystemBackend {
             .map(|result| (result, dir))
     }
 
+    /// Check if the stored content still matches the digest of the content to be stored.
+    ///
+    /// Content which can't be read (e.g. because it can't be decrypted) counts as mismatch.
+    async fn verify(&self, path: &Path, compression: Compression, result: &StorageResult) -> bool {
+        let digests = async {
+            let file = File::open(path).await?;
+            let reader = decrypt(self.encryption.as_ref(), file).await?;
+            let mut reader = HashingRead::new(compression.reader(reader));
+            copy(&mut reader, &mut sink()).await?;
+            Ok::<_, std::io::Error>(reader.digests())
+        };
+
+        match digests.await {
+            Ok(digests) => digests.sha256.as_ref() == result.digests.sha256.as_ref(),
+            Err(err) => {
+                log::info!("Failed to verify stored content: {}: {err}", path.display());
+                false
+            }
+        }
+    }
+
     async fn locate(
         &self,
         StorageKey(hash): StorageKey,
@@ -136,6 +173,21 @@ impl StorageBackend for FileSystemBackend {
         let mut source = file.reader().await?;
 
         let result = file.to_result();
+
+        // content is stored only once, keyed by its digest
+
+        if let Some((path, compression)) = self.locate(result.key()).await? {
+            if self.verify(&path, compression, &result).await {
+                log::debug!("Already stored: {}", result.key());
+                // storing it again counts as modification, for the grace period of the gc
+                touch(&path).await?;
+                return Ok(result);
+            }
+
+            log::warn!("Replacing corrupted content: {}", result.key());
+            remove_file(&path).await?;
+        }
+
         let key = result.key().to_string();
 
         // create the target path
@@ -146,9 +198,13 @@ impl StorageBackend for FileSystemBackend {
         target.set_extension(self.write_compression.extension());
 
         let mut target = File::create(target).await?;
-        self.write_compression
-            .write(&mut source, &mut target)
-            .await?;
+        encode(
+            &mut source,
+            &mut target,
+            self.write_compression,
+            self.encryption.as_ref(),
+        )
+        .await?;
 
         // ensure we have all bytes on disk for the target file,
         // then close it
@@ -170,13 +226,91 @@ impl StorageBackend for FileSystemBackend {
         key: StorageKey,
     ) -> Result<Option<impl Stream<Item = Result<Bytes, Self::Error>> + use<>>, Self::Error> {
         match self.locate(key).await? {
-            Some((path, compression)) => File::open(&path)
-                .await
-                .map(|f| Some(ReaderStream::new(compression.reader(f)))),
+            Some((path, compression)) => {
+                let file = File::open(&path).await?;
+                let reader = decrypt(self.encryption.as_ref(), file).await?;
+                Ok(Some(ReaderStream::new(compression.reader(reader))))
+            }
             None => Ok(None),
         }
     }
 
+    async fn retrieve_range(
+        &self,
+        key: StorageKey,
+        range: Range<u64>,
+    ) -> Result<Option<impl Stream<Item = Result<Bytes, Self::Error>> + use<>>, Self::Error> {
+        let Some((path, compression)) = self.locate(key).await? else {
+            return Ok(None);
+        };
+
+        let mut file = File::open(&path).await?;
+
+        if compression == Compression::None && self.encryption.is_none() {
+            // the file is the content, so we can skip right to the start of the range
+            file.seek(SeekFrom::Start(range.start)).await?;
+            let reader = file.take(range.end.saturating_sub(range.start));
+            return Ok(Some(ReaderStream::new(reader).boxed()));
+        }
+
+        let reader = decrypt(self.encryption.as_ref(), file).await?;
+        Ok(Some(
+            range::slice(ReaderStream::new(compression.reader(reader)), range).boxed(),
+        ))
+    }
+
+    async fn list(
+        &self,
+    ) -> Result<impl Stream<Item = Result<StorageEntry, Self::Error>> + use<>, Self::Error> {
+        // walk the directory tree, depth first, keeping the directories still to visit
+
+        struct State {
+            current: Option<ReadDir>,
+            pending: Vec<PathBuf>,
+        }
+
+        let state = State {
+            current: None,
+            pending: vec![self.content.clone()],
+        };
+
+        Ok(stream::try_unfold(state, async |mut state| {
+            loop {
+                let Some(current) = &mut state.current else {
+                    match state.pending.pop() {
+                        Some(dir) => state.current = Some(read_dir(dir).await?),
+                        None => return Ok::<_, std::io::Error>(None),
+                    }
+                    continue;
+                };
+
+                let Some(entry) = current.next_entry().await? else {
+                    state.current = None;
+                    continue;
+                };
+
+                let metadata = entry.metadata().await?;
+                if metadata.is_dir() {
+                    state.pending.push(entry.path());
+                    continue;
+                }
+
+                // the file name is the digest, with the extension of the compression
+                let path = entry.path();
+                let Some(key) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                    continue;
+                };
+
+                let entry = StorageEntry {
+                    key: StorageKey(key.to_string()),
+                    size: metadata.len(),
+                    modified: metadata.modified().ok(),
+                };
+                return Ok(Some((entry, state)));
+            }
+        }))
+    }
+
     async fn delete(&self, key: StorageKey) -> Result<(), Self::Error> {
         match self.locate(key).await? {
             Some((path, _)) => remove_file(path).await,
@@ -203,15 +337,24 @@ fn level_dir(base: impl AsRef<Path>, hash: &str, levels: usize) -> PathBuf {
     path
 }
 
+/// Set the modification time of a file to now
+async fn touch(path: &Path) -> IoResult<()> {
+    let file = OpenOptions::new().append(true).open(path).await?;
+    file.into_std().await.set_modified(SystemTime::now())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::service::{
+        StaticKeyProvider,
         dispatch::DispatchBackend,
-        test::{test_read_not_found, test_store_read_and_delete, test_store_read_and_delete_rng},
+        test::{
+            test_read_not_found, test_retrieve_range, test_store_read_and_delete,
+            test_store_read_and_delete_rng,
+        },
     };
     use bytes::BytesMut;
-    use futures::StreamExt;
     use rstest::rstest;
     use sha2::{Digest, Sha256};
     use tempfile::tempdir;
@@ -314,6 +457,145 @@ mod test {
         assert_eq!(content.as_ref(), b"Hello World");
     }
 
+    /// Ensure the content is encrypted on disk, and can be read back.
+    #[test(tokio::test)]
+    #[rstest]
+    #[case::none(Compression::None)]
+    #[case::zstd(Compression::Zstd)]
+    async fn store_encrypted(#[case] compression: Compression) {
+        const DIGEST: &str = "a591a6d40bf420404a011733cfb7b190d62c65bf0bcda32b57b277d9ad9f146e";
+
+        let encryption = Encryption::new(
+            StaticKeyProvider::new(vec![
+                "MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY="
+                    .parse()
+                    .unwrap(),
+            ])
+            .unwrap(),
+        );
+
+        let dir = tempdir().unwrap();
+        let backend = FileSystemBackend::new(dir.path(), compression)
+            .await
+            .unwrap()
+            .with_encryption(Some(encryption));
+
+        let digest = backend
+            .store(&b"Hello World"[..])
+            .await
+            .expect("store must succeed");
+        assert_eq!(digest.key().to_string(), DIGEST);
+
+        let (path, _) = backend.locate(digest.key()).await.unwrap().unwrap();
+        let data = std::fs::read(path).unwrap();
+        assert!(!data.windows(5).any(|w| w == b"Hello"));
+
+        test_store_read_and_delete(backend).await
+    }
+
+    #[test(tokio::test)]
+    async fn list() {
+        use futures::TryStreamExt;
+
+        let (backend, _dir) = backend(Compression::Zstd).await;
+
+        let hello = backend.store(&b"Hello World"[..]).await.unwrap();
+        let bye = backend.store(&b"Bye World"[..]).await.unwrap();
+
+        let mut keys = backend
+            .list()
+            .await
+            .unwrap()
+            .map_ok(|entry| entry.key)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        keys.sort();
+
+        let mut expected = vec![hello.key(), bye.key()];
+        expected.sort();
+        assert_eq!(keys, expected);
+    }
+
+    /// Ensure the same content is only stored once.
+    #[test(tokio::test)]
+    async fn store_once() {
+        let encryption = Encryption::new(
+            StaticKeyProvider::new(vec![
+                "MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY="
+                    .parse()
+                    .unwrap(),
+            ])
+            .unwrap(),
+        );
+
+        let dir = tempdir().unwrap();
+        let backend = FileSystemBackend::new(dir.path(), Compression::None)
+            .await
+            .unwrap()
+            .with_encryption(Some(encryption));
+
+        // as the encryption uses a new data key every time, the content would differ if written again
+
+        let first = backend.store(&b"Hello World"[..]).await.unwrap();
+        let (path, _) = backend.locate(first.key()).await.unwrap().unwrap();
+        let stored = std::fs::read(&path).unwrap();
+
+        let second = backend.store(&b"Hello World"[..]).await.unwrap();
+        assert_eq!(first.key(), second.key());
+        assert_eq!(std::fs::read(&path).unwrap(), stored);
+    }
+
+    /// Ensure corrupted content gets replaced when storing the same content again.
+    #[test(tokio::test)]
+    async fn store_repairs() {
+        let dir = tempdir().unwrap();
+        let backend = FileSystemBackend::new(dir.path(), Compression::None)
+            .await
+            .unwrap();
+
+        let first = backend.store(&b"Hello World"[..]).await.unwrap();
+        let (path, _) = backend.locate(first.key()).await.unwrap().unwrap();
+        std::fs::write(&path, b"Hello Corruption").unwrap();
+
+        backend.store(&b"Hello World"[..]).await.unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"Hello World");
+    }
+
+    /// Ensure storing the same content again refreshes its modification time.
+    #[test(tokio::test)]
+    async fn store_touches() {
+        let dir = tempdir().unwrap();
+        let backend = FileSystemBackend::new(dir.path(), Compression::None)
+            .await
+            .unwrap();
+
+        let first = backend.store(&b"Hello World"[..]).await.unwrap();
+        let (path, _) = backend.locate(first.key()).await.unwrap().unwrap();
+        let past = SystemTime::now() - std::time::Duration::from_secs(3600);
+        std::fs::File::options()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(past)
+            .unwrap();
+
+        backend.store(&b"Hello World"[..]).await.unwrap();
+        let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+        assert!(modified > past);
+    }
+
+    /// Ensure ranges refer to the content, not to the compressed file.
+    #[test(tokio::test)]
+    #[rstest]
+    #[case::none(Compression::None)]
+    #[case::zstd(Compression::Zstd)]
+    async fn retrieve_range(#[case] compression: Compression) {
+        let (backend, _dir) = backend(compression).await;
+
+        test_retrieve_range(backend).await;
+    }
+
     /// Ensure retrieving the information that the file does not exist works.
     #[test(tokio::test)]
     async fn read_not_found() {
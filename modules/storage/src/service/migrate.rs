@@ -0,0 +1,238 @@
+use crate::service::{StorageBackend, StorageKey};
+use futures::{StreamExt, TryStreamExt};
+use hex::ToHex;
+use ring::digest::{Context, SHA256};
+use std::{
+    collections::{BTreeMap, HashSet},
+    io,
+    path::{Path, PathBuf},
+    pin::pin,
+};
+use tokio::{
+    fs::{OpenOptions, read_to_string},
+    io::AsyncWriteExt,
+};
+use tokio_util::io::StreamReader;
+use tracing::instrument;
+
+/// Options for migrating the content of one storage to another
+#[derive(Clone, Debug, Default)]
+pub struct MigrateOptions {
+    /// A file recording the keys which were already migrated.
+    ///
+    /// Blobs recorded in the file are skipped, and newly migrated ones get appended. This allows
+    /// resuming an interrupted migration.
+    pub checkpoint: Option<PathBuf>,
+}
+
+/// The outcome of migrating the content of one storage to another
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize)]
+pub struct MigrateReport {
+    /// The number of blobs found in the source storage
+    pub scanned: u64,
+    /// The number of blobs copied to the target storage
+    pub copied: u64,
+    /// The number of blobs skipped, as they were already migrated, or aren't managed by us
+    pub skipped: u64,
+    /// The blobs which failed to be migrated, with the reason
+    pub errors: BTreeMap<String, String>,
+}
+
+/// Copy all blobs from one storage to another.
+///
+/// The content is stored in the target using the target's own compression and encryption. After
+/// copying a blob, it gets read back from the target, and its digest must match the key it had in
+/// the source. Blobs failing this check are reported as errors.
+///
+/// Blobs with a key which isn't a SHA256 digest are skipped, as they aren't managed by us.
+#[instrument(skip(source, target), err)]
+pub async fn migrate<S, T>(
+    source: &S,
+    target: &T,
+    options: &MigrateOptions,
+) -> anyhow::Result<MigrateReport>
+where
+    S: StorageBackend,
+    S::Error: Into<anyhow::Error>,
+    T: StorageBackend,
+{
+    let mut done = match &options.checkpoint {
+        Some(checkpoint) => Checkpoint::open(checkpoint).await?,
+        None => Checkpoint::default(),
+    };
+
+    let mut entries = pin!(source.list().await.map_err(Into::<anyhow::Error>::into)?);
+    let mut report = MigrateReport::default();
+
+    while let Some(entry) = entries
+        .try_next()
+        .await
+        .map_err(Into::<anyhow::Error>::into)?
+    {
+        report.scanned += 1;
+
+        let key = entry.key.to_string();
+        if !entry.key.is_sha256() || done.contains(&key) {
+            report.skipped += 1;
+            continue;
+        }
+
+        match copy(source, target, entry.key).await {
+            Ok(()) => {
+                log::debug!("Migrated: {key}");
+                done.record(key).await?;
+                report.copied += 1;
+            }
+            Err(err) => {
+                log::warn!("Failed to migrate {key}: {err}");
+                report.errors.insert(key, err);
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Copy a single blob, verifying the outcome.
+async fn copy<S, T>(source: &S, target: &T, key: StorageKey) -> Result<(), String>
+where
+    S: StorageBackend,
+    T: StorageBackend,
+{
+    let Some(stream) = source
+        .retrieve(key.clone())
+        .await
+        .map_err(|err| format!("failed to retrieve: {err}"))?
+    else {
+        return Err("vanished from the source".into());
+    };
+
+    let reader = StreamReader::new(stream.map_err(|err| io::Error::other(err.to_string())));
+    let result = target
+        .store(pin!(reader))
+        .await
+        .map_err(|err| format!("failed to store: {err}"))?;
+
+    if result.key() != key {
+        return Err(format!("content has a digest of {}", result.key()));
+    }
+
+    // read back what was stored
+
+    let Some(stream) = target
+        .retrieve(key.clone())
+        .await
+        .map_err(|err| format!("failed to verify: {err}"))?
+    else {
+        return Err("missing from the target".into());
+    };
+
+    let mut stream = pin!(stream);
+    let mut context = Context::new(&SHA256);
+    while let Some(chunk) = stream.next().await {
+        context.update(&chunk.map_err(|err| format!("failed to verify: {err}"))?);
+    }
+
+    let digest: String = context.finish().encode_hex();
+    match digest == key.to_string() {
+        true => Ok(()),
+        false => Err(format!("stored content has a digest of {digest}")),
+    }
+}
+
+/// The keys of blobs already migrated, backed by a file
+#[derive(Default)]
+struct Checkpoint {
+    file: Option<tokio::fs::File>,
+    keys: HashSet<String>,
+}
+
+impl Checkpoint {
+    async fn open(path: &Path) -> io::Result<Self> {
+        let keys = match read_to_string(path).await {
+            Ok(content) => content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(ToString::to_string)
+                .collect(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => HashSet::new(),
+            Err(err) => return Err(err),
+        };
+
+        if !keys.is_empty() {
+            log::info!("Resuming migration, skipping {} blobs", keys.len());
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?;
+
+        Ok(Self {
+            file: Some(file),
+            keys,
+        })
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        self.keys.contains(key)
+    }
+
+    async fn record(&mut self, key: String) -> io::Result<()> {
+        if let Some(file) = &mut self.file {
+            file.write_all(format!("{key}\n").as_bytes()).await?;
+            file.flush().await?;
+        }
+        self.keys.insert(key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::service::{Compression, fs::FileSystemBackend};
+    use tempfile::tempdir;
+    use test_log::test;
+
+    #[test(tokio::test)]
+    async fn migrate_fs() {
+        let source_dir = tempdir().unwrap();
+        let source = FileSystemBackend::new(source_dir.path(), Compression::Zstd)
+            .await
+            .unwrap();
+        let target_dir = tempdir().unwrap();
+        let target = FileSystemBackend::new(target_dir.path(), Compression::None)
+            .await
+            .unwrap();
+
+        let mut keys = vec![];
+        for content in ["Hello World", "Hello Trustify", "Hello Storage"] {
+            keys.push(source.store(content.as_bytes()).await.unwrap().key());
+        }
+
+        let checkpoint = tempdir().unwrap();
+        let options = MigrateOptions {
+            checkpoint: Some(checkpoint.path().join("checkpoint")),
+        };
+
+        let report = migrate(&source, &target, &options).await.unwrap();
+        assert_eq!(report.scanned, 3);
+        assert_eq!(report.copied, 3);
+        assert!(report.errors.is_empty());
+
+        for key in keys {
+            assert!(target.retrieve(key).await.unwrap().is_some());
+        }
+
+        // resume, with one more blob to migrate
+        source.store(&b"Hello Again"[..]).await.unwrap();
+
+        let report = migrate(&source, &target, &options).await.unwrap();
+        assert_eq!(report.scanned, 4);
+        assert_eq!(report.copied, 1);
+        assert_eq!(report.skipped, 3);
+    }
+}
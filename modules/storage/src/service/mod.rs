@@ -1,13 +1,20 @@
+pub mod cache;
 pub mod dispatch;
 pub mod fs;
+pub mod migrate;
 pub mod s3;
 
 mod test;
 
 mod compression;
+mod encryption;
+mod range;
 mod temp;
 
 pub use compression::Compression;
+pub use encryption::{
+    Encryption, EncryptionError, KeyProvider, MasterKey, StaticKeyProvider, WrappedKey,
+};
 
 use crate::service::fs::FileSystemBackend;
 use bytes::Bytes;
@@ -16,6 +23,8 @@ use hex::ToHex;
 use std::collections::HashMap;
 use std::fmt::{Debug, Display, Formatter};
 use std::future::Future;
+use std::ops::Range;
+use std::time::SystemTime;
 use tokio::io::AsyncRead;
 use trustify_common::hashing::Digests;
 use trustify_common::id::Id;
@@ -49,6 +58,11 @@ impl StorageKey {
     pub fn from_sha256(digest: &str) -> Self {
         Self(digest.into())
     }
+
+    /// Check if the key is a SHA256 digest, like the keys of all content we store.
+    pub fn is_sha256(&self) -> bool {
+        self.0.len() == 64 && self.0.chars().all(|c| c.is_ascii_hexdigit())
+    }
 }
 
 impl Display for StorageKey {
@@ -105,6 +119,16 @@ impl StorageResult {
     }
 }
 
+/// A blob held by the storage
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StorageEntry {
+    pub key: StorageKey,
+    /// The size of the blob in the storage, which might be compressed and encrypted
+    pub size: u64,
+    /// The time the blob was last modified, if known
+    pub modified: Option<SystemTime>,
+}
+
 pub trait StorageBackend {
     type Error: Debug + Display;
 
@@ -127,6 +151,41 @@ pub trait StorageBackend {
         >,
     > + Send;
 
+    /// Retrieve a range of the content as an async reader.
+    ///
+    /// The range refers to the content as it was stored, not to the blob held by the storage,
+    /// which might be compressed or encrypted. A range reaching beyond the end of the content
+    /// gets truncated.
+    fn retrieve_range(
+        &self,
+        key: StorageKey,
+        range: Range<u64>,
+    ) -> impl Future<
+        Output = Result<
+            Option<impl Stream<Item = Result<Bytes, Self::Error>> + Send + use<Self>>,
+            Self::Error,
+        >,
+    > {
+        async move {
+            Ok(self
+                .retrieve(key)
+                .await?
+                .map(|stream| range::slice(stream, range)))
+        }
+    }
+
+    /// List all stored blobs.
+    ///
+    /// The order of the entries is not specified.
+    fn list(
+        &self,
+    ) -> impl Future<
+        Output = Result<
+            impl Stream<Item = Result<StorageEntry, Self::Error>> + Send + use<Self>,
+            Self::Error,
+        >,
+    > + Send;
+
     /// Delete the stored content.
     ///
     /// This operation MUST be idempotent: deleting a non-existent key should succeed
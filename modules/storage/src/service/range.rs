@@ -0,0 +1,68 @@
+use bytes::Bytes;
+use futures::{Stream, TryStreamExt, stream};
+use std::ops::Range;
+
+/// Limit a stream of content to a range of it.
+///
+/// Chunks before the range are skipped, and the stream ends once the end of the range is reached,
+/// without consuming the rest of it.
+pub fn slice<S, E>(stream: S, range: Range<u64>) -> impl Stream<Item = Result<Bytes, E>>
+where
+    S: Stream<Item = Result<Bytes, E>>,
+{
+    let Range { start, end } = range;
+
+    stream::try_unfold(
+        (Box::pin(stream), 0u64),
+        move |(mut stream, mut offset)| async move {
+            while offset < end {
+                let Some(chunk) = stream.try_next().await? else {
+                    break;
+                };
+
+                let chunk_start = offset;
+                offset += chunk.len() as u64;
+
+                let from = start.clamp(chunk_start, offset) - chunk_start;
+                let to = end.clamp(chunk_start, offset) - chunk_start;
+                if from < to {
+                    return Ok(Some((
+                        chunk.slice(from as usize..to as usize),
+                        (stream, offset),
+                    )));
+                }
+            }
+
+            Ok(None)
+        },
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0..10, "0123456789")]
+    #[case(0..3, "012")]
+    #[case(2..5, "234")]
+    #[case(3..4, "3")]
+    #[case(4..8, "4567")]
+    #[case(7..100, "789")]
+    #[case(10..20, "")]
+    #[case(5..5, "")]
+    #[tokio::test]
+    async fn slice_chunks(#[case] range: Range<u64>, #[case] expected: &str) {
+        let chunks = ["012", "3", "", "456", "789"]
+            .into_iter()
+            .map(|chunk| Ok::<_, std::io::Error>(Bytes::from(chunk)));
+
+        let result: Vec<Bytes> = slice(stream::iter(chunks), range)
+            .try_collect()
+            .await
+            .expect("must not fail");
+
+        assert_eq!(result.concat(), expected.as_bytes());
+    }
+}
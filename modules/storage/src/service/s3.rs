@@ -1,8 +1,12 @@
 use crate::{
     config::S3Config,
     service::{
-        DeleteManyError, StorageBackend, StorageKey, StorageResult, StoreError,
-        compression::Compression, temp::TempFile,
+        DeleteManyError, StorageBackend, StorageEntry, StorageKey, StorageResult, StoreError,
+        cache::DiskCache,
+        compression::Compression,
+        encryption::{Encryption, decrypt},
+        range,
+        temp::TempFile,
     },
 };
 use anyhow::{Context, anyhow, bail};
@@ -13,15 +17,18 @@ use aws_sdk_s3::{
         self, Credentials, Region, SharedHttpClient,
         endpoint::{EndpointFuture, Params, ResolveEndpoint},
     },
-    operation::get_object::GetObjectError,
+    operation::{
+        get_object::GetObjectError, head_object::HeadObjectError,
+        list_objects_v2::ListObjectsV2Output,
+    },
     primitives::FsBuilder,
-    types::{Delete, ObjectIdentifier},
+    types::{Delete, Object, ObjectIdentifier},
 };
 use aws_smithy_http_client::tls::{Provider, TlsContext, TrustStore, rustls_provider::CryptoMode};
 use aws_smithy_types::endpoint::Endpoint;
 use bytes::Bytes;
-use futures::{Stream, TryStreamExt};
-use std::{fmt::Debug, io, str::FromStr};
+use futures::{Stream, StreamExt, TryStreamExt, stream};
+use std::{fmt::Debug, io, ops::Range, str::FromStr, time::SystemTime};
 use tokio::{fs, io::AsyncRead};
 use tokio_util::io::ReaderStream;
 use tracing::instrument;
@@ -61,6 +68,8 @@ pub struct S3Backend {
     client: Client,
     bucket: String,
     compression: Compression,
+    encryption: Option<Encryption>,
+    cache: Option<DiskCache>,
 }
 
 impl S3Backend {
@@ -141,8 +150,22 @@ impl S3Backend {
             client,
             bucket: bucket.unwrap_or_default(),
             compression,
+            encryption: None,
+            cache: None,
         })
     }
+
+    /// Encrypt new objects, and decrypt encrypted ones.
+    pub fn with_encryption(mut self, encryption: Option<Encryption>) -> Self {
+        self.encryption = encryption;
+        self
+    }
+
+    /// Keep retrieved objects in a local cache, serving repeated reads from it.
+    pub fn with_cache(mut self, cache: Option<DiskCache>) -> Self {
+        self.cache = cache;
+        self
+    }
 }
 
 impl StorageBackend for S3Backend {
@@ -153,9 +176,14 @@ impl StorageBackend for S3Backend {
     where
         S: AsyncRead + Unpin + Send,
     {
-        let file = TempFile::with_compression(stream, self.compression).await?;
+        let file =
+            TempFile::with_encoding(stream, self.compression, self.encryption.as_ref()).await?;
         let result = file.to_result();
 
+        // content is stored only once, keyed by its digest. Storing it again replaces the object
+        // with the same content, which repairs a corrupted object and refreshes its modification
+        // time for the grace period of the gc.
+
         self.client
             .put_object()
             .bucket(&self.bucket)
@@ -176,6 +204,11 @@ impl StorageBackend for S3Backend {
             .await
             .map_err(|err| Error::S3(err.into()))?;
 
+        // drop a cached copy, which might be corrupted too
+        if let Some(cache) = &self.cache {
+            cache.remove(&result.key().to_string()).await?;
+        }
+
         Ok(result)
     }
 
@@ -183,23 +216,33 @@ impl StorageBackend for S3Backend {
         &self,
         StorageKey(key): StorageKey,
     ) -> Result<Option<impl Stream<Item = Result<Bytes, Self::Error>> + use<>>, Self::Error> {
+        if let Some(cache) = &self.cache
+            && let Some((file, compression)) = cache.get(&key).await?
+        {
+            log::debug!("Serving from cache: {key}");
+            let reader = decrypt(self.encryption.as_ref(), file).await?;
+            return Ok(Some(
+                ReaderStream::new(compression.reader(reader)).map_err(Error::Io),
+            ));
+        }
+
         let req = self.client.get_object().bucket(&self.bucket).key(&key);
 
         match req.send().await {
             Ok(resp) => {
-                let content_encoding = resp.content_encoding().and_then(cleanup);
-                log::debug!("Content encoding: {content_encoding:?}");
-
-                let compression = match content_encoding {
-                    Some(encoding) => Compression::from_str(&encoding).inspect_err(|_| {
-                        log::warn!("Content encoding: '{encoding}' not supported")
-                    })?,
-                    None => Compression::None,
+                let compression = content_compression(resp.content_encoding())?;
+
+                let body = resp.body.into_async_read();
+                let reader = match &self.cache {
+                    Some(cache) => {
+                        let file = cache.insert(&key, compression, body).await?;
+                        decrypt(self.encryption.as_ref(), file).await?
+                    }
+                    None => decrypt(self.encryption.as_ref(), body).await?,
                 };
 
                 Ok(Some(
-                    ReaderStream::new(compression.reader(resp.body.into_async_read()))
-                        .map_err(Error::Io),
+                    ReaderStream::new(compression.reader(reader)).map_err(Error::Io),
                 ))
             }
             Err(err) => match err.into_service_error() {
@@ -209,7 +252,101 @@ impl StorageBackend for S3Backend {
         }
     }
 
+    async fn retrieve_range(
+        &self,
+        StorageKey(key): StorageKey,
+        range: Range<u64>,
+    ) -> Result<Option<impl Stream<Item = Result<Bytes, Self::Error>> + use<>>, Self::Error> {
+        if let Some(cache) = &self.cache
+            && let Some((file, compression)) = cache.get(&key).await?
+        {
+            log::debug!("Serving range from cache: {key}");
+            let reader = decrypt(self.encryption.as_ref(), file).await?;
+            let stream = ReaderStream::new(compression.reader(reader)).map_err(Error::Io);
+            return Ok(Some(range::slice(stream, range).boxed()));
+        }
+
+        // Adding the object to the cache would require fetching all of it. So a range is only
+        // served from the cache, but never populates it.
+
+        let head = match self
+            .client
+            .head_object()
+            .bucket(&self.bucket)
+            .key(&key)
+            .send()
+            .await
+        {
+            Ok(head) => head,
+            Err(err) => {
+                return match err.into_service_error() {
+                    HeadObjectError::NotFound(_) => Ok(None),
+                    err => Err(Error::S3(err.into())),
+                };
+            }
+        };
+
+        let compression = content_compression(head.content_encoding())?;
+        let mut req = self.client.get_object().bucket(&self.bucket).key(&key);
+
+        let plain = compression == Compression::None && self.encryption.is_none();
+        if plain {
+            // the object is the content, so only the range itself needs to be fetched
+            let size = head.content_length().unwrap_or_default().max(0) as u64;
+            let end = range.end.min(size);
+            if range.start >= end {
+                return Ok(Some(stream::empty::<Result<Bytes, Error>>().boxed()));
+            }
+            req = req.range(format!("bytes={}-{}", range.start, end - 1));
+        }
+
+        match req.send().await {
+            Ok(resp) if plain => Ok(Some(
+                ReaderStream::new(resp.body.into_async_read())
+                    .map_err(Error::Io)
+                    .boxed(),
+            )),
+            Ok(resp) => {
+                // decode from the start, but stop reading once the end of the range is reached
+                let reader = decrypt(self.encryption.as_ref(), resp.body.into_async_read()).await?;
+                let stream = ReaderStream::new(compression.reader(reader)).map_err(Error::Io);
+                Ok(Some(range::slice(stream, range).boxed()))
+            }
+            Err(err) => match err.into_service_error() {
+                GetObjectError::NoSuchKey(_) => Ok(None),
+                err => Err(Error::S3(err.into())),
+            },
+        }
+    }
+
+    async fn list(
+        &self,
+    ) -> Result<impl Stream<Item = Result<StorageEntry, Self::Error>> + use<>, Self::Error> {
+        let pages = self
+            .client
+            .list_objects_v2()
+            .bucket(&self.bucket)
+            .into_paginator()
+            .send();
+
+        let pages = stream::try_unfold(pages, async |mut pages| match pages.next().await {
+            Some(page) => Ok(Some((page.map_err(|err| Error::S3(err.into()))?, pages))),
+            None => Ok::<_, Error>(None),
+        });
+
+        Ok(pages
+            .map_ok(|page: ListObjectsV2Output| {
+                let entries = page.contents.unwrap_or_default().into_iter();
+                stream::iter(entries.filter_map(entry).map(Ok::<_, Error>))
+            })
+            .try_flatten())
+    }
+
     async fn delete(&self, StorageKey(key): StorageKey) -> Result<(), Self::Error> {
+        if let Some(cache) = &self.cache {
+            cache.remove(&key).await?;
+        }
+
         let req = self.client.delete_object().bucket(&self.bucket).key(&key);
         match req.send().await {
             Ok(_) => Ok(()),
@@ -222,6 +359,15 @@ impl StorageBackend for S3Backend {
             return Ok(());
         }
 
+        if let Some(cache) = &self.cache {
+            for StorageKey(key) in keys {
+                cache
+                    .remove(key)
+                    .await
+                    .map_err(|err| DeleteManyError::Generic(Error::Io(err)))?;
+            }
+        }
+
         // From official AWS S3 SDK examples
         //
         // Push into a mut vector to use `?` early return errors while building object keys.
@@ -250,6 +396,29 @@ impl StorageBackend for S3Backend {
     }
 }
 
+/// Convert a listed object into an entry, skipping objects without a key.
+fn entry(object: Object) -> Option<StorageEntry> {
+    Some(StorageEntry {
+        key: StorageKey(object.key?),
+        size: object.size.unwrap_or_default().max(0) as u64,
+        modified: object
+            .last_modified
+            .and_then(|modified| SystemTime::try_from(modified).ok()),
+    })
+}
+
+/// The compression of an object, from its content encoding
+fn content_compression(content_encoding: Option<&str>) -> Result<Compression, strum::ParseError> {
+    let content_encoding = content_encoding.and_then(cleanup);
+    log::debug!("Content encoding: {content_encoding:?}");
+
+    Ok(match content_encoding {
+        Some(encoding) => Compression::from_str(&encoding)
+            .inspect_err(|_| log::warn!("Content encoding: '{encoding}' not supported"))?,
+        None => Compression::None,
+    })
+}
+
 /// Cleanup the encoding header returned by the S3 storage.
 ///
 /// Today, this removes the `aws-chunked` encoding, which should not be present in the metadata, but
@@ -292,7 +461,10 @@ mod test {
     use super::*;
     use crate::service::{
         dispatch::DispatchBackend,
-        test::{test_read_not_found, test_store_read_and_delete, test_store_read_and_delete_rng},
+        test::{
+            test_read_not_found, test_retrieve_range, test_store_read_and_delete,
+            test_store_read_and_delete_rng,
+        },
     };
     use rstest::rstest;
     use std::fmt::Write;
@@ -368,6 +540,18 @@ mod test {
         log::info!("test finished: {compression}");
     }
 
+    /// Ensure ranges refer to the content, fetching only the range of uncompressed objects.
+    #[test(tokio::test)]
+    #[rstest]
+    #[case::none(Compression::None)]
+    #[case::zstd(Compression::Zstd)]
+    #[cfg_attr(not(feature = "_test-s3"), ignore = "requires minio or s3")]
+    async fn retrieve_range(#[case] compression: Compression) {
+        let backend = backend(compression).await;
+
+        test_retrieve_range(backend).await;
+    }
+
     /// Ensure retrieving the information that the file does not exist works.
     #[test(tokio::test)]
     #[cfg_attr(not(feature = "_test-s3"), ignore = "requires minio or s3")]
@@ -6,7 +6,10 @@ use tokio::{
 };
 use trustify_common::hashing::{Digests, HashingRead};
 
-use super::{Compression, StorageResult};
+use super::{
+    Compression, StorageResult,
+    encryption::{Encryption, encode},
+};
 
 pub struct TempFile {
     file: File,
@@ -29,12 +32,26 @@ impl TempFile {
     /// The file will have the content of the reader, compressed using the provided algorithm. The
     /// digest however, will be from the original (uncompressed) payload.
     pub async fn with_compression<S>(stream: S, compression: Compression) -> Result<Self, Error>
+    where
+        S: AsyncRead + Unpin,
+    {
+        Self::with_encoding(stream, compression, None).await
+    }
+
+    /// Create a new temp file with compressed, and optionally encrypted, payload.
+    ///
+    /// The digest will be from the original (plain) payload.
+    pub async fn with_encoding<S>(
+        stream: S,
+        compression: Compression,
+        encryption: Option<&Encryption>,
+    ) -> Result<Self, Error>
     where
         S: AsyncRead + Unpin,
     {
         let mut file = File::from(tempfile()?);
         let mut reader = HashingRead::new(stream);
-        compression.write(&mut reader, &mut file).await?;
+        encode(&mut reader, &mut file, compression, encryption).await?;
         let digests = reader.digests();
 
         Ok(Self { file, digests })
@@ -68,6 +68,33 @@ async fn test_store_read_and_delete_with_data<B: StorageBackend>(backend: &B, co
         .expect("delete should be idempotent");
 }
 
+/// Ensure ranges refer to the content, and get truncated at its end
+pub async fn test_retrieve_range<B: StorageBackend>(backend: B) {
+    let digest = backend
+        .store(&b"Hello World"[..])
+        .await
+        .expect("store must succeed");
+
+    for (range, expected) in [
+        (0..5, &b"Hello"[..]),
+        (6..100, b"World"),
+        (4..7, b"o W"),
+        (11..20, b""),
+        (3..3, b""),
+    ] {
+        let read = backend
+            .retrieve_range(digest.key(), range.clone())
+            .await
+            .expect("retrieve must succeed")
+            .expect("must be found")
+            .try_collect::<BytesMut>()
+            .await
+            .expect("read must succeed");
+
+        assert_eq!(read.as_ref(), expected, "range: {range:?}");
+    }
+}
+
 pub async fn test_read_not_found<B: StorageBackend>(backend: B) {
     const DIGEST: &str = "a591a6d40bf420404a011733cfb7b190d62c65bf0bcda32b57b277d9ad9f146e";
 
@@ -3,9 +3,10 @@ use crate::embedded_oidc;
 
 use crate::{endpoints, profile::spawn_db_check, sample_data};
 use actix_web::web;
+use anyhow::Context;
 use bytesize::ByteSize;
 use futures::FutureExt;
-use std::{env, process::ExitCode, sync::Arc};
+use std::{env, path::PathBuf, process::ExitCode, sync::Arc};
 use trustify_auth::{
     auth::AuthConfigArguments,
     authenticator::Authenticator,
@@ -32,7 +33,15 @@ use trustify_infrastructure::{
     otel::{Metrics as OtelMetrics, Tracing},
 };
 use trustify_module_analysis::{config::AnalysisConfig, service::AnalysisService};
-use trustify_module_ingestor::graph::Graph;
+use trustify_module_importer::model::SecretKey;
+use trustify_module_ingestor::{
+    graph::Graph,
+    service::{
+        limits::Limits,
+        quota::{self, Quotas},
+        signature::TrustRoot,
+    },
+};
 use trustify_module_storage::{config::StorageConfig, service::dispatch::DispatchBackend};
 use trustify_module_ui::{UI, endpoints::UiResources};
 use utoipa::openapi::{Info, License};
@@ -92,6 +101,32 @@ pub struct Run {
     )]
     pub scan_limit: BinaryByteSize,
 
+    /// The maximum size of a single document when ingesting it, uncompressed.
+    #[arg(
+        long,
+        env = "TRUSTD_INGEST_MAX_DOCUMENT_SIZE",
+        default_value_t = default::ingest_max_document_size()
+    )]
+    pub ingest_max_document_size: BinaryByteSize,
+
+    /// The maximum nesting depth of a JSON document when ingesting it.
+    #[arg(
+        long,
+        env = "TRUSTD_INGEST_MAX_JSON_DEPTH",
+        default_value_t = Limits::DEFAULT_MAX_DEPTH
+    )]
+    pub ingest_max_json_depth: usize,
+
+    /// The maximum time parsing and loading a single document may take (humantime, e.g. "30m").
+    #[arg(long, env = "TRUSTD_INGEST_MAX_PARSE_DURATION", default_value = "30m")]
+    pub ingest_max_parse_duration: humantime::Duration,
+
+    /// The key for encrypting the secrets of importer configurations, a base64 encoded 256-bit key.
+    ///
+    /// Required for storing configurations carrying secrets, like git credentials.
+    #[arg(long, id = "importer_secret_key", env = "IMPORTER_SECRET_KEY")]
+    pub secret_key: Option<SecretKey>,
+
     // flattened commands must go last
     //
     /// Analysis configuration
@@ -127,6 +162,12 @@ pub struct Run {
 
     #[command(flatten)]
     pub ui: UiConfig,
+
+    #[command(flatten)]
+    pub quota: QuotaConfig,
+
+    #[command(flatten)]
+    pub signature: SignatureConfig,
 }
 
 mod default {
@@ -148,6 +189,109 @@ mod default {
     pub const fn scan_limit() -> BinaryByteSize {
         BinaryByteSize(ByteSize::gib(1))
     }
+
+    pub const fn ingest_max_document_size() -> BinaryByteSize {
+        BinaryByteSize(ByteSize::gib(1))
+    }
+}
+
+/// Storage quotas of tenants, identified by the value of a label.
+#[derive(clap::Args, Debug, Clone)]
+#[command(next_help_heading = "Quotas")]
+pub struct QuotaConfig {
+    /// The label identifying the tenant of a document
+    #[arg(
+        id = "quota-label",
+        long,
+        env = "TRUSTD_QUOTA_LABEL",
+        default_value = quota::DEFAULT_LABEL
+    )]
+    pub label: String,
+
+    /// The storage quota of tenants without an explicit one, uncompressed
+    #[arg(id = "quota-default", long, env = "TRUSTD_QUOTA_DEFAULT")]
+    pub default: Option<BinaryByteSize>,
+
+    /// The storage quotas of individual tenants, like `tenant=10GiB`
+    #[arg(
+        id = "quota",
+        long,
+        env = "TRUSTD_QUOTAS",
+        value_delimiter = ',',
+        value_parser = parse_quota
+    )]
+    pub quotas: Vec<(String, BinaryByteSize)>,
+}
+
+impl QuotaConfig {
+    /// The configured quotas, if there are any
+    fn into_quotas(self) -> Option<Quotas> {
+        if self.default.is_none() && self.quotas.is_empty() {
+            return None;
+        }
+
+        Some(Quotas {
+            label: self.label,
+            default: self.default.map(|size| size.0.as_u64()),
+            limits: self
+                .quotas
+                .into_iter()
+                .map(|(tenant, size)| (tenant, size.0.as_u64()))
+                .collect(),
+        })
+    }
+}
+
+/// Trust roots for verifying signatures of uploaded documents.
+#[derive(clap::Args, Debug, Clone)]
+#[command(next_help_heading = "Signatures")]
+pub struct SignatureConfig {
+    /// PEM files with the certificates of authorities issuing signing certificates, like Fulcio
+    #[arg(
+        id = "signature-trust-root",
+        long,
+        env = "TRUSTD_SIGNATURE_TRUST_ROOTS",
+        value_delimiter = ','
+    )]
+    pub trust_roots: Vec<PathBuf>,
+
+    /// PEM files with the public keys of transparency logs, like Rekor
+    #[arg(
+        id = "signature-log-key",
+        long,
+        env = "TRUSTD_SIGNATURE_LOG_KEYS",
+        value_delimiter = ','
+    )]
+    pub log_keys: Vec<PathBuf>,
+}
+
+impl SignatureConfig {
+    /// Load the configured trust root, rejecting all signatures if there is none
+    fn into_trust_root(self) -> anyhow::Result<TrustRoot> {
+        let read = |path: &PathBuf| {
+            std::fs::read(path).with_context(|| format!("failed to read: {}", path.display()))
+        };
+
+        let mut trust_root = TrustRoot::new();
+        for path in &self.trust_roots {
+            trust_root = trust_root
+                .add_certificates(&read(path)?)
+                .with_context(|| format!("failed to load trust root: {}", path.display()))?;
+        }
+        for path in &self.log_keys {
+            trust_root = trust_root
+                .add_log_key(&read(path)?)
+                .with_context(|| format!("failed to load log key: {}", path.display()))?;
+        }
+        Ok(trust_root)
+    }
+}
+
+fn parse_quota(value: &str) -> Result<(String, BinaryByteSize), String> {
+    let (tenant, size) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected `<tenant>=<size>`, got: {value}"))?;
+    Ok((tenant.into(), size.parse()?))
 }
 
 #[derive(clap::Args, Debug, Clone)]
@@ -200,6 +344,7 @@ struct InitData {
 #[derive(Clone, Default)]
 pub(crate) struct ModuleConfig {
     fundamental: trustify_module_fundamental::endpoints::Config,
+    importer: trustify_module_importer::endpoints::Config,
     ingestor: trustify_module_ingestor::endpoints::Config,
     ui: trustify_module_ui::endpoints::Config,
 }
@@ -284,14 +429,32 @@ impl InitData {
             oidc_load_user: run.ui.load_user.to_string(),
         };
 
+        let limits = Limits {
+            max_bytes: run.ingest_max_document_size.into(),
+            max_depth: run.ingest_max_json_depth,
+            max_duration: run.ingest_max_parse_duration.into(),
+        };
+        let quotas = run.quota.into_quotas();
+        let trust_root = run.signature.into_trust_root()?;
+
         let config = ModuleConfig {
             fundamental: trustify_module_fundamental::endpoints::Config {
                 sbom_upload_limit: run.sbom_upload_limit.into(),
                 advisory_upload_limit: run.advisory_upload_limit.into(),
                 max_group_name_length: run.max_group_name_length,
+                limits,
+                loaders: Default::default(),
+                quotas: quotas.clone(),
+                trust_root,
+            },
+            importer: trustify_module_importer::endpoints::Config {
+                secret_key: run.secret_key,
             },
             ingestor: trustify_module_ingestor::endpoints::Config {
                 dataset_entry_limit: run.dataset_entry_limit.into(),
+                limits,
+                loaders: Default::default(),
+                quotas,
             },
             ui: trustify_module_ui::endpoints::Config {
                 scan_limit: run.scan_limit.into(),
@@ -398,6 +561,7 @@ pub(crate) fn configure(svc: &mut utoipa_actix_web::service_config::ServiceConfi
         config:
             ModuleConfig {
                 ingestor,
+                importer,
                 fundamental,
                 ui,
             },
@@ -425,7 +589,12 @@ pub(crate) fn configure(svc: &mut utoipa_actix_web::service_config::ServiceConfi
         utoipa_actix_web::scope("/api")
             .map(|scope| scope.wrap(new_auth(auth)))
             .configure(|svc| {
-                trustify_module_importer::endpoints::configure(svc, db_rw.clone(), cache.clone());
+                trustify_module_importer::endpoints::configure(
+                    svc,
+                    importer,
+                    db_rw.clone(),
+                    cache.clone(),
+                );
                 trustify_module_ingestor::endpoints::configure(
                     svc,
                     ingestor,
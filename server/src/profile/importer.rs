@@ -1,6 +1,7 @@
 use crate::profile::spawn_db_check;
 use futures::FutureExt;
-use std::{path::PathBuf, process::ExitCode};
+use std::{path::PathBuf, process::ExitCode, time::Duration};
+use tokio::time::MissedTickBehavior;
 use trustify_common::{
     config::Database,
     db::{
@@ -9,7 +10,15 @@ use trustify_common::{
     },
 };
 use trustify_infrastructure::{Infrastructure, InfrastructureConfig, InitContext};
-use trustify_module_importer::server::importer;
+use trustify_module_fundamental::{
+    retention::{RetentionPolicy, RetentionService},
+    source_document::scrub,
+};
+use trustify_module_importer::{
+    model::{Proxy, SecretKey},
+    runner::common::lock::ImporterLock,
+    server::importer,
+};
 use trustify_module_storage::{config::StorageConfig, service::dispatch::DispatchBackend};
 
 /// Run the importer server
@@ -32,8 +41,36 @@ pub struct Run {
     )]
     pub concurrency: usize,
 
+    /// A proxy for reaching the sources of the importers, like `http://proxy.example.com:3128`
+    #[arg(long, id = "importer_proxy", env = "IMPORTER_PROXY")]
+    pub proxy: Option<String>,
+
+    /// Hosts which the importers reach directly, without the proxy
+    #[arg(
+        long,
+        id = "importer_no_proxy",
+        env = "IMPORTER_NO_PROXY",
+        value_delimiter = ','
+    )]
+    pub no_proxy: Vec<String>,
+
+    /// The key for encrypting the secrets of importer configurations, a base64 encoded 256-bit key.
+    ///
+    /// Required for storing configurations carrying secrets, like git credentials.
+    #[arg(long, id = "importer_secret_key", env = "IMPORTER_SECRET_KEY")]
+    pub secret_key: Option<SecretKey>,
+
+    /// The interval for re-hashing all stored documents, reporting missing and corrupted ones
+    /// (humantime, e.g. "24h")
+    #[arg(long, env = "SCRUB_INTERVAL")]
+    pub scrub_interval: Option<humantime::Duration>,
+
     // flattened commands must go last
     //
+    /// Retention policies for ingested documents
+    #[command(flatten)]
+    pub retention: RetentionConfig,
+
     /// Pagination configuration
     #[command(flatten)]
     pub pagination: PaginationConfig,
@@ -50,6 +87,50 @@ pub struct Run {
     pub infra: InfrastructureConfig,
 }
 
+/// CLI/env configuration for the retention of ingested documents.
+///
+/// Without any rule configured, no documents get deleted.
+#[derive(clap::Args, Debug, Clone)]
+#[command(next_help_heading = "Retention")]
+pub struct RetentionConfig {
+    /// Delete advisory revisions, once they were superseded for this many days
+    #[arg(long, env = "RETENTION_ADVISORY_DAYS")]
+    pub retention_advisory_days: Option<u64>,
+
+    /// Labels identifying SBOMs of the same product and version, keeping only the latest ones
+    #[arg(long, env = "RETENTION_SBOM_LABELS", value_delimiter = ',')]
+    pub retention_sbom_labels: Vec<String>,
+
+    /// The number of SBOMs to keep for the same values of the retention labels, at least one
+    #[arg(
+        long,
+        env = "RETENTION_SBOM_KEEP",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    pub retention_sbom_keep: u32,
+
+    /// Only report the documents which would get deleted
+    #[arg(long, env = "RETENTION_DRY_RUN")]
+    pub retention_dry_run: bool,
+
+    /// The interval for applying the retention policies (humantime, e.g. "1h")
+    #[arg(long, env = "RETENTION_INTERVAL", default_value = "1h")]
+    pub retention_interval: humantime::Duration,
+}
+
+impl RetentionConfig {
+    fn policy(&self) -> RetentionPolicy {
+        RetentionPolicy {
+            superseded_advisories: self
+                .retention_advisory_days
+                .map(|days| Duration::from_secs(days * 24 * 60 * 60)),
+            sbom_labels: self.retention_sbom_labels.clone(),
+            sbom_keep: self.retention_sbom_keep,
+        }
+    }
+}
+
 const SERVICE_ID: &str = "trustify-importer";
 
 struct InitData {
@@ -57,8 +138,12 @@ struct InitData {
     cache: PaginationCache,
     storage: DispatchBackend,
     working_dir: Option<PathBuf>,
+    proxy: Option<Proxy>,
+    secret_key: Option<SecretKey>,
     concurrency: usize,
     read_only: bool,
+    retention: RetentionConfig,
+    scrub_interval: Option<Duration>,
 }
 
 impl Run {
@@ -93,8 +178,15 @@ impl InitData {
             cache: run.pagination.into_cache(),
             storage,
             working_dir: run.working_dir,
+            proxy: run.proxy.map(|url| Proxy {
+                url,
+                no_proxy: run.no_proxy,
+            }),
+            secret_key: run.secret_key,
             concurrency: run.concurrency,
             read_only: run.read_only,
+            retention: run.retention,
+            scrub_interval: run.scrub_interval.map(Into::into),
         })
     }
 
@@ -104,11 +196,13 @@ impl InitData {
 
         let importer = async {
             importer(
-                db,
-                self.cache,
-                storage,
+                db.clone(),
+                self.cache.clone(),
+                storage.clone(),
                 self.working_dir,
                 None, // Running the importer, we don't need an analysis graph update
+                self.proxy,
+                self.secret_key,
                 self.concurrency,
                 self.read_only,
             )
@@ -116,7 +210,38 @@ impl InitData {
         }
         .boxed_local();
 
-        let tasks = vec![importer];
+        let mut tasks = vec![importer];
+
+        if let Some(interval) = self.scrub_interval {
+            if self.read_only {
+                log::info!("Read-only mode, not scrubbing the storage");
+            } else {
+                let (db, storage) = (db.clone(), storage.clone());
+                tasks.push(
+                    maintenance("scrub", db.clone(), interval, async move || {
+                        scrub::run(&db, &storage).await
+                    })
+                    .boxed_local(),
+                );
+            }
+        }
+
+        let policy = self.retention.policy();
+        policy.validate()?;
+        if self.read_only {
+            log::info!("Read-only mode, not applying retention policies");
+        } else if !policy.is_empty() {
+            let service = RetentionService::new(self.cache.clone());
+            let dry_run = self.retention.retention_dry_run;
+            let interval = self.retention.retention_interval.into();
+            let (db, storage) = (db.clone(), storage.clone());
+            tasks.push(
+                maintenance("retention", db.clone(), interval, async move || {
+                    service.run(&policy, dry_run, &db, &storage).await
+                })
+                .boxed_local(),
+            );
+        }
 
         let (result, _, _) = futures::future::select_all(tasks).await;
 
@@ -125,3 +250,39 @@ impl InitData {
         result
     }
 }
+
+/// Run a maintenance task in the given interval, forever.
+///
+/// Only one instance sharing the database runs the task at a time, guarded by the same kind of
+/// lock as the importers.
+async fn maintenance(
+    name: &str,
+    db: db::ReadWrite,
+    interval: Duration,
+    mut task: impl AsyncFnMut(),
+) -> anyhow::Result<()> {
+    let name = format!("trustify:{name}");
+    let mut interval = tokio::time::interval(interval);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        match ImporterLock::try_acquire(&name, &db).await {
+            Ok(Some(lock)) => {
+                tokio::select! {
+                    _ = task() => {}
+                    _ = lock.lost() => {
+                        log::warn!("Lost the lock for {name}, aborting");
+                        continue;
+                    }
+                }
+                if let Err(err) = lock.release().await {
+                    log::warn!("Failed to release lock for {name}: {err}");
+                }
+            }
+            Ok(None) => log::debug!("{name} is running on another instance, skipping"),
+            Err(err) => log::warn!("Failed to acquire lock for {name}: {err}"),
+        }
+    }
+}
@@ -4,7 +4,8 @@ use trustify_common::db::{ReadWrite, pagination_cache::PaginationCache};
 use trustify_module_importer::model::{
     ClearlyDefinedImporter, ClearlyDefinedPackageType, CveImporter, CweImporter,
     DEFAULT_SOURCE_CLEARLY_DEFINED_CURATION, DEFAULT_SOURCE_CVEPROJECT, DEFAULT_SOURCE_CWE_CATALOG,
-    DEFAULT_SOURCE_QUAY, QuayImporter,
+    DEFAULT_SOURCE_KEV, DEFAULT_SOURCE_QUAY, DEFAULT_SOURCE_RED_HAT_VEX, KevImporter, QuayImporter,
+    RedHatVexImporter,
 };
 use trustify_module_importer::{
     model::{
@@ -45,14 +46,29 @@ async fn add_osv(
             common: CommonImporter {
                 disabled: true,
                 period: Duration::from_secs(300),
+                schedule: None,
+                dry_run: false,
+                rate_limit: None,
+                retry: None,
+                proxy: None,
+                include: vec![],
+                exclude: vec![],
                 description: Some(description.into()),
                 labels: Default::default(),
             },
             source: source.to_string(),
             branch: branch.map(ToString::to_string),
+            tag: None,
+            commit: None,
             path: base.map(|s| s.into()),
             years: Default::default(),
             start_year,
+            modified_since: None,
+            ecosystems: Default::default(),
+            on_deleted: Default::default(),
+            concurrency: None,
+            depth: None,
+            credentials: None,
         }),
     )
     .await
@@ -71,12 +87,27 @@ async fn add_cve(
             common: CommonImporter {
                 disabled: true,
                 period: Duration::from_secs(300),
+                schedule: None,
+                dry_run: false,
+                rate_limit: None,
+                retry: None,
+                proxy: None,
+                include: vec![],
+                exclude: vec![],
                 description: Some(description.into()),
                 labels: Default::default(),
             },
             source: DEFAULT_SOURCE_CVEPROJECT.into(),
+            branch: None,
+            tag: None,
+            commit: None,
             years: HashSet::default(),
             start_year,
+            skip_rejected: false,
+            on_deleted: Default::default(),
+            concurrency: None,
+            depth: None,
+            credentials: None,
         }),
     )
     .await
@@ -95,11 +126,19 @@ async fn add_clearly_defined_curations(
                 disabled: true,
                 // once an hour is plenty
                 period: Duration::from_secs(60 * 60),
+                schedule: None,
+                dry_run: false,
+                rate_limit: None,
+                retry: None,
+                proxy: None,
+                include: vec![],
+                exclude: vec![],
                 description: Some(description.into()),
                 labels: Default::default(),
             },
             source: DEFAULT_SOURCE_CLEARLY_DEFINED_CURATION.into(),
             types: ClearlyDefinedPackageType::all(),
+            credentials: None,
         }),
     )
     .await
@@ -118,6 +157,13 @@ async fn add_clearly_defined(
                 disabled: true,
                 // once an hour is plenty
                 period: Duration::from_secs(60 * 60),
+                schedule: None,
+                dry_run: false,
+                rate_limit: None,
+                retry: None,
+                proxy: None,
+                include: vec![],
+                exclude: vec![],
                 description: Some(description.into()),
                 labels: Default::default(),
             },
@@ -137,6 +183,13 @@ async fn add_cwe(importer: &ImporterService, name: &str, description: &str) -> a
                 disabled: true,
                 // once a day is plenty
                 period: Duration::from_secs(60 * 60 * 24),
+                schedule: None,
+                dry_run: false,
+                rate_limit: None,
+                retry: None,
+                proxy: None,
+                include: vec![],
+                exclude: vec![],
                 description: Some(description.into()),
                 labels: Default::default(),
             },
@@ -146,6 +199,31 @@ async fn add_cwe(importer: &ImporterService, name: &str, description: &str) -> a
     .await
 }
 
+async fn add_kev(importer: &ImporterService, name: &str, description: &str) -> anyhow::Result<()> {
+    add(
+        importer,
+        name,
+        ImporterConfiguration::Kev(KevImporter {
+            common: CommonImporter {
+                disabled: true,
+                // the catalog gets updated a few times a week
+                period: Duration::from_secs(60 * 60 * 24),
+                schedule: None,
+                dry_run: false,
+                rate_limit: None,
+                retry: None,
+                proxy: None,
+                include: vec![],
+                exclude: vec![],
+                description: Some(description.into()),
+                labels: Default::default(),
+            },
+            source: DEFAULT_SOURCE_KEV.into(),
+        }),
+    )
+    .await
+}
+
 async fn add_quay(
     importer: &ImporterService,
     name: &str,
@@ -159,6 +237,13 @@ async fn add_quay(
             common: CommonImporter {
                 disabled: true,
                 period: Duration::from_secs(60 * 10),
+                schedule: None,
+                dry_run: false,
+                rate_limit: None,
+                retry: None,
+                proxy: None,
+                include: vec![],
+                exclude: vec![],
                 description: Some(description.into()),
                 labels: Default::default(),
             },
@@ -182,6 +267,13 @@ pub async fn sample_data(
         common: CommonImporter {
             disabled: true,
             period: Duration::from_secs(300),
+            schedule: None,
+            dry_run: false,
+            rate_limit: None,
+            retry: None,
+            proxy: None,
+            include: vec![],
+            exclude: vec![],
             description: Some("All Red Hat SBOMs".into()),
             labels: Default::default(),
         },
@@ -203,6 +295,13 @@ pub async fn sample_data(
             common: CommonImporter {
                 disabled: true,
                 period: Duration::from_secs(300),
+                schedule: None,
+                dry_run: false,
+                rate_limit: None,
+                retry: None,
+                proxy: None,
+                include: vec![],
+                exclude: vec![],
                 description: Some("All Red Hat CSAF data".into()),
                 labels: Default::default(),
             },
@@ -211,6 +310,7 @@ pub async fn sample_data(
             only_patterns: vec![],
             fetch_retries: Some(50),
             ignore_missing: false,
+            security_txt: false,
         }),
     )
     .await?;
@@ -222,6 +322,13 @@ pub async fn sample_data(
             common: CommonImporter {
                 disabled: true,
                 period: Duration::from_secs(300),
+                schedule: None,
+                dry_run: false,
+                rate_limit: None,
+                retry: None,
+                proxy: None,
+                include: vec![],
+                exclude: vec![],
                 description: Some("Red Hat VEX files from 2024".into()),
                 labels: Default::default(),
             },
@@ -230,11 +337,37 @@ pub async fn sample_data(
             only_patterns: vec!["^cve-2024-".into()],
             fetch_retries: Some(50),
             ignore_missing: false,
+            security_txt: false,
+        }),
+    )
+    .await?;
+
+    add(
+        &importer,
+        "redhat-vex",
+        ImporterConfiguration::RedHatVex(RedHatVexImporter {
+            common: CommonImporter {
+                disabled: true,
+                period: Duration::from_secs(60 * 60),
+                schedule: None,
+                dry_run: false,
+                rate_limit: None,
+                retry: None,
+                proxy: None,
+                include: vec![],
+                exclude: vec![],
+                description: Some(
+                    "Red Hat VEX files, from the archive and its daily changes".into(),
+                ),
+                labels: Default::default(),
+            },
+            source: DEFAULT_SOURCE_RED_HAT_VEX.into(),
         }),
     )
     .await?;
 
     add_cwe(&importer, "cwe", "Common Weakness Enumeration").await?;
+    add_kev(&importer, "kev", "CISA Known Exploited Vulnerabilities").await?;
 
     add_quay(
         &importer,
@@ -359,7 +492,7 @@ mod test {
             ImporterService::new(ReadWrite::new(ctx.db.clone()), PaginationCache::for_test());
         let result = service.list().await?;
 
-        assert_eq!(result.len(), 16);
+        assert_eq!(result.len(), 18);
 
         Ok(())
     }
@@ -164,7 +164,7 @@ $$;
     /// Consumed raw bytes.
     pub async fn ingest_bytes_as(
         &self,
-        bytes: &[u8],
+        bytes: impl Into<Bytes>,
         format: Format,
         labels: impl Into<Labels> + Debug,
     ) -> Result<IngestResult, anyhow::Error> {
@@ -189,14 +189,14 @@ $$;
     ) -> Result<IngestResult, anyhow::Error> {
         let bytes = document_bytes(path).await?;
 
-        self.ingest_bytes_as(&bytes, format, labels).await
+        self.ingest_bytes_as(bytes, format, labels).await
     }
 
     pub async fn ingest_read<R: Read>(&self, mut read: R) -> Result<IngestResult, anyhow::Error> {
         let mut bytes = Vec::new();
         read.read_to_end(&mut bytes)?;
 
-        self.ingest_bytes_as(&bytes, Format::Unknown, ("source", "TrustifyContext"))
+        self.ingest_bytes_as(bytes, Format::Unknown, ("source", "TrustifyContext"))
             .await
     }
 
@@ -204,7 +204,7 @@ $$;
     pub async fn ingest_json<S: Serialize>(&self, doc: S) -> Result<IngestResult, anyhow::Error> {
         let bytes = serde_json::to_vec(&doc)?;
 
-        self.ingest_bytes_as(&bytes, Format::Unknown, ("source", "TrustifyContext"))
+        self.ingest_bytes_as(bytes, Format::Unknown, ("source", "TrustifyContext"))
             .await
     }
 
@@ -12,6 +12,7 @@ use tokio::{
 
 mod db;
 mod openapi;
+mod storage;
 
 #[allow(clippy::large_enum_variant)]
 #[derive(clap::Subcommand, Debug)]
@@ -24,6 +25,8 @@ pub enum Command {
     Db(db::Run),
     /// Access OpenAPI related information of the API server
     Openapi(openapi::Run),
+    /// Manage the storage
+    Storage(storage::Run),
 }
 
 #[derive(clap::Parser, Debug)]
@@ -45,6 +48,7 @@ impl Trustd {
             Some(Command::Importer(run)) => run.run().await,
             Some(Command::Db(run)) => run.run().await,
             Some(Command::Openapi(run)) => run.run().await,
+            Some(Command::Storage(run)) => run.run().await,
             None => pm_mode().await,
         }
     }
@@ -0,0 +1,181 @@
+use std::{path::PathBuf, process::ExitCode};
+use trustify_common::{config::Database, db};
+use trustify_infrastructure::otel::{Tracing, init_tracing};
+use trustify_module_fundamental::source_document::{
+    gc::{GcOptions, collect_garbage},
+    scrub::scrub,
+};
+use trustify_module_storage::{
+    config::{S3Config, StorageConfig, StorageStrategy},
+    service::{
+        Compression,
+        migrate::{MigrateOptions, migrate},
+    },
+};
+
+#[derive(clap::Args, Debug)]
+pub struct Run {
+    #[command(subcommand)]
+    pub(crate) command: Command,
+    #[command(flatten)]
+    pub(crate) database: Database,
+    #[command(flatten)]
+    pub(crate) storage: StorageConfig,
+}
+
+#[derive(clap::Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Find (and remove) blobs in the storage, which are not referenced by the database
+    Gc(Gc),
+    /// Copy all blobs to another storage, e.g. when switching from the filesystem to S3
+    Migrate(Migrate),
+    /// Re-hash all stored documents, reporting missing and corrupted ones
+    Scrub,
+}
+
+impl Run {
+    pub async fn run(self) -> anyhow::Result<ExitCode> {
+        init_tracing("storage-run", Tracing::Disabled);
+        match self.command {
+            Command::Gc(gc) => gc.run(self.database, self.storage).await,
+            Command::Migrate(migrate) => migrate.run(self.storage).await,
+            Command::Scrub => run_scrub(self.database, self.storage).await,
+        }
+    }
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct Gc {
+    /// Delete the orphaned blobs, instead of only reporting them
+    #[arg(long)]
+    delete: bool,
+    /// Ignore blobs modified more recently, as they might belong to an ongoing ingestion
+    #[arg(long, default_value = "1h")]
+    grace_period: humantime::Duration,
+}
+
+impl Gc {
+    async fn run(self, database: Database, storage: StorageConfig) -> anyhow::Result<ExitCode> {
+        let db = db::Database::new(&database).await?;
+        let storage = storage.into_storage(false).await?;
+
+        let options = GcOptions {
+            delete: self.delete,
+            grace_period: self.grace_period.into(),
+        };
+        let report = collect_garbage(&db, &storage, &options).await?;
+
+        println!("{}", serde_json::to_string_pretty(&report)?);
+
+        Ok(match report.errors.is_empty() {
+            true => ExitCode::SUCCESS,
+            false => ExitCode::FAILURE,
+        })
+    }
+}
+
+async fn run_scrub(database: Database, storage: StorageConfig) -> anyhow::Result<ExitCode> {
+    let db = db::Database::new(&database).await?;
+    let storage = storage.into_storage(false).await?;
+
+    let report = scrub(&db, &storage).await?;
+
+    println!("{}", serde_json::to_string_pretty(&report)?);
+
+    Ok(match report.is_clean() {
+        true => ExitCode::SUCCESS,
+        false => ExitCode::FAILURE,
+    })
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct Migrate {
+    /// A file recording the migrated blobs, which allows resuming an interrupted migration
+    #[arg(long)]
+    checkpoint: Option<PathBuf>,
+
+    /// The storage strategy of the target
+    #[arg(long)]
+    target_strategy: StorageStrategy,
+
+    /// The path of the target filesystem storage
+    #[arg(long, required_if_eq("target_strategy", "fs"))]
+    target_fs_path: Option<PathBuf>,
+
+    /// The compression of the content in the target
+    #[arg(long, default_value_t = Compression::None)]
+    target_compression: Compression,
+
+    /// Master keys for encrypting the content in the target (base64 encoded 256-bit AES keys)
+    #[arg(
+        long,
+        env = "TRUSTD_TARGET_STORAGE_ENCRYPTION_KEYS",
+        value_delimiter = ','
+    )]
+    target_encryption_key: Vec<String>,
+
+    /// The bucket of the target S3 storage
+    #[arg(long, required_if_eq("target_strategy", "s3"))]
+    target_s3_bucket: Option<String>,
+
+    /// The region of the target S3 storage
+    #[arg(long, required_if_eq("target_strategy", "s3"))]
+    target_s3_region: Option<String>,
+
+    /// The access key of the target S3 storage
+    #[arg(long, requires = "target_s3_secret_key")]
+    target_s3_access_key: Option<String>,
+
+    /// The secret key of the target S3 storage
+    #[arg(
+        long,
+        env = "TRUSTD_TARGET_S3_SECRET_KEY",
+        requires = "target_s3_access_key"
+    )]
+    target_s3_secret_key: Option<String>,
+
+    /// Use path style instead of virtual host style for the target S3 storage
+    #[arg(long)]
+    target_s3_path_style: bool,
+}
+
+impl Migrate {
+    async fn run(self, storage: StorageConfig) -> anyhow::Result<ExitCode> {
+        let options = MigrateOptions {
+            checkpoint: self.checkpoint.clone(),
+        };
+
+        let source = storage.into_storage(false).await?;
+        let target = self.target().into_storage(false).await?;
+
+        let report = migrate(&source, &target, &options).await?;
+
+        println!("{}", serde_json::to_string_pretty(&report)?);
+
+        Ok(match report.errors.is_empty() {
+            true => ExitCode::SUCCESS,
+            false => ExitCode::FAILURE,
+        })
+    }
+
+    /// The configuration of the target storage
+    fn target(self) -> StorageConfig {
+        StorageConfig {
+            storage_strategy: self.target_strategy,
+            fs_path: self.target_fs_path,
+            compression: self.target_compression,
+            encryption_keys: self.target_encryption_key,
+            // the target is only written to, there is nothing to cache
+            cache_path: None,
+            cache_size: Default::default(),
+            s3_config: S3Config {
+                bucket: self.target_s3_bucket,
+                region: self.target_s3_region,
+                access_key: self.target_s3_access_key,
+                secret_key: self.target_s3_secret_key,
+                trust_anchors: vec![],
+                path_style: self.target_s3_path_style,
+            },
+        }
+    }
+}
@@ -76,8 +76,16 @@ impl GenerateDump {
                     ImporterConfiguration::Cve(CveImporter {
                         common: default_common("CVEs starting 2024"),
                         source: "https://github.com/CVEProject/cvelistV5".to_string(),
+                        branch: None,
+                        tag: None,
+                        commit: None,
                         years: Default::default(),
                         start_year: Some(2024),
+                        skip_rejected: false,
+                        on_deleted: Default::default(),
+                        concurrency: None,
+                        depth: None,
+                        credentials: None,
                     }),
                     ImporterConfiguration::Sbom(SbomImporter {
                         common: default_common("All Red Hat SBOMs"),
@@ -96,6 +104,7 @@ impl GenerateDump {
                         only_patterns: vec!["^cve-2024-".into()],
                         fetch_retries: self.fetch_retries,
                         ignore_missing: false,
+                        security_txt: false,
                     })
                 ];
 
@@ -136,6 +145,8 @@ impl GenerateDump {
             working_dir: self.working_dir.as_ref().map(|wd| wd.join("wd")),
             // The xtask doesn't need the analysis graph
             analysis: None,
+            proxy: None,
+            secret_key: None,
         };
 
         // ingest documents
@@ -232,7 +243,7 @@ impl GenerateDump {
                     .db
                     .transaction(async |tx| {
                         service
-                            .ingest(&data, Format::Unknown, (), None, Cache::Skip, tx)
+                            .ingest(data.clone(), Format::Unknown, (), None, Cache::Skip, tx)
                             .await
                     })
                     .await?;
@@ -277,6 +288,13 @@ fn default_common(description: impl Into<String>) -> CommonImporter {
     CommonImporter {
         disabled: false,
         period: Default::default(),
+        schedule: None,
+        dry_run: false,
+        rate_limit: None,
+        retry: None,
+        proxy: None,
+        include: vec![],
+        exclude: vec![],
         description: Some(description.into()),
         labels: Default::default(),
     }